@@ -0,0 +1,258 @@
+//! Role-based permissions on transitions
+//!
+//! A `from -> to` transition may declare required roles; the requesting
+//! `Actor` (passed on the StateTransition) must hold at least one of them.
+//! Transitions without declared roles are open to any caller, with or
+//! without an actor.
+//!
+//! The audit log records every denied attempt and every applied transition
+//! that named an actor. It is kept separately from `LifecycleHistory`,
+//! which only holds states the component actually entered.
+//!
+//! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle
+
+use harmony_schemas::{AuthorizationError, ComponentState, StateTransition};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{parse_json, ComponentLifecycleBC};
+
+/// Whether an audited transition went through
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Applied,
+    Denied,
+}
+
+/// One entry in the audit log
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub component_id: String,
+    /// ISO 8601 timestamp of the attempt
+    pub timestamp: String,
+    pub actor_id: Option<String>,
+    pub from: ComponentState,
+    pub to: ComponentState,
+    pub outcome: AuditOutcome,
+}
+
+impl AuditRecord {
+    /// Creates a record stamped with the current time
+    pub fn new(
+        component_id: &str,
+        actor_id: Option<String>,
+        from: ComponentState,
+        to: ComponentState,
+        outcome: AuditOutcome,
+    ) -> Self {
+        Self {
+            component_id: component_id.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            actor_id,
+            from,
+            to,
+            outcome,
+        }
+    }
+}
+
+impl ComponentLifecycleBC {
+    /// Sets the roles allowed to perform a transition; an empty list opens it
+    pub fn set_transition_roles(
+        &mut self,
+        from: ComponentState,
+        to: ComponentState,
+        roles: Vec<String>,
+    ) {
+        if roles.is_empty() {
+            self.transition_roles.remove(&(from, to));
+        } else {
+            self.transition_roles.insert((from, to), roles);
+        }
+    }
+
+    /// Checks the transition's actor against the roles required from `current_state`
+    ///
+    /// Denials are recorded in the audit log.
+    pub(crate) fn authorize(
+        &mut self,
+        transition: &StateTransition,
+        current_state: ComponentState,
+    ) -> Result<(), AuthorizationError> {
+        let Some(required) = self
+            .transition_roles
+            .get(&(current_state, transition.to_state))
+        else {
+            return Ok(());
+        };
+        if transition
+            .actor
+            .as_ref()
+            .is_some_and(|actor| actor.has_any_role(required))
+        {
+            return Ok(());
+        }
+
+        let error = AuthorizationError {
+            actor_id: transition.actor.as_ref().map(|actor| actor.id.clone()),
+            required_roles: required.clone(),
+        };
+        self.record_audit(AuditRecord::new(
+            &transition.component_id,
+            error.actor_id.clone(),
+            current_state,
+            transition.to_state,
+            AuditOutcome::Denied,
+        ));
+        Err(error)
+    }
+
+    pub(crate) fn record_audit(&mut self, record: AuditRecord) {
+        self.audit_log.push(record);
+    }
+
+    /// Audit records for a component, oldest first
+    pub fn audit_log(&self, component_id: &str) -> Vec<&AuditRecord> {
+        self.audit_log
+            .iter()
+            .filter(|record| record.component_id == component_id)
+            .collect()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ComponentLifecycleBC {
+    /// Declares the roles allowed to perform a transition
+    ///
+    /// # Arguments
+    /// * `from` - Source state (snake_case)
+    /// * `to` - Target state (snake_case)
+    /// * `roles_json` - JSON array of role names (any one suffices); `[]` opens it
+    ///
+    /// # Returns
+    /// false if a state name or the JSON is invalid
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setTransitionRoles))]
+    pub fn set_transition_roles_js(&mut self, from: &str, to: &str, roles_json: &str) -> bool {
+        let (Ok(from), Ok(to)) = (from.parse(), to.parse()) else {
+            return false;
+        };
+        let Ok(roles) = parse_json::<Vec<String>>(roles_json) else {
+            return false;
+        };
+        self.set_transition_roles(from, to, roles);
+        true
+    }
+
+    /// Get the audit log of a component
+    ///
+    /// # Returns
+    /// JSON array of AuditRecord, oldest first
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getAuditLog))]
+    pub fn get_audit_log(&self, component_id: &str) -> String {
+        serde_json::to_string(&self.audit_log(component_id)).unwrap_or_else(|_| "[]".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harmony_schemas::TransitionResult;
+
+    fn transition(bc: &mut ComponentLifecycleBC, actor: &str) -> TransitionResult {
+        serde_json::from_str(&bc.transition_component(&format!(
+            r#"{{"component_id":"button","from_state":"draft","to_state":"design_complete"{}}}"#,
+            actor
+        )))
+        .unwrap()
+    }
+
+    fn restricted() -> ComponentLifecycleBC {
+        let mut bc = ComponentLifecycleBC::new();
+        bc.initialize_component("button");
+        assert!(bc.set_transition_roles_js(
+            "draft",
+            "design_complete",
+            r#"["designer","admin"]"#
+        ));
+        bc
+    }
+
+    #[test]
+    fn test_unauthorized_attempts_are_denied_and_audited() {
+        let mut bc = restricted();
+
+        let result = transition(&mut bc, "");
+        assert!(!result.success);
+        let error = result.authorization.unwrap();
+        assert_eq!(error.actor_id, None);
+        assert_eq!(error.required_roles, vec!["designer", "admin"]);
+
+        let result = transition(&mut bc, r#","actor":{"id":"dev1","roles":["developer"]}"#);
+        assert_eq!(result.authorization.unwrap().actor_id.as_deref(), Some("dev1"));
+        assert_eq!(bc.get_component_state("button"), "\"draft\"");
+
+        let log = bc.audit_log("button");
+        assert_eq!(log.len(), 2);
+        assert!(log.iter().all(|r| r.outcome == AuditOutcome::Denied));
+    }
+
+    #[test]
+    fn test_authorized_actor_is_recorded() {
+        let mut bc = restricted();
+        let result = transition(&mut bc, r#","actor":{"id":"ana","roles":["designer"]}"#);
+        assert!(result.success);
+
+        let log = bc.audit_log("button");
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].outcome, AuditOutcome::Applied);
+        assert_eq!(log[0].actor_id.as_deref(), Some("ana"));
+
+        let entry = bc.history("button").unwrap().current_state().unwrap();
+        assert_eq!(entry.metadata.as_ref().unwrap().changed_by.as_deref(), Some("ana"));
+    }
+
+    #[test]
+    fn test_open_transitions_need_no_actor() {
+        let mut bc = restricted();
+        assert!(bc.set_transition_roles_js("draft", "design_complete", "[]"));
+        assert!(transition(&mut bc, "").success);
+        assert!(bc.audit_log("button").is_empty());
+        assert!(!bc.set_transition_roles_js("draft", "archived", "[]"));
+    }
+
+    #[test]
+    fn test_deprecation_and_schedules_carry_actor() {
+        let mut bc = restricted();
+        assert!(bc.set_transition_roles_js("draft", "deprecated", r#"["admin"]"#));
+        let admin = r#"{"id":"root","roles":["admin"]}"#;
+
+        assert!(bc.deprecate_component("button", "", None).contains("Unauthorized"));
+        assert!(bc
+            .deprecate_component("button", "", Some("{".to_string()))
+            .contains("Invalid actor JSON"));
+        assert!(bc
+            .deprecate_component("button", "", Some(admin.to_string()))
+            .contains("\"success\":true"));
+
+        bc.initialize_component("card");
+        let unattended = bc.schedule_transition_js(
+            r#"{"component_id":"card","to_state":"deprecated","trigger":{"type":"at","at_ms":0}}"#,
+        );
+        let fired = bc.run_due_transitions(0.0);
+        assert_eq!(fired[0].schedule_id, unattended);
+        assert!(fired[0].result.authorization.is_some());
+
+        bc.schedule_transition_js(&format!(
+            r#"{{"component_id":"card","to_state":"deprecated","trigger":{{"type":"at","at_ms":0}},"actor":{}}}"#,
+            admin
+        ));
+        assert!(bc.run_due_transitions(0.0)[0].result.success);
+        assert_eq!(bc.audit_log("card").last().unwrap().actor_id.as_deref(), Some("root"));
+    }
+}
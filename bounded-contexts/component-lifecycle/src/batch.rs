@@ -0,0 +1,239 @@
+//! Bulk state transitions
+//!
+//! A `TransitionBatch` is applied in order. By default each transition
+//! stands alone, as if submitted through `transitionComponent`. With
+//! `all_or_nothing`, every transition is first validated against the states
+//! produced by the earlier items (so one component may move several steps
+//! in a batch, and publishing checks dependencies as the batch leaves
+//! them), and nothing is applied unless all of them pass. Listeners
+//! are only notified for transitions that are actually applied; pre-transition
+//! hooks are consulted during validation.
+//!
+//! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle
+
+use std::collections::HashMap;
+
+use harmony_schemas::{BatchTransitionResult, TransitionBatch, TransitionResult};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{failure, parse_json, ComponentLifecycleBC};
+
+impl ComponentLifecycleBC {
+    /// Validates and applies a batch of transitions
+    pub fn apply_batch(&mut self, batch: TransitionBatch) -> BatchTransitionResult {
+        let results: Vec<TransitionResult> = if batch.all_or_nothing {
+            self.apply_all_or_nothing(batch)
+        } else {
+            batch
+                .transitions
+                .into_iter()
+                .map(|transition| self.apply_transition(transition))
+                .collect()
+        };
+
+        let applied = results.iter().filter(|r| r.success).count();
+        BatchTransitionResult {
+            success: applied == results.len(),
+            applied,
+            results,
+        }
+    }
+
+    fn apply_all_or_nothing(&mut self, batch: TransitionBatch) -> Vec<TransitionResult> {
+        let mut staged = HashMap::new();
+        let mut prepared = Vec::with_capacity(batch.transitions.len());
+        for transition in batch.transitions {
+            let outcome = self.prepare_transition(transition, &staged);
+            if let Ok(event) = &outcome {
+                staged.insert(event.component_id.clone(), event.to);
+            }
+            prepared.push(outcome);
+        }
+
+        if prepared.iter().all(Result::is_ok) {
+            return prepared
+                .into_iter()
+                .flatten()
+                .map(|event| self.commit_transition(event))
+                .collect();
+        }
+
+        prepared
+            .into_iter()
+            .map(|outcome| match outcome {
+                Ok(event) => {
+                    let state = self.component_states.get(&event.component_id).copied();
+                    failure(
+                        event.component_id,
+                        state,
+                        "Not applied: another transition in the batch failed".to_string(),
+                    )
+                }
+                Err(result) => *result,
+            })
+            .collect()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ComponentLifecycleBC {
+    /// Validates and applies many transitions in one call
+    ///
+    /// # Arguments
+    /// * `batch_json` - JSON TransitionBatch:
+    ///   `{"transitions": [StateTransition...], "all_or_nothing": bool}`
+    ///
+    /// # Returns
+    /// JSON BatchTransitionResult with one result per transition, in order
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = transitionComponents))]
+    pub fn transition_components(&mut self, batch_json: &str) -> String {
+        let result = match parse_json::<TransitionBatch>(batch_json) {
+            Ok(batch) => self.apply_batch(batch),
+            Err(e) => BatchTransitionResult {
+                success: false,
+                applied: 0,
+                results: vec![failure(
+                    String::new(),
+                    None,
+                    format!("Invalid batch JSON: {}", e),
+                )],
+            },
+        };
+        serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harmony_schemas::{ComponentState, Edge, EdgeType};
+
+    fn setup() -> ComponentLifecycleBC {
+        let mut bc = ComponentLifecycleBC::new();
+        bc.initialize_component("button");
+        bc.initialize_component("card");
+        bc
+    }
+
+    fn batch(all_or_nothing: bool) -> String {
+        format!(
+            r#"{{"all_or_nothing":{},"transitions":[
+                {{"component_id":"button","from_state":"draft","to_state":"design_complete"}},
+                {{"component_id":"button","from_state":"design_complete","to_state":"in_development"}},
+                {{"component_id":"card","from_state":"draft","to_state":"published"}}
+            ]}}"#,
+            all_or_nothing
+        )
+    }
+
+    #[test]
+    fn test_independent_batch_applies_valid_items() {
+        let mut bc = setup();
+        let result: BatchTransitionResult =
+            serde_json::from_str(&bc.transition_components(&batch(false))).unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.applied, 2);
+        assert!(!result.results[2].success);
+        assert_eq!(bc.component_states["button"], ComponentState::InDevelopment);
+    }
+
+    #[test]
+    fn test_all_or_nothing_rolls_back() {
+        let mut bc = setup();
+        let result: BatchTransitionResult =
+            serde_json::from_str(&bc.transition_components(&batch(true))).unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.applied, 0);
+        assert_eq!(result.results.len(), 3);
+        assert!(result.results[0].error.as_deref().unwrap().starts_with("Not applied"));
+        assert!(result.results[2].error.as_deref().unwrap().starts_with("Invalid transition"));
+        assert_eq!(bc.component_states["button"], ComponentState::Draft);
+        assert_eq!(bc.history("button").unwrap().entries.len(), 1);
+    }
+
+    #[test]
+    fn test_all_or_nothing_applies_chained_steps() {
+        let mut bc = setup();
+        let json = batch(true).replace(
+            r#""from_state":"draft","to_state":"published""#,
+            r#""from_state":"draft","to_state":"design_complete""#,
+        );
+        let result: BatchTransitionResult =
+            serde_json::from_str(&bc.transition_components(&json)).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.applied, 3);
+        assert_eq!(bc.component_states["button"], ComponentState::InDevelopment);
+        assert_eq!(bc.history("button").unwrap().entries.len(), 3);
+    }
+
+    #[test]
+    fn test_all_or_nothing_checks_staged_dependencies() {
+        let mut bc = setup();
+        bc.initialize_component("form");
+        bc.component_states
+            .insert("form".to_string(), ComponentState::Implemented);
+        bc.add_dependency_edge(Edge::new(
+            "e1".to_string(),
+            "form".to_string(),
+            "button".to_string(),
+            EdgeType::ComposesOf,
+        ));
+        let publish_form =
+            r#"{"component_id":"form","from_state":"implemented","to_state":"published"}"#;
+
+        // Advancing the dependency earlier in the batch unblocks publishing
+        let json = format!(
+            r#"{{"all_or_nothing":true,"transitions":[
+                {{"component_id":"button","from_state":"draft","to_state":"design_complete"}},
+                {}
+            ]}}"#,
+            publish_form
+        );
+        let result: BatchTransitionResult =
+            serde_json::from_str(&bc.transition_components(&json)).unwrap();
+        assert!(result.success);
+        assert_eq!(bc.component_states["form"], ComponentState::Published);
+
+        // Deprecating it earlier in the batch blocks publishing
+        bc.component_states
+            .insert("form".to_string(), ComponentState::Implemented);
+        let json = format!(
+            r#"{{"all_or_nothing":true,"transitions":[
+                {{"component_id":"button","from_state":"design_complete","to_state":"deprecated"}},
+                {}
+            ]}}"#,
+            publish_form
+        );
+        let result: BatchTransitionResult =
+            serde_json::from_str(&bc.transition_components(&json)).unwrap();
+        assert!(!result.success);
+        assert_eq!(result.applied, 0);
+        assert_eq!(result.results[1].blocking_dependencies[0].component_id, "button");
+        assert_eq!(bc.component_states["button"], ComponentState::DesignComplete);
+    }
+
+    #[test]
+    fn test_batch_over_json_limits_rejected() {
+        let mut bc = setup();
+        let nested = format!("{}{}", "[".repeat(100), "]".repeat(100));
+        let result: BatchTransitionResult =
+            serde_json::from_str(&bc.transition_components(&nested)).unwrap();
+        assert!(!result.success);
+        assert!(result.results[0].error.as_deref().unwrap().contains("nesting depth"));
+
+        harmony_error::limits::set(harmony_error::limits::Limits {
+            max_json_bytes: 16,
+            ..Default::default()
+        });
+        let result: BatchTransitionResult =
+            serde_json::from_str(&bc.transition_components(&batch(false))).unwrap();
+        harmony_error::limits::set(Default::default());
+        assert_eq!(result.applied, 0);
+        assert!(result.results[0].error.as_deref().unwrap().contains("payload size"));
+        assert_eq!(bc.get_component_state("button"), "\"draft\"");
+    }
+}
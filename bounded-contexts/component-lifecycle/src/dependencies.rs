@@ -0,0 +1,210 @@
+//! Lifecycle dependencies across components
+//!
+//! The host mirrors the graph's `composes_of`, `uses_token` and `depends_on`
+//! edges into the BC (`used_by` and `depended_on_by` edges are stored as
+//! their forward reverse). A component cannot be published while any
+//! dependency it points at is Draft or Deprecated. Dependencies the BC does
+//! not track (e.g. tokens without a lifecycle) never block.
+//!
+//! Edges are not part of `exportState`, and `importState` clears them;
+//! re-sync them from the graph after a restore.
+//!
+//! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle
+
+use std::collections::HashMap;
+
+use harmony_schemas::{BlockingDependency, ComponentState, Edge, EdgeType};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{parse_json, ComponentLifecycleBC};
+
+/// Whether a component could be published now
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishReadiness {
+    pub component_id: String,
+    pub ready: bool,
+    pub blocking: Vec<BlockingDependency>,
+}
+
+impl ComponentLifecycleBC {
+    /// Stores a dependency edge, replacing any edge with the same id
+    ///
+    /// # Returns
+    /// false if the edge type does not gate publishing
+    pub fn add_dependency_edge(&mut self, edge: Edge) -> bool {
+        let edge = match edge.edge_type {
+            EdgeType::ComposesOf | EdgeType::UsesToken | EdgeType::DependsOn => edge,
+            EdgeType::UsedBy | EdgeType::DependedOnBy => Edge {
+                id: edge.id.clone(),
+                ..edge.reverse().unwrap_or(edge)
+            },
+            _ => return false,
+        };
+        self.dependency_edges.insert(edge.id.clone(), edge);
+        true
+    }
+
+    /// Dependencies of a component that currently prevent publishing
+    ///
+    /// Ordered by dependency id.
+    pub fn blocking_dependencies(&self, component_id: &str) -> Vec<BlockingDependency> {
+        self.staged_blocking_dependencies(component_id, &HashMap::new())
+    }
+
+    /// `blocking_dependencies`, with states in `staged` taking precedence
+    pub(crate) fn staged_blocking_dependencies(
+        &self,
+        component_id: &str,
+        staged: &HashMap<String, ComponentState>,
+    ) -> Vec<BlockingDependency> {
+        let mut blocking: Vec<BlockingDependency> = self
+            .dependency_edges
+            .values()
+            .filter(|edge| edge.from == component_id)
+            .filter_map(|edge| {
+                let state = *staged
+                    .get(&edge.to)
+                    .or_else(|| self.component_states.get(&edge.to))?;
+                (!state.satisfies_publish_dependency()).then(|| BlockingDependency {
+                    component_id: edge.to.clone(),
+                    edge_type: edge.edge_type,
+                    state,
+                })
+            })
+            .collect();
+        blocking.sort_by(|a, b| a.component_id.cmp(&b.component_id));
+        blocking.dedup_by(|a, b| a.component_id == b.component_id);
+        blocking
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ComponentLifecycleBC {
+    /// Adds graph edges that gate publishing
+    ///
+    /// # Arguments
+    /// * `edges_json` - JSON array of graph Edges
+    ///
+    /// # Returns
+    /// Number of edges stored; other edge types are ignored
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = addDependencyEdges))]
+    pub fn add_dependency_edges(&mut self, edges_json: &str) -> u32 {
+        let Ok(edges) = parse_json::<Vec<Edge>>(edges_json) else {
+            return 0;
+        };
+        edges
+            .into_iter()
+            .map(|edge| self.add_dependency_edge(edge))
+            .filter(|stored| *stored)
+            .count() as u32
+    }
+
+    /// Removes a dependency edge by id
+    ///
+    /// # Returns
+    /// false if no edge has that id
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = removeDependencyEdge))]
+    pub fn remove_dependency_edge(&mut self, edge_id: &str) -> bool {
+        self.dependency_edges.remove(edge_id).is_some()
+    }
+
+    /// Checks whether a component's dependencies allow it to be published
+    ///
+    /// # Returns
+    /// JSON PublishReadiness listing the blocking dependencies
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = validatePublishReadiness))]
+    pub fn validate_publish_readiness(&self, component_id: &str) -> String {
+        let blocking = self.blocking_dependencies(component_id);
+        let readiness = PublishReadiness {
+            component_id: component_id.to_string(),
+            ready: blocking.is_empty(),
+            blocking,
+        };
+        serde_json::to_string(&readiness).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harmony_schemas::{ComponentState, TransitionResult};
+
+    fn edge(id: &str, from: &str, to: &str, edge_type: EdgeType) -> Edge {
+        Edge::new(id.to_string(), from.to_string(), to.to_string(), edge_type)
+    }
+
+    fn setup() -> ComponentLifecycleBC {
+        let mut bc = ComponentLifecycleBC::new();
+        for id in ["form", "button", "color-primary"] {
+            bc.initialize_component(id);
+        }
+        bc.component_states
+            .insert("form".to_string(), ComponentState::Implemented);
+        let edges = vec![
+            edge("e1", "button", "form", EdgeType::UsedBy),
+            edge("e2", "form", "color-primary", EdgeType::UsesToken),
+            edge("e3", "form", "untracked-token", EdgeType::UsesToken),
+            edge("e4", "form", "spec", EdgeType::ImplementsDesign),
+        ];
+        assert_eq!(bc.add_dependency_edges(&serde_json::to_string(&edges).unwrap()), 3);
+        bc
+    }
+
+    #[test]
+    fn test_readiness_lists_blocking_dependencies() {
+        let mut bc = setup();
+        let readiness: PublishReadiness =
+            serde_json::from_str(&bc.validate_publish_readiness("form")).unwrap();
+        assert!(!readiness.ready);
+        let ids: Vec<&str> = readiness.blocking.iter().map(|d| d.component_id.as_str()).collect();
+        assert_eq!(ids, vec!["button", "color-primary"]);
+        assert_eq!(readiness.blocking[0].edge_type, EdgeType::ComposesOf);
+
+        bc.component_states
+            .insert("button".to_string(), ComponentState::Published);
+        bc.deprecate_component("color-primary", "", None);
+        let blocking = bc.blocking_dependencies("form");
+        assert_eq!(blocking.len(), 1);
+        assert_eq!(blocking[0].state, ComponentState::Deprecated);
+
+        assert!(bc.remove_dependency_edge("e2"));
+        assert!(bc.blocking_dependencies("form").is_empty());
+    }
+
+    #[test]
+    fn test_generic_dependencies_gate_publishing() {
+        let mut bc = setup();
+        assert!(bc.add_dependency_edge(edge("e5", "date-utils", "form", EdgeType::DependedOnBy)));
+        assert!(!bc.add_dependency_edge(edge("e6", "form-docs", "form", EdgeType::Documents)));
+        bc.initialize_component("date-utils");
+
+        let ids: Vec<String> = bc
+            .blocking_dependencies("form")
+            .into_iter()
+            .map(|d| d.component_id)
+            .collect();
+        assert_eq!(ids, vec!["button", "color-primary", "date-utils"]);
+    }
+
+    #[test]
+    fn test_publish_rejected_while_blocked() {
+        let mut bc = setup();
+        let publish = r#"{"component_id":"form","from_state":"implemented","to_state":"published"}"#;
+
+        let result: TransitionResult = serde_json::from_str(&bc.transition_component(publish)).unwrap();
+        assert!(!result.success);
+        assert_eq!(result.blocking_dependencies.len(), 2);
+
+        bc.component_states
+            .insert("button".to_string(), ComponentState::InDevelopment);
+        bc.component_states
+            .insert("color-primary".to_string(), ComponentState::Published);
+        let result: TransitionResult = serde_json::from_str(&bc.transition_component(publish)).unwrap();
+        assert!(result.success);
+    }
+}
@@ -0,0 +1,159 @@
+//! Guard conditions on transitions
+//!
+//! A guard is a named flag (e.g. "design_review", "a11y_check") that a
+//! component must carry before a particular `from -> to` transition is
+//! allowed. Flags are set per component by reviewers or CI and persist until
+//! cleared. A transition with missing flags fails with the list of unmet
+//! guards in `TransitionResult::unmet_guards`.
+//!
+//! No guards are configured by default.
+//!
+//! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle
+
+use harmony_schemas::ComponentState;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{parse_json, ComponentLifecycleBC};
+
+impl ComponentLifecycleBC {
+    /// Sets the flags required for a transition; an empty list removes the guard
+    pub fn set_transition_guards(
+        &mut self,
+        from: ComponentState,
+        to: ComponentState,
+        guards: Vec<String>,
+    ) {
+        if guards.is_empty() {
+            self.transition_guards.remove(&(from, to));
+        } else {
+            self.transition_guards.insert((from, to), guards);
+        }
+    }
+
+    /// Flags required for `from -> to` that the component does not carry
+    pub fn unmet_guards(
+        &self,
+        component_id: &str,
+        from: ComponentState,
+        to: ComponentState,
+    ) -> Vec<String> {
+        let Some(guards) = self.transition_guards.get(&(from, to)) else {
+            return Vec::new();
+        };
+        let flags = self.component_flags.get(component_id);
+        guards
+            .iter()
+            .filter(|guard| !flags.is_some_and(|flags| flags.contains(*guard)))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ComponentLifecycleBC {
+    /// Declares the flags a component needs before a transition
+    ///
+    /// # Arguments
+    /// * `from` - Source state (snake_case)
+    /// * `to` - Target state (snake_case)
+    /// * `guards_json` - JSON array of flag names; `[]` removes the guard
+    ///
+    /// # Returns
+    /// false if a state name or the JSON is invalid
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setTransitionGuards))]
+    pub fn set_transition_guards_js(&mut self, from: &str, to: &str, guards_json: &str) -> bool {
+        let (Ok(from), Ok(to)) = (from.parse(), to.parse()) else {
+            return false;
+        };
+        let Ok(guards) = parse_json::<Vec<String>>(guards_json) else {
+            return false;
+        };
+        self.set_transition_guards(from, to, guards);
+        true
+    }
+
+    /// Sets or clears a guard flag on a component
+    ///
+    /// # Returns
+    /// false if the component is unknown
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setComponentFlag))]
+    pub fn set_component_flag(&mut self, component_id: &str, flag: &str, value: bool) -> bool {
+        if !self.component_states.contains_key(component_id) {
+            return false;
+        }
+        let flags = self
+            .component_flags
+            .entry(component_id.to_string())
+            .or_default();
+        if value {
+            flags.insert(flag.to_string());
+        } else {
+            flags.remove(flag);
+        }
+        true
+    }
+
+    /// Lists the guard flags blocking a component's move to `to_state`
+    ///
+    /// # Returns
+    /// JSON array of flag names (empty if nothing blocks or inputs are unknown)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getUnmetGuards))]
+    pub fn get_unmet_guards(&self, component_id: &str, to_state: &str) -> String {
+        let unmet = match (self.component_states.get(component_id), to_state.parse()) {
+            (Some(from), Ok(to)) => self.unmet_guards(component_id, *from, to),
+            _ => Vec::new(),
+        };
+        serde_json::to_string(&unmet).unwrap_or_else(|_| "[]".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harmony_schemas::TransitionResult;
+
+    fn publish(bc: &mut ComponentLifecycleBC) -> TransitionResult {
+        serde_json::from_str(&bc.transition_component(
+            r#"{"component_id":"button","from_state":"implemented","to_state":"published"}"#,
+        ))
+        .unwrap()
+    }
+
+    fn implemented() -> ComponentLifecycleBC {
+        let mut bc = ComponentLifecycleBC::new();
+        bc.initialize_component("button");
+        bc.component_states
+            .insert("button".to_string(), ComponentState::Implemented);
+        assert!(bc.set_transition_guards_js(
+            "implemented",
+            "published",
+            r#"["design_review","a11y_check"]"#
+        ));
+        bc
+    }
+
+    #[test]
+    fn test_unmet_guards_reject_transition() {
+        let mut bc = implemented();
+        bc.set_component_flag("button", "design_review", true);
+
+        let result = publish(&mut bc);
+        assert!(!result.success);
+        assert_eq!(result.unmet_guards, vec!["a11y_check".to_string()]);
+        assert_eq!(bc.get_unmet_guards("button", "published"), r#"["a11y_check"]"#);
+
+        bc.set_component_flag("button", "a11y_check", true);
+        assert!(publish(&mut bc).success);
+    }
+
+    #[test]
+    fn test_guard_configuration() {
+        let mut bc = implemented();
+        assert!(!bc.set_transition_guards_js("implemented", "archived", "[]"));
+        assert!(!bc.set_component_flag("missing", "design_review", true));
+
+        assert!(bc.set_transition_guards_js("implemented", "published", "[]"));
+        assert!(publish(&mut bc).success);
+    }
+}
@@ -0,0 +1,282 @@
+//! Transition hooks and event emission
+//!
+//! Pre-transition hooks run after a transition passes the state table and
+//! may veto it with a reason. Transition listeners are notified after a
+//! transition has been applied and recorded in the history. Both kinds can
+//! be registered from Rust (closures) or, with the `wasm` feature, JS
+//! (functions), and share one id space for removal.
+//!
+//! JS callbacks receive `(componentId, from, to, metadata)` where states are
+//! snake_case strings and metadata is a plain object or null. A JS
+//! pre-transition hook vetoes by returning `false` or a reason string, or by
+//! throwing.
+//!
+//! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle
+
+use harmony_schemas::{Actor, ComponentState, StateMetadata};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::ComponentLifecycleBC;
+
+/// A transition about to be applied, or just applied
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransitionEvent {
+    pub component_id: String,
+    pub from: ComponentState,
+    pub to: ComponentState,
+    /// Metadata recorded in the history entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<StateMetadata>,
+    /// Caller that requested the transition, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<Actor>,
+}
+
+/// Rust pre-transition hook; `Err(reason)` vetoes the transition
+pub type PreTransitionHook = Box<dyn FnMut(&TransitionEvent) -> Result<(), String>>;
+
+/// Rust transition listener
+pub type TransitionListener = Box<dyn FnMut(&TransitionEvent)>;
+
+/// Pre-transition hook registered on the BC
+pub(crate) enum HookCallback {
+    Rust(PreTransitionHook),
+    #[cfg(feature = "wasm")]
+    Js(js_sys::Function),
+}
+
+/// Transition listener registered on the BC
+pub(crate) enum ListenerCallback {
+    Rust(TransitionListener),
+    #[cfg(feature = "wasm")]
+    Js(js_sys::Function),
+}
+
+#[cfg(feature = "wasm")]
+fn js_args(event: &TransitionEvent) -> js_sys::Array {
+    let metadata = event
+        .metadata
+        .as_ref()
+        .and_then(|m| serde_json::to_string(m).ok())
+        .and_then(|json| js_sys::JSON::parse(&json).ok())
+        .unwrap_or(JsValue::NULL);
+    js_sys::Array::of4(
+        &JsValue::from_str(&event.component_id),
+        &JsValue::from_str(&event.from.to_string()),
+        &JsValue::from_str(&event.to.to_string()),
+        &metadata,
+    )
+}
+
+impl ComponentLifecycleBC {
+    /// Registers a Rust pre-transition hook
+    ///
+    /// # Returns
+    /// Hook id for `remove_transition_hook`
+    pub fn on_before_transition(
+        &mut self,
+        hook: impl FnMut(&TransitionEvent) -> Result<(), String> + 'static,
+    ) -> u32 {
+        let id = self.allocate_hook_id();
+        self.pre_transition_hooks
+            .push((id, HookCallback::Rust(Box::new(hook))));
+        id
+    }
+
+    /// Registers a Rust transition listener
+    ///
+    /// # Returns
+    /// Hook id for `remove_transition_hook`
+    pub fn on_transition(&mut self, listener: impl FnMut(&TransitionEvent) + 'static) -> u32 {
+        let id = self.allocate_hook_id();
+        self.transition_listeners
+            .push((id, ListenerCallback::Rust(Box::new(listener))));
+        id
+    }
+
+    fn allocate_hook_id(&mut self) -> u32 {
+        let id = self.next_hook_id;
+        self.next_hook_id += 1;
+        id
+    }
+
+    /// Runs pre-transition hooks in registration order
+    ///
+    /// # Returns
+    /// The first veto reason, if any hook rejects the transition
+    pub(crate) fn run_pre_transition_hooks(&mut self, event: &TransitionEvent) -> Result<(), String> {
+        for (_, hook) in &mut self.pre_transition_hooks {
+            match hook {
+                HookCallback::Rust(f) => f(event)?,
+                #[cfg(feature = "wasm")]
+                HookCallback::Js(f) => match f.apply(&JsValue::NULL, &js_args(event)) {
+                    Ok(value) if value.as_bool() == Some(false) => {
+                        return Err("rejected by hook".to_string());
+                    }
+                    Ok(value) => {
+                        if let Some(reason) = value.as_string() {
+                            return Err(reason);
+                        }
+                    }
+                    Err(error) => {
+                        return Err(error
+                            .as_string()
+                            .unwrap_or_else(|| "hook threw an exception".to_string()));
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Notifies every transition listener in registration order
+    pub(crate) fn notify_transition_listeners(&mut self, event: &TransitionEvent) {
+        for (_, listener) in &mut self.transition_listeners {
+            match listener {
+                ListenerCallback::Rust(f) => f(event),
+                #[cfg(feature = "wasm")]
+                ListenerCallback::Js(f) => {
+                    // Listener errors stay on the JS side
+                    let _ = f.apply(&JsValue::NULL, &js_args(event));
+                }
+            }
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ComponentLifecycleBC {
+    /// Registers a JS callback for applied transitions
+    ///
+    /// The callback is invoked as `callback(componentId, from, to, metadata)`.
+    ///
+    /// # Returns
+    /// Hook id for `removeTransitionHook`
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(js_name = onTransition)]
+    pub fn on_transition_js(&mut self, callback: js_sys::Function) -> u32 {
+        let id = self.allocate_hook_id();
+        self.transition_listeners
+            .push((id, ListenerCallback::Js(callback)));
+        id
+    }
+
+    /// Registers a JS pre-transition hook that can veto a change
+    ///
+    /// The hook is invoked as `hook(componentId, from, to, metadata)` and
+    /// vetoes by returning `false` or a reason string, or by throwing.
+    ///
+    /// # Returns
+    /// Hook id for `removeTransitionHook`
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(js_name = onBeforeTransition)]
+    pub fn on_before_transition_js(&mut self, hook: js_sys::Function) -> u32 {
+        let id = self.allocate_hook_id();
+        self.pre_transition_hooks.push((id, HookCallback::Js(hook)));
+        id
+    }
+
+    /// Removes a hook or listener by id
+    ///
+    /// # Returns
+    /// false if no hook has that id
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = removeTransitionHook))]
+    pub fn remove_transition_hook(&mut self, id: u32) -> bool {
+        let before = self.pre_transition_hooks.len() + self.transition_listeners.len();
+        self.pre_transition_hooks.retain(|(hook_id, _)| *hook_id != id);
+        self.transition_listeners.retain(|(hook_id, _)| *hook_id != id);
+        before != self.pre_transition_hooks.len() + self.transition_listeners.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harmony_schemas::StateTransition;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn transition(id: &str, from: ComponentState, to: ComponentState) -> StateTransition {
+        StateTransition {
+            component_id: id.to_string(),
+            from_state: from,
+            to_state: to,
+            reason: Some("review passed".to_string()),
+            metadata: None,
+            actor: None,
+        }
+    }
+
+    #[test]
+    fn test_listener_receives_applied_transitions() {
+        let mut bc = ComponentLifecycleBC::new();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let sink = events.clone();
+        bc.on_transition(move |event| sink.borrow_mut().push(event.clone()));
+
+        bc.initialize_component("button");
+        let result = bc.apply_transition(transition(
+            "button",
+            ComponentState::Draft,
+            ComponentState::DesignComplete,
+        ));
+        assert!(result.success);
+        // Rejected by the state table, so no event
+        bc.apply_transition(transition(
+            "button",
+            ComponentState::DesignComplete,
+            ComponentState::Published,
+        ));
+
+        let events = events.borrow();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].from, ComponentState::Draft);
+        assert_eq!(events[0].to, ComponentState::DesignComplete);
+        assert_eq!(
+            events[0].metadata.as_ref().and_then(|m| m.reason.as_deref()),
+            Some("review passed")
+        );
+    }
+
+    #[test]
+    fn test_pre_hook_vetoes_transition() {
+        let mut bc = ComponentLifecycleBC::new();
+        let notified = Rc::new(RefCell::new(0));
+        let counter = notified.clone();
+        bc.on_transition(move |_| *counter.borrow_mut() += 1);
+        let hook = bc.on_before_transition(|event| {
+            if event.to == ComponentState::DesignComplete {
+                Err("design freeze".to_string())
+            } else {
+                Ok(())
+            }
+        });
+
+        bc.initialize_component("card");
+        let result = bc.apply_transition(transition(
+            "card",
+            ComponentState::Draft,
+            ComponentState::DesignComplete,
+        ));
+        assert!(!result.success);
+        assert_eq!(result.new_state, Some(ComponentState::Draft));
+        assert_eq!(result.error.as_deref(), Some("Transition vetoed: design freeze"));
+        assert_eq!(bc.history("card").unwrap().entries.len(), 1);
+        assert_eq!(*notified.borrow(), 0);
+
+        assert!(bc.remove_transition_hook(hook));
+        assert!(!bc.remove_transition_hook(hook));
+        let result = bc.apply_transition(transition(
+            "card",
+            ComponentState::Draft,
+            ComponentState::DesignComplete,
+        ));
+        assert!(result.success);
+        assert_eq!(*notified.borrow(), 1);
+    }
+}
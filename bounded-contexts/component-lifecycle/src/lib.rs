@@ -1,128 +1,384 @@
 //! Component Lifecycle Bounded Context
 //! 
 //! Manages component state transitions through the design system lifecycle.
+//! Every successful transition is appended to the component's
+//! `LifecycleHistory` with a timestamp and `StateMetadata`. Guards require
+//! approval flags before specific transitions, role requirements restrict who
+//! may perform them (with an audit log), publishing waits on the
+//! component's graph dependencies, pre-transition hooks can veto changes,
+//! and listeners are notified of applied ones.
 //! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle
 
-use harmony_schemas::{ComponentState, StateTransition, TransitionResult};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use harmony_error::limits;
+use harmony_schemas::{
+    Actor, ComponentState, Edge, LifecycleEntry, LifecycleHistory, StateMetadata,
+    StateTransition, TransitionResult,
+};
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
+mod authorization;
+mod batch;
+mod dependencies;
+mod guards;
+mod hooks;
+mod metrics;
+mod persist;
+mod query;
+mod rollback;
+mod schedule;
+
+pub use authorization::{AuditOutcome, AuditRecord};
+pub use dependencies::PublishReadiness;
+pub use hooks::{PreTransitionHook, TransitionEvent, TransitionListener};
+pub use metrics::{ComponentMetrics, LifecycleMetrics, StuckComponent};
+pub use persist::{
+    ComponentSnapshot, GuardSnapshot, LifecycleSnapshot, RoleSnapshot, SNAPSHOT_VERSION,
+};
+pub use schedule::{FiredTransition, ScheduleTrigger, ScheduledTransition, MS_PER_DAY};
+
+use hooks::{HookCallback, ListenerCallback};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct ComponentLifecycleBC {
     component_states: HashMap<String, ComponentState>,
+    histories: HashMap<String, LifecycleHistory>,
+    transition_guards: HashMap<(ComponentState, ComponentState), Vec<String>>,
+    component_flags: HashMap<String, BTreeSet<String>>,
+    dependency_edges: HashMap<String, Edge>,
+    schedules: BTreeMap<u32, ScheduledTransition>,
+    next_schedule_id: u32,
+    transition_roles: HashMap<(ComponentState, ComponentState), Vec<String>>,
+    audit_log: Vec<AuditRecord>,
+    pre_transition_hooks: Vec<(u32, HookCallback)>,
+    transition_listeners: Vec<(u32, ListenerCallback)>,
+    next_hook_id: u32,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl ComponentLifecycleBC {
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new() -> Self {
         Self {
             component_states: HashMap::new(),
+            histories: HashMap::new(),
+            transition_guards: HashMap::new(),
+            component_flags: HashMap::new(),
+            dependency_edges: HashMap::new(),
+            schedules: BTreeMap::new(),
+            next_schedule_id: 1,
+            transition_roles: HashMap::new(),
+            audit_log: Vec::new(),
+            pre_transition_hooks: Vec::new(),
+            transition_listeners: Vec::new(),
+            next_hook_id: 1,
         }
     }
 
     /// Initialize a component in draft state
-    #[wasm_bindgen(js_name = initializeComponent)]
+    ///
+    /// Re-initializing an existing component starts a fresh history.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = initializeComponent))]
     pub fn initialize_component(&mut self, component_id: &str) -> String {
         self.component_states
             .insert(component_id.to_string(), ComponentState::Draft);
+        self.histories
+            .insert(component_id.to_string(), LifecycleHistory::new());
+        self.record_entry(component_id, ComponentState::Draft, None);
         
         serde_json::to_string(&TransitionResult {
             success: true,
             component_id: component_id.to_string(),
             new_state: Some(ComponentState::Draft),
             error: None,
+            unmet_guards: Vec::new(),
+            blocking_dependencies: Vec::new(),
+            authorization: None,
         })
         .unwrap_or_else(|_| "{}".to_string())
     }
 
     /// Attempt to transition a component to a new state
-    #[wasm_bindgen(js_name = transitionComponent)]
+    ///
+    /// Guards and pre-transition hooks run after the transition table check
+    /// and may reject the change; transition listeners are notified once it is applied.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = transitionComponent))]
     pub fn transition_component(&mut self, transition_json: &str) -> String {
-        let transition: StateTransition = match serde_json::from_str(transition_json) {
+        let transition: StateTransition = match parse_json(transition_json) {
             Ok(t) => t,
             Err(e) => {
-                return serde_json::to_string(&TransitionResult {
-                    success: false,
-                    component_id: String::new(),
-                    new_state: None,
-                    error: Some(format!("Invalid transition JSON: {}", e)),
-                })
-                .unwrap_or_else(|_| "{}".to_string());
+                return result_json(&failure(
+                    String::new(),
+                    None,
+                    format!("Invalid transition JSON: {}", e),
+                ));
             }
         };
 
-        let current_state = match self.component_states.get(&transition.component_id) {
-            Some(state) => *state,
-            None => {
-                return serde_json::to_string(&TransitionResult {
-                    success: false,
-                    component_id: transition.component_id,
-                    new_state: None,
-                    error: Some("Component not found".to_string()),
-                })
-                .unwrap_or_else(|_| "{}".to_string());
+        result_json(&self.apply_transition(transition))
+    }
+
+    /// Deprecate a component from whatever state it is in
+    ///
+    /// Deprecation is terminal: no further transitions are accepted, and
+    /// deprecating a deprecated component fails without recording anything.
+    ///
+    /// # Arguments
+    /// * `component_id` - Component to deprecate
+    /// * `reason` - Recorded in the history entry; empty for none
+    /// * `actor_json` - Optional JSON Actor, checked against the roles
+    ///   required for deprecation
+    ///
+    /// # Returns
+    /// JSON TransitionResult
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = deprecateComponent))]
+    pub fn deprecate_component(
+        &mut self,
+        component_id: &str,
+        reason: &str,
+        actor_json: Option<String>,
+    ) -> String {
+        let actor = match parse_actor(actor_json.as_deref()) {
+            Ok(actor) => actor,
+            Err(error) => return result_json(&failure(component_id.to_string(), None, error)),
+        };
+        let Some(current_state) = self.component_states.get(component_id).copied() else {
+            return result_json(&failure(
+                component_id.to_string(),
+                None,
+                "Component not found".to_string(),
+            ));
+        };
+        result_json(&self.apply_transition(StateTransition {
+            component_id: component_id.to_string(),
+            from_state: current_state,
+            to_state: ComponentState::Deprecated,
+            reason: (!reason.is_empty()).then(|| reason.to_string()),
+            metadata: None,
+            actor,
+        }))
+    }
+
+    /// Get current state of a component
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getComponentState))]
+    pub fn get_component_state(&self, component_id: &str) -> String {
+        match self.component_states.get(component_id) {
+            Some(state) => format!("\"{}\"", state),
+            None => "null".to_string(),
+        }
+    }
+
+    /// Get all valid next states for a component
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getNextStates))]
+    pub fn get_next_states(&self, component_id: &str) -> String {
+        match self.component_states.get(component_id) {
+            Some(state) => {
+                let next = state.next_states();
+                serde_json::to_string(&next).unwrap_or_else(|_| "[]".to_string())
             }
+            None => "[]".to_string(),
+        }
+    }
+
+    /// Get the ordered transition history of a component
+    ///
+    /// # Returns
+    /// JSON-encoded LifecycleHistory (oldest entry first), or "null" if the
+    /// component is unknown
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getComponentHistory))]
+    pub fn get_component_history(&self, component_id: &str) -> String {
+        match self.histories.get(component_id) {
+            Some(history) => serde_json::to_string(history).unwrap_or_else(|_| "null".to_string()),
+            None => "null".to_string(),
+        }
+    }
+}
+
+impl ComponentLifecycleBC {
+    /// Validates and applies a single transition
+    pub fn apply_transition(&mut self, transition: StateTransition) -> TransitionResult {
+        match self.prepare_transition(transition, &HashMap::new()) {
+            Ok(event) => self.commit_transition(event),
+            Err(result) => *result,
+        }
+    }
+
+    /// Checks a transition against the current states and the pre-transition hooks
+    ///
+    /// States in `staged` take precedence over the committed ones, for the
+    /// component itself and for its dependencies. Only the audit log is
+    /// modified (for denied actors), so callers may validate a sequence of
+    /// transitions before applying any.
+    ///
+    /// # Returns
+    /// The event to commit, or the failed result
+    fn prepare_transition(
+        &mut self,
+        transition: StateTransition,
+        staged: &HashMap<String, ComponentState>,
+    ) -> Result<TransitionEvent, Box<TransitionResult>> {
+        let current_state = staged
+            .get(&transition.component_id)
+            .or_else(|| self.component_states.get(&transition.component_id))
+            .copied();
+        let Some(current_state) = current_state else {
+            return Err(Box::new(failure(
+                transition.component_id,
+                None,
+                "Component not found".to_string(),
+            )));
         };
 
         if current_state != transition.from_state {
-            return serde_json::to_string(&TransitionResult {
-                success: false,
-                component_id: transition.component_id,
-                new_state: Some(current_state),
-                error: Some(format!(
+            return Err(Box::new(failure(
+                transition.component_id,
+                Some(current_state),
+                format!(
                     "State mismatch: expected {}, found {}",
                     transition.from_state, current_state
-                )),
-            })
-            .unwrap_or_else(|_| "{}".to_string());
+                ),
+            )));
+        }
+
+        // The transition table accepts same-state moves, but a terminal
+        // state must not be re-entered (and so re-recorded)
+        if current_state.is_terminal() && transition.to_state == current_state {
+            return Err(Box::new(failure(
+                transition.component_id,
+                Some(current_state),
+                format!("Component is already {}", current_state),
+            )));
         }
 
         if !current_state.can_transition_to(transition.to_state) {
-            return serde_json::to_string(&TransitionResult {
-                success: false,
-                component_id: transition.component_id,
-                new_state: Some(current_state),
-                error: Some(format!(
+            return Err(Box::new(failure(
+                transition.component_id,
+                Some(current_state),
+                format!(
                     "Invalid transition: {} -> {}",
                     transition.from_state, transition.to_state
-                )),
-            })
-            .unwrap_or_else(|_| "{}".to_string());
+                ),
+            )));
         }
 
+        if let Err(error) = self.authorize(&transition, current_state) {
+            let mut result = failure(
+                transition.component_id,
+                Some(current_state),
+                format!("Unauthorized: requires one of {}", error.required_roles.join(", ")),
+            );
+            result.authorization = Some(error);
+            return Err(Box::new(result));
+        }
+
+        let unmet_guards =
+            self.unmet_guards(&transition.component_id, current_state, transition.to_state);
+        if !unmet_guards.is_empty() {
+            let mut result = failure(
+                transition.component_id,
+                Some(current_state),
+                format!("Unmet guards: {}", unmet_guards.join(", ")),
+            );
+            result.unmet_guards = unmet_guards;
+            return Err(Box::new(result));
+        }
+
+        if transition.to_state == ComponentState::Published {
+            let blocking = self.staged_blocking_dependencies(&transition.component_id, staged);
+            if !blocking.is_empty() {
+                let names: Vec<String> = blocking
+                    .iter()
+                    .map(|dep| format!("{} ({})", dep.component_id, dep.state))
+                    .collect();
+                let mut result = failure(
+                    transition.component_id,
+                    Some(current_state),
+                    format!("Blocked by dependencies: {}", names.join(", ")),
+                );
+                result.blocking_dependencies = blocking;
+                return Err(Box::new(result));
+            }
+        }
+
+        let mut metadata = transition.metadata.unwrap_or_default();
+        if metadata.reason.is_none() {
+            metadata.reason = transition.reason;
+        }
+        if metadata.changed_by.is_none() {
+            metadata.changed_by = transition.actor.as_ref().map(|actor| actor.id.clone());
+        }
+        let event = TransitionEvent {
+            component_id: transition.component_id,
+            from: current_state,
+            to: transition.to_state,
+            metadata: Some(metadata).filter(|m| *m != StateMetadata::default()),
+            actor: transition.actor,
+        };
+
+        if let Err(reason) = self.run_pre_transition_hooks(&event) {
+            return Err(Box::new(failure(
+                event.component_id,
+                Some(current_state),
+                format!("Transition vetoed: {}", reason),
+            )));
+        }
+        Ok(event)
+    }
+
+    /// Applies a prepared transition, records it and notifies listeners
+    fn commit_transition(&mut self, event: TransitionEvent) -> TransitionResult {
         self.component_states
-            .insert(transition.component_id.clone(), transition.to_state);
+            .insert(event.component_id.clone(), event.to);
+        self.record_entry(&event.component_id, event.to, event.metadata.clone());
+        if let Some(actor) = &event.actor {
+            self.record_audit(AuditRecord::new(
+                &event.component_id,
+                Some(actor.id.clone()),
+                event.from,
+                event.to,
+                AuditOutcome::Applied,
+            ));
+        }
+        self.notify_transition_listeners(&event);
 
-        serde_json::to_string(&TransitionResult {
+        TransitionResult {
             success: true,
-            component_id: transition.component_id,
-            new_state: Some(transition.to_state),
+            component_id: event.component_id,
+            new_state: Some(event.to),
             error: None,
-        })
-        .unwrap_or_else(|_| "{}".to_string())
+            unmet_guards: Vec::new(),
+            blocking_dependencies: Vec::new(),
+            authorization: None,
+        }
     }
 
-    /// Get current state of a component
-    #[wasm_bindgen(js_name = getComponentState)]
-    pub fn get_component_state(&self, component_id: &str) -> String {
-        match self.component_states.get(component_id) {
-            Some(state) => format!("\"{}\"", state),
-            None => "null".to_string(),
-        }
+    /// History of a component, if it has been initialized
+    pub fn history(&self, component_id: &str) -> Option<&LifecycleHistory> {
+        self.histories.get(component_id)
     }
 
-    /// Get all valid next states for a component
-    #[wasm_bindgen(js_name = getNextStates)]
-    pub fn get_next_states(&self, component_id: &str) -> String {
-        match self.component_states.get(component_id) {
-            Some(state) => {
-                let next = state.next_states();
-                serde_json::to_string(&next).unwrap_or_else(|_| "[]".to_string())
-            }
-            None => "[]".to_string(),
-        }
+    /// Appends a timestamped entry to a component's history
+    ///
+    /// Entries are pushed directly rather than through
+    /// `LifecycleHistory::transition_to`, because ComponentState has its own
+    /// transition table which has already been checked.
+    fn record_entry(
+        &mut self,
+        component_id: &str,
+        state: ComponentState,
+        metadata: Option<StateMetadata>,
+    ) {
+        let entry = match metadata {
+            Some(metadata) => LifecycleEntry::with_metadata(state.into(), metadata),
+            None => LifecycleEntry::new(state.into()),
+        };
+        self.histories
+            .entry(component_id.to_string())
+            .or_default()
+            .entries
+            .push(entry);
     }
 }
 
@@ -130,4 +386,122 @@ impl Default for ComponentLifecycleBC {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+fn failure(component_id: String, state: Option<ComponentState>, error: String) -> TransitionResult {
+    TransitionResult {
+        success: false,
+        component_id,
+        new_state: state,
+        error: Some(error),
+        unmet_guards: Vec::new(),
+        blocking_dependencies: Vec::new(),
+        authorization: None,
+    }
+}
+
+/// Milliseconds since the Unix epoch of an RFC 3339 history timestamp
+fn timestamp_ms(timestamp: &str) -> Option<f64> {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|time| time.timestamp_millis() as f64)
+}
+
+/// Parses an optional JSON Actor argument; `None` or an empty string for no actor
+fn parse_actor(actor_json: Option<&str>) -> Result<Option<Actor>, String> {
+    match actor_json.filter(|json| !json.is_empty()) {
+        Some(json) => parse_json(json)
+            .map(Some)
+            .map_err(|e| format!("Invalid actor JSON: {}", e)),
+        None => Ok(None),
+    }
+}
+
+/// Parses a JSON argument once it passes the input limits
+///
+/// Oversized or too deeply nested JSON fails with the limit's message.
+fn parse_json<T: DeserializeOwned>(json: &str) -> Result<T, String> {
+    limits::check_json(json).map_err(|e| e.to_string())?;
+    serde_json::from_str(json).map_err(|e| e.to_string())
+}
+
+fn result_json(result: &TransitionResult) -> String {
+    serde_json::to_string(result).unwrap_or_else(|_| "{}".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harmony_schemas::LifecycleState;
+
+    fn transition(id: &str, from: &str, to: &str) -> String {
+        format!(
+            r#"{{"component_id":"{}","from_state":"{}","to_state":"{}","reason":"ready"}}"#,
+            id, from, to
+        )
+    }
+
+    #[test]
+    fn test_history_records_transitions() {
+        let mut bc = ComponentLifecycleBC::new();
+        bc.initialize_component("button");
+        bc.transition_component(&transition("button", "draft", "design_complete"));
+        // Rejected transitions are not recorded
+        bc.transition_component(&transition("button", "design_complete", "published"));
+
+        let history = bc.history("button").unwrap();
+        let states: Vec<LifecycleState> = history.entries.iter().map(|e| e.state).collect();
+        assert_eq!(states, vec![LifecycleState::Draft, LifecycleState::DesignComplete]);
+        assert!(history.entries[0].metadata.is_none());
+        assert_eq!(
+            history.entries[1].metadata.as_ref().unwrap().reason.as_deref(),
+            Some("ready")
+        );
+    }
+
+    #[test]
+    fn test_history_json() {
+        let mut bc = ComponentLifecycleBC::new();
+        assert_eq!(bc.get_component_history("card"), "null");
+
+        bc.initialize_component("card");
+        let json = bc.transition_component(
+            r#"{"component_id":"card","from_state":"draft","to_state":"design_complete",
+                "metadata":{"changed_by":"alice","notes":"spec signed off"}}"#,
+        );
+        assert!(json.contains("\"success\":true"));
+
+        let history: LifecycleHistory =
+            serde_json::from_str(&bc.get_component_history("card")).unwrap();
+        assert_eq!(history.entries.len(), 2);
+        let metadata = history.entries[1].metadata.as_ref().unwrap();
+        assert_eq!(metadata.changed_by.as_deref(), Some("alice"));
+        assert!(metadata.reason.is_none());
+    }
+
+    #[test]
+    fn test_deprecation_is_terminal() {
+        let mut bc = ComponentLifecycleBC::new();
+        bc.initialize_component("legacy-button");
+        assert!(bc
+            .deprecate_component("legacy-button", "replaced by button", None)
+            .contains("\"new_state\":\"deprecated\""));
+        assert_eq!(bc.get_next_states("legacy-button"), "[]");
+        assert!(bc
+            .deprecate_component("legacy-button", "", None)
+            .contains("Component is already deprecated"));
+        assert!(bc
+            .transition_component(&transition("legacy-button", "deprecated", "draft"))
+            .contains("Invalid transition"));
+        assert!(bc.deprecate_component("missing", "", None).contains("Component not found"));
+
+        let history = bc.history("legacy-button").unwrap();
+        assert_eq!(history.entries.len(), 2);
+        let deprecated = &history.entries[1];
+        assert_eq!(deprecated.state, LifecycleState::Deprecated);
+        assert_eq!(
+            deprecated.metadata.as_ref().unwrap().reason.as_deref(),
+            Some("replaced by button")
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Lifecycle analytics: time-in-state metrics
+//!
+//! Derived entirely from each component's `LifecycleHistory`. A stint is the
+//! span between entering a state and the next history entry; the current
+//! stint runs until `now`. Entries with unparseable timestamps are skipped.
+//!
+//! Report contents:
+//! - per component: time spent in each state, time in the current state,
+//!   and Draft→Published lead time (first Draft to first later Published)
+//! - aggregate: mean stint length per state and mean Draft→Published time
+//! - stuck components: those whose current stint exceeds a threshold;
+//!   Published and Deprecated are resting states and never count as stuck
+//!
+//! Times are milliseconds; `now` is ms since the Unix epoch.
+//!
+//! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle
+
+use std::collections::BTreeMap;
+
+use harmony_schemas::{ComponentState, LifecycleHistory};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{timestamp_ms, ComponentLifecycleBC};
+
+/// Time-in-state figures for one component
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentMetrics {
+    pub component_id: String,
+    pub current_state: ComponentState,
+    pub time_in_current_state_ms: f64,
+    /// Total time spent in each state, keyed by state name
+    pub time_in_state_ms: BTreeMap<String, f64>,
+    pub draft_to_published_ms: Option<f64>,
+}
+
+/// A component that has stayed in a working state too long
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StuckComponent {
+    pub component_id: String,
+    pub state: ComponentState,
+    pub time_in_state_ms: f64,
+}
+
+/// Lifecycle metrics report
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleMetrics {
+    /// Per-component figures, sorted by id
+    pub components: Vec<ComponentMetrics>,
+    /// Mean stint length per state, keyed by state name
+    pub average_time_in_state_ms: BTreeMap<String, f64>,
+    /// Mean Draft→Published time over components that have been published
+    pub average_draft_to_published_ms: Option<f64>,
+    /// Components over the stuck threshold, longest first
+    pub stuck: Vec<StuckComponent>,
+}
+
+/// (state, entered at) for every entry with a valid timestamp
+fn stints(history: &LifecycleHistory) -> Vec<(ComponentState, f64)> {
+    history
+        .entries
+        .iter()
+        .filter_map(|entry| Some((entry.state.into(), timestamp_ms(&entry.timestamp)?)))
+        .collect()
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+impl ComponentLifecycleBC {
+    /// Computes time-in-state metrics
+    ///
+    /// # Arguments
+    /// * `now_ms` - Current time, ends every current stint
+    /// * `stuck_after_ms` - Current-stint length above which a component is stuck
+    pub fn lifecycle_metrics(&self, now_ms: f64, stuck_after_ms: f64) -> LifecycleMetrics {
+        let mut components = Vec::new();
+        let mut stint_lengths: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        let mut lead_times = Vec::new();
+        let mut stuck = Vec::new();
+
+        let mut ids: Vec<&String> = self.component_states.keys().collect();
+        ids.sort();
+        for id in ids {
+            let current_state = self.component_states[id];
+            let stints = self.histories.get(id).map(stints).unwrap_or_default();
+
+            let mut time_in_state_ms = BTreeMap::new();
+            for (i, (state, entered)) in stints.iter().enumerate() {
+                let left = stints.get(i + 1).map_or(now_ms, |(_, next)| *next);
+                let length = (left - entered).max(0.0);
+                *time_in_state_ms.entry(state.to_string()).or_insert(0.0) += length;
+                stint_lengths.entry(state.to_string()).or_default().push(length);
+            }
+
+            let time_in_current_state_ms = stints
+                .last()
+                .map_or(0.0, |(_, entered)| (now_ms - entered).max(0.0));
+
+            let draft = stints
+                .iter()
+                .position(|(state, _)| *state == ComponentState::Draft);
+            let draft_to_published_ms = draft.and_then(|start| {
+                stints[start..]
+                    .iter()
+                    .find(|(state, _)| *state == ComponentState::Published)
+                    .map(|(_, published)| published - stints[start].1)
+            });
+            lead_times.extend(draft_to_published_ms);
+
+            let resting = matches!(
+                current_state,
+                ComponentState::Published | ComponentState::Deprecated
+            );
+            if !resting && time_in_current_state_ms > stuck_after_ms {
+                stuck.push(StuckComponent {
+                    component_id: id.clone(),
+                    state: current_state,
+                    time_in_state_ms: time_in_current_state_ms,
+                });
+            }
+
+            components.push(ComponentMetrics {
+                component_id: id.clone(),
+                current_state,
+                time_in_current_state_ms,
+                time_in_state_ms,
+                draft_to_published_ms,
+            });
+        }
+
+        stuck.sort_by(|a, b| b.time_in_state_ms.total_cmp(&a.time_in_state_ms));
+        LifecycleMetrics {
+            components,
+            average_time_in_state_ms: stint_lengths
+                .into_iter()
+                .filter_map(|(state, lengths)| Some((state, mean(&lengths)?)))
+                .collect(),
+            average_draft_to_published_ms: mean(&lead_times),
+            stuck,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ComponentLifecycleBC {
+    /// Reports time-in-state metrics for all components
+    ///
+    /// # Arguments
+    /// * `now_ms` - Current time in ms since the Unix epoch (`Date.now()`)
+    /// * `stuck_after_ms` - Threshold for listing a component as stuck
+    ///
+    /// # Returns
+    /// JSON LifecycleMetrics report
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getLifecycleMetrics))]
+    pub fn get_lifecycle_metrics(&self, now_ms: f64, stuck_after_ms: f64) -> String {
+        serde_json::to_string(&self.lifecycle_metrics(now_ms, stuck_after_ms))
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MS_PER_DAY as DAY;
+    use harmony_schemas::{LifecycleEntry, LifecycleState};
+
+    fn history(entries: &[(LifecycleState, &str)]) -> LifecycleHistory {
+        LifecycleHistory {
+            entries: entries
+                .iter()
+                .map(|(state, timestamp)| LifecycleEntry {
+                    state: *state,
+                    timestamp: timestamp.to_string(),
+                    metadata: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn setup() -> (ComponentLifecycleBC, f64) {
+        let mut bc = ComponentLifecycleBC::new();
+        let components = [
+            (
+                "button",
+                ComponentState::Published,
+                history(&[
+                    (LifecycleState::Draft, "2026-01-01T00:00:00Z"),
+                    (LifecycleState::DesignComplete, "2026-01-03T00:00:00Z"),
+                    (LifecycleState::Published, "2026-01-11T00:00:00Z"),
+                ]),
+            ),
+            (
+                "card",
+                ComponentState::Published,
+                history(&[
+                    (LifecycleState::Draft, "2026-01-01T00:00:00Z"),
+                    (LifecycleState::Published, "2026-01-21T00:00:00Z"),
+                ]),
+            ),
+            (
+                "modal",
+                ComponentState::InDevelopment,
+                history(&[
+                    (LifecycleState::Draft, "2026-01-01T00:00:00Z"),
+                    (LifecycleState::InDevelopment, "2026-01-02T00:00:00Z"),
+                ]),
+            ),
+        ];
+        for (id, state, history) in components {
+            bc.component_states.insert(id.to_string(), state);
+            bc.histories.insert(id.to_string(), history);
+        }
+        let now = timestamp_ms("2026-01-31T00:00:00Z").unwrap();
+        (bc, now)
+    }
+
+    #[test]
+    fn test_per_component_metrics() {
+        let (bc, now) = setup();
+        let metrics = bc.lifecycle_metrics(now, 14.0 * DAY);
+
+        let button = &metrics.components[0];
+        assert_eq!(button.component_id, "button");
+        assert_eq!(button.time_in_state_ms["draft"], 2.0 * DAY);
+        assert_eq!(button.time_in_state_ms["design_complete"], 8.0 * DAY);
+        assert_eq!(button.time_in_current_state_ms, 20.0 * DAY);
+        assert_eq!(button.draft_to_published_ms, Some(10.0 * DAY));
+
+        assert_eq!(metrics.components[2].draft_to_published_ms, None);
+    }
+
+    #[test]
+    fn test_aggregates_and_stuck() {
+        let (bc, now) = setup();
+        let metrics = bc.lifecycle_metrics(now, 14.0 * DAY);
+
+        assert_eq!(metrics.average_draft_to_published_ms, Some(15.0 * DAY));
+        // Draft stints: 2, 20 and 1 days
+        assert_eq!(metrics.average_time_in_state_ms["draft"], 23.0 * DAY / 3.0);
+
+        // Published components are never stuck, however long they rest
+        assert_eq!(metrics.stuck.len(), 1);
+        assert_eq!(metrics.stuck[0].component_id, "modal");
+        assert_eq!(metrics.stuck[0].time_in_state_ms, 29.0 * DAY);
+
+        assert!(bc.lifecycle_metrics(now, 30.0 * DAY).stuck.is_empty());
+    }
+}
@@ -0,0 +1,301 @@
+//! Persisting and restoring lifecycle state
+//!
+//! `exportState` serializes every component's state, history and guard
+//! flags, plus the configured transition guards and roles, pending
+//! scheduled transitions and the audit log, into a JSON snapshot that
+//! the host stores (e.g. in IndexedDB). `importState` replaces the BC's
+//! contents with a snapshot. Hooks and listeners are functions and are not
+//! part of the snapshot; they stay registered across an import. Dependency
+//! edges are not part of it either; an import drops them, and the host
+//! re-syncs them from the graph.
+//!
+//! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle
+
+use std::collections::{BTreeSet, HashMap};
+
+use harmony_schemas::{ComponentState, LifecycleHistory, LifecycleState};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{parse_json, AuditRecord, ComponentLifecycleBC, ScheduledTransition};
+
+/// Snapshot format version written by `export_snapshot`
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// One component in a snapshot
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentSnapshot {
+    pub component_id: String,
+    pub state: ComponentState,
+    #[serde(default)]
+    pub history: Option<LifecycleHistory>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub flags: BTreeSet<String>,
+}
+
+/// Guard requirement in a snapshot
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuardSnapshot {
+    pub from: ComponentState,
+    pub to: ComponentState,
+    pub guards: Vec<String>,
+}
+
+/// Role requirement in a snapshot
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoleSnapshot {
+    pub from: ComponentState,
+    pub to: ComponentState,
+    pub roles: Vec<String>,
+}
+
+/// Serializable contents of a ComponentLifecycleBC
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleSnapshot {
+    pub version: u32,
+    pub components: Vec<ComponentSnapshot>,
+    #[serde(default)]
+    pub guards: Vec<GuardSnapshot>,
+    #[serde(default)]
+    pub schedules: Vec<ScheduledTransition>,
+    #[serde(default)]
+    pub roles: Vec<RoleSnapshot>,
+    #[serde(default)]
+    pub audit: Vec<AuditRecord>,
+}
+
+#[derive(Serialize)]
+struct ImportResult {
+    success: bool,
+    components: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ComponentLifecycleBC {
+    /// Captures the BC's contents, sorted by component id
+    pub fn export_snapshot(&self) -> LifecycleSnapshot {
+        let mut components: Vec<ComponentSnapshot> = self
+            .component_states
+            .iter()
+            .map(|(id, state)| ComponentSnapshot {
+                component_id: id.clone(),
+                state: *state,
+                history: self.histories.get(id).cloned(),
+                flags: self.component_flags.get(id).cloned().unwrap_or_default(),
+            })
+            .collect();
+        components.sort_by(|a, b| a.component_id.cmp(&b.component_id));
+
+        let mut guards: Vec<GuardSnapshot> = self
+            .transition_guards
+            .iter()
+            .map(|((from, to), guards)| GuardSnapshot {
+                from: *from,
+                to: *to,
+                guards: guards.clone(),
+            })
+            .collect();
+        guards.sort_by_key(|g| (g.from.to_string(), g.to.to_string()));
+
+        let mut roles: Vec<RoleSnapshot> = self
+            .transition_roles
+            .iter()
+            .map(|((from, to), roles)| RoleSnapshot {
+                from: *from,
+                to: *to,
+                roles: roles.clone(),
+            })
+            .collect();
+        roles.sort_by_key(|r| (r.from.to_string(), r.to.to_string()));
+
+        LifecycleSnapshot {
+            version: SNAPSHOT_VERSION,
+            components,
+            guards,
+            schedules: self.schedules.values().cloned().collect(),
+            roles,
+            audit: self.audit_log.clone(),
+        }
+    }
+
+    /// Replaces the BC's contents with a snapshot
+    ///
+    /// The snapshot is validated first; on error nothing changes. A history,
+    /// when present, must end in the component's current state. Dependency
+    /// edges are cleared, since they may name components the snapshot lacks.
+    ///
+    /// # Returns
+    /// Number of components restored
+    pub fn import_snapshot(&mut self, snapshot: LifecycleSnapshot) -> Result<usize, String> {
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(format!("Unsupported snapshot version {}", snapshot.version));
+        }
+
+        let mut states = HashMap::new();
+        let mut histories = HashMap::new();
+        let mut flags = HashMap::new();
+        for component in snapshot.components {
+            let id = component.component_id;
+            if states.insert(id.clone(), component.state).is_some() {
+                return Err(format!("Duplicate component {}", id));
+            }
+            if let Some(history) = component.history {
+                let last = history.current_state().map(|entry| entry.state);
+                if last != Some(LifecycleState::from(component.state)) {
+                    return Err(format!("History of {} does not end in its current state", id));
+                }
+                histories.insert(id.clone(), history);
+            }
+            if !component.flags.is_empty() {
+                flags.insert(id, component.flags);
+            }
+        }
+
+        for schedule in &snapshot.schedules {
+            if !states.contains_key(&schedule.component_id) {
+                return Err(format!("Schedule {} targets unknown component", schedule.id));
+            }
+        }
+
+        let count = states.len();
+        self.component_states = states;
+        self.histories = histories;
+        self.component_flags = flags;
+        self.dependency_edges.clear();
+        self.transition_guards = snapshot
+            .guards
+            .into_iter()
+            .filter(|g| !g.guards.is_empty())
+            .map(|g| ((g.from, g.to), g.guards))
+            .collect();
+        self.transition_roles = snapshot
+            .roles
+            .into_iter()
+            .filter(|r| !r.roles.is_empty())
+            .map(|r| ((r.from, r.to), r.roles))
+            .collect();
+        self.audit_log = snapshot.audit;
+        self.schedules = snapshot
+            .schedules
+            .into_iter()
+            .map(|schedule| (schedule.id, schedule))
+            .collect();
+        self.next_schedule_id = self
+            .schedules
+            .keys()
+            .max()
+            .map_or(1, |id| id + 1)
+            .max(self.next_schedule_id);
+        Ok(count)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ComponentLifecycleBC {
+    /// Serializes all component states, histories and guards
+    ///
+    /// # Returns
+    /// JSON LifecycleSnapshot for `importState`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = exportState))]
+    pub fn export_state(&self) -> String {
+        serde_json::to_string(&self.export_snapshot()).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Replaces all lifecycle state with a snapshot from `exportState`
+    ///
+    /// # Returns
+    /// JSON `{"success", "components", "error"?}`; on failure the BC is unchanged
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = importState))]
+    pub fn import_state(&mut self, snapshot_json: &str) -> String {
+        let outcome = parse_json::<LifecycleSnapshot>(snapshot_json)
+            .map_err(|e| format!("Invalid snapshot JSON: {}", e))
+            .and_then(|snapshot| self.import_snapshot(snapshot));
+        let result = match outcome {
+            Ok(components) => ImportResult {
+                success: true,
+                components,
+                error: None,
+            },
+            Err(error) => ImportResult {
+                success: false,
+                components: 0,
+                error: Some(error),
+            },
+        };
+        serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let mut bc = ComponentLifecycleBC::new();
+        bc.initialize_component("card");
+        bc.initialize_component("button");
+        bc.transition_component(
+            r#"{"component_id":"button","from_state":"draft","to_state":"design_complete","reason":"approved"}"#,
+        );
+        bc.set_component_flag("button", "design_review", true);
+        bc.set_transition_guards_js("implemented", "published", r#"["a11y_check"]"#);
+        bc.schedule_deprecation_after_replacement("card", "button", 7.0, None);
+        bc.set_transition_roles_js("draft", "design_complete", r#"["designer"]"#);
+        bc.transition_component(
+            r#"{"component_id":"card","from_state":"draft","to_state":"design_complete"}"#,
+        );
+
+        let json = bc.export_state();
+        let mut restored = ComponentLifecycleBC::new();
+        assert_eq!(
+            restored.import_state(&json),
+            r#"{"success":true,"components":2}"#
+        );
+        assert_eq!(restored.export_snapshot(), bc.export_snapshot());
+        assert_eq!(restored.get_component_state("button"), "\"design_complete\"");
+        assert_eq!(restored.history("button").unwrap().entries.len(), 2);
+    }
+
+    #[test]
+    fn test_import_rejects_inconsistent_snapshot() {
+        let mut bc = ComponentLifecycleBC::new();
+        bc.initialize_component("button");
+        let mut snapshot = bc.export_snapshot();
+        snapshot.components[0].state = ComponentState::Published;
+
+        let mut target = ComponentLifecycleBC::new();
+        target.initialize_component("existing");
+        assert!(target.import_snapshot(snapshot).is_err());
+        assert!(target.import_state("not json").contains("\"success\":false"));
+        assert_eq!(target.get_component_state("existing"), "\"draft\"");
+    }
+
+    #[test]
+    fn test_import_clears_dependency_edges() {
+        let mut source = ComponentLifecycleBC::new();
+        source.initialize_component("form");
+        source.initialize_component("button");
+        let json = source.export_state();
+
+        let mut target = ComponentLifecycleBC::new();
+        target.initialize_component("form");
+        target.initialize_component("button");
+        let edges = r#"[{"id":"e1","from":"form","to":"button","edge_type":"composes_of"}]"#;
+        assert_eq!(target.add_dependency_edges(edges), 1);
+        assert_eq!(target.blocking_dependencies("form").len(), 1);
+
+        assert!(target.import_state(&json).contains("\"success\":true"));
+        assert!(target.blocking_dependencies("form").is_empty());
+        assert!(!target.remove_dependency_edge("e1"));
+    }
+}
@@ -0,0 +1,94 @@
+//! Aggregated views over component states
+//!
+//! Lets dashboards show totals such as "12 in development, 48 published"
+//! without fetching every component across the bridge.
+//!
+//! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle
+
+use std::collections::BTreeMap;
+
+use harmony_schemas::ComponentState;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::ComponentLifecycleBC;
+
+impl ComponentLifecycleBC {
+    /// Ids of components currently in `state`, sorted
+    pub fn components_in_state(&self, state: ComponentState) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .component_states
+            .iter()
+            .filter(|(_, s)| **s == state)
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Number of components in each state, including empty states
+    pub fn count_by_state(&self) -> BTreeMap<String, usize> {
+        let mut counts: BTreeMap<String, usize> = ComponentState::all_states()
+            .into_iter()
+            .map(|state| (state.to_string(), 0))
+            .collect();
+        for state in self.component_states.values() {
+            *counts.entry(state.to_string()).or_default() += 1;
+        }
+        counts
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ComponentLifecycleBC {
+    /// Lists components in a state
+    ///
+    /// # Arguments
+    /// * `state` - State name (snake_case)
+    ///
+    /// # Returns
+    /// JSON array of component ids, sorted; empty for unknown states
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getComponentsInState))]
+    pub fn get_components_in_state(&self, state: &str) -> String {
+        let ids = state
+            .parse()
+            .map(|state| self.components_in_state(state))
+            .unwrap_or_default();
+        serde_json::to_string(&ids).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Counts components per state
+    ///
+    /// # Returns
+    /// JSON object mapping every state name to its component count
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = countByState))]
+    pub fn count_by_state_json(&self) -> String {
+        serde_json::to_string(&self.count_by_state()).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_queries() {
+        let mut bc = ComponentLifecycleBC::new();
+        for id in ["card", "button", "input"] {
+            bc.initialize_component(id);
+        }
+        bc.transition_component(
+            r#"{"component_id":"input","from_state":"draft","to_state":"design_complete"}"#,
+        );
+
+        assert_eq!(bc.get_components_in_state("draft"), r#"["button","card"]"#);
+        assert_eq!(bc.get_components_in_state("published"), "[]");
+        assert_eq!(bc.get_components_in_state("bogus"), "[]");
+
+        let counts = bc.count_by_state();
+        assert_eq!(counts["draft"], 2);
+        assert_eq!(counts["design_complete"], 1);
+        assert_eq!(counts["published"], 0);
+        assert_eq!(counts.len(), ComponentState::all_states().len());
+    }
+}
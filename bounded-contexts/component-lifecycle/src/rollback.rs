@@ -0,0 +1,159 @@
+//! Undo of the last transition
+//!
+//! Rolling back moves a component to the state it held before its most
+//! recent history entry. The rollback is an ordinary transition: it must be
+//! allowed by the transition table (so Deprecated cannot be undone) and it
+//! passes through roles, guards, dependencies and hooks. It is appended to
+//! the history rather than erasing the undone entry, with notes marking it
+//! as a rollback.
+//!
+//! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle
+
+use harmony_schemas::{Actor, ComponentState, StateMetadata, StateTransition, TransitionResult};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{failure, parse_actor, result_json, ComponentLifecycleBC};
+
+impl ComponentLifecycleBC {
+    /// Reverts a component to the state before its last transition
+    pub fn rollback(
+        &mut self,
+        component_id: &str,
+        reason: Option<String>,
+        actor: Option<Actor>,
+    ) -> TransitionResult {
+        let Some(current_state) = self.component_states.get(component_id).copied() else {
+            return failure(component_id.to_string(), None, "Component not found".to_string());
+        };
+        let previous = self
+            .histories
+            .get(component_id)
+            .and_then(|history| history.entries.iter().rev().nth(1))
+            .map(|entry| ComponentState::from(entry.state));
+        let Some(previous) = previous else {
+            return failure(
+                component_id.to_string(),
+                Some(current_state),
+                "Nothing to roll back".to_string(),
+            );
+        };
+        if !current_state.can_transition_to(previous) {
+            return failure(
+                component_id.to_string(),
+                Some(current_state),
+                format!("Rollback not allowed: {} -> {}", current_state, previous),
+            );
+        }
+
+        self.apply_transition(StateTransition {
+            component_id: component_id.to_string(),
+            from_state: current_state,
+            to_state: previous,
+            reason: None,
+            metadata: Some(StateMetadata {
+                reason,
+                changed_by: None,
+                notes: Some(format!("Rollback of {} -> {}", previous, current_state)),
+            }),
+            actor,
+        })
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ComponentLifecycleBC {
+    /// Reverts the last transition of a component
+    ///
+    /// # Arguments
+    /// * `component_id` - Component to roll back
+    /// * `reason` - Recorded in the history entry; empty for none
+    /// * `actor_json` - Optional JSON Actor, checked against the roles
+    ///   required for the reverse transition
+    ///
+    /// # Returns
+    /// JSON TransitionResult
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = rollbackTransition))]
+    pub fn rollback_transition(
+        &mut self,
+        component_id: &str,
+        reason: &str,
+        actor_json: Option<String>,
+    ) -> String {
+        let actor = match parse_actor(actor_json.as_deref()) {
+            Ok(actor) => actor,
+            Err(error) => return result_json(&failure(component_id.to_string(), None, error)),
+        };
+        let reason = (!reason.is_empty()).then(|| reason.to_string());
+        result_json(&self.rollback(component_id, reason, actor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harmony_schemas::LifecycleState;
+
+    #[test]
+    fn test_rollback_accidental_publish() {
+        let mut bc = ComponentLifecycleBC::new();
+        bc.initialize_component("button");
+        let path = ["draft", "design_complete", "in_development", "implemented", "published"];
+        for step in path.windows(2) {
+            bc.transition_component(&format!(
+                r#"{{"component_id":"button","from_state":"{}","to_state":"{}"}}"#,
+                step[0], step[1]
+            ));
+        }
+
+        let result = bc.rollback("button", Some("published by mistake".to_string()), None);
+        assert!(result.success);
+        assert_eq!(result.new_state, Some(ComponentState::Implemented));
+
+        let entry = bc.history("button").unwrap().current_state().unwrap();
+        assert_eq!(entry.state, LifecycleState::Implemented);
+        let metadata = entry.metadata.as_ref().unwrap();
+        assert_eq!(metadata.reason.as_deref(), Some("published by mistake"));
+        assert_eq!(
+            metadata.notes.as_deref(),
+            Some("Rollback of implemented -> published")
+        );
+    }
+
+    #[test]
+    fn test_rollback_limits() {
+        let mut bc = ComponentLifecycleBC::new();
+        assert!(bc.rollback_transition("missing", "", None).contains("Component not found"));
+
+        bc.initialize_component("card");
+        assert!(bc.rollback_transition("card", "", None).contains("Nothing to roll back"));
+
+        bc.deprecate_component("card", "", None);
+        let json = bc.rollback_transition("card", "", None);
+        assert!(json.contains("Rollback not allowed: deprecated -> draft"));
+        assert_eq!(bc.get_component_state("card"), "\"deprecated\"");
+    }
+
+    #[test]
+    fn test_rollback_checks_actor_roles() {
+        let mut bc = ComponentLifecycleBC::new();
+        bc.initialize_component("card");
+        bc.transition_component(
+            r#"{"component_id":"card","from_state":"draft","to_state":"design_complete"}"#,
+        );
+        bc.set_transition_roles(
+            ComponentState::DesignComplete,
+            ComponentState::Draft,
+            vec!["designer".to_string()],
+        );
+
+        assert!(bc.rollback_transition("card", "", None).contains("Unauthorized"));
+        let json = bc.rollback_transition(
+            "card",
+            "",
+            Some(r#"{"id":"ana","roles":["designer"]}"#.to_string()),
+        );
+        assert!(json.contains("\"new_state\":\"draft\""));
+        assert_eq!(bc.audit_log("card").last().unwrap().actor_id.as_deref(), Some("ana"));
+    }
+}
@@ -0,0 +1,276 @@
+//! Scheduled and conditional automatic transitions
+//!
+//! A scheduled transition moves a component to a target state once its
+//! trigger is due:
+//! - `at`: a fixed time
+//! - `after_published`: a delay after another component (typically the
+//!   replacement) entered Published, taken from that component's history;
+//!   not due while the other component is not Published
+//!
+//! The host calls `tick(now)` periodically. Each due schedule is applied
+//! from the component's current state through the normal transition path
+//! (roles, guards, dependencies, hooks) on behalf of the schedule's actor,
+//! so listeners receive the usual events. A schedule is removed once it has
+//! fired, whether or not the transition was accepted; the result is
+//! reported by `tick`.
+//!
+//! Times are milliseconds since the Unix epoch, as returned by `Date.now()`.
+//!
+//! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle
+
+use harmony_schemas::{Actor, ComponentState, LifecycleState, StateTransition, TransitionResult};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{failure, parse_actor, parse_json, timestamp_ms, ComponentLifecycleBC};
+
+/// Milliseconds per day, for day-based delays
+pub const MS_PER_DAY: f64 = 86_400_000.0;
+
+/// When a scheduled transition becomes due
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleTrigger {
+    /// At a fixed time
+    At { at_ms: f64 },
+    /// `delay_ms` after `component_id` was published
+    AfterPublished { component_id: String, delay_ms: f64 },
+}
+
+/// A transition waiting for its trigger
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledTransition {
+    /// Assigned by the BC when scheduled
+    #[serde(default)]
+    pub id: u32,
+    pub component_id: String,
+    pub to_state: ComponentState,
+    pub trigger: ScheduleTrigger,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Performs the transition when it fires, for transitions that require roles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<Actor>,
+}
+
+/// Outcome of one schedule fired by `tick`
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FiredTransition {
+    pub schedule_id: u32,
+    pub result: TransitionResult,
+}
+
+impl ComponentLifecycleBC {
+    /// Adds a scheduled transition
+    ///
+    /// # Returns
+    /// Schedule id, or an error if the component is unknown
+    pub fn schedule_transition(&mut self, mut schedule: ScheduledTransition) -> Result<u32, String> {
+        if !self.component_states.contains_key(&schedule.component_id) {
+            return Err("Component not found".to_string());
+        }
+        schedule.id = self.next_schedule_id;
+        self.next_schedule_id += 1;
+        self.schedules.insert(schedule.id, schedule.clone());
+        Ok(schedule.id)
+    }
+
+    /// Time a schedule becomes due, if its condition currently holds
+    pub fn due_time(&self, trigger: &ScheduleTrigger) -> Option<f64> {
+        match trigger {
+            ScheduleTrigger::At { at_ms } => Some(*at_ms),
+            ScheduleTrigger::AfterPublished {
+                component_id,
+                delay_ms,
+            } => {
+                if self.component_states.get(component_id) != Some(&ComponentState::Published) {
+                    return None;
+                }
+                let entry = self.histories.get(component_id)?.current_state()?;
+                if entry.state != LifecycleState::Published {
+                    return None;
+                }
+                Some(timestamp_ms(&entry.timestamp)? + delay_ms)
+            }
+        }
+    }
+
+    /// Applies every schedule that is due at `now_ms`, in schedule order
+    pub fn run_due_transitions(&mut self, now_ms: f64) -> Vec<FiredTransition> {
+        let due: Vec<u32> = self
+            .schedules
+            .values()
+            .filter(|s| self.due_time(&s.trigger).is_some_and(|at| at <= now_ms))
+            .map(|s| s.id)
+            .collect();
+
+        let mut fired = Vec::with_capacity(due.len());
+        for id in due {
+            let Some(schedule) = self.schedules.remove(&id) else {
+                continue;
+            };
+            let result = match self.component_states.get(&schedule.component_id).copied() {
+                Some(from_state) => self.apply_transition(StateTransition {
+                    component_id: schedule.component_id,
+                    from_state,
+                    to_state: schedule.to_state,
+                    reason: schedule.reason,
+                    metadata: None,
+                    actor: schedule.actor,
+                }),
+                None => failure(schedule.component_id, None, "Component not found".to_string()),
+            };
+            fired.push(FiredTransition {
+                schedule_id: id,
+                result,
+            });
+        }
+        fired
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ComponentLifecycleBC {
+    /// Schedules an automatic transition
+    ///
+    /// # Arguments
+    /// * `schedule_json` - JSON ScheduledTransition, e.g.
+    ///   `{"component_id", "to_state", "trigger": {"type": "at", "at_ms"}}`
+    ///
+    /// # Returns
+    /// Schedule id, or 0 if the JSON is invalid or the component is unknown
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = scheduleTransition))]
+    pub fn schedule_transition_js(&mut self, schedule_json: &str) -> u32 {
+        parse_json::<ScheduledTransition>(schedule_json)
+            .and_then(|schedule| self.schedule_transition(schedule))
+            .unwrap_or(0)
+    }
+
+    /// Deprecates a component a number of days after its replacement is published
+    ///
+    /// # Arguments
+    /// * `actor_json` - Optional JSON Actor the deprecation is performed as
+    ///
+    /// # Returns
+    /// Schedule id, or 0 if the component is unknown or the actor JSON is invalid
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = scheduleDeprecationAfterReplacement))]
+    pub fn schedule_deprecation_after_replacement(
+        &mut self,
+        component_id: &str,
+        replacement_id: &str,
+        days: f64,
+        actor_json: Option<String>,
+    ) -> u32 {
+        let Ok(actor) = parse_actor(actor_json.as_deref()) else {
+            return 0;
+        };
+        self.schedule_transition(ScheduledTransition {
+            id: 0,
+            component_id: component_id.to_string(),
+            to_state: ComponentState::Deprecated,
+            trigger: ScheduleTrigger::AfterPublished {
+                component_id: replacement_id.to_string(),
+                delay_ms: days * MS_PER_DAY,
+            },
+            reason: Some(format!("Replaced by {}", replacement_id)),
+            actor,
+        })
+        .unwrap_or(0)
+    }
+
+    /// Cancels a scheduled transition
+    ///
+    /// # Returns
+    /// false if no schedule has that id
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = cancelScheduledTransition))]
+    pub fn cancel_scheduled_transition(&mut self, id: u32) -> bool {
+        self.schedules.remove(&id).is_some()
+    }
+
+    /// Lists pending schedules
+    ///
+    /// # Returns
+    /// JSON array of ScheduledTransition, by id
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getScheduledTransitions))]
+    pub fn get_scheduled_transitions(&self) -> String {
+        let schedules: Vec<&ScheduledTransition> = self.schedules.values().collect();
+        serde_json::to_string(&schedules).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Processes schedules that are due
+    ///
+    /// # Arguments
+    /// * `now_ms` - Current time in ms since the Unix epoch
+    ///
+    /// # Returns
+    /// JSON array of `{schedule_id, result}` for each schedule fired
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn tick(&mut self, now_ms: f64) -> String {
+        serde_json::to_string(&self.run_due_transitions(now_ms)).unwrap_or_else(|_| "[]".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_fixed_time_schedule() {
+        let mut bc = ComponentLifecycleBC::new();
+        bc.initialize_component("banner");
+        let events = Rc::new(RefCell::new(0));
+        let counter = events.clone();
+        bc.on_transition(move |_| *counter.borrow_mut() += 1);
+
+        let id = bc.schedule_transition_js(
+            r#"{"component_id":"banner","to_state":"design_complete","trigger":{"type":"at","at_ms":1000}}"#,
+        );
+        assert_ne!(id, 0);
+        assert_eq!(bc.schedule_transition_js(r#"{"component_id":"missing","to_state":"draft","trigger":{"type":"at","at_ms":0}}"#), 0);
+
+        assert!(bc.run_due_transitions(999.0).is_empty());
+        let fired = bc.run_due_transitions(1000.0);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].schedule_id, id);
+        assert!(fired[0].result.success);
+        assert_eq!(*events.borrow(), 1);
+        assert_eq!(bc.get_scheduled_transitions(), "[]");
+        assert!(!bc.cancel_scheduled_transition(id));
+    }
+
+    #[test]
+    fn test_deprecation_after_replacement() {
+        let mut bc = ComponentLifecycleBC::new();
+        bc.initialize_component("old-button");
+        bc.initialize_component("button");
+        let id = bc.schedule_deprecation_after_replacement("old-button", "button", 30.0, None);
+
+        let far_future = 4.0e12;
+        assert!(bc.run_due_transitions(far_future).is_empty(), "replacement not published");
+
+        bc.component_states
+            .insert("button".to_string(), ComponentState::Implemented);
+        bc.transition_component(
+            r#"{"component_id":"button","from_state":"implemented","to_state":"published"}"#,
+        );
+        let due = bc.due_time(&bc.schedules[&id].trigger).unwrap();
+        assert!(bc.run_due_transitions(due - 1.0).is_empty());
+
+        let fired = bc.run_due_transitions(due);
+        assert!(fired[0].result.success);
+        assert_eq!(bc.get_component_state("old-button"), "\"deprecated\"");
+        let entry = bc.history("old-button").unwrap().current_state().unwrap();
+        assert_eq!(
+            entry.metadata.as_ref().unwrap().reason.as_deref(),
+            Some("Replaced by button")
+        );
+    }
+}
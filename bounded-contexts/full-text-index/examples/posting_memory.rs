@@ -0,0 +1,75 @@
+//! Benchmark: posting list memory and search time for a 50k-document index
+//!
+//! Indexes generated documents (see `generate_document_set`) and compares
+//! the compressed posting lists with the same postings held as a node ID
+//! string per occurrence, the layout before compression:
+//!
+//! ```text
+//! cargo run --release --example posting_memory --features testing
+//! ```
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use full_text_index::{
+    generate_document_set, try_add_document, try_create_index, try_index_stats, try_search,
+    DocumentSpec, IndexConfig,
+};
+use serde_json::Value;
+
+const DOCUMENTS: usize = 50_000;
+const SEARCHES: u32 = 200;
+
+fn main() {
+    let config: IndexConfig =
+        serde_json::from_str(r#"{"index_id": "bench", "property_name": "name"}"#).unwrap();
+    try_create_index(config).unwrap();
+    let documents = generate_document_set(&DocumentSpec {
+        documents: DOCUMENTS,
+        words_per_document: 20,
+        vocabulary: 5000,
+        id_prefix: "node-".to_string(),
+        seed: 1,
+    })
+    .unwrap();
+
+    let start = Instant::now();
+    for document in &documents {
+        try_add_document("bench", document.node_id.clone(), document.content.clone()).unwrap();
+    }
+    let indexing = start.elapsed();
+
+    let stats = try_index_stats("bench").unwrap();
+    let number = |field: &str| stats[field].as_u64().unwrap();
+    let (compressed, strings) = (number("postingBytes"), number("stringPostingBytes"));
+    println!(
+        "{} documents, {} terms, {} postings, indexed in {:.0} ms",
+        number("documents"),
+        number("terms"),
+        number("postings"),
+        indexing.as_secs_f64() * 1000.0
+    );
+    println!("{:<24} {:>12}", "posting lists", "bytes");
+    println!("{:<24} {:>12}", "node ID strings", strings);
+    println!("{:<24} {:>12}", "compressed", compressed);
+    println!(
+        "{:<24} {:>11.1}x",
+        "reduction",
+        strings as f64 / compressed as f64
+    );
+
+    // "baba" is the generator's most common word, with the longest list
+    for query in ["baba", "bako ritesu"] {
+        let start = Instant::now();
+        let mut results = Value::Null;
+        for _ in 0..SEARCHES {
+            results = black_box(try_search("bench", black_box(query)).unwrap());
+        }
+        println!(
+            "search {:<14} {:>9.3} ms, {} results",
+            format!("{:?}", query),
+            start.elapsed().as_secs_f64() * 1000.0 / SEARCHES as f64,
+            results["results"].as_array().unwrap().len()
+        );
+    }
+}
@@ -8,6 +8,9 @@
  * - Case-sensitive/insensitive search
  * - Configurable result limits
  * 
+ * Calls the `*_value` exports, which return response objects directly
+ * instead of JSON strings.
+ *
  * Performance targets:
  * - Index creation: < 5ms for 1000 documents
  * - Search: < 10ms for 10000 documents
@@ -17,6 +20,7 @@
  */
 
 import { loadWasmModule } from '../wasm-bridge/wasm-bridge.js';
+import { ErrorCode } from '../../harmony-error/harmony-error.js';
 
 /** @type {WebAssembly.Instance | null} */
 let wasmInstance = null;
@@ -51,7 +55,7 @@ async function initWasm() {
 /**
  * Create a new full-text index
  * @param {IndexConfig} config - Index configuration
- * @returns {Promise<{success: boolean, indexId?: string, error?: string}>}
+ * @returns {Promise<{success: boolean, indexId?: string, error?: string, code?: string}>}
  */
 export async function createIndex(config) {
   await initWasm();
@@ -59,13 +63,12 @@ export async function createIndex(config) {
   if (!config.indexId || !config.propertyName) {
     return {
       success: false,
-      error: 'indexId and propertyName are required'
+      error: 'indexId and propertyName are required',
+      code: ErrorCode.INVALID_INPUT
     };
   }
 
-  const configJson = JSON.stringify(config);
-  const resultJson = wasmInstance.exports.create_index(configJson);
-  return JSON.parse(resultJson);
+  return wasmInstance.exports.create_index_value(config);
 }
 
 /**
@@ -73,7 +76,7 @@ export async function createIndex(config) {
  * @param {string} indexId - Index identifier
  * @param {string} nodeId - Node identifier
  * @param {string} content - Text content to index
- * @returns {Promise<{success: boolean, nodeId?: string, tokenCount?: number, error?: string}>}
+ * @returns {Promise<{success: boolean, nodeId?: string, tokenCount?: number, error?: string, code?: string}>}
  */
 export async function addDocument(indexId, nodeId, content) {
   await initWasm();
@@ -81,19 +84,19 @@ export async function addDocument(indexId, nodeId, content) {
   if (!indexId || !nodeId || content === undefined) {
     return {
       success: false,
-      error: 'indexId, nodeId, and content are required'
+      error: 'indexId, nodeId, and content are required',
+      code: ErrorCode.INVALID_INPUT
     };
   }
 
-  const resultJson = wasmInstance.exports.add_document(indexId, nodeId, content);
-  return JSON.parse(resultJson);
+  return wasmInstance.exports.add_document_value(indexId, nodeId, content);
 }
 
 /**
  * Remove a document from the index
  * @param {string} indexId - Index identifier
  * @param {string} nodeId - Node identifier
- * @returns {Promise<{success: boolean, nodeId?: string, error?: string}>}
+ * @returns {Promise<{success: boolean, nodeId?: string, error?: string, code?: string}>}
  */
 export async function removeDocument(indexId, nodeId) {
   await initWasm();
@@ -101,19 +104,19 @@ export async function removeDocument(indexId, nodeId) {
   if (!indexId || !nodeId) {
     return {
       success: false,
-      error: 'indexId and nodeId are required'
+      error: 'indexId and nodeId are required',
+      code: ErrorCode.INVALID_INPUT
     };
   }
 
-  const resultJson = wasmInstance.exports.remove_document(indexId, nodeId);
-  return JSON.parse(resultJson);
+  return wasmInstance.exports.remove_document_value(indexId, nodeId);
 }
 
 /**
  * Search the index
  * @param {string} indexId - Index identifier
  * @param {string} query - Search query
- * @returns {Promise<{success: boolean, results?: SearchResult[], queryTokens?: string[], error?: string}>}
+ * @returns {Promise<{success: boolean, results?: SearchResult[], queryTokens?: string[], error?: string, code?: string}>}
  */
 export async function search(indexId, query) {
   await initWasm();
@@ -121,18 +124,18 @@ export async function search(indexId, query) {
   if (!indexId || !query) {
     return {
       success: false,
-      error: 'indexId and query are required'
+      error: 'indexId and query are required',
+      code: ErrorCode.INVALID_INPUT
     };
   }
 
-  const resultJson = wasmInstance.exports.search(indexId, query);
-  return JSON.parse(resultJson);
+  return wasmInstance.exports.search_value(indexId, query);
 }
 
 /**
  * Clear all documents from the index
  * @param {string} indexId - Index identifier
- * @returns {Promise<{success: boolean, indexId?: string, error?: string}>}
+ * @returns {Promise<{success: boolean, indexId?: string, error?: string, code?: string}>}
  */
 export async function clearIndex(indexId) {
   await initWasm();
@@ -140,12 +143,32 @@ export async function clearIndex(indexId) {
   if (!indexId) {
     return {
       success: false,
-      error: 'indexId is required'
+      error: 'indexId is required',
+      code: ErrorCode.INVALID_INPUT
     };
   }
 
-  const resultJson = wasmInstance.exports.clear_index(indexId);
-  return JSON.parse(resultJson);
+  return wasmInstance.exports.clear_index_value(indexId);
+}
+
+/**
+ * Reserve room for more documents before a bulk import
+ * @param {string} indexId - Index identifier
+ * @param {number} documentCount - Number of documents about to be added
+ * @returns {Promise<{success: boolean, indexId?: string, reserved?: number, error?: string, code?: string}>}
+ */
+export async function reserve(indexId, documentCount) {
+  await initWasm();
+
+  if (!indexId || !Number.isInteger(documentCount) || documentCount < 0) {
+    return {
+      success: false,
+      error: 'indexId and a non-negative documentCount are required',
+      code: ErrorCode.INVALID_INPUT
+    };
+  }
+
+  return wasmInstance.exports.reserve_value(indexId, documentCount);
 }
 
 /**
@@ -161,6 +184,11 @@ export async function batchAddDocuments(indexId, documents) {
   let failed = 0;
   const errors = [];
 
+  const reserved = await reserve(indexId, documents.length);
+  if (!reserved.success) {
+    return { success: false, added, failed: documents.length, errors: [reserved.error] };
+  }
+
   for (const doc of documents) {
     const result = await addDocument(indexId, doc.nodeId, doc.content);
     if (result.success) {
@@ -180,15 +208,94 @@ export async function batchAddDocuments(indexId, documents) {
 }
 
 /**
- * Get index statistics
+ * Capture every index's config and documents, for `importIndices`
+ * @returns {Promise<{success: boolean, indices?: Array<{config: Object, documents: Array<{node_id: string, content: string}>}>, error?: string, code?: string}>}
+ */
+export async function exportIndices() {
+  await initWasm();
+
+  return wasmInstance.exports.export_indices_value();
+}
+
+/**
+ * Replace every index with ones saved by `exportIndices`, re-tokenizing
+ * the documents. Not atomic: on failure, the indices imported so far stay.
+ * @param {Array<{config: Object, documents: Array<{node_id: string, content: string}>}>} snapshots - Saved indices
+ * @returns {Promise<{success: boolean, indices?: number, error?: string, code?: string}>}
+ */
+export async function importIndices(snapshots) {
+  await initWasm();
+
+  const current = await exportIndices();
+  if (!current.success) {
+    return current;
+  }
+  for (const { config } of current.indices) {
+    wasmInstance.exports.delete_index_value(config.index_id);
+  }
+
+  for (const { config, documents } of snapshots) {
+    const steps = [
+      () => wasmInstance.exports.create_index_value(config),
+      () => wasmInstance.exports.reserve_value(config.index_id, documents.length),
+      ...documents.map(doc => () => wasmInstance.exports.add_document_value(
+        config.index_id, doc.node_id, doc.content
+      ))
+    ];
+    for (const step of steps) {
+      const result = step();
+      if (!result.success) {
+        return result;
+      }
+    }
+  }
+
+  return { success: true, indices: snapshots.length };
+}
+
+/**
+ * Explain how a query is scored, to debug rankings
+ * @param {string} indexId - Index identifier
+ * @param {string} query - Search query
+ * @returns {Promise<{success: boolean, indexId?: string, queryTokens?: string[], filters?: Object, documents?: number, terms?: Array<Object>, matchedDocuments?: number, results?: Array<Object>, error?: string, code?: string}>}
+ *   `filters` holds the tokenizer settings, the query tokens dropped as
+ *   shorter than `minTokenLength`, and `maxResults`. Each of `terms` is
+ *   `{term, document_frequency, occurrences, idf}`; each of `results` is
+ *   `{node_id, rank, score, contributions}`, in `search` order, with the
+ *   score each term contributed
+ */
+export async function explain(indexId, query) {
+  await initWasm();
+
+  if (!indexId || !query) {
+    return {
+      success: false,
+      error: 'indexId and query are required',
+      code: ErrorCode.INVALID_INPUT
+    };
+  }
+
+  return wasmInstance.exports.explain_value(indexId, query);
+}
+
+/**
+ * Get index statistics, including posting list memory
  * @param {string} indexId - Index identifier
- * @returns {Promise<{exists: boolean, documentCount?: number, tokenCount?: number}>}
+ * @returns {Promise<{success: boolean, indexId?: string, documents?: number, terms?: number, postings?: number, postingBytes?: number, stringPostingBytes?: number, error?: string, code?: string}>}
+ *   `postings` counts (term, document) pairs; `postingBytes` is their
+ *   compressed size and `stringPostingBytes` their size as a node ID string
+ *   per occurrence
  */
 export async function getIndexStats(indexId) {
-  // This is a placeholder - actual implementation would need to be added to Rust
-  return {
-    exists: true,
-    documentCount: 0,
-    tokenCount: 0
-  };
-}
\ No newline at end of file
+  await initWasm();
+
+  if (!indexId) {
+    return {
+      success: false,
+      error: 'indexId is required',
+      code: ErrorCode.INVALID_INPUT
+    };
+  }
+
+  return wasmInstance.exports.index_stats_value(indexId);
+}
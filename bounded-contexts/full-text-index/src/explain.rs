@@ -0,0 +1,230 @@
+//! Query explanation, for debugging why a document ranks where it does
+//!
+//! `explain` runs a search the way `search` does and reports each step:
+//! the tokens the query became and the ones the index's filters dropped,
+//! how common each remaining term is, and for every returned document the
+//! score each term contributed. Contributions add up to the document's
+//! `search` score.
+//!
+//! A document scores `term_frequency² · idf` per query term, with
+//! `idf = ln(documents / occurrences)` counting every occurrence of the
+//! term, so a term repeated within a document weighs more than linearly.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#full-text-index
+
+use harmony_error::HarmonyError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::respond_value;
+use crate::{respond, respond_encoded, split_tokens, with_index};
+
+/// How common a query term is in the index
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TermStats {
+    pub term: String,
+    /// Documents containing the term
+    pub document_frequency: usize,
+    /// Occurrences of the term over all documents
+    pub occurrences: usize,
+    /// Inverse document frequency; null for a term no document contains
+    pub idf: Option<f64>,
+}
+
+/// One query term's part of a document's score
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TermContribution {
+    pub term: String,
+    /// Occurrences of the term in the document
+    pub term_frequency: u32,
+    pub idf: f64,
+    /// `term_frequency² · idf`
+    pub score: f64,
+}
+
+/// A returned document with its score broken down by term
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExplainedResult {
+    pub node_id: String,
+    /// Position in the results, from 1
+    pub rank: usize,
+    pub score: f64,
+    pub contributions: Vec<TermContribution>,
+}
+
+/// See `explain`
+pub fn try_explain(index_id: &str, query: &str) -> Result<Value, HarmonyError> {
+    with_index(index_id, |config, index| {
+        let (query_tokens, dropped): (Vec<String>, Vec<String>) = split_tokens(query, config)
+            .into_iter()
+            .partition(|token| token.len() >= config.min_token_length);
+
+        let mut terms: Vec<TermStats> = Vec::new();
+        for token in &query_tokens {
+            if terms.iter().any(|stats| &stats.term == token) {
+                continue;
+            }
+            let postings = index.token_to_nodes.get(token);
+            terms.push(TermStats {
+                term: token.clone(),
+                document_frequency: postings.map_or(0, |postings| postings.len()),
+                occurrences: postings.map_or(0, |postings| postings.occurrences()),
+                idf: postings.map(|postings| index.idf(postings)),
+            });
+        }
+
+        let mut ranked = index.rank(&query_tokens);
+        let matched = ranked.len();
+        ranked.truncate(config.max_results);
+        let results: Vec<ExplainedResult> = harmony_ids::with_node_ids(|ids| {
+            ranked
+                .into_iter()
+                .enumerate()
+                .filter_map(|(position, (id, score, contributions))| {
+                    Some(ExplainedResult {
+                        node_id: ids.name(id)?.to_string(),
+                        rank: position + 1,
+                        score,
+                        contributions,
+                    })
+                })
+                .collect()
+        });
+
+        json!({
+            "indexId": index_id,
+            "queryTokens": query_tokens,
+            "filters": {
+                "tokenizer": config.tokenizer,
+                "caseSensitive": config.case_sensitive,
+                "minTokenLength": config.min_token_length,
+                "droppedTokens": dropped,
+                "maxResults": config.max_results
+            },
+            "documents": index.node_to_content.len(),
+            "terms": terms,
+            "matchedDocuments": matched,
+            "results": results
+        })
+    })
+}
+
+/// How a query is tokenized, filtered and scored, to debug rankings
+///
+/// # Returns
+/// `{"success": true, "indexId", "queryTokens", "filters", "documents",
+/// "terms", "matchedDocuments", "results"}`: `filters` holds the tokenizer
+/// settings, the query tokens shorter than `minTokenLength` that were
+/// dropped, and `maxResults`, which cuts `matchedDocuments` down to
+/// `results`; `terms` are TermStats and `results` ExplainedResults, in
+/// `search` order
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn explain(index_id: String, query: String) -> String {
+    respond(try_explain(&index_id, &query))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn explain_encoded(
+    index_id: String,
+    query: String,
+    format: &str,
+) -> Result<Vec<u8>, HarmonyError> {
+    respond_encoded(try_explain(&index_id, &query), format)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(
+    unchecked_return_type = "{ success: boolean; indexId?: string; queryTokens?: string[]; filters?: { tokenizer: string; caseSensitive: boolean; minTokenLength: number; droppedTokens: string[]; maxResults: number }; documents?: number; terms?: TermStats[]; matchedDocuments?: number; results?: ExplainedResult[]; error?: string; code?: string }"
+)]
+pub fn explain_value(index_id: String, query: String) -> JsValue {
+    respond_value(try_explain(&index_id, &query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{add_document, create_index, search};
+    use harmony_error::wire;
+
+    fn response(json: String) -> Value {
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_explain() {
+        create_index(
+            r#"{"index_id": "explained", "property_name": "name", "min_token_length": 3, "max_results": 2}"#
+                .to_string(),
+        );
+        for (id, content) in [
+            ("lp", "Low pass filter filter"),
+            ("hp", "High pass filter"),
+            ("bp", "Band pass"),
+            ("eq", "Parametric EQ"),
+        ] {
+            add_document("explained".to_string(), id.to_string(), content.to_string());
+        }
+
+        let explained = response(explain(
+            "explained".to_string(),
+            "Pass filter of reverb".to_string(),
+        ));
+        assert_eq!(
+            explained["queryTokens"],
+            json!(["pass", "filter", "reverb"])
+        );
+        assert_eq!(explained["filters"]["droppedTokens"], json!(["of"]));
+        assert_eq!(explained["filters"]["maxResults"], 2);
+        assert_eq!(
+            (
+                explained["documents"].clone(),
+                explained["matchedDocuments"].clone()
+            ),
+            (json!(4), json!(3))
+        );
+
+        let terms: Vec<TermStats> = serde_json::from_value(explained["terms"].clone()).unwrap();
+        assert_eq!((terms[0].document_frequency, terms[0].occurrences), (3, 3));
+        assert_eq!((terms[1].document_frequency, terms[1].occurrences), (2, 3));
+        assert_eq!((terms[2].document_frequency, terms[2].idf), (0, None));
+
+        // Same order and scores as `search`, with scores split by term
+        let results: Vec<ExplainedResult> =
+            serde_json::from_value(explained["results"].clone()).unwrap();
+        let found = response(search(
+            "explained".to_string(),
+            "Pass filter of reverb".to_string(),
+        ));
+        assert_eq!(results.len(), 2);
+        for (result, found) in results.iter().zip(found["results"].as_array().unwrap()) {
+            assert_eq!(result.node_id, found["node_id"]);
+            assert!((result.score - found["score"].as_f64().unwrap()).abs() < 1e-12);
+            let total: f64 = result.contributions.iter().map(|part| part.score).sum();
+            assert!((total - result.score).abs() < 1e-12);
+        }
+        assert_eq!((results[0].node_id.as_str(), results[0].rank), ("lp", 1));
+        let filter = &results[0].contributions[1];
+        assert_eq!((filter.term.as_str(), filter.term_frequency), ("filter", 2));
+        assert!((filter.score - 4.0 * (4.0f64 / 3.0).ln()).abs() < 1e-12);
+
+        let missing = response(explain("none".to_string(), "pass".to_string()));
+        assert_eq!(missing["code"], "not_found");
+        let encoded: Value = wire::decode_as(
+            &explain_encoded("explained".to_string(), "pass".to_string(), "cbor").unwrap(),
+            "cbor",
+        )
+        .unwrap();
+        assert_eq!(
+            encoded,
+            response(explain("explained".to_string(), "pass".to_string()))
+        );
+    }
+}
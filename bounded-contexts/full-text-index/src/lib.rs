@@ -1,18 +1,55 @@
+use harmony_error::{limits, wire, HarmonyError, ResultExt};
+use harmony_trace::Level;
+#[cfg(feature = "threads")]
+use rayon::prelude::*;
+#[cfg(feature = "wasm")]
+use serde_wasm_bindgen::Serializer;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, TryReserveError};
+
+mod explain;
+mod postings;
+#[cfg(feature = "testing")]
+mod testing;
+pub use explain::{
+    explain, explain_encoded, try_explain, ExplainedResult, TermContribution, TermStats,
+};
+#[cfg(feature = "wasm")]
+pub use explain::explain_value;
+use postings::PostingList;
+#[cfg(feature = "testing")]
+pub use testing::{
+    add_generated_documents, generate_document_set, generate_documents,
+    try_add_generated_documents, try_generate_documents, DocumentSpec,
+};
+
+/// Posting list length from which the `threads` feature decodes term
+/// frequencies in parallel
+#[cfg(feature = "threads")]
+const PARALLEL_POSTINGS_MIN: usize = 512;
+
+#[cfg_attr(feature = "wasm", derive(Tsify))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexConfig {
     pub index_id: String,
     pub property_name: String,
     #[serde(default = "default_tokenizer")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
     pub tokenizer: String,
     #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
     pub case_sensitive: bool,
     #[serde(default = "default_min_token_length")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
     pub min_token_length: usize,
     #[serde(default = "default_max_results")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
     pub max_results: usize,
 }
 
@@ -28,6 +65,7 @@ fn default_max_results() -> usize {
     100
 }
 
+#[cfg_attr(feature = "wasm", derive(Tsify))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     pub node_id: String,
@@ -35,9 +73,28 @@ pub struct SearchResult {
     pub matches: Vec<String>,
 }
 
+/// A document's content, as indexed
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedDocument {
+    pub node_id: String,
+    pub content: String,
+}
+
+/// An index's config and documents, enough to rebuild it
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexSnapshot {
+    pub config: IndexConfig,
+    pub documents: Vec<IndexedDocument>,
+}
+
+/// Documents are keyed by their harmony-ids node IDs in the posting lists,
+/// and by node ID string elsewhere
 #[derive(Debug, Clone)]
 struct InvertedIndex {
-    token_to_nodes: HashMap<String, Vec<String>>,
+    token_to_nodes: HashMap<String, PostingList>,
+    /// Distinct tokens of each document, for removing its postings
     node_to_tokens: HashMap<String, Vec<String>>,
     node_to_content: HashMap<String, String>,
 }
@@ -51,31 +108,45 @@ impl InvertedIndex {
         }
     }
 
-    fn add_document(&mut self, node_id: String, tokens: Vec<String>, content: String) {
+    /// Reserve room for `docs` more documents; distinct tokens can't be
+    /// known up front, so only the per-document maps are grown
+    fn reserve(&mut self, docs: usize) -> Result<(), TryReserveError> {
+        self.node_to_tokens.try_reserve(docs)?;
+        self.node_to_content.try_reserve(docs)
+    }
+
+    /// Indexes a document, replacing any with the same node ID; the ID is
+    /// interned into the module's node ID map (harmony-ids)
+    fn add_document(&mut self, node_id: String, tokens: Vec<String>, content: String) -> Result<(), HarmonyError> {
+        let id = harmony_ids::intern(&node_id)?;
         // Remove existing document if present
         self.remove_document(&node_id);
 
-        // Store content
-        self.node_to_content.insert(node_id.clone(), content);
-
-        // Store tokens for this node
-        self.node_to_tokens.insert(node_id.clone(), tokens.clone());
-
-        // Update inverted index
+        let mut counts: BTreeMap<String, u32> = BTreeMap::new();
         for token in tokens {
-            self.token_to_nodes
-                .entry(token)
-                .or_insert_with(Vec::new)
-                .push(node_id.clone());
+            *counts.entry(token).or_default() += 1;
         }
+        for (token, &count) in &counts {
+            match self.token_to_nodes.get_mut(token) {
+                Some(postings) => postings.insert(id, count),
+                None => {
+                    let mut postings = PostingList::default();
+                    postings.insert(id, count);
+                    self.token_to_nodes.insert(token.clone(), postings);
+                }
+            }
+        }
+        self.node_to_tokens.insert(node_id.clone(), counts.into_keys().collect());
+        self.node_to_content.insert(node_id, content);
+        Ok(())
     }
 
     fn remove_document(&mut self, node_id: &str) {
-        if let Some(tokens) = self.node_to_tokens.remove(node_id) {
+        if let (Some(tokens), Some(id)) = (self.node_to_tokens.remove(node_id), harmony_ids::id(node_id)) {
             for token in tokens {
-                if let Some(nodes) = self.token_to_nodes.get_mut(&token) {
-                    nodes.retain(|id| id != node_id);
-                    if nodes.is_empty() {
+                if let Some(postings) = self.token_to_nodes.get_mut(&token) {
+                    postings.remove(id);
+                    if postings.is_empty() {
                         self.token_to_nodes.remove(&token);
                     }
                 }
@@ -84,43 +155,111 @@ impl InvertedIndex {
         self.node_to_content.remove(node_id);
     }
 
-    fn search(&self, query_tokens: &[String], max_results: usize) -> Vec<SearchResult> {
-        let mut node_scores: HashMap<String, (f64, Vec<String>)> = HashMap::new();
+    /// (node ID, occurrences) of every document in `postings`
+    ///
+    /// Decoded a block per task on the rayon pool for long posting lists
+    /// with the `threads` feature.
+    fn term_frequencies(&self, postings: &PostingList) -> Vec<(u32, u32)> {
+        #[cfg(feature = "threads")]
+        if postings.len() >= PARALLEL_POSTINGS_MIN {
+            return (0..postings.block_count())
+                .into_par_iter()
+                .flat_map_iter(|block| postings.block(block))
+                .collect();
+        }
+        postings.iter().collect()
+    }
 
-        // Calculate TF-IDF-like scores
-        let total_docs = self.node_to_content.len() as f64;
+    /// Inverse document frequency of a term; over occurrences, see `rank`
+    fn idf(&self, postings: &PostingList) -> f64 {
+        (self.node_to_content.len() as f64 / postings.occurrences() as f64).ln()
+    }
+
+    /// Documents matching `query_tokens`, best first, with each query
+    /// token's part of their score
+    fn rank(&self, query_tokens: &[String]) -> Vec<(u32, f64, Vec<TermContribution>)> {
+        let mut node_scores: HashMap<u32, (f64, Vec<TermContribution>)> = HashMap::new();
 
+        // Calculate TF-IDF-like scores
         for query_token in query_tokens {
-            if let Some(matching_nodes) = self.token_to_nodes.get(query_token) {
-                let idf = (total_docs / matching_nodes.len() as f64).ln();
-
-                for node_id in matching_nodes {
-                    let entry = node_scores.entry(node_id.clone()).or_insert((0.0, Vec::new()));
-                    
-                    // Calculate term frequency
-                    let node_tokens = self.node_to_tokens.get(node_id).unwrap();
-                    let tf = node_tokens.iter().filter(|t| *t == query_token).count() as f64;
-                    
-                    entry.0 += tf * idf;
-                    entry.1.push(query_token.clone());
+            if let Some(postings) = self.token_to_nodes.get(query_token) {
+                // Each occurrence scores tf * idf, with idf over occurrences,
+                // as when postings listed a document once per occurrence
+                let idf = self.idf(postings);
+
+                for (id, tf) in self.term_frequencies(postings) {
+                    let entry = node_scores.entry(id).or_insert((0.0, Vec::new()));
+                    let score = (tf as f64) * (tf as f64) * idf;
+                    entry.0 += score;
+                    entry.1.push(TermContribution {
+                        term: query_token.clone(),
+                        term_frequency: tf,
+                        idf,
+                        score,
+                    });
                 }
             }
         }
 
-        // Convert to results and sort by score
-        let mut results: Vec<SearchResult> = node_scores
+        let mut ranked: Vec<(u32, f64, Vec<TermContribution>)> = node_scores
             .into_iter()
-            .map(|(node_id, (score, matches))| SearchResult {
-                node_id,
-                score,
-                matches,
-            })
+            .map(|(id, (score, contributions))| (id, score, contributions))
             .collect();
+        // Ties go to the lower node ID, so `search` and `explain` agree
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+        ranked
+    }
 
-        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-        results.truncate(max_results);
+    fn search(&self, query_tokens: &[String], max_results: usize) -> Vec<SearchResult> {
+        let mut ranked = self.rank(query_tokens);
+        ranked.truncate(max_results);
+
+        harmony_ids::with_node_ids(|ids| {
+            ranked
+                .into_iter()
+                .filter_map(|(id, score, contributions)| {
+                    let matches = contributions
+                        .iter()
+                        .flat_map(|part| std::iter::repeat_n(&part.term, part.term_frequency as usize))
+                        .cloned()
+                        .collect();
+                    Some(SearchResult {
+                        node_id: ids.name(id)?.to_string(),
+                        score,
+                        matches,
+                    })
+                })
+                .collect()
+        })
+    }
 
-        results
+    /// Posting list sizes, compressed and as they would be with a node ID
+    /// string per occurrence
+    fn posting_stats(&self) -> Value {
+        let postings: usize = self.token_to_nodes.values().map(PostingList::len).sum();
+        let posting_bytes: usize = self.token_to_nodes.values().map(PostingList::heap_size).sum();
+        let string_bytes: usize = harmony_ids::with_node_ids(|ids| {
+            self.token_to_nodes
+                .values()
+                .map(|list| {
+                    let strings: usize = list
+                        .iter()
+                        .map(|(id, count)| {
+                            let name = ids.name(id).map_or(0, str::len);
+                            count as usize * (std::mem::size_of::<String>() + name)
+                        })
+                        .sum();
+                    std::mem::size_of::<Vec<String>>() + strings
+                })
+                .sum()
+        });
+        json!({
+            "documents": self.node_to_content.len(),
+            "terms": self.token_to_nodes.len(),
+            "postings": postings,
+            "postingBytes": posting_bytes,
+            "stringPostingBytes": string_bytes
+        })
     }
 
     fn clear(&mut self) {
@@ -130,26 +269,79 @@ impl InvertedIndex {
     }
 }
 
-// Global state for indices
-static mut INDICES: Option<HashMap<String, (IndexConfig, InvertedIndex)>> = None;
+thread_local! {
+    // Global state for indices. Per thread: with wasm threads each worker
+    // has its own indices, and the rayon pool only reads the index a search
+    // hands it
+    static INDICES: RefCell<HashMap<String, (IndexConfig, InvertedIndex)>> =
+        RefCell::new(HashMap::new());
+}
 
-fn get_indices() -> &'static mut HashMap<String, (IndexConfig, InvertedIndex)> {
-    unsafe {
-        if INDICES.is_none() {
-            INDICES = Some(HashMap::new());
+/// Runs `f` on an index, or fails with `not_found`
+fn with_index<T>(
+    index_id: &str,
+    f: impl FnOnce(&IndexConfig, &mut InvertedIndex) -> T,
+) -> Result<T, HarmonyError> {
+    INDICES.with(|indices| {
+        let mut indices = indices.borrow_mut();
+        let (config, index) = indices
+            .get_mut(index_id)
+            .ok_or_else(|| HarmonyError::NotFound(format!("Index not found: {}", index_id)))?;
+        Ok(f(config, index))
+    })
+}
+
+/// Builds the response: the fields of `result` with `"success": true`,
+/// or `"success": false` with the error message and its HarmonyError code
+fn response(result: Result<Value, HarmonyError>) -> Value {
+    match result {
+        Ok(mut response) => {
+            response["success"] = Value::Bool(true);
+            response
         }
-        INDICES.as_mut().unwrap()
+        Err(error) => json!({
+            "success": false,
+            "error": error.to_string(),
+            "code": error.code(),
+        }),
     }
 }
 
+/// The response as a JSON string
+fn respond(result: Result<Value, HarmonyError>) -> String {
+    response(result).to_string()
+}
+
+/// The response encoded in a wire format; an error only for an unknown format
+fn respond_encoded(result: Result<Value, HarmonyError>, format: &str) -> Result<Vec<u8>, HarmonyError> {
+    wire::encode_as(&response(result), format)
+}
+
+/// The response as a JS object, without a JSON round trip
+#[cfg(feature = "wasm")]
+fn respond_value(result: Result<Value, HarmonyError>) -> JsValue {
+    response(result)
+        .serialize(&Serializer::json_compatible())
+        .unwrap_or(JsValue::NULL)
+}
+
 fn tokenize(text: &str, config: &IndexConfig) -> Vec<String> {
+    // Filter by minimum length
+    split_tokens(text, config)
+        .into_iter()
+        .filter(|t| t.len() >= config.min_token_length)
+        .collect()
+}
+
+/// Tokens of `text` before the `min_token_length` filter
+fn split_tokens(text: &str, config: &IndexConfig) -> Vec<String> {
     let normalized = if config.case_sensitive {
         text.to_string()
     } else {
         text.to_lowercase()
     };
 
-    let tokens: Vec<String> = match config.tokenizer.as_str() {
+    match config.tokenizer.as_str() {
         "whitespace" => normalized
             .split_whitespace()
             .map(|s| s.to_string())
@@ -189,135 +381,504 @@ fn tokenize(text: &str, config: &IndexConfig) -> Vec<String> {
             .split_whitespace()
             .map(|s| s.to_string())
             .collect(),
-    };
+    }
+}
 
-    // Filter by minimum length
-    tokens
-        .into_iter()
-        .filter(|t| t.len() >= config.min_token_length)
-        .collect()
+// The operations behind the exports below, returning the response fields
+// on success; hosts calling the index directly (worker dispatchers) use these
+
+pub fn try_create_index(config: IndexConfig) -> Result<Value, HarmonyError> {
+    let index_id = config.index_id.clone();
+    INDICES.with(|indices| {
+        indices
+            .borrow_mut()
+            .insert(index_id.clone(), (config, InvertedIndex::new()))
+    });
+    Ok(json!({ "indexId": index_id }))
 }
 
-#[wasm_bindgen]
-pub fn create_index(config_json: String) -> String {
-    let config: IndexConfig = match serde_json::from_str(&config_json) {
-        Ok(c) => c,
-        Err(e) => {
-            return serde_json::json!({
-                "success": false,
-                "error": format!("Invalid config: {}", e)
+pub fn try_add_document(index_id: &str, node_id: String, content: String) -> Result<Value, HarmonyError> {
+    with_index(index_id, |config, index| {
+        let tokens = tokenize(&content, config);
+        let token_count = tokens.len();
+        let max_tokens = limits::limits().max_tokens_per_document;
+        limits::check("Document token count", token_count, max_tokens)
+            .with_context(|| format!("Document {}", node_id))?;
+        index.add_document(node_id.clone(), tokens, content)?;
+        Ok(json!({
+            "nodeId": node_id,
+            "tokenCount": token_count
+        }))
+    })?
+}
+
+pub fn try_reserve(index_id: &str, docs: usize) -> Result<Value, HarmonyError> {
+    with_index(index_id, |_config, index| {
+        index.reserve(docs).map_err(|error| {
+            HarmonyError::CapacityExceeded(format!("Cannot reserve {} documents: {}", docs, error))
+        })?;
+        Ok(json!({
+            "indexId": index_id,
+            "reserved": docs
+        }))
+    })?
+}
+
+/// Every index, sorted by index id, with documents sorted by node id
+pub fn try_export_indices() -> Result<Value, HarmonyError> {
+    let mut snapshots: Vec<IndexSnapshot> = INDICES.with(|indices| {
+        indices
+            .borrow()
+            .values()
+            .map(|(config, index)| {
+                let mut documents: Vec<IndexedDocument> = index
+                    .node_to_content
+                    .iter()
+                    .map(|(node_id, content)| IndexedDocument {
+                        node_id: node_id.clone(),
+                        content: content.clone(),
+                    })
+                    .collect();
+                documents.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+                IndexSnapshot {
+                    config: config.clone(),
+                    documents,
+                }
             })
-            .to_string();
-        }
-    };
+            .collect()
+    });
+    snapshots.sort_by(|a, b| a.config.index_id.cmp(&b.config.index_id));
+    Ok(json!({ "indices": snapshots }))
+}
 
-    let indices = get_indices();
-    let index = InvertedIndex::new();
-    indices.insert(config.index_id.clone(), (config.clone(), index));
+pub fn try_delete_index(index_id: &str) -> Result<Value, HarmonyError> {
+    INDICES
+        .with(|indices| indices.borrow_mut().remove(index_id))
+        .ok_or_else(|| HarmonyError::NotFound(format!("Index not found: {}", index_id)))?;
+    Ok(json!({ "indexId": index_id }))
+}
 
-    serde_json::json!({
-        "success": true,
-        "indexId": config.index_id
+pub fn try_remove_document(index_id: &str, node_id: String) -> Result<Value, HarmonyError> {
+    with_index(index_id, |_config, index| {
+        index.remove_document(&node_id);
+        json!({ "nodeId": node_id })
     })
-    .to_string()
 }
 
-#[wasm_bindgen]
-pub fn add_document(index_id: String, node_id: String, content: String) -> String {
-    let indices = get_indices();
-
-    let (config, index) = match indices.get_mut(&index_id) {
-        Some(entry) => entry,
-        None => {
-            return serde_json::json!({
-                "success": false,
-                "error": "Index not found"
-            })
-            .to_string();
-        }
-    };
+pub fn try_search(index_id: &str, query: &str) -> Result<Value, HarmonyError> {
+    let mut span = harmony_trace::span(Level::Debug, "full-text-index", "search");
+    span.field("index", index_id);
+    with_index(index_id, |config, index| {
+        let query_tokens = tokenize(query, config);
+        let results = index.search(&query_tokens, config.max_results);
+        span.field("tokens", query_tokens.len())
+            .field("results", results.len());
+        json!({
+            "results": results,
+            "queryTokens": query_tokens
+        })
+    })
+}
 
-    let tokens = tokenize(&content, config);
-    index.add_document(node_id.clone(), tokens.clone(), content);
+/// Posting list memory of an index; see `index_stats`
+pub fn try_index_stats(index_id: &str) -> Result<Value, HarmonyError> {
+    with_index(index_id, |_config, index| {
+        let mut stats = index.posting_stats();
+        stats["indexId"] = json!(index_id);
+        stats
+    })
+}
 
-    serde_json::json!({
-        "success": true,
-        "nodeId": node_id,
-        "tokenCount": tokens.len()
+pub fn try_clear_index(index_id: &str) -> Result<Value, HarmonyError> {
+    with_index(index_id, |_config, index| {
+        index.clear();
+        json!({ "indexId": index_id })
     })
-    .to_string()
 }
 
-#[wasm_bindgen]
-pub fn remove_document(index_id: String, node_id: String) -> String {
-    let indices = get_indices();
-
-    let (_config, index) = match indices.get_mut(&index_id) {
-        Some(entry) => entry,
-        None => {
-            return serde_json::json!({
-                "success": false,
-                "error": "Index not found"
-            })
-            .to_string();
-        }
-    };
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn create_index(config_json: String) -> String {
+    respond(
+        limits::check_json(&config_json)
+            .and_then(|()| Ok(serde_json::from_str(&config_json)?))
+            .context("Invalid config")
+            .and_then(try_create_index),
+    )
+}
 
-    index.remove_document(&node_id);
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn add_document(index_id: String, node_id: String, content: String) -> String {
+    respond(try_add_document(&index_id, node_id, content))
+}
 
-    serde_json::json!({
-        "success": true,
-        "nodeId": node_id
-    })
-    .to_string()
+/// Reserve room for `docs` more documents before a bulk import
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn reserve(index_id: String, docs: usize) -> String {
+    respond(try_reserve(&index_id, docs))
 }
 
-#[wasm_bindgen]
-pub fn search(index_id: String, query: String) -> String {
-    let indices = get_indices();
-
-    let (config, index) = match indices.get(&index_id) {
-        Some(entry) => entry,
-        None => {
-            return serde_json::json!({
-                "success": false,
-                "error": "Index not found"
-            })
-            .to_string();
-        }
-    };
+/// Every index's config and documents, for rebuilding with `create_index`
+/// and `add_document`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn export_indices() -> String {
+    respond(try_export_indices())
+}
 
-    let query_tokens = tokenize(&query, config);
-    let results = index.search(&query_tokens, config.max_results);
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn delete_index(index_id: String) -> String {
+    respond(try_delete_index(&index_id))
+}
 
-    serde_json::json!({
-        "success": true,
-        "results": results,
-        "queryTokens": query_tokens
-    })
-    .to_string()
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn remove_document(index_id: String, node_id: String) -> String {
+    respond(try_remove_document(&index_id, node_id))
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn search(index_id: String, query: String) -> String {
+    respond(try_search(&index_id, &query))
+}
+
+/// Posting list memory of an index
+///
+/// # Returns
+/// `{"success": true, "indexId", "documents", "terms", "postings",
+/// "postingBytes", "stringPostingBytes"}`: `postings` counts (term,
+/// document) pairs, `postingBytes` their compressed size and
+/// `stringPostingBytes` their size as a node ID string per occurrence
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn index_stats(index_id: String) -> String {
+    respond(try_index_stats(&index_id))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn clear_index(index_id: String) -> String {
-    let indices = get_indices();
-
-    let (_config, index) = match indices.get_mut(&index_id) {
-        Some(entry) => entry,
-        None => {
-            return serde_json::json!({
-                "success": false,
-                "error": "Index not found"
-            })
-            .to_string();
+    respond(try_clear_index(&index_id))
+}
+
+// Encoded versions of the exports above: the same responses as bytes in a
+// harmony-error `wire` format ("json", "cbor" or "msgpack"). They throw only
+// for an unknown format; other failures are in the response, as above
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn create_index_encoded(config: &[u8], format: &str) -> Result<Vec<u8>, HarmonyError> {
+    respond_encoded(
+        wire::decode_as(config, format)
+            .context("Invalid config")
+            .and_then(try_create_index),
+        format,
+    )
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn add_document_encoded(
+    index_id: String,
+    node_id: String,
+    content: String,
+    format: &str,
+) -> Result<Vec<u8>, HarmonyError> {
+    respond_encoded(try_add_document(&index_id, node_id, content), format)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn reserve_encoded(index_id: String, docs: usize, format: &str) -> Result<Vec<u8>, HarmonyError> {
+    respond_encoded(try_reserve(&index_id, docs), format)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn export_indices_encoded(format: &str) -> Result<Vec<u8>, HarmonyError> {
+    respond_encoded(try_export_indices(), format)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn delete_index_encoded(index_id: String, format: &str) -> Result<Vec<u8>, HarmonyError> {
+    respond_encoded(try_delete_index(&index_id), format)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn remove_document_encoded(index_id: String, node_id: String, format: &str) -> Result<Vec<u8>, HarmonyError> {
+    respond_encoded(try_remove_document(&index_id, node_id), format)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn search_encoded(index_id: String, query: String, format: &str) -> Result<Vec<u8>, HarmonyError> {
+    respond_encoded(try_search(&index_id, &query), format)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn index_stats_encoded(index_id: String, format: &str) -> Result<Vec<u8>, HarmonyError> {
+    respond_encoded(try_index_stats(&index_id), format)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn clear_index_encoded(index_id: String, format: &str) -> Result<Vec<u8>, HarmonyError> {
+    respond_encoded(try_clear_index(&index_id), format)
+}
+
+// JS value versions of the exports above: the same responses as objects,
+// converted with serde-wasm-bindgen instead of JSON strings, and typed in
+// the generated TypeScript declarations
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(
+    unchecked_return_type = "{ success: boolean; indexId?: string; error?: string; code?: string }"
+)]
+pub fn create_index_value(
+    #[wasm_bindgen(unchecked_param_type = "IndexConfig")] config: JsValue,
+) -> JsValue {
+    respond_value(
+        serde_wasm_bindgen::from_value(config)
+            .context("Invalid config")
+            .and_then(try_create_index),
+    )
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(
+    unchecked_return_type = "{ success: boolean; nodeId?: string; tokenCount?: number; error?: string; code?: string }"
+)]
+pub fn add_document_value(index_id: String, node_id: String, content: String) -> JsValue {
+    respond_value(try_add_document(&index_id, node_id, content))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(
+    unchecked_return_type = "{ success: boolean; indexId?: string; reserved?: number; error?: string; code?: string }"
+)]
+pub fn reserve_value(index_id: String, docs: usize) -> JsValue {
+    respond_value(try_reserve(&index_id, docs))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(
+    unchecked_return_type = "{ success: boolean; indices?: IndexSnapshot[]; error?: string; code?: string }"
+)]
+pub fn export_indices_value() -> JsValue {
+    respond_value(try_export_indices())
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(
+    unchecked_return_type = "{ success: boolean; indexId?: string; error?: string; code?: string }"
+)]
+pub fn delete_index_value(index_id: String) -> JsValue {
+    respond_value(try_delete_index(&index_id))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(
+    unchecked_return_type = "{ success: boolean; nodeId?: string; error?: string; code?: string }"
+)]
+pub fn remove_document_value(index_id: String, node_id: String) -> JsValue {
+    respond_value(try_remove_document(&index_id, node_id))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(
+    unchecked_return_type = "{ success: boolean; results?: SearchResult[]; queryTokens?: string[]; error?: string; code?: string }"
+)]
+pub fn search_value(index_id: String, query: String) -> JsValue {
+    respond_value(try_search(&index_id, &query))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(
+    unchecked_return_type = "{ success: boolean; indexId?: string; documents?: number; terms?: number; postings?: number; postingBytes?: number; stringPostingBytes?: number; error?: string; code?: string }"
+)]
+pub fn index_stats_value(index_id: String) -> JsValue {
+    respond_value(try_index_stats(&index_id))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(
+    unchecked_return_type = "{ success: boolean; indexId?: string; error?: string; code?: string }"
+)]
+pub fn clear_index_value(index_id: String) -> JsValue {
+    respond_value(try_clear_index(&index_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(json: String) -> Value {
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_search_and_error_codes() {
+        harmony_trace::use_ring_buffer_sink(4);
+        harmony_trace::set_level(Some(Level::Debug));
+        let created = response(create_index(
+            r#"{"index_id": "names", "property_name": "name"}"#.to_string(),
+        ));
+        assert_eq!(created, json!({ "success": true, "indexId": "names" }));
+
+        add_document("names".to_string(), "1".to_string(), "Low pass filter".to_string());
+        add_document("names".to_string(), "2".to_string(), "High pass".to_string());
+        let found = response(search("names".to_string(), "filter".to_string()));
+        assert_eq!(found["results"][0]["node_id"], "1");
+        // Documents share the module's node IDs with the other contexts
+        let id = harmony_ids::node_id("1").unwrap();
+        assert_eq!(harmony_ids::node_name(id).as_deref(), Some("1"));
+        let spans = harmony_trace::drain();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].message, "search");
+        assert_eq!(spans[0].fields["results"], "1");
+
+        let missing = response(search("tags".to_string(), "filter".to_string()));
+        assert_eq!(missing["success"], false);
+        assert_eq!(missing["code"], "not_found");
+        assert_eq!(missing["error"], "Index not found: tags");
+
+        let invalid = response(create_index("{}".to_string()));
+        assert_eq!(invalid["code"], "malformed");
+        assert!(invalid["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid config: "));
+
+        limits::set(limits::Limits {
+            max_tokens_per_document: 2,
+            ..limits::Limits::default()
+        });
+        let too_long = response(add_document(
+            "names".to_string(),
+            "3".to_string(),
+            "Band pass filter".to_string(),
+        ));
+        assert_eq!(too_long["code"], "capacity_exceeded");
+        assert_eq!(
+            too_long["error"],
+            "Document 3: Document token count is 3, over the limit of 2"
+        );
+        limits::set(limits::Limits::default());
+    }
+
+    #[test]
+    fn test_export_and_delete() {
+        create_index(r#"{"index_id": "b", "property_name": "name"}"#.to_string());
+        create_index(r#"{"index_id": "a", "property_name": "tags"}"#.to_string());
+        add_document("b".to_string(), "2".to_string(), "High pass".to_string());
+        add_document("b".to_string(), "1".to_string(), "Low pass".to_string());
+
+        let exported = response(export_indices());
+        assert_eq!(exported["indices"][0]["config"]["index_id"], "a");
+        assert_eq!(exported["indices"][0]["documents"], json!([]));
+        assert_eq!(
+            exported["indices"][1]["documents"],
+            json!([
+                { "node_id": "1", "content": "Low pass" },
+                { "node_id": "2", "content": "High pass" }
+            ])
+        );
+
+        let deleted = response(delete_index("a".to_string()));
+        assert_eq!(deleted, json!({ "success": true, "indexId": "a" }));
+        assert_eq!(response(delete_index("a".to_string()))["code"], "not_found");
+        assert_eq!(response(export_indices())["indices"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_encoded_responses() {
+        let config = wire::encode_as(&json!({ "index_id": "tags", "property_name": "tags" }), "cbor").unwrap();
+        let created: Value = wire::decode_as(&create_index_encoded(&config, "cbor").unwrap(), "cbor").unwrap();
+        assert_eq!(created, json!({ "success": true, "indexId": "tags" }));
+
+        add_document("tags".to_string(), "1".to_string(), "reverb delay".to_string());
+        for format in ["json", "cbor", "msgpack"] {
+            let bytes = search_encoded("tags".to_string(), "delay".to_string(), format).unwrap();
+            let found: Value = wire::decode_as(&bytes, format).unwrap();
+            assert_eq!(found, response(search("tags".to_string(), "delay".to_string())));
         }
-    };
+        let bytes = export_indices_encoded("json").unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), export_indices());
+
+        let missing: Value = wire::decode_as(&clear_index_encoded("none".to_string(), "msgpack").unwrap(), "msgpack").unwrap();
+        assert_eq!(missing["code"], "not_found");
+        let malformed: Value = wire::decode_as(&create_index_encoded(&[0xc1], "msgpack").unwrap(), "msgpack").unwrap();
+        assert_eq!(malformed["code"], "malformed");
+        assert_eq!(export_indices_encoded("xml").unwrap_err().code(), "invalid_input");
+    }
+
+    #[test]
+    fn test_long_posting_list() {
+        // Long enough for the `threads` feature to count in parallel
+        let mut index = InvertedIndex::new();
+        for i in 0..1000 {
+            let tokens = match i {
+                7 => vec!["gain", "gain"],
+                0..=599 => vec!["gain", "trim"],
+                _ => vec!["trim"],
+            };
+            let tokens = tokens.into_iter().map(String::from).collect();
+            index.add_document(i.to_string(), tokens, String::new()).unwrap();
+        }
+        let results = index.search(&["gain".to_string()], 1000);
+        let idf = (1000.0f64 / 601.0).ln();
+        assert_eq!(results.len(), 600);
+        assert_eq!(results[0].node_id, "7");
+        assert!((results[0].score - 4.0 * idf).abs() < 1e-9);
+        assert_eq!(results[0].matches, vec!["gain", "gain"]);
+        assert!(results[1..].iter().all(|result| (result.score - idf).abs() < 1e-9));
+    }
 
-    index.clear();
+    #[test]
+    fn test_index_stats() {
+        create_index(r#"{"index_id": "stats", "property_name": "name"}"#.to_string());
+        add_document("stats".to_string(), "osc-1".to_string(), "saw saw square".to_string());
+        add_document("stats".to_string(), "osc-2".to_string(), "saw sine".to_string());
+        add_document("stats".to_string(), "osc-3".to_string(), "sine".to_string());
+        remove_document("stats".to_string(), "osc-3".to_string());
+
+        let stats = response(index_stats("stats".to_string()));
+        assert_eq!(stats["documents"], 2);
+        assert_eq!((stats["terms"].clone(), stats["postings"].clone()), (json!(3), json!(4)));
+        // 5 occurrences as strings: a String header plus "osc-n" each
+        let strings = 3 * std::mem::size_of::<Vec<String>>() + 5 * (std::mem::size_of::<String>() + 5);
+        assert_eq!(stats["stringPostingBytes"], strings);
+        assert!(stats["postingBytes"].as_u64().unwrap() < strings as u64);
+
+        let found = response(search("stats".to_string(), "sine".to_string()));
+        assert_eq!(found["results"].as_array().unwrap().len(), 1);
+        assert_eq!(found["results"][0]["node_id"], "osc-2");
+        assert_eq!(response(index_stats("none".to_string()))["code"], "not_found");
+    }
 
-    serde_json::json!({
-        "success": true,
-        "indexId": index_id
-    })
-    .to_string()
-}
\ No newline at end of file
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<InvertedIndex>();
+        assert_send_sync::<IndexSnapshot>();
+    }
+
+    #[test]
+    fn test_reserve() {
+        create_index(r#"{"index_id": "bulk", "property_name": "name"}"#.to_string());
+        let reserved = response(reserve("bulk".to_string(), 1000));
+        assert_eq!(
+            reserved,
+            json!({ "success": true, "indexId": "bulk", "reserved": 1000 })
+        );
+        INDICES.with(|indices| {
+            let indices = indices.borrow();
+            assert!(indices["bulk"].1.node_to_content.capacity() >= 1000);
+        });
+
+        let too_many = response(reserve("bulk".to_string(), usize::MAX));
+        assert_eq!(too_many["code"], "capacity_exceeded");
+        let missing = response(reserve("tags".to_string(), 10));
+        assert_eq!(missing["code"], "not_found");
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_typescript_declarations() {
+        assert_eq!(
+            IndexConfig::DECL,
+            "export interface IndexConfig {\n    index_id: string;\n    property_name: string;\n    \
+             tokenizer?: string;\n    case_sensitive?: boolean;\n    min_token_length?: number;\n    \
+             max_results?: number;\n}"
+        );
+    }
+}
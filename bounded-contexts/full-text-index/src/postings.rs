@@ -0,0 +1,204 @@
+//! Compressed posting lists
+//!
+//! A term's posting list holds the documents containing it, as harmony-ids
+//! node IDs in ascending order, each with the term's count in the document.
+//! IDs are stored as the gap from the previous ID, and gaps and counts as
+//! LEB128 varints, so a posting usually takes two or three bytes instead of
+//! a copy of the node ID string.
+//!
+//! Postings are grouped in blocks of `POSTING_BLOCK`. Each block's first ID
+//! and byte offset are kept uncompressed, so blocks decode independently;
+//! the `threads` feature decodes long lists a block per task.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#full-text-index
+
+/// Postings per independently decodable block
+pub(crate) const POSTING_BLOCK: usize = 128;
+
+/// Sorted (node ID, term count) pairs, delta and varint encoded
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PostingList {
+    bytes: Vec<u8>,
+    /// First node ID and byte offset of each block
+    blocks: Vec<(u32, usize)>,
+    len: usize,
+    occurrences: usize,
+    last: u32,
+}
+
+impl PostingList {
+    /// Number of documents
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Term counts summed over the documents
+    pub(crate) fn occurrences(&self) -> usize {
+        self.occurrences
+    }
+
+    /// Bytes allocated for the encoded postings and block table
+    pub(crate) fn heap_size(&self) -> usize {
+        self.bytes.capacity() + self.blocks.capacity() * std::mem::size_of::<(u32, usize)>()
+    }
+
+    /// Adds `doc` with `count` occurrences, replacing any posting for it
+    ///
+    /// Appending a new highest ID is O(1); anything else re-encodes the list.
+    pub(crate) fn insert(&mut self, doc: u32, count: u32) {
+        if self.is_empty() || doc > self.last {
+            self.push(doc, count);
+            return;
+        }
+        let mut postings: Vec<(u32, u32)> = self.iter().filter(|&(id, _)| id != doc).collect();
+        let at = postings.partition_point(|&(id, _)| id < doc);
+        postings.insert(at, (doc, count));
+        *self = Self::from_sorted(postings);
+    }
+
+    /// Removes the posting for `doc`
+    ///
+    /// # Returns
+    /// false if `doc` had none
+    pub(crate) fn remove(&mut self, doc: u32) -> bool {
+        let postings: Vec<(u32, u32)> = self.iter().filter(|&(id, _)| id != doc).collect();
+        if postings.len() == self.len {
+            return false;
+        }
+        *self = Self::from_sorted(postings);
+        true
+    }
+
+    /// Every posting, in ID order
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        (0..self.block_count()).flat_map(|block| self.block(block))
+    }
+
+    pub(crate) fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// The postings of one block, in ID order
+    pub(crate) fn block(&self, block: usize) -> Postings<'_> {
+        let (first, start) = self.blocks[block];
+        let end = self
+            .blocks
+            .get(block + 1)
+            .map_or(self.bytes.len(), |&(_, offset)| offset);
+        Postings {
+            bytes: &self.bytes[start..end],
+            doc: first,
+        }
+    }
+
+    fn from_sorted(postings: impl IntoIterator<Item = (u32, u32)>) -> Self {
+        let mut list = Self::default();
+        for (doc, count) in postings {
+            list.push(doc, count);
+        }
+        list.bytes.shrink_to_fit();
+        list.blocks.shrink_to_fit();
+        list
+    }
+
+    /// Appends a posting; `doc` must be above every ID in the list
+    fn push(&mut self, doc: u32, count: u32) {
+        let gap = if self.len.is_multiple_of(POSTING_BLOCK) {
+            self.blocks.push((doc, self.bytes.len()));
+            0
+        } else {
+            doc - self.last
+        };
+        write_varint(&mut self.bytes, gap);
+        write_varint(&mut self.bytes, count);
+        self.len += 1;
+        self.occurrences += count as usize;
+        self.last = doc;
+    }
+}
+
+/// Decoder over one block
+pub(crate) struct Postings<'a> {
+    bytes: &'a [u8],
+    doc: u32,
+}
+
+impl Iterator for Postings<'_> {
+    type Item = (u32, u32);
+
+    fn next(&mut self) -> Option<(u32, u32)> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        self.doc += read_varint(&mut self.bytes);
+        let count = read_varint(&mut self.bytes);
+        Some((self.doc, count))
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Reads a varint written by `write_varint`, advancing `bytes` past it
+fn read_varint(bytes: &mut &[u8]) -> u32 {
+    let mut value = 0;
+    let mut shift = 0;
+    while let Some((&byte, rest)) = bytes.split_first() {
+        *bytes = rest;
+        value |= u32::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            break;
+        }
+        shift += 7;
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_posting_list() {
+        let mut list = PostingList::default();
+        let docs: Vec<u32> = (0..300).map(|i| i * 3).collect();
+        for &doc in &docs {
+            list.insert(doc, 1);
+        }
+        list.insert(u32::MAX, 200);
+        assert_eq!((list.len(), list.block_count()), (301, 3));
+        assert_eq!(list.occurrences(), 500);
+
+        // Out of order and replacing inserts keep the list sorted
+        list.insert(4, 2);
+        list.insert(3, 7);
+        let postings: Vec<_> = list.iter().collect();
+        assert_eq!(postings[..4], [(0, 1), (3, 7), (4, 2), (6, 1)]);
+        assert_eq!(postings.last(), Some(&(u32::MAX, 200)));
+        assert_eq!(list.occurrences(), 508);
+        // Re-encoded lists are trimmed: a few bytes per posting
+        assert!(list.heap_size() < 302 * 3);
+        let from_blocks: Vec<_> = (0..list.block_count())
+            .flat_map(|block| list.block(block))
+            .collect();
+        assert_eq!(from_blocks, postings);
+
+        assert!(list.remove(3));
+        assert!(!list.remove(5));
+        assert_eq!((list.len(), list.occurrences()), (301, 501));
+        for (doc, _) in postings {
+            list.remove(doc);
+        }
+        assert!(list.is_empty());
+        assert_eq!(list.iter().count(), 0);
+    }
+}
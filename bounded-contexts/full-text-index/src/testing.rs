@@ -0,0 +1,279 @@
+//! Synthetic documents for tests and benchmarks (`testing` feature)
+//!
+//! `generate_document_set` writes documents from a `DocumentSpec`; the same
+//! spec and seed give the same documents on every platform. Documents are
+//! named `id_prefix` followed by `0..documents`, so they line up with nodes
+//! from spatial-index's generator. Words come from a made-up vocabulary of
+//! pronounceable words ("bako", "ritesu", ...) and are drawn with a Zipf
+//! distribution: the word of rank r turns up about 1/r as often as the
+//! most common one, as in natural text, so searches hit both long and
+//! short posting lists.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#full-text-index
+
+use harmony_error::{limits, HarmonyError, ResultExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{respond, try_add_document, try_reserve, IndexedDocument};
+
+const CONSONANTS: &[u8] = b"bdfgklmnprstvz";
+const VOWELS: &[u8] = b"aeiou";
+
+/// Shape of a generated document set
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentSpec {
+    /// Number of documents
+    pub documents: usize,
+    #[serde(default = "default_words_per_document")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub words_per_document: usize,
+    /// Number of distinct words to draw from
+    #[serde(default = "default_vocabulary")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub vocabulary: usize,
+    /// Prefix of the node IDs
+    #[serde(default = "default_id_prefix")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub id_prefix: String,
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub seed: u64,
+}
+
+fn default_words_per_document() -> usize {
+    20
+}
+
+fn default_vocabulary() -> usize {
+    1000
+}
+
+fn default_id_prefix() -> String {
+    "node-".to_string()
+}
+
+/// Word of rank `rank`: two or more consonant-vowel syllables, distinct
+/// for every rank
+fn word(rank: usize) -> String {
+    let syllables = CONSONANTS.len() * VOWELS.len();
+    let mut rest = rank;
+    let mut word = String::new();
+    loop {
+        let syllable = rest % syllables;
+        word.push(CONSONANTS[syllable / VOWELS.len()] as char);
+        word.push(VOWELS[syllable % VOWELS.len()] as char);
+        rest /= syllables;
+        if rest == 0 && word.len() >= 4 {
+            return word;
+        }
+    }
+}
+
+/// Random documents following `spec`
+///
+/// # Returns
+/// `invalid_input` for an empty vocabulary; `capacity_exceeded` for more
+/// words per document than `max_tokens_per_document`, or more documents
+/// or vocabulary words than `max_edges_per_batch`
+pub fn generate_document_set(spec: &DocumentSpec) -> Result<Vec<IndexedDocument>, HarmonyError> {
+    if spec.vocabulary == 0 {
+        return Err(HarmonyError::InvalidInput(
+            "Documents need a vocabulary of at least 1 word".to_string(),
+        ));
+    }
+    let limits = limits::limits();
+    limits::check(
+        "Generated document count",
+        spec.documents,
+        limits.max_edges_per_batch,
+    )?;
+    limits::check(
+        "Vocabulary size",
+        spec.vocabulary,
+        limits.max_edges_per_batch,
+    )?;
+    limits::check(
+        "Words per document",
+        spec.words_per_document,
+        limits.max_tokens_per_document,
+    )?;
+
+    let words: Vec<String> = (0..spec.vocabulary).map(word).collect();
+    // Cumulative Zipf weights: rank r weighs 1 / (r + 1)
+    let cumulative: Vec<f64> = words
+        .iter()
+        .enumerate()
+        .scan(0.0, |total, (rank, _)| {
+            *total += 1.0 / (rank as f64 + 1.0);
+            Some(*total)
+        })
+        .collect();
+    let total = cumulative[cumulative.len() - 1];
+
+    let mut rng = fastrand::Rng::with_seed(spec.seed);
+    let documents = (0..spec.documents)
+        .map(|i| {
+            let content = (0..spec.words_per_document)
+                .map(|_| {
+                    let pick = rng.f64() * total;
+                    let rank = cumulative
+                        .partition_point(|&weight| weight <= pick)
+                        .min(words.len() - 1);
+                    words[rank].as_str()
+                })
+                .collect::<Vec<&str>>()
+                .join(" ");
+            IndexedDocument {
+                node_id: format!("{}{}", spec.id_prefix, i),
+                content,
+            }
+        })
+        .collect();
+    Ok(documents)
+}
+
+fn parse_spec(spec_json: &str) -> Result<DocumentSpec, HarmonyError> {
+    limits::check_json(spec_json)
+        .and_then(|()| Ok(serde_json::from_str(spec_json)?))
+        .context("Invalid document spec")
+}
+
+/// See `generate_documents`
+pub fn try_generate_documents(spec: &DocumentSpec) -> Result<Value, HarmonyError> {
+    let documents = generate_document_set(spec).context("Invalid document spec")?;
+    Ok(json!({ "documents": documents }))
+}
+
+/// See `add_generated_documents`
+pub fn try_add_generated_documents(
+    index_id: &str,
+    spec: &DocumentSpec,
+) -> Result<Value, HarmonyError> {
+    let documents = generate_document_set(spec).context("Invalid document spec")?;
+    try_reserve(index_id, documents.len())?;
+    for document in &documents {
+        try_add_document(index_id, document.node_id.clone(), document.content.clone())?;
+    }
+    Ok(json!({
+        "indexId": index_id,
+        "added": documents.len()
+    }))
+}
+
+/// Random documents for tests and benchmarks
+///
+/// # Arguments
+/// * `spec_json` - JSON DocumentSpec:
+///   `{"documents", "words_per_document"?, "vocabulary"?, "id_prefix"?, "seed"?}`
+///
+/// # Returns
+/// `{"success": true, "documents": [{"node_id", "content"}, ...]}`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn generate_documents(spec_json: String) -> String {
+    respond(parse_spec(&spec_json).and_then(|spec| try_generate_documents(&spec)))
+}
+
+/// Adds random documents to an index for tests and benchmarks
+///
+/// # Returns
+/// `{"success": true, "indexId", "added"}`; documents added before an
+/// error stay in the index
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn add_generated_documents(index_id: String, spec_json: String) -> String {
+    respond(parse_spec(&spec_json).and_then(|spec| try_add_generated_documents(&index_id, &spec)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_index, search};
+    use std::collections::HashMap;
+
+    fn spec(seed: u64) -> DocumentSpec {
+        DocumentSpec {
+            documents: 200,
+            words_per_document: 30,
+            vocabulary: 500,
+            id_prefix: default_id_prefix(),
+            seed,
+        }
+    }
+
+    fn contents(documents: &[IndexedDocument]) -> Vec<&str> {
+        documents
+            .iter()
+            .map(|document| document.content.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_generated_documents() {
+        assert_eq!(
+            (word(0), word(1), word(70)),
+            ("baba".to_string(), "beba".to_string(), "babe".to_string())
+        );
+        let words: std::collections::HashSet<String> = (0..5000).map(word).collect();
+        assert_eq!(words.len(), 5000);
+
+        let documents = generate_document_set(&spec(1)).unwrap();
+        assert_eq!(documents.len(), 200);
+        assert_eq!(documents[199].node_id, "node-199");
+        assert!(documents
+            .iter()
+            .all(|document| document.content.split(' ').count() == 30));
+        assert_eq!(
+            contents(&documents),
+            contents(&generate_document_set(&spec(1)).unwrap())
+        );
+        assert_ne!(
+            contents(&documents),
+            contents(&generate_document_set(&spec(2)).unwrap())
+        );
+
+        // Zipf: the top word is far more common than the 50th
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for document in &documents {
+            for token in document.content.split(' ') {
+                *counts.entry(token).or_insert(0) += 1;
+            }
+        }
+        assert!(
+            counts[word(0).as_str()] > 10 * counts.get(word(49).as_str()).copied().unwrap_or(1)
+        );
+    }
+
+    #[test]
+    fn test_add_generated_documents() {
+        let response = |json: String| serde_json::from_str::<Value>(&json).unwrap();
+        create_index(r#"{"index_id": "generated", "property_name": "content"}"#.to_string());
+        let added = response(add_generated_documents(
+            "generated".to_string(),
+            r#"{"documents": 50, "vocabulary": 20, "seed": 4}"#.to_string(),
+        ));
+        assert_eq!(
+            (added["success"].as_bool(), added["added"].as_u64()),
+            (Some(true), Some(50))
+        );
+        let found = response(search("generated".to_string(), word(0)));
+        assert!(!found["results"].as_array().unwrap().is_empty());
+
+        let generated = response(generate_documents(
+            r#"{"documents": 2, "seed": 4}"#.to_string(),
+        ));
+        assert_eq!(generated["documents"][1]["node_id"], "node-1");
+        let invalid = response(generate_documents(
+            r#"{"documents": 2, "vocabulary": 0}"#.to_string(),
+        ));
+        assert_eq!(invalid["code"], "invalid_input");
+        assert!(invalid["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid document spec: "));
+    }
+}
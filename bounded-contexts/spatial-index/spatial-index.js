@@ -7,6 +7,9 @@
 /**
  * SpatialIndex wrapper class
  * Manages spatial indexing of nodes with 2D coordinates for efficient spatial queries
+ *
+ * Uses the JS value methods of the WASM index (insertValue, queryRangeValue,
+ * ...), which pass objects directly instead of JSON strings.
  */
 class SpatialIndexWrapper {
   /**
@@ -16,11 +19,15 @@ class SpatialIndexWrapper {
    * @param {number} maxX - Maximum X coordinate of index bounds
    * @param {number} maxY - Maximum Y coordinate of index bounds
    * @param {number} capacity - Maximum nodes per quadtree node before subdivision (default: 4)
+   * @param {number} expectedNodes - Nodes to reserve room for up front (default: 0)
    */
-  constructor(wasmModule, minX, minY, maxX, maxY, capacity = 4) {
+  constructor(wasmModule, minX, minY, maxX, maxY, capacity = 4, expectedNodes = 0) {
     this.wasm = wasmModule;
-    this.index = new wasmModule.SpatialIndex(minX, minY, maxX, maxY, capacity);
+    this.index = expectedNodes > 0
+      ? wasmModule.SpatialIndex.withCapacity(minX, minY, maxX, maxY, capacity, expectedNodes)
+      : new wasmModule.SpatialIndex(minX, minY, maxX, maxY, capacity);
     this.bounds = { minX, minY, maxX, maxY };
+    this.capacity = capacity;
   }
 
   /**
@@ -29,11 +36,36 @@ class SpatialIndexWrapper {
    * @param {number} x - X coordinate
    * @param {number} y - Y coordinate
    * @param {object} metadata - Additional metadata for the node
-   * @returns {boolean} True if insertion successful, false if out of bounds
+   * @returns {boolean} True if insertion successful, false if out of bounds;
+   *   a node with the same ID is replaced
+   * @throws {Error} HarmonyError with code 'malformed' for metadata values
+   *   that are not strings, or 'invalid_input' for non-finite coordinates
    */
   insert(id, x, y, metadata = {}) {
-    const metadataJson = JSON.stringify(metadata);
-    return this.index.insert(id, x, y, metadataJson);
+    return this.index.insertValue(id, x, y, metadata);
+  }
+
+  /**
+   * Remove a node from the spatial index
+   * @param {string} id - Node identifier
+   * @returns {boolean} True if the node was removed, false if not found
+   */
+  remove(id) {
+    return this.index.remove(id);
+  }
+
+  /**
+   * Move a node to new coordinates, keeping its metadata
+   * @param {string} id - Node identifier
+   * @param {number} x - New X coordinate
+   * @param {number} y - New Y coordinate
+   * @returns {boolean} True if moved, false if the new point is out of bounds
+   *   (the node stays where it was)
+   * @throws {Error} HarmonyError with code 'not_found' for an unknown node, or
+   *   'invalid_input' for non-finite coordinates
+   */
+  moveNode(id, x, y) {
+    return this.index.moveNode(id, x, y);
   }
 
   /**
@@ -45,8 +77,7 @@ class SpatialIndexWrapper {
    * @returns {Array<object>} Array of nodes within the range
    */
   queryRange(minX, minY, maxX, maxY) {
-    const resultJson = this.index.query_range(minX, minY, maxX, maxY);
-    return JSON.parse(resultJson);
+    return this.index.queryRangeValue(minX, minY, maxX, maxY);
   }
 
   /**
@@ -57,8 +88,7 @@ class SpatialIndexWrapper {
    * @returns {Array<object>} Array of nodes within the radius
    */
   queryRadius(centerX, centerY, radius) {
-    const resultJson = this.index.query_radius(centerX, centerY, radius);
-    return JSON.parse(resultJson);
+    return this.index.queryRadiusValue(centerX, centerY, radius);
   }
 
   /**
@@ -69,8 +99,108 @@ class SpatialIndexWrapper {
    * @returns {Array<object>} Array of k nearest nodes, sorted by distance
    */
   queryNearest(x, y, k) {
-    const resultJson = this.index.query_nearest(x, y, k);
-    return JSON.parse(resultJson);
+    return this.index.queryNearestValue(x, y, k);
+  }
+
+  /**
+   * Query what to draw in a viewport: every visible node, or clusters of
+   * them once more than `lodThreshold` are visible
+   * @param {number} minX - Minimum X coordinate
+   * @param {number} minY - Minimum Y coordinate
+   * @param {number} maxX - Maximum X coordinate
+   * @param {number} maxY - Maximum Y coordinate
+   * @param {number} zoom - Screen pixels per world unit; clusters cover
+   *   64-pixel grid cells
+   * @returns {{kind: 'nodes', nodes: Array<object>}|{kind: 'clusters',
+   *   visible: number, clusters: Array<{center: object, bounds: object,
+   *   count: number, id: string}>}} Visible nodes or clusters, in row order
+   * @throws {Error} HarmonyError with code 'invalid_input' unless zoom is
+   *   finite and positive
+   */
+  queryViewport(minX, minY, maxX, maxY, zoom) {
+    return this.index.queryViewportValue(minX, minY, maxX, maxY, zoom);
+  }
+
+  /**
+   * Set the visible node count above which queryViewport returns clusters
+   * @param {number} threshold - Node count (default: 1000)
+   */
+  setLodThreshold(threshold) {
+    this.index.setLodThreshold(threshold);
+  }
+
+  /**
+   * Set the world-to-screen matrix used by the screen-space methods, in
+   * canvas setTransform / DOMMatrix order:
+   * screen = (a*x + c*y + e, b*x + d*y + f)
+   * @throws {Error} HarmonyError with code 'invalid_input' for a singular or
+   *   non-finite matrix (the previous one is kept)
+   */
+  setTransform(a, b, c, d, e, f) {
+    this.index.setTransform(a, b, c, d, e, f);
+  }
+
+  /**
+   * Drop the transform, so screen and world coordinates are the same
+   */
+  clearTransform() {
+    this.index.clearTransform();
+  }
+
+  /**
+   * Convert a world point to screen coordinates
+   * @returns {{x: number, y: number}} Screen point
+   */
+  toScreen(x, y) {
+    return this.index.toScreen(x, y);
+  }
+
+  /**
+   * Convert a screen point to world coordinates
+   * @returns {{x: number, y: number}} World point
+   */
+  toWorld(x, y) {
+    return this.index.toWorld(x, y);
+  }
+
+  /**
+   * Insert a node at a screen point; it is stored at the world point
+   * @returns {boolean} True if inserted, false if out of bounds
+   */
+  insertScreen(id, x, y, metadata = {}) {
+    return this.index.insertScreen(id, x, y, metadata);
+  }
+
+  /**
+   * Query nodes within a screen-space box
+   * @returns {Array<object>} Nodes, with screen positions
+   */
+  queryRangeScreen(minX, minY, maxX, maxY) {
+    return this.index.queryRangeScreen(minX, minY, maxX, maxY);
+  }
+
+  /**
+   * Query nodes within a screen-space radius
+   * @returns {Array<object>} Nodes, with screen positions
+   */
+  queryRadiusScreen(centerX, centerY, radius) {
+    return this.index.queryRadiusScreen(centerX, centerY, radius);
+  }
+
+  /**
+   * Find the k nodes nearest a screen point
+   * @returns {Array<object>} Nodes, with screen positions, closest first
+   */
+  queryNearestScreen(x, y, k) {
+    return this.index.queryNearestScreen(x, y, k);
+  }
+
+  /**
+   * Get the screen position of a node by its ID
+   * @returns {object|null} Screen position {x, y} or null if not found
+   */
+  getPositionScreen(id) {
+    return this.index.getPositionScreen(id);
   }
 
   /**
@@ -79,8 +209,32 @@ class SpatialIndexWrapper {
    * @returns {object|null} Position object {x, y} or null if not found
    */
   getPosition(id) {
-    const positionJson = this.index.get_position(id);
-    return JSON.parse(positionJson);
+    return this.index.getPositionValue(id);
+  }
+
+  /**
+   * Reserve room for more nodes before a bulk import
+   * @param {number} additional - Number of nodes about to be inserted
+   * @throws {Error} HarmonyError with code 'capacity_exceeded' if the memory
+   *   cannot be reserved
+   */
+  reserve(additional) {
+    this.index.reserve(additional);
+  }
+
+  /**
+   * Rebuild the quadtree with the capacity that makes recent queries
+   * cheapest (deep trees vs. overfull cells); the index keeps its nodes
+   * @returns {{previous_capacity: number, capacity: number,
+   *   previous_cost: number, cost: number, queries: number}} Capacities and
+   *   estimated cost per query (cells visited plus nodes tested) before and
+   *   after; `queries` is 0 when no queries were logged and point lookups
+   *   at stored nodes were replayed instead
+   */
+  tune() {
+    const report = JSON.parse(this.index.tune());
+    this.capacity = report.capacity;
+    return report;
   }
 
   /**
@@ -105,6 +259,46 @@ class SpatialIndexWrapper {
   getBounds() {
     return { ...this.bounds };
   }
+
+  /**
+   * Capture the bounds, capacity and every node, for `importState`
+   * @returns {{bounds: object, capacity: number, nodes: Array<object>}}
+   */
+  exportState() {
+    const { minX, minY, maxX, maxY } = this.bounds;
+    const nodes = this.queryRange(minX, minY, maxX, maxY);
+    nodes.sort((a, b) => (a.id < b.id ? -1 : a.id > b.id ? 1 : 0));
+    return { bounds: this.getBounds(), capacity: this.capacity, nodes };
+  }
+
+  /**
+   * Replace the index with one saved by `exportState`; on failure the
+   * index is unchanged
+   * @param {{bounds: object, capacity: number, nodes: Array<object>}} state
+   * @throws {Error} HarmonyError from an insert, or an Error for a node
+   *   outside the saved bounds
+   */
+  importState(state) {
+    const { minX, minY, maxX, maxY } = state.bounds;
+    const index = this.wasm.SpatialIndex.withCapacity(
+      minX, minY, maxX, maxY, state.capacity, state.nodes.length
+    );
+    index.setLodThreshold(this.index.lodThreshold());
+    const transform = this.index.getTransform();
+    if (transform) {
+      const { a, b, c, d, e, f } = transform;
+      index.setTransform(a, b, c, d, e, f);
+    }
+    for (const node of state.nodes) {
+      const { x, y } = node.position;
+      if (!index.insertValue(node.id, x, y, node.metadata)) {
+        throw new Error(`Spatial node ${node.id} is outside the index bounds`);
+      }
+    }
+    this.index = index;
+    this.bounds = { minX, minY, maxX, maxY };
+    this.capacity = state.capacity;
+  }
 }
 
 /**
@@ -123,10 +317,11 @@ async function loadSpatialIndex(wasmPath = '/bounded-contexts/spatial-index/spat
    * @param {number} maxX - Maximum X coordinate of index bounds
    * @param {number} maxY - Maximum Y coordinate of index bounds
    * @param {number} capacity - Maximum nodes per quadtree node (default: 4)
+   * @param {number} expectedNodes - Nodes to reserve room for (default: 0)
    * @returns {SpatialIndexWrapper} New spatial index instance
    */
-  return (minX, minY, maxX, maxY, capacity = 4) => {
-    return new SpatialIndexWrapper(wasmModule, minX, minY, maxX, maxY, capacity);
+  return (minX, minY, maxX, maxY, capacity = 4, expectedNodes = 0) => {
+    return new SpatialIndexWrapper(wasmModule, minX, minY, maxX, maxY, capacity, expectedNodes);
   };
 }
 
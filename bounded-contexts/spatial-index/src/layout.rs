@@ -0,0 +1,525 @@
+//! Force-directed layout with the index as its Barnes-Hut tree
+//!
+//! Each `ForceLayout::step` pushes every pair of nodes apart and pulls the
+//! ends of each edge of an edge executor together, then moves the nodes in
+//! the index. Repulsion is Barnes-Hut approximated on the index's own
+//! quadtree: a cell that looks small from a node (width over distance below
+//! `theta`) acts as one body of its node count at its centre of mass, so a
+//! step costs O(n log n) rather than O(n²). Attraction is a spring per edge
+//! scaled by the edge's weight.
+//!
+//! Nodes are matched to executor node IDs through the module's node ID map
+//! (harmony-ids), so the index and executor must share a wasm module. After
+//! each step the positions are written to a caller's `Float32Array` as
+//! `x, y` pairs at twice the node ID, so a renderer can read them in place.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#spatial-index
+
+use std::collections::HashMap;
+
+use harmony_error::{limits, HarmonyError, ResultExt};
+use harmony_trace::Level;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+use wasm_edge_executor::WASMEdgeExecutor;
+
+use crate::{check_finite, BoundingBox, Point, QuadTreeNode, SpatialIndex, TRACE_TARGET};
+
+/// Distance below which repulsion stops growing, so coincident nodes get a
+/// finite push
+const MIN_DISTANCE: f64 = 0.01;
+
+/// Force constants for `ForceLayout`; missing fields take their defaults
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Repulsion between two nodes at distance 1; falls off as 1 / distance
+    pub repulsion: f64,
+    /// Length an edge's spring pulls toward
+    pub spring_length: f64,
+    /// Spring stiffness, scaled by the edge's weight
+    pub spring_strength: f64,
+    /// Barnes-Hut opening threshold; 0 computes every pair exactly
+    pub theta: f64,
+    /// Fraction of a node's velocity kept from one step to the next
+    pub damping: f64,
+    /// Furthest a node moves in one step
+    pub max_step: f64,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            repulsion: 500.0,
+            spring_length: 50.0,
+            spring_strength: 0.05,
+            theta: 0.8,
+            damping: 0.8,
+            max_step: 10.0,
+        }
+    }
+}
+
+impl LayoutConfig {
+    /// Fails with `invalid_input` unless every constant is finite and in
+    /// range: `damping` within 0-1, `max_step` positive, the rest not
+    /// negative
+    pub fn validate(&self) -> Result<(), HarmonyError> {
+        let checks = [
+            ("repulsion", self.repulsion, self.repulsion >= 0.0),
+            ("spring_length", self.spring_length, self.spring_length >= 0.0),
+            ("spring_strength", self.spring_strength, self.spring_strength >= 0.0),
+            ("theta", self.theta, self.theta >= 0.0),
+            ("damping", self.damping, (0.0..=1.0).contains(&self.damping)),
+            ("max_step", self.max_step, self.max_step > 0.0),
+        ];
+        for (name, value, in_range) in checks {
+            if !value.is_finite() || !in_range {
+                return Err(HarmonyError::InvalidInput(format!(
+                    "Layout config {} of {} is out of range",
+                    name, value
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Force-directed layout state: the config and each node's velocity
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Default)]
+pub struct ForceLayout {
+    config: LayoutConfig,
+    velocities: HashMap<u32, Point>,
+}
+
+/// Node count and centre of mass of a quadtree cell and its descendants
+struct Cell<'a> {
+    bounds: BoundingBox,
+    mass: f64,
+    center: Point,
+    /// Nodes held by the quadtree node itself
+    bodies: Vec<(&'a str, Point)>,
+    children: Vec<Cell<'a>>,
+}
+
+impl<'a> Cell<'a> {
+    fn build(node: &'a QuadTreeNode) -> Option<Self> {
+        let bodies: Vec<_> = node
+            .nodes
+            .iter()
+            .map(|body| (body.id.as_str(), body.position))
+            .collect();
+        let children: Vec<_> = [
+            &node.northeast,
+            &node.northwest,
+            &node.southeast,
+            &node.southwest,
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|child| Cell::build(child))
+        .collect();
+
+        let mass = bodies.len() as f64 + children.iter().map(|cell| cell.mass).sum::<f64>();
+        if mass == 0.0 {
+            return None;
+        }
+        let (mut x, mut y) = (0.0, 0.0);
+        for (_, position) in &bodies {
+            x += position.x;
+            y += position.y;
+        }
+        for cell in &children {
+            x += cell.center.x * cell.mass;
+            y += cell.center.y * cell.mass;
+        }
+        Some(Cell {
+            bounds: node.bounds,
+            mass,
+            center: Point {
+                x: x / mass,
+                y: y / mass,
+            },
+            bodies,
+            children,
+        })
+    }
+
+    /// Adds the repulsion on node `id` at `at` from this cell to `force`
+    fn repel(&self, id: &str, at: Point, config: &LayoutConfig, force: &mut Point) {
+        let distance = ((at.x - self.center.x).powi(2) + (at.y - self.center.y).powi(2)).sqrt();
+        let width = self.bounds.max_x - self.bounds.min_x;
+        if !self.bounds.contains(&at) && width < config.theta * distance {
+            push(force, at, self.center, config.repulsion * self.mass);
+            return;
+        }
+        for &(other, position) in &self.bodies {
+            if other == id {
+                continue;
+            }
+            if position.x == at.x && position.y == at.y {
+                // Coincident: split them along x, in ID order
+                force.x += if id < other { -1.0 } else { 1.0 } * config.repulsion / MIN_DISTANCE;
+                continue;
+            }
+            push(force, at, position, config.repulsion);
+        }
+        for cell in &self.children {
+            cell.repel(id, at, config, force);
+        }
+    }
+}
+
+/// Adds a push of `strength / distance` on `at`, away from `from`
+fn push(force: &mut Point, at: Point, from: Point, strength: f64) {
+    let (dx, dy) = (at.x - from.x, at.y - from.y);
+    let distance = (dx * dx + dy * dy).sqrt().max(MIN_DISTANCE);
+    let scale = strength / (distance * distance);
+    force.x += dx * scale;
+    force.y += dy * scale;
+}
+
+/// Length of a positions array holding every node ID interned so far
+fn required_length() -> usize {
+    harmony_ids::with_node_ids(|ids| 2 * (ids.first() as usize + ids.len()))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ForceLayout {
+    /// Create a layout from a JSON LayoutConfig; `"{}"` for the defaults
+    ///
+    /// # Returns
+    /// `malformed` for JSON that is not a LayoutConfig, `capacity_exceeded`
+    /// for JSON over the input limits, `invalid_input` for constants out of
+    /// range (see `LayoutConfig::validate`)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(config_json: &str) -> Result<ForceLayout, HarmonyError> {
+        limits::check_json(config_json).context("Invalid layout config")?;
+        let config: LayoutConfig =
+            serde_json::from_str(config_json).context("Invalid layout config")?;
+        Self::with_config(config)
+    }
+
+    /// Length the positions array passed to `step` needs: two floats per
+    /// node ID interned so far
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = positionsLength))]
+    pub fn positions_length() -> usize {
+        required_length()
+    }
+
+    /// Forget every node's velocity, as for a fresh layout
+    pub fn reset(&mut self) {
+        self.velocities.clear();
+    }
+
+    /// Run one iteration: move every node in `index` by the forces from the
+    /// other nodes and from `executor`'s edges, then write each node's
+    /// `x, y` to `positions` at twice its node ID
+    ///
+    /// Nodes stay inside the index bounds. Executor edges whose ends are not
+    /// both in the index are ignored.
+    ///
+    /// # Returns
+    /// The largest distance a node moved, to stop iterating once it is
+    /// small; `invalid_input` if `positions` is shorter than
+    /// `positionsLength()` or the forces overflow to a non-finite
+    /// position, with nothing moved
+    pub fn step(
+        &mut self,
+        index: &mut SpatialIndex,
+        executor: &WASMEdgeExecutor,
+        positions: &mut [f32],
+    ) -> Result<f64, HarmonyError> {
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "layout_step");
+        let needed = required_length();
+        if positions.len() < needed {
+            return Err(HarmonyError::InvalidInput(format!(
+                "Positions array holds {} floats; the layout needs {}",
+                positions.len(),
+                needed
+            )));
+        }
+
+        // Sorted by node ID, so runs do not depend on hash order
+        let mut bodies: Vec<(u32, &str, Point)> = harmony_ids::with_node_ids(|ids| {
+            index
+                .node_lookup
+                .iter()
+                .filter_map(|(name, &position)| Some((ids.id(name)?, name.as_str(), position)))
+                .collect()
+        });
+        bodies.sort_unstable_by_key(|&(id, _, _)| id);
+        let slots: HashMap<u32, usize> = bodies
+            .iter()
+            .enumerate()
+            .map(|(slot, &(id, _, _))| (id, slot))
+            .collect();
+
+        let mut forces = vec![Point { x: 0.0, y: 0.0 }; bodies.len()];
+        if let Some(tree) = Cell::build(&index.root) {
+            for (force, &(_, name, position)) in forces.iter_mut().zip(&bodies) {
+                tree.repel(name, position, &self.config, force);
+            }
+        }
+
+        let mut springs = 0;
+        for (edge, &weight) in executor.edges().iter().zip(executor.weights()) {
+            let (Some(&source), Some(&target)) =
+                (slots.get(&edge.source()), slots.get(&edge.target()))
+            else {
+                continue;
+            };
+            if source == target {
+                continue;
+            }
+            let (from, to) = (bodies[source].2, bodies[target].2);
+            let (dx, dy) = (to.x - from.x, to.y - from.y);
+            let distance = (dx * dx + dy * dy).sqrt().max(MIN_DISTANCE);
+            let pull = self.config.spring_strength
+                * weight as f64
+                * (distance - self.config.spring_length)
+                / distance;
+            forces[source].x += dx * pull;
+            forces[source].y += dy * pull;
+            forces[target].x -= dx * pull;
+            forces[target].y -= dy * pull;
+            springs += 1;
+        }
+
+        let bounds = index.root.bounds;
+        let mut velocities = HashMap::with_capacity(bodies.len());
+        let mut moves = Vec::with_capacity(bodies.len());
+        let mut largest: f64 = 0.0;
+        for (&(id, name, position), force) in bodies.iter().zip(&forces) {
+            let previous = self.velocities.get(&id).copied();
+            let previous = previous.unwrap_or(Point { x: 0.0, y: 0.0 });
+            let mut velocity = Point {
+                x: (previous.x + force.x) * self.config.damping,
+                y: (previous.y + force.y) * self.config.damping,
+            };
+            let speed = (velocity.x * velocity.x + velocity.y * velocity.y).sqrt();
+            if speed > self.config.max_step {
+                velocity.x *= self.config.max_step / speed;
+                velocity.y *= self.config.max_step / speed;
+            }
+            let target = Point {
+                x: (position.x + velocity.x).clamp(bounds.min_x, bounds.max_x),
+                y: (position.y + velocity.y).clamp(bounds.min_y, bounds.max_y),
+            };
+            largest = largest
+                .max(((target.x - position.x).powi(2) + (target.y - position.y).powi(2)).sqrt());
+            velocities.insert(id, velocity);
+            moves.push((id, name.to_string(), target));
+        }
+        // Check every target first, so a failure leaves the layout unmoved
+        for (_, name, target) in &moves {
+            check_finite(name, target.x, target.y)?;
+        }
+        self.velocities = velocities;
+        let count = bodies.len();
+
+        for (id, name, target) in moves {
+            index.move_node(name, target.x, target.y)?;
+            let slot = 2 * id as usize;
+            positions[slot] = target.x as f32;
+            positions[slot + 1] = target.y as f32;
+        }
+        span.field("nodes", count)
+            .field("springs", springs)
+            .field("largest_move", largest);
+        Ok(largest)
+    }
+}
+
+impl ForceLayout {
+    /// Create a layout with typed force constants
+    ///
+    /// # Returns
+    /// `invalid_input` for constants out of range
+    pub fn with_config(config: LayoutConfig) -> Result<Self, HarmonyError> {
+        config.validate()?;
+        Ok(ForceLayout {
+            config,
+            velocities: HashMap::new(),
+        })
+    }
+
+    /// The force constants
+    pub fn config(&self) -> &LayoutConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(nodes: &[(&str, f64, f64)]) -> SpatialIndex {
+        let mut index = SpatialIndex::new(0.0, 0.0, 1000.0, 1000.0, 4);
+        for &(name, x, y) in nodes {
+            index
+                .insert(name.to_string(), x, y, "{}".to_string())
+                .unwrap();
+        }
+        index
+    }
+
+    fn distance(index: &SpatialIndex, a: &str, b: &str) -> f64 {
+        let (a, b) = (index.position(a).unwrap(), index.position(b).unwrap());
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_layout_step() {
+        let mut index = index(&[
+            ("a", 100.0, 500.0),
+            ("b", 900.0, 500.0),
+            ("c", 500.0, 100.0),
+            ("d", 501.0, 100.0),
+        ]);
+        let mut executor = WASMEdgeExecutor::new();
+        let id = |name| harmony_ids::id(name).unwrap();
+        executor.add_edge(id("a"), id("b"), 0);
+        let mut layout = ForceLayout::new("{\"max_step\": 20}").unwrap();
+        assert_eq!(layout.config().max_step, 20.0);
+        assert_eq!(layout.config().spring_length, 50.0);
+
+        let mut positions = vec![f32::NAN; ForceLayout::positions_length()];
+        let mut moved = 0.0;
+        for _ in 0..200 {
+            moved = layout.step(&mut index, &executor, &mut positions).unwrap();
+        }
+        // The edge pulls a and b to where springs balance repulsion; the
+        // unconnected c and d push apart
+        let ab = distance(&index, "a", "b");
+        assert!(ab > 50.0 && ab < 300.0, "a-b at {}", ab);
+        assert!(distance(&index, "c", "d") > 50.0);
+        assert!(moved < 1.0, "still moving {}", moved);
+        assert_eq!(index.size(), 4);
+
+        let a = index.position("a").unwrap();
+        let slot = 2 * id("a") as usize;
+        assert_eq!(
+            (positions[slot], positions[slot + 1]),
+            (a.x as f32, a.y as f32)
+        );
+
+        let error = layout
+            .step(&mut index, &executor, &mut [0.0; 2])
+            .unwrap_err();
+        assert_eq!(error.code(), "invalid_input");
+        assert_eq!(index.position("a").unwrap().x, a.x);
+        assert_eq!(
+            ForceLayout::new("{\"theta\": \"wide\"}")
+                .unwrap_err()
+                .code(),
+            "malformed"
+        );
+        let nested = format!("{}{}", "[".repeat(100), "]".repeat(100));
+        assert_eq!(
+            ForceLayout::new(&nested).unwrap_err().code(),
+            "capacity_exceeded"
+        );
+    }
+
+    #[test]
+    fn test_layout_config_validated() {
+        for config in [
+            "{\"damping\": 1.5}",
+            "{\"max_step\": 0}",
+            "{\"theta\": -1}",
+            "{\"repulsion\": -500}",
+        ] {
+            assert_eq!(
+                ForceLayout::new(config).unwrap_err().code(),
+                "invalid_input",
+                "{}",
+                config
+            );
+        }
+        let config = LayoutConfig {
+            spring_length: f64::NAN,
+            ..LayoutConfig::default()
+        };
+        assert_eq!(config.validate().unwrap_err().code(), "invalid_input");
+        assert!(ForceLayout::with_config(config).is_err());
+        assert!(ForceLayout::new("{}").is_ok());
+    }
+
+    #[test]
+    fn test_overflowing_step_moves_nothing() {
+        let mut index = index(&[
+            ("overflow-a", 400.0, 400.0),
+            ("overflow-b", 400.0, 400.0),
+            ("overflow-c", 600.0, 600.0),
+        ]);
+        let executor = WASMEdgeExecutor::new();
+        let mut layout = ForceLayout::with_config(LayoutConfig {
+            repulsion: f64::MAX,
+            ..LayoutConfig::default()
+        })
+        .unwrap();
+        let mut positions = vec![0.0; ForceLayout::positions_length()];
+
+        let error = layout
+            .step(&mut index, &executor, &mut positions)
+            .unwrap_err();
+        assert_eq!(error.code(), "invalid_input");
+        for (name, x) in [("overflow-a", 400.0), ("overflow-b", 400.0), ("overflow-c", 600.0)] {
+            assert_eq!(index.position(name).unwrap().x, x, "{}", name);
+        }
+        assert!(positions.iter().all(|&p| p == 0.0));
+        assert!(layout.velocities.is_empty());
+    }
+
+    #[test]
+    fn test_barnes_hut_matches_exact() {
+        let nodes: Vec<(String, f64, f64)> = (0..100)
+            .map(|i| {
+                let (x, y) = (
+                    (i % 10) as f64 * 40.0 + 300.0,
+                    (i / 10) as f64 * 40.0 + 300.0,
+                );
+                (format!("grid{}", i), x, y)
+            })
+            .collect();
+        let nodes: Vec<_> = nodes.iter().map(|(n, x, y)| (n.as_str(), *x, *y)).collect();
+        let executor = WASMEdgeExecutor::new();
+        let mut positions = vec![0.0; 0];
+        let mut run = |theta| {
+            let mut index = index(&nodes);
+            positions.resize(ForceLayout::positions_length(), 0.0);
+            let config = LayoutConfig {
+                theta,
+                max_step: 1000.0,
+                ..LayoutConfig::default()
+            };
+            ForceLayout::with_config(config).unwrap()
+                .step(&mut index, &executor, &mut positions)
+                .unwrap();
+            nodes
+                .iter()
+                .map(|(name, _, _)| index.position(name).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let exact = run(0.0);
+        let approximate = run(0.8);
+        for (node, (exact, approximate)) in nodes.iter().zip(exact.iter().zip(&approximate)) {
+            let moved = ((exact.x - node.1).powi(2) + (exact.y - node.2).powi(2)).sqrt();
+            let error =
+                ((exact.x - approximate.x).powi(2) + (exact.y - approximate.y).powi(2)).sqrt();
+            assert!(
+                error <= 0.1 * moved + 0.01,
+                "{}: off by {} of {}",
+                node.0,
+                error,
+                moved
+            );
+        }
+    }
+}
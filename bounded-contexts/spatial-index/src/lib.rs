@@ -1,8 +1,36 @@
+use harmony_error::{limits, wire, HarmonyError, ResultExt};
+use harmony_trace::Level;
+#[cfg(feature = "wasm")]
+use serde_wasm_bindgen::Serializer;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+#[cfg(feature = "layout")]
+mod layout;
+#[cfg(feature = "testing")]
+mod testing;
+mod transform;
+mod tuning;
+mod viewport;
+#[cfg(feature = "testing")]
+pub use testing::{generate_positions, PositionDistribution, PositionSpec};
+#[cfg(feature = "layout")]
+pub use layout::{ForceLayout, LayoutConfig};
+pub use transform::Transform;
+pub use tuning::{TuneReport, QUERY_LOG_SIZE};
+use tuning::QueryLog;
+pub use viewport::{Cluster, ViewportResult, CLUSTER_CELL_PIXELS, DEFAULT_LOD_THRESHOLD};
+
+/// harmony-trace target of the index's query spans
+const TRACE_TARGET: &str = "spatial-index";
 
 /// Point in 2D space with coordinates
+#[cfg_attr(feature = "wasm", derive(Tsify))]
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Point {
     pub x: f64,
@@ -10,6 +38,7 @@ pub struct Point {
 }
 
 /// Bounding box for spatial queries
+#[cfg_attr(feature = "wasm", derive(Tsify))]
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct BoundingBox {
     pub min_x: f64,
@@ -37,6 +66,7 @@ impl BoundingBox {
 }
 
 /// Node with spatial coordinates
+#[cfg_attr(feature = "wasm", derive(Tsify))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpatialNode {
     pub id: String,
@@ -147,6 +177,25 @@ impl QuadTreeNode {
         false
     }
 
+    /// Removes the node with `id` stored at `position`
+    fn remove(&mut self, id: &str, position: &Point) -> Option<SpatialNode> {
+        if !self.bounds.contains(position) {
+            return None;
+        }
+        if let Some(index) = self.nodes.iter().position(|node| node.id == id) {
+            return Some(self.nodes.remove(index));
+        }
+        [
+            &mut self.northeast,
+            &mut self.northwest,
+            &mut self.southeast,
+            &mut self.southwest,
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|child| child.remove(id, position))
+    }
+
     fn query(&self, range: &BoundingBox, found: &mut Vec<SpatialNode>) {
         if !self.bounds.intersects(range) {
             return;
@@ -213,17 +262,120 @@ impl QuadTreeNode {
     }
 }
 
+/// Squared distance from a point to the nearest point of a box (0 inside)
+fn box_distance_squared(bounds: &BoundingBox, point: &Point) -> f64 {
+    let dx = (bounds.min_x - point.x).max(point.x - bounds.max_x).max(0.0);
+    let dy = (bounds.min_y - point.y).max(point.y - bounds.max_y).max(0.0);
+    dx * dx + dy * dy
+}
+
+/// Quadtree cell or stored node waiting in a `NearestNodes` queue
+enum Candidate<'a> {
+    Cell(&'a QuadTreeNode),
+    Node(&'a SpatialNode),
+}
+
+/// Queue entry, ordered nearest first, then first queued first
+struct Queued<'a> {
+    distance_squared: f64,
+    order: usize,
+    candidate: Candidate<'a>,
+}
+
+impl PartialEq for Queued<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued<'_> {}
+
+impl PartialOrd for Queued<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued<'_> {
+    // Reversed: BinaryHeap pops the greatest
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance_squared
+            .total_cmp(&self.distance_squared)
+            .then(other.order.cmp(&self.order))
+    }
+}
+
+/// Stored nodes in order of distance from a point, found best-first: a
+/// quadtree cell is only opened once it could hold the next nearest node,
+/// so taking a few candidates reads a few cells
+struct NearestNodes<'a> {
+    point: Point,
+    queue: BinaryHeap<Queued<'a>>,
+    queued: usize,
+}
+
+impl<'a> NearestNodes<'a> {
+    fn new(root: &'a QuadTreeNode, point: Point) -> Self {
+        let mut nearest = NearestNodes {
+            point,
+            queue: BinaryHeap::new(),
+            queued: 0,
+        };
+        nearest.push(box_distance_squared(&root.bounds, &point), Candidate::Cell(root));
+        nearest
+    }
+
+    fn push(&mut self, distance_squared: f64, candidate: Candidate<'a>) {
+        self.queue.push(Queued {
+            distance_squared,
+            order: self.queued,
+            candidate,
+        });
+        self.queued += 1;
+    }
+}
+
+impl<'a> Iterator for NearestNodes<'a> {
+    type Item = &'a SpatialNode;
+
+    fn next(&mut self) -> Option<&'a SpatialNode> {
+        while let Some(queued) = self.queue.pop() {
+            let cell = match queued.candidate {
+                Candidate::Node(node) => return Some(node),
+                Candidate::Cell(cell) => cell,
+            };
+            for node in &cell.nodes {
+                let dx = node.position.x - self.point.x;
+                let dy = node.position.y - self.point.y;
+                self.push(dx * dx + dy * dy, Candidate::Node(node));
+            }
+            for child in [&cell.northeast, &cell.northwest, &cell.southeast, &cell.southwest]
+                .into_iter()
+                .flatten()
+            {
+                self.push(box_distance_squared(&child.bounds, &self.point), Candidate::Cell(child));
+            }
+        }
+        None
+    }
+}
+
 /// Spatial index using quadtree for efficient spatial queries
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct SpatialIndex {
     root: QuadTreeNode,
     node_lookup: HashMap<String, Point>,
+    queries: QueryLog,
+    lod_threshold: usize,
+    /// World-to-screen matrix and its inverse
+    transform: Option<(Transform, Transform)>,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl SpatialIndex {
     /// Create a new spatial index with given bounds and capacity per node
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new(min_x: f64, min_y: f64, max_x: f64, max_y: f64, capacity: usize) -> Self {
         let bounds = BoundingBox {
             min_x,
@@ -234,51 +386,320 @@ impl SpatialIndex {
         SpatialIndex {
             root: QuadTreeNode::new(bounds, capacity),
             node_lookup: HashMap::new(),
+            queries: QueryLog::default(),
+            lod_threshold: DEFAULT_LOD_THRESHOLD,
+            transform: None,
         }
     }
 
-    /// Insert a node with coordinates into the spatial index
-    pub fn insert(&mut self, id: String, x: f64, y: f64, metadata_json: String) -> bool {
-        let metadata: HashMap<String, String> = serde_json::from_str(&metadata_json).unwrap_or_default();
+    /// Create a spatial index with room for `expected_nodes` nodes, so bulk
+    /// inserts do not rehash the ID lookup
+    ///
+    /// # Returns
+    /// `capacity_exceeded` if the memory cannot be reserved
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = withCapacity))]
+    pub fn with_capacity(
+        min_x: f64,
+        min_y: f64,
+        max_x: f64,
+        max_y: f64,
+        capacity: usize,
+        expected_nodes: usize,
+    ) -> Result<SpatialIndex, HarmonyError> {
+        let mut index = Self::new(min_x, min_y, max_x, max_y, capacity);
+        index.reserve(expected_nodes)?;
+        Ok(index)
+    }
+
+    /// Reserve room for `additional` more nodes
+    ///
+    /// # Returns
+    /// `capacity_exceeded` if the memory cannot be reserved
+    pub fn reserve(&mut self, additional: usize) -> Result<(), HarmonyError> {
+        self.node_lookup.try_reserve(additional).map_err(|error| {
+            HarmonyError::CapacityExceeded(format!("Cannot reserve {} nodes: {}", additional, error))
+        })
+    }
+
+    /// Insert a node with coordinates into the spatial index, replacing
+    /// any node with the same ID
+    ///
+    /// The ID is interned into the module's node ID map (harmony-ids), so
+    /// `node_id` gives the edge executor's u32 for it.
+    ///
+    /// # Arguments
+    /// * `metadata_json` - JSON object of string values
+    ///
+    /// # Returns
+    /// false if the point is outside the index bounds; an error for
+    /// non-finite coordinates, or malformed or oversized metadata
+    pub fn insert(&mut self, id: String, x: f64, y: f64, metadata_json: String) -> Result<bool, HarmonyError> {
+        limits::check_json(&metadata_json)
+            .with_context(|| format!("Invalid metadata for node {}", id))?;
+        let metadata: HashMap<String, String> = serde_json::from_str(&metadata_json)
+            .with_context(|| format!("Invalid metadata for node {}", id))?;
+        self.insert_node(id, x, y, metadata)
+    }
+
+    /// Query nodes within a bounding box
+    pub fn query_range(&self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> String {
+        let found = self.range(min_x, min_y, max_x, max_y);
+        serde_json::to_string(&found).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Query nodes within a radius from a center point
+    pub fn query_radius(&self, center_x: f64, center_y: f64, radius: f64) -> String {
+        let found = self.radius(center_x, center_y, radius);
+        serde_json::to_string(&found).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Find k-nearest neighbors to a point
+    pub fn query_nearest(&self, x: f64, y: f64, k: usize) -> String {
+        let nearest = self.nearest(x, y, k);
+        serde_json::to_string(&nearest).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Get position of a node by ID
+    pub fn get_position(&self, id: String) -> String {
+        if let Some(pos) = self.node_lookup.get(&id) {
+            serde_json::to_string(pos).unwrap_or_else(|_| "null".to_string())
+        } else {
+            "null".to_string()
+        }
+    }
+
+    /// Remove a node by ID
+    ///
+    /// # Returns
+    /// false if no node has the ID
+    pub fn remove(&mut self, id: String) -> bool {
+        self.remove_node(&id).is_some()
+    }
+
+    /// Move a node, keeping its metadata
+    ///
+    /// # Returns
+    /// false, leaving the node where it was, if the new point is outside
+    /// the index bounds; `not_found` for an unknown ID and `invalid_input`
+    /// for non-finite coordinates
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = moveNode))]
+    pub fn move_node(&mut self, id: String, x: f64, y: f64) -> Result<bool, HarmonyError> {
+        if self.position(&id).is_none() {
+            return Err(HarmonyError::NotFound(format!("Unknown spatial node {}", id)));
+        }
+        check_finite(&id, x, y)?;
+        let point = Point { x, y };
+        if !self.root.bounds.contains(&point) {
+            return Ok(false);
+        }
+        let Some(mut node) = self.remove_node(&id) else {
+            return Ok(false);
+        };
+        node.position = point;
+        let inserted = self.root.insert(node);
+        if inserted {
+            self.node_lookup.insert(id, point);
+        }
+        Ok(inserted)
+    }
+
+    /// Get total number of indexed nodes
+    pub fn size(&self) -> usize {
+        self.node_lookup.len()
+    }
+
+    /// Clear all nodes from the index
+    pub fn clear(&mut self) {
+        let bounds = self.root.bounds;
+        let capacity = self.root.capacity;
+        self.root = QuadTreeNode::new(bounds, capacity);
+        self.node_lookup.clear();
+    }
+}
+
+/// Encoded versions of the JSON methods: the same data as bytes in a
+/// harmony-error `wire` format ("json", "cbor" or "msgpack")
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl SpatialIndex {
+    /// Insert a node with encoded metadata, a map of string values
+    ///
+    /// # Returns
+    /// false if the point is outside the index bounds; an error for
+    /// non-finite coordinates, or malformed or oversized metadata
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = insertEncoded))]
+    pub fn insert_encoded(&mut self, id: String, x: f64, y: f64, metadata: &[u8], format: &str) -> Result<bool, HarmonyError> {
+        let metadata: HashMap<String, String> = wire::decode_as(metadata, format)
+            .with_context(|| format!("Invalid metadata for node {}", id))?;
+        self.insert_node(id, x, y, metadata)
+    }
+
+    /// Query nodes within a bounding box, as an encoded array
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = queryRangeEncoded))]
+    pub fn query_range_encoded(&self, min_x: f64, min_y: f64, max_x: f64, max_y: f64, format: &str) -> Result<Vec<u8>, HarmonyError> {
+        wire::encode_as(&self.range(min_x, min_y, max_x, max_y), format)
+    }
+
+    /// Query nodes within a radius from a center point, as an encoded array
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = queryRadiusEncoded))]
+    pub fn query_radius_encoded(&self, center_x: f64, center_y: f64, radius: f64, format: &str) -> Result<Vec<u8>, HarmonyError> {
+        wire::encode_as(&self.radius(center_x, center_y, radius), format)
+    }
+
+    /// Find k-nearest neighbors to a point, as an encoded array
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = queryNearestEncoded))]
+    pub fn query_nearest_encoded(&self, x: f64, y: f64, k: usize, format: &str) -> Result<Vec<u8>, HarmonyError> {
+        wire::encode_as(&self.nearest(x, y, k), format)
+    }
+
+    /// Get position of a node by ID, encoded; null if not found
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getPositionEncoded))]
+    pub fn get_position_encoded(&self, id: String, format: &str) -> Result<Vec<u8>, HarmonyError> {
+        wire::encode_as(&self.node_lookup.get(&id), format)
+    }
+}
+
+/// JS value versions of the JSON methods, converted with serde-wasm-bindgen
+/// instead of going through JSON strings and typed with the generated
+/// TypeScript declarations
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl SpatialIndex {
+    /// Insert a node with a metadata object of string values
+    ///
+    /// # Returns
+    /// false if the point is outside the index bounds; an error for
+    /// non-finite coordinates or malformed metadata
+    #[wasm_bindgen(js_name = insertValue)]
+    pub fn insert_value(
+        &mut self,
+        id: String,
+        x: f64,
+        y: f64,
+        #[wasm_bindgen(unchecked_param_type = "Record<string, string> | undefined")] metadata: JsValue,
+    ) -> Result<bool, HarmonyError> {
+        let metadata: HashMap<String, String> = if metadata.is_undefined() {
+            HashMap::new()
+        } else {
+            serde_wasm_bindgen::from_value(metadata)
+                .with_context(|| format!("Invalid metadata for node {}", id))?
+        };
+        self.insert_node(id, x, y, metadata)
+    }
+
+    /// Query nodes within a bounding box
+    #[wasm_bindgen(js_name = queryRangeValue, unchecked_return_type = "SpatialNode[]")]
+    pub fn query_range_value(&self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Result<JsValue, HarmonyError> {
+        let found = self.range(min_x, min_y, max_x, max_y);
+        Ok(found.serialize(&Serializer::json_compatible())?)
+    }
+
+    /// Query nodes within a radius from a center point
+    #[wasm_bindgen(js_name = queryRadiusValue, unchecked_return_type = "SpatialNode[]")]
+    pub fn query_radius_value(&self, center_x: f64, center_y: f64, radius: f64) -> Result<JsValue, HarmonyError> {
+        let found = self.radius(center_x, center_y, radius);
+        Ok(found.serialize(&Serializer::json_compatible())?)
+    }
+
+    /// Find k-nearest neighbors to a point
+    #[wasm_bindgen(js_name = queryNearestValue, unchecked_return_type = "SpatialNode[]")]
+    pub fn query_nearest_value(&self, x: f64, y: f64, k: usize) -> Result<JsValue, HarmonyError> {
+        let nearest = self.nearest(x, y, k);
+        Ok(nearest.serialize(&Serializer::json_compatible())?)
+    }
+
+    /// Get position of a node by ID, or null
+    #[wasm_bindgen(js_name = getPositionValue, unchecked_return_type = "Point | null")]
+    pub fn get_position_value(&self, id: String) -> Result<JsValue, HarmonyError> {
+        let position = self.node_lookup.get(&id);
+        Ok(position.serialize(&Serializer::json_compatible())?)
+    }
+}
+
+fn check_finite(id: &str, x: f64, y: f64) -> Result<(), HarmonyError> {
+    if !x.is_finite() || !y.is_finite() {
+        return Err(HarmonyError::InvalidInput(format!(
+            "Node {} has non-finite coordinates ({}, {})",
+            id, x, y
+        )));
+    }
+    Ok(())
+}
+
+/// Typed versions of the JSON methods, for native callers
+impl SpatialIndex {
+    /// Insert a node; see `insert`
+    pub fn insert_node(
+        &mut self,
+        id: String,
+        x: f64,
+        y: f64,
+        metadata: HashMap<String, String>,
+    ) -> Result<bool, HarmonyError> {
+        check_finite(&id, x, y)?;
         let node = SpatialNode {
             id: id.clone(),
             position: Point { x, y },
             metadata,
         };
+        if !self.root.bounds.contains(&node.position) {
+            return Ok(false);
+        }
+        harmony_ids::intern(&id)?;
 
-        let result = self.root.insert(node);
-        if result {
+        // Replaces a node with the same ID
+        self.remove_node(&id);
+        let inserted = self.root.insert(node);
+        if inserted {
             self.node_lookup.insert(id, Point { x, y });
         }
-        result
+        Ok(inserted)
     }
 
-    /// Query nodes within a bounding box
-    pub fn query_range(&self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> String {
+    /// Remove a node by ID; see `remove`
+    pub fn remove_node(&mut self, id: &str) -> Option<SpatialNode> {
+        let position = self.node_lookup.remove(id)?;
+        self.root.remove(id, &position)
+    }
+
+    /// Position of a node by ID
+    pub fn position(&self, id: &str) -> Option<Point> {
+        self.node_lookup.get(id).copied()
+    }
+
+    /// Nodes within a bounding box
+    pub fn range(&self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Vec<SpatialNode> {
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "query_range");
         let range = BoundingBox {
             min_x,
             min_y,
             max_x,
             max_y,
         };
+        self.queries.record(range);
         let mut found = Vec::new();
         self.root.query(&range, &mut found);
-        serde_json::to_string(&found).unwrap_or_else(|_| "[]".to_string())
+        span.field("results", found.len());
+        found
     }
 
-    /// Query nodes within a radius from a center point
-    pub fn query_radius(&self, center_x: f64, center_y: f64, radius: f64) -> String {
+    /// Nodes within a radius from a center point
+    pub fn radius(&self, center_x: f64, center_y: f64, radius: f64) -> Vec<SpatialNode> {
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "query_radius");
         let center = Point {
             x: center_x,
             y: center_y,
         };
+        self.queries.record(tuning::square(center, radius));
         let mut found = Vec::new();
         self.root.query_radius(&center, radius, &mut found);
-        serde_json::to_string(&found).unwrap_or_else(|_| "[]".to_string())
+        span.field("results", found.len());
+        found
     }
 
-    /// Find k-nearest neighbors to a point
-    pub fn query_nearest(&self, x: f64, y: f64, k: usize) -> String {
+    /// The k nodes nearest a point, closest first
+    pub fn nearest(&self, x: f64, y: f64, k: usize) -> Vec<SpatialNode> {
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "query_nearest");
+        span.field("k", k);
         let point = Point { x, y };
         let mut all_nodes = Vec::new();
         
@@ -293,31 +714,40 @@ impl SpatialIndex {
             dist_a.partial_cmp(&dist_b).unwrap_or(std::cmp::Ordering::Equal)
         });
 
+        span.field("candidates", all_nodes.len());
+
         // Take k nearest
         let nearest: Vec<SpatialNode> = all_nodes.into_iter().take(k).collect();
-        serde_json::to_string(&nearest).unwrap_or_else(|_| "[]".to_string())
-    }
-
-    /// Get position of a node by ID
-    pub fn get_position(&self, id: String) -> String {
-        if let Some(pos) = self.node_lookup.get(&id) {
-            serde_json::to_string(pos).unwrap_or_else(|_| "null".to_string())
-        } else {
-            "null".to_string()
-        }
+        self.queries.record_nearest(point, &nearest);
+        nearest
     }
 
-    /// Get total number of indexed nodes
-    pub fn size(&self) -> usize {
-        self.node_lookup.len()
-    }
-
-    /// Clear all nodes from the index
-    pub fn clear(&mut self) {
-        let bounds = self.root.bounds;
-        let capacity = self.root.capacity;
-        self.root = QuadTreeNode::new(bounds, capacity);
-        self.node_lookup.clear();
+    /// The k nodes nearest a point that `accept` lets through, closest
+    /// first
+    ///
+    /// Candidates are read from the quadtree in order of distance until k
+    /// are accepted, so a selective filter (e.g. graph reachability, see
+    /// harmony-worker-protocol's `queryNearestReachable`) costs only the
+    /// candidates it rejects, with no search radius to guess.
+    pub fn nearest_where(
+        &self,
+        x: f64,
+        y: f64,
+        k: usize,
+        mut accept: impl FnMut(&SpatialNode) -> bool,
+    ) -> Vec<SpatialNode> {
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "query_nearest_where");
+        span.field("k", k);
+        let mut candidates = 0;
+        let found: Vec<SpatialNode> = NearestNodes::new(&self.root, Point { x, y })
+            .inspect(|_| candidates += 1)
+            .filter(|node| accept(node))
+            .take(k)
+            .cloned()
+            .collect();
+        span.field("candidates", candidates);
+        self.queries.record_nearest(Point { x, y }, &found);
+        found
     }
 }
 
@@ -337,24 +767,161 @@ mod tests {
         assert!(!bbox.contains(&Point { x: 150.0, y: 50.0 }));
     }
 
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SpatialIndex>();
+    }
+
     #[test]
     fn test_spatial_index_insert_and_query() {
         let mut index = SpatialIndex::new(0.0, 0.0, 1000.0, 1000.0, 4);
-        assert!(index.insert("node1".to_string(), 100.0, 100.0, "{}".to_string()));
-        assert!(index.insert("node2".to_string(), 200.0, 200.0, "{}".to_string()));
+        assert!(index.insert("node1".to_string(), 100.0, 100.0, "{}".to_string()).unwrap());
+        assert!(index.insert("node2".to_string(), 200.0, 200.0, "{}".to_string()).unwrap());
+        assert!(!index.insert("node3".to_string(), 2000.0, 0.0, "{}".to_string()).unwrap());
         
         let result = index.query_range(50.0, 50.0, 150.0, 150.0);
         assert!(result.contains("node1"));
     }
 
+    #[test]
+    fn test_remove_and_move() {
+        let mut index = SpatialIndex::new(0.0, 0.0, 1000.0, 1000.0, 1);
+        for (i, x) in [10.0, 20.0, 600.0, 700.0].into_iter().enumerate() {
+            index
+                .insert(format!("node{}", i), x, x, format!(r#"{{"n": "{}"}}"#, i))
+                .unwrap();
+        }
+        // Re-inserting replaces instead of duplicating
+        index.insert("node0".to_string(), 15.0, 15.0, "{}".to_string()).unwrap();
+        assert_eq!(index.size(), 4);
+        assert_eq!(index.range(0.0, 0.0, 1000.0, 1000.0).len(), 4);
+
+        assert!(index.move_node("node3".to_string(), 5.0, 5.0).unwrap());
+        let moved = index.nearest(0.0, 0.0, 1);
+        assert_eq!(moved[0].id, "node3");
+        assert_eq!(moved[0].metadata["n"], "3");
+        assert!(!index.move_node("node3".to_string(), 5000.0, 5.0).unwrap());
+        let position = index.position("node3").unwrap();
+        assert_eq!((position.x, position.y), (5.0, 5.0));
+        let error = index.move_node("node9".to_string(), 1.0, 1.0).unwrap_err();
+        assert_eq!(error.code(), "not_found");
+
+        assert_eq!(
+            harmony_ids::node_names(vec![harmony_ids::node_id("node3").unwrap()]).unwrap(),
+            ["node3"]
+        );
+
+        assert!(index.remove("node2".to_string()));
+        assert!(!index.remove("node2".to_string()));
+        assert_eq!(index.size(), 3);
+        assert!(index.radius(600.0, 600.0, 1.0).is_empty());
+    }
+
     #[test]
     fn test_query_radius() {
         let mut index = SpatialIndex::new(0.0, 0.0, 1000.0, 1000.0, 4);
-        index.insert("node1".to_string(), 100.0, 100.0, "{}".to_string());
-        index.insert("node2".to_string(), 200.0, 200.0, "{}".to_string());
+        index.insert("node1".to_string(), 100.0, 100.0, "{}".to_string()).unwrap();
+        index.insert("node2".to_string(), 200.0, 200.0, "{}".to_string()).unwrap();
         
         let result = index.query_radius(100.0, 100.0, 50.0);
         assert!(result.contains("node1"));
         assert!(!result.contains("node2"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_nearest_where() {
+        let mut index = SpatialIndex::new(0.0, 0.0, 10000.0, 10000.0, 2);
+        for i in 0..300u32 {
+            // Scattered, with distinct distances from the query point
+            let (x, y) = ((i * 7919 % 10000) as f64, (i * 104729 % 10000) as f64 + 0.5);
+            index.insert(format!("node{}", i), x, y, "{}".to_string()).unwrap();
+        }
+        let distance = |node: &SpatialNode| {
+            (node.position.x - 5000.0).powi(2) + (node.position.y - 5000.0).powi(2)
+        };
+        let mut expected = index.range(0.0, 0.0, 10000.0, 10000.0);
+        expected.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+        let ids = |nodes: &[SpatialNode]| nodes.iter().map(|node| node.id.clone()).collect::<Vec<_>>();
+
+        let all = index.nearest_where(5000.0, 5000.0, 25, |_| true);
+        assert_eq!(ids(&all), ids(&expected[..25]));
+        let is_odd = |node: &SpatialNode| node.id.ends_with(['1', '3', '5', '7', '9']);
+        let odd = index.nearest_where(5000.0, 5000.0, 5, is_odd);
+        let expected_odd: Vec<SpatialNode> = expected
+            .iter()
+            .filter(|node| is_odd(node))
+            .take(5)
+            .cloned()
+            .collect();
+        assert_eq!(ids(&odd), ids(&expected_odd));
+
+        // No search radius: the one accepted node can be anywhere
+        let farthest = &expected[expected.len() - 1];
+        let far = index.nearest_where(0.0, 0.0, 3, |node| node.id == farthest.id);
+        assert_eq!(ids(&far), vec![farthest.id.clone()]);
+        assert!(index.nearest_where(0.0, 0.0, 0, |_| true).is_empty());
+    }
+
+    #[test]
+    fn test_insert_errors() {
+        let mut index = SpatialIndex::new(0.0, 0.0, 1000.0, 1000.0, 4);
+        let error = index
+            .insert("node1".to_string(), 1.0, 1.0, r#"{"count": 3}"#.to_string())
+            .unwrap_err();
+        assert_eq!(error.code(), "malformed");
+        assert_eq!(error.context(), vec!["Invalid metadata for node node1"]);
+
+        let error = index
+            .insert("node2".to_string(), f64::NAN, 1.0, "{}".to_string())
+            .unwrap_err();
+        assert_eq!(error.code(), "invalid_input");
+        assert_eq!(index.size(), 0);
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let mut index = SpatialIndex::with_capacity(0.0, 0.0, 1000.0, 1000.0, 4, 500).unwrap();
+        assert!(index.node_lookup.capacity() >= 500);
+        assert!(index.insert("node1".to_string(), 1.0, 1.0, "{}".to_string()).unwrap());
+        let error = index.reserve(usize::MAX).unwrap_err();
+        assert_eq!(error.code(), "capacity_exceeded");
+    }
+
+    #[test]
+    fn test_encoded_methods() {
+        let mut index = SpatialIndex::new(0.0, 0.0, 1000.0, 1000.0, 4);
+        for format in ["json", "cbor", "msgpack"] {
+            let metadata = wire::encode_as(&HashMap::from([("kind", "gain")]), format).unwrap();
+            assert!(index.insert_encoded(format.to_string(), 100.0, 100.0, &metadata, format).unwrap());
+
+            let bytes = index.query_radius_encoded(100.0, 100.0, 1.0, format).unwrap();
+            let found: Vec<SpatialNode> = wire::decode_as(&bytes, format).unwrap();
+            assert_eq!(found.len(), index.size());
+            assert_eq!(found[0].metadata["kind"], "gain");
+
+            let bytes = index.get_position_encoded("missing".to_string(), format).unwrap();
+            assert!(wire::decode_as::<Option<Point>>(&bytes, format).unwrap().is_none());
+        }
+        assert_eq!(
+            index.query_range_encoded(0.0, 0.0, 500.0, 500.0, "json").unwrap(),
+            index.query_range(0.0, 0.0, 500.0, 500.0).into_bytes()
+        );
+
+        let error = index
+            .insert_encoded("node".to_string(), 1.0, 1.0, &[0xc1], "msgpack")
+            .unwrap_err();
+        assert_eq!(error.code(), "malformed");
+        assert_eq!(error.context(), vec!["Invalid metadata for node node"]);
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_typescript_declarations() {
+        assert_eq!(
+            SpatialNode::DECL,
+            "export interface SpatialNode {\n    id: string;\n    position: Point;\n    \
+             metadata: Record<string, string>;\n}"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Synthetic node positions for tests and benchmarks (`testing` feature)
+//!
+//! `generate_positions` places nodes in a bounding box from a
+//! `PositionSpec`; the same spec and seed give the same positions on every
+//! platform. Nodes are named `id_prefix` followed by `0..count`, so they
+//! line up with documents from full-text-index's generator. Layouts:
+//! - `uniform`: spread evenly over the box
+//! - `clustered`: normally distributed around `clusters` random centres,
+//!   like groups of components on a canvas; points are clamped to the box
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#spatial-index
+
+use harmony_error::{limits, HarmonyError, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{BoundingBox, Point, SpatialIndex, SpatialNode};
+
+/// How generated nodes are laid out
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionDistribution {
+    #[default]
+    Uniform,
+    Clustered,
+}
+
+/// Shape of a generated node set
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionSpec {
+    /// Number of nodes
+    pub count: usize,
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub distribution: PositionDistribution,
+    /// Number of clusters for `clustered`
+    #[serde(default = "default_clusters")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub clusters: u32,
+    /// Standard deviation of a cluster, as a fraction of the box size
+    #[serde(default = "default_spread")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub spread: f64,
+    /// Prefix of the node IDs
+    #[serde(default = "default_id_prefix")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub id_prefix: String,
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub seed: u64,
+}
+
+fn default_clusters() -> u32 {
+    8
+}
+
+fn default_spread() -> f64 {
+    0.05
+}
+
+fn default_id_prefix() -> String {
+    "node-".to_string()
+}
+
+/// Random nodes following `spec`, inside `bounds`
+///
+/// # Returns
+/// `invalid_input` for a clustered spec without clusters or with a
+/// negative or non-finite spread, or an empty or non-finite box;
+/// `capacity_exceeded` above `max_edges_per_batch` nodes
+pub fn generate_positions(
+    bounds: &BoundingBox,
+    spec: &PositionSpec,
+) -> Result<Vec<SpatialNode>, HarmonyError> {
+    let BoundingBox {
+        min_x,
+        min_y,
+        max_x,
+        max_y,
+    } = *bounds;
+    let (width, height) = (max_x - min_x, max_y - min_y);
+    if !width.is_finite() || !height.is_finite() || width < 0.0 || height < 0.0 {
+        return Err(HarmonyError::InvalidInput(
+            "Positions need a finite, non-empty bounding box".to_string(),
+        ));
+    }
+    if spec.distribution == PositionDistribution::Clustered
+        && (spec.clusters == 0 || !spec.spread.is_finite() || spec.spread < 0.0)
+    {
+        return Err(HarmonyError::InvalidInput(
+            "Clustered positions need clusters and a non-negative spread".to_string(),
+        ));
+    }
+    limits::check(
+        "Generated node count",
+        spec.count,
+        limits::limits().max_edges_per_batch,
+    )?;
+
+    let mut rng = fastrand::Rng::with_seed(spec.seed);
+    let uniform = |rng: &mut fastrand::Rng| Point {
+        x: min_x + rng.f64() * width,
+        y: min_y + rng.f64() * height,
+    };
+    let centres: Vec<Point> = match spec.distribution {
+        PositionDistribution::Uniform => Vec::new(),
+        PositionDistribution::Clustered => (0..spec.clusters).map(|_| uniform(&mut rng)).collect(),
+    };
+    let mut nodes = Vec::with_capacity(spec.count);
+    for i in 0..spec.count {
+        let position = match spec.distribution {
+            PositionDistribution::Uniform => uniform(&mut rng),
+            PositionDistribution::Clustered => {
+                let centre = centres[rng.usize(0..centres.len())];
+                let (dx, dy) = gaussian_pair(&mut rng);
+                Point {
+                    x: (centre.x + dx * spec.spread * width).clamp(min_x, max_x),
+                    y: (centre.y + dy * spec.spread * height).clamp(min_y, max_y),
+                }
+            }
+        };
+        nodes.push(SpatialNode {
+            id: format!("{}{}", spec.id_prefix, i),
+            position,
+            metadata: HashMap::new(),
+        });
+    }
+    Ok(nodes)
+}
+
+/// Two independent standard normal samples (Box–Muller)
+fn gaussian_pair(rng: &mut fastrand::Rng) -> (f64, f64) {
+    // 1 - f64() is in (0, 1], keeping ln finite
+    let radius = (-2.0 * (1.0 - rng.f64()).ln()).sqrt();
+    let angle = std::f64::consts::TAU * rng.f64();
+    (radius * angle.cos(), radius * angle.sin())
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl SpatialIndex {
+    /// Inserts random nodes for tests and benchmarks, replacing nodes with
+    /// the same IDs
+    ///
+    /// # Arguments
+    /// * `spec_json` - JSON PositionSpec:
+    ///   `{"count", "distribution"?, "clusters"?, "spread"?, "id_prefix"?, "seed"?}`
+    ///
+    /// # Returns
+    /// Number of nodes inserted
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = generateNodes))]
+    pub fn generate_nodes(&mut self, spec_json: &str) -> Result<usize, HarmonyError> {
+        limits::check_json(spec_json).context("Invalid position spec")?;
+        let spec: PositionSpec =
+            serde_json::from_str(spec_json).context("Invalid position spec")?;
+        let nodes =
+            generate_positions(&self.root.bounds, &spec).context("Invalid position spec")?;
+        self.reserve(nodes.len())?;
+        let mut inserted = 0;
+        for node in nodes {
+            if self.insert_node(node.id, node.position.x, node.position.y, node.metadata)? {
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: BoundingBox = BoundingBox {
+        min_x: 0.0,
+        min_y: 0.0,
+        max_x: 1000.0,
+        max_y: 1000.0,
+    };
+
+    fn spec(distribution: PositionDistribution, seed: u64) -> PositionSpec {
+        PositionSpec {
+            count: 1000,
+            distribution,
+            clusters: 4,
+            spread: 0.02,
+            id_prefix: default_id_prefix(),
+            seed,
+        }
+    }
+
+    fn positions(nodes: &[SpatialNode]) -> Vec<(f64, f64)> {
+        nodes
+            .iter()
+            .map(|node| (node.position.x, node.position.y))
+            .collect()
+    }
+
+    /// Occupied cells of a 10x10 grid over BOUNDS
+    fn occupied_cells(nodes: &[SpatialNode]) -> usize {
+        let mut cells = std::collections::HashSet::new();
+        for node in nodes {
+            cells.insert((
+                (node.position.x / 100.0) as u32,
+                (node.position.y / 100.0) as u32,
+            ));
+        }
+        cells.len()
+    }
+
+    #[test]
+    fn test_generated_positions() {
+        let uniform = generate_positions(&BOUNDS, &spec(PositionDistribution::Uniform, 3)).unwrap();
+        assert_eq!(uniform.len(), 1000);
+        assert_eq!(
+            (uniform[0].id.as_str(), uniform[999].id.as_str()),
+            ("node-0", "node-999")
+        );
+        assert_eq!(
+            positions(&uniform),
+            positions(
+                &generate_positions(&BOUNDS, &spec(PositionDistribution::Uniform, 3)).unwrap()
+            )
+        );
+        assert!(uniform.iter().all(|node| BOUNDS.contains(&node.position)));
+
+        let clustered =
+            generate_positions(&BOUNDS, &spec(PositionDistribution::Clustered, 3)).unwrap();
+        assert!(clustered.iter().all(|node| BOUNDS.contains(&node.position)));
+        assert!(occupied_cells(&clustered) * 3 < occupied_cells(&uniform));
+
+        let mut no_clusters = spec(PositionDistribution::Clustered, 3);
+        no_clusters.clusters = 0;
+        let error = generate_positions(&BOUNDS, &no_clusters).unwrap_err();
+        assert_eq!(error.code(), "invalid_input");
+    }
+
+    #[test]
+    fn test_generate_nodes_json() {
+        let mut index = SpatialIndex::new(0.0, 0.0, 100.0, 100.0, 4);
+        let inserted = index
+            .generate_nodes(
+                r#"{"count": 50, "distribution": "clustered", "id_prefix": "n", "seed": 9}"#,
+            )
+            .unwrap();
+        assert_eq!((inserted, index.size()), (50, 50));
+        assert!(index.position("n49").is_some());
+
+        let error = index.generate_nodes(r#"{"count": -1}"#).unwrap_err();
+        assert_eq!(error.code(), "malformed");
+        assert!(error.to_string().starts_with("Invalid position spec: "));
+        assert_eq!(index.size(), 50);
+    }
+}
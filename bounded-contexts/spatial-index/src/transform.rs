@@ -0,0 +1,389 @@
+//! Screen-space access to a world-space index
+//!
+//! The index stores world coordinates. An optional `Transform`, the affine
+//! matrix from world to screen coordinates, lets the `*_screen` methods take
+//! and return screen coordinates instead: inserts map points back to world
+//! space, and queries map their area to world space, search there, and
+//! return nodes with screen positions. Both kinds of caller share the one
+//! index, and a pan or zoom only replaces the matrix.
+//!
+//! Without a transform, screen and world coordinates are the same.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#spatial-index
+
+use std::collections::HashMap;
+
+use harmony_error::HarmonyError;
+#[cfg(feature = "wasm")]
+use harmony_error::ResultExt;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use serde_wasm_bindgen::Serializer;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{BoundingBox, Point, SpatialIndex, SpatialNode};
+
+/// Affine map from world to screen coordinates, in the order of canvas
+/// `setTransform` and `DOMMatrix`:
+/// `screen = (a·x + c·y + e, b·x + d·y + f)`
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Transform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Transform {
+    /// Scale by `scale`, rotate by `rotation` radians, then translate
+    pub fn from_view(scale: f64, rotation: f64, translate_x: f64, translate_y: f64) -> Self {
+        let (sin, cos) = rotation.sin_cos();
+        Transform {
+            a: scale * cos,
+            b: scale * sin,
+            c: -scale * sin,
+            d: scale * cos,
+            e: translate_x,
+            f: translate_y,
+        }
+    }
+
+    /// Screen point of a world point
+    pub fn apply(&self, point: Point) -> Point {
+        Point {
+            x: self.a * point.x + self.c * point.y + self.e,
+            y: self.b * point.x + self.d * point.y + self.f,
+        }
+    }
+
+    /// The map from screen back to world coordinates, or None for a
+    /// singular or non-finite matrix
+    pub fn inverse(&self) -> Option<Transform> {
+        let determinant = self.a * self.d - self.b * self.c;
+        if determinant == 0.0
+            || !determinant.is_finite()
+            || !self.e.is_finite()
+            || !self.f.is_finite()
+        {
+            return None;
+        }
+        let (a, b, c, d) = (
+            self.d / determinant,
+            -self.b / determinant,
+            -self.c / determinant,
+            self.a / determinant,
+        );
+        Some(Transform {
+            a,
+            b,
+            c,
+            d,
+            e: -(a * self.e + c * self.f),
+            f: -(b * self.e + d * self.f),
+        })
+    }
+
+    /// Smallest box holding the image of `area`
+    fn map_box(&self, area: &BoundingBox) -> BoundingBox {
+        let corners = [
+            (area.min_x, area.min_y),
+            (area.max_x, area.min_y),
+            (area.min_x, area.max_y),
+            (area.max_x, area.max_y),
+        ]
+        .map(|(x, y)| self.apply(Point { x, y }));
+        let mut mapped = BoundingBox {
+            min_x: f64::INFINITY,
+            min_y: f64::INFINITY,
+            max_x: f64::NEG_INFINITY,
+            max_y: f64::NEG_INFINITY,
+        };
+        for corner in corners {
+            mapped.min_x = mapped.min_x.min(corner.x);
+            mapped.min_y = mapped.min_y.min(corner.y);
+            mapped.max_x = mapped.max_x.max(corner.x);
+            mapped.max_y = mapped.max_y.max(corner.y);
+        }
+        mapped
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl SpatialIndex {
+    /// Set the world-to-screen matrix used by the screen-space methods
+    ///
+    /// # Returns
+    /// `invalid_input` for a singular or non-finite matrix, keeping the
+    /// previous one
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setTransform))]
+    pub fn set_transform(
+        &mut self,
+        a: f64,
+        b: f64,
+        c: f64,
+        d: f64,
+        e: f64,
+        f: f64,
+    ) -> Result<(), HarmonyError> {
+        let transform = Transform { a, b, c, d, e, f };
+        let inverse = transform.inverse().ok_or_else(|| {
+            HarmonyError::InvalidInput(format!("Transform {:?} is not invertible", transform))
+        })?;
+        self.transform = Some((transform, inverse));
+        Ok(())
+    }
+
+    /// Drop the transform, so screen and world coordinates are the same
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = clearTransform))]
+    pub fn clear_transform(&mut self) {
+        self.transform = None;
+    }
+}
+
+/// Screen-space versions of the JS value methods
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl SpatialIndex {
+    /// The world-to-screen matrix, or null
+    #[wasm_bindgen(js_name = getTransform, unchecked_return_type = "Transform | null")]
+    pub fn get_transform(&self) -> Result<JsValue, HarmonyError> {
+        Ok(self.transform().serialize(&Serializer::json_compatible())?)
+    }
+
+    /// Screen point of a world point
+    #[wasm_bindgen(js_name = toScreen, unchecked_return_type = "Point")]
+    pub fn to_screen(&self, x: f64, y: f64) -> Result<JsValue, HarmonyError> {
+        let point = self.screen_point(Point { x, y });
+        Ok(point.serialize(&Serializer::json_compatible())?)
+    }
+
+    /// World point of a screen point
+    #[wasm_bindgen(js_name = toWorld, unchecked_return_type = "Point")]
+    pub fn to_world(&self, x: f64, y: f64) -> Result<JsValue, HarmonyError> {
+        let point = self.world_point(Point { x, y });
+        Ok(point.serialize(&Serializer::json_compatible())?)
+    }
+
+    /// Insert a node at a screen point; see `insertValue`
+    #[wasm_bindgen(js_name = insertScreen)]
+    pub fn insert_screen_value(
+        &mut self,
+        id: String,
+        x: f64,
+        y: f64,
+        #[wasm_bindgen(unchecked_param_type = "Record<string, string> | undefined")]
+        metadata: JsValue,
+    ) -> Result<bool, HarmonyError> {
+        let metadata: HashMap<String, String> = if metadata.is_undefined() {
+            HashMap::new()
+        } else {
+            serde_wasm_bindgen::from_value(metadata)
+                .with_context(|| format!("Invalid metadata for node {}", id))?
+        };
+        self.insert_screen(id, x, y, metadata)
+    }
+
+    /// Query nodes within a screen-space box
+    #[wasm_bindgen(js_name = queryRangeScreen, unchecked_return_type = "SpatialNode[]")]
+    pub fn query_range_screen(
+        &self,
+        min_x: f64,
+        min_y: f64,
+        max_x: f64,
+        max_y: f64,
+    ) -> Result<JsValue, HarmonyError> {
+        let found = self.range_screen(min_x, min_y, max_x, max_y);
+        Ok(found.serialize(&Serializer::json_compatible())?)
+    }
+
+    /// Query nodes within a screen-space radius
+    #[wasm_bindgen(js_name = queryRadiusScreen, unchecked_return_type = "SpatialNode[]")]
+    pub fn query_radius_screen(
+        &self,
+        center_x: f64,
+        center_y: f64,
+        radius: f64,
+    ) -> Result<JsValue, HarmonyError> {
+        let found = self.radius_screen(center_x, center_y, radius);
+        Ok(found.serialize(&Serializer::json_compatible())?)
+    }
+
+    /// Find the k nodes nearest a screen point
+    #[wasm_bindgen(js_name = queryNearestScreen, unchecked_return_type = "SpatialNode[]")]
+    pub fn query_nearest_screen(&self, x: f64, y: f64, k: usize) -> Result<JsValue, HarmonyError> {
+        let nearest = self.nearest_screen(x, y, k);
+        Ok(nearest.serialize(&Serializer::json_compatible())?)
+    }
+
+    /// Screen position of a node by ID, or null
+    #[wasm_bindgen(js_name = getPositionScreen, unchecked_return_type = "Point | null")]
+    pub fn get_position_screen(&self, id: String) -> Result<JsValue, HarmonyError> {
+        let position = self.position_screen(&id);
+        Ok(position.serialize(&Serializer::json_compatible())?)
+    }
+}
+
+impl SpatialIndex {
+    /// The world-to-screen matrix, if one is set
+    pub fn transform(&self) -> Option<Transform> {
+        self.transform.map(|(transform, _)| transform)
+    }
+
+    /// Screen point of a world point
+    pub fn screen_point(&self, point: Point) -> Point {
+        match &self.transform {
+            Some((transform, _)) => transform.apply(point),
+            None => point,
+        }
+    }
+
+    /// World point of a screen point
+    pub fn world_point(&self, point: Point) -> Point {
+        match &self.transform {
+            Some((_, inverse)) => inverse.apply(point),
+            None => point,
+        }
+    }
+
+    /// `node` with its position in screen coordinates
+    fn to_screen_node(&self, mut node: SpatialNode) -> SpatialNode {
+        node.position = self.screen_point(node.position);
+        node
+    }
+
+    /// Insert a node at a screen point; see `insert`
+    pub fn insert_screen(
+        &mut self,
+        id: String,
+        x: f64,
+        y: f64,
+        metadata: HashMap<String, String>,
+    ) -> Result<bool, HarmonyError> {
+        let world = self.world_point(Point { x, y });
+        self.insert_node(id, world.x, world.y, metadata)
+    }
+
+    /// Nodes within a screen-space box, with screen positions
+    ///
+    /// Under rotation the box covers a rotated area of the world; the world
+    /// box around it is searched and the nodes outside the screen box
+    /// dropped.
+    pub fn range_screen(&self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Vec<SpatialNode> {
+        let area = BoundingBox {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        };
+        let Some((_, inverse)) = &self.transform else {
+            return self.range(min_x, min_y, max_x, max_y);
+        };
+        let world = inverse.map_box(&area);
+        self.range(world.min_x, world.min_y, world.max_x, world.max_y)
+            .into_iter()
+            .map(|node| self.to_screen_node(node))
+            .filter(|node| area.contains(&node.position))
+            .collect()
+    }
+
+    /// Nodes within a screen-space radius, with screen positions
+    pub fn radius_screen(&self, center_x: f64, center_y: f64, radius: f64) -> Vec<SpatialNode> {
+        let center = Point {
+            x: center_x,
+            y: center_y,
+        };
+        self.range_screen(
+            center_x - radius,
+            center_y - radius,
+            center_x + radius,
+            center_y + radius,
+        )
+        .into_iter()
+        .filter(|node| {
+            (node.position.x - center.x).powi(2) + (node.position.y - center.y).powi(2)
+                <= radius * radius
+        })
+        .collect()
+    }
+
+    /// The k nodes nearest a screen point, with screen positions
+    ///
+    /// Ordered by world distance, which is screen distance unless the
+    /// transform scales x and y differently.
+    pub fn nearest_screen(&self, x: f64, y: f64, k: usize) -> Vec<SpatialNode> {
+        let world = self.world_point(Point { x, y });
+        self.nearest_where(world.x, world.y, k, |_| true)
+            .into_iter()
+            .map(|node| self.to_screen_node(node))
+            .collect()
+    }
+
+    /// Screen position of a node by ID
+    pub fn position_screen(&self, id: &str) -> Option<Point> {
+        self.position(id).map(|point| self.screen_point(point))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn close(point: Point, x: f64, y: f64) -> bool {
+        (point.x - x).abs() < 1e-9 && (point.y - y).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_screen_coordinates() {
+        let mut index = SpatialIndex::new(0.0, 0.0, 1000.0, 1000.0, 4);
+        index
+            .insert("world".to_string(), 10.0, 20.0, "{}".to_string())
+            .unwrap();
+        // Without a transform, screen is world
+        assert!(close(index.position_screen("world").unwrap(), 10.0, 20.0));
+
+        // Zoom 2, a quarter turn, then shift by (500, 100)
+        let view = Transform::from_view(2.0, FRAC_PI_2, 500.0, 100.0);
+        index
+            .set_transform(view.a, view.b, view.c, view.d, view.e, view.f)
+            .unwrap();
+        assert!(close(index.position_screen("world").unwrap(), 460.0, 120.0));
+        index
+            .insert_screen("screen".to_string(), 400.0, 300.0, HashMap::new())
+            .unwrap();
+        assert!(close(index.position("screen").unwrap(), 100.0, 50.0));
+
+        let found = index.range_screen(450.0, 110.0, 470.0, 130.0);
+        assert_eq!(found.len(), 1);
+        assert!(close(found[0].position, 460.0, 120.0));
+        // The world box around (390..410, 290..310) is 5 units across
+        assert_eq!(
+            index.range_screen(390.0, 290.0, 410.0, 310.0)[0].id,
+            "screen"
+        );
+
+        assert_eq!(index.radius_screen(405.0, 300.0, 6.0).len(), 1);
+        assert!(index.radius_screen(408.0, 308.0, 10.0).is_empty());
+        let nearest = index.nearest_screen(450.0, 120.0, 2);
+        assert_eq!(
+            (nearest[0].id.as_str(), nearest[1].id.as_str()),
+            ("world", "screen")
+        );
+        assert!(close(nearest[1].position, 400.0, 300.0));
+
+        let error = index
+            .set_transform(1.0, 2.0, 2.0, 4.0, 0.0, 0.0)
+            .unwrap_err();
+        assert_eq!(error.code(), "invalid_input");
+        assert_eq!(index.transform(), Some(view));
+        index.clear_transform();
+        assert!(close(index.world_point(Point { x: 1.0, y: 2.0 }), 1.0, 2.0));
+    }
+}
@@ -0,0 +1,266 @@
+//! Quadtree capacity tuning from observed queries
+//!
+//! A node's capacity trades tree depth against leaf size: a small capacity
+//! gives a deep tree whose queries visit many cells, a large one gives
+//! overfull cells whose every node is tested. Which is cheaper depends on
+//! the queries, so the index keeps the areas of its last `QUERY_LOG_SIZE`
+//! queries (range boxes, radius squares, and for nearest queries the
+//! square reaching the farthest result).
+//!
+//! `tune` replays those areas against trees rebuilt with each candidate
+//! capacity, counting quadtree cells visited plus nodes tested per query,
+//! and keeps the cheapest tree. Without logged queries it replays point
+//! lookups at a sample of the stored nodes, like hit tests.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#spatial-index
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use harmony_trace::Level;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{BoundingBox, Point, QuadTreeNode, SpatialIndex, SpatialNode, TRACE_TARGET};
+
+/// Number of recent query areas `tune` replays
+pub const QUERY_LOG_SIZE: usize = 256;
+
+/// Capacities `tune` tries, besides the current one
+const CANDIDATE_CAPACITIES: [usize; 9] = [1, 2, 4, 8, 16, 32, 64, 128, 256];
+
+/// Outcome of `tune`
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TuneReport {
+    /// Capacity before tuning
+    pub previous_capacity: usize,
+    /// Capacity the tree now uses; the previous one if nothing was cheaper
+    pub capacity: usize,
+    /// Mean cells visited plus nodes tested per query, before tuning
+    pub previous_cost: f64,
+    /// The same estimate with `capacity`
+    pub cost: f64,
+    /// Logged queries replayed; 0 if point lookups were used instead
+    pub queries: usize,
+}
+
+/// Areas of the most recent queries, oldest first
+///
+/// Behind a mutex so queries, which take `&self`, can record, and the
+/// index stays `Sync`.
+#[derive(Debug, Default)]
+pub(crate) struct QueryLog {
+    areas: Mutex<VecDeque<BoundingBox>>,
+}
+
+impl QueryLog {
+    pub(crate) fn record(&self, area: BoundingBox) {
+        if let Ok(mut areas) = self.areas.lock() {
+            if areas.len() == QUERY_LOG_SIZE {
+                areas.pop_front();
+            }
+            areas.push_back(area);
+        }
+    }
+
+    /// Square around `center` reaching the farthest of `found`
+    pub(crate) fn record_nearest(&self, center: Point, found: &[SpatialNode]) {
+        let reach = found.last().map_or(0.0, |node| {
+            ((node.position.x - center.x).powi(2) + (node.position.y - center.y).powi(2)).sqrt()
+        });
+        self.record(square(center, reach));
+    }
+
+    fn areas(&self) -> Vec<BoundingBox> {
+        self.areas
+            .lock()
+            .map(|areas| areas.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Box of half-width `reach` around `center`
+pub(crate) fn square(center: Point, reach: f64) -> BoundingBox {
+    BoundingBox {
+        min_x: center.x - reach,
+        min_y: center.y - reach,
+        max_x: center.x + reach,
+        max_y: center.y + reach,
+    }
+}
+
+impl QuadTreeNode {
+    /// Cells a query for `area` visits plus the nodes it tests
+    fn query_cost(&self, area: &BoundingBox) -> usize {
+        if !self.bounds.intersects(area) {
+            return 0;
+        }
+        let children = [
+            &self.northeast,
+            &self.northwest,
+            &self.southeast,
+            &self.southwest,
+        ];
+        1 + self.nodes.len()
+            + children
+                .into_iter()
+                .flatten()
+                .map(|child| child.query_cost(area))
+                .sum::<usize>()
+    }
+}
+
+/// Mean query cost of `tree` over `areas`
+fn mean_cost(tree: &QuadTreeNode, areas: &[BoundingBox]) -> f64 {
+    if areas.is_empty() {
+        return 0.0;
+    }
+    let total: usize = areas.iter().map(|area| tree.query_cost(area)).sum();
+    total as f64 / areas.len() as f64
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl SpatialIndex {
+    /// Maximum nodes per quadtree node before it subdivides
+    pub fn capacity(&self) -> usize {
+        self.root.capacity
+    }
+
+    /// Rebuilds the quadtree with the capacity that makes recent queries
+    /// cheapest; see `tune_capacity`
+    ///
+    /// # Returns
+    /// JSON TuneReport:
+    /// `{"previous_capacity", "capacity", "previous_cost", "cost", "queries"}`
+    pub fn tune(&mut self) -> String {
+        let report = self.tune_capacity();
+        serde_json::to_string(&report).unwrap_or_else(|_| "null".to_string())
+    }
+}
+
+impl SpatialIndex {
+    /// Rebuilds the quadtree with the candidate capacity whose tree has the
+    /// lowest estimated cost over the logged queries; keeps the tree when
+    /// no candidate beats the current capacity
+    pub fn tune_capacity(&mut self) -> TuneReport {
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "tune");
+        let bounds = self.root.bounds;
+        // Not through `range`, which would log a query
+        let mut nodes = Vec::with_capacity(self.size());
+        self.root.query(&bounds, &mut nodes);
+        let logged = self.queries.areas();
+        let areas = if logged.is_empty() {
+            let step = nodes.len().div_ceil(QUERY_LOG_SIZE).max(1);
+            nodes
+                .iter()
+                .step_by(step)
+                .map(|node| square(node.position, 0.0))
+                .collect()
+        } else {
+            logged.clone()
+        };
+
+        let previous_capacity = self.root.capacity;
+        let previous_cost = mean_cost(&self.root, &areas);
+        let mut best: Option<(usize, f64, QuadTreeNode)> = None;
+        for capacity in CANDIDATE_CAPACITIES {
+            if capacity == previous_capacity {
+                continue;
+            }
+            let mut tree = QuadTreeNode::new(bounds, capacity);
+            for node in &nodes {
+                tree.insert(node.clone());
+            }
+            let cost = mean_cost(&tree, &areas);
+            let best_cost = best.as_ref().map_or(previous_cost, |(_, cost, _)| *cost);
+            if cost < best_cost {
+                best = Some((capacity, cost, tree));
+            }
+        }
+
+        let (capacity, cost) = match best {
+            Some((capacity, cost, tree)) => {
+                self.root = tree;
+                (capacity, cost)
+            }
+            None => (previous_capacity, previous_cost),
+        };
+        span.field("previous_capacity", previous_capacity)
+            .field("capacity", capacity);
+        TuneReport {
+            previous_capacity,
+            capacity,
+            previous_cost,
+            cost,
+            queries: logged.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(capacity: usize) -> SpatialIndex {
+        let mut index = SpatialIndex::new(0.0, 0.0, 1000.0, 1000.0, capacity);
+        for i in 0..1600 {
+            let (x, y) = ((i % 40) as f64 * 25.0 + 1.0, (i / 40) as f64 * 25.0 + 1.0);
+            index
+                .insert(format!("node{}", i), x, y, "{}".to_string())
+                .unwrap();
+        }
+        index
+    }
+
+    #[test]
+    fn test_tune_for_query_pattern() {
+        // Point lookups: an overfull root tests every node
+        let mut index = grid(256);
+        let report = index.tune_capacity();
+        assert_eq!((report.previous_capacity, report.queries), (256, 0));
+        assert!(report.capacity < 256);
+        assert!(report.cost < report.previous_cost);
+        assert_eq!(index.capacity(), report.capacity);
+        assert_eq!(index.size(), 1600);
+        assert_eq!(index.range(0.0, 0.0, 1000.0, 1000.0).len(), 1600);
+
+        // Whole-canvas ranges: a deep tree only adds cells to visit
+        let mut index = grid(1);
+        for _ in 0..10 {
+            index.range(0.0, 0.0, 1000.0, 1000.0);
+        }
+        let report = index.tune_capacity();
+        assert_eq!(report.queries, 10);
+        assert!(report.capacity > 1 && report.cost < report.previous_cost);
+        // Tuning again finds nothing better and keeps the tree
+        let again = index.tune_capacity();
+        assert_eq!(
+            (again.previous_capacity, again.capacity),
+            (report.capacity, report.capacity)
+        );
+        assert_eq!(again.cost, again.previous_cost);
+        let nearest = index.nearest_where(500.0, 500.0, 1, |_| true);
+        assert_eq!(nearest[0].id, "node820");
+    }
+
+    #[test]
+    fn test_query_log() {
+        let index = grid(4);
+        for _ in 0..QUERY_LOG_SIZE + 5 {
+            index.radius(10.0, 10.0, 5.0);
+        }
+        index.nearest(0.0, 0.0, 1);
+        let areas = index.queries.areas();
+        assert_eq!(areas.len(), QUERY_LOG_SIZE);
+        let reach = 2f64.sqrt();
+        let last = areas[QUERY_LOG_SIZE - 1];
+        assert!((last.max_x - reach).abs() < 1e-9 && (last.min_y + reach).abs() < 1e-9);
+
+        let report: TuneReport = serde_json::from_str(&grid(4).tune()).unwrap();
+        assert_eq!(report.previous_capacity, 4);
+    }
+}
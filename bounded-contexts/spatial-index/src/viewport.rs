@@ -0,0 +1,295 @@
+//! Level-of-detail query for the visible part of the canvas
+//!
+//! `viewport` is the renderer's one call per frame. When at most
+//! `lod_threshold` nodes are in view it returns them all; above that it
+//! returns clusters instead: the visible nodes grouped into square grid
+//! cells `CLUSTER_CELL_PIXELS` screen pixels wide at the given zoom. The
+//! grid is aligned to the world origin, not the viewport, so clusters stay
+//! put while the view pans.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#spatial-index
+
+use std::collections::BTreeMap;
+
+use harmony_error::HarmonyError;
+use harmony_trace::Level;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use serde_wasm_bindgen::Serializer;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{BoundingBox, Point, QuadTreeNode, SpatialIndex, SpatialNode, TRACE_TARGET};
+
+/// Visible nodes above which `viewport` returns clusters, unless set with
+/// `set_lod_threshold`
+pub const DEFAULT_LOD_THRESHOLD: usize = 1000;
+
+/// Width of a cluster's grid cell on screen, in pixels
+pub const CLUSTER_CELL_PIXELS: f64 = 64.0;
+
+/// Visible nodes of one grid cell
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cluster {
+    /// Mean position of the nodes
+    pub center: Point,
+    /// Box around the nodes
+    pub bounds: BoundingBox,
+    /// Number of nodes
+    pub count: usize,
+    /// Smallest node ID, to label the cluster or draw a lone node
+    pub id: String,
+}
+
+/// Result of `viewport`, tagged by `kind`
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ViewportResult {
+    /// Every visible node, when there are at most `lod_threshold`
+    Nodes { nodes: Vec<SpatialNode> },
+    /// Visible nodes per grid cell, in row order
+    Clusters {
+        /// Nodes in view
+        visible: usize,
+        clusters: Vec<Cluster>,
+    },
+}
+
+impl QuadTreeNode {
+    /// Calls `visit` with every node inside `area`, without cloning
+    fn visit<'a>(&'a self, area: &BoundingBox, visit: &mut impl FnMut(&'a SpatialNode)) {
+        if !self.bounds.intersects(area) {
+            return;
+        }
+        for node in &self.nodes {
+            if area.contains(&node.position) {
+                visit(node);
+            }
+        }
+        for child in [
+            &self.northeast,
+            &self.northwest,
+            &self.southeast,
+            &self.southwest,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            child.visit(area, visit);
+        }
+    }
+}
+
+/// Cluster being gathered for a grid cell
+struct Gathered<'a> {
+    sum: Point,
+    bounds: BoundingBox,
+    count: usize,
+    id: &'a str,
+}
+
+impl<'a> Gathered<'a> {
+    fn new(node: &'a SpatialNode) -> Self {
+        let Point { x, y } = node.position;
+        Gathered {
+            sum: node.position,
+            bounds: BoundingBox {
+                min_x: x,
+                min_y: y,
+                max_x: x,
+                max_y: y,
+            },
+            count: 1,
+            id: &node.id,
+        }
+    }
+
+    fn add(&mut self, node: &'a SpatialNode) {
+        let Point { x, y } = node.position;
+        self.sum.x += x;
+        self.sum.y += y;
+        self.bounds.min_x = self.bounds.min_x.min(x);
+        self.bounds.min_y = self.bounds.min_y.min(y);
+        self.bounds.max_x = self.bounds.max_x.max(x);
+        self.bounds.max_y = self.bounds.max_y.max(y);
+        self.count += 1;
+        self.id = self.id.min(&node.id);
+    }
+
+    fn finish(self) -> Cluster {
+        let count = self.count as f64;
+        Cluster {
+            center: Point {
+                x: self.sum.x / count,
+                y: self.sum.y / count,
+            },
+            bounds: self.bounds,
+            count: self.count,
+            id: self.id.to_string(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl SpatialIndex {
+    /// Visible nodes above which `query_viewport` returns clusters
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = lodThreshold))]
+    pub fn lod_threshold(&self) -> usize {
+        self.lod_threshold
+    }
+
+    /// Set the visible node count above which `query_viewport` returns
+    /// clusters
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setLodThreshold))]
+    pub fn set_lod_threshold(&mut self, threshold: usize) {
+        self.lod_threshold = threshold;
+    }
+
+    /// Nodes or clusters in a viewport; see `viewport`
+    ///
+    /// # Returns
+    /// JSON ViewportResult: `{"kind": "nodes", "nodes"}` or
+    /// `{"kind": "clusters", "visible", "clusters"}`
+    pub fn query_viewport(
+        &self,
+        min_x: f64,
+        min_y: f64,
+        max_x: f64,
+        max_y: f64,
+        zoom: f64,
+    ) -> Result<String, HarmonyError> {
+        let result = self.viewport(min_x, min_y, max_x, max_y, zoom)?;
+        Ok(serde_json::to_string(&result)?)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl SpatialIndex {
+    /// Nodes or clusters in a viewport, as a JS object
+    #[wasm_bindgen(js_name = queryViewportValue, unchecked_return_type = "ViewportResult")]
+    pub fn query_viewport_value(
+        &self,
+        min_x: f64,
+        min_y: f64,
+        max_x: f64,
+        max_y: f64,
+        zoom: f64,
+    ) -> Result<JsValue, HarmonyError> {
+        let result = self.viewport(min_x, min_y, max_x, max_y, zoom)?;
+        Ok(result.serialize(&Serializer::json_compatible())?)
+    }
+}
+
+impl SpatialIndex {
+    /// Every node in the box when at most `lod_threshold` are, otherwise
+    /// clusters of them per grid cell; see the module docs
+    ///
+    /// # Arguments
+    /// * `zoom` - Screen pixels per world unit
+    ///
+    /// # Returns
+    /// `invalid_input` unless `zoom` is finite and positive
+    pub fn viewport(
+        &self,
+        min_x: f64,
+        min_y: f64,
+        max_x: f64,
+        max_y: f64,
+        zoom: f64,
+    ) -> Result<ViewportResult, HarmonyError> {
+        if !zoom.is_finite() || zoom <= 0.0 {
+            return Err(HarmonyError::InvalidInput(format!(
+                "Viewport zoom must be finite and positive, got {}",
+                zoom
+            )));
+        }
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "query_viewport");
+        let area = BoundingBox {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        };
+        self.queries.record(area);
+        let mut visible = 0;
+        self.root.visit(&area, &mut |_| visible += 1);
+        span.field("visible", visible);
+
+        if visible <= self.lod_threshold {
+            let mut nodes = Vec::with_capacity(visible);
+            self.root.query(&area, &mut nodes);
+            return Ok(ViewportResult::Nodes { nodes });
+        }
+
+        let cell = CLUSTER_CELL_PIXELS / zoom;
+        let mut cells: BTreeMap<(i64, i64), Gathered> = BTreeMap::new();
+        self.root.visit(&area, &mut |node| {
+            let key = (
+                (node.position.y / cell).floor() as i64,
+                (node.position.x / cell).floor() as i64,
+            );
+            cells
+                .entry(key)
+                .and_modify(|gathered| gathered.add(node))
+                .or_insert_with(|| Gathered::new(node));
+        });
+        let clusters: Vec<Cluster> = cells.into_values().map(Gathered::finish).collect();
+        span.field("clusters", clusters.len());
+        Ok(ViewportResult::Clusters { visible, clusters })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viewport_level_of_detail() {
+        let mut index = SpatialIndex::new(0.0, 0.0, 1000.0, 1000.0, 4);
+        for i in 0..100 {
+            let (x, y) = ((i % 10) as f64 * 10.0 + 5.0, (i / 10) as f64 * 10.0 + 5.0);
+            index
+                .insert(format!("node{:02}", i), x, y, "{}".to_string())
+                .unwrap();
+        }
+        assert_eq!(index.lod_threshold(), DEFAULT_LOD_THRESHOLD);
+
+        let ViewportResult::Nodes { nodes } = index.viewport(0.0, 0.0, 50.0, 50.0, 1.0).unwrap()
+        else {
+            panic!("expected nodes");
+        };
+        assert_eq!(nodes.len(), 25);
+
+        // 64 px cells at zoom 1.6 are 40 units: a 3 x 3 grid over the nodes
+        index.set_lod_threshold(50);
+        let ViewportResult::Clusters { visible, clusters } =
+            index.viewport(0.0, 0.0, 100.0, 100.0, 1.6).unwrap()
+        else {
+            panic!("expected clusters");
+        };
+        assert_eq!((visible, clusters.len()), (100, 9));
+        assert_eq!(clusters.iter().map(|c| c.count).sum::<usize>(), 100);
+        assert_eq!(clusters[0].count, 16);
+        assert_eq!(clusters[0].id, "node00");
+        assert_eq!((clusters[0].center.x, clusters[0].center.y), (20.0, 20.0));
+        assert_eq!(
+            (clusters[0].bounds.min_x, clusters[0].bounds.max_y),
+            (5.0, 35.0)
+        );
+        assert_eq!((clusters[8].count, clusters[8].id.as_str()), (4, "node88"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&index.query_viewport(0.0, 0.0, 20.0, 20.0, 1.0).unwrap())
+                .unwrap();
+        assert_eq!(json["kind"], "nodes");
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 4);
+
+        let error = index.viewport(0.0, 0.0, 1.0, 1.0, 0.0).unwrap_err();
+        assert_eq!(error.code(), "invalid_input");
+    }
+}
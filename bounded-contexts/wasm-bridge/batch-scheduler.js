@@ -0,0 +1,329 @@
+/**
+ * BatchScheduler: time-sliced expensive operations on the main thread
+ *
+ * Bulk indexing, big traversals and re-layouts can take longer than a frame.
+ * The scheduler runs them as tasks made of small steps: each slice calls
+ * steps for at most `sliceMs`, then yields to the event loop (so input and
+ * rendering get a turn) and waits `gapMs` before the next slice. Several
+ * tasks share the slices round-robin, in the order they were scheduled.
+ *
+ * A task is `{ step, progress?, result? }`:
+ * - `step()`: does a bounded amount of work; returns (or resolves to)
+ *   true once the task is finished
+ * - `progress()`: `{ done, total }`, reported to `onProgress` after each
+ *   slice; `total` may be null when unknown
+ * - `result()`: the value the task's promise resolves to
+ *
+ * Cancel a task through the `CancellationToken` passed to `schedule`: its
+ * promise rejects with an `AbortError` before the next step, and work
+ * already done stays done.
+ *
+ * Factories at the end of this module make tasks for a TraversalCursor of
+ * the edge executor, bulk spatial inserts, bulk full-text indexing and any
+ * array of items (e.g. a re-layout, one node per item).
+ *
+ * @module BatchScheduler
+ * @see {@link file://./DESIGN_SYSTEM.md#wasm-bridge WASMBridge Documentation}
+ */
+
+import { ErrorCode } from '../../harmony-error/harmony-error.js';
+
+/** Milliseconds of work per slice when no `sliceMs` is given */
+export const DEFAULT_SLICE_MS = 8;
+
+/**
+ * @typedef {Object} BatchTask
+ * @property {function(): (boolean|Promise<boolean>)} step - Do some work;
+ *   true once finished
+ * @property {function(): {done: number, total: (number|null)}} [progress] - How far along
+ * @property {function(): unknown} [result] - Value to resolve with
+ */
+
+/**
+ * @typedef {Object} ScheduleOptions
+ * @property {string} [label] - Name used in errors and logs
+ * @property {CancellationToken} [token] - Cancels the task
+ * @property {function(number, (number|null)): void} [onProgress] - Called
+ *   with `(done, total)` after each slice
+ */
+
+/**
+ * Error shaped like the WASM contexts' HarmonyError
+ *
+ * @param {string} code - ErrorCode
+ * @param {string} message - Error message
+ * @param {string[]} [context] - Context, outermost first
+ * @returns {Error}
+ */
+function schedulerError(code, message, context = []) {
+  const error = new Error(context.length > 0 ? `${context.join(': ')}: ${message}` : message);
+  error.name = 'HarmonyError';
+  error.code = code;
+  error.context = context;
+  return error;
+}
+
+/**
+ * Lets the caller of `schedule` stop a task
+ */
+export class CancellationToken {
+  constructor() {
+    this.cancelled = false;
+    /** @type {string|null} */
+    this.reason = null;
+    /** @type {Set<function(string): void>} */
+    this.listeners = new Set();
+  }
+
+  /**
+   * Cancel every task scheduled with this token; later calls do nothing
+   *
+   * @param {string} [reason='Cancelled']
+   */
+  cancel(reason = 'Cancelled') {
+    if (this.cancelled) {
+      return;
+    }
+    this.cancelled = true;
+    this.reason = reason;
+    for (const listener of this.listeners) {
+      listener(reason);
+    }
+    this.listeners.clear();
+  }
+
+  /**
+   * Be told when the token is cancelled
+   *
+   * @param {function(string): void} listener - Gets the reason
+   * @returns {function(): boolean} Remove the listener
+   */
+  onCancel(listener) {
+    this.listeners.add(listener);
+    return () => this.listeners.delete(listener);
+  }
+
+  /**
+   * @throws {Error} AbortError if the token is cancelled
+   */
+  throwIfCancelled() {
+    if (this.cancelled) {
+      const error = new Error(this.reason);
+      error.name = 'AbortError';
+      throw error;
+    }
+  }
+}
+
+/**
+ * Resolves on a later event loop task, after pending input and rendering
+ *
+ * @returns {Promise<void>}
+ */
+function yieldToEventLoop() {
+  if (globalThis.scheduler?.yield) {
+    return globalThis.scheduler.yield();
+  }
+  if (typeof MessageChannel !== 'undefined') {
+    return new Promise((resolve) => {
+      const channel = new MessageChannel();
+      channel.port1.onmessage = () => {
+        channel.port1.close();
+        resolve();
+      };
+      channel.port2.postMessage(null);
+    });
+  }
+  return new Promise((resolve) => setTimeout(resolve, 0));
+}
+
+/**
+ * Runs tasks in time slices, yielding to the event loop between them
+ */
+export class BatchScheduler {
+  /**
+   * @param {{sliceMs?: number, gapMs?: number}} [options] - `sliceMs`:
+   *   work per slice; `gapMs`: extra wait between slices, to cap the share
+   *   of the main thread the tasks take
+   */
+  constructor({ sliceMs = DEFAULT_SLICE_MS, gapMs = 0 } = {}) {
+    this.sliceMs = sliceMs;
+    this.gapMs = gapMs;
+    /** @type {Array<{task: BatchTask, options: ScheduleOptions, resolve: Function, reject: Function}>} */
+    this.jobs = [];
+    this.running = false;
+  }
+
+  /**
+   * Run a task in slices
+   *
+   * @param {BatchTask} task - Task to run
+   * @param {ScheduleOptions} [options]
+   * @returns {Promise<unknown>} What `task.result()` returns
+   * @throws {Error} AbortError once cancelled, or the step's error as a
+   *   HarmonyError with the label as context
+   */
+  schedule(task, options = {}) {
+    return new Promise((resolve, reject) => {
+      options.token?.throwIfCancelled();
+      this.jobs.push({ task, options, resolve, reject });
+      if (!this.running) {
+        this.running = true;
+        this.#loop();
+      }
+    });
+  }
+
+  /** @returns {number} Tasks scheduled and not finished */
+  get pending() {
+    return this.jobs.length;
+  }
+
+  /**
+   * @private
+   */
+  async #loop() {
+    while (this.jobs.length > 0) {
+      const job = this.jobs.shift();
+      if (await this.#slice(job)) {
+        continue;
+      }
+      this.jobs.push(job);
+      await yieldToEventLoop();
+      if (this.gapMs > 0) {
+        await new Promise((resolve) => setTimeout(resolve, this.gapMs));
+      }
+    }
+    this.running = false;
+  }
+
+  /**
+   * Run one slice of a job
+   * @private
+   * @param {{task: BatchTask, options: ScheduleOptions, resolve: Function, reject: Function}} job
+   * @returns {Promise<boolean>} Whether the job settled
+   */
+  async #slice({ task, options, resolve, reject }) {
+    const { label = 'Batch task', token, onProgress } = options;
+    const deadline = performance.now() + this.sliceMs;
+    try {
+      let finished = false;
+      do {
+        token?.throwIfCancelled();
+        finished = await task.step();
+      } while (!finished && performance.now() < deadline);
+
+      if (onProgress && task.progress) {
+        const { done, total = null } = task.progress();
+        onProgress(done, total);
+      }
+      if (finished) {
+        resolve(task.result?.());
+      }
+      return finished;
+    } catch (error) {
+      if (error?.name === 'AbortError') {
+        reject(error);
+      } else if (error instanceof Error && error.name === 'HarmonyError') {
+        const wrapped = schedulerError(error.code, error.message, [label]);
+        wrapped.context = [label, ...(error.context ?? [])];
+        reject(wrapped);
+      } else {
+        reject(schedulerError(ErrorCode.INTERNAL, String(error?.message ?? error), [label]));
+      }
+      return true;
+    }
+  }
+}
+
+/**
+ * Task handling an array a chunk at a time
+ *
+ * @param {Array} items - Items to handle
+ * @param {function(Array, number): (void|Promise<void>)} handleChunk - Gets
+ *   each chunk and the index of its first item
+ * @param {number} [chunkSize=100] - Items per step
+ * @returns {BatchTask} Resolves to the number of items
+ */
+export function chunkedTask(items, handleChunk, chunkSize = 100) {
+  let next = 0;
+  return {
+    step: async () => {
+      const chunk = items.slice(next, next + chunkSize);
+      if (chunk.length > 0) {
+        await handleChunk(chunk, next);
+      }
+      next += chunk.length;
+      return next >= items.length;
+    },
+    progress: () => ({ done: next, total: items.length }),
+    result: () => items.length,
+  };
+}
+
+/**
+ * Task running a TraversalCursor of a WASMEdgeExecutor
+ *
+ * The executor should not change until the task settles, or the result
+ * mixes the graph before and after.
+ *
+ * @param {Object} executor - WASMEdgeExecutor instance
+ * @param {Object} cursor - TraversalCursor, e.g. `new TraversalCursor(start, maxDepth, mask)`
+ * @param {number} [budget=1024] - Nodes expanded per step
+ * @returns {BatchTask} Resolves to the parsed TraversalResult
+ */
+export function traversalTask(executor, cursor, budget = 1024) {
+  return {
+    step: () => cursor.step(executor, budget),
+    progress: () => ({ done: cursor.visited, total: null }),
+    result: () => JSON.parse(cursor.resultJSON()),
+  };
+}
+
+/**
+ * Task inserting nodes into a SpatialIndexWrapper
+ *
+ * @param {Object} spatialIndex - SpatialIndexWrapper instance
+ * @param {Array<{id: string, x: number, y: number, metadata?: Object}>} nodes
+ * @param {number} [chunkSize=500] - Nodes per step
+ * @returns {BatchTask} Resolves to the number of nodes
+ */
+export function spatialInsertTask(spatialIndex, nodes, chunkSize = 500) {
+  return chunkedTask(
+    nodes,
+    (chunk, first) => {
+      if (first === 0) {
+        spatialIndex.reserve(nodes.length);
+      }
+      for (const { id, x, y, metadata = {} } of chunk) {
+        spatialIndex.insert(id, x, y, metadata);
+      }
+    },
+    chunkSize
+  );
+}
+
+/**
+ * Task adding documents to an index of the full-text-index module
+ *
+ * @param {{addDocument: Function}} fullTextIndex - The full-text-index.js module
+ * @param {string} indexId - Index identifier
+ * @param {Array<{nodeId: string, content: string}>} documents
+ * @param {number} [chunkSize=200] - Documents per step
+ * @returns {BatchTask} Resolves to the number of documents
+ * @throws {Error} HarmonyError for the first document the index rejects
+ */
+export function fullTextIndexTask(fullTextIndex, indexId, documents, chunkSize = 200) {
+  return chunkedTask(
+    documents,
+    async (chunk) => {
+      for (const { nodeId, content } of chunk) {
+        const result = await fullTextIndex.addDocument(indexId, nodeId, content);
+        if (!result.success) {
+          throw schedulerError(result.code, result.error, [nodeId]);
+        }
+      }
+    },
+    chunkSize
+  );
+}
@@ -0,0 +1,395 @@
+/**
+ * CommandBus: undo and redo across bounded contexts
+ *
+ * Every mutation the design tool makes goes through the bus as a command:
+ * an `apply` that makes the change and a `revert` that takes it back.
+ * `execute` applies a command and records it; `undo` and `redo` walk the
+ * one history shared by every context, so "undo" takes back the last change
+ * whichever context it touched. Executing a new command drops the redo
+ * stack.
+ *
+ * Operations run one at a time, in call order, even when commands are
+ * async (e.g. contexts behind a `WorkerClient`). A command that throws is
+ * not recorded; an `undo` or `redo` that throws leaves the history where
+ * it was.
+ *
+ * Factories at the end of this module make commands for the graph
+ * executor, lifecycle BC, node registry and spatial index.
+ *
+ * @module CommandBus
+ * @see {@link file://./DESIGN_SYSTEM.md#wasm-bridge WASMBridge Documentation}
+ */
+
+import { ErrorCode } from '../../harmony-error/harmony-error.js';
+
+/** Commands kept for undo when no limit is given */
+export const DEFAULT_HISTORY_LIMIT = 100;
+
+/**
+ * A reversible mutation
+ * @typedef {Object} Command
+ * @property {string} label - Shown in the UI, e.g. 'Move node'
+ * @property {function(): (unknown|Promise<unknown>)} apply - Make the change;
+ *   called again on redo
+ * @property {function(): (void|Promise<void>)} revert - Take the change back
+ */
+
+/**
+ * @typedef {Object} HistoryState
+ * @property {string[]} undo - Labels of undoable commands, most recent last
+ * @property {string[]} redo - Labels of redoable commands, next one last
+ */
+
+/**
+ * Error shaped like the WASM contexts' HarmonyError
+ *
+ * @param {string} code - ErrorCode
+ * @param {string} message - Error message
+ * @param {string[]} [context] - Context, outermost first
+ * @returns {Error}
+ */
+function commandError(code, message, context = []) {
+  const error = new Error(context.length > 0 ? `${context.join(': ')}: ${message}` : message);
+  error.name = 'HarmonyError';
+  error.code = code;
+  error.context = context;
+  return error;
+}
+
+/**
+ * Runs commands and keeps the shared undo and redo history
+ */
+export class CommandBus {
+  /**
+   * @param {{limit?: number}} [options] - `limit`: commands kept for undo;
+   *   the oldest are dropped beyond it
+   */
+  constructor({ limit = DEFAULT_HISTORY_LIMIT } = {}) {
+    this.limit = limit;
+    /** @type {Command[]} */
+    this.undoStack = [];
+    /** @type {Command[]} */
+    this.redoStack = [];
+    /** @type {Set<function(HistoryState): void>} */
+    this.subscribers = new Set();
+    /** @type {Promise<unknown>} */
+    this.queue = Promise.resolve();
+  }
+
+  /**
+   * Apply a command and record it for undo
+   *
+   * @param {Command} command - Command to run
+   * @returns {Promise<unknown>} What `apply` returned
+   * @throws {Error} HarmonyError from `apply`, with the label as context;
+   *   nothing is recorded
+   */
+  execute(command) {
+    return this.#enqueue(async () => {
+      const result = await run(command, 'apply');
+      this.undoStack.push(command);
+      if (this.undoStack.length > this.limit) {
+        this.undoStack.shift();
+      }
+      this.redoStack = [];
+      return result;
+    });
+  }
+
+  /**
+   * Revert the most recent command
+   *
+   * @returns {Promise<string|null>} Label of the command undone, or null if
+   *   there is nothing to undo
+   * @throws {Error} HarmonyError from `revert`, with the label as context;
+   *   the command stays on the undo stack
+   */
+  undo() {
+    return this.#enqueue(async () => {
+      const command = this.undoStack.at(-1);
+      if (!command) {
+        return null;
+      }
+      await run(command, 'revert');
+      this.redoStack.push(this.undoStack.pop());
+      return command.label;
+    });
+  }
+
+  /**
+   * Apply the most recently undone command again
+   *
+   * @returns {Promise<string|null>} Label of the command redone, or null if
+   *   there is nothing to redo
+   * @throws {Error} HarmonyError from `apply`, with the label as context;
+   *   the command stays on the redo stack
+   */
+  redo() {
+    return this.#enqueue(async () => {
+      const command = this.redoStack.at(-1);
+      if (!command) {
+        return null;
+      }
+      await run(command, 'apply');
+      this.undoStack.push(this.redoStack.pop());
+      return command.label;
+    });
+  }
+
+  /** @returns {boolean} Whether `undo` has a command to revert */
+  canUndo() {
+    return this.undoStack.length > 0;
+  }
+
+  /** @returns {boolean} Whether `redo` has a command to apply */
+  canRedo() {
+    return this.redoStack.length > 0;
+  }
+
+  /** @returns {HistoryState} Labels on both stacks */
+  history() {
+    return {
+      undo: this.undoStack.map(command => command.label),
+      redo: this.redoStack.map(command => command.label),
+    };
+  }
+
+  /**
+   * Forget the history, e.g. after loading a project; the contexts keep
+   * their state
+   */
+  clear() {
+    this.undoStack = [];
+    this.redoStack = [];
+    this.#notify();
+  }
+
+  /**
+   * Be told whenever the history changes, e.g. to enable the undo button
+   *
+   * @param {function(HistoryState): void} callback
+   * @returns {function(): boolean} Unsubscribe
+   */
+  subscribe(callback) {
+    this.subscribers.add(callback);
+    return () => this.subscribers.delete(callback);
+  }
+
+  /**
+   * Run an operation after the ones already queued
+   * @private
+   * @param {function(): Promise<unknown>} operation
+   * @returns {Promise<unknown>}
+   */
+  #enqueue(operation) {
+    const result = this.queue.then(async () => {
+      const value = await operation();
+      this.#notify();
+      return value;
+    });
+    this.queue = result.catch(() => {});
+    return result;
+  }
+
+  /**
+   * @private
+   */
+  #notify() {
+    const state = this.history();
+    for (const callback of this.subscribers) {
+      try {
+        callback(state);
+      } catch (error) {
+        console.error('[CommandBus] Subscriber failed:', error);
+      }
+    }
+  }
+}
+
+/**
+ * Calls `apply` or `revert`, adding the command label as context to errors
+ *
+ * @param {Command} command
+ * @param {'apply'|'revert'} step
+ * @returns {Promise<unknown>}
+ */
+async function run(command, step) {
+  try {
+    return await command[step]();
+  } catch (error) {
+    if (error instanceof Error && error.name === 'HarmonyError') {
+      const wrapped = commandError(error.code, error.message, [command.label]);
+      wrapped.context = [command.label, ...(error.context ?? [])];
+      throw wrapped;
+    }
+    throw commandError(ErrorCode.INTERNAL, String(error?.message ?? error), [command.label]);
+  }
+}
+
+/**
+ * One command made of several, undone and redone together
+ *
+ * If one of them fails to apply, the ones before it are reverted, so the
+ * group applies all or nothing.
+ *
+ * @param {string} label - Label of the group
+ * @param {Command[]} commands - Applied in order, reverted in reverse order
+ * @returns {Command}
+ */
+export function groupCommand(label, commands) {
+  return {
+    label,
+    apply: async () => {
+      const results = [];
+      for (const [i, command] of commands.entries()) {
+        try {
+          results.push(await run(command, 'apply'));
+        } catch (error) {
+          for (const applied of commands.slice(0, i).reverse()) {
+            try {
+              await applied.revert();
+            } catch (revertError) {
+              console.error(`[CommandBus] Revert of '${applied.label}' failed:`, revertError);
+            }
+          }
+          throw error;
+        }
+      }
+      return results;
+    },
+    revert: async () => {
+      for (const command of [...commands].reverse()) {
+        await run(command, 'revert');
+      }
+    },
+  };
+}
+
+/**
+ * Add an edge to a WASMEdgeExecutor; undo removes it
+ *
+ * @param {Object} executor - WASMEdgeExecutor instance
+ * @param {number} source - Source node ID
+ * @param {number} target - Target node ID
+ * @param {string} edgeType - Built-in or registered edge type id
+ * @returns {Command}
+ */
+export function addEdgeCommand(executor, source, target, edgeType) {
+  return {
+    label: `Add ${edgeType} edge`,
+    apply: () => executor.addNamedEdge(source, target, edgeType),
+    revert: () => {
+      if (!executor.removeEdge(source, target, executor.edgeTypeCode(edgeType))) {
+        throw commandError(ErrorCode.NOT_FOUND, `No ${edgeType} edge ${source} -> ${target}`);
+      }
+    },
+  };
+}
+
+/**
+ * Register a custom edge type with a WASMEdgeExecutor; undo unregisters it
+ *
+ * Undo fails while edges of the type remain, or once a later type has been
+ * registered, so undo those first.
+ *
+ * @param {Object} executor - WASMEdgeExecutor instance
+ * @param {Object} definition - EdgeTypeDefinition
+ * @returns {Command}
+ */
+export function registerEdgeTypeCommand(executor, definition) {
+  return {
+    label: `Register edge type ${definition.id}`,
+    apply: () => executor.registerEdgeType(JSON.stringify(definition)),
+    revert: () => executor.unregisterEdgeType(definition.id),
+  };
+}
+
+/**
+ * Move a node of a SpatialIndexWrapper; undo moves it back
+ *
+ * The node's position before the move is read when the command is applied.
+ *
+ * @param {Object} spatialIndex - SpatialIndexWrapper instance
+ * @param {string} id - Node identifier
+ * @param {number} x - New X coordinate
+ * @param {number} y - New Y coordinate
+ * @returns {Command}
+ */
+export function moveNodeCommand(spatialIndex, id, x, y) {
+  let previous = null;
+  const moveTo = (position) => {
+    if (!spatialIndex.moveNode(id, position.x, position.y)) {
+      throw commandError(
+        ErrorCode.INVALID_INPUT,
+        `(${position.x}, ${position.y}) is outside the index bounds`,
+        [id]
+      );
+    }
+  };
+  return {
+    label: `Move ${id}`,
+    apply: () => {
+      previous = spatialIndex.getPosition(id);
+      moveTo({ x, y });
+    },
+    revert: () => moveTo(previous),
+  };
+}
+
+/**
+ * Transition a component of a ComponentLifecycleBC; undo rolls it back as
+ * the same actor
+ *
+ * @param {Object} lifecycle - ComponentLifecycleBC instance
+ * @param {Object} transition - StateTransition:
+ *   `{component_id, from_state, to_state, ...}`
+ * @returns {Command}
+ */
+export function transitionCommand(lifecycle, transition) {
+  const check = (json) => {
+    const result = JSON.parse(json);
+    if (!result.success) {
+      throw commandError(ErrorCode.INVALID_INPUT, result.error, [transition.component_id]);
+    }
+    return result;
+  };
+  return {
+    label: `${transition.component_id}: ${transition.from_state} -> ${transition.to_state}`,
+    apply: () => check(lifecycle.transitionComponent(JSON.stringify(transition))),
+    revert: () => {
+      check(
+        lifecycle.rollbackTransition(
+          transition.component_id,
+          'Undo',
+          transition.actor ? JSON.stringify(transition.actor) : undefined
+        )
+      );
+    },
+  };
+}
+
+/**
+ * Register a node type with a WASMNodeRegistry; undo unregisters it
+ *
+ * @param {Object} registry - WASMNodeRegistry instance
+ * @param {Object} metadata - NodeTypeMetadata
+ * @returns {Command}
+ */
+export function registerNodeTypeCommand(registry, metadata) {
+  return {
+    label: `Register node type ${metadata.type_id}`,
+    apply: () => {
+      if (!registry.register(metadata)) {
+        throw commandError(
+          ErrorCode.INVALID_INPUT,
+          `Node type '${metadata.type_id}' is already registered`
+        );
+      }
+    },
+    revert: () => {
+      if (!registry.unregister(metadata.type_id)) {
+        throw commandError(ErrorCode.NOT_FOUND, `Unknown node type '${metadata.type_id}'`);
+      }
+    },
+  };
+}
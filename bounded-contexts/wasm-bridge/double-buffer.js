@@ -0,0 +1,131 @@
+/**
+ * DoubleBuffer: Tear-free latest-frame exchange over shared memory
+ *
+ * Two frame slots plus an atomic flip index. The producer writes into the
+ * slot readers are not using and then flips, so consumers always see the
+ * latest complete frame (audio blocks, graph snapshots).
+ *
+ * Layout matches the Rust DoubleBuffer in src/double_buffer.rs:
+ * - Bytes 0-3:   latest slot (u32) - 0 or 1
+ * - Bytes 4-7:   sequence (u32) - twice the frames published, odd while a
+ *   frame is being written; 0 = none yet
+ * - Bytes 8-11:  frame capacity (u32) - bytes per slot
+ * - Bytes 12-15: slot 0 length (u32)
+ * - Bytes 16-19: slot 1 length (u32)
+ * - Bytes 20-23: reserved
+ * - Bytes 24..:  slot 0 data, then slot 1 data
+ *
+ * The sequence word is a seqlock: readers keep a copy only if the sequence
+ * was even and unchanged while they copied.
+ *
+ * @module DoubleBuffer
+ * @see {@link file://./DESIGN_SYSTEM.md#wasm-bridge WASMBridge Documentation}
+ */
+
+const LATEST = 0;
+const SEQUENCE = 1;
+const CAPACITY = 2;
+const LENGTHS = [3, 4];
+
+/** Size of the double-buffer header in bytes */
+export const DOUBLE_BUFFER_HEADER_SIZE = 24;
+
+/** Read attempts before giving up on a producer that keeps overtaking */
+const MAX_READ_ATTEMPTS = 4;
+
+/**
+ * Double-buffered frame exchange over shared memory
+ */
+export class DoubleBuffer {
+  /**
+   * Attach to an initialized double buffer
+   *
+   * @param {SharedArrayBuffer|ArrayBuffer} buffer - Backing memory
+   * @param {number} byteOffset - Offset of the header (4-byte aligned)
+   */
+  constructor(buffer, byteOffset = 0) {
+    this.header = new Int32Array(buffer, byteOffset, DOUBLE_BUFFER_HEADER_SIZE / 4);
+    this.frameCapacity = Atomics.load(this.header, CAPACITY) >>> 0;
+
+    if (this.frameCapacity === 0) {
+      throw new Error('[DoubleBuffer] Region is not initialized');
+    }
+
+    const dataStart = byteOffset + DOUBLE_BUFFER_HEADER_SIZE;
+    this.slots = [
+      new Uint8Array(buffer, dataStart, this.frameCapacity),
+      new Uint8Array(buffer, dataStart + this.frameCapacity, this.frameCapacity),
+    ];
+  }
+
+  /**
+   * Allocate a SharedArrayBuffer and initialize a double buffer in it
+   *
+   * @param {number} frameCapacity - Bytes per frame slot
+   * @returns {DoubleBuffer} New double buffer
+   */
+  static create(frameCapacity) {
+    const buffer = new SharedArrayBuffer(DOUBLE_BUFFER_HEADER_SIZE + 2 * frameCapacity);
+    const header = new Int32Array(buffer, 0, DOUBLE_BUFFER_HEADER_SIZE / 4);
+    Atomics.store(header, CAPACITY, frameCapacity);
+    return new DoubleBuffer(buffer, 0);
+  }
+
+  /**
+   * Frames published so far
+   *
+   * @returns {number}
+   */
+  sequence() {
+    return Atomics.load(this.header, SEQUENCE) >>> 1;
+  }
+
+  /**
+   * Publish a frame (producer side)
+   *
+   * @param {Uint8Array} frame - Frame bytes
+   * @returns {boolean} False if the frame exceeds the slot capacity
+   */
+  writeFrame(frame) {
+    if (frame.length > this.frameCapacity) {
+      return false;
+    }
+
+    const sequence = Atomics.load(this.header, SEQUENCE) & ~1;
+    const target = sequence === 0 ? 0 : 1 - (Atomics.load(this.header, LATEST) & 1);
+
+    // Odd while the slot is being written
+    Atomics.store(this.header, SEQUENCE, (sequence + 1) | 0);
+    this.slots[target].set(frame);
+    Atomics.store(this.header, LENGTHS[target], frame.length);
+    Atomics.store(this.header, LATEST, target);
+    Atomics.store(this.header, SEQUENCE, (sequence + 2) | 0);
+    return true;
+  }
+
+  /**
+   * Copy the most recently published frame (consumer side)
+   *
+   * @returns {Uint8Array|null} Frame copy, or null if none is available
+   */
+  readLatestFrame() {
+    for (let attempt = 0; attempt < MAX_READ_ATTEMPTS; attempt++) {
+      const before = Atomics.load(this.header, SEQUENCE);
+      if (before === 0) {
+        return null;
+      }
+      if (before & 1) {
+        continue;
+      }
+
+      const slot = Atomics.load(this.header, LATEST) & 1;
+      const length = Math.min(Atomics.load(this.header, LENGTHS[slot]) >>> 0, this.frameCapacity);
+      const frame = this.slots[slot].slice(0, length);
+
+      if (Atomics.load(this.header, SEQUENCE) === before) {
+        return frame;
+      }
+    }
+    return null;
+  }
+}
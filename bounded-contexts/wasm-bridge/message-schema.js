@@ -0,0 +1,303 @@
+/**
+ * MessageSchema: Fixed-layout bridge message codecs
+ *
+ * Declares each bridge message once and generates encode/decode functions
+ * from the declaration, so callers never hard-code byte offsets. Layouts
+ * match the `bridge_message!` declarations in src/schema.rs: fields are
+ * little-endian, in declaration order, with no padding. Type ids match
+ * MessageType in wasm-bridge.js. Frame helpers build and verify channel
+ * frames, including the optional CRC-32 trailer (see src/integrity.rs).
+ *
+ * @module MessageSchema
+ * @see {@link file://./DESIGN_SYSTEM.md#wasm-bridge WASMBridge Documentation}
+ */
+
+/**
+ * Field encodings (matches Rust FieldKind)
+ */
+const FIELD_KINDS = {
+  u32: { size: 4, get: 'getUint32', set: 'setUint32' },
+  i32: { size: 4, get: 'getInt32', set: 'setInt32' },
+  f32: { size: 4, get: 'getFloat32', set: 'setFloat32' },
+  f64: { size: 8, get: 'getFloat64', set: 'setFloat64' },
+};
+
+/**
+ * Build a codec from a field list
+ *
+ * @param {number} msgType - Message type id
+ * @param {string} name - Registered type name
+ * @param {Array<[string, string]>} fields - [fieldName, kind] pairs in layout order
+ * @returns {{msgType: number, name: string, size: number, offsets: Object<string, number>,
+ *   encode: function(Object, Uint8Array=): Uint8Array, decode: function(Uint8Array): Object|null}}
+ */
+export function defineMessage(msgType, name, fields) {
+  const layout = [];
+  const offsets = {};
+  let size = 0;
+
+  for (const [field, kind] of fields) {
+    const spec = FIELD_KINDS[kind];
+    if (!spec) {
+      throw new Error(`[MessageSchema] Unknown field kind '${kind}' in ${name}`);
+    }
+    layout.push({ field, offset: size, ...spec });
+    offsets[field] = size;
+    size += spec.size;
+  }
+
+  return Object.freeze({
+    msgType,
+    name,
+    size,
+    offsets: Object.freeze(offsets),
+
+    encode(message, out = new Uint8Array(size)) {
+      const view = new DataView(out.buffer, out.byteOffset, size);
+      for (const { field, offset, set } of layout) {
+        view[set](offset, message[field] ?? 0, true);
+      }
+      return out;
+    },
+
+    decode(bytes) {
+      if (bytes.length < size) {
+        return null;
+      }
+      const view = new DataView(bytes.buffer, bytes.byteOffset, size);
+      const message = {};
+      for (const { field, offset, get } of layout) {
+        message[field] = view[get](offset, true);
+      }
+      return message;
+    },
+  });
+}
+
+/**
+ * Message header preceding every framed payload (matches Rust MessageHeader)
+ */
+export const MessageHeaderLayout = defineMessage(0, 'message-header', [
+  ['msgType', 'u32'],
+  ['payloadOffset', 'u32'],
+  ['payloadLen', 'u32'],
+  ['sequence', 'u32'],
+]);
+
+/** Set an audio node parameter, optionally ramping over rampMs */
+export const ParameterChange = defineMessage(7, 'parameter-change', [
+  ['nodeId', 'u32'],
+  ['paramId', 'u32'],
+  ['value', 'f32'],
+  ['rampMs', 'f32'],
+]);
+
+/** Transport control; position is in seconds */
+export const Transport = defineMessage(8, 'transport', [
+  ['command', 'u32'],
+  ['tempo', 'f32'],
+  ['position', 'f64'],
+]);
+
+/** Structural change to the graph */
+export const GraphMutation = defineMessage(1, 'graph-mutation', [
+  ['op', 'u32'],
+  ['source', 'u32'],
+  ['target', 'u32'],
+  ['edgeType', 'u32'],
+]);
+
+/** Ask the edge executor to traverse from startNode (edgeTypeMask 0 = all types) */
+export const TraversalRequest = defineMessage(3, 'traversal-request', [
+  ['requestId', 'u32'],
+  ['startNode', 'u32'],
+  ['maxDepth', 'u32'],
+  ['edgeTypeMask', 'u32'],
+]);
+
+/** Where a traversal result was written; free resultOffset once read */
+export const TraversalComplete = defineMessage(9, 'traversal-complete', [
+  ['requestId', 'u32'],
+  ['resultOffset', 'u32'],
+  ['resultLen', 'u32'],
+  ['status', 'u32'],
+]);
+
+/** Set an audio node parameter at an exact audio frame */
+export const ScheduledParameter = defineMessage(10, 'scheduled-parameter', [
+  ['frame', 'f64'],
+  ['nodeId', 'u32'],
+  ['paramId', 'u32'],
+  ['value', 'f32'],
+]);
+
+/** MIDI channel message (status, data1, data2) for an audio node at an exact audio frame */
+export const MidiEvent = defineMessage(11, 'midi-event', [
+  ['frame', 'f64'],
+  ['nodeId', 'u32'],
+  ['status', 'u32'],
+  ['data1', 'u32'],
+  ['data2', 'u32'],
+]);
+
+/** Bypass an audio node (bypassed = 1) or bring it back (0) at an exact audio frame */
+export const NodeBypass = defineMessage(12, 'node-bypass', [
+  ['frame', 'f64'],
+  ['nodeId', 'u32'],
+  ['bypassed', 'u32'],
+]);
+
+/** Set an audio graph's dry/wet level (0 = dry, 1 = wet) at an exact audio frame */
+export const GraphMix = defineMessage(13, 'graph-mix', [
+  ['frame', 'f64'],
+  ['wet', 'f32'],
+]);
+
+/**
+ * TraversalComplete.status values (matches Rust TraversalStatus)
+ * @enum {number}
+ */
+export const TraversalStatus = {
+  OK: 0,
+  OUT_OF_MEMORY: 1,
+};
+
+/**
+ * Transport.command values (matches Rust TransportCommand)
+ * @enum {number}
+ */
+export const TransportCommand = {
+  STOP: 0,
+  PLAY: 1,
+  PAUSE: 2,
+  SEEK: 3,
+};
+
+/**
+ * GraphMutation.op values (matches Rust GraphMutationOp)
+ * @enum {number}
+ */
+export const GraphMutationOp = {
+  ADD_NODE: 0,
+  REMOVE_NODE: 1,
+  CONNECT: 2,
+  DISCONNECT: 3,
+};
+
+/**
+ * Codecs keyed by message type id
+ * @type {Map<number, ReturnType<typeof defineMessage>>}
+ */
+export const MessageSchemas = new Map(
+  [
+    ParameterChange,
+    Transport,
+    GraphMutation,
+    TraversalRequest,
+    TraversalComplete,
+    ScheduledParameter,
+    MidiEvent,
+    NodeBypass,
+    GraphMix,
+  ].map((schema) => [schema.msgType, schema])
+);
+
+/** Size of the optional CRC-32 frame trailer in bytes */
+export const CHECKSUM_SIZE = 4;
+
+/**
+ * Integrity policies for setChannelIntegrity (matches Rust ViolationPolicy)
+ * @enum {number}
+ */
+export const ViolationPolicy = {
+  DROP: 0,
+  REPORT: 1,
+};
+
+/**
+ * Kinds passed to the integrity violation callback (matches Rust ViolationKind)
+ * @enum {number}
+ */
+export const ViolationKind = {
+  CHECKSUM: 1,
+  SEQUENCE: 2,
+};
+
+const CRC32_TABLE = (() => {
+  const table = new Uint32Array(256);
+  for (let i = 0; i < 256; i++) {
+    let crc = i;
+    for (let bit = 0; bit < 8; bit++) {
+      crc = crc & 1 ? 0xedb88320 ^ (crc >>> 1) : crc >>> 1;
+    }
+    table[i] = crc >>> 0;
+  }
+  return table;
+})();
+
+/**
+ * CRC-32 (IEEE 802.3), matching Rust crc32()
+ *
+ * @param {Uint8Array} bytes
+ * @returns {number} Unsigned checksum
+ */
+export function crc32(bytes) {
+  let crc = 0xffffffff;
+  for (let i = 0; i < bytes.length; i++) {
+    crc = CRC32_TABLE[(crc ^ bytes[i]) & 0xff] ^ (crc >>> 8);
+  }
+  return (crc ^ 0xffffffff) >>> 0;
+}
+
+/**
+ * Build a channel frame: header, payload, and optional CRC trailer
+ *
+ * @param {number} msgType - Message type id
+ * @param {number} sequence - Frame sequence number
+ * @param {Uint8Array} payload - Encoded message
+ * @param {boolean} [checksum=false] - Append a CRC-32 trailer
+ * @returns {Uint8Array} Frame ready for SpscRingBuffer.pushMessage
+ */
+export function encodeFrame(msgType, sequence, payload, checksum = false) {
+  const headerSize = MessageHeaderLayout.size;
+  const frame = new Uint8Array(headerSize + payload.length + (checksum ? CHECKSUM_SIZE : 0));
+  MessageHeaderLayout.encode(
+    { msgType, payloadOffset: headerSize, payloadLen: payload.length, sequence },
+    frame
+  );
+  frame.set(payload, headerSize);
+
+  if (checksum) {
+    const bodyLen = headerSize + payload.length;
+    new DataView(frame.buffer).setUint32(bodyLen, crc32(frame.subarray(0, bodyLen)), true);
+  }
+  return frame;
+}
+
+/**
+ * Split a channel frame into header and payload, verifying its CRC
+ *
+ * @param {Uint8Array} frame - Frame from SpscRingBuffer.popMessage
+ * @param {boolean} [checksum=false] - Frame carries a CRC-32 trailer
+ * @returns {{header: Object, payload: Uint8Array}|null} Null if truncated or corrupt
+ */
+export function decodeFrame(frame, checksum = false) {
+  let body = frame;
+  if (checksum) {
+    if (frame.length < CHECKSUM_SIZE) {
+      return null;
+    }
+    body = frame.subarray(0, frame.length - CHECKSUM_SIZE);
+    const view = new DataView(frame.buffer, frame.byteOffset, frame.byteLength);
+    if (view.getUint32(body.length, true) !== crc32(body)) {
+      return null;
+    }
+  }
+
+  const header = MessageHeaderLayout.decode(body);
+  if (!header || header.payloadOffset + header.payloadLen > body.length) {
+    return null;
+  }
+  const payload = body.subarray(header.payloadOffset, header.payloadOffset + header.payloadLen);
+  return { header, payload };
+}
@@ -0,0 +1,398 @@
+/**
+ * SnapshotOrchestrator: one archive for every bounded context
+ *
+ * Each context registers as a participant that exports its state as
+ * JSON-compatible data and imports it back. `snapshot()` collects every
+ * participant into one versioned archive, so "save project" is a single
+ * call; `restore()` hands each participant its part, in registration order,
+ * and rolls every participant back to its previous state if one fails.
+ *
+ * Archive layout:
+ * - `format`: always 'harmony-project'
+ * - `version`: ARCHIVE_VERSION of the layout
+ * - `createdAt`: milliseconds since the Unix epoch
+ * - `contexts`: participant name → `{ version, data }`
+ *
+ * Adapters at the end of this module make participants of the graph
+ * executor, lifecycle BC, node registry, spatial index, full-text index
+ * and the harmony-ids node ID map.
+ *
+ * @module SnapshotOrchestrator
+ * @see {@link file://./DESIGN_SYSTEM.md#wasm-bridge WASMBridge Documentation}
+ */
+
+import { ErrorCode } from '../../harmony-error/harmony-error.js';
+
+/** Value of an archive's `format` field */
+export const ARCHIVE_FORMAT = 'harmony-project';
+
+/** Archive layout version written by `snapshot` */
+export const ARCHIVE_VERSION = 1;
+
+/**
+ * A bounded context taking part in snapshots
+ * @typedef {Object} SnapshotParticipant
+ * @property {number} version - Version of the data `export` returns
+ * @property {function(): (unknown|Promise<unknown>)} export - Capture state as JSON-compatible data
+ * @property {function(unknown, number): (void|Promise<void>)} import - Replace state
+ *   with exported data of the given version
+ */
+
+/**
+ * @typedef {Object} ProjectArchive
+ * @property {string} format - ARCHIVE_FORMAT
+ * @property {number} version - ARCHIVE_VERSION
+ * @property {number} createdAt - Milliseconds since the Unix epoch
+ * @property {Object<string, {version: number, data: unknown}>} contexts - Data by participant name
+ */
+
+/**
+ * Error shaped like the WASM contexts' HarmonyError
+ *
+ * @param {string} code - ErrorCode
+ * @param {string} message - Error message
+ * @param {string[]} [context] - Context, outermost first
+ * @returns {Error}
+ */
+function snapshotError(code, message, context = []) {
+  const error = new Error(context.length > 0 ? `${context.join(': ')}: ${message}` : message);
+  error.name = 'HarmonyError';
+  error.code = code;
+  error.context = context;
+  return error;
+}
+
+/**
+ * Coordinates snapshots and restores across bounded contexts
+ */
+export class SnapshotOrchestrator {
+  constructor() {
+    /** @type {Map<string, SnapshotParticipant>} */
+    this.participants = new Map();
+  }
+
+  /**
+   * Add a participant; restores run in registration order
+   *
+   * @param {string} name - Key of the participant's data in the archive
+   * @param {SnapshotParticipant} participant - Export and import functions
+   * @returns {SnapshotOrchestrator} This orchestrator, for chaining
+   */
+  register(name, participant) {
+    if (this.participants.has(name)) {
+      throw snapshotError(ErrorCode.INVALID_INPUT, `Participant '${name}' is already registered`);
+    }
+    this.participants.set(name, participant);
+    return this;
+  }
+
+  /**
+   * Remove a participant
+   *
+   * @param {string} name - Participant name
+   * @returns {boolean} Whether the participant was registered
+   */
+  unregister(name) {
+    return this.participants.delete(name);
+  }
+
+  /**
+   * Capture every participant into one archive
+   *
+   * @returns {Promise<ProjectArchive>}
+   */
+  async snapshot() {
+    const contexts = {};
+    for (const [name, participant] of this.participants) {
+      try {
+        contexts[name] = { version: participant.version, data: await participant.export() };
+      } catch (error) {
+        throw withContext(error, name);
+      }
+    }
+    return { format: ARCHIVE_FORMAT, version: ARCHIVE_VERSION, createdAt: Date.now(), contexts };
+  }
+
+  /**
+   * Restore every participant from an archive
+   *
+   * Participants missing from the archive keep their state, and contexts
+   * with no registered participant are skipped. If any import fails, every
+   * participant imported so far is restored to its state before the call.
+   *
+   * @param {ProjectArchive} archive - Archive from `snapshot` or `parse`
+   * @returns {Promise<{restored: string[], skipped: string[]}>} Participant
+   *   names restored, and archive contexts with no participant
+   * @throws {Error} HarmonyError: `unsupported` for a newer archive or
+   *   context version, `malformed` for a non-archive, or the failing
+   *   participant's error with its name as context
+   */
+  async restore(archive) {
+    validateArchive(archive);
+    for (const [name, participant] of this.participants) {
+      const saved = archive.contexts[name];
+      if (saved && saved.version > participant.version) {
+        throw snapshotError(
+          ErrorCode.UNSUPPORTED,
+          `Version ${saved.version} is newer than ${participant.version}`,
+          [name]
+        );
+      }
+    }
+
+    const backup = await this.snapshot();
+    const restored = [];
+    for (const [name, participant] of this.participants) {
+      const saved = archive.contexts[name];
+      if (!saved) {
+        continue;
+      }
+      try {
+        await participant.import(saved.data, saved.version);
+        restored.push(name);
+      } catch (error) {
+        await this.#rollback([...restored, name], backup);
+        throw withContext(error, name);
+      }
+    }
+
+    const skipped = Object.keys(archive.contexts).filter(name => !this.participants.has(name));
+    return { restored, skipped };
+  }
+
+  /**
+   * Serialize an archive for storage
+   *
+   * @param {ProjectArchive} archive - Archive from `snapshot`
+   * @returns {string} JSON
+   */
+  static stringify(archive) {
+    return JSON.stringify(archive);
+  }
+
+  /**
+   * Read an archive written by `stringify`
+   *
+   * @param {string} json - Stored archive
+   * @returns {ProjectArchive}
+   * @throws {Error} HarmonyError `malformed` for invalid JSON or a non-archive,
+   *   `unsupported` for a newer archive version
+   */
+  static parse(json) {
+    let archive;
+    try {
+      archive = JSON.parse(json);
+    } catch (error) {
+      throw snapshotError(ErrorCode.MALFORMED, error.message, ['Invalid archive JSON']);
+    }
+    validateArchive(archive);
+    return archive;
+  }
+
+  /**
+   * Best-effort restore of participants from the pre-restore backup
+   * @private
+   * @param {string[]} names - Participants to roll back
+   * @param {ProjectArchive} backup - Snapshot taken before the restore
+   */
+  async #rollback(names, backup) {
+    for (const name of names) {
+      const saved = backup.contexts[name];
+      try {
+        await this.participants.get(name).import(saved.data, saved.version);
+      } catch (error) {
+        console.error(`[SnapshotOrchestrator] Rollback of '${name}' failed:`, error);
+      }
+    }
+  }
+}
+
+/**
+ * Checks an archive's format and version
+ *
+ * @param {unknown} archive - Value claiming to be a ProjectArchive
+ * @throws {Error} HarmonyError `malformed` or `unsupported`
+ */
+function validateArchive(archive) {
+  if (
+    !archive ||
+    archive.format !== ARCHIVE_FORMAT ||
+    typeof archive.contexts !== 'object' ||
+    archive.contexts === null
+  ) {
+    throw snapshotError(ErrorCode.MALFORMED, `Not a ${ARCHIVE_FORMAT} archive`);
+  }
+  if (!(archive.version <= ARCHIVE_VERSION)) {
+    throw snapshotError(
+      ErrorCode.UNSUPPORTED,
+      `Archive version ${archive.version} is newer than ${ARCHIVE_VERSION}`
+    );
+  }
+}
+
+/**
+ * Adds a participant name as the outermost context of an error
+ *
+ * @param {unknown} error - Error thrown by a participant
+ * @param {string} name - Participant name
+ * @returns {Error}
+ */
+function withContext(error, name) {
+  if (error instanceof Error && error.name === 'HarmonyError') {
+    const wrapped = snapshotError(error.code, error.message, [name]);
+    wrapped.context = [name, ...(error.context ?? [])];
+    return wrapped;
+  }
+  return snapshotError(ErrorCode.INTERNAL, String(error?.message ?? error), [name]);
+}
+
+/**
+ * Encode bytes as base64, for binary state in the JSON archive
+ *
+ * @param {Uint8Array} bytes
+ * @returns {string}
+ */
+function toBase64(bytes) {
+  let binary = '';
+  for (let i = 0; i < bytes.length; i += 0x8000) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + 0x8000));
+  }
+  return btoa(binary);
+}
+
+/**
+ * Decode base64 from `toBase64`
+ *
+ * @param {string} base64
+ * @returns {Uint8Array}
+ */
+function fromBase64(base64) {
+  return Uint8Array.from(atob(base64), char => char.charCodeAt(0));
+}
+
+/**
+ * Participant for a WASMEdgeExecutor: edge types as JSON, edges as
+ * base64 EdgeBinaryFormat, the nodes without edges as an ID array and
+ * edge weights as base64 f32s, in edge order
+ *
+ * Version 1 data has no `isolatedNodes`; it restores the nodes on edges.
+ * Versions 1 and 2 have no `weights`; every edge gets the default weight.
+ *
+ * @param {Object} executor - WASMEdgeExecutor instance
+ * @returns {SnapshotParticipant}
+ */
+export function graphParticipant(executor) {
+  return {
+    version: 3,
+    export: () => {
+      const weights = executor.exportWeights();
+      return {
+        edgeTypes: JSON.parse(executor.getEdgeTypes()),
+        edges: toBase64(executor.exportEdges()),
+        isolatedNodes: Array.from(executor.isolatedNodes()),
+        weights: toBase64(new Uint8Array(weights.buffer, weights.byteOffset, weights.byteLength)),
+      };
+    },
+    import: (data) => {
+      executor.restore(JSON.stringify(data.edgeTypes), fromBase64(data.edges));
+      executor.addNodes(Uint32Array.from(data.isolatedNodes ?? []));
+      if (data.weights !== undefined) {
+        executor.setWeights(new Float32Array(fromBase64(data.weights).buffer));
+      }
+    },
+  };
+}
+
+/**
+ * Participant for a ComponentLifecycleBC, using its LifecycleSnapshot
+ *
+ * @param {Object} lifecycle - ComponentLifecycleBC instance
+ * @returns {SnapshotParticipant}
+ */
+export function lifecycleParticipant(lifecycle) {
+  return {
+    version: 1,
+    export: () => JSON.parse(lifecycle.exportState()),
+    import: (data) => {
+      const result = JSON.parse(lifecycle.importState(JSON.stringify(data)));
+      if (!result.success) {
+        throw snapshotError(ErrorCode.MALFORMED, result.error);
+      }
+    },
+  };
+}
+
+/**
+ * Participant for a WASMNodeRegistry: its registered node types
+ *
+ * @param {Object} registry - WASMNodeRegistry instance
+ * @returns {SnapshotParticipant}
+ */
+export function registryParticipant(registry) {
+  return {
+    version: 1,
+    export: () => registry.listAll(),
+    import: (data) => {
+      registry.clear();
+      for (const metadata of data) {
+        registry.register(metadata);
+      }
+    },
+  };
+}
+
+/**
+ * Participant for a SpatialIndexWrapper
+ *
+ * @param {Object} spatialIndex - SpatialIndexWrapper instance
+ * @returns {SnapshotParticipant}
+ */
+export function spatialParticipant(spatialIndex) {
+  return {
+    version: 1,
+    export: () => spatialIndex.exportState(),
+    import: (data) => spatialIndex.importState(data),
+  };
+}
+
+/**
+ * Participant for every index of the full-text-index module
+ *
+ * @param {{exportIndices: Function, importIndices: Function}} fullTextIndex -
+ *   The full-text-index.js module
+ * @returns {SnapshotParticipant}
+ */
+export function fullTextParticipant(fullTextIndex) {
+  return {
+    version: 1,
+    export: async () => {
+      const result = await fullTextIndex.exportIndices();
+      if (!result.success) {
+        throw snapshotError(result.code, result.error);
+      }
+      return result.indices;
+    },
+    import: async (data) => {
+      const result = await fullTextIndex.importIndices(data);
+      if (!result.success) {
+        throw snapshotError(result.code, result.error);
+      }
+    },
+  };
+}
+
+/**
+ * Participant for a WASM module's harmony-ids node ID map, so node IDs
+ * in a restored project match the ones it was saved with
+ *
+ * @param {{export_node_ids: function(): string, import_node_ids: function(string): void}} module -
+ *   Exports of a WASM module linking harmony-ids
+ * @returns {SnapshotParticipant}
+ */
+export function nodeIdsParticipant(module) {
+  return {
+    version: 1,
+    export: () => JSON.parse(module.export_node_ids()),
+    import: (data) => module.import_node_ids(JSON.stringify(data)),
+  };
+}
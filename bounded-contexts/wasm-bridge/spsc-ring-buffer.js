@@ -0,0 +1,246 @@
+/**
+ * SpscRingBuffer: Lock-free single-producer/single-consumer message queue
+ *
+ * Exchanges length-prefixed messages over a SharedArrayBuffer (or WASM
+ * memory) using Atomics-based head/tail indices, so the audio worklet and
+ * main thread can pass events without postMessage copies.
+ *
+ * Layout matches the Rust RingBuffer in src/ring_buffer.rs:
+ * - Bytes 0-3:   head (u32) - read index, written only by the consumer
+ * - Bytes 4-7:   tail (u32) - write index, written only by the producer
+ * - Bytes 8-11:  capacity (u32) - data bytes, power of two
+ * - Bytes 12-15: message count (u32) - incremented by producer, decremented by consumer
+ * - Bytes 16..:  data
+ *
+ * @module SpscRingBuffer
+ * @see {@link file://./DESIGN_SYSTEM.md#wasm-bridge WASMBridge Documentation}
+ */
+
+const HEAD = 0;
+const TAIL = 1;
+const CAPACITY = 2;
+const COUNT = 3;
+
+/** Size of the ring header in bytes */
+export const RING_HEADER_SIZE = 16;
+
+/** Size of the per-message length prefix in bytes */
+export const MESSAGE_PREFIX_SIZE = 4;
+
+/**
+ * Results of tryPushMessage
+ * @enum {number}
+ */
+export const PushResult = {
+  OK: 0,
+  WOULD_BLOCK: 1,
+  FULL: 2,
+};
+
+/**
+ * Single-producer/single-consumer ring buffer over shared memory
+ */
+export class SpscRingBuffer {
+  /**
+   * Attach to an initialized ring
+   *
+   * @param {SharedArrayBuffer|ArrayBuffer} buffer - Backing memory
+   * @param {number} byteOffset - Offset of the ring header (4-byte aligned)
+   */
+  constructor(buffer, byteOffset = 0) {
+    this.header = new Int32Array(buffer, byteOffset, 4);
+    this.shared = typeof SharedArrayBuffer !== 'undefined' && buffer instanceof SharedArrayBuffer;
+    this.capacity = Atomics.load(this.header, CAPACITY) >>> 0;
+
+    if (this.capacity < 8 || (this.capacity & (this.capacity - 1)) !== 0) {
+      throw new Error(`[SpscRingBuffer] Invalid capacity ${this.capacity}`);
+    }
+
+    this.mask = this.capacity - 1;
+    this.data = new Uint8Array(buffer, byteOffset + RING_HEADER_SIZE, this.capacity);
+    this.prefix = new Uint8Array(MESSAGE_PREFIX_SIZE);
+    this.prefixView = new DataView(this.prefix.buffer);
+
+    /** @type {{low: number, high: number}|null} */
+    this.watermarks = null;
+    this.blocked = false;
+  }
+
+  /**
+   * Allocate a SharedArrayBuffer and initialize a ring in it
+   *
+   * @param {number} capacity - Data capacity in bytes (power of two, >= 8)
+   * @returns {SpscRingBuffer} New ring
+   */
+  static create(capacity) {
+    const buffer = new SharedArrayBuffer(RING_HEADER_SIZE + capacity);
+    SpscRingBuffer.initialize(buffer, 0, capacity);
+    return new SpscRingBuffer(buffer, 0);
+  }
+
+  /**
+   * Write a fresh ring header into existing memory
+   *
+   * @param {SharedArrayBuffer|ArrayBuffer} buffer - Backing memory
+   * @param {number} byteOffset - Offset of the ring header
+   * @param {number} capacity - Data capacity in bytes (power of two, >= 8)
+   */
+  static initialize(buffer, byteOffset, capacity) {
+    const header = new Int32Array(buffer, byteOffset, 4);
+    Atomics.store(header, HEAD, 0);
+    Atomics.store(header, TAIL, 0);
+    Atomics.store(header, CAPACITY, capacity);
+    Atomics.store(header, COUNT, 0);
+  }
+
+  /**
+   * Bytes currently queued, including length prefixes
+   *
+   * @returns {number}
+   */
+  usedBytes() {
+    const head = Atomics.load(this.header, HEAD);
+    const tail = Atomics.load(this.header, TAIL);
+    return (tail - head) >>> 0;
+  }
+
+  /**
+   * Messages currently queued
+   *
+   * @returns {number}
+   */
+  pendingMessages() {
+    return Atomics.load(this.header, COUNT) >>> 0;
+  }
+
+  /**
+   * Set flow-control thresholds in queued bytes (producer side)
+   *
+   * Once a push would exceed `high`, tryPushMessage reports WOULD_BLOCK
+   * until the consumer drains the ring to `low`.
+   *
+   * @param {number} low - Queued bytes at which a blocked ring reopens
+   * @param {number} high - Queued bytes a push may not exceed
+   */
+  setWatermarks(low, high) {
+    if (low > high || high > this.capacity) {
+      throw new Error('[SpscRingBuffer] Watermarks must satisfy low <= high <= capacity');
+    }
+    this.watermarks = { low, high };
+    this.blocked = false;
+  }
+
+  /**
+   * Enqueue a message honoring watermarks (producer side)
+   *
+   * @param {Uint8Array} payload - Message bytes
+   * @returns {number} A PushResult value
+   */
+  tryPushMessage(payload) {
+    if (this.watermarks) {
+      const used = this.usedBytes();
+      if (this.blocked) {
+        if (used > this.watermarks.low) {
+          return PushResult.WOULD_BLOCK;
+        }
+        this.blocked = false;
+      }
+      if (used + MESSAGE_PREFIX_SIZE + payload.length > this.watermarks.high) {
+        this.blocked = true;
+        return PushResult.WOULD_BLOCK;
+      }
+    }
+    return this.pushMessage(payload) ? PushResult.OK : PushResult.FULL;
+  }
+
+  /**
+   * Enqueue a message (producer side)
+   *
+   * @param {Uint8Array} payload - Message bytes
+   * @returns {boolean} False if the ring is full
+   */
+  pushMessage(payload) {
+    const needed = MESSAGE_PREFIX_SIZE + payload.length;
+    const head = Atomics.load(this.header, HEAD);
+    const tail = Atomics.load(this.header, TAIL);
+
+    if (needed > this.capacity - ((tail - head) >>> 0)) {
+      return false;
+    }
+
+    this.prefixView.setUint32(0, payload.length, true);
+    this._write(tail, this.prefix);
+    this._write(tail + MESSAGE_PREFIX_SIZE, payload);
+    Atomics.store(this.header, TAIL, (tail + needed) | 0);
+    Atomics.add(this.header, COUNT, 1);
+    if (this.shared) {
+      Atomics.notify(this.header, TAIL);
+    }
+    return true;
+  }
+
+  /**
+   * Block until a message is queued (consumer side, workers only)
+   *
+   * Sleeps on the tail index, which producers notify after each push,
+   * including pushes made from Rust. Browsers forbid Atomics.wait on the
+   * main thread, and non-shared buffers cannot block at all.
+   *
+   * @param {number} [timeoutMs=Infinity] - Maximum time to wait
+   * @returns {boolean} True if a message is available
+   */
+  waitForMessage(timeoutMs = Infinity) {
+    const tail = Atomics.load(this.header, TAIL);
+    if (Atomics.load(this.header, HEAD) !== tail) {
+      return true;
+    }
+    if (this.shared) {
+      Atomics.wait(this.header, TAIL, tail, timeoutMs);
+    }
+    return this.usedBytes() > 0;
+  }
+
+  /**
+   * Dequeue the oldest message (consumer side)
+   *
+   * @returns {Uint8Array|null} Message bytes, or null if empty
+   */
+  popMessage() {
+    const tail = Atomics.load(this.header, TAIL);
+    const head = Atomics.load(this.header, HEAD);
+
+    if (head === tail) {
+      return null;
+    }
+
+    this._read(head, this.prefix);
+    const length = this.prefixView.getUint32(0, true);
+    const payload = new Uint8Array(length);
+    this._read(head + MESSAGE_PREFIX_SIZE, payload);
+    Atomics.store(this.header, HEAD, (head + MESSAGE_PREFIX_SIZE + length) | 0);
+    Atomics.sub(this.header, COUNT, 1);
+    return payload;
+  }
+
+  /**
+   * Copy bytes into the data region with wrap-around
+   * @private
+   */
+  _write(index, bytes) {
+    const pos = index & this.mask;
+    const first = Math.min(bytes.length, this.capacity - pos);
+    this.data.set(bytes.subarray(0, first), pos);
+    this.data.set(bytes.subarray(first), 0);
+  }
+
+  /**
+   * Copy bytes out of the data region with wrap-around
+   * @private
+   */
+  _read(index, out) {
+    const pos = index & this.mask;
+    const first = Math.min(out.length, this.capacity - pos);
+    out.set(this.data.subarray(pos, pos + first), 0);
+    out.set(this.data.subarray(0, out.length - first), first);
+  }
+}
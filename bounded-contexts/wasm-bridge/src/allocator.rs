@@ -0,0 +1,344 @@
+//! FreeListAllocator: Reusable region allocator for the shared buffer
+//!
+//! First-fit free-list allocator with coalescing on free, plus region-scoped
+//! arenas: every allocation made between `begin_scope` and `end_scope` is
+//! released when the scope ends. Long-running sessions reuse freed space
+//! instead of exhausting the buffer like a bump allocator would.
+//!
+//! All offsets and sizes are aligned to `ALIGNMENT` bytes. Each live
+//! allocation keeps a record (tag, optional call site, generation) that the
+//! diagnostics report uses to attribute usage and find leaks.
+
+use std::collections::BTreeMap;
+
+/// Alignment of every allocation in bytes
+pub const ALIGNMENT: usize = 8;
+
+/// Rounds `size` up to the allocation alignment
+#[inline]
+fn align_up(size: usize) -> Option<usize> {
+    size.checked_add(ALIGNMENT - 1).map(|s| s & !(ALIGNMENT - 1))
+}
+
+/// Tag given to allocations made without one
+pub const UNTAGGED: &str = "untagged";
+
+/// Bookkeeping for one live allocation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllocationRecord {
+    pub offset: usize,
+    /// Aligned size in bytes
+    pub size: usize,
+    /// Owning subsystem, e.g. "ring-buffer" or "channel:params"
+    pub tag: String,
+    /// Where the allocation was made, recorded in debug mode
+    pub call_site: Option<String>,
+    /// Allocation sequence number; later allocations have larger values
+    pub generation: u32,
+}
+
+/// Snapshot of allocator usage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AllocatorStats {
+    pub total_bytes: usize,
+    pub used_bytes: usize,
+    pub free_bytes: usize,
+    pub largest_free_block: usize,
+    pub free_block_count: usize,
+    pub allocation_count: usize,
+    /// Highest `used_bytes` observed since creation or reset
+    pub high_water_mark: usize,
+    /// 0.0 when all free space is contiguous, approaching 1.0 as it splinters
+    pub fragmentation: f32,
+}
+
+/// First-fit free-list allocator over a fixed-size region
+#[derive(Debug, Clone)]
+pub struct FreeListAllocator {
+    capacity: usize,
+    /// Free blocks keyed by offset → size, kept coalesced
+    free_blocks: BTreeMap<usize, usize>,
+    /// Live allocations keyed by offset
+    allocations: BTreeMap<usize, AllocationRecord>,
+    /// Offsets allocated inside each open scope (innermost last)
+    scopes: Vec<Vec<usize>>,
+    used_bytes: usize,
+    high_water_mark: usize,
+    next_generation: u32,
+}
+
+impl FreeListAllocator {
+    /// Creates an allocator managing `capacity` bytes
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity & !(ALIGNMENT - 1);
+        let mut free_blocks = BTreeMap::new();
+        if capacity > 0 {
+            free_blocks.insert(0, capacity);
+        }
+
+        Self {
+            capacity,
+            free_blocks,
+            allocations: BTreeMap::new(),
+            scopes: Vec::new(),
+            used_bytes: 0,
+            high_water_mark: 0,
+            next_generation: 0,
+        }
+    }
+
+    /// Allocates `size` bytes and returns the offset, or None when no block fits
+    pub fn allocate(&mut self, size: usize) -> Option<usize> {
+        self.allocate_tagged(size, UNTAGGED, None)
+    }
+
+    /// Allocates `size` bytes on behalf of `tag`, optionally noting the call site
+    pub fn allocate_tagged(
+        &mut self,
+        size: usize,
+        tag: &str,
+        call_site: Option<String>,
+    ) -> Option<usize> {
+        let size = align_up(size.max(1))?;
+
+        let (&offset, &block_size) = self
+            .free_blocks
+            .iter()
+            .find(|(_, &block_size)| block_size >= size)?;
+
+        self.free_blocks.remove(&offset);
+        if block_size > size {
+            self.free_blocks.insert(offset + size, block_size - size);
+        }
+
+        self.allocations.insert(
+            offset,
+            AllocationRecord {
+                offset,
+                size,
+                tag: tag.to_string(),
+                call_site,
+                generation: self.next_generation,
+            },
+        );
+        self.next_generation = self.next_generation.wrapping_add(1);
+        self.used_bytes += size;
+        self.high_water_mark = self.high_water_mark.max(self.used_bytes);
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(offset);
+        }
+
+        Some(offset)
+    }
+
+    /// Frees the allocation starting at `offset`
+    ///
+    /// # Returns
+    /// false if `offset` is not the start of a live allocation
+    pub fn free(&mut self, offset: usize) -> bool {
+        let size = match self.allocations.remove(&offset) {
+            Some(record) => record.size,
+            None => return false,
+        };
+        self.used_bytes -= size;
+
+        let mut start = offset;
+        let mut len = size;
+
+        // Coalesce with the preceding free block
+        if let Some((&prev_offset, &prev_size)) = self.free_blocks.range(..offset).next_back() {
+            if prev_offset + prev_size == offset {
+                self.free_blocks.remove(&prev_offset);
+                start = prev_offset;
+                len += prev_size;
+            }
+        }
+
+        // Coalesce with the following free block
+        if let Some(next_size) = self.free_blocks.remove(&(offset + size)) {
+            len += next_size;
+        }
+
+        self.free_blocks.insert(start, len);
+        true
+    }
+
+    /// Opens a region scope and returns the new scope depth
+    pub fn begin_scope(&mut self) -> usize {
+        self.scopes.push(Vec::new());
+        self.scopes.len()
+    }
+
+    /// Closes the innermost scope, freeing every allocation made inside it
+    ///
+    /// # Returns
+    /// false if no scope is open
+    pub fn end_scope(&mut self) -> bool {
+        match self.scopes.pop() {
+            Some(offsets) => {
+                for offset in offsets {
+                    // Already-freed offsets are skipped by `free`
+                    self.free(offset);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Number of open scopes
+    #[inline]
+    pub fn scope_depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Releases every allocation and closes all scopes
+    pub fn reset(&mut self) {
+        *self = Self::new(self.capacity);
+    }
+
+    /// Size of the live allocation at `offset`
+    #[inline]
+    pub fn allocation_size(&self, offset: usize) -> Option<usize> {
+        self.allocations.get(&offset).map(|record| record.size)
+    }
+
+    /// Record of the live allocation at `offset`
+    pub fn allocation(&self, offset: usize) -> Option<&AllocationRecord> {
+        self.allocations.get(&offset)
+    }
+
+    /// Live allocations in offset order
+    pub fn allocations(&self) -> impl Iterator<Item = &AllocationRecord> {
+        self.allocations.values()
+    }
+
+    /// Re-tags a live allocation
+    ///
+    /// # Returns
+    /// false if `offset` is not the start of a live allocation
+    pub fn set_tag(&mut self, offset: usize, tag: &str) -> bool {
+        match self.allocations.get_mut(&offset) {
+            Some(record) => {
+                record.tag = tag.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Generation the next allocation will receive
+    #[inline]
+    pub fn next_generation(&self) -> u32 {
+        self.next_generation
+    }
+
+    /// Managed capacity in bytes
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Current usage and fragmentation
+    pub fn stats(&self) -> AllocatorStats {
+        let free_bytes: usize = self.free_blocks.values().sum();
+        let largest_free_block = self.free_blocks.values().copied().max().unwrap_or(0);
+        let fragmentation = if free_bytes == 0 {
+            0.0
+        } else {
+            1.0 - largest_free_block as f32 / free_bytes as f32
+        };
+
+        AllocatorStats {
+            total_bytes: self.capacity,
+            used_bytes: self.capacity - free_bytes,
+            free_bytes,
+            largest_free_block,
+            free_block_count: self.free_blocks.len(),
+            allocation_count: self.allocations.len(),
+            high_water_mark: self.high_water_mark,
+            fragmentation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate_aligns_and_fills() {
+        let mut alloc = FreeListAllocator::new(32);
+        assert_eq!(alloc.allocate(3), Some(0));
+        assert_eq!(alloc.allocate(8), Some(8));
+        assert_eq!(alloc.allocate(16), Some(16));
+        assert_eq!(alloc.allocate(1), None);
+        assert_eq!(alloc.allocation_size(0), Some(8));
+    }
+
+    #[test]
+    fn test_free_reuses_and_coalesces() {
+        let mut alloc = FreeListAllocator::new(32);
+        let a = alloc.allocate(8).unwrap();
+        let b = alloc.allocate(8).unwrap();
+        let c = alloc.allocate(8).unwrap();
+
+        assert!(alloc.free(a));
+        assert!(alloc.free(c));
+        assert_eq!(alloc.stats().free_block_count, 2);
+
+        assert!(alloc.free(b));
+        let stats = alloc.stats();
+        assert_eq!(stats.free_block_count, 1);
+        assert_eq!(stats.largest_free_block, 32);
+        assert_eq!(stats.fragmentation, 0.0);
+
+        assert!(!alloc.free(b));
+    }
+
+    #[test]
+    fn test_fragmentation_stats() {
+        let mut alloc = FreeListAllocator::new(32);
+        let a = alloc.allocate(8).unwrap();
+        alloc.allocate(8).unwrap();
+
+        alloc.free(a);
+        let stats = alloc.stats();
+        assert_eq!(stats.used_bytes, 8);
+        assert_eq!(stats.free_bytes, 24);
+        assert_eq!(stats.largest_free_block, 16);
+        assert_eq!(stats.high_water_mark, 16);
+        assert!(stats.fragmentation > 0.3 && stats.fragmentation < 0.34);
+    }
+
+    #[test]
+    fn test_scopes_free_inner_allocations() {
+        let mut alloc = FreeListAllocator::new(64);
+        let outer = alloc.allocate(8).unwrap();
+
+        assert_eq!(alloc.begin_scope(), 1);
+        let inner = alloc.allocate(8).unwrap();
+        alloc.allocate(8).unwrap();
+        alloc.free(inner);
+
+        assert!(alloc.end_scope());
+        assert!(!alloc.end_scope());
+
+        let stats = alloc.stats();
+        assert_eq!(stats.allocation_count, 1);
+        assert_eq!(alloc.allocation_size(outer), Some(8));
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut alloc = FreeListAllocator::new(16);
+        alloc.begin_scope();
+        alloc.allocate(16).unwrap();
+
+        alloc.reset();
+        assert_eq!(alloc.scope_depth(), 0);
+        assert_eq!(alloc.stats().free_bytes, 16);
+        assert_eq!(alloc.allocate(16), Some(0));
+    }
+}
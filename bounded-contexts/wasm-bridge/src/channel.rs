@@ -0,0 +1,456 @@
+//! Named channels with message-type dispatch
+//!
+//! A channel is a named SPSC ring carrying framed messages. Each frame is a
+//! `MessageHeader` followed by its payload; `payload_offset` is relative to
+//! the start of the frame. Message types must be registered before they can
+//! be sent, and the dispatcher delivers received frames to every Rust
+//! handler or JS callback registered for the frame's `msg_type`.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-bridge
+
+use std::collections::HashMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::flow_control::{frame_bytes, SendError, Watermarks};
+use crate::integrity::IntegrityState;
+use crate::{BridgeContext, MessageHeader};
+
+/// Rust message handler
+pub type MessageHandler = Box<dyn FnMut(&MessageHeader, &[u8])>;
+
+/// Handler registered for a message type
+enum Handler {
+    Rust(MessageHandler),
+    #[cfg(feature = "wasm")]
+    Js(js_sys::Function),
+}
+
+/// Named channel backed by a ring buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Channel {
+    pub name: String,
+    /// Offset of the channel's ring region
+    pub ring_offset: usize,
+    /// Sequence number assigned to the next sent message
+    pub next_sequence: u32,
+    /// Flow-control thresholds, if configured
+    pub watermarks: Option<Watermarks>,
+    /// True while sends are rejected until the queue drains to the low watermark
+    pub blocked: bool,
+    /// Checksum/sequence validation, if configured
+    pub integrity: Option<IntegrityState>,
+}
+
+/// Message-type registry and handler table
+#[derive(Default)]
+pub struct MessageDispatcher {
+    type_names: HashMap<u32, String>,
+    handlers: HashMap<u32, Vec<Handler>>,
+}
+
+impl MessageDispatcher {
+    /// Creates an empty dispatcher
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a message type under a name
+    ///
+    /// # Returns
+    /// false if the type is already registered under a different name
+    pub fn register_type(&mut self, msg_type: u32, name: &str) -> bool {
+        match self.type_names.get(&msg_type) {
+            Some(existing) => existing == name,
+            None => {
+                self.type_names.insert(msg_type, name.to_string());
+                true
+            }
+        }
+    }
+
+    /// Returns true if the message type is registered
+    pub fn is_registered(&self, msg_type: u32) -> bool {
+        self.type_names.contains_key(&msg_type)
+    }
+
+    /// Name a message type was registered under
+    pub fn type_name(&self, msg_type: u32) -> Option<&str> {
+        self.type_names.get(&msg_type).map(String::as_str)
+    }
+
+    /// Adds a Rust handler for a message type
+    pub fn on_message(&mut self, msg_type: u32, handler: MessageHandler) {
+        self.handlers
+            .entry(msg_type)
+            .or_default()
+            .push(Handler::Rust(handler));
+    }
+
+    /// Adds a JS callback for a message type
+    ///
+    /// The callback is invoked as `callback(header, payload)` where header is
+    /// `[msgType, payloadOffset, payloadLen, sequence]`.
+    #[cfg(feature = "wasm")]
+    pub fn on_message_js(&mut self, msg_type: u32, callback: js_sys::Function) {
+        self.handlers
+            .entry(msg_type)
+            .or_default()
+            .push(Handler::Js(callback));
+    }
+
+    /// Merges registrations from `other`, keeping its handlers first
+    ///
+    /// Used to restore a dispatcher that was taken out of its context for a
+    /// re-entrant dispatch, without losing registrations made meanwhile.
+    pub fn absorb(&mut self, other: MessageDispatcher) {
+        for (msg_type, name) in other.type_names {
+            self.type_names.entry(msg_type).or_insert(name);
+        }
+        for (msg_type, mut handlers) in other.handlers {
+            let added = self.handlers.remove(&msg_type).unwrap_or_default();
+            handlers.extend(added);
+            self.handlers.insert(msg_type, handlers);
+        }
+    }
+
+    /// Removes every handler for a message type
+    pub fn clear_handlers(&mut self, msg_type: u32) {
+        self.handlers.remove(&msg_type);
+    }
+
+    /// Delivers a message to its handlers
+    ///
+    /// # Returns
+    /// Number of handlers invoked
+    pub fn dispatch(&mut self, header: &MessageHeader, payload: &[u8]) -> usize {
+        let handlers = match self.handlers.get_mut(&header.msg_type) {
+            Some(handlers) => handlers,
+            None => return 0,
+        };
+
+        for handler in handlers.iter_mut() {
+            match handler {
+                Handler::Rust(f) => f(header, payload),
+                #[cfg(feature = "wasm")]
+                Handler::Js(f) => {
+                    let js_header = js_sys::Uint32Array::from(
+                        &[
+                            header.msg_type,
+                            header.payload_offset,
+                            header.payload_len,
+                            header.sequence,
+                        ][..],
+                    );
+                    let js_payload = js_sys::Uint8Array::from(payload);
+                    // Callback errors stay on the JS side; dispatch continues
+                    let _ = f.call2(&JsValue::NULL, &js_header, &js_payload);
+                }
+            }
+        }
+
+        handlers.len()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BridgeContext {
+    /// Opens a named channel backed by a ring of `capacity` bytes
+    ///
+    /// Reopening an existing channel returns its current ring.
+    ///
+    /// # Returns
+    /// Offset of the channel's ring region, or -1 on failure
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = openChannel))]
+    pub fn open_channel(&mut self, name: &str, capacity: u32) -> i32 {
+        if let Some(channel) = self.channels.get(name) {
+            return channel.ring_offset as i32;
+        }
+
+        let ring_offset = self.create_ring_buffer(capacity);
+        if ring_offset >= 0 {
+            self.set_allocation_tag(ring_offset as usize, &format!("channel:{name}"));
+            self.channels.insert(
+                name.to_string(),
+                Channel {
+                    name: name.to_string(),
+                    ring_offset: ring_offset as usize,
+                    next_sequence: 0,
+                    watermarks: None,
+                    blocked: false,
+                    integrity: None,
+                },
+            );
+        }
+        ring_offset
+    }
+
+    /// Closes a channel and frees its ring region
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = closeChannel))]
+    pub fn close_channel(&mut self, name: &str) -> bool {
+        match self.channels.remove(name) {
+            Some(channel) => self.free(channel.ring_offset),
+            None => false,
+        }
+    }
+
+    /// Ring offset of a channel, or -1 if it is not open
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = channelOffset))]
+    pub fn channel_offset(&self, name: &str) -> i32 {
+        self.channels
+            .get(name)
+            .map_or(-1, |channel| channel.ring_offset as i32)
+    }
+
+    /// Registers a message type name; false on a conflicting registration
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = registerMessageType))]
+    pub fn register_message_type(&mut self, msg_type: u32, name: &str) -> bool {
+        self.dispatcher.register_type(msg_type, name)
+    }
+
+    /// Registers a JS callback for a message type
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(js_name = onMessage)]
+    pub fn on_message_js(&mut self, msg_type: u32, callback: js_sys::Function) {
+        self.dispatcher.on_message_js(msg_type, callback);
+    }
+
+    /// Sends a message of a registered type on a channel
+    ///
+    /// # Returns
+    /// The message sequence number, `SEND_WOULD_BLOCK` (-2) when the channel
+    /// is above its watermark, or `SEND_ERROR` (-1) if the channel is not
+    /// open, the type is unregistered, or the ring is full
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn send(&mut self, channel: &str, msg_type: u32, payload: &[u8]) -> i64 {
+        match self.try_send(channel, msg_type, payload) {
+            Ok(sequence) => sequence as i64,
+            Err(error) => error.code(),
+        }
+    }
+
+    /// Drains a channel, delivering every message to its handlers
+    ///
+    /// # Returns
+    /// Number of messages drained
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn dispatch(&mut self, channel: &str) -> u32 {
+        let mut count = 0;
+        while let Some((header, payload)) = self.receive(channel) {
+            self.dispatcher.dispatch(&header, &payload);
+            count += 1;
+        }
+        count
+    }
+}
+
+impl BridgeContext {
+    /// Sends a message of a registered type on a channel, honoring watermarks
+    pub fn try_send(&mut self, channel: &str, msg_type: u32, payload: &[u8]) -> Result<u32, SendError> {
+        if !self.dispatcher.is_registered(msg_type) {
+            return Err(SendError::UnregisteredType);
+        }
+        let (ring_offset, sequence, watermarks) = match self.channels.get(channel) {
+            Some(channel) => (channel.ring_offset, channel.next_sequence, channel.watermarks),
+            None => return Err(SendError::ChannelNotOpen),
+        };
+
+        if let Some(watermarks) = watermarks {
+            let used = self.ring_used_bytes(ring_offset);
+            let frame = (frame_bytes(payload.len()) + self.frame_overhead(channel)) as u32;
+            let mut blocked = self.channels.get(channel).is_some_and(|c| c.blocked);
+            let admitted = watermarks.admit(&mut blocked, used, frame);
+            if let Some(channel) = self.channels.get_mut(channel) {
+                channel.blocked = blocked;
+            }
+            if !admitted {
+                return Err(SendError::WouldBlock);
+            }
+        }
+
+        let header = MessageHeader {
+            msg_type,
+            payload_offset: MessageHeader::SIZE as u32,
+            payload_len: payload.len() as u32,
+            sequence,
+        };
+        let mut frame = Vec::with_capacity(MessageHeader::SIZE + payload.len());
+        frame.extend_from_slice(&header.to_bytes());
+        frame.extend_from_slice(payload);
+        self.seal_frame(channel, &mut frame);
+
+        if !self.push_message(ring_offset, &frame) {
+            return Err(SendError::Full);
+        }
+        if let Some(channel) = self.channels.get_mut(channel) {
+            channel.next_sequence = sequence.wrapping_add(1);
+        }
+        Ok(sequence)
+    }
+
+    /// Registers a Rust handler for a message type
+    pub fn on_message(&mut self, msg_type: u32, handler: impl FnMut(&MessageHeader, &[u8]) + 'static) {
+        self.dispatcher.on_message(msg_type, Box::new(handler));
+    }
+
+    /// Pops the next message from a channel without dispatching it
+    ///
+    /// Frames rejected by the channel's integrity policy are skipped.
+    pub fn receive(&mut self, channel: &str) -> Option<(MessageHeader, Vec<u8>)> {
+        let ring_offset = self.channels.get(channel)?.ring_offset;
+        let frame = loop {
+            let mut frame = self.pop_message(ring_offset)?;
+            if self.verify_frame(channel, &mut frame) {
+                break frame;
+            }
+        };
+        if frame.len() < MessageHeader::SIZE {
+            return None;
+        }
+
+        let header = MessageHeader::from_bytes(&frame);
+        let start = header.payload_offset as usize;
+        let end = start.checked_add(header.payload_len as usize)?;
+        let payload = frame.get(start..end)?.to_vec();
+        Some((header, payload))
+    }
+
+    /// Message-type registry and handlers
+    pub fn dispatcher(&self) -> &MessageDispatcher {
+        &self.dispatcher
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const PARAM_CHANGE: u32 = 1;
+    const TRANSPORT: u32 = 2;
+
+    #[test]
+    fn test_type_registration() {
+        let mut ctx = BridgeContext::new(256);
+        assert!(ctx.register_message_type(PARAM_CHANGE, "param-change"));
+        assert!(ctx.register_message_type(PARAM_CHANGE, "param-change"));
+        assert!(!ctx.register_message_type(PARAM_CHANGE, "transport"));
+        assert_eq!(ctx.dispatcher().type_name(PARAM_CHANGE), Some("param-change"));
+    }
+
+    #[test]
+    fn test_send_requires_channel_and_type() {
+        let mut ctx = BridgeContext::new(256);
+        assert_eq!(ctx.send("audio", PARAM_CHANGE, b"x"), -1);
+
+        ctx.open_channel("audio", 64);
+        assert_eq!(ctx.send("audio", PARAM_CHANGE, b"x"), -1);
+
+        ctx.register_message_type(PARAM_CHANGE, "param-change");
+        assert_eq!(ctx.send("audio", PARAM_CHANGE, b"x"), 0);
+        assert_eq!(ctx.send("audio", PARAM_CHANGE, b"y"), 1);
+    }
+
+    #[test]
+    fn test_dispatch_by_type() {
+        let mut ctx = BridgeContext::new(512);
+        ctx.open_channel("audio", 128);
+        ctx.register_message_type(PARAM_CHANGE, "param-change");
+        ctx.register_message_type(TRANSPORT, "transport");
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = seen.clone();
+        ctx.on_message(PARAM_CHANGE, move |header, payload| {
+            sink.borrow_mut().push((header.sequence, payload.to_vec()));
+        });
+
+        ctx.send("audio", PARAM_CHANGE, b"gain");
+        ctx.send("audio", TRANSPORT, b"play");
+        ctx.send("audio", PARAM_CHANGE, b"freq");
+
+        assert_eq!(ctx.dispatch("audio"), 3);
+        assert_eq!(
+            *seen.borrow(),
+            vec![(0, b"gain".to_vec()), (2, b"freq".to_vec())]
+        );
+        assert_eq!(ctx.dispatch("audio"), 0);
+    }
+
+    #[test]
+    fn test_channels_are_independent() {
+        let mut ctx = BridgeContext::new(512);
+        let a = ctx.open_channel("ui", 64);
+        let b = ctx.open_channel("audio", 64);
+        assert_ne!(a, b);
+        assert_eq!(ctx.open_channel("ui", 64), a);
+
+        ctx.register_message_type(TRANSPORT, "transport");
+        ctx.send("ui", TRANSPORT, b"stop");
+
+        assert!(ctx.receive("audio").is_none());
+        let (header, payload) = ctx.receive("ui").unwrap();
+        assert_eq!(header.msg_type, TRANSPORT);
+        assert_eq!(payload, b"stop");
+
+        assert!(ctx.close_channel("ui"));
+        assert_eq!(ctx.channel_offset("ui"), -1);
+        assert!(!ctx.close_channel("ui"));
+    }
+
+    #[test]
+    fn test_absorb_keeps_both_registrations() {
+        let mut original = MessageDispatcher::new();
+        original.register_type(PARAM_CHANGE, "param-change");
+        let hits = Rc::new(RefCell::new(0));
+        let h = hits.clone();
+        original.on_message(PARAM_CHANGE, Box::new(move |_, _| *h.borrow_mut() += 1));
+
+        let mut added = MessageDispatcher::new();
+        added.register_type(TRANSPORT, "transport");
+        let h = hits.clone();
+        added.on_message(PARAM_CHANGE, Box::new(move |_, _| *h.borrow_mut() += 10));
+
+        added.absorb(original);
+        assert!(added.is_registered(PARAM_CHANGE));
+        assert!(added.is_registered(TRANSPORT));
+
+        let header = MessageHeader {
+            msg_type: PARAM_CHANGE,
+            payload_offset: 16,
+            payload_len: 0,
+            sequence: 0,
+        };
+        assert_eq!(added.dispatch(&header, &[]), 2);
+        assert_eq!(*hits.borrow(), 11);
+    }
+
+    #[test]
+    fn test_dispatch_channel_keeps_handler_order() {
+        crate::init_shared_buffer(512);
+        crate::open_channel("ui", 128);
+        crate::register_message_type(PARAM_CHANGE, "param-change");
+
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let (first, second) = (order.clone(), order.clone());
+        let mut late = Some(order.clone());
+        crate::with_default_context(|ctx| {
+            ctx.on_message(PARAM_CHANGE, move |_, _| {
+                first.borrow_mut().push("first");
+                // Registered while the dispatcher is taken out of the context
+                if let Some(late) = late.take() {
+                    crate::with_default_context(|ctx| {
+                        ctx.on_message(PARAM_CHANGE, move |_, _| late.borrow_mut().push("late"));
+                    });
+                }
+            });
+            ctx.on_message(PARAM_CHANGE, move |_, _| second.borrow_mut().push("second"));
+        });
+
+        for _ in 0..2 {
+            crate::send_on_channel("ui", PARAM_CHANGE, b"gain");
+            assert_eq!(crate::dispatch_channel("ui"), 1);
+        }
+        assert_eq!(*order.borrow(), vec!["first", "second", "first", "second", "late"]);
+    }
+}
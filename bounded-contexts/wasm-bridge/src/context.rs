@@ -0,0 +1,303 @@
+//! BridgeContext: Owned shared buffer state for one bridge instance
+//!
+//! Each context owns its buffer and allocator, so independent subsystems
+//! (e.g. audio worklet and graph engine) can hold separate contexts without
+//! trampling each other's memory.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-bridge
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+use std::collections::HashMap;
+use std::slice;
+use std::sync::atomic::AtomicU32;
+
+use crate::allocator::{AllocatorStats, FreeListAllocator, UNTAGGED};
+use crate::channel::{Channel, MessageDispatcher};
+use crate::integrity::ViolationCallback;
+use crate::MessageHeader;
+
+/// Independent shared buffer with its own allocator
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct BridgeContext {
+    /// Backing words; u64 storage keeps every allocator offset 8-aligned in memory
+    storage: Vec<u64>,
+    len: usize,
+    allocator: FreeListAllocator,
+    /// Named channels by name
+    pub(crate) channels: HashMap<String, Channel>,
+    pub(crate) dispatcher: MessageDispatcher,
+    /// Record call sites for new allocations
+    pub(crate) debug_allocations: bool,
+    pub(crate) violation_callback: Option<ViolationCallback>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BridgeContext {
+    /// Creates a context with a zeroed buffer of `capacity` bytes
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            storage: vec![0u64; capacity.div_ceil(8)],
+            len: capacity,
+            allocator: FreeListAllocator::new(capacity),
+            channels: HashMap::new(),
+            dispatcher: MessageDispatcher::new(),
+            debug_allocations: false,
+            violation_callback: None,
+        }
+    }
+
+    /// Pointer to the start of the buffer (for JS views into WASM memory)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = bufferPtr))]
+    pub fn buffer_ptr(&mut self) -> *mut u8 {
+        self.storage.as_mut_ptr() as *mut u8
+    }
+
+    /// Buffer length in bytes
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = bufferLen))]
+    pub fn buffer_len(&self) -> usize {
+        self.len
+    }
+
+    /// Copies `data` into the buffer at `offset`
+    ///
+    /// # Returns
+    /// false if the write would run past the end of the buffer
+    pub fn write(&mut self, offset: usize, data: &[u8]) -> bool {
+        match self.range_mut(offset, data.len()) {
+            Some(dst) => {
+                dst.copy_from_slice(data);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pointer to `len` bytes at `offset`, or null if out of bounds
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = readPtr))]
+    pub fn read_ptr(&self, offset: usize, len: usize) -> *const u8 {
+        match self.range(offset, len) {
+            Some(src) => src.as_ptr(),
+            None => std::ptr::null(),
+        }
+    }
+
+    /// Writes a message header at `offset`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeMessageHeader))]
+    pub fn write_message_header(
+        &mut self,
+        offset: usize,
+        msg_type: u32,
+        payload_offset: u32,
+        payload_len: u32,
+        sequence: u32,
+    ) -> bool {
+        let header = MessageHeader {
+            msg_type,
+            payload_offset,
+            payload_len,
+            sequence,
+        };
+        self.write(offset, &header.to_bytes())
+    }
+
+    /// Reserves `size` bytes and returns their offset, or -1 when full
+    pub fn allocate(&mut self, size: usize) -> i32 {
+        self.allocate_tagged(size, UNTAGGED, None)
+    }
+
+    /// Returns an allocation to the free list
+    pub fn free(&mut self, offset: usize) -> bool {
+        self.allocator.free(offset)
+    }
+
+    /// Opens an allocation scope; returns the new scope depth
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = beginScope))]
+    pub fn begin_scope(&mut self) -> usize {
+        self.allocator.begin_scope()
+    }
+
+    /// Frees everything allocated since the matching `begin_scope`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = endScope))]
+    pub fn end_scope(&mut self) -> bool {
+        self.allocator.end_scope()
+    }
+
+    /// Releases every allocation (channel regions included)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = resetAllocator))]
+    pub fn reset_allocator(&mut self) {
+        self.allocator.reset();
+        self.channels.clear();
+    }
+
+    /// Returns [total, used, free, high-water mark, live allocations]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = memoryStats))]
+    pub fn memory_stats(&self) -> Vec<u32> {
+        let stats = self.allocator.stats();
+        vec![
+            stats.total_bytes as u32,
+            stats.used_bytes as u32,
+            stats.free_bytes as u32,
+            stats.high_water_mark as u32,
+            stats.allocation_count as u32,
+        ]
+    }
+
+    /// Returns [largest free block, free block count, live allocation count]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = fragmentationStats))]
+    pub fn fragmentation_stats(&self) -> Vec<u32> {
+        let stats = self.allocator.stats();
+        vec![
+            stats.largest_free_block as u32,
+            stats.free_block_count as u32,
+            stats.allocation_count as u32,
+        ]
+    }
+
+    /// Fragmentation ratio of free space (0.0 = contiguous)
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn fragmentation(&self) -> f32 {
+        self.allocator.stats().fragmentation
+    }
+}
+
+impl BridgeContext {
+    /// Full allocator statistics
+    pub fn allocator_stats(&self) -> AllocatorStats {
+        self.allocator.stats()
+    }
+
+    /// The context's allocator (for diagnostics)
+    pub fn allocator(&self) -> &FreeListAllocator {
+        &self.allocator
+    }
+
+    pub(crate) fn allocator_mut(&mut self) -> &mut FreeListAllocator {
+        &mut self.allocator
+    }
+
+    /// Reads the message header stored at `offset`
+    pub fn read_message_header(&self, offset: usize) -> Option<MessageHeader> {
+        self.range(offset, MessageHeader::SIZE)
+            .map(MessageHeader::from_bytes)
+    }
+
+    /// Borrows `len` bytes at `offset`
+    pub fn range(&self, offset: usize, len: usize) -> Option<&[u8]> {
+        let end = offset.checked_add(len)?;
+        self.bytes().get(offset..end)
+    }
+
+    /// Mutably borrows `len` bytes at `offset`
+    pub fn range_mut(&mut self, offset: usize, len: usize) -> Option<&mut [u8]> {
+        let end = offset.checked_add(len)?;
+        self.bytes_mut().get_mut(offset..end)
+    }
+
+    /// Views the 4-aligned word at `offset` as an atomic
+    ///
+    /// JS shares these words through `Atomics` on an `Int32Array` over the
+    /// same memory, so index updates are visible across threads.
+    pub fn atomic_u32(&mut self, offset: usize) -> Option<&AtomicU32> {
+        if !offset.is_multiple_of(4) || offset.checked_add(4)? > self.len {
+            return None;
+        }
+        // Safety: storage is 8-aligned so a 4-aligned offset is a valid,
+        // in-bounds u32 location; AtomicU32 has the same layout as u32 and
+        // the pointer comes from an exclusive borrow of the storage.
+        unsafe {
+            let ptr = (self.storage.as_mut_ptr() as *mut u8).add(offset) as *mut u32;
+            Some(AtomicU32::from_ptr(ptr))
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // Safety: storage holds at least `len` initialized bytes
+        unsafe { slice::from_raw_parts(self.storage.as_ptr() as *const u8, self.len) }
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        // Safety: storage holds at least `len` initialized bytes
+        unsafe { slice::from_raw_parts_mut(self.storage.as_mut_ptr() as *mut u8, self.len) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contexts_are_independent() {
+        let mut a = BridgeContext::new(64);
+        let mut b = BridgeContext::new(64);
+
+        assert!(a.write(0, &[1, 2, 3]));
+        assert!(b.write(0, &[9, 9, 9]));
+
+        assert_eq!(a.range(0, 3), Some(&[1u8, 2, 3][..]));
+        assert_eq!(b.range(0, 3), Some(&[9u8, 9, 9][..]));
+        assert_ne!(a.buffer_ptr(), b.buffer_ptr());
+    }
+
+    #[test]
+    fn test_write_bounds() {
+        let mut ctx = BridgeContext::new(8);
+        assert!(ctx.write(4, &[0; 4]));
+        assert!(!ctx.write(5, &[0; 4]));
+        assert!(!ctx.write(usize::MAX, &[0; 2]));
+        assert!(ctx.read_ptr(8, 1).is_null());
+    }
+
+    #[test]
+    fn test_atomic_word_access() {
+        use std::sync::atomic::Ordering;
+
+        let mut ctx = BridgeContext::new(16);
+        ctx.atomic_u32(4).unwrap().store(0xDEAD_BEEF, Ordering::Release);
+        assert_eq!(ctx.range(4, 4), Some(&0xDEAD_BEEFu32.to_le_bytes()[..]));
+
+        assert!(ctx.atomic_u32(2).is_none());
+        assert!(ctx.atomic_u32(16).is_none());
+    }
+
+    #[test]
+    fn test_message_header_roundtrip() {
+        let mut ctx = BridgeContext::new(64);
+        assert!(ctx.write_message_header(16, 3, 32, 12, 7));
+
+        let header = ctx.read_message_header(16).unwrap();
+        assert_eq!(header.msg_type, 3);
+        assert_eq!(header.payload_offset, 32);
+        assert_eq!(header.payload_len, 12);
+        assert_eq!(header.sequence, 7);
+        assert!(ctx.read_message_header(60).is_none());
+    }
+
+    #[test]
+    fn test_allocate_free_and_reset() {
+        let mut ctx = BridgeContext::new(16);
+        assert_eq!(ctx.allocate(10), 0);
+        assert_eq!(ctx.allocate(10), -1);
+        assert_eq!(ctx.memory_stats(), vec![16, 16, 0, 16, 1]);
+
+        assert!(ctx.free(0));
+        assert_eq!(ctx.allocate(6), 0);
+
+        ctx.reset_allocator();
+        assert_eq!(ctx.memory_stats(), vec![16, 0, 16, 0, 0]);
+    }
+
+    #[test]
+    fn test_scoped_allocations() {
+        let mut ctx = BridgeContext::new(32);
+        ctx.begin_scope();
+        ctx.allocate(8);
+        ctx.allocate(8);
+        assert_eq!(ctx.memory_stats()[1], 16);
+
+        assert!(ctx.end_scope());
+        assert_eq!(ctx.memory_stats()[1], 0);
+        assert_eq!(ctx.fragmentation_stats(), vec![32, 1, 0]);
+    }
+}
@@ -0,0 +1,239 @@
+//! Bridge memory diagnostics and leak detection
+//!
+//! Every allocation carries a tag naming the subsystem that owns it (ring
+//! buffers, channels, double buffers, traversal results, or a caller-chosen
+//! tag). `memoryReport` aggregates live usage per tag alongside the
+//! high-water mark. In debug mode each allocation also records its call
+//! site: the Rust caller location, or a site string supplied from JS (e.g.
+//! a stack line). Leak hunting works by taking a checkpoint, running a
+//! workload, and listing allocations made since the checkpoint that are
+//! still live.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-bridge
+
+use std::collections::BTreeMap;
+use std::panic::Location;
+
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::allocator::AllocationRecord;
+use crate::BridgeContext;
+
+/// Live usage attributed to one tag
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagUsage {
+    pub tag: String,
+    pub count: usize,
+    pub bytes: usize,
+}
+
+/// Live allocation as reported to JS
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AllocationReport {
+    pub offset: usize,
+    pub size: usize,
+    pub tag: String,
+    pub call_site: Option<String>,
+    pub generation: u32,
+}
+
+impl From<&AllocationRecord> for AllocationReport {
+    fn from(record: &AllocationRecord) -> Self {
+        Self {
+            offset: record.offset,
+            size: record.size,
+            tag: record.tag.clone(),
+            call_site: record.call_site.clone(),
+            generation: record.generation,
+        }
+    }
+}
+
+/// Structured memory report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MemoryReport {
+    pub total_bytes: usize,
+    pub used_bytes: usize,
+    pub free_bytes: usize,
+    pub high_water_mark: usize,
+    pub live_allocations: usize,
+    pub largest_free_block: usize,
+    pub fragmentation: f32,
+    /// Usage per tag, largest first
+    pub by_tag: Vec<TagUsage>,
+    /// Every live allocation; only populated in debug mode
+    pub allocations: Vec<AllocationReport>,
+}
+
+impl BridgeContext {
+    /// Reserves `size` bytes on behalf of `tag`
+    ///
+    /// In debug mode the caller's source location is recorded.
+    #[track_caller]
+    pub fn allocate_as(&mut self, size: usize, tag: &str) -> i32 {
+        let call_site = self
+            .debug_allocations
+            .then(|| Location::caller().to_string());
+        self.allocate_tagged(size, tag, call_site)
+    }
+
+    /// Builds the structured memory report
+    pub fn memory_report(&self) -> MemoryReport {
+        let stats = self.allocator_stats();
+
+        let mut by_tag: BTreeMap<&str, TagUsage> = BTreeMap::new();
+        for record in self.allocator().allocations() {
+            let usage = by_tag.entry(&record.tag).or_insert_with(|| TagUsage {
+                tag: record.tag.clone(),
+                count: 0,
+                bytes: 0,
+            });
+            usage.count += 1;
+            usage.bytes += record.size;
+        }
+        let mut by_tag: Vec<TagUsage> = by_tag.into_values().collect();
+        by_tag.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.tag.cmp(&b.tag)));
+
+        let allocations = if self.debug_allocations {
+            self.allocator().allocations().map(AllocationReport::from).collect()
+        } else {
+            Vec::new()
+        };
+
+        MemoryReport {
+            total_bytes: stats.total_bytes,
+            used_bytes: stats.used_bytes,
+            free_bytes: stats.free_bytes,
+            high_water_mark: stats.high_water_mark,
+            live_allocations: stats.allocation_count,
+            largest_free_block: stats.largest_free_block,
+            fragmentation: stats.fragmentation,
+            by_tag,
+            allocations,
+        }
+    }
+
+    /// Live allocations made at or after `checkpoint`, oldest first
+    pub fn leaks_since(&self, checkpoint: u32) -> Vec<AllocationReport> {
+        let mut leaks: Vec<AllocationReport> = self
+            .allocator()
+            .allocations()
+            .filter(|record| record.generation >= checkpoint)
+            .map(AllocationReport::from)
+            .collect();
+        leaks.sort_by_key(|leak| leak.generation);
+        leaks
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BridgeContext {
+    /// Reserves `size` bytes on behalf of `tag`
+    ///
+    /// # Arguments
+    /// * `size` - Bytes to reserve
+    /// * `tag` - Owning subsystem, shown in `memoryReport`
+    /// * `call_site` - Caller description kept in debug mode (e.g. a JS stack line)
+    ///
+    /// # Returns
+    /// Offset of the region, or -1 when full
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = allocateTagged))]
+    pub fn allocate_tagged(&mut self, size: usize, tag: &str, call_site: Option<String>) -> i32 {
+        let call_site = call_site.filter(|_| self.debug_allocations);
+        self.allocator_mut()
+            .allocate_tagged(size, tag, call_site)
+            .map_or(-1, |offset| offset as i32)
+    }
+
+    /// Re-tags a live allocation
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setAllocationTag))]
+    pub fn set_allocation_tag(&mut self, offset: usize, tag: &str) -> bool {
+        self.allocator_mut().set_tag(offset, tag)
+    }
+
+    /// Enables or disables call-site recording for new allocations
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setAllocationDebug))]
+    pub fn set_allocation_debug(&mut self, enabled: bool) {
+        self.debug_allocations = enabled;
+    }
+
+    /// Structured memory report as JSON
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = memoryReport))]
+    pub fn memory_report_json(&self) -> String {
+        serde_json::to_string(&self.memory_report()).unwrap_or_default()
+    }
+
+    /// Marks the current point in the allocation sequence
+    ///
+    /// # Returns
+    /// Checkpoint to pass to `leaksSince`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = leakCheckpoint))]
+    pub fn leak_checkpoint(&self) -> u32 {
+        self.allocator().next_generation()
+    }
+
+    /// Allocations made since `checkpoint` that are still live, as JSON
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = leaksSince))]
+    pub fn leaks_since_json(&self, checkpoint: u32) -> String {
+        serde_json::to_string(&self.leaks_since(checkpoint)).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_groups_by_tag() {
+        let mut ctx = BridgeContext::new(4096);
+        ctx.open_channel("params", 256);
+        ctx.create_double_buffer(64);
+        ctx.allocate_tagged(100, "graph-snapshot", None);
+        ctx.allocate(8);
+
+        let report = ctx.memory_report();
+        assert_eq!(report.live_allocations, 4);
+        assert_eq!(report.high_water_mark, report.used_bytes);
+        let tags: Vec<&str> = report.by_tag.iter().map(|usage| usage.tag.as_str()).collect();
+        assert_eq!(tags, ["channel:params", "double-buffer", "graph-snapshot", "untagged"]);
+        assert_eq!(report.by_tag[2].bytes, 104);
+        assert!(report.allocations.is_empty());
+    }
+
+    #[test]
+    fn test_high_water_mark_survives_free() {
+        let mut ctx = BridgeContext::new(1024);
+        let a = ctx.allocate(512) as usize;
+        ctx.free(a);
+        ctx.allocate(64);
+
+        let report = ctx.memory_report();
+        assert_eq!(report.used_bytes, 64);
+        assert_eq!(report.high_water_mark, 512);
+    }
+
+    #[test]
+    fn test_debug_mode_records_call_sites_and_leaks() {
+        let mut ctx = BridgeContext::new(1024);
+        ctx.allocate(8);
+        ctx.set_allocation_debug(true);
+
+        let checkpoint = ctx.leak_checkpoint();
+        let freed = ctx.allocate_as(16, "scratch") as usize;
+        let leaked = ctx.allocate_as(32, "scratch") as usize;
+        ctx.allocate_tagged(8, "js", Some("Editor.save (editor.js:42)".to_string()));
+        ctx.free(freed);
+
+        let leaks = ctx.leaks_since(checkpoint);
+        assert_eq!(leaks.len(), 2);
+        assert_eq!(leaks[0].offset, leaked);
+        assert!(leaks[0].call_site.as_deref().unwrap().contains("diagnostics.rs"));
+        assert_eq!(leaks[1].call_site.as_deref(), Some("Editor.save (editor.js:42)"));
+
+        let report = ctx.memory_report();
+        assert_eq!(report.allocations.len(), 3);
+        assert!(ctx.memory_report_json().contains("\"high_water_mark\""));
+    }
+}
@@ -0,0 +1,299 @@
+//! Double-buffered frame exchange
+//!
+//! Two frame slots plus an atomic flip index. The producer writes the next
+//! frame into the slot readers are not looking at, then flips the index, so
+//! consumers always read the latest complete frame without tearing. Used for
+//! audio blocks and graph snapshots where only the newest frame matters.
+//!
+//! Region Layout:
+//! - Bytes 0-3:   latest slot (u32) - 0 or 1
+//! - Bytes 4-7:   sequence (u32) - twice the frames published, odd while a
+//!   frame is being written; 0 = none yet
+//! - Bytes 8-11:  frame capacity (u32) - bytes per slot
+//! - Bytes 12-15: slot 0 length (u32)
+//! - Bytes 16-19: slot 1 length (u32)
+//! - Bytes 20-23: reserved
+//! - Bytes 24..:  slot 0 data, then slot 1 data
+//!
+//! The sequence word is a seqlock: the producer makes it odd before copying
+//! into a slot and even again once the frame is published. A reader copies
+//! the latest slot and keeps the copy only if the sequence was even and
+//! unchanged throughout; otherwise the producer may have been writing into
+//! the slot being read, and the reader retries.
+
+use std::sync::atomic::{fence, Ordering};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::BridgeContext;
+
+/// Size of the double-buffer header in bytes
+pub const DOUBLE_BUFFER_HEADER_SIZE: usize = 24;
+
+/// Read attempts before giving up on a producer that keeps overtaking
+const MAX_READ_ATTEMPTS: usize = 4;
+
+const LATEST_OFFSET: usize = 0;
+const SEQUENCE_OFFSET: usize = 4;
+const CAPACITY_OFFSET: usize = 8;
+const LENGTH_OFFSETS: [usize; 2] = [12, 16];
+
+/// View over a double-buffer region inside a bridge context
+pub struct DoubleBuffer<'a> {
+    ctx: &'a mut BridgeContext,
+    offset: usize,
+    frame_capacity: u32,
+}
+
+impl<'a> DoubleBuffer<'a> {
+    /// Attaches to an initialized double buffer at `offset`
+    pub fn attach(ctx: &'a mut BridgeContext, offset: usize) -> Option<Self> {
+        let frame_capacity = ctx.atomic_u32(offset + CAPACITY_OFFSET)?.load(Ordering::Acquire);
+        if frame_capacity == 0 {
+            return None;
+        }
+        ctx.range(offset, DOUBLE_BUFFER_HEADER_SIZE + 2 * frame_capacity as usize)?;
+
+        Some(Self {
+            ctx,
+            offset,
+            frame_capacity,
+        })
+    }
+
+    /// Bytes available per frame
+    #[inline]
+    pub fn frame_capacity(&self) -> u32 {
+        self.frame_capacity
+    }
+
+    /// Number of frames published so far
+    pub fn sequence(&mut self) -> u32 {
+        self.load(SEQUENCE_OFFSET) / 2
+    }
+
+    /// Publishes a frame (producer side)
+    ///
+    /// # Returns
+    /// false if the frame exceeds the slot capacity
+    pub fn write_frame(&mut self, frame: &[u8]) -> bool {
+        if frame.len() > self.frame_capacity as usize {
+            return false;
+        }
+
+        let (sequence, target) = self.begin_write();
+        let start = self.slot_start(target);
+        if let Some(dst) = self.ctx.range_mut(start, frame.len()) {
+            dst.copy_from_slice(frame);
+        }
+        self.end_write(sequence, target, frame.len() as u32);
+        true
+    }
+
+    /// Marks a write in progress and picks the slot readers are not meant to use
+    ///
+    /// # Returns
+    /// The (even) sequence before the write and the target slot
+    fn begin_write(&mut self) -> (u32, usize) {
+        let sequence = self.load(SEQUENCE_OFFSET) & !1;
+        let target = if sequence == 0 {
+            0
+        } else {
+            1 - (self.load(LATEST_OFFSET) as usize & 1)
+        };
+        self.store(SEQUENCE_OFFSET, sequence.wrapping_add(1));
+        // Keep the slot copy from being reordered before the odd sequence
+        fence(Ordering::Release);
+        (sequence, target)
+    }
+
+    /// Publishes the frame copied into `target` and ends the write
+    fn end_write(&mut self, sequence: u32, target: usize, len: u32) {
+        self.store(LENGTH_OFFSETS[target], len);
+        self.store(LATEST_OFFSET, target as u32);
+        self.store(SEQUENCE_OFFSET, sequence.wrapping_add(2));
+    }
+
+    /// Copies the most recently published frame (consumer side)
+    ///
+    /// Returns None if nothing has been published yet, or if the producer
+    /// overtook every read attempt.
+    pub fn read_latest_frame(&mut self) -> Option<Vec<u8>> {
+        for _ in 0..MAX_READ_ATTEMPTS {
+            let before = self.load(SEQUENCE_OFFSET);
+            if before == 0 {
+                return None;
+            }
+            if before & 1 == 1 {
+                continue;
+            }
+
+            let slot = self.load(LATEST_OFFSET) as usize & 1;
+            let len = (self.load(LENGTH_OFFSETS[slot]) as usize).min(self.frame_capacity as usize);
+            let start = self.slot_start(slot);
+            let frame = self.ctx.range(start, len)?.to_vec();
+
+            if self.unchanged_since(before) {
+                return Some(frame);
+            }
+        }
+        None
+    }
+
+    /// Returns true if no write started since the sequence read as `before`
+    fn unchanged_since(&mut self, before: u32) -> bool {
+        // Keep the slot copy from being reordered after the sequence check
+        fence(Ordering::Acquire);
+        self.load(SEQUENCE_OFFSET) == before
+    }
+
+    fn slot_start(&self, slot: usize) -> usize {
+        self.offset + DOUBLE_BUFFER_HEADER_SIZE + slot * self.frame_capacity as usize
+    }
+
+    fn load(&mut self, field: usize) -> u32 {
+        // Header bounds were validated in `attach`
+        self.ctx
+            .atomic_u32(self.offset + field)
+            .map_or(0, |word| word.load(Ordering::Acquire))
+    }
+
+    fn store(&mut self, field: usize, value: u32) {
+        if let Some(word) = self.ctx.atomic_u32(self.offset + field) {
+            word.store(value, Ordering::Release);
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BridgeContext {
+    /// Allocates a double buffer with two slots of `frame_capacity` bytes
+    ///
+    /// # Returns
+    /// Offset of the region, or -1 on failure
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = createDoubleBuffer))]
+    pub fn create_double_buffer(&mut self, frame_capacity: u32) -> i32 {
+        if frame_capacity == 0 {
+            return -1;
+        }
+
+        let offset = self.allocate_as(DOUBLE_BUFFER_HEADER_SIZE + 2 * frame_capacity as usize, "double-buffer");
+        if offset < 0 {
+            return -1;
+        }
+
+        let base = offset as usize;
+        for field in [LATEST_OFFSET, SEQUENCE_OFFSET, LENGTH_OFFSETS[0], LENGTH_OFFSETS[1]] {
+            if let Some(word) = self.atomic_u32(base + field) {
+                word.store(0, Ordering::Release);
+            }
+        }
+        if let Some(word) = self.atomic_u32(base + CAPACITY_OFFSET) {
+            word.store(frame_capacity, Ordering::Release);
+        }
+        offset
+    }
+
+    /// Publishes a frame to the double buffer at `offset`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeFrame))]
+    pub fn write_frame(&mut self, offset: usize, frame: &[u8]) -> bool {
+        self.double_buffer(offset)
+            .is_some_and(|mut buffer| buffer.write_frame(frame))
+    }
+
+    /// Copies the latest frame from the double buffer at `offset`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = readLatestFrame))]
+    pub fn read_latest_frame(&mut self, offset: usize) -> Option<Vec<u8>> {
+        self.double_buffer(offset)?.read_latest_frame()
+    }
+
+    /// Frames published to the double buffer at `offset`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = frameSequence))]
+    pub fn frame_sequence(&mut self, offset: usize) -> u32 {
+        self.double_buffer(offset)
+            .map_or(0, |mut buffer| buffer.sequence())
+    }
+}
+
+impl BridgeContext {
+    /// Attaches a view to the double buffer at `offset`
+    pub fn double_buffer(&mut self, offset: usize) -> Option<DoubleBuffer<'_>> {
+        DoubleBuffer::attach(self, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_frame_before_first_write() {
+        let mut ctx = BridgeContext::new(128);
+        let buffer = ctx.create_double_buffer(16) as usize;
+        assert_eq!(ctx.read_latest_frame(buffer), None);
+        assert_eq!(ctx.frame_sequence(buffer), 0);
+    }
+
+    #[test]
+    fn test_latest_frame_wins() {
+        let mut ctx = BridgeContext::new(128);
+        let buffer = ctx.create_double_buffer(16) as usize;
+
+        assert!(ctx.write_frame(buffer, &[1, 1, 1]));
+        assert_eq!(ctx.read_latest_frame(buffer), Some(vec![1, 1, 1]));
+
+        assert!(ctx.write_frame(buffer, &[2, 2]));
+        assert!(ctx.write_frame(buffer, &[3; 16]));
+        assert_eq!(ctx.read_latest_frame(buffer), Some(vec![3; 16]));
+        assert_eq!(ctx.frame_sequence(buffer), 3);
+    }
+
+    #[test]
+    fn test_writes_alternate_slots() {
+        let mut ctx = BridgeContext::new(128);
+        let buffer = ctx.create_double_buffer(8) as usize;
+
+        ctx.write_frame(buffer, &[1; 8]);
+        ctx.write_frame(buffer, &[2; 8]);
+
+        // Previous frame stays intact in the other slot
+        let slot0 = buffer + DOUBLE_BUFFER_HEADER_SIZE;
+        assert_eq!(ctx.range(slot0, 8), Some(&[1u8; 8][..]));
+        assert_eq!(ctx.range(slot0 + 8, 8), Some(&[2u8; 8][..]));
+    }
+
+    #[test]
+    fn test_reader_overtaken_mid_copy_retries() {
+        let mut ctx = BridgeContext::new(128);
+        let offset = ctx.create_double_buffer(8) as usize;
+        let mut buffer = ctx.double_buffer(offset).unwrap();
+        buffer.write_frame(&[1; 8]);
+
+        // Reader starts copying slot 0
+        let before = buffer.load(SEQUENCE_OFFSET);
+        let slot = buffer.load(LATEST_OFFSET) as usize;
+
+        // Writer publishes into slot 1, then starts overwriting slot 0
+        buffer.write_frame(&[2; 8]);
+        let (sequence, target) = buffer.begin_write();
+        assert_eq!(target, slot);
+        assert!(!buffer.unchanged_since(before));
+        assert_eq!(buffer.read_latest_frame(), None, "write in progress");
+
+        // Once that write is published the reader gets the new frame whole
+        let start = buffer.slot_start(target);
+        buffer.ctx.range_mut(start, 8).unwrap().copy_from_slice(&[3; 8]);
+        buffer.end_write(sequence, target, 8);
+        assert_eq!(buffer.read_latest_frame(), Some(vec![3; 8]));
+        assert_eq!(buffer.sequence(), 3);
+    }
+
+    #[test]
+    fn test_oversized_frame_rejected() {
+        let mut ctx = BridgeContext::new(128);
+        let buffer = ctx.create_double_buffer(4) as usize;
+        assert!(!ctx.write_frame(buffer, &[0; 5]));
+        assert_eq!(ctx.create_double_buffer(0), -1);
+        assert!(ctx.double_buffer(96).is_none());
+    }
+}
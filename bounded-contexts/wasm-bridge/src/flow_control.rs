@@ -0,0 +1,214 @@
+//! Backpressure and flow control for bridge channels
+//!
+//! Channels can carry low/high watermarks measured in queued ring bytes.
+//! Once a send would push the queue above the high watermark the channel
+//! blocks, and producers get `WouldBlock` until the consumer drains it to the
+//! low watermark. The hysteresis keeps a fast producer (UI parameter
+//! changes) from flooding a slower consumer (audio thread) one message at a
+//! time.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-bridge
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::ring_buffer::MESSAGE_PREFIX_SIZE;
+use crate::{BridgeContext, MessageHeader};
+
+/// `send` result code for channel, type, or ring errors
+pub const SEND_ERROR: i64 = -1;
+
+/// `send` result code when the channel is above its watermark
+pub const SEND_WOULD_BLOCK: i64 = -2;
+
+/// Reasons a channel send did not enqueue a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendError {
+    /// No channel with that name is open
+    ChannelNotOpen,
+    /// The message type was never registered
+    UnregisteredType,
+    /// The consumer is lagging; retry after it drains
+    WouldBlock,
+    /// The ring has no room for the message
+    Full,
+}
+
+impl SendError {
+    /// Numeric code returned to JS by `send`
+    pub fn code(&self) -> i64 {
+        match self {
+            SendError::WouldBlock => SEND_WOULD_BLOCK,
+            _ => SEND_ERROR,
+        }
+    }
+}
+
+/// Queue thresholds in ring bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watermarks {
+    /// Queued bytes at or below which a blocked channel reopens
+    pub low: u32,
+    /// Queued bytes a send may not exceed
+    pub high: u32,
+}
+
+impl Watermarks {
+    /// Decides whether a frame of `frame_bytes` may be queued on top of
+    /// `used_bytes`, updating the channel's `blocked` state
+    pub fn admit(&self, blocked: &mut bool, used_bytes: u32, frame_bytes: u32) -> bool {
+        if *blocked {
+            if used_bytes > self.low {
+                return false;
+            }
+            *blocked = false;
+        }
+
+        if used_bytes.saturating_add(frame_bytes) > self.high {
+            *blocked = true;
+            return false;
+        }
+        true
+    }
+}
+
+/// Ring bytes taken by a channel frame with `payload_len` bytes of payload
+#[inline]
+pub fn frame_bytes(payload_len: usize) -> usize {
+    MESSAGE_PREFIX_SIZE + MessageHeader::SIZE + payload_len
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BridgeContext {
+    /// Sets low/high watermarks (queued bytes) on a channel
+    ///
+    /// # Returns
+    /// false if the channel is not open or `low <= high <= capacity` fails
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setWatermarks))]
+    pub fn set_watermarks(&mut self, channel: &str, low: u32, high: u32) -> bool {
+        let ring_offset = match self.channels.get(channel) {
+            Some(channel) => channel.ring_offset,
+            None => return false,
+        };
+        let capacity = match self.ring_buffer(ring_offset) {
+            Some(ring) => ring.capacity(),
+            None => return false,
+        };
+        if low > high || high > capacity {
+            return false;
+        }
+
+        if let Some(channel) = self.channels.get_mut(channel) {
+            channel.watermarks = Some(Watermarks { low, high });
+            channel.blocked = false;
+        }
+        true
+    }
+
+    /// Removes watermarks from a channel; sends then only fail when full
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = clearWatermarks))]
+    pub fn clear_watermarks(&mut self, channel: &str) -> bool {
+        match self.channels.get_mut(channel) {
+            Some(channel) => {
+                channel.watermarks = None;
+                channel.blocked = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Messages waiting to be received on a channel
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = pendingMessages))]
+    pub fn pending_messages(&mut self, channel: &str) -> u32 {
+        match self.channels.get(channel) {
+            Some(channel) => self.ring_pending_messages(channel.ring_offset),
+            None => 0,
+        }
+    }
+
+    /// Ring bytes waiting to be received on a channel
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = pendingBytes))]
+    pub fn pending_bytes(&mut self, channel: &str) -> u32 {
+        match self.channels.get(channel) {
+            Some(channel) => self.ring_used_bytes(channel.ring_offset),
+            None => 0,
+        }
+    }
+
+    /// Returns true while a channel rejects sends with "would block"
+    ///
+    /// Re-evaluates the low watermark, so a drained channel reports unblocked.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = isBlocked))]
+    pub fn is_blocked(&mut self, channel: &str) -> bool {
+        let used = self.pending_bytes(channel);
+        match self.channels.get_mut(channel) {
+            Some(channel) => {
+                if let (true, Some(watermarks)) = (channel.blocked, channel.watermarks) {
+                    channel.blocked = used > watermarks.low;
+                }
+                channel.blocked
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAM_CHANGE: u32 = 1;
+
+    fn setup() -> BridgeContext {
+        let mut ctx = BridgeContext::new(1024);
+        ctx.open_channel("params", 256);
+        ctx.register_message_type(PARAM_CHANGE, "param-change");
+        ctx
+    }
+
+    #[test]
+    fn test_watermark_validation() {
+        let mut ctx = setup();
+        assert!(!ctx.set_watermarks("params", 100, 50));
+        assert!(!ctx.set_watermarks("params", 10, 512));
+        assert!(!ctx.set_watermarks("missing", 0, 10));
+        assert!(ctx.set_watermarks("params", 0, 128));
+    }
+
+    #[test]
+    fn test_would_block_with_hysteresis() {
+        let mut ctx = setup();
+        let frame = frame_bytes(4) as u32;
+        ctx.set_watermarks("params", frame, 3 * frame);
+
+        for _ in 0..3 {
+            assert!(ctx.send("params", PARAM_CHANGE, b"gain") >= 0);
+        }
+        assert_eq!(ctx.send("params", PARAM_CHANGE, b"gain"), SEND_WOULD_BLOCK);
+        assert_eq!(ctx.pending_messages("params"), 3);
+        assert_eq!(ctx.pending_bytes("params"), 3 * frame);
+
+        // Draining one message is not enough to cross the low watermark
+        ctx.receive("params");
+        assert!(ctx.is_blocked("params"));
+        assert_eq!(ctx.send("params", PARAM_CHANGE, b"gain"), SEND_WOULD_BLOCK);
+
+        ctx.receive("params");
+        assert!(!ctx.is_blocked("params"));
+        assert!(ctx.send("params", PARAM_CHANGE, b"gain") >= 0);
+    }
+
+    #[test]
+    fn test_full_without_watermarks() {
+        let mut ctx = setup();
+        let payload = [0u8; 100];
+        assert!(ctx.send("params", PARAM_CHANGE, &payload) >= 0);
+        assert!(ctx.send("params", PARAM_CHANGE, &payload) >= 0);
+        assert_eq!(
+            ctx.try_send("params", PARAM_CHANGE, &payload),
+            Err(SendError::Full)
+        );
+        assert!(ctx.clear_watermarks("params"));
+    }
+}
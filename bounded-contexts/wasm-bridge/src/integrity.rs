@@ -0,0 +1,356 @@
+//! Frame checksums and sequence validation for channels
+//!
+//! A channel can opt into a CRC-32 trailer on every frame and/or strict
+//! sequence checking on receive. Corrupt frames are always discarded; frames
+//! that arrive out of sequence are discarded under `ViolationPolicy::Drop`
+//! and delivered under `ViolationPolicy::Report`. Both policies count
+//! violations; `Report` also invokes the registered violation callback.
+//! After a sequence violation the expected sequence resynchronizes to the
+//! received one, so a single lost message yields a single report.
+//!
+//! Frame Layout with checksum:
+//! - Bytes 0-15:  MessageHeader
+//! - Bytes 16..:  payload (payload_len bytes)
+//! - Last 4:      CRC-32 (IEEE, u32 little-endian) of header and payload
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-bridge
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{BridgeContext, MessageHeader};
+
+/// Size of the CRC trailer in bytes
+pub const CHECKSUM_SIZE: usize = 4;
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// CRC-32 (IEEE 802.3) of `data`
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// What happens to a frame that fails validation
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationPolicy {
+    /// Discard silently (violations are still counted)
+    Drop = 0,
+    /// Invoke the violation callback; out-of-sequence frames are delivered
+    Report = 1,
+}
+
+impl ViolationPolicy {
+    /// Parses the numeric policy passed from JS
+    pub fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(ViolationPolicy::Drop),
+            1 => Some(ViolationPolicy::Report),
+            _ => None,
+        }
+    }
+}
+
+/// Kind of validation failure
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// CRC mismatch or truncated frame
+    Checksum = 1,
+    /// Sequence number differs from the expected one
+    Sequence = 2,
+}
+
+/// A detected validation failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityViolation {
+    pub channel: String,
+    pub kind: ViolationKind,
+    /// Expected CRC or sequence number
+    pub expected: u32,
+    /// Received CRC or sequence number
+    pub actual: u32,
+}
+
+/// Rust violation callback
+pub type ViolationHandler = Box<dyn FnMut(&IntegrityViolation)>;
+
+/// Violation callback registered on a context
+pub(crate) enum ViolationCallback {
+    Rust(ViolationHandler),
+    #[cfg(feature = "wasm")]
+    Js(js_sys::Function),
+}
+
+/// Per-channel validation settings and counters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityState {
+    pub checksum: bool,
+    pub verify_sequence: bool,
+    pub policy: ViolationPolicy,
+    /// Sequence number the next received frame should carry
+    pub expected_sequence: u32,
+    pub checksum_failures: u32,
+    pub sequence_errors: u32,
+}
+
+impl BridgeContext {
+    /// Registers a Rust callback for integrity violations
+    pub fn on_integrity_violation(&mut self, handler: impl FnMut(&IntegrityViolation) + 'static) {
+        self.violation_callback = Some(ViolationCallback::Rust(Box::new(handler)));
+    }
+
+    fn uses_checksum(&self, channel: &str) -> bool {
+        self.channels
+            .get(channel)
+            .and_then(|channel| channel.integrity.as_ref())
+            .is_some_and(|state| state.checksum)
+    }
+
+    /// Appends the CRC trailer when the channel uses checksums
+    pub(crate) fn seal_frame(&self, channel: &str, frame: &mut Vec<u8>) {
+        if self.uses_checksum(channel) {
+            let crc = crc32(frame);
+            frame.extend_from_slice(&crc.to_le_bytes());
+        }
+    }
+
+    /// Extra ring bytes each frame on a channel carries for integrity
+    pub(crate) fn frame_overhead(&self, channel: &str) -> usize {
+        if self.uses_checksum(channel) {
+            CHECKSUM_SIZE
+        } else {
+            0
+        }
+    }
+
+    /// Validates a received frame, stripping its CRC trailer
+    ///
+    /// # Returns
+    /// true if the frame should be delivered
+    pub(crate) fn verify_frame(&mut self, channel: &str, frame: &mut Vec<u8>) -> bool {
+        let state = match self.channels.get_mut(channel).and_then(|c| c.integrity.as_mut()) {
+            Some(state) => state,
+            None => return true,
+        };
+        let policy = state.policy;
+        let mut violation = None;
+        let mut deliver = true;
+
+        if state.checksum {
+            let body_len = frame.len().saturating_sub(CHECKSUM_SIZE);
+            let expected = crc32(&frame[..body_len]);
+            let actual = frame
+                .get(body_len..)
+                .filter(|trailer| trailer.len() == CHECKSUM_SIZE)
+                .map_or(!expected, |t| u32::from_le_bytes([t[0], t[1], t[2], t[3]]));
+            frame.truncate(body_len);
+
+            if frame.len() < MessageHeader::SIZE || actual != expected {
+                state.checksum_failures += 1;
+                violation = Some((ViolationKind::Checksum, expected, actual));
+                deliver = false;
+            }
+        }
+
+        if deliver && state.verify_sequence {
+            let actual = MessageHeader::from_bytes(frame).sequence;
+            let expected = state.expected_sequence;
+            state.expected_sequence = actual.wrapping_add(1);
+            if actual != expected {
+                state.sequence_errors += 1;
+                violation = Some((ViolationKind::Sequence, expected, actual));
+                deliver = policy == ViolationPolicy::Report;
+            }
+        }
+
+        if let (Some((kind, expected, actual)), ViolationPolicy::Report) = (violation, policy) {
+            self.report_violation(IntegrityViolation {
+                channel: channel.to_string(),
+                kind,
+                expected,
+                actual,
+            });
+        }
+        deliver
+    }
+
+    fn report_violation(&mut self, violation: IntegrityViolation) {
+        // Taken out so the callback may re-enter the context
+        let Some(mut callback) = self.violation_callback.take() else {
+            return;
+        };
+        match &mut callback {
+            ViolationCallback::Rust(f) => f(&violation),
+            #[cfg(feature = "wasm")]
+            ViolationCallback::Js(f) => {
+                let args = js_sys::Array::of4(
+                    &JsValue::from_str(&violation.channel),
+                    &JsValue::from(violation.kind as u32),
+                    &JsValue::from(violation.expected),
+                    &JsValue::from(violation.actual),
+                );
+                // Callback errors stay on the JS side
+                let _ = f.apply(&JsValue::NULL, &args);
+            }
+        }
+        self.violation_callback.get_or_insert(callback);
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BridgeContext {
+    /// Configures frame validation on a channel
+    ///
+    /// Both ends of a channel share this setting, so it should be chosen
+    /// before any messages are sent.
+    ///
+    /// # Arguments
+    /// * `channel` - Channel name
+    /// * `checksum` - Append and verify a CRC-32 trailer on every frame
+    /// * `verify_sequence` - Require consecutive sequence numbers on receive
+    /// * `policy` - 0 = drop violating frames, 1 = report via callback
+    ///
+    /// # Returns
+    /// false if the channel is not open or the policy is unknown
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setIntegrity))]
+    pub fn set_integrity(&mut self, channel: &str, checksum: bool, verify_sequence: bool, policy: u32) -> bool {
+        let Some(policy) = ViolationPolicy::from_u32(policy) else {
+            return false;
+        };
+        let Some(channel) = self.channels.get_mut(channel) else {
+            return false;
+        };
+
+        channel.integrity = (checksum || verify_sequence).then_some(IntegrityState {
+            checksum,
+            verify_sequence,
+            policy,
+            expected_sequence: channel.next_sequence,
+            checksum_failures: 0,
+            sequence_errors: 0,
+        });
+        true
+    }
+
+    /// Registers a JS callback for integrity violations
+    ///
+    /// The callback is invoked as `callback(channel, kind, expected, actual)`
+    /// where kind is 1 for checksum and 2 for sequence failures.
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(js_name = onIntegrityViolation)]
+    pub fn on_integrity_violation_js(&mut self, callback: js_sys::Function) {
+        self.violation_callback = Some(ViolationCallback::Js(callback));
+    }
+
+    /// Returns [checksum failures, sequence errors] for a channel
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = integrityStats))]
+    pub fn integrity_stats(&self, channel: &str) -> Vec<u32> {
+        self.channels
+            .get(channel)
+            .and_then(|channel| channel.integrity.as_ref())
+            .map_or(vec![0, 0], |state| {
+                vec![state.checksum_failures, state.sequence_errors]
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ring_buffer::{MESSAGE_PREFIX_SIZE, RING_HEADER_SIZE};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const PARAM_CHANGE: u32 = 1;
+
+    fn setup(checksum: bool, verify_sequence: bool, policy: ViolationPolicy) -> BridgeContext {
+        let mut ctx = BridgeContext::new(1024);
+        ctx.open_channel("params", 256);
+        ctx.register_message_type(PARAM_CHANGE, "param-change");
+        assert!(ctx.set_integrity("params", checksum, verify_sequence, policy as u32));
+        ctx
+    }
+
+    #[test]
+    fn test_crc32_known_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_checksum_round_trip_and_corruption() {
+        let mut ctx = setup(true, false, ViolationPolicy::Report);
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = seen.clone();
+        ctx.on_integrity_violation(move |violation| sink.borrow_mut().push(violation.kind));
+
+        ctx.send("params", PARAM_CHANGE, b"gain");
+        assert_eq!(ctx.receive("params").unwrap().1, b"gain");
+
+        ctx.send("params", PARAM_CHANGE, b"freq");
+        // Flip a payload byte of the second frame inside the ring
+        let ring = ctx.channel_offset("params") as usize;
+        let first_frame = MESSAGE_PREFIX_SIZE + MessageHeader::SIZE + 4 + CHECKSUM_SIZE;
+        let payload_at = ring + RING_HEADER_SIZE + first_frame + MESSAGE_PREFIX_SIZE + MessageHeader::SIZE;
+        ctx.range_mut(payload_at, 1).unwrap()[0] ^= 0xFF;
+
+        assert!(ctx.receive("params").is_none());
+        assert_eq!(ctx.integrity_stats("params"), vec![1, 0]);
+        assert_eq!(*seen.borrow(), vec![ViolationKind::Checksum]);
+    }
+
+    #[test]
+    fn test_sequence_gap_dropped() {
+        let mut ctx = setup(false, true, ViolationPolicy::Drop);
+        for payload in [b"a", b"b", b"c"] {
+            ctx.send("params", PARAM_CHANGE, payload);
+        }
+
+        // Lose the first frame below the channel layer
+        let ring = ctx.channel_offset("params") as usize;
+        ctx.pop_message(ring);
+
+        assert_eq!(ctx.receive("params").map(|m| m.1), Some(b"c".to_vec()));
+        assert_eq!(ctx.integrity_stats("params"), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_sequence_gap_reported_and_delivered() {
+        let mut ctx = setup(false, true, ViolationPolicy::Report);
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = seen.clone();
+        ctx.on_integrity_violation(move |v| sink.borrow_mut().push((v.expected, v.actual)));
+
+        ctx.send("params", PARAM_CHANGE, b"a");
+        ctx.send("params", PARAM_CHANGE, b"b");
+        let ring = ctx.channel_offset("params") as usize;
+        ctx.pop_message(ring);
+
+        assert_eq!(ctx.receive("params").map(|m| m.0.sequence), Some(1));
+        assert_eq!(*seen.borrow(), vec![(0, 1)]);
+        assert!(!ctx.set_integrity("params", true, true, 7));
+    }
+}
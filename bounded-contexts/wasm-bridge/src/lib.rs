@@ -1,86 +1,116 @@
 //! WASMBridge: Zero-copy JavaScript ↔ WASM communication layer
-//! 
+//!
 //! Provides efficient data transfer between JavaScript and WebAssembly with
 //! zero-copy optimization for large buffers using SharedArrayBuffer.
-//! 
+//!
+//! State lives in [`BridgeContext`] objects. The free functions below operate
+//! on a per-thread default context for callers that only need one buffer.
+//!
 //! See: harmony-design/DESIGN_SYSTEM.md#wasm-bridge
 
+mod allocator;
+mod channel;
+mod context;
+mod diagnostics;
+mod double_buffer;
+mod flow_control;
+mod integrity;
+pub mod persistence;
+mod ring_buffer;
+mod schema;
+mod traversal;
+mod wait;
+
+pub use allocator::{AllocationRecord, AllocatorStats, FreeListAllocator, ALIGNMENT, UNTAGGED};
+pub use channel::{Channel, MessageDispatcher, MessageHandler};
+pub use context::BridgeContext;
+pub use diagnostics::{AllocationReport, MemoryReport, TagUsage};
+pub use double_buffer::{DoubleBuffer, DOUBLE_BUFFER_HEADER_SIZE};
+pub use integrity::{
+    crc32, IntegrityState, IntegrityViolation, ViolationHandler, ViolationKind, ViolationPolicy,
+    CHECKSUM_SIZE,
+};
+pub use flow_control::{SendError, Watermarks, SEND_ERROR, SEND_WOULD_BLOCK};
+pub use ring_buffer::{RingBuffer, MESSAGE_PREFIX_SIZE, RING_HEADER_SIZE};
+pub use wait::{notify, timeout_from_ms, wait, WaitResult};
+pub use schema::{
+    BridgeMessage, FieldKind, GraphMix, GraphMutation, GraphMutationOp, MidiEvent, NodeBypass,
+    ParameterChange, ScheduledParameter, SchemaField, Transport, TransportCommand,
+    TraversalComplete, TraversalRequest, TraversalStatus,
+};
+
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
+use std::cell::RefCell;
 use std::slice;
 
-/// Shared memory pool for zero-copy transfers
-static mut SHARED_BUFFER: Vec<u8> = Vec::new();
+thread_local! {
+    /// Default context used by the free-function API
+    static DEFAULT_CONTEXT: RefCell<BridgeContext> = RefCell::new(BridgeContext::new(0));
+}
+
+fn with_default_context<R>(f: impl FnOnce(&mut BridgeContext) -> R) -> R {
+    DEFAULT_CONTEXT.with(|ctx| f(&mut ctx.borrow_mut()))
+}
 
 /// Initialize shared buffer with specified capacity
-/// 
+///
 /// # Arguments
 /// * `capacity` - Initial buffer capacity in bytes
-/// 
+///
 /// # Returns
 /// Pointer to the shared buffer for JavaScript access
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn init_shared_buffer(capacity: usize) -> *mut u8 {
-    unsafe {
-        SHARED_BUFFER = Vec::with_capacity(capacity);
-        SHARED_BUFFER.set_len(capacity);
-        SHARED_BUFFER.as_mut_ptr()
-    }
+    with_default_context(|ctx| {
+        *ctx = BridgeContext::new(capacity);
+        ctx.buffer_ptr()
+    })
 }
 
 /// Get pointer to shared buffer (for zero-copy reads)
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn get_shared_buffer_ptr() -> *const u8 {
-    unsafe { SHARED_BUFFER.as_ptr() }
+    with_default_context(|ctx| ctx.buffer_ptr() as *const u8)
 }
 
 /// Get current shared buffer length
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn get_shared_buffer_len() -> usize {
-    unsafe { SHARED_BUFFER.len() }
+    with_default_context(|ctx| ctx.buffer_len())
 }
 
 /// Write data to shared buffer at offset (zero-copy from JS TypedArray)
-/// 
+///
 /// # Arguments
 /// * `offset` - Byte offset in shared buffer
 /// * `data` - Pointer to source data
 /// * `len` - Length of data in bytes
-/// 
+///
 /// # Safety
 /// Caller must ensure data pointer is valid and len is accurate
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub unsafe fn write_to_shared_buffer(offset: usize, data: *const u8, len: usize) -> bool {
-    if offset + len > SHARED_BUFFER.len() {
-        return false;
-    }
-    
     let src = slice::from_raw_parts(data, len);
-    let dst = &mut SHARED_BUFFER[offset..offset + len];
-    dst.copy_from_slice(src);
-    true
+    with_default_context(|ctx| ctx.write(offset, src))
 }
 
 /// Read data from shared buffer at offset (zero-copy to JS TypedArray)
-/// 
+///
 /// # Arguments
 /// * `offset` - Byte offset in shared buffer
 /// * `len` - Length of data to read
-/// 
+///
 /// # Returns
 /// Pointer to data in shared buffer (no copy)
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn read_from_shared_buffer(offset: usize, len: usize) -> *const u8 {
-    unsafe {
-        if offset + len > SHARED_BUFFER.len() {
-            return std::ptr::null();
-        }
-        SHARED_BUFFER[offset..].as_ptr()
-    }
+    with_default_context(|ctx| ctx.read_ptr(offset, len))
 }
 
 /// Message header for structured communication
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MessageHeader {
     pub msg_type: u32,
     pub payload_offset: u32,
@@ -88,8 +118,34 @@ pub struct MessageHeader {
     pub sequence: u32,
 }
 
+impl MessageHeader {
+    /// Encoded header size in bytes (4 u32 fields)
+    pub const SIZE: usize = 16;
+
+    /// Encodes the header as little-endian bytes
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..4].copy_from_slice(&self.msg_type.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.payload_offset.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.payload_len.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.sequence.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a header from at least `SIZE` little-endian bytes
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let field = |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        Self {
+            msg_type: field(0),
+            payload_offset: field(4),
+            payload_len: field(8),
+            sequence: field(12),
+        }
+    }
+}
+
 /// Write message header to shared buffer
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn write_message_header(
     offset: usize,
     msg_type: u32,
@@ -97,74 +153,253 @@ pub fn write_message_header(
     payload_len: u32,
     sequence: u32,
 ) -> bool {
-    unsafe {
-        if offset + std::mem::size_of::<MessageHeader>() > SHARED_BUFFER.len() {
-            return false;
-        }
-        
-        let header = MessageHeader {
-            msg_type,
-            payload_offset,
-            payload_len,
-            sequence,
-        };
-        
-        let header_bytes = slice::from_raw_parts(
-            &header as *const MessageHeader as *const u8,
-            std::mem::size_of::<MessageHeader>(),
-        );
-        
-        let dst = &mut SHARED_BUFFER[offset..offset + header_bytes.len()];
-        dst.copy_from_slice(header_bytes);
-        true
-    }
+    with_default_context(|ctx| {
+        ctx.write_message_header(offset, msg_type, payload_offset, payload_len, sequence)
+    })
 }
 
 /// Read message header from shared buffer
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn read_message_header(offset: usize) -> *const MessageHeader {
-    unsafe {
-        if offset + std::mem::size_of::<MessageHeader>() > SHARED_BUFFER.len() {
-            return std::ptr::null();
-        }
-        
-        &SHARED_BUFFER[offset] as *const u8 as *const MessageHeader
-    }
+    with_default_context(|ctx| ctx.read_ptr(offset, MessageHeader::SIZE) as *const MessageHeader)
 }
 
 /// Allocate space in shared buffer and return offset
-/// Simple bump allocator for demo purposes
-static mut ALLOC_OFFSET: usize = 0;
-
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn allocate_in_shared_buffer(size: usize) -> i32 {
-    unsafe {
-        if ALLOC_OFFSET + size > SHARED_BUFFER.len() {
-            return -1; // Out of memory
-        }
-        
-        let offset = ALLOC_OFFSET;
-        ALLOC_OFFSET += size;
-        offset as i32
-    }
+    with_default_context(|ctx| ctx.allocate(size))
+}
+
+/// Free a previous allocation in shared buffer
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn free_in_shared_buffer(offset: usize) -> bool {
+    with_default_context(|ctx| ctx.free(offset))
+}
+
+/// Open an allocation scope in shared buffer
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn begin_shared_buffer_scope() -> usize {
+    with_default_context(|ctx| ctx.begin_scope())
+}
+
+/// Close the innermost allocation scope, freeing its allocations
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn end_shared_buffer_scope() -> bool {
+    with_default_context(|ctx| ctx.end_scope())
 }
 
 /// Reset allocator (for testing or cleanup)
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn reset_shared_buffer_allocator() {
-    unsafe {
-        ALLOC_OFFSET = 0;
-    }
+    with_default_context(|ctx| ctx.reset_allocator())
 }
 
 /// Get memory statistics
-#[wasm_bindgen]
+/// [total, used, free, high-water mark, live allocation count]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn get_memory_stats() -> Vec<u32> {
-    unsafe {
-        vec![
-            SHARED_BUFFER.len() as u32,
-            ALLOC_OFFSET as u32,
-            (SHARED_BUFFER.len() - ALLOC_OFFSET) as u32,
-        ]
+    with_default_context(|ctx| ctx.memory_stats())
+}
+
+/// Get the structured memory report (per-tag usage, high-water mark) as JSON
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_memory_report() -> String {
+    with_default_context(|ctx| ctx.memory_report_json())
+}
+
+/// Allocate tagged space in shared buffer
+///
+/// # Arguments
+/// * `size` - Bytes to reserve
+/// * `tag` - Owning subsystem, shown in the memory report
+/// * `call_site` - Caller description kept in debug mode
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn allocate_tagged_in_shared_buffer(size: usize, tag: &str, call_site: Option<String>) -> i32 {
+    with_default_context(|ctx| ctx.allocate_tagged(size, tag, call_site))
+}
+
+/// Enable or disable call-site recording for shared buffer allocations
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn set_allocation_debug(enabled: bool) {
+    with_default_context(|ctx| ctx.set_allocation_debug(enabled))
+}
+
+/// Mark the current point in the allocation sequence for leak checks
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_leak_checkpoint() -> u32 {
+    with_default_context(|ctx| ctx.leak_checkpoint())
+}
+
+/// Get allocations made since a checkpoint that are still live, as JSON
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_leaks_since(checkpoint: u32) -> String {
+    with_default_context(|ctx| ctx.leaks_since_json(checkpoint))
+}
+
+/// Get fragmentation statistics
+/// [largest free block, free block count, live allocation count]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_fragmentation_stats() -> Vec<u32> {
+    with_default_context(|ctx| ctx.fragmentation_stats())
+}
+
+/// Create an SPSC ring buffer in shared buffer
+///
+/// # Returns
+/// Offset of the ring region, or -1 on failure
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn create_ring_buffer(capacity: u32) -> i32 {
+    with_default_context(|ctx| ctx.create_ring_buffer(capacity))
+}
+
+/// Enqueue a message on a ring buffer in shared buffer
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn push_message(ring_offset: usize, payload: &[u8]) -> bool {
+    with_default_context(|ctx| ctx.push_message(ring_offset, payload))
+}
+
+/// Dequeue the oldest message from a ring buffer in shared buffer
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn pop_message(ring_offset: usize) -> Option<Vec<u8>> {
+    with_default_context(|ctx| ctx.pop_message(ring_offset))
+}
+
+/// Block while the u32 at `offset` in shared buffer equals `expected`
+///
+/// # Returns
+/// 0 woken, 1 not equal, 2 timed out, 3 invalid offset
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn wait_on_shared_buffer(offset: usize, expected: u32, timeout_ms: f64) -> u32 {
+    with_default_context(|ctx| ctx.wait_on(offset, expected, timeout_ms))
+}
+
+/// Wake up to `count` waiters blocked on the u32 at `offset` in shared buffer
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn notify_shared_buffer(offset: usize, count: u32) -> u32 {
+    with_default_context(|ctx| ctx.notify(offset, count))
+}
+
+/// Open a named channel in shared buffer
+///
+/// # Returns
+/// Offset of the channel's ring region, or -1 on failure
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn open_channel(name: &str, capacity: u32) -> i32 {
+    with_default_context(|ctx| ctx.open_channel(name, capacity))
+}
+
+/// Register a message type name for channel messages
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn register_message_type(msg_type: u32, name: &str) -> bool {
+    with_default_context(|ctx| ctx.register_message_type(msg_type, name))
+}
+
+/// Register the built-in schema message types
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn register_schema_message_types() -> bool {
+    with_default_context(|ctx| ctx.register_schema_types())
+}
+
+/// Register a JS callback for a message type
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn on_channel_message(msg_type: u32, callback: js_sys::Function) {
+    with_default_context(|ctx| ctx.on_message_js(msg_type, callback))
+}
+
+/// Send a message on a named channel
+///
+/// # Returns
+/// Message sequence number, -2 when the channel would block, or -1 on failure
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn send_on_channel(channel: &str, msg_type: u32, payload: &[u8]) -> i64 {
+    with_default_context(|ctx| ctx.send(channel, msg_type, payload))
+}
+
+/// Set low/high watermarks (queued bytes) on a named channel
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn set_channel_watermarks(channel: &str, low: u32, high: u32) -> bool {
+    with_default_context(|ctx| ctx.set_watermarks(channel, low, high))
+}
+
+/// Configure checksum/sequence validation on a named channel
+///
+/// # Arguments
+/// * `policy` - 0 = drop violating frames, 1 = report via callback
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn set_channel_integrity(channel: &str, checksum: bool, verify_sequence: bool, policy: u32) -> bool {
+    with_default_context(|ctx| ctx.set_integrity(channel, checksum, verify_sequence, policy))
+}
+
+/// Register a JS callback for integrity violations
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn on_integrity_violation(callback: js_sys::Function) {
+    with_default_context(|ctx| ctx.on_integrity_violation_js(callback))
+}
+
+/// Get the number of messages waiting on a named channel
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_channel_pending(channel: &str) -> u32 {
+    with_default_context(|ctx| ctx.pending_messages(channel))
+}
+
+/// Drain a named channel into its registered handlers
+///
+/// Handlers run outside the default-context borrow, so JS callbacks may call
+/// back into the bridge (e.g. to reply on another channel).
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn dispatch_channel(channel: &str) -> u32 {
+    let (messages, mut dispatcher) = with_default_context(|ctx| {
+        let mut messages = Vec::new();
+        while let Some(message) = ctx.receive(channel) {
+            messages.push(message);
+        }
+        (messages, std::mem::take(&mut ctx.dispatcher))
+    });
+
+    for (header, payload) in &messages {
+        dispatcher.dispatch(header, payload);
     }
-}
\ No newline at end of file
+
+    // Handlers registered during dispatch go after the existing ones
+    with_default_context(|ctx| {
+        let mut added = std::mem::take(&mut ctx.dispatcher);
+        added.absorb(dispatcher);
+        ctx.dispatcher = added;
+    });
+    messages.len() as u32
+}
+
+/// Answer pending traversal requests on a channel, replying on another
+///
+/// Results are written into shared buffer regions the receiver must free.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn serve_traversals(
+    executor: &wasm_edge_executor::WASMEdgeExecutor,
+    requests: &str,
+    replies: &str,
+) -> u32 {
+    with_default_context(|ctx| ctx.serve_traversals(executor, requests, replies))
+}
+
+/// Create a double buffer in shared buffer
+///
+/// # Returns
+/// Offset of the region, or -1 on failure
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn create_double_buffer(frame_capacity: u32) -> i32 {
+    with_default_context(|ctx| ctx.create_double_buffer(frame_capacity))
+}
+
+/// Publish a frame to a double buffer in shared buffer
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn write_frame(offset: usize, frame: &[u8]) -> bool {
+    with_default_context(|ctx| ctx.write_frame(offset, frame))
+}
+
+/// Copy the latest frame from a double buffer in shared buffer
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn read_latest_frame(offset: usize) -> Option<Vec<u8>> {
+    with_default_context(|ctx| ctx.read_latest_frame(offset))
+}
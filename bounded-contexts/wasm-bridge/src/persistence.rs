@@ -0,0 +1,711 @@
+//! Chunked persistence of binary snapshots to OPFS or IndexedDB
+//!
+//! `PersistenceAdapter` stores byte buffers (`exportEdges` buffers, audio
+//! graph states, serialized project archives) under string keys. Buffers
+//! are written and read one chunk at a time, awaiting the browser between
+//! chunks, so multi-megabyte snapshots persist without blocking the main
+//! thread. An optional progress callback is called after every chunk with
+//! `(bytesDone, bytesTotal)`.
+//!
+//! Every buffer has a `ChunkManifest` stored next to it, holding its length,
+//! chunk size and CRC-32; reads fail with `malformed` if the data does not
+//! match. Backends:
+//! - OPFS: one file per key plus a `<key>.manifest` file. Writable streams
+//!   replace the file only when closed, so a failed write keeps the old data.
+//! - IndexedDB: one record per chunk in the `chunks` store, keyed
+//!   `<key>/<generation>/<index>`, plus a `<key>/manifest` record. A write
+//!   goes to a new generation and switches the manifest last, then deletes
+//!   the old generation's chunks.
+//!
+//! The chunking and manifest logic is plain Rust; the browser I/O needs the
+//! `wasm` feature.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-bridge
+
+use std::ops::Range;
+use std::str::FromStr;
+
+use harmony_error::{limits, HarmonyError};
+use serde::{Deserialize, Serialize};
+
+use crate::integrity::crc32;
+
+/// Chunk size used unless the adapter is opened with another
+pub const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Manifest format version written by `ChunkManifest::new`
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Where a `PersistenceAdapter` stores its data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// Origin private file system
+    Opfs,
+    IndexedDb,
+}
+
+impl FromStr for StorageBackend {
+    type Err = HarmonyError;
+
+    /// Parses "opfs" or "indexeddb"
+    fn from_str(backend: &str) -> Result<Self, HarmonyError> {
+        match backend {
+            "opfs" => Ok(Self::Opfs),
+            "indexeddb" => Ok(Self::IndexedDb),
+            _ => Err(HarmonyError::InvalidInput(format!(
+                "Unknown storage backend '{}'; expected 'opfs' or 'indexeddb'",
+                backend
+            ))),
+        }
+    }
+}
+
+/// Layout and checksum of one stored buffer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkManifest {
+    pub version: u32,
+    /// Buffer length in bytes
+    pub len: usize,
+    pub chunk_size: usize,
+    pub chunk_count: usize,
+    /// CRC-32 of the whole buffer
+    pub crc32: u32,
+    /// Write generation; IndexedDB chunk keys include it
+    pub generation: u32,
+}
+
+impl ChunkManifest {
+    /// Describes `data` split into chunks of `chunk_size` bytes
+    pub fn new(data: &[u8], chunk_size: usize, generation: u32) -> Self {
+        let chunk_size = chunk_size.max(1);
+        Self {
+            version: MANIFEST_VERSION,
+            len: data.len(),
+            chunk_size,
+            chunk_count: data.len().div_ceil(chunk_size),
+            crc32: crc32(data),
+            generation,
+        }
+    }
+
+    /// Parses a stored manifest, checking it against the input limits first
+    pub fn from_json(json: &str) -> Result<Self, HarmonyError> {
+        limits::check_json(json)?;
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Byte range of chunk `index` in the buffer
+    pub fn chunk_range(&self, index: usize) -> Range<usize> {
+        let start = (index * self.chunk_size).min(self.len);
+        start..(start + self.chunk_size).min(self.len)
+    }
+
+    /// Fails with `malformed` if `data` is not the buffer this describes
+    pub fn verify(&self, data: &[u8]) -> Result<(), HarmonyError> {
+        if data.len() != self.len {
+            return Err(HarmonyError::Malformed(format!(
+                "Stored data is {} bytes, expected {}",
+                data.len(),
+                self.len
+            )));
+        }
+        let checksum = crc32(data);
+        if checksum != self.crc32 {
+            return Err(HarmonyError::Malformed(format!(
+                "Stored data has CRC-32 {:08x}, expected {:08x}",
+                checksum, self.crc32
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Fails with `invalid_input` unless `key` can name a file and prefix
+/// IndexedDB keys: non-empty, without `/` or `\`, and not a manifest name
+pub fn validate_key(key: &str) -> Result<(), HarmonyError> {
+    if key.is_empty() || key.contains(['/', '\\']) || key.ends_with(".manifest") {
+        return Err(HarmonyError::InvalidInput(format!(
+            "Invalid storage key '{}'",
+            key
+        )));
+    }
+    Ok(())
+}
+
+/// IndexedDB key of a chunk
+pub fn chunk_key(key: &str, generation: u32, index: usize) -> String {
+    format!("{}/{}/{}", key, generation, index)
+}
+
+/// IndexedDB key, or OPFS file name, of a buffer's manifest
+pub fn manifest_key(backend: StorageBackend, key: &str) -> String {
+    match backend {
+        StorageBackend::Opfs => format!("{}.manifest", key),
+        StorageBackend::IndexedDb => format!("{}/manifest", key),
+    }
+}
+
+#[cfg(feature = "wasm")]
+pub use browser::PersistenceAdapter;
+
+#[cfg(feature = "wasm")]
+mod browser {
+    use js_sys::{Function, Promise, Reflect, Uint8Array};
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::{future_to_promise, JsFuture};
+    use web_sys::{
+        Blob, FileSystemDirectoryHandle, FileSystemFileHandle, FileSystemGetDirectoryOptions,
+        FileSystemGetFileOptions, FileSystemWritableFileStream, IdbDatabase, IdbFactory,
+        IdbObjectStore, IdbOpenDbRequest, IdbRequest, IdbTransaction, IdbTransactionMode,
+        StorageManager,
+    };
+
+    use super::*;
+
+    /// IndexedDB object store holding chunks and manifests
+    const STORE: &str = "chunks";
+
+    #[derive(Clone)]
+    enum Storage {
+        Opfs(FileSystemDirectoryHandle),
+        IndexedDb(IdbDatabase),
+    }
+
+    /// Chunked, non-blocking storage of byte buffers; see the module docs
+    #[wasm_bindgen]
+    #[derive(Clone)]
+    pub struct PersistenceAdapter {
+        storage: Storage,
+        chunk_size: usize,
+    }
+
+    #[wasm_bindgen]
+    impl PersistenceAdapter {
+        /// Opens (creating if needed) the storage area `name`
+        ///
+        /// # Arguments
+        /// * `backend` - "opfs" or "indexeddb"
+        /// * `name` - OPFS directory or IndexedDB database name
+        /// * `chunk_size` - Bytes per chunk (default: 1 MiB)
+        ///
+        /// # Returns
+        /// Promise of the adapter; rejects with `unsupported` if the browser
+        /// lacks the backend
+        #[wasm_bindgen(unchecked_return_type = "Promise<PersistenceAdapter>")]
+        pub fn open(backend: &str, name: String, chunk_size: Option<usize>) -> Promise {
+            let backend = backend.parse::<StorageBackend>();
+            future_to_promise(async move {
+                let chunk_size = chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+                if chunk_size == 0 {
+                    return Err(HarmonyError::InvalidInput(
+                        "Chunk size must be positive".to_string(),
+                    )
+                    .into());
+                }
+                let storage = match backend? {
+                    StorageBackend::Opfs => Storage::Opfs(open_opfs(&name).await?),
+                    StorageBackend::IndexedDb => Storage::IndexedDb(open_indexed_db(&name).await?),
+                };
+                Ok(PersistenceAdapter {
+                    storage,
+                    chunk_size,
+                }
+                .into())
+            })
+        }
+
+        /// Stores `data` under `key`, replacing what was there
+        ///
+        /// # Arguments
+        /// * `on_progress` - Called as `(bytesWritten, bytesTotal)` after each chunk
+        #[wasm_bindgen(unchecked_return_type = "Promise<void>")]
+        pub fn write(&self, key: String, data: Vec<u8>, on_progress: Option<Function>) -> Promise {
+            let adapter = self.clone();
+            future_to_promise(async move {
+                validate_key(&key)?;
+                match &adapter.storage {
+                    Storage::Opfs(dir) => {
+                        opfs_write(dir, &key, &data, adapter.chunk_size, on_progress.as_ref())
+                            .await?
+                    }
+                    Storage::IndexedDb(db) => {
+                        idb_write(db, &key, &data, adapter.chunk_size, on_progress.as_ref()).await?
+                    }
+                }
+                Ok(JsValue::UNDEFINED)
+            })
+        }
+
+        /// Reads the data stored under `key`
+        ///
+        /// # Arguments
+        /// * `on_progress` - Called as `(bytesRead, bytesTotal)` after each chunk
+        ///
+        /// # Returns
+        /// Promise of the bytes; rejects with `not_found` for an unknown key or
+        /// `malformed` if the data fails its manifest's checks
+        #[wasm_bindgen(unchecked_return_type = "Promise<Uint8Array>")]
+        pub fn read(&self, key: String, on_progress: Option<Function>) -> Promise {
+            let adapter = self.clone();
+            future_to_promise(async move {
+                validate_key(&key)?;
+                let data = match &adapter.storage {
+                    Storage::Opfs(dir) => opfs_read(dir, &key, on_progress.as_ref()).await?,
+                    Storage::IndexedDb(db) => idb_read(db, &key, on_progress.as_ref()).await?,
+                };
+                Ok(Uint8Array::from(data.as_slice()).into())
+            })
+        }
+
+        /// Deletes the data stored under `key`
+        ///
+        /// # Returns
+        /// Promise of whether there was data to delete
+        #[wasm_bindgen(unchecked_return_type = "Promise<boolean>")]
+        pub fn remove(&self, key: String) -> Promise {
+            let adapter = self.clone();
+            future_to_promise(async move {
+                validate_key(&key)?;
+                let removed = match &adapter.storage {
+                    Storage::Opfs(dir) => opfs_remove(dir, &key).await?,
+                    Storage::IndexedDb(db) => idb_remove(db, &key).await?,
+                };
+                Ok(removed.into())
+            })
+        }
+    }
+
+    /// Converts a rejected promise or thrown value, using the JS error's
+    /// name to spot missing entries
+    fn js_error(what: &str, error: JsValue) -> HarmonyError {
+        let field = |name: &str| {
+            Reflect::get(&error, &name.into())
+                .ok()
+                .and_then(|value| value.as_string())
+        };
+        let message = field("message")
+            .or_else(|| error.as_string())
+            .unwrap_or_else(|| format!("{:?}", error));
+        let message = format!("{}: {}", what, message);
+        match field("name").as_deref() {
+            Some("NotFoundError") => HarmonyError::NotFound(message),
+            Some("QuotaExceededError") => HarmonyError::CapacityExceeded(message),
+            _ => HarmonyError::Internal(message),
+        }
+    }
+
+    async fn await_promise(what: &str, promise: Promise) -> Result<JsValue, HarmonyError> {
+        JsFuture::from(promise)
+            .await
+            .map_err(|error| js_error(what, error))
+    }
+
+    fn report(
+        on_progress: Option<&Function>,
+        done: usize,
+        total: usize,
+    ) -> Result<(), HarmonyError> {
+        if let Some(callback) = on_progress {
+            callback
+                .call2(
+                    &JsValue::NULL,
+                    &(done as f64).into(),
+                    &(total as f64).into(),
+                )
+                .map_err(|error| js_error("Progress callback", error))?;
+        }
+        Ok(())
+    }
+
+    fn global_property(name: &str) -> Result<JsValue, HarmonyError> {
+        Reflect::get(&js_sys::global(), &name.into())
+            .ok()
+            .filter(|value| !value.is_undefined())
+            .ok_or_else(|| HarmonyError::Unsupported(format!("No '{}' in this environment", name)))
+    }
+
+    async fn open_opfs(name: &str) -> Result<FileSystemDirectoryHandle, HarmonyError> {
+        let storage: StorageManager =
+            Reflect::get(&global_property("navigator")?, &"storage".into())
+                .ok()
+                .and_then(|storage| storage.dyn_into().ok())
+                .ok_or_else(|| {
+                    HarmonyError::Unsupported(
+                        "No navigator.storage in this environment".to_string(),
+                    )
+                })?;
+        let root: FileSystemDirectoryHandle =
+            await_promise("Opening OPFS", storage.get_directory())
+                .await?
+                .unchecked_into();
+        let options = FileSystemGetDirectoryOptions::new();
+        options.set_create(true);
+        Ok(await_promise(
+            "Opening OPFS directory",
+            root.get_directory_handle_with_options(name, &options),
+        )
+        .await?
+        .unchecked_into())
+    }
+
+    async fn opfs_file(
+        dir: &FileSystemDirectoryHandle,
+        name: &str,
+        create: bool,
+    ) -> Result<FileSystemFileHandle, HarmonyError> {
+        let options = FileSystemGetFileOptions::new();
+        options.set_create(create);
+        Ok(await_promise(
+            &format!("Opening '{}'", name),
+            dir.get_file_handle_with_options(name, &options),
+        )
+        .await?
+        .unchecked_into())
+    }
+
+    async fn opfs_write_file(
+        dir: &FileSystemDirectoryHandle,
+        name: &str,
+        chunks: impl Iterator<Item = &[u8]>,
+        mut on_chunk: impl FnMut(usize) -> Result<(), HarmonyError>,
+    ) -> Result<(), HarmonyError> {
+        let what = format!("Writing '{}'", name);
+        let file = opfs_file(dir, name, true).await?;
+        let stream: FileSystemWritableFileStream = await_promise(&what, file.create_writable())
+            .await?
+            .unchecked_into();
+        let mut written = 0;
+        for chunk in chunks {
+            let pending = stream
+                .write_with_u8_array(chunk)
+                .map_err(|error| js_error(&what, error))?;
+            if let Err(error) = await_promise(&what, pending).await {
+                // Abandon the swap file; the old contents stay
+                let _ = JsFuture::from(stream.abort()).await;
+                return Err(error);
+            }
+            written += chunk.len();
+            on_chunk(written)?;
+        }
+        await_promise(&what, stream.close()).await?;
+        Ok(())
+    }
+
+    async fn opfs_read_file(
+        dir: &FileSystemDirectoryHandle,
+        name: &str,
+    ) -> Result<Blob, HarmonyError> {
+        let file = opfs_file(dir, name, false).await?;
+        Ok(
+            await_promise(&format!("Reading '{}'", name), file.get_file())
+                .await?
+                .unchecked_into(),
+        )
+    }
+
+    async fn opfs_write(
+        dir: &FileSystemDirectoryHandle,
+        key: &str,
+        data: &[u8],
+        chunk_size: usize,
+        on_progress: Option<&Function>,
+    ) -> Result<(), HarmonyError> {
+        let manifest = ChunkManifest::new(data, chunk_size, 0);
+        let chunks = (0..manifest.chunk_count).map(|index| &data[manifest.chunk_range(index)]);
+        opfs_write_file(dir, key, chunks, |written| {
+            report(on_progress, written, data.len())
+        })
+        .await?;
+        let manifest_json = serde_json::to_vec(&manifest)?;
+        opfs_write_file(
+            dir,
+            &manifest_key(StorageBackend::Opfs, key),
+            std::iter::once(manifest_json.as_slice()),
+            |_| Ok(()),
+        )
+        .await
+    }
+
+    async fn opfs_read(
+        dir: &FileSystemDirectoryHandle,
+        key: &str,
+        on_progress: Option<&Function>,
+    ) -> Result<Vec<u8>, HarmonyError> {
+        let manifest_file = opfs_read_file(dir, &manifest_key(StorageBackend::Opfs, key)).await?;
+        let manifest_json = await_promise("Reading manifest", manifest_file.text()).await?;
+        let manifest =
+            ChunkManifest::from_json(&manifest_json.as_string().unwrap_or_default())?;
+        let file = opfs_read_file(dir, key).await?;
+        let mut data = vec![0; manifest.len];
+        let what = format!("Reading '{}'", key);
+        for index in 0..manifest.chunk_count {
+            let range = manifest.chunk_range(index);
+            let slice = file
+                .slice_with_f64_and_f64(range.start as f64, range.end as f64)
+                .map_err(|error| js_error(&what, error))?;
+            let buffer = await_promise(&what, slice.array_buffer()).await?;
+            let chunk = Uint8Array::new(&buffer);
+            if chunk.length() as usize != range.len() {
+                return Err(HarmonyError::Malformed(format!(
+                    "{}: file is shorter than its manifest",
+                    what
+                )));
+            }
+            chunk.copy_to(&mut data[range.clone()]);
+            report(on_progress, range.end, manifest.len)?;
+        }
+        manifest.verify(&data)?;
+        Ok(data)
+    }
+
+    async fn opfs_remove(dir: &FileSystemDirectoryHandle, key: &str) -> Result<bool, HarmonyError> {
+        let mut removed = false;
+        for name in [manifest_key(StorageBackend::Opfs, key), key.to_string()] {
+            match await_promise(&format!("Removing '{}'", name), dir.remove_entry(&name)).await {
+                Ok(_) => removed = true,
+                Err(HarmonyError::NotFound(_)) => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Resolves with the request's result once it succeeds
+    async fn request_done(what: &str, request: &IdbRequest) -> Result<JsValue, HarmonyError> {
+        let done = Promise::new(&mut |resolve, reject| {
+            request.set_onsuccess(Some(&resolve));
+            request.set_onerror(Some(&reject));
+        });
+        if JsFuture::from(done).await.is_err() {
+            let error = request.error().ok().flatten().map(JsValue::from);
+            return Err(js_error(what, error.unwrap_or(JsValue::NULL)));
+        }
+        request.result().map_err(|error| js_error(what, error))
+    }
+
+    /// Resolves once the transaction commits
+    async fn transaction_done(
+        what: &str,
+        transaction: &IdbTransaction,
+    ) -> Result<(), HarmonyError> {
+        let done = Promise::new(&mut |resolve, reject| {
+            transaction.set_oncomplete(Some(&resolve));
+            transaction.set_onerror(Some(&reject));
+            transaction.set_onabort(Some(&reject));
+        });
+        if JsFuture::from(done).await.is_err() {
+            let error = transaction.error().map(JsValue::from);
+            return Err(js_error(what, error.unwrap_or(JsValue::NULL)));
+        }
+        Ok(())
+    }
+
+    async fn open_indexed_db(name: &str) -> Result<IdbDatabase, HarmonyError> {
+        let factory: IdbFactory = global_property("indexedDB")?.unchecked_into();
+        let request: IdbOpenDbRequest = factory
+            .open_with_u32(name, 1)
+            .map_err(|error| js_error("Opening IndexedDB", error))?;
+        let upgrading = request.clone();
+        let on_upgrade = Closure::once(move || {
+            if let Ok(db) = upgrading.result() {
+                let _ = db
+                    .unchecked_into::<IdbDatabase>()
+                    .create_object_store(STORE);
+            }
+        });
+        request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+        let db = request_done("Opening IndexedDB", &request).await;
+        request.set_onupgradeneeded(None);
+        Ok(db?.unchecked_into())
+    }
+
+    fn store(
+        db: &IdbDatabase,
+        mode: IdbTransactionMode,
+    ) -> Result<(IdbTransaction, IdbObjectStore), HarmonyError> {
+        let transaction = db
+            .transaction_with_str_and_mode(STORE, mode)
+            .map_err(|error| js_error("Starting transaction", error))?;
+        let store = transaction
+            .object_store(STORE)
+            .map_err(|error| js_error("Opening object store", error))?;
+        Ok((transaction, store))
+    }
+
+    async fn idb_get(db: &IdbDatabase, key: &str) -> Result<JsValue, HarmonyError> {
+        let what = format!("Reading '{}'", key);
+        let (_, store) = store(db, IdbTransactionMode::Readonly)?;
+        let request = store
+            .get(&key.into())
+            .map_err(|error| js_error(&what, error))?;
+        request_done(&what, &request).await
+    }
+
+    async fn idb_put(db: &IdbDatabase, key: &str, value: &JsValue) -> Result<(), HarmonyError> {
+        let what = format!("Writing '{}'", key);
+        let (transaction, store) = store(db, IdbTransactionMode::Readwrite)?;
+        store
+            .put_with_key(value, &key.into())
+            .map_err(|error| js_error(&what, error))?;
+        transaction_done(&what, &transaction).await
+    }
+
+    async fn idb_delete(db: &IdbDatabase, keys: &[String]) -> Result<(), HarmonyError> {
+        let (transaction, store) = store(db, IdbTransactionMode::Readwrite)?;
+        for key in keys {
+            store
+                .delete(&key.into())
+                .map_err(|error| js_error(&format!("Removing '{}'", key), error))?;
+        }
+        transaction_done("Removing chunks", &transaction).await
+    }
+
+    async fn idb_manifest(
+        db: &IdbDatabase,
+        key: &str,
+    ) -> Result<Option<ChunkManifest>, HarmonyError> {
+        let manifest = idb_get(db, &manifest_key(StorageBackend::IndexedDb, key)).await?;
+        match manifest.as_string() {
+            Some(json) => Ok(Some(ChunkManifest::from_json(&json)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn chunk_keys(key: &str, manifest: &ChunkManifest) -> Vec<String> {
+        (0..manifest.chunk_count)
+            .map(|index| chunk_key(key, manifest.generation, index))
+            .collect()
+    }
+
+    async fn idb_write(
+        db: &IdbDatabase,
+        key: &str,
+        data: &[u8],
+        chunk_size: usize,
+        on_progress: Option<&Function>,
+    ) -> Result<(), HarmonyError> {
+        let previous = idb_manifest(db, key).await?;
+        let generation = previous
+            .as_ref()
+            .map_or(0, |manifest| manifest.generation.wrapping_add(1));
+        let manifest = ChunkManifest::new(data, chunk_size, generation);
+        for index in 0..manifest.chunk_count {
+            let range = manifest.chunk_range(index);
+            let chunk = Uint8Array::from(&data[range.clone()]);
+            idb_put(db, &chunk_key(key, generation, index), &chunk).await?;
+            report(on_progress, range.end, data.len())?;
+        }
+        let manifest_json = serde_json::to_string(&manifest)?;
+        idb_put(
+            db,
+            &manifest_key(StorageBackend::IndexedDb, key),
+            &manifest_json.into(),
+        )
+        .await?;
+        if let Some(previous) = previous {
+            idb_delete(db, &chunk_keys(key, &previous)).await?;
+        }
+        Ok(())
+    }
+
+    async fn idb_read(
+        db: &IdbDatabase,
+        key: &str,
+        on_progress: Option<&Function>,
+    ) -> Result<Vec<u8>, HarmonyError> {
+        let manifest = idb_manifest(db, key)
+            .await?
+            .ok_or_else(|| HarmonyError::NotFound(format!("No data stored under '{}'", key)))?;
+        let mut data = vec![0; manifest.len];
+        for index in 0..manifest.chunk_count {
+            let range = manifest.chunk_range(index);
+            let chunk_key = chunk_key(key, manifest.generation, index);
+            let chunk = idb_get(db, &chunk_key)
+                .await?
+                .dyn_into::<Uint8Array>()
+                .map_err(|_| {
+                    HarmonyError::Malformed(format!("Chunk '{}' is missing", chunk_key))
+                })?;
+            if chunk.length() as usize != range.len() {
+                return Err(HarmonyError::Malformed(format!(
+                    "Chunk '{}' is {} bytes, expected {}",
+                    chunk_key,
+                    chunk.length(),
+                    range.len()
+                )));
+            }
+            chunk.copy_to(&mut data[range.clone()]);
+            report(on_progress, range.end, manifest.len)?;
+        }
+        manifest.verify(&data)?;
+        Ok(data)
+    }
+
+    async fn idb_remove(db: &IdbDatabase, key: &str) -> Result<bool, HarmonyError> {
+        let Some(manifest) = idb_manifest(db, key).await? else {
+            return Ok(false);
+        };
+        let mut keys = chunk_keys(key, &manifest);
+        keys.push(manifest_key(StorageBackend::IndexedDb, key));
+        idb_delete(db, &keys).await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_chunks_and_verify() {
+        let data: Vec<u8> = (0..=255).cycle().take(2500).collect();
+        let manifest = ChunkManifest::new(&data, 1000, 3);
+        assert_eq!(manifest.chunk_count, 3);
+        assert_eq!(manifest.chunk_range(0), 0..1000);
+        assert_eq!(manifest.chunk_range(2), 2000..2500);
+        assert!(manifest.verify(&data).is_ok());
+
+        let mut corrupt = data.clone();
+        corrupt[1234] ^= 1;
+        assert_eq!(manifest.verify(&corrupt).unwrap_err().code(), "malformed");
+        assert_eq!(
+            manifest.verify(&data[..10]).unwrap_err().code(),
+            "malformed"
+        );
+
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert_eq!(ChunkManifest::from_json(&json).unwrap(), manifest);
+        let nested = format!("{}{}", "[".repeat(100), "]".repeat(100));
+        assert_eq!(
+            ChunkManifest::from_json(&nested).unwrap_err().code(),
+            "capacity_exceeded"
+        );
+        assert_eq!(ChunkManifest::new(&[], 1000, 0).chunk_count, 0);
+    }
+
+    #[test]
+    fn test_keys_and_backends() {
+        assert_eq!(
+            "opfs".parse::<StorageBackend>().unwrap(),
+            StorageBackend::Opfs
+        );
+        assert_eq!(
+            "s3".parse::<StorageBackend>().unwrap_err().code(),
+            "invalid_input"
+        );
+        assert!(validate_key("project-graph").is_ok());
+        for key in ["", "a/b", "a\\b", "graph.manifest"] {
+            assert_eq!(validate_key(key).unwrap_err().code(), "invalid_input");
+        }
+        assert_eq!(chunk_key("graph", 2, 7), "graph/2/7");
+        assert_eq!(
+            manifest_key(StorageBackend::Opfs, "graph"),
+            "graph.manifest"
+        );
+        assert_eq!(
+            manifest_key(StorageBackend::IndexedDb, "graph"),
+            "graph/manifest"
+        );
+    }
+}
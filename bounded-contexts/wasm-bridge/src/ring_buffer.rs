@@ -0,0 +1,384 @@
+//! SPSC ring buffer for JS ↔ WASM messaging
+//!
+//! Lock-free single-producer/single-consumer queue of length-prefixed
+//! messages living inside a bridge buffer. Head and tail are u32 words
+//! updated with acquire/release atomics, matching `Atomics.load`/`store` on
+//! the JS side (see spsc-ring-buffer.js), so an audio worklet and the main
+//! thread can exchange events over SharedArrayBuffer without postMessage.
+//!
+//! Region Layout:
+//! - Bytes 0-3:   head (u32) - read index, written only by the consumer
+//! - Bytes 4-7:   tail (u32) - write index, written only by the producer
+//! - Bytes 8-11:  capacity (u32) - data bytes, power of two
+//! - Bytes 12-15: message count (u32) - incremented by producer, decremented by consumer
+//! - Bytes 16..:  data (capacity bytes)
+//!
+//! Indices grow monotonically and wrap at u32::MAX; the data position is
+//! `index & (capacity - 1)`. Each message is a u32 length followed by its
+//! payload, both written with wrap-around.
+//!
+//! The region is writable from JS, so indices and length prefixes are not
+//! trusted: a ring whose queued bytes exceed its capacity, or whose next
+//! length prefix runs past the queued bytes, is treated as corrupt and
+//! neither accepts nor yields messages.
+
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::wait::{self, timeout_from_ms};
+use crate::BridgeContext;
+
+/// Size of the ring header in bytes
+pub const RING_HEADER_SIZE: usize = 16;
+
+/// Size of the per-message length prefix in bytes
+pub const MESSAGE_PREFIX_SIZE: usize = 4;
+
+/// Smallest supported data capacity in bytes
+pub const MIN_RING_CAPACITY: u32 = 8;
+
+const HEAD_OFFSET: usize = 0;
+const TAIL_OFFSET: usize = 4;
+const CAPACITY_OFFSET: usize = 8;
+const COUNT_OFFSET: usize = 12;
+
+/// View over a ring buffer region inside a bridge context
+pub struct RingBuffer<'a> {
+    ctx: &'a mut BridgeContext,
+    offset: usize,
+    capacity: u32,
+}
+
+impl<'a> RingBuffer<'a> {
+    /// Attaches to an initialized ring at `offset`
+    ///
+    /// Returns None if the header is out of bounds or the capacity is invalid.
+    pub fn attach(ctx: &'a mut BridgeContext, offset: usize) -> Option<Self> {
+        let capacity = ctx.atomic_u32(offset + CAPACITY_OFFSET)?.load(Ordering::Acquire);
+        if capacity < MIN_RING_CAPACITY || !capacity.is_power_of_two() {
+            return None;
+        }
+        ctx.range(offset, RING_HEADER_SIZE + capacity as usize)?;
+
+        Some(Self {
+            ctx,
+            offset,
+            capacity,
+        })
+    }
+
+    /// Data capacity in bytes
+    #[inline]
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Bytes currently queued, including length prefixes
+    pub fn used_bytes(&mut self) -> u32 {
+        let head = self.load(HEAD_OFFSET, Ordering::Acquire);
+        let tail = self.load(TAIL_OFFSET, Ordering::Acquire);
+        tail.wrapping_sub(head)
+    }
+
+    /// Messages currently queued
+    pub fn pending_messages(&mut self) -> u32 {
+        self.load(COUNT_OFFSET, Ordering::Acquire)
+    }
+
+    /// Bytes available for new messages, including length prefixes
+    ///
+    /// 0 if the ring is corrupt.
+    pub fn free_bytes(&mut self) -> u32 {
+        self.capacity.saturating_sub(self.used_bytes())
+    }
+
+    /// Returns true if no message is queued
+    pub fn is_empty(&mut self) -> bool {
+        self.used_bytes() == 0
+    }
+
+    /// Enqueues a message (producer side)
+    ///
+    /// # Returns
+    /// false if the ring does not have room for the message
+    pub fn push_message(&mut self, payload: &[u8]) -> bool {
+        let needed = match u32::try_from(MESSAGE_PREFIX_SIZE + payload.len()) {
+            Ok(needed) => needed,
+            Err(_) => return false,
+        };
+
+        let head = self.load(HEAD_OFFSET, Ordering::Acquire);
+        let tail = self.load(TAIL_OFFSET, Ordering::Relaxed);
+        match self.capacity.checked_sub(tail.wrapping_sub(head)) {
+            Some(free) if needed <= free => {}
+            _ => return false,
+        }
+
+        self.write_wrapped(tail, &(payload.len() as u32).to_le_bytes());
+        self.write_wrapped(tail.wrapping_add(MESSAGE_PREFIX_SIZE as u32), payload);
+        self.store(TAIL_OFFSET, tail.wrapping_add(needed));
+        self.adjust_count(true);
+        if let Some(word) = self.ctx.atomic_u32(self.offset + TAIL_OFFSET) {
+            wait::notify(word, u32::MAX);
+        }
+        true
+    }
+
+    /// Blocks until a message is queued or `timeout` elapses (consumer side)
+    ///
+    /// Sleeps on the tail word, which producers notify after each push.
+    ///
+    /// # Returns
+    /// true if a message is available
+    pub fn wait_for_message(&mut self, timeout: Option<Duration>) -> bool {
+        let tail = self.load(TAIL_OFFSET, Ordering::Acquire);
+        if self.load(HEAD_OFFSET, Ordering::Relaxed) != tail {
+            return true;
+        }
+        if let Some(word) = self.ctx.atomic_u32(self.offset + TAIL_OFFSET) {
+            wait::wait(word, tail, timeout);
+        }
+        !self.is_empty()
+    }
+
+    /// Dequeues the oldest message (consumer side)
+    ///
+    /// Returns None if the ring is empty or corrupt; a corrupt message is
+    /// left in place.
+    pub fn pop_message(&mut self) -> Option<Vec<u8>> {
+        let tail = self.load(TAIL_OFFSET, Ordering::Acquire);
+        let head = self.load(HEAD_OFFSET, Ordering::Relaxed);
+        let used = tail.wrapping_sub(head);
+        if used == 0 || used > self.capacity || (used as usize) < MESSAGE_PREFIX_SIZE {
+            return None;
+        }
+
+        let mut prefix = [0u8; MESSAGE_PREFIX_SIZE];
+        self.read_wrapped(head, &mut prefix);
+        let len = u32::from_le_bytes(prefix) as usize;
+        if len > used as usize - MESSAGE_PREFIX_SIZE {
+            return None;
+        }
+
+        let mut payload = vec![0u8; len];
+        self.read_wrapped(head.wrapping_add(MESSAGE_PREFIX_SIZE as u32), &mut payload);
+        self.store(
+            HEAD_OFFSET,
+            head.wrapping_add((MESSAGE_PREFIX_SIZE + len) as u32),
+        );
+        self.adjust_count(false);
+        Some(payload)
+    }
+
+    fn load(&mut self, field: usize, order: Ordering) -> u32 {
+        // Header bounds were validated in `attach`
+        self.ctx
+            .atomic_u32(self.offset + field)
+            .map_or(0, |word| word.load(order))
+    }
+
+    fn store(&mut self, field: usize, value: u32) {
+        if let Some(word) = self.ctx.atomic_u32(self.offset + field) {
+            word.store(value, Ordering::Release);
+        }
+    }
+
+    /// Adjusts the shared message count (fetch_add/fetch_sub, both sides write it)
+    fn adjust_count(&mut self, increment: bool) {
+        if let Some(word) = self.ctx.atomic_u32(self.offset + COUNT_OFFSET) {
+            if increment {
+                word.fetch_add(1, Ordering::AcqRel);
+            } else {
+                word.fetch_sub(1, Ordering::AcqRel);
+            }
+        }
+    }
+
+    fn data_range(&mut self, pos: usize, len: usize) -> &mut [u8] {
+        let start = self.offset + RING_HEADER_SIZE + pos;
+        // Data bounds were validated in `attach`
+        self.ctx.range_mut(start, len).unwrap_or_default()
+    }
+
+    fn write_wrapped(&mut self, index: u32, data: &[u8]) {
+        let pos = (index & (self.capacity - 1)) as usize;
+        let first = data.len().min(self.capacity as usize - pos);
+        self.data_range(pos, first).copy_from_slice(&data[..first]);
+        self.data_range(0, data.len() - first)
+            .copy_from_slice(&data[first..]);
+    }
+
+    fn read_wrapped(&mut self, index: u32, out: &mut [u8]) {
+        let pos = (index & (self.capacity - 1)) as usize;
+        let first = out.len().min(self.capacity as usize - pos);
+        out[..first].copy_from_slice(self.data_range(pos, first));
+        let rest = out.len() - first;
+        out[first..].copy_from_slice(self.data_range(0, rest));
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BridgeContext {
+    /// Allocates and initializes a ring buffer with `capacity` data bytes
+    ///
+    /// # Returns
+    /// Offset of the ring region, or -1 if capacity is not a power of two
+    /// (minimum 8) or the buffer is full
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = createRingBuffer))]
+    pub fn create_ring_buffer(&mut self, capacity: u32) -> i32 {
+        if capacity < MIN_RING_CAPACITY || !capacity.is_power_of_two() {
+            return -1;
+        }
+
+        let offset = self.allocate_as(RING_HEADER_SIZE + capacity as usize, "ring-buffer");
+        if offset < 0 {
+            return -1;
+        }
+
+        let base = offset as usize;
+        for (field, value) in [
+            (HEAD_OFFSET, 0),
+            (TAIL_OFFSET, 0),
+            (CAPACITY_OFFSET, capacity),
+            (COUNT_OFFSET, 0),
+        ] {
+            if let Some(word) = self.atomic_u32(base + field) {
+                word.store(value, Ordering::Release);
+            }
+        }
+        offset
+    }
+
+    /// Enqueues a message on the ring at `ring_offset`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = pushMessage))]
+    pub fn push_message(&mut self, ring_offset: usize, payload: &[u8]) -> bool {
+        self.ring_buffer(ring_offset)
+            .is_some_and(|mut ring| ring.push_message(payload))
+    }
+
+    /// Dequeues the oldest message from the ring at `ring_offset`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = popMessage))]
+    pub fn pop_message(&mut self, ring_offset: usize) -> Option<Vec<u8>> {
+        self.ring_buffer(ring_offset)?.pop_message()
+    }
+
+    /// Blocks a worker until the ring at `ring_offset` has a message
+    ///
+    /// # Arguments
+    /// * `timeout_ms` - Milliseconds to wait; negative or Infinity waits forever
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = waitForMessage))]
+    pub fn wait_for_message(&mut self, ring_offset: usize, timeout_ms: f64) -> bool {
+        let timeout = timeout_from_ms(timeout_ms);
+        self.ring_buffer(ring_offset)
+            .is_some_and(|mut ring| ring.wait_for_message(timeout))
+    }
+
+    /// Messages queued on the ring at `ring_offset`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = ringPendingMessages))]
+    pub fn ring_pending_messages(&mut self, ring_offset: usize) -> u32 {
+        self.ring_buffer(ring_offset)
+            .map_or(0, |mut ring| ring.pending_messages())
+    }
+
+    /// Bytes queued on the ring at `ring_offset`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = ringUsedBytes))]
+    pub fn ring_used_bytes(&mut self, ring_offset: usize) -> u32 {
+        self.ring_buffer(ring_offset)
+            .map_or(0, |mut ring| ring.used_bytes())
+    }
+}
+
+impl BridgeContext {
+    /// Attaches a view to the ring at `ring_offset`
+    pub fn ring_buffer(&mut self, ring_offset: usize) -> Option<RingBuffer<'_>> {
+        RingBuffer::attach(self, ring_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_pop_fifo() {
+        let mut ctx = BridgeContext::new(256);
+        let ring = ctx.create_ring_buffer(64) as usize;
+
+        assert!(ctx.push_message(ring, b"note-on"));
+        assert!(ctx.push_message(ring, b"note-off"));
+        assert_eq!(ctx.ring_pending_messages(ring), 2);
+
+        assert_eq!(ctx.pop_message(ring).as_deref(), Some(&b"note-on"[..]));
+        assert_eq!(ctx.pop_message(ring).as_deref(), Some(&b"note-off"[..]));
+        assert_eq!(ctx.pop_message(ring), None);
+        assert_eq!(ctx.ring_pending_messages(ring), 0);
+    }
+
+    #[test]
+    fn test_wait_for_message() {
+        let mut ctx = BridgeContext::new(256);
+        let ring = ctx.create_ring_buffer(64) as usize;
+
+        assert!(!ctx.wait_for_message(ring, 1.0));
+        ctx.push_message(ring, b"tick");
+        assert!(ctx.wait_for_message(ring, -1.0));
+    }
+
+    #[test]
+    fn test_full_ring_rejects() {
+        let mut ctx = BridgeContext::new(128);
+        let ring = ctx.create_ring_buffer(16) as usize;
+
+        assert!(ctx.push_message(ring, &[1; 8]));
+        assert!(!ctx.push_message(ring, &[2; 8]));
+        assert_eq!(ctx.ring_used_bytes(ring), 12);
+
+        ctx.pop_message(ring);
+        assert!(ctx.push_message(ring, &[2; 8]));
+    }
+
+    #[test]
+    fn test_wraparound() {
+        let mut ctx = BridgeContext::new(128);
+        let ring = ctx.create_ring_buffer(16) as usize;
+
+        for i in 0..20u8 {
+            let msg = [i, i.wrapping_mul(3), i.wrapping_add(7)];
+            assert!(ctx.push_message(ring, &msg));
+            assert_eq!(ctx.pop_message(ring).as_deref(), Some(&msg[..]));
+        }
+        assert!(ctx.ring_buffer(ring).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_rings() {
+        let mut ctx = BridgeContext::new(128);
+        assert_eq!(ctx.create_ring_buffer(12), -1);
+        assert_eq!(ctx.create_ring_buffer(4), -1);
+        assert_eq!(ctx.create_ring_buffer(256), -1);
+
+        // Uninitialized region has capacity 0
+        assert!(ctx.ring_buffer(64).is_none());
+        assert!(!ctx.push_message(64, b"x"));
+    }
+
+    #[test]
+    fn test_corrupt_rings() {
+        let mut ctx = BridgeContext::new(128);
+        let ring = ctx.create_ring_buffer(16) as usize;
+
+        // Length prefix claims more bytes than are queued
+        assert!(ctx.push_message(ring, b"abc"));
+        assert!(ctx.write(ring + RING_HEADER_SIZE, &u32::MAX.to_le_bytes()));
+        assert_eq!(ctx.pop_message(ring), None);
+        assert_eq!(ctx.ring_pending_messages(ring), 1);
+
+        // Tail more than a capacity ahead of head
+        assert!(ctx.write(ring + TAIL_OFFSET, &64u32.to_le_bytes()));
+        assert_eq!(ctx.ring_buffer(ring).unwrap().free_bytes(), 0);
+        assert!(!ctx.push_message(ring, b"x"));
+        assert_eq!(ctx.pop_message(ring), None);
+    }
+}
@@ -0,0 +1,381 @@
+//! Structured bridge message schema
+//!
+//! Each message kind is declared once with `bridge_message!`, which generates
+//! the struct, its fixed little-endian layout, and encode/decode functions.
+//! Field offsets follow declaration order with no padding. The JS mirror in
+//! message-schema.js declares the same layouts, so neither side hard-codes
+//! byte offsets.
+//!
+//! Message Layouts (bytes):
+//! - ParameterChange (7):  node_id u32 | param_id u32 | value f32 | ramp_ms f32
+//! - Transport (8):        command u32 | tempo f32 | position f64
+//! - GraphMutation (1):    op u32 | source u32 | target u32 | edge_type u32
+//! - TraversalRequest (3): request_id u32 | start_node u32 | max_depth u32 | edge_type_mask u32
+//! - TraversalComplete (9): request_id u32 | result_offset u32 | result_len u32 | status u32
+//! - ScheduledParameter (10): frame f64 | node_id u32 | param_id u32 | value f32
+//! - MidiEvent (11):       frame f64 | node_id u32 | status u32 | data1 u32 | data2 u32
+//! - NodeBypass (12):      frame f64 | node_id u32 | bypassed u32
+//! - GraphMix (13):        frame f64 | wet f32
+//!
+//! Type ids match `MessageType` in wasm-bridge.js.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-bridge
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::BridgeContext;
+
+/// Primitive field encodings supported by the schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    U32,
+    I32,
+    F32,
+    F64,
+}
+
+impl FieldKind {
+    /// Encoded width in bytes
+    pub const fn size(self) -> usize {
+        match self {
+            FieldKind::U32 | FieldKind::I32 | FieldKind::F32 => 4,
+            FieldKind::F64 => 8,
+        }
+    }
+}
+
+/// Scalar that can appear in a message layout
+pub trait SchemaField: Sized + Copy {
+    const KIND: FieldKind;
+
+    /// Writes the value into `out[..KIND.size()]`
+    fn write_le(self, out: &mut [u8]);
+
+    /// Reads the value from `bytes[..KIND.size()]`
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+macro_rules! schema_field {
+    ($ty:ty, $kind:expr) => {
+        impl SchemaField for $ty {
+            const KIND: FieldKind = $kind;
+
+            fn write_le(self, out: &mut [u8]) {
+                out[..Self::KIND.size()].copy_from_slice(&self.to_le_bytes());
+            }
+
+            fn read_le(bytes: &[u8]) -> Self {
+                let mut raw = [0u8; std::mem::size_of::<$ty>()];
+                raw.copy_from_slice(&bytes[..Self::KIND.size()]);
+                <$ty>::from_le_bytes(raw)
+            }
+        }
+    };
+}
+
+schema_field!(u32, FieldKind::U32);
+schema_field!(i32, FieldKind::I32);
+schema_field!(f32, FieldKind::F32);
+schema_field!(f64, FieldKind::F64);
+
+/// Message with a fixed binary layout
+pub trait BridgeMessage: Sized {
+    /// Message type id carried in `MessageHeader::msg_type`
+    const MSG_TYPE: u32;
+    /// Name registered with the dispatcher
+    const NAME: &'static str;
+    /// Field names and encodings in layout order
+    const FIELDS: &'static [(&'static str, FieldKind)];
+    /// Encoded size in bytes
+    const SIZE: usize;
+
+    /// Encodes into the first `SIZE` bytes of `out`
+    ///
+    /// # Returns
+    /// false if `out` is shorter than `SIZE`
+    fn encode_into(&self, out: &mut [u8]) -> bool;
+
+    /// Decodes from at least `SIZE` bytes
+    fn decode(bytes: &[u8]) -> Option<Self>;
+
+    /// Encodes into a new buffer
+    fn encode(&self) -> Vec<u8> {
+        let mut out = vec![0u8; Self::SIZE];
+        self.encode_into(&mut out);
+        out
+    }
+}
+
+/// Declares a bridge message and generates its layout and codec
+macro_rules! bridge_message {
+    (
+        $(#[$meta:meta])*
+        $name:ident = $msg_type:expr, $type_name:literal {
+            $($(#[$field_meta:meta])* $field:ident: $ty:ty),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Default)]
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: $ty,)+
+        }
+
+        impl BridgeMessage for $name {
+            const MSG_TYPE: u32 = $msg_type;
+            const NAME: &'static str = $type_name;
+            const FIELDS: &'static [(&'static str, FieldKind)] =
+                &[$((stringify!($field), <$ty as SchemaField>::KIND),)+];
+            const SIZE: usize = 0 $(+ <$ty as SchemaField>::KIND.size())+;
+
+            fn encode_into(&self, out: &mut [u8]) -> bool {
+                if out.len() < Self::SIZE {
+                    return false;
+                }
+                let mut cursor = 0;
+                $(
+                    self.$field.write_le(&mut out[cursor..]);
+                    cursor += <$ty as SchemaField>::KIND.size();
+                )+
+                debug_assert_eq!(cursor, Self::SIZE);
+                true
+            }
+
+            fn decode(bytes: &[u8]) -> Option<Self> {
+                if bytes.len() < Self::SIZE {
+                    return None;
+                }
+                let mut cursor = 0;
+                $(
+                    let $field = <$ty as SchemaField>::read_le(&bytes[cursor..]);
+                    cursor += <$ty as SchemaField>::KIND.size();
+                )+
+                debug_assert_eq!(cursor, Self::SIZE);
+                Some(Self { $($field,)+ })
+            }
+        }
+    };
+}
+
+bridge_message! {
+    /// Sets an audio node parameter, optionally ramping over `ramp_ms`
+    ParameterChange = 7, "parameter-change" {
+        node_id: u32,
+        param_id: u32,
+        value: f32,
+        ramp_ms: f32,
+    }
+}
+
+bridge_message! {
+    /// Transport control (see `TransportCommand`)
+    Transport = 8, "transport" {
+        command: u32,
+        tempo: f32,
+        /// Playhead position in seconds
+        position: f64,
+    }
+}
+
+bridge_message! {
+    /// Structural change to the graph (see `GraphMutationOp`)
+    GraphMutation = 1, "graph-mutation" {
+        op: u32,
+        source: u32,
+        target: u32,
+        edge_type: u32,
+    }
+}
+
+bridge_message! {
+    /// Asks the edge executor to traverse from `start_node`
+    TraversalRequest = 3, "traversal-request" {
+        request_id: u32,
+        start_node: u32,
+        max_depth: u32,
+        /// Bit per edge type to follow; 0 follows every type
+        edge_type_mask: u32,
+    }
+}
+
+bridge_message! {
+    /// Reports where a traversal result was written (see `TraversalStatus`)
+    TraversalComplete = 9, "traversal-complete" {
+        request_id: u32,
+        /// Bridge offset of the binary TraversalResult; free it once read
+        result_offset: u32,
+        result_len: u32,
+        status: u32,
+    }
+}
+
+bridge_message! {
+    /// Sets an audio node parameter at an exact sample frame
+    ScheduledParameter = 10, "scheduled-parameter" {
+        /// Audio frame the change lands on (integer-valued)
+        frame: f64,
+        node_id: u32,
+        param_id: u32,
+        value: f32,
+    }
+}
+
+bridge_message! {
+    /// MIDI channel message for an audio node at an exact sample frame
+    MidiEvent = 11, "midi-event" {
+        /// Audio frame the event lands on (integer-valued)
+        frame: f64,
+        node_id: u32,
+        status: u32,
+        data1: u32,
+        data2: u32,
+    }
+}
+
+bridge_message! {
+    /// Bypasses an audio node (or brings it back) at an exact sample frame
+    NodeBypass = 12, "node-bypass" {
+        /// Audio frame the change lands on (integer-valued)
+        frame: f64,
+        node_id: u32,
+        /// 1 to bypass, 0 to process
+        bypassed: u32,
+    }
+}
+
+bridge_message! {
+    /// Sets the dry/wet level of an audio graph at an exact sample frame
+    GraphMix = 13, "graph-mix" {
+        /// Audio frame the change lands on (integer-valued)
+        frame: f64,
+        /// 0 = dry input only, 1 = processed only
+        wet: f32,
+    }
+}
+
+/// Values of `Transport::command`
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportCommand {
+    Stop = 0,
+    Play = 1,
+    Pause = 2,
+    Seek = 3,
+}
+
+/// Values of `TraversalComplete::status`
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraversalStatus {
+    Ok = 0,
+    /// The result did not fit in the bridge buffer
+    OutOfMemory = 1,
+}
+
+/// Values of `GraphMutation::op`
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphMutationOp {
+    AddNode = 0,
+    RemoveNode = 1,
+    Connect = 2,
+    Disconnect = 3,
+}
+
+impl BridgeContext {
+    /// Registers a schema message type with the dispatcher
+    pub fn register_schema<M: BridgeMessage>(&mut self) -> bool {
+        self.register_message_type(M::MSG_TYPE, M::NAME)
+    }
+
+    /// Encodes and sends a schema message on a channel
+    ///
+    /// # Returns
+    /// Same codes as `send`
+    pub fn send_message<M: BridgeMessage>(&mut self, channel: &str, message: &M) -> i64 {
+        self.send(channel, M::MSG_TYPE, &message.encode())
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BridgeContext {
+    /// Registers every built-in schema message type
+    ///
+    /// # Returns
+    /// false if any type id is already registered under another name
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = registerSchemaTypes))]
+    pub fn register_schema_types(&mut self) -> bool {
+        [
+            self.register_schema::<ParameterChange>(),
+            self.register_schema::<Transport>(),
+            self.register_schema::<GraphMutation>(),
+            self.register_schema::<TraversalRequest>(),
+            self.register_schema::<TraversalComplete>(),
+            self.register_schema::<ScheduledParameter>(),
+            self.register_schema::<MidiEvent>(),
+            self.register_schema::<NodeBypass>(),
+            self.register_schema::<GraphMix>(),
+        ]
+        .iter()
+        .all(|ok| *ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_sizes() {
+        assert_eq!(ParameterChange::SIZE, 16);
+        assert_eq!(Transport::SIZE, 16);
+        assert_eq!(GraphMutation::SIZE, 16);
+        assert_eq!(TraversalRequest::SIZE, 16);
+        assert_eq!(ScheduledParameter::SIZE, 20);
+        assert_eq!(MidiEvent::SIZE, 24);
+        assert_eq!(NodeBypass::SIZE, 16);
+        assert_eq!(GraphMix::SIZE, 12);
+        assert_eq!(
+            Transport::FIELDS,
+            &[
+                ("command", FieldKind::U32),
+                ("tempo", FieldKind::F32),
+                ("position", FieldKind::F64),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let message = Transport {
+            command: TransportCommand::Seek as u32,
+            tempo: 120.0,
+            position: 12.5,
+        };
+        let bytes = message.encode();
+        assert_eq!(&bytes[0..4], &3u32.to_le_bytes());
+        assert_eq!(&bytes[8..16], &12.5f64.to_le_bytes());
+        assert_eq!(Transport::decode(&bytes), Some(message));
+        assert_eq!(Transport::decode(&bytes[..15]), None);
+        assert!(!message.encode_into(&mut [0u8; 8]));
+    }
+
+    #[test]
+    fn test_send_schema_message() {
+        let mut ctx = BridgeContext::new(1024);
+        ctx.open_channel("control", 256);
+        assert!(ctx.register_schema_types());
+
+        let change = ParameterChange {
+            node_id: 4,
+            param_id: 1,
+            value: 0.5,
+            ramp_ms: 10.0,
+        };
+        assert!(ctx.send_message("control", &change) >= 0);
+
+        let (header, payload) = ctx.receive("control").unwrap();
+        assert_eq!(header.msg_type, ParameterChange::MSG_TYPE);
+        assert_eq!(ParameterChange::decode(&payload), Some(change));
+    }
+}
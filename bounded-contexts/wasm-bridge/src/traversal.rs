@@ -0,0 +1,162 @@
+//! Edge-executor traversals over the bridge
+//!
+//! A `TraversalRequest` message names a start node, depth and edge-type
+//! mask. The bridge runs it on a `WASMEdgeExecutor`, writes the binary
+//! TraversalResult (see wasm-edge-executor/src/executor.rs) straight into a
+//! freshly allocated bridge region, and replies with a `TraversalComplete`
+//! carrying the region's offset and length. The receiver reads the result in
+//! place and frees the region; no JSON is produced on either side.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-bridge
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+use wasm_edge_executor::WASMEdgeExecutor;
+
+use crate::schema::{BridgeMessage, TraversalComplete, TraversalRequest, TraversalStatus};
+use crate::BridgeContext;
+
+impl BridgeContext {
+    /// Runs a traversal and writes its binary result into a new allocation
+    ///
+    /// # Returns
+    /// Completion message describing the result region
+    pub fn run_traversal(
+        &mut self,
+        executor: &WASMEdgeExecutor,
+        request: &TraversalRequest,
+    ) -> TraversalComplete {
+        let result = executor.traverse_bfs(
+            request.start_node,
+            request.max_depth,
+            request.edge_type_mask,
+        );
+        let len = result.encoded_len();
+
+        let mut complete = TraversalComplete {
+            request_id: request.request_id,
+            status: TraversalStatus::OutOfMemory as u32,
+            ..TraversalComplete::default()
+        };
+
+        let offset = self.allocate_as(len, "traversal-result");
+        if offset < 0 {
+            return complete;
+        }
+        let written = self
+            .range_mut(offset as usize, len)
+            .is_some_and(|region| result.write_to(region).is_ok());
+        if !written {
+            self.free(offset as usize);
+            return complete;
+        }
+
+        complete.result_offset = offset as u32;
+        complete.result_len = len as u32;
+        complete.status = TraversalStatus::Ok as u32;
+        complete
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BridgeContext {
+    /// Answers every pending traversal request on a channel
+    ///
+    /// Drains `requests`, runs each `TraversalRequest` on `executor`, and
+    /// sends a `TraversalComplete` per request on `replies`. Messages of other
+    /// types on the request channel are discarded. Both message types must be
+    /// registered (see `registerSchemaTypes`).
+    ///
+    /// # Returns
+    /// Number of requests answered
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = serveTraversals))]
+    pub fn serve_traversals(
+        &mut self,
+        executor: &WASMEdgeExecutor,
+        requests: &str,
+        replies: &str,
+    ) -> u32 {
+        let mut answered = 0;
+        while let Some((header, payload)) = self.receive(requests) {
+            if header.msg_type != TraversalRequest::MSG_TYPE {
+                continue;
+            }
+            let Some(request) = TraversalRequest::decode(&payload) else {
+                continue;
+            };
+
+            let complete = self.run_traversal(executor, &request);
+            if self.send_message(replies, &complete) < 0 {
+                // Nobody will learn about the region, so reclaim it
+                if complete.status == TraversalStatus::Ok as u32 {
+                    self.free(complete.result_offset as usize);
+                }
+                continue;
+            }
+            answered += 1;
+        }
+        answered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_edge_executor::TraversalResult;
+
+    fn executor() -> WASMEdgeExecutor {
+        let mut executor = WASMEdgeExecutor::new();
+        executor.add_edge(1, 2, 0);
+        executor.add_edge(2, 3, 0);
+        executor.add_edge(1, 4, 1);
+        executor
+    }
+
+    #[test]
+    fn test_request_reply_round_trip() {
+        let mut ctx = BridgeContext::new(2048);
+        ctx.open_channel("traversal-requests", 256);
+        ctx.open_channel("traversal-replies", 256);
+        assert!(ctx.register_schema_types());
+
+        let request = TraversalRequest {
+            request_id: 42,
+            start_node: 1,
+            max_depth: 5,
+            edge_type_mask: 1 << 0,
+        };
+        ctx.send_message("traversal-requests", &request);
+
+        let executor = executor();
+        assert_eq!(ctx.serve_traversals(&executor, "traversal-requests", "traversal-replies"), 1);
+
+        let (header, payload) = ctx.receive("traversal-replies").unwrap();
+        assert_eq!(header.msg_type, TraversalComplete::MSG_TYPE);
+        let complete = TraversalComplete::decode(&payload).unwrap();
+        assert_eq!(complete.request_id, 42);
+        assert_eq!(complete.status, TraversalStatus::Ok as u32);
+
+        let bytes = ctx
+            .range(complete.result_offset as usize, complete.result_len as usize)
+            .unwrap();
+        let result = TraversalResult::from_bytes(bytes).unwrap();
+        assert_eq!(result, executor.traverse_bfs(1, 5, 1));
+        assert_eq!(result.nodes, vec![1, 2, 3]);
+
+        assert!(ctx.free(complete.result_offset as usize));
+    }
+
+    #[test]
+    fn test_out_of_memory_reports_status() {
+        let mut ctx = BridgeContext::new(16);
+        let request = TraversalRequest {
+            request_id: 7,
+            start_node: 1,
+            max_depth: 5,
+            edge_type_mask: 0,
+        };
+        let complete = ctx.run_traversal(&executor(), &request);
+        assert_eq!(complete.status, TraversalStatus::OutOfMemory as u32);
+        assert_eq!(complete.result_len, 0);
+    }
+}
@@ -0,0 +1,282 @@
+//! Futex-style wait/notify on bridge words
+//!
+//! `wait` blocks while a u32 word still holds an expected value, and
+//! `notify` wakes threads blocked on that word, so worker-side Rust can
+//! sleep until a producer publishes instead of spin-polling. Result codes
+//! match `Atomics.wait` and `memory.atomic.wait32`.
+//!
+//! Backends:
+//! - wasm32 with the `atomics` target feature: `memory.atomic.wait32` /
+//!   `memory.atomic.notify`, interoperable with `Atomics.notify` from JS
+//! - wasm32 without threads: nothing else can change the word, so a wait
+//!   returns `NotEqual` or `TimedOut` immediately and notify wakes nobody
+//! - native: a process-wide parking table (mutex + condvar)
+//!
+//! Browsers forbid blocking waits on the main thread; call these from
+//! workers or audio-worklet helpers only.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-bridge
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::BridgeContext;
+
+/// Outcome of a wait
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitResult {
+    /// Woken by a notify
+    Ok = 0,
+    /// The word did not hold the expected value
+    NotEqual = 1,
+    /// The timeout elapsed
+    TimedOut = 2,
+    /// The offset is out of bounds or not 4-byte aligned
+    Invalid = 3,
+}
+
+/// Converts a JS-style millisecond timeout (negative or infinite = forever)
+pub fn timeout_from_ms(timeout_ms: f64) -> Option<Duration> {
+    (timeout_ms.is_finite() && timeout_ms >= 0.0).then(|| Duration::from_secs_f64(timeout_ms / 1000.0))
+}
+
+/// Blocks while `word` equals `expected`, up to `timeout` (None = forever)
+pub fn wait(word: &AtomicU32, expected: u32, timeout: Option<Duration>) -> WaitResult {
+    imp::wait(word, expected, timeout)
+}
+
+/// Wakes up to `count` threads waiting on `word`
+///
+/// # Returns
+/// Number of waiters woken
+pub fn notify(word: &AtomicU32, count: u32) -> u32 {
+    imp::notify(word, count)
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "atomics"))]
+mod imp {
+    use super::*;
+    use core::arch::wasm32;
+
+    pub fn wait(word: &AtomicU32, expected: u32, timeout: Option<Duration>) -> WaitResult {
+        let timeout_ns = timeout.map_or(-1, |t| t.as_nanos().min(i64::MAX as u128) as i64);
+        // SAFETY: the pointer comes from a live, aligned AtomicU32
+        let code = unsafe {
+            wasm32::memory_atomic_wait32(word.as_ptr() as *mut i32, expected as i32, timeout_ns)
+        };
+        match code {
+            0 => WaitResult::Ok,
+            1 => WaitResult::NotEqual,
+            _ => WaitResult::TimedOut,
+        }
+    }
+
+    pub fn notify(word: &AtomicU32, count: u32) -> u32 {
+        // SAFETY: the pointer comes from a live, aligned AtomicU32
+        unsafe { wasm32::memory_atomic_notify(word.as_ptr() as *mut i32, count) }
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+mod imp {
+    use super::*;
+
+    pub fn wait(word: &AtomicU32, expected: u32, _timeout: Option<Duration>) -> WaitResult {
+        if word.load(Ordering::SeqCst) != expected {
+            WaitResult::NotEqual
+        } else {
+            WaitResult::TimedOut
+        }
+    }
+
+    pub fn notify(_word: &AtomicU32, _count: u32) -> u32 {
+        0
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod imp {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::{Condvar, Mutex, MutexGuard};
+    use std::time::Instant;
+
+    /// Waiters parked on one address
+    struct Slot {
+        waiters: u32,
+        /// Wakeups granted by notify but not yet consumed
+        wakeups: u32,
+    }
+
+    static SLOTS: Mutex<BTreeMap<usize, Slot>> = Mutex::new(BTreeMap::new());
+    static WAKE: Condvar = Condvar::new();
+
+    fn slots() -> MutexGuard<'static, BTreeMap<usize, Slot>> {
+        SLOTS.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn leave(slots: &mut BTreeMap<usize, Slot>, addr: usize) {
+        if let Some(slot) = slots.get_mut(&addr) {
+            slot.waiters -= 1;
+            if slot.waiters == 0 {
+                slots.remove(&addr);
+            }
+        }
+    }
+
+    pub fn wait(word: &AtomicU32, expected: u32, timeout: Option<Duration>) -> WaitResult {
+        let addr = word.as_ptr() as usize;
+        let deadline = timeout.map(|t| Instant::now() + t);
+
+        // The value check happens under the lock notify takes, so a store +
+        // notify cannot slip in between the check and parking
+        let mut guard = slots();
+        if word.load(Ordering::SeqCst) != expected {
+            return WaitResult::NotEqual;
+        }
+        guard
+            .entry(addr)
+            .or_insert(Slot { waiters: 0, wakeups: 0 })
+            .waiters += 1;
+
+        loop {
+            if let Some(slot) = guard.get_mut(&addr) {
+                if slot.wakeups > 0 {
+                    slot.wakeups -= 1;
+                    leave(&mut guard, addr);
+                    return WaitResult::Ok;
+                }
+            }
+
+            guard = match deadline {
+                None => WAKE.wait(guard).unwrap_or_else(|p| p.into_inner()),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        leave(&mut guard, addr);
+                        return WaitResult::TimedOut;
+                    }
+                    WAKE.wait_timeout(guard, deadline - now)
+                        .unwrap_or_else(|p| p.into_inner())
+                        .0
+                }
+            };
+        }
+    }
+
+    pub fn notify(word: &AtomicU32, count: u32) -> u32 {
+        let addr = word.as_ptr() as usize;
+        let mut guard = slots();
+        let Some(slot) = guard.get_mut(&addr) else {
+            return 0;
+        };
+
+        let woken = count.min(slot.waiters - slot.wakeups);
+        slot.wakeups += woken;
+        drop(guard);
+        if woken > 0 {
+            WAKE.notify_all();
+        }
+        woken
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BridgeContext {
+    /// Blocks while the u32 at `offset` equals `expected`
+    ///
+    /// # Arguments
+    /// * `offset` - 4-byte aligned offset of the word
+    /// * `expected` - Value to sleep on
+    /// * `timeout_ms` - Milliseconds to wait; negative or Infinity waits forever
+    ///
+    /// # Returns
+    /// A `WaitResult` code: 0 woken, 1 not equal, 2 timed out, 3 invalid offset
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = waitOn))]
+    pub fn wait_on(&mut self, offset: usize, expected: u32, timeout_ms: f64) -> u32 {
+        let timeout = timeout_from_ms(timeout_ms);
+        let result = match self.atomic_u32(offset) {
+            Some(word) => wait(word, expected, timeout),
+            None => WaitResult::Invalid,
+        };
+        result as u32
+    }
+
+    /// Wakes up to `count` waiters blocked on the u32 at `offset`
+    ///
+    /// # Returns
+    /// Number of waiters woken
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn notify(&mut self, offset: usize, count: u32) -> u32 {
+        self.atomic_u32(offset).map_or(0, |word| notify(word, count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_wait_not_equal_and_timeout() {
+        let mut ctx = BridgeContext::new(64);
+        assert!(ctx.write(8, &5u32.to_le_bytes()));
+        assert_eq!(ctx.wait_on(8, 4, 10.0), WaitResult::NotEqual as u32);
+        assert_eq!(ctx.wait_on(8, 5, 1.0), WaitResult::TimedOut as u32);
+        assert_eq!(ctx.wait_on(6, 0, 1.0), WaitResult::Invalid as u32);
+        assert_eq!(ctx.notify(8, 1), 0);
+    }
+
+    #[test]
+    fn test_notify_wakes_waiter() {
+        let word = Arc::new(AtomicU32::new(0));
+        let waiter = {
+            let word = word.clone();
+            thread::spawn(move || wait(&word, 0, Some(Duration::from_secs(5))))
+        };
+
+        // Keep notifying until the waiter has parked and been woken
+        word.store(1, Ordering::SeqCst);
+        let mut woken = 0;
+        while woken == 0 && !waiter.is_finished() {
+            woken = notify(&word, 1);
+            thread::yield_now();
+        }
+        let result = waiter.join().unwrap();
+        assert!(matches!(result, WaitResult::Ok | WaitResult::NotEqual));
+    }
+
+    #[test]
+    fn test_notify_count_is_bounded() {
+        let word = Arc::new(AtomicU32::new(0));
+        let waiters: Vec<_> = (0..3)
+            .map(|_| {
+                let word = word.clone();
+                thread::spawn(move || wait(&word, 0, Some(Duration::from_millis(500))))
+            })
+            .collect();
+
+        // Wake exactly one of the parked threads
+        let mut woken = 0;
+        while woken == 0 {
+            woken = notify(&word, 1);
+            thread::yield_now();
+        }
+        assert_eq!(woken, 1);
+
+        let results: Vec<WaitResult> = waiters.into_iter().map(|w| w.join().unwrap()).collect();
+        assert_eq!(results.iter().filter(|r| **r == WaitResult::Ok).count(), 1);
+        assert_eq!(results.iter().filter(|r| **r == WaitResult::TimedOut).count(), 2);
+    }
+
+    #[test]
+    fn test_timeout_conversion() {
+        assert_eq!(timeout_from_ms(-1.0), None);
+        assert_eq!(timeout_from_ms(f64::INFINITY), None);
+        assert_eq!(timeout_from_ms(250.0), Some(Duration::from_millis(250)));
+    }
+}
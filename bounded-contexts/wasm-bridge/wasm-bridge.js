@@ -8,6 +8,8 @@
  * @see {@link file://./DESIGN_SYSTEM.md#wasm-bridge WASMBridge Documentation}
  */
 
+import { MessageHeaderLayout } from './message-schema.js';
+
 /**
  * Message types for structured communication
  * @enum {number}
@@ -19,6 +21,13 @@ export const MessageType = {
   PROPS_UPDATE: 4,
   RESULT: 5,
   ERROR: 6,
+  PARAMETER_CHANGE: 7,
+  TRANSPORT: 8,
+  TRAVERSAL_COMPLETE: 9,
+  SCHEDULED_PARAMETER: 10,
+  MIDI_EVENT: 11,
+  NODE_BYPASS: 12,
+  GRAPH_MIX: 13,
 };
 
 /**
@@ -44,6 +53,7 @@ export class WASMBridge {
     this.bufferSize = bufferSize;
     this.sequence = 0;
     this.messageHandlers = new Map();
+    this.debugAllocations = false;
     
     // Initialize shared buffer
     this.sharedBufferPtr = this.wasm.init_shared_buffer(bufferSize);
@@ -54,7 +64,7 @@ export class WASMBridge {
     );
     
     // Message header size (4 u32 fields = 16 bytes)
-    this.headerSize = 16;
+    this.headerSize = MessageHeaderLayout.size;
     
     console.log(`[WASMBridge] Initialized with ${bufferSize} byte buffer`);
   }
@@ -106,7 +116,7 @@ export class WASMBridge {
     
     // Allocate space for header + payload
     const totalSize = this.headerSize + payload.length;
-    const offset = this.wasm.allocate_in_shared_buffer(totalSize);
+    const offset = this.wasm.allocate_tagged_in_shared_buffer(totalSize, 'message', undefined);
     
     if (offset < 0) {
       console.error('[WASMBridge] Failed to allocate buffer space');
@@ -140,18 +150,9 @@ export class WASMBridge {
       return null;
     }
     
-    const view = new DataView(
-      this.memory.buffer,
-      this.sharedBufferPtr + offset,
-      this.headerSize
+    return MessageHeaderLayout.decode(
+      new Uint8Array(this.memory.buffer, this.sharedBufferPtr + offset, this.headerSize)
     );
-    
-    return {
-      msgType: view.getUint32(0, true),
-      payloadOffset: view.getUint32(4, true),
-      payloadLen: view.getUint32(8, true),
-      sequence: view.getUint32(12, true),
-    };
   }
   
   /**
@@ -198,14 +199,101 @@ export class WASMBridge {
    */
   getMemoryStats() {
     const stats = this.wasm.get_memory_stats();
+    const frag = this.wasm.get_fragmentation_stats();
     return {
       totalBytes: stats[0],
       usedBytes: stats[1],
       freeBytes: stats[2],
       usagePercent: ((stats[1] / stats[0]) * 100).toFixed(2),
+      highWaterMark: stats[3],
+      largestFreeBlock: frag[0],
+      freeBlockCount: frag[1],
+      allocationCount: frag[2],
     };
   }
   
+  /**
+   * Get the structured memory report: usage per allocation tag, high-water
+   * mark, and (in debug mode) every live allocation with its call site
+   * 
+   * @returns {Object} Parsed report
+   */
+  getMemoryReport() {
+    return JSON.parse(this.wasm.get_memory_report());
+  }
+  
+  /**
+   * Allocate a tagged region; in debug mode the caller's stack line is recorded
+   * 
+   * @param {number} size - Bytes to reserve
+   * @param {string} tag - Owning subsystem, shown in the memory report
+   * @returns {number} Offset, or -1 when the buffer is full
+   */
+  allocate(size, tag = 'untagged') {
+    const callSite = this.debugAllocations
+      ? new Error().stack?.split('\n')[2]?.trim()
+      : undefined;
+    return this.wasm.allocate_tagged_in_shared_buffer(size, tag, callSite);
+  }
+  
+  /**
+   * Enable or disable call-site recording for new allocations
+   * 
+   * @param {boolean} enabled
+   */
+  setAllocationDebug(enabled) {
+    this.debugAllocations = enabled;
+    this.wasm.set_allocation_debug(enabled);
+  }
+  
+  /**
+   * Mark the current point in the allocation sequence
+   * 
+   * @returns {number} Checkpoint for findLeaks()
+   */
+  leakCheckpoint() {
+    return this.wasm.get_leak_checkpoint();
+  }
+  
+  /**
+   * List allocations made since a checkpoint that are still live
+   * 
+   * @param {number} checkpoint - Value from leakCheckpoint()
+   * @returns {Array<{offset: number, size: number, tag: string, call_site: ?string, generation: number}>}
+   */
+  findLeaks(checkpoint) {
+    return JSON.parse(this.wasm.get_leaks_since(checkpoint));
+  }
+  
+  /**
+   * Free a region previously returned by the allocator
+   * 
+   * @param {number} offset - Offset returned by allocation
+   * @returns {boolean} True if the region was live and is now free
+   */
+  free(offset) {
+    return this.wasm.free_in_shared_buffer(offset);
+  }
+  
+  /**
+   * Open an allocation scope; everything allocated until the matching
+   * endScope() is freed together
+   * 
+   * @returns {number} New scope depth
+   */
+  beginScope() {
+    return this.wasm.begin_shared_buffer_scope();
+  }
+  
+  /**
+   * Close the innermost allocation scope
+   * 
+   * @returns {boolean} False if no scope was open
+   */
+  endScope() {
+    return this.wasm.end_shared_buffer_scope();
+  }
+  
   /**
    * Reset buffer allocator (for testing)
    */
@@ -191,4 +191,81 @@ export const EDGE_SIZE = 12;
  */
 export function calculateBufferSize(edgeCount) {
   return edgeCount * EDGE_SIZE;
-}
\ No newline at end of file
+}
+/**
+ * Decodes a binary traversal result in place (matches Rust TraversalResult)
+ *
+ * Layout: node count u32, edge count u32, (node u32, depth u32) per visited
+ * node, then edges in EdgeBinaryFormat. Works directly on a view of the
+ * bridge buffer, so results delivered by the bridge need no JSON step.
+ * Also reads `neighborhood` results, whose depths are distances from the
+ * focal node and whose edges are every edge between their nodes.
+ *
+ * @param {Uint8Array} bytes - Encoded traversal result
+ * @returns {{nodes: Uint32Array, depths: Uint32Array,
+ *   edges: Array<{source: number, target: number, edgeType: number}>}}
+ */
+export function decodeTraversalResult(bytes) {
+  const view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+  const nodeCount = view.getUint32(0, true);
+  const edgeCount = view.getUint32(4, true);
+
+  const nodes = new Uint32Array(nodeCount);
+  const depths = new Uint32Array(nodeCount);
+  let offset = 8;
+  for (let i = 0; i < nodeCount; i++, offset += 8) {
+    nodes[i] = view.getUint32(offset, true);
+    depths[i] = view.getUint32(offset + 4, true);
+  }
+
+  const edges = [];
+  for (let i = 0; i < edgeCount; i++, offset += EDGE_SIZE) {
+    edges.push({
+      source: view.getUint32(offset, true),
+      target: view.getUint32(offset + 4, true),
+      edgeType: view.getUint32(offset + 8, true),
+    });
+  }
+
+  return { nodes, depths, edges };
+}
+
+/**
+ * Route from a traversal's start node to `target`
+ *
+ * Node `i` (after the start) was discovered through `edges[i - 1]`, whose
+ * other end is its parent; the route follows parents back to the start.
+ * Matches Rust `TraversalResult::path_to`.
+ *
+ * @param {{nodes: ArrayLike<number>, edges: Array<{source: number, target: number}>}} result -
+ *   Parsed TraversalResult JSON or `decodeTraversalResult` output
+ * @param {number} target - Node ID
+ * @returns {{nodes: number[], edges: Array<Object>}|null} Nodes from the
+ *   start to `target` and the edges between them, each in its own
+ *   direction; null if `target` was not visited
+ */
+export function pathTo(result, target) {
+  const positions = new Map();
+  Array.from(result.nodes).forEach((node, position) => positions.set(node, position));
+  let position = positions.get(target);
+  if (position === undefined) {
+    return null;
+  }
+
+  const nodes = [target];
+  const edges = [];
+  while (position > 0) {
+    const node = result.nodes[position];
+    const edge = result.edges[position - 1];
+    const parent = edge?.target === node ? edge.source : edge?.source === node ? edge.target : undefined;
+    const parentPosition = positions.get(parent);
+    // Parents are discovered first; anything else would loop
+    if (parentPosition === undefined || parentPosition >= position) {
+      return null;
+    }
+    nodes.push(parent);
+    edges.push(edge);
+    position = parentPosition;
+  }
+  return { nodes: nodes.reverse(), edges: edges.reverse() };
+}
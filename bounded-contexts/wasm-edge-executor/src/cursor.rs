@@ -0,0 +1,200 @@
+//! Resumable breadth-first traversal
+//!
+//! A `TraversalCursor` runs the same traversal as `traverseBFS` a few nodes
+//! at a time, so a big traversal can be spread over several event loop
+//! turns (see batch-scheduler.js in wasm-bridge). Each `step` expands at
+//! most `budget` visited nodes against the executor as it is at that
+//! moment; the finished result matches `traverseBFS` on an executor left
+//! unchanged in between.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-edge-executor
+
+use std::collections::HashSet;
+
+use harmony_error::HarmonyError;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::executor::{edge_type_matches, TraversalResult, WASMEdgeExecutor};
+
+/// Breadth-first traversal run in steps
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct TraversalCursor {
+    max_depth: u32,
+    edge_type_mask: u32,
+    result: TraversalResult,
+    visited: HashSet<u32>,
+    // Nodes at `depth`, expanded up to `position`
+    frontier: Vec<u32>,
+    position: usize,
+    next: Vec<u32>,
+    depth: u32,
+    done: bool,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TraversalCursor {
+    /// Traversal from `start`, with the arguments of `traverseBFS`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(start: u32, max_depth: u32, edge_type_mask: u32) -> Self {
+        Self {
+            max_depth,
+            edge_type_mask,
+            result: TraversalResult {
+                nodes: vec![start],
+                depths: vec![0],
+                edges: Vec::new(),
+            },
+            visited: HashSet::from([start]),
+            frontier: vec![start],
+            position: 0,
+            next: Vec::new(),
+            depth: 0,
+            done: max_depth == 0,
+        }
+    }
+
+    /// Expands up to `budget` visited nodes
+    ///
+    /// # Returns
+    /// true once the traversal is finished
+    pub fn step(&mut self, executor: &WASMEdgeExecutor, budget: u32) -> bool {
+        let mut expanded = 0;
+        while !self.done && expanded < budget {
+            if self.position == self.frontier.len() {
+                self.next_level();
+                continue;
+            }
+            let node = self.frontier[self.position];
+            self.position += 1;
+            expanded += 1;
+            for edge in executor.outgoing_edges(node) {
+                if edge_type_matches(self.edge_type_mask, edge.edge_type())
+                    && self.visited.insert(edge.target())
+                {
+                    self.result.edges.push(edge);
+                    self.next.push(edge.target());
+                }
+            }
+        }
+        // A finished last level needs no budget to close
+        if !self.done && self.position == self.frontier.len() && self.next.is_empty() {
+            self.done = true;
+        }
+        self.done
+    }
+
+    /// Whether the traversal is finished
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    /// Nodes visited so far
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn visited(&self) -> usize {
+        self.result.nodes.len()
+    }
+
+    /// Depth being expanded
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// The traversal so far, as JSON TraversalResult
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = resultJSON))]
+    pub fn result_json(&self) -> Result<String, HarmonyError> {
+        Ok(serde_json::to_string(&self.result)?)
+    }
+
+    /// The traversal so far, in the binary result layout
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = resultBinary))]
+    pub fn result_binary(&self) -> Vec<u8> {
+        self.result.to_bytes()
+    }
+
+    /// Route from the start node to `target`, once visited
+    ///
+    /// # Returns
+    /// JSON TraversalPath, or undefined if `target` is not visited yet
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = pathTo))]
+    pub fn path_to_json(&self, target: u32) -> Result<Option<String>, HarmonyError> {
+        Ok(match self.result.path_to(target) {
+            Some(path) => Some(serde_json::to_string(&path)?),
+            None => None,
+        })
+    }
+}
+
+impl TraversalCursor {
+    /// The traversal so far
+    pub fn result(&self) -> &TraversalResult {
+        &self.result
+    }
+
+    /// The finished (or abandoned) traversal
+    pub fn into_result(self) -> TraversalResult {
+        self.result
+    }
+
+    fn next_level(&mut self) {
+        if self.next.is_empty() {
+            self.done = true;
+            return;
+        }
+        self.depth += 1;
+        self.frontier = std::mem::take(&mut self.next);
+        self.position = 0;
+        self.result.nodes.extend_from_slice(&self.frontier);
+        self.result
+            .depths
+            .resize(self.result.nodes.len(), self.depth);
+        if self.depth >= self.max_depth {
+            self.done = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_matches_traversal() {
+        let mut executor = WASMEdgeExecutor::new();
+        for (source, target, edge_type) in [
+            (0, 1, 0),
+            (0, 2, 1),
+            (1, 3, 0),
+            (2, 3, 0),
+            (3, 4, 0),
+            (4, 0, 0),
+        ] {
+            executor.add_edge(source, target, edge_type);
+        }
+        for (max_depth, mask) in [(0, 0), (1, 0), (2, 0b1), (10, 0)] {
+            let expected = executor.traverse_bfs(0, max_depth, mask);
+            let mut cursor = TraversalCursor::new(0, max_depth, mask);
+            let mut steps = 0;
+            while !cursor.step(&executor, 1) {
+                steps += 1;
+                assert!(steps < 10);
+            }
+            assert_eq!(cursor.result(), &expected);
+            assert_eq!(cursor.result_binary(), expected.to_bytes());
+        }
+
+        let mut cursor = TraversalCursor::new(0, 10, 0);
+        assert!(!cursor.step(&executor, 2));
+        assert_eq!((cursor.visited(), cursor.depth()), (3, 1));
+        assert_eq!(cursor.path_to_json(4).unwrap(), None);
+        assert!(cursor.step(&executor, 100));
+        let path: serde_json::Value =
+            serde_json::from_str(&cursor.path_to_json(4).unwrap().unwrap()).unwrap();
+        assert_eq!(path["nodes"], serde_json::json!([0, 1, 3, 4]));
+        assert_eq!(path["edges"][2]["source"], 3);
+        assert_eq!(cursor.into_result().nodes, vec![0, 1, 2, 3, 4]);
+    }
+}
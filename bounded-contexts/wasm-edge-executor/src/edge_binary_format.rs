@@ -13,6 +13,11 @@
 //!
 //! See: harmony-design/DESIGN_SYSTEM.md#graph-binary-formats
 
+use harmony_error::{limits, HarmonyError};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 /// Size of a single edge in bytes
@@ -28,15 +33,20 @@ const TARGET_OFFSET: usize = 4;
 const TYPE_OFFSET: usize = 8;
 
 /// Compact binary representation of a graph edge
-#[wasm_bindgen]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Its serde form (in traversal results) is the `BinaryEdge` TypeScript
+/// interface, as the `EdgeBinaryFormat` name belongs to the JS class.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "BinaryEdge")]
 pub struct EdgeBinaryFormat {
     source: u32,
     target: u32,
     edge_type: u32,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl EdgeBinaryFormat {
     /// Creates a new edge with the given source, target, and type
     ///
@@ -44,7 +54,7 @@ impl EdgeBinaryFormat {
     /// * `source` - Source node ID
     /// * `target` - Target node ID
     /// * `edge_type` - Edge type ID
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new(source: u32, target: u32, edge_type: u32) -> Self {
         Self {
             source,
@@ -54,19 +64,19 @@ impl EdgeBinaryFormat {
     }
 
     /// Gets the source node ID
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn source(&self) -> u32 {
         self.source
     }
 
     /// Gets the target node ID
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn target(&self) -> u32 {
         self.target
     }
 
     /// Gets the edge type ID
-    #[wasm_bindgen(getter, js_name = edgeType)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter, js_name = edgeType))]
     pub fn edge_type(&self) -> u32 {
         self.edge_type
     }
@@ -79,10 +89,59 @@ impl EdgeBinaryFormat {
     ///
     /// # Returns
     /// Number of bytes written (always EDGE_SIZE)
-    #[wasm_bindgen(js_name = toBytes)]
-    pub fn to_bytes(&self, buffer: &mut [u8], offset: usize) -> Result<usize, JsValue> {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = toBytes))]
+    pub fn to_bytes(&self, buffer: &mut [u8], offset: usize) -> Result<usize, HarmonyError> {
+        self.write_to(buffer, offset)
+    }
+
+    /// Deserializes an edge from a byte buffer
+    ///
+    /// # Arguments
+    /// * `buffer` - Source buffer
+    /// * `offset` - Offset in buffer to read from
+    ///
+    /// # Returns
+    /// Deserialized edge
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = fromBytes))]
+    pub fn from_bytes(buffer: &[u8], offset: usize) -> Result<EdgeBinaryFormat, HarmonyError> {
+        Self::read_from(buffer, offset)
+    }
+
+    /// Checks if this edge connects the given nodes (in either direction)
+    ///
+    /// # Arguments
+    /// * `node_a` - First node ID
+    /// * `node_b` - Second node ID
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = connectsNodes))]
+    pub fn connects_nodes(&self, node_a: u32, node_b: u32) -> bool {
+        (self.source == node_a && self.target == node_b)
+            || (self.source == node_b && self.target == node_a)
+    }
+
+    /// Checks if this edge is a self-loop
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = isSelfLoop))]
+    pub fn is_self_loop(&self) -> bool {
+        self.source == self.target
+    }
+
+    /// Reverses the direction of the edge (swaps source and target)
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn reverse(&self) -> EdgeBinaryFormat {
+        EdgeBinaryFormat {
+            source: self.target,
+            target: self.source,
+            edge_type: self.edge_type,
+        }
+    }
+}
+
+impl EdgeBinaryFormat {
+    /// Writes the edge at `offset` (Rust-side counterpart of `toBytes`)
+    pub fn write_to(&self, buffer: &mut [u8], offset: usize) -> Result<usize, HarmonyError> {
         if buffer.len() < offset + EDGE_SIZE {
-            return Err(JsValue::from_str("Buffer too small for edge serialization"));
+            return Err(HarmonyError::CapacityExceeded(
+                "Buffer too small for edge serialization".to_string(),
+            ));
         }
 
         let slice = &mut buffer[offset..offset + EDGE_SIZE];
@@ -99,18 +158,12 @@ impl EdgeBinaryFormat {
         Ok(EDGE_SIZE)
     }
 
-    /// Deserializes an edge from a byte buffer
-    ///
-    /// # Arguments
-    /// * `buffer` - Source buffer
-    /// * `offset` - Offset in buffer to read from
-    ///
-    /// # Returns
-    /// Deserialized edge
-    #[wasm_bindgen(js_name = fromBytes)]
-    pub fn from_bytes(buffer: &[u8], offset: usize) -> Result<EdgeBinaryFormat, JsValue> {
+    /// Reads an edge at `offset` (Rust-side counterpart of `fromBytes`)
+    pub fn read_from(buffer: &[u8], offset: usize) -> Result<EdgeBinaryFormat, HarmonyError> {
         if buffer.len() < offset + EDGE_SIZE {
-            return Err(JsValue::from_str("Buffer too small for edge deserialization"));
+            return Err(HarmonyError::Malformed(
+                "Buffer too small for edge deserialization".to_string(),
+            ));
         }
 
         let slice = &buffer[offset..offset + EDGE_SIZE];
@@ -145,78 +198,67 @@ impl EdgeBinaryFormat {
             edge_type,
         })
     }
-
-    /// Checks if this edge connects the given nodes (in either direction)
-    ///
-    /// # Arguments
-    /// * `node_a` - First node ID
-    /// * `node_b` - Second node ID
-    #[wasm_bindgen(js_name = connectsNodes)]
-    pub fn connects_nodes(&self, node_a: u32, node_b: u32) -> bool {
-        (self.source == node_a && self.target == node_b)
-            || (self.source == node_b && self.target == node_a)
-    }
-
-    /// Checks if this edge is a self-loop
-    #[wasm_bindgen(js_name = isSelfLoop)]
-    pub fn is_self_loop(&self) -> bool {
-        self.source == self.target
-    }
-
-    /// Reverses the direction of the edge (swaps source and target)
-    #[wasm_bindgen]
-    pub fn reverse(&self) -> EdgeBinaryFormat {
-        EdgeBinaryFormat {
-            source: self.target,
-            target: self.source,
-            edge_type: self.edge_type,
-        }
-    }
 }
 
 /// Batch serialization of multiple edges to a contiguous buffer
 ///
 /// # Arguments
-/// * `edges` - Vector of edges to serialize
+/// * `edges` - Edges to serialize
 ///
 /// # Returns
 /// Byte buffer containing all serialized edges
-#[wasm_bindgen(js_name = serializeEdges)]
 pub fn serialize_edges(edges: &[EdgeBinaryFormat]) -> Vec<u8> {
     let mut buffer = vec![0u8; edges.len() * EDGE_SIZE];
     
     for (i, edge) in edges.iter().enumerate() {
         let offset = i * EDGE_SIZE;
-        edge.to_bytes(&mut buffer, offset).unwrap();
+        edge.write_to(&mut buffer, offset).unwrap();
     }
     
     buffer
 }
 
+/// Batch serialization exported to JS (takes ownership of the edge array)
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = serializeEdges)]
+pub fn serialize_edges_js(edges: Vec<EdgeBinaryFormat>) -> Vec<u8> {
+    serialize_edges(&edges)
+}
+
 /// Batch deserialization of multiple edges from a contiguous buffer
 ///
 /// # Arguments
 /// * `buffer` - Byte buffer containing serialized edges
 ///
 /// # Returns
-/// Vector of deserialized edges
-#[wasm_bindgen(js_name = deserializeEdges)]
-pub fn deserialize_edges(buffer: &[u8]) -> Result<Vec<EdgeBinaryFormat>, JsValue> {
-    if buffer.len() % EDGE_SIZE != 0 {
-        return Err(JsValue::from_str("Buffer size must be multiple of EDGE_SIZE"));
+/// Vector of deserialized edges, or `capacity_exceeded` for more than
+/// `max_edges_per_batch` edges
+pub fn deserialize_edges(buffer: &[u8]) -> Result<Vec<EdgeBinaryFormat>, HarmonyError> {
+    if !buffer.len().is_multiple_of(EDGE_SIZE) {
+        return Err(HarmonyError::Malformed(
+            "Buffer size must be multiple of EDGE_SIZE".to_string(),
+        ));
     }
 
     let edge_count = buffer.len() / EDGE_SIZE;
+    limits::check("Edge batch", edge_count, limits::limits().max_edges_per_batch)?;
     let mut edges = Vec::with_capacity(edge_count);
 
     for i in 0..edge_count {
         let offset = i * EDGE_SIZE;
-        edges.push(EdgeBinaryFormat::from_bytes(buffer, offset)?);
+        edges.push(EdgeBinaryFormat::read_from(buffer, offset)?);
     }
 
     Ok(edges)
 }
 
+/// Batch deserialization exported to JS
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = deserializeEdges)]
+pub fn deserialize_edges_js(buffer: &[u8]) -> Result<Vec<EdgeBinaryFormat>, HarmonyError> {
+    deserialize_edges(buffer)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,8 +276,8 @@ mod tests {
         let edge = EdgeBinaryFormat::new(42, 100, 5);
         let mut buffer = vec![0u8; EDGE_SIZE];
         
-        edge.to_bytes(&mut buffer, 0).unwrap();
-        let deserialized = EdgeBinaryFormat::from_bytes(&buffer, 0).unwrap();
+        edge.write_to(&mut buffer, 0).unwrap();
+        let deserialized = EdgeBinaryFormat::read_from(&buffer, 0).unwrap();
         
         assert_eq!(edge, deserialized);
     }
@@ -280,6 +322,15 @@ mod tests {
 
         let deserialized = deserialize_edges(&buffer).unwrap();
         assert_eq!(edges, deserialized);
+
+        limits::set(limits::Limits {
+            max_edges_per_batch: 2,
+            ..limits::Limits::default()
+        });
+        let error = deserialize_edges(&buffer).unwrap_err();
+        assert_eq!(error.code(), "capacity_exceeded");
+        assert_eq!(error.to_string(), "Edge batch is 3, over the limit of 2");
+        limits::set(limits::Limits::default());
     }
 
     #[test]
@@ -287,7 +338,7 @@ mod tests {
         let edge = EdgeBinaryFormat::new(1, 2, 3);
         let mut small_buffer = vec![0u8; 8]; // Too small
         
-        assert!(edge.to_bytes(&mut small_buffer, 0).is_err());
-        assert!(EdgeBinaryFormat::from_bytes(&small_buffer, 0).is_err());
+        assert!(edge.write_to(&mut small_buffer, 0).is_err());
+        assert!(EdgeBinaryFormat::read_from(&small_buffer, 0).is_err());
     }
 }
\ No newline at end of file
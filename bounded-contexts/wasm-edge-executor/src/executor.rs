@@ -0,0 +1,1368 @@
+//! WASMEdgeExecutor: Adjacency-indexed edge store with BFS traversal
+//!
+//! Edges are stored once in insertion order; per-node adjacency lists hold
+//! indices into that store. Traversal results can be returned as JSON, as
+//! JS values or in a compact binary layout suitable for writing straight
+//! into shared memory. The JS value methods (`...Value`) build and read JS
+//! objects directly with serde-wasm-bindgen, skipping JSON encoding on one
+//! side and `JSON.parse` on the other; the objects match the parsed JSON.
+//! The encoded methods (`...Encoded`) pass the same data as JSON, CBOR or
+//! MessagePack bytes, in the format named by their `format` argument.
+//!
+//! Edge type IDs are the codes of the executor's `EdgeTypeRegistry`: the
+//! built-in edge types come first, and custom types registered at runtime
+//! follow. Type masks only cover codes below 32; filter by type name to
+//! reach custom types beyond that.
+//!
+//! Traversals follow outgoing edges unless given a `TraversalDirection`:
+//! `incoming` walks edges backwards (e.g. everything that depends on a
+//! node) and `both` ignores direction. Result edges always keep their own
+//! direction, so with `incoming` an edge's target is the node it was
+//! reached from.
+//!
+//! `neighborhood` returns the nodes around a focal node and every edge
+//! between them, in the binary layout below; see neighborhood.rs.
+//!
+//! Traversals and edge type registrations are traced as harmony-trace
+//! spans at `debug` level.
+//!
+//! The executor is `Send + Sync`. With the `threads` feature, traversals
+//! expand wide BFS levels on the rayon pool; results are identical to the
+//! sequential traversal.
+//!
+//! Nodes are known once an edge touches them or `addNode` adds them, and
+//! stay known when their edges are removed, so a graph can hold isolated
+//! nodes (e.g. a component nothing uses yet).
+//!
+//! `exportEdges` and `getEdgeTypes` together capture the graph; `restore`
+//! replaces the executor's contents with them, keeping every edge type code.
+//! `isolatedNodes` lists the nodes the edges leave out, for `addNodes`
+//! after a `restore`.
+//!
+//! Each edge has an f32 weight (default 1.0, e.g. a relationship's usage
+//! frequency), stored beside the edges by insertion index rather than in
+//! EdgeBinaryFormat. `updateWeights` takes typed arrays of indices and
+//! weights, so automation can change thousands of weights per frame
+//! without JSON; `exportWeights` and `setWeights` carry them through
+//! snapshots.
+//!
+//! Traversal Result Binary Layout:
+//! - Bytes 0-3: Visited node count N (u32, little-endian)
+//! - Bytes 4-7: Traversed edge count E (u32, little-endian)
+//! - Next N * 8 bytes: (node ID u32, depth u32) in visit order
+//! - Next E * 12 bytes: edges in EdgeBinaryFormat, in discovery order
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-edge-executor
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use harmony_error::{limits, wire, HarmonyError, ResultExt};
+use harmony_schemas::{EdgeTypeDefinition, EdgeTypeRegistry, SchemaError};
+use harmony_trace::Level;
+#[cfg(feature = "threads")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use serde_wasm_bindgen::Serializer;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::edge_binary_format::{deserialize_edges, serialize_edges, EdgeBinaryFormat, EDGE_SIZE};
+
+/// harmony-trace target of the executor's spans
+pub(crate) const TRACE_TARGET: &str = "wasm-edge-executor";
+
+/// Frontier size from which the `threads` feature expands a BFS level in
+/// parallel; smaller levels are not worth the hand-off to the pool
+#[cfg(feature = "threads")]
+const PARALLEL_FRONTIER_MIN: usize = 256;
+
+/// Weight of an edge nobody has weighted
+pub const DEFAULT_EDGE_WEIGHT: f32 = 1.0;
+
+/// Size of the traversal result header in bytes
+pub const TRAVERSAL_HEADER_SIZE: usize = 8;
+
+/// Size of one (node, depth) entry in a binary traversal result
+pub const VISITED_ENTRY_SIZE: usize = 8;
+
+/// `invalid_input` naming the first non-finite weight
+fn check_weights(weights: &[f32]) -> Result<(), HarmonyError> {
+    match weights.iter().position(|weight| !weight.is_finite()) {
+        Some(position) => Err(HarmonyError::InvalidInput(format!(
+            "Weight {} at position {} is not finite",
+            weights[position], position
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Returns true if `edge_type` passes a type mask (bit per type; 0 = all)
+#[inline]
+pub fn edge_type_matches(mask: u32, edge_type: u32) -> bool {
+    mask == 0 || (edge_type < 32 && mask & (1 << edge_type) != 0)
+}
+
+/// Which edges a traversal follows from each node
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraversalDirection {
+    /// Source to target
+    #[default]
+    Outgoing = 0,
+    /// Target to source
+    Incoming = 1,
+    /// Either way; a self loop is followed once
+    Both = 2,
+}
+
+/// Route to `target` in a binary traversal result (from
+/// `traverseBFSBinary`, `traverseBFSDirectedBinary` or a cursor's
+/// `resultBinary`)
+///
+/// # Returns
+/// JSON TraversalPath, or undefined if `target` was not visited;
+/// `malformed` for a buffer that is not a traversal result
+#[cfg_attr(feature = "wasm", wasm_bindgen(js_name = traversalPath))]
+pub fn traversal_path(result: &[u8], target: u32) -> Result<Option<String>, HarmonyError> {
+    Ok(match TraversalResult::from_bytes(result)?.path_to(target) {
+        Some(path) => Some(serde_json::to_string(&path)?),
+        None => None,
+    })
+}
+
+/// Result of a breadth-first traversal
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TraversalResult {
+    /// Visited node IDs in BFS order, starting with the start node
+    pub nodes: Vec<u32>,
+    /// Depth of each entry in `nodes`
+    pub depths: Vec<u32>,
+    /// Edges followed to discover each node, in discovery order (for a
+    /// `neighborhood`, every edge between its nodes)
+    #[cfg_attr(feature = "wasm", tsify(type = "BinaryEdge[]"))]
+    pub edges: Vec<EdgeBinaryFormat>,
+}
+
+/// Route from a traversal's start node to one visited node
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TraversalPath {
+    /// Node IDs from the start node to the target, both included
+    pub nodes: Vec<u32>,
+    /// Edges between consecutive nodes, each in its own direction
+    #[cfg_attr(feature = "wasm", tsify(type = "BinaryEdge[]"))]
+    pub edges: Vec<EdgeBinaryFormat>,
+}
+
+impl TraversalResult {
+    /// Route the traversal took to `target`: a shortest path from the
+    /// start node within the traversal's direction and edge types
+    ///
+    /// Node `i` (after the start) was discovered through `edges[i - 1]`,
+    /// whose other end is its parent; the path follows parents back.
+    ///
+    /// # Returns
+    /// None if `target` was not visited or the result is inconsistent
+    /// (e.g. decoded from a foreign buffer); a path without edges for the
+    /// start node
+    pub fn path_to(&self, target: u32) -> Option<TraversalPath> {
+        let positions: HashMap<u32, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(position, &node)| (node, position))
+            .collect();
+        let mut position = *positions.get(&target)?;
+        let mut path = TraversalPath {
+            nodes: vec![target],
+            edges: Vec::new(),
+        };
+        while position > 0 {
+            let node = self.nodes[position];
+            let edge = *self.edges.get(position - 1)?;
+            let parent = if edge.target() == node {
+                edge.source()
+            } else if edge.source() == node {
+                edge.target()
+            } else {
+                return None;
+            };
+            // Parents are discovered first; anything else would loop
+            position = positions.get(&parent).copied().filter(|&p| p < position)?;
+            path.nodes.push(parent);
+            path.edges.push(edge);
+        }
+        path.nodes.reverse();
+        path.edges.reverse();
+        Some(path)
+    }
+
+    /// Size of the binary encoding in bytes
+    pub fn encoded_len(&self) -> usize {
+        TRAVERSAL_HEADER_SIZE + self.nodes.len() * VISITED_ENTRY_SIZE + self.edges.len() * EDGE_SIZE
+    }
+
+    /// Encodes the result into `buffer` using the binary layout
+    ///
+    /// # Returns
+    /// Number of bytes written
+    pub fn write_to(&self, buffer: &mut [u8]) -> Result<usize, HarmonyError> {
+        let len = self.encoded_len();
+        if buffer.len() < len {
+            return Err(HarmonyError::CapacityExceeded(
+                "Buffer too small for traversal result".to_string(),
+            ));
+        }
+
+        buffer[0..4].copy_from_slice(&(self.nodes.len() as u32).to_le_bytes());
+        buffer[4..8].copy_from_slice(&(self.edges.len() as u32).to_le_bytes());
+
+        let mut offset = TRAVERSAL_HEADER_SIZE;
+        for (node, depth) in self.nodes.iter().zip(&self.depths) {
+            buffer[offset..offset + 4].copy_from_slice(&node.to_le_bytes());
+            buffer[offset + 4..offset + 8].copy_from_slice(&depth.to_le_bytes());
+            offset += VISITED_ENTRY_SIZE;
+        }
+        for edge in &self.edges {
+            offset += edge.write_to(buffer, offset)?;
+        }
+        Ok(len)
+    }
+
+    /// Encodes the result into a new buffer
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = vec![0u8; self.encoded_len()];
+        // Buffer is sized by encoded_len
+        let _ = self.write_to(&mut buffer);
+        buffer
+    }
+
+    /// Decodes a result from the binary layout
+    pub fn from_bytes(buffer: &[u8]) -> Result<Self, HarmonyError> {
+        if buffer.len() < TRAVERSAL_HEADER_SIZE {
+            return Err(HarmonyError::Malformed(
+                "Buffer too small for traversal header".to_string(),
+            ));
+        }
+        let read_u32 = |i: usize| u32::from_le_bytes([buffer[i], buffer[i + 1], buffer[i + 2], buffer[i + 3]]);
+        let node_count = read_u32(0) as usize;
+        let edge_count = read_u32(4) as usize;
+
+        // Counts come from the caller, so the sizes may not fit in usize
+        let bounds = node_count
+            .checked_mul(VISITED_ENTRY_SIZE)
+            .and_then(|nodes| nodes.checked_add(TRAVERSAL_HEADER_SIZE))
+            .and_then(|edges_start| {
+                let edges_end = edge_count
+                    .checked_mul(EDGE_SIZE)
+                    .and_then(|edges| edges.checked_add(edges_start))?;
+                Some((edges_start, edges_end))
+            });
+        let Some((edges_start, edges_end)) = bounds.filter(|&(_, end)| end <= buffer.len()) else {
+            return Err(HarmonyError::Malformed(
+                "Buffer too small for traversal result".to_string(),
+            ));
+        };
+
+        let mut result = TraversalResult::default();
+        for i in 0..node_count {
+            let offset = TRAVERSAL_HEADER_SIZE + i * VISITED_ENTRY_SIZE;
+            result.nodes.push(read_u32(offset));
+            result.depths.push(read_u32(offset + 4));
+        }
+        result.edges = deserialize_edges(&buffer[edges_start..edges_end])?;
+        Ok(result)
+    }
+}
+
+/// Edge store with outgoing and incoming adjacency and BFS traversal
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Default)]
+pub struct WASMEdgeExecutor {
+    edges: Vec<EdgeBinaryFormat>,
+    outgoing: HashMap<u32, Vec<usize>>,
+    incoming: HashMap<u32, Vec<usize>>,
+    nodes: BTreeSet<u32>,
+    // Weight of each edge, by index in `edges`
+    weights: Vec<f32>,
+    edge_types: EdgeTypeRegistry,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl WASMEdgeExecutor {
+    /// Creates an empty executor
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserves room for `nodes` more nodes and `edges` more edges, so bulk
+    /// imports do not rehash or reallocate as they go
+    ///
+    /// # Returns
+    /// `capacity_exceeded` if the memory cannot be reserved
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn reserve(&mut self, nodes: usize, edges: usize) -> Result<(), HarmonyError> {
+        for adjacency in [&mut self.outgoing, &mut self.incoming] {
+            adjacency.try_reserve(nodes).map_err(|error| {
+                HarmonyError::CapacityExceeded(format!("Cannot reserve {} nodes: {}", nodes, error))
+            })?;
+        }
+        self.edges.try_reserve(edges).map_err(|error| {
+            HarmonyError::CapacityExceeded(format!("Cannot reserve {} edges: {}", edges, error))
+        })?;
+        self.weights.try_reserve(edges).map_err(|error| {
+            HarmonyError::CapacityExceeded(format!("Cannot reserve {} edges: {}", edges, error))
+        })
+    }
+
+    /// Adds an edge
+    ///
+    /// # Returns
+    /// Index of the edge in insertion order
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = addEdge))]
+    pub fn add_edge(&mut self, source: u32, target: u32, edge_type: u32) -> usize {
+        self.insert(EdgeBinaryFormat::new(source, target, edge_type))
+    }
+
+    /// Adds an edge by edge type name
+    ///
+    /// # Arguments
+    /// * `edge_type` - Built-in or registered custom edge type id
+    ///
+    /// # Returns
+    /// Index of the edge in insertion order
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = addNamedEdge))]
+    pub fn add_named_edge(&mut self, source: u32, target: u32, edge_type: &str) -> Result<usize, HarmonyError> {
+        let code = self.edge_type(edge_type)?;
+        Ok(self.add_edge(source, target, code))
+    }
+
+    /// Adds a node without edges; nodes on edges are added with them
+    ///
+    /// # Returns
+    /// false if the node was already known
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = addNode))]
+    pub fn add_node(&mut self, id: u32) -> bool {
+        self.nodes.insert(id)
+    }
+
+    /// Adds several nodes, e.g. `isolatedNodes` saved with a snapshot
+    ///
+    /// # Returns
+    /// Number of nodes that were not known yet
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = addNodes))]
+    pub fn add_nodes(&mut self, ids: Vec<u32>) -> usize {
+        ids.into_iter().filter(|&id| self.add_node(id)).count()
+    }
+
+    /// Whether the node was added or is on an edge
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = hasNode))]
+    pub fn has_node(&self, id: u32) -> bool {
+        self.nodes.contains(&id)
+    }
+
+    /// Every known node ID, ascending
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = listNodes))]
+    pub fn list_nodes(&self) -> Vec<u32> {
+        self.nodes.iter().copied().collect()
+    }
+
+    /// Known nodes without any edge, ascending
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = isolatedNodes))]
+    pub fn isolated_nodes(&self) -> Vec<u32> {
+        self.nodes
+            .iter()
+            .copied()
+            .filter(|node| !self.outgoing.contains_key(node) && !self.incoming.contains_key(node))
+            .collect()
+    }
+
+    /// Number of known nodes
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter, js_name = nodeCount))]
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Removes the most recently added edge matching all three fields
+    ///
+    /// Later edges move down one place in insertion order. Both nodes stay
+    /// known.
+    ///
+    /// # Returns
+    /// false if no edge matches
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = removeEdge))]
+    pub fn remove_edge(&mut self, source: u32, target: u32, edge_type: u32) -> bool {
+        let wanted = EdgeBinaryFormat::new(source, target, edge_type);
+        let Some(indices) = self.outgoing.get_mut(&source) else {
+            return false;
+        };
+        let Some(position) = indices.iter().rposition(|&index| self.edges[index] == wanted) else {
+            return false;
+        };
+        let index = indices.remove(position);
+        if indices.is_empty() {
+            self.outgoing.remove(&source);
+        }
+        if let Some(indices) = self.incoming.get_mut(&target) {
+            indices.retain(|&other| other != index);
+            if indices.is_empty() {
+                self.incoming.remove(&target);
+            }
+        }
+        self.edges.remove(index);
+        self.weights.remove(index);
+        for indices in self.outgoing.values_mut().chain(self.incoming.values_mut()) {
+            for later in indices.iter_mut().filter(|later| **later > index) {
+                *later -= 1;
+            }
+        }
+        true
+    }
+
+    /// Registers a custom edge type
+    ///
+    /// # Arguments
+    /// * `definition_json` - JSON EdgeTypeDefinition:
+    ///   `{"id", "directed"?, "reverse"?, "dependency"?, "composition"?, "description"?}`
+    ///
+    /// # Returns
+    /// Numeric code to use as the edge type ID
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = registerEdgeType))]
+    pub fn register_edge_type(&mut self, definition_json: &str) -> Result<u32, HarmonyError> {
+        limits::check_json(definition_json).context("Invalid edge type JSON")?;
+        let definition: EdgeTypeDefinition =
+            serde_json::from_str(definition_json).context("Invalid edge type JSON")?;
+        self.register(definition)
+    }
+
+    /// Removes the most recently registered custom edge type
+    ///
+    /// Only the last type can go, so every other type keeps its code, and
+    /// only while no edge uses it.
+    ///
+    /// # Returns
+    /// `not_found` for an unknown type, `invalid_input` for a built-in, an
+    /// earlier custom type or a type in use
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = unregisterEdgeType))]
+    pub fn unregister_edge_type(&mut self, edge_type: &str) -> Result<(), HarmonyError> {
+        let code = self.edge_type(edge_type)?;
+        if self.edge_types.is_builtin(edge_type) {
+            return Err(HarmonyError::InvalidInput(format!(
+                "Built-in edge type '{}' cannot be unregistered",
+                edge_type
+            )));
+        }
+        if code as usize + 1 != self.edge_types.definitions().len() {
+            return Err(HarmonyError::InvalidInput(format!(
+                "Edge type '{}' is not the most recently registered",
+                edge_type
+            )));
+        }
+        let used = self.edges.iter().filter(|edge| edge.edge_type() == code).count();
+        if used > 0 {
+            return Err(HarmonyError::InvalidInput(format!(
+                "Edge type '{}' is used by {} edges",
+                edge_type, used
+            )));
+        }
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "unregister_edge_type");
+        span.field("id", edge_type).field("code", code);
+        self.edge_types.pop_custom();
+        Ok(())
+    }
+
+    /// Numeric code of a built-in or registered edge type
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = edgeTypeCode))]
+    pub fn edge_type_code(&self, edge_type: &str) -> Option<u32> {
+        self.edge_types.code(edge_type)
+    }
+
+    /// Every known edge type definition
+    ///
+    /// # Returns
+    /// JSON array of EdgeTypeDefinition, ordered by code
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getEdgeTypes))]
+    pub fn get_edge_types(&self) -> String {
+        serde_json::to_string(self.edge_types.definitions()).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Adds every edge from a buffer in EdgeBinaryFormat
+    ///
+    /// # Returns
+    /// Number of edges added
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = loadEdges))]
+    pub fn load_edges(&mut self, buffer: &[u8]) -> Result<usize, HarmonyError> {
+        let edges = deserialize_edges(buffer)?;
+        let count = edges.len();
+        self.reserve(0, count)?;
+        for edge in edges {
+            self.insert(edge);
+        }
+        Ok(count)
+    }
+
+    /// Every stored edge, in insertion order
+    ///
+    /// # Returns
+    /// Buffer in EdgeBinaryFormat, for `loadEdges` or `restore`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = exportEdges))]
+    pub fn export_edges(&self) -> Vec<u8> {
+        serialize_edges(&self.edges)
+    }
+
+    /// Replaces the edge types and edges with ones saved from another
+    /// executor; on failure the executor is unchanged
+    ///
+    /// # Arguments
+    /// * `edge_types_json` - JSON array from `getEdgeTypes`
+    /// * `edges` - Buffer from `exportEdges`
+    ///
+    /// # Returns
+    /// Number of edges restored, or `malformed` if an edge type would get
+    /// a different code than it had
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn restore(&mut self, edge_types_json: &str, edges: &[u8]) -> Result<usize, HarmonyError> {
+        limits::check_json(edge_types_json).context("Invalid edge types JSON")?;
+        let definitions: Vec<EdgeTypeDefinition> =
+            serde_json::from_str(edge_types_json).context("Invalid edge types JSON")?;
+        self.restore_definitions(definitions, edges)
+    }
+
+    /// Sets the weights of the edges at `indices` (insertion order) to the
+    /// matching entries of `weights`; all or none
+    ///
+    /// # Returns
+    /// `invalid_input` for arrays of different lengths, an index past the
+    /// last edge or a non-finite weight
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = updateWeights))]
+    pub fn update_weights(&mut self, indices: &[u32], weights: &[f32]) -> Result<(), HarmonyError> {
+        if indices.len() != weights.len() {
+            return Err(HarmonyError::InvalidInput(format!(
+                "{} edge indices for {} weights",
+                indices.len(),
+                weights.len()
+            )));
+        }
+        if let Some(&index) = indices.iter().find(|&&index| index as usize >= self.edges.len()) {
+            return Err(HarmonyError::InvalidInput(format!(
+                "Edge index {} is past the last edge ({} edges)",
+                index,
+                self.edges.len()
+            )));
+        }
+        check_weights(weights)?;
+        for (&index, &weight) in indices.iter().zip(weights) {
+            self.weights[index as usize] = weight;
+        }
+        Ok(())
+    }
+
+    /// Weight of the edge at `index` (insertion order)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getWeight))]
+    pub fn weight(&self, index: usize) -> Option<f32> {
+        self.weights.get(index).copied()
+    }
+
+    /// Every edge's weight, in insertion order
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = exportWeights))]
+    pub fn export_weights(&self) -> Vec<f32> {
+        self.weights.clone()
+    }
+
+    /// Replaces every edge's weight, e.g. with `exportWeights` saved with
+    /// a snapshot after `restore`
+    ///
+    /// # Returns
+    /// `invalid_input` unless there is one finite weight per edge
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setWeights))]
+    pub fn set_weights(&mut self, weights: &[f32]) -> Result<(), HarmonyError> {
+        if weights.len() != self.edges.len() {
+            return Err(HarmonyError::InvalidInput(format!(
+                "{} weights for {} edges",
+                weights.len(),
+                self.edges.len()
+            )));
+        }
+        check_weights(weights)?;
+        self.weights.copy_from_slice(weights);
+        Ok(())
+    }
+
+    /// Number of stored edges
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter, js_name = edgeCount))]
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Breadth-first traversal following outgoing edges
+    ///
+    /// # Arguments
+    /// * `start` - Start node ID
+    /// * `max_depth` - Maximum hop count (0 visits only the start node)
+    /// * `edge_type_mask` - Bit per edge type to follow; 0 follows every type
+    ///
+    /// # Returns
+    /// JSON-encoded TraversalResult
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = traverseBFS))]
+    pub fn traverse_bfs_json(&self, start: u32, max_depth: u32, edge_type_mask: u32) -> Result<String, HarmonyError> {
+        Ok(serde_json::to_string(&self.traverse_bfs(start, max_depth, edge_type_mask))?)
+    }
+
+    /// Breadth-first traversal following only the named edge types
+    ///
+    /// # Arguments
+    /// * `start` - Start node ID
+    /// * `max_depth` - Maximum hop count (0 visits only the start node)
+    /// * `edge_types_json` - JSON array of edge type ids; unknown ids are an error
+    ///
+    /// # Returns
+    /// JSON-encoded TraversalResult
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = traverseBFSTypes))]
+    pub fn traverse_bfs_types_json(
+        &self,
+        start: u32,
+        max_depth: u32,
+        edge_types_json: &str,
+    ) -> Result<String, HarmonyError> {
+        limits::check_json(edge_types_json).context("Invalid edge type list")?;
+        let names: Vec<String> =
+            serde_json::from_str(edge_types_json).context("Invalid edge type list")?;
+        let result = self.traverse_bfs_types(start, max_depth, &names)?;
+        Ok(serde_json::to_string(&result)?)
+    }
+
+    /// Breadth-first traversal returning the binary result layout
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = traverseBFSBinary))]
+    pub fn traverse_bfs_binary(&self, start: u32, max_depth: u32, edge_type_mask: u32) -> Vec<u8> {
+        self.traverse_bfs(start, max_depth, edge_type_mask).to_bytes()
+    }
+
+    /// Breadth-first traversal following edges in `direction`
+    ///
+    /// # Returns
+    /// JSON-encoded TraversalResult; edges keep their own direction
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = traverseBFSDirected))]
+    pub fn traverse_bfs_directed_json(
+        &self,
+        start: u32,
+        max_depth: u32,
+        edge_type_mask: u32,
+        direction: TraversalDirection,
+    ) -> Result<String, HarmonyError> {
+        let result = self.traverse_bfs_directed(start, max_depth, edge_type_mask, direction);
+        Ok(serde_json::to_string(&result)?)
+    }
+
+    /// Breadth-first traversal following edges in `direction`, returning
+    /// the binary result layout
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = traverseBFSDirectedBinary))]
+    pub fn traverse_bfs_directed_binary(
+        &self,
+        start: u32,
+        max_depth: u32,
+        edge_type_mask: u32,
+        direction: TraversalDirection,
+    ) -> Vec<u8> {
+        self.traverse_bfs_directed(start, max_depth, edge_type_mask, direction)
+            .to_bytes()
+    }
+}
+
+/// Encoded versions of the JSON methods: the same data as bytes in a
+/// harmony-error `wire` format ("json", "cbor" or "msgpack")
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl WASMEdgeExecutor {
+    /// Registers a custom edge type from an encoded EdgeTypeDefinition
+    ///
+    /// # Returns
+    /// Numeric code to use as the edge type ID
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = registerEdgeTypeEncoded))]
+    pub fn register_edge_type_encoded(&mut self, definition: &[u8], format: &str) -> Result<u32, HarmonyError> {
+        let definition: EdgeTypeDefinition = wire::decode_as(definition, format).context("Invalid edge type")?;
+        self.register(definition)
+    }
+
+    /// Every known edge type definition, as an encoded array ordered by code
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getEdgeTypesEncoded))]
+    pub fn get_edge_types_encoded(&self, format: &str) -> Result<Vec<u8>, HarmonyError> {
+        wire::encode_as(self.edge_types.definitions(), format)
+    }
+
+    /// Like `restore`, with the edge types from `getEdgeTypesEncoded`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = restoreEncoded))]
+    pub fn restore_encoded(&mut self, edge_types: &[u8], format: &str, edges: &[u8]) -> Result<usize, HarmonyError> {
+        let definitions: Vec<EdgeTypeDefinition> =
+            wire::decode_as(edge_types, format).context("Invalid edge types")?;
+        self.restore_definitions(definitions, edges)
+    }
+
+    /// Breadth-first traversal following outgoing edges
+    ///
+    /// # Returns
+    /// Encoded TraversalResult
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = traverseBFSEncoded))]
+    pub fn traverse_bfs_encoded(
+        &self,
+        start: u32,
+        max_depth: u32,
+        edge_type_mask: u32,
+        format: &str,
+    ) -> Result<Vec<u8>, HarmonyError> {
+        wire::encode_as(&self.traverse_bfs(start, max_depth, edge_type_mask), format)
+    }
+
+    /// Breadth-first traversal following only the named edge types
+    ///
+    /// # Arguments
+    /// * `edge_types` - Encoded array of edge type ids; unknown ids are an error
+    ///
+    /// # Returns
+    /// Encoded TraversalResult
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = traverseBFSTypesEncoded))]
+    pub fn traverse_bfs_types_encoded(
+        &self,
+        start: u32,
+        max_depth: u32,
+        edge_types: &[u8],
+        format: &str,
+    ) -> Result<Vec<u8>, HarmonyError> {
+        let names: Vec<String> = wire::decode_as(edge_types, format).context("Invalid edge type list")?;
+        let result = self.traverse_bfs_types(start, max_depth, &names)?;
+        wire::encode_as(&result, format)
+    }
+}
+
+/// JS value versions of the JSON methods, typed with the generated
+/// TypeScript declarations
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl WASMEdgeExecutor {
+    /// Registers a custom edge type from an EdgeTypeDefinition object
+    ///
+    /// # Returns
+    /// Numeric code to use as the edge type ID
+    #[wasm_bindgen(js_name = registerEdgeTypeValue)]
+    pub fn register_edge_type_value(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "EdgeTypeDefinition")] definition: JsValue,
+    ) -> Result<u32, HarmonyError> {
+        let definition: EdgeTypeDefinition =
+            serde_wasm_bindgen::from_value(definition).context("Invalid edge type")?;
+        self.register(definition)
+    }
+
+    /// Every known edge type definition, as an array ordered by code
+    #[wasm_bindgen(js_name = getEdgeTypesValue, unchecked_return_type = "EdgeTypeDefinition[]")]
+    pub fn get_edge_types_value(&self) -> Result<JsValue, HarmonyError> {
+        Ok(self.edge_types.definitions().serialize(&Serializer::json_compatible())?)
+    }
+
+    /// Breadth-first traversal following outgoing edges
+    ///
+    /// # Returns
+    /// TraversalResult object
+    #[wasm_bindgen(js_name = traverseBFSValue, unchecked_return_type = "TraversalResult")]
+    pub fn traverse_bfs_value(&self, start: u32, max_depth: u32, edge_type_mask: u32) -> Result<JsValue, HarmonyError> {
+        let result = self.traverse_bfs(start, max_depth, edge_type_mask);
+        Ok(result.serialize(&Serializer::json_compatible())?)
+    }
+
+    /// Breadth-first traversal following only the named edge types
+    ///
+    /// # Arguments
+    /// * `edge_types` - Array of edge type ids; unknown ids are an error
+    ///
+    /// # Returns
+    /// TraversalResult object
+    #[wasm_bindgen(js_name = traverseBFSTypesValue, unchecked_return_type = "TraversalResult")]
+    pub fn traverse_bfs_types_value(
+        &self,
+        start: u32,
+        max_depth: u32,
+        #[wasm_bindgen(unchecked_param_type = "string[]")] edge_types: JsValue,
+    ) -> Result<JsValue, HarmonyError> {
+        let names: Vec<String> =
+            serde_wasm_bindgen::from_value(edge_types).context("Invalid edge type list")?;
+        let result = self.traverse_bfs_types(start, max_depth, &names)?;
+        Ok(result.serialize(&Serializer::json_compatible())?)
+    }
+}
+
+impl WASMEdgeExecutor {
+    fn restore_definitions(
+        &mut self,
+        definitions: Vec<EdgeTypeDefinition>,
+        edges: &[u8],
+    ) -> Result<usize, HarmonyError> {
+        let mut restored = Self::new();
+        for (code, definition) in definitions.into_iter().enumerate() {
+            let id = definition.id.clone();
+            // Built-in types are registered already
+            if restored.edge_types.code(&id).is_none() {
+                restored.register(definition)?;
+            }
+            let restored_code = restored.edge_types.code(&id);
+            if restored_code != Some(code as u32) {
+                return Err(HarmonyError::Malformed(format!(
+                    "Edge type '{}' would get code {:?} instead of {}",
+                    id, restored_code, code
+                )));
+            }
+        }
+        let count = restored.load_edges(edges)?;
+        *self = restored;
+        Ok(count)
+    }
+
+    pub(crate) fn insert(&mut self, edge: EdgeBinaryFormat) -> usize {
+        self.insert_weighted(edge, DEFAULT_EDGE_WEIGHT)
+    }
+
+    /// Appends an edge with a weight already checked to be finite
+    pub(crate) fn insert_weighted(&mut self, edge: EdgeBinaryFormat, weight: f32) -> usize {
+        let index = self.edges.len();
+        self.outgoing.entry(edge.source()).or_default().push(index);
+        self.incoming.entry(edge.target()).or_default().push(index);
+        self.nodes.extend([edge.source(), edge.target()]);
+        self.edges.push(edge);
+        self.weights.push(weight);
+        index
+    }
+
+    /// Stored edges in insertion order
+    pub fn edges(&self) -> &[EdgeBinaryFormat] {
+        &self.edges
+    }
+
+    /// Edge weights in insertion order
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// Numeric code of an edge type, or a `not_found` error
+    pub(crate) fn edge_type(&self, edge_type: &str) -> Result<u32, HarmonyError> {
+        self.edge_types.code(edge_type).ok_or_else(|| {
+            SchemaError::UnknownEdgeType {
+                edge_type: edge_type.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Edge types known to this executor
+    pub fn edge_types(&self) -> &EdgeTypeRegistry {
+        &self.edge_types
+    }
+
+    fn register(&mut self, definition: EdgeTypeDefinition) -> Result<u32, HarmonyError> {
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "register_edge_type");
+        span.field("id", &definition.id);
+        let code = self.edge_types.register(definition)?;
+        span.field("code", code);
+        Ok(code)
+    }
+
+    /// Breadth-first traversal following outgoing edges
+    pub fn traverse_bfs(&self, start: u32, max_depth: u32, edge_type_mask: u32) -> TraversalResult {
+        self.traverse_bfs_directed(start, max_depth, edge_type_mask, TraversalDirection::Outgoing)
+    }
+
+    /// Breadth-first traversal following edges in `direction`
+    pub fn traverse_bfs_directed(
+        &self,
+        start: u32,
+        max_depth: u32,
+        edge_type_mask: u32,
+        direction: TraversalDirection,
+    ) -> TraversalResult {
+        self.traverse_bfs_where(start, max_depth, direction, |edge_type| {
+            edge_type_matches(edge_type_mask, edge_type)
+        })
+    }
+
+    /// Breadth-first traversal following only the named edge types
+    pub fn traverse_bfs_types(
+        &self,
+        start: u32,
+        max_depth: u32,
+        edge_types: &[String],
+    ) -> Result<TraversalResult, HarmonyError> {
+        let codes = edge_types
+            .iter()
+            .map(|name| self.edge_type(name))
+            .collect::<Result<HashSet<u32>, HarmonyError>>()?;
+        Ok(self.traverse_bfs_where(start, max_depth, TraversalDirection::Outgoing, |edge_type| {
+            codes.contains(&edge_type)
+        }))
+    }
+
+    fn traverse_bfs_where(
+        &self,
+        start: u32,
+        max_depth: u32,
+        direction: TraversalDirection,
+        follow: impl Fn(u32) -> bool + Sync,
+    ) -> TraversalResult {
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "traverse_bfs");
+        span.field("start", start).field("max_depth", max_depth);
+        if direction != TraversalDirection::Outgoing {
+            span.field("direction", format!("{:?}", direction));
+        }
+        let mut result = TraversalResult::default();
+        let mut visited = HashSet::from([start]);
+        let mut frontier = vec![start];
+        let mut depth = 0u32;
+
+        // Level by level: expanding a whole frontier at once lets the
+        // `threads` feature scan its adjacency lists in parallel, while
+        // merging in frontier order keeps the visit order sequential BFS's
+        while !frontier.is_empty() {
+            result.nodes.extend_from_slice(&frontier);
+            result.depths.resize(result.nodes.len(), depth);
+            if depth >= max_depth {
+                break;
+            }
+
+            let mut next = Vec::new();
+            for (edge, neighbour) in self.expand(&frontier, direction, &follow) {
+                if visited.insert(neighbour) {
+                    result.edges.push(edge);
+                    next.push(neighbour);
+                }
+            }
+            frontier = next;
+            depth += 1;
+        }
+        span.field("visited", result.nodes.len());
+        result
+    }
+
+    /// Edges out of `node`, in insertion order
+    pub(crate) fn outgoing_edges(&self, node: u32) -> impl Iterator<Item = EdgeBinaryFormat> + '_ {
+        self.outgoing
+            .get(&node)
+            .into_iter()
+            .flatten()
+            .map(|&index| self.edges[index])
+    }
+
+    /// Edges at `node` in `direction`, each with the node at its other
+    /// end: outgoing edges in insertion order, then incoming ones
+    ///
+    /// Reads the adjacency lists in place. With `Both`, a self loop is in
+    /// both lists and is only yielded from the outgoing one.
+    pub(crate) fn adjacent_edges(
+        &self,
+        node: u32,
+        direction: TraversalDirection,
+    ) -> impl Iterator<Item = (EdgeBinaryFormat, u32)> + '_ {
+        let outgoing = match direction {
+            TraversalDirection::Incoming => None,
+            _ => self.outgoing.get(&node),
+        };
+        let incoming = match direction {
+            TraversalDirection::Outgoing => None,
+            _ => self.incoming.get(&node),
+        };
+        let skip_loops = direction == TraversalDirection::Both;
+        outgoing
+            .into_iter()
+            .flatten()
+            .map(|&index| (self.edges[index], self.edges[index].target()))
+            .chain(incoming.into_iter().flatten().filter_map(move |&index| {
+                let edge = self.edges[index];
+                (!skip_loops || edge.source() != edge.target()).then_some((edge, edge.source()))
+            }))
+    }
+
+    /// Edges at each frontier node in `direction` that `follow` accepts,
+    /// with their far ends, in frontier and then adjacency order
+    fn expand(
+        &self,
+        frontier: &[u32],
+        direction: TraversalDirection,
+        follow: &(impl Fn(u32) -> bool + Sync),
+    ) -> Vec<(EdgeBinaryFormat, u32)> {
+        let adjacent = |&node: &u32| {
+            self.adjacent_edges(node, direction)
+                .filter(|(edge, _)| follow(edge.edge_type()))
+        };
+        #[cfg(feature = "threads")]
+        if frontier.len() >= PARALLEL_FRONTIER_MIN {
+            return frontier.par_iter().flat_map_iter(adjacent).collect();
+        }
+        frontier.iter().flat_map(adjacent).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> WASMEdgeExecutor {
+        let mut executor = WASMEdgeExecutor::new();
+        executor.add_edge(1, 2, 0);
+        executor.add_edge(1, 3, 1);
+        executor.add_edge(2, 4, 0);
+        executor.add_edge(3, 4, 0);
+        executor.add_edge(4, 1, 0);
+        executor
+    }
+
+    #[test]
+    fn test_bfs_order_and_depths() {
+        let result = sample().traverse_bfs(1, 10, 0);
+        assert_eq!(result.nodes, vec![1, 2, 3, 4]);
+        assert_eq!(result.depths, vec![0, 1, 1, 2]);
+        assert_eq!(result.edges.len(), 3);
+        assert_eq!(result.edges[2], EdgeBinaryFormat::new(2, 4, 0));
+    }
+
+    #[test]
+    fn test_depth_and_type_limits() {
+        let executor = sample();
+        assert_eq!(executor.traverse_bfs(1, 1, 0).nodes, vec![1, 2, 3]);
+        assert_eq!(executor.traverse_bfs(1, 10, 1 << 1).nodes, vec![1, 3]);
+        assert_eq!(executor.traverse_bfs(99, 10, 0).nodes, vec![99]);
+    }
+
+    #[test]
+    fn test_remove_edge() {
+        let mut executor = sample();
+        assert!(executor.remove_edge(1, 2, 0));
+        assert!(!executor.remove_edge(1, 2, 0));
+        assert!(!executor.remove_edge(7, 8, 0));
+        assert_eq!(executor.edge_count(), 4);
+        assert_eq!(executor.edges()[0], EdgeBinaryFormat::new(1, 3, 1));
+        // Adjacency still points at the moved edges
+        assert_eq!(executor.traverse_bfs(1, 10, 0).nodes, vec![1, 3, 4]);
+        assert_eq!(executor.traverse_bfs(2, 10, 0).nodes, vec![2, 4, 1, 3]);
+    }
+
+    #[test]
+    fn test_wide_frontier_order() {
+        // Wide enough for the `threads` feature to expand levels in parallel
+        let mut executor = WASMEdgeExecutor::new();
+        for leaf in 1..=1000 {
+            executor.add_edge(0, leaf, 0);
+            executor.add_edge(leaf, 2000 + leaf % 10, 1);
+            executor.add_edge(leaf, 3000 + leaf, 0);
+        }
+        let result = executor.traverse_bfs(0, 2, 0);
+        let first: Vec<u32> = (0..=1000).collect();
+        let mut seen = HashSet::new();
+        let second: Vec<u32> = (1..=1000)
+            .flat_map(|leaf| [2000 + leaf % 10, 3000 + leaf])
+            .filter(|&node| seen.insert(node))
+            .collect();
+        assert_eq!(result.nodes[..1001], first[..]);
+        assert_eq!(result.nodes[1001..], second[..]);
+        assert_eq!(result.depths.iter().filter(|&&depth| depth == 2).count(), 1010);
+        assert_eq!(result.edges.len(), result.nodes.len() - 1);
+        assert_eq!(executor.traverse_bfs(0, 2, 1 << 1).nodes, vec![0]);
+    }
+
+    #[test]
+    fn test_directed_traversal() {
+        let mut executor = sample();
+        executor.add_edge(4, 4, 1);
+        executor.add_edge(5, 3, 0);
+
+        let incoming = executor.traverse_bfs_directed(4, 10, 0, TraversalDirection::Incoming);
+        assert_eq!(incoming.nodes, vec![4, 2, 3, 1, 5]);
+        assert_eq!(incoming.depths, vec![0, 1, 1, 2, 2]);
+        // Edges keep their direction
+        assert_eq!(incoming.edges[0], EdgeBinaryFormat::new(2, 4, 0));
+        assert_eq!(incoming.edges[3], EdgeBinaryFormat::new(5, 3, 0));
+
+        let both = executor.traverse_bfs_directed(5, 1, 0, TraversalDirection::Both);
+        assert_eq!(both.nodes, vec![5, 3]);
+        let both = executor.traverse_bfs_directed(3, 10, 1 << 0, TraversalDirection::Both);
+        assert_eq!(both.nodes, vec![3, 4, 5, 1, 2]);
+        assert_eq!(
+            executor.traverse_bfs_directed(1, 10, 0, TraversalDirection::Outgoing),
+            executor.traverse_bfs(1, 10, 0)
+        );
+
+        // The self loop is listed once either way
+        let at_4: Vec<_> = executor.adjacent_edges(4, TraversalDirection::Both).collect();
+        assert_eq!(at_4.iter().filter(|(edge, _)| edge.source() == edge.target()).count(), 1);
+        assert_eq!(at_4.len(), 4);
+
+        assert!(executor.remove_edge(5, 3, 0));
+        assert_eq!(
+            executor.traverse_bfs_directed(4, 10, 0, TraversalDirection::Incoming).nodes,
+            vec![4, 2, 3, 1]
+        );
+        let json = executor
+            .traverse_bfs_directed_json(2, 1, 0, TraversalDirection::Incoming)
+            .unwrap();
+        let parsed: TraversalResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.nodes, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_path_to() {
+        let executor = sample();
+        let result = executor.traverse_bfs(1, 10, 0);
+        let path = result.path_to(4).unwrap();
+        assert_eq!(path.nodes, vec![1, 2, 4]);
+        assert_eq!(
+            path.edges,
+            vec![EdgeBinaryFormat::new(1, 2, 0), EdgeBinaryFormat::new(2, 4, 0)]
+        );
+        assert_eq!(result.path_to(1).unwrap(), TraversalPath { nodes: vec![1], edges: vec![] });
+        assert_eq!(result.path_to(99), None);
+
+        // Against the edges' direction, the path keeps them as they are
+        let incoming = executor.traverse_bfs_directed(4, 10, 0, TraversalDirection::Incoming);
+        let path = incoming.path_to(1).unwrap();
+        assert_eq!(path.nodes, vec![4, 2, 1]);
+        assert_eq!(path.edges[1], EdgeBinaryFormat::new(1, 2, 0));
+
+        let decoded = TraversalResult::from_bytes(&result.to_bytes()).unwrap();
+        assert_eq!(decoded.path_to(3).unwrap().nodes, vec![1, 3]);
+        let json = traversal_path(&result.to_bytes(), 4).unwrap().unwrap();
+        assert_eq!(serde_json::from_str::<TraversalPath>(&json).unwrap().nodes, vec![1, 2, 4]);
+        assert_eq!(traversal_path(&result.to_bytes(), 99).unwrap(), None);
+        assert_eq!(traversal_path(&[1, 2], 4).unwrap_err().code(), "malformed");
+        let mut crafted = result.to_bytes();
+        crafted[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(traversal_path(&crafted, 4).unwrap_err().code(), "malformed");
+        let broken = TraversalResult {
+            nodes: vec![1, 2],
+            depths: vec![0, 1],
+            edges: vec![EdgeBinaryFormat::new(3, 4, 0)],
+        };
+        assert_eq!(broken.path_to(2), None);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<WASMEdgeExecutor>();
+        assert_send_sync::<TraversalResult>();
+    }
+
+    #[test]
+    fn test_nodes() {
+        let mut executor = sample();
+        assert_eq!(executor.list_nodes(), vec![1, 2, 3, 4]);
+        assert!(executor.add_node(9));
+        assert!(!executor.add_node(9));
+        assert!(!executor.add_node(2));
+        assert!(executor.has_node(9) && executor.has_node(3) && !executor.has_node(5));
+        assert_eq!(executor.add_nodes(vec![8, 9, 7]), 2);
+        assert_eq!(executor.node_count(), 7);
+        assert_eq!(executor.traverse_bfs(9, 10, 0).nodes, vec![9]);
+
+        // Removing the only edge at 2 leaves it isolated
+        assert!(executor.remove_edge(2, 4, 0));
+        assert!(executor.remove_edge(1, 2, 0));
+        assert_eq!(executor.isolated_nodes(), vec![2, 7, 8, 9]);
+
+        // Edges and isolated nodes together round-trip the graph
+        let mut restored = WASMEdgeExecutor::new();
+        restored
+            .restore(&executor.get_edge_types(), &executor.export_edges())
+            .unwrap();
+        restored.add_nodes(executor.isolated_nodes());
+        assert_eq!(restored.list_nodes(), executor.list_nodes());
+    }
+
+    #[test]
+    fn test_weights() {
+        let mut executor = sample();
+        assert_eq!(executor.weights(), [DEFAULT_EDGE_WEIGHT; 5]);
+        executor.update_weights(&[4, 0, 4], &[0.5, 2.0, 3.0]).unwrap();
+        assert_eq!(executor.export_weights(), vec![2.0, 1.0, 1.0, 1.0, 3.0]);
+        assert_eq!((executor.weight(4), executor.weight(5)), (Some(3.0), None));
+
+        for (indices, weights) in [(&[0, 1][..], &[1.0][..]), (&[5], &[1.0]), (&[1], &[f32::NAN])] {
+            let error = executor.update_weights(indices, weights).unwrap_err();
+            assert_eq!(error.code(), "invalid_input");
+        }
+        let error = executor.update_weights(&[1, 9], &[7.0, 7.0]).unwrap_err();
+        assert_eq!(error.to_string(), "Edge index 9 is past the last edge (5 edges)");
+        assert_eq!(executor.weight(1), Some(1.0));
+
+        // Weights follow their edges when earlier ones are removed
+        assert!(executor.remove_edge(1, 2, 0));
+        assert_eq!(executor.weights(), [1.0, 1.0, 1.0, 3.0]);
+
+        let mut restored = WASMEdgeExecutor::new();
+        restored
+            .restore(&executor.get_edge_types(), &executor.export_edges())
+            .unwrap();
+        restored.set_weights(&executor.export_weights()).unwrap();
+        assert_eq!(restored.weights(), executor.weights());
+        assert_eq!(restored.set_weights(&[1.0]).unwrap_err().code(), "invalid_input");
+    }
+
+    #[test]
+    fn test_reserve() {
+        let mut executor = WASMEdgeExecutor::new();
+        executor.reserve(100, 1000).unwrap();
+        assert!(executor.outgoing.capacity() >= 100);
+        assert!(executor.edges.capacity() >= 1000);
+        let error = executor.reserve(0, usize::MAX).unwrap_err();
+        assert_eq!(error.code(), "capacity_exceeded");
+    }
+
+    #[test]
+    fn test_custom_edge_types() {
+        let mut executor = WASMEdgeExecutor::new();
+        let tested_by = executor
+            .register_edge_type(r#"{"id":"tested_by","reverse":"tests"}"#)
+            .unwrap();
+        assert_eq!(executor.edge_type_code("tested_by"), Some(tested_by));
+        assert_eq!(executor.edge_type_code("composes_of"), Some(0));
+
+        executor.add_named_edge(1, 2, "composes_of").unwrap();
+        executor.add_named_edge(1, 3, "tested_by").unwrap();
+        executor.add_named_edge(3, 4, "tested_by").unwrap();
+        assert_eq!(executor.edges()[1].edge_type(), tested_by);
+
+        let names = vec!["tested_by".to_string()];
+        let result = executor.traverse_bfs_types(1, 10, &names).unwrap();
+        assert_eq!(result.nodes, vec![1, 3, 4]);
+        let error = executor
+            .traverse_bfs_types(1, 10, &["unknown".to_string()])
+            .unwrap_err();
+        assert_eq!(error.code(), "not_found");
+        assert_eq!(error.context(), vec!["unknown_edge_type"]);
+        assert_eq!(
+            executor.register_edge_type("{").unwrap_err().code(),
+            "malformed"
+        );
+
+        executor.register_edge_type(r#"{"id":"tests"}"#).unwrap();
+        let in_use = executor.unregister_edge_type("tested_by").unwrap_err();
+        assert_eq!(in_use.code(), "invalid_input");
+        executor.unregister_edge_type("tests").unwrap();
+        assert_eq!(executor.edge_type_code("tests"), None);
+        let error = executor.unregister_edge_type("tested_by").unwrap_err();
+        assert_eq!(error.to_string(), "Edge type 'tested_by' is used by 2 edges");
+        let error = executor.unregister_edge_type("composes_of").unwrap_err();
+        assert_eq!(error.code(), "invalid_input");
+        let error = executor.unregister_edge_type("unknown").unwrap_err();
+        assert_eq!(error.code(), "not_found");
+    }
+
+    #[test]
+    fn test_export_and_restore() {
+        let mut executor = WASMEdgeExecutor::new();
+        executor
+            .register_edge_type(r#"{"id":"tested_by","reverse":"tests"}"#)
+            .unwrap();
+        executor.add_named_edge(1, 2, "tested_by").unwrap();
+        executor.add_named_edge(2, 3, "composes_of").unwrap();
+
+        let mut restored = sample();
+        let count = restored
+            .restore(&executor.get_edge_types(), &executor.export_edges())
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(restored.edges(), executor.edges());
+        assert_eq!(restored.get_edge_types(), executor.get_edge_types());
+        assert_eq!(restored.traverse_bfs(1, 10, 0).nodes, vec![1, 2, 3]);
+
+        let error = restored.restore(r#"[{"id":"tests"}]"#, &[]).unwrap_err();
+        assert_eq!(error.code(), "malformed");
+        let error = restored.restore("[]", &[0; 5]).unwrap_err();
+        assert_eq!(error.code(), "malformed");
+        assert_eq!(restored.edge_count(), 2);
+    }
+
+    #[test]
+    fn test_encoded_methods() {
+        let mut executor = sample();
+        let definition = wire::encode_as(&serde_json::json!({"id": "tested_by"}), "cbor").unwrap();
+        let tested_by = executor.register_edge_type_encoded(&definition, "cbor").unwrap();
+        executor.add_named_edge(4, 5, "tested_by").unwrap();
+
+        for format in ["json", "cbor", "msgpack"] {
+            let bytes = executor.traverse_bfs_encoded(1, 10, 0, format).unwrap();
+            let result: TraversalResult = wire::decode_as(&bytes, format).unwrap();
+            assert_eq!(result, executor.traverse_bfs(1, 10, 0));
+
+            let names = wire::encode_as(&["tested_by"], format).unwrap();
+            let bytes = executor.traverse_bfs_types_encoded(4, 10, &names, format).unwrap();
+            let result: TraversalResult = wire::decode_as(&bytes, format).unwrap();
+            assert_eq!(result.nodes, vec![4, 5]);
+
+            let mut restored = WASMEdgeExecutor::new();
+            let edge_types = executor.get_edge_types_encoded(format).unwrap();
+            restored.restore_encoded(&edge_types, format, &executor.export_edges()).unwrap();
+            assert_eq!(restored.edge_type_code("tested_by"), Some(tested_by));
+            assert_eq!(restored.edges(), executor.edges());
+        }
+        assert_eq!(
+            executor.traverse_bfs_encoded(1, 10, 0, "json").unwrap(),
+            executor.traverse_bfs_json(1, 10, 0).unwrap().into_bytes()
+        );
+        let error = executor.get_edge_types_encoded("yaml").unwrap_err();
+        assert_eq!(error.code(), "invalid_input");
+        let error = executor.register_edge_type_encoded(&[0xff], "msgpack").unwrap_err();
+        assert_eq!(error.code(), "malformed");
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let result = sample().traverse_bfs(1, 10, 0);
+        let bytes = result.to_bytes();
+        assert_eq!(bytes.len(), TRAVERSAL_HEADER_SIZE + 4 * VISITED_ENTRY_SIZE + 3 * EDGE_SIZE);
+        assert_eq!(TraversalResult::from_bytes(&bytes), Ok(result));
+        let error = TraversalResult::from_bytes(&bytes[..20]).unwrap_err();
+        assert_eq!(error.code(), "malformed");
+        // Counts whose sizes overflow usize on wasm32
+        let mut huge = bytes.clone();
+        huge[0..8].copy_from_slice(&[0xff; 8]);
+        assert_eq!(TraversalResult::from_bytes(&huge).unwrap_err().code(), "malformed");
+        let decoded = TraversalResult::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            decoded.write_to(&mut [0u8; 8]).unwrap_err().code(),
+            "capacity_exceeded"
+        );
+    }
+
+    #[test]
+    fn test_traversal_spans() {
+        harmony_trace::use_ring_buffer_sink(4);
+        harmony_trace::set_level(Some(Level::Debug));
+        let mut executor = sample();
+        executor
+            .register_edge_type(r#"{"id": "sends_to", "directed": true}"#)
+            .unwrap();
+        executor.traverse_bfs(1, 1, 0);
+
+        let records = harmony_trace::drain();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].message, "register_edge_type");
+        assert_eq!(records[0].fields["id"], "sends_to");
+        assert_eq!(records[1].message, "traverse_bfs");
+        assert_eq!(records[1].target, "wasm-edge-executor");
+        assert_eq!(records[1].fields["visited"], "3");
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_typescript_declarations() {
+        assert_eq!(
+            TraversalResult::DECL,
+            "export interface TraversalResult {\n    nodes: number[];\n    depths: number[];\n    \
+             edges: BinaryEdge[];\n}"
+        );
+        assert_eq!(
+            EdgeBinaryFormat::DECL,
+            "export interface BinaryEdge {\n    source: number;\n    target: number;\n    \
+             edge_type: number;\n}"
+        );
+    }
+}
@@ -0,0 +1,478 @@
+//! GraphML and CSV edge list import
+//!
+//! Both formats name nodes with strings. Parsing gives an `EdgeList` of
+//! string-keyed nodes and edges; `WASMEdgeExecutor::import_edge_list` loads
+//! it into the executor, giving each node a u32 ID through a harmony-ids
+//! `NodeIdMap` that translates traversal results back. harmony-core's
+//! `HarmonyGraph::import_edge_list` loads the same list by string ID.
+//!
+//! CSV: one edge per line, `source,target[,type[,weight]]`. Fields are
+//! trimmed, `#` starts a comment line, and a first line whose first field
+//! is `source` is a header. An empty type means the importer's default.
+//!
+//! GraphML: the `<node>` and `<edge>` elements of the first `<graph>`, as
+//! written by `HarmonyGraph::to_graphml`. Data keys named `node_type`,
+//! `edge_type` (or `type` for either) and `weight` are read; other keys,
+//! nested graphs, ports and hyperedges are ignored. Edges keep the
+//! direction they are written in, even under `edgedefault="undirected"`.
+//!
+//! Both parsers cap the edge count at `max_edges_per_batch`.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-edge-executor
+
+use std::collections::HashMap;
+
+use harmony_error::{limits, HarmonyError, ResultExt};
+use harmony_ids::NodeIdMap;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::edge_binary_format::EdgeBinaryFormat;
+use crate::executor::{WASMEdgeExecutor, DEFAULT_EDGE_WEIGHT};
+
+/// A node named by an imported file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportedNode {
+    pub id: String,
+    /// GraphML `node_type` data; None in CSV
+    pub node_type: Option<String>,
+}
+
+/// An edge read from an imported file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportedEdge {
+    /// GraphML edge `id`; None in CSV
+    pub id: Option<String>,
+    pub source: String,
+    pub target: String,
+    /// Edge type name; None for the importer's default
+    pub edge_type: Option<String>,
+    pub weight: Option<f64>,
+}
+
+/// Nodes and edges parsed from GraphML or CSV
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EdgeList {
+    /// Every node, declared or only named by an edge, in order of first
+    /// appearance
+    pub nodes: Vec<ImportedNode>,
+    /// Edges in file order
+    pub edges: Vec<ImportedEdge>,
+}
+
+impl EdgeList {
+    /// Parses a document in the named format: "graphml" or "csv"
+    pub fn parse(text: &str, format: &str) -> Result<Self, HarmonyError> {
+        match format {
+            "graphml" => parse_graphml(text),
+            "csv" => parse_csv(text),
+            other => Err(HarmonyError::Unsupported(format!(
+                "Unknown import format '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Collects nodes once each, in order of first appearance
+#[derive(Default)]
+struct EdgeListBuilder {
+    list: EdgeList,
+    positions: HashMap<String, usize>,
+}
+
+impl EdgeListBuilder {
+    fn node(&mut self, id: &str, node_type: Option<String>) {
+        match self.positions.get(id) {
+            Some(&position) => {
+                let node = &mut self.list.nodes[position];
+                if node.node_type.is_none() {
+                    node.node_type = node_type;
+                }
+            }
+            None => {
+                self.positions.insert(id.to_string(), self.list.nodes.len());
+                self.list.nodes.push(ImportedNode {
+                    id: id.to_string(),
+                    node_type,
+                });
+            }
+        }
+    }
+
+    fn edge(&mut self, edge: ImportedEdge) -> Result<(), HarmonyError> {
+        limits::check(
+            "Imported edge list",
+            self.list.edges.len() + 1,
+            limits::limits().max_edges_per_batch,
+        )?;
+        self.node(&edge.source, None);
+        self.node(&edge.target, None);
+        self.list.edges.push(edge);
+        Ok(())
+    }
+}
+
+/// Weights must be finite as an `f32`, the executor's weight type
+fn parse_weight(text: &str) -> Result<f64, HarmonyError> {
+    text.parse()
+        .ok()
+        .filter(|weight: &f64| (*weight as f32).is_finite())
+        .ok_or_else(|| HarmonyError::Malformed(format!("Invalid weight '{}'", text)))
+}
+
+/// Parses a CSV edge list; see the module docs
+///
+/// # Returns
+/// `malformed` naming the line of the first bad record
+pub fn parse_csv(text: &str) -> Result<EdgeList, HarmonyError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(text.as_bytes());
+    let mut builder = EdgeListBuilder::default();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|error| HarmonyError::Malformed(error.to_string()))?;
+        let line = record
+            .position()
+            .map_or(index as u64 + 1, |position| position.line());
+        if index == 0
+            && record
+                .get(0)
+                .is_some_and(|field| field.eq_ignore_ascii_case("source"))
+        {
+            continue;
+        }
+        let edge = csv_edge(&record).with_context(|| format!("Line {}", line))?;
+        builder.edge(edge)?;
+    }
+    Ok(builder.list)
+}
+
+fn csv_edge(record: &csv::StringRecord) -> Result<ImportedEdge, HarmonyError> {
+    if !(2..=4).contains(&record.len()) {
+        return Err(HarmonyError::Malformed(format!(
+            "Expected 2 to 4 fields, found {}",
+            record.len()
+        )));
+    }
+    let field = |index: usize| record.get(index).filter(|field| !field.is_empty());
+    let (Some(source), Some(target)) = (field(0), field(1)) else {
+        return Err(HarmonyError::Malformed(
+            "Source and target must not be empty".to_string(),
+        ));
+    };
+    Ok(ImportedEdge {
+        id: None,
+        source: source.to_string(),
+        target: target.to_string(),
+        edge_type: field(2).map(str::to_string),
+        weight: field(3).map(parse_weight).transpose()?,
+    })
+}
+
+/// Parses a GraphML document; see the module docs
+///
+/// # Returns
+/// `malformed` for invalid XML, a missing `<graph>`, or a node or edge
+/// without its required attributes
+pub fn parse_graphml(text: &str) -> Result<EdgeList, HarmonyError> {
+    let document = roxmltree::Document::parse(text)
+        .map_err(|error| HarmonyError::Malformed(error.to_string()))?;
+    let root = document.root_element();
+    if root.tag_name().name() != "graphml" {
+        return Err(HarmonyError::Malformed(format!(
+            "Root element is <{}>, not <graphml>",
+            root.tag_name().name()
+        )));
+    }
+
+    // key id → attr.name
+    let keys: HashMap<&str, &str> = root
+        .children()
+        .filter(|element| element.tag_name().name() == "key")
+        .filter_map(|key| Some((key.attribute("id")?, key.attribute("attr.name")?)))
+        .collect();
+    // Text of the element's first <data> whose key is named one of `names`
+    let data = |element: roxmltree::Node, names: &[&str]| {
+        element
+            .children()
+            .filter(|child| child.tag_name().name() == "data")
+            .find(|child| {
+                child
+                    .attribute("key")
+                    .map(|key| keys.get(key).copied().unwrap_or(key))
+                    .is_some_and(|name| names.contains(&name))
+            })
+            .map(|child| child.text().unwrap_or_default().trim().to_string())
+    };
+
+    let graph = root
+        .children()
+        .find(|child| child.tag_name().name() == "graph")
+        .ok_or_else(|| HarmonyError::Malformed("No <graph> element".to_string()))?;
+    let mut builder = EdgeListBuilder::default();
+    for element in graph.children().filter(roxmltree::Node::is_element) {
+        let line = document.text_pos_at(element.range().start).row;
+        let attribute = |name: &str| {
+            element.attribute(name).ok_or_else(|| {
+                HarmonyError::Malformed(format!(
+                    "<{}> without '{}'",
+                    element.tag_name().name(),
+                    name
+                ))
+            })
+        };
+        let at_line = || format!("Line {}", line);
+        match element.tag_name().name() {
+            "node" => {
+                let id = attribute("id").with_context(at_line)?;
+                builder.node(id, data(element, &["node_type", "type"]));
+            }
+            "edge" => {
+                let weight = data(element, &["weight"]).map(|weight| parse_weight(&weight));
+                builder.edge(ImportedEdge {
+                    id: element.attribute("id").map(str::to_string),
+                    source: attribute("source").with_context(at_line)?.to_string(),
+                    target: attribute("target").with_context(at_line)?.to_string(),
+                    edge_type: data(element, &["edge_type", "type"]),
+                    weight: weight.transpose().with_context(at_line)?,
+                })?;
+            }
+            _ => {}
+        }
+    }
+    Ok(builder.list)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl WASMEdgeExecutor {
+    /// Adds the edges of a CSV edge list (`source,target[,type[,weight]]`)
+    ///
+    /// # Arguments
+    /// * `default_edge_type` - Edge type id for lines without a type
+    ///
+    /// # Returns
+    /// Number of edges added. Node names are interned into the module's
+    /// node ID map (`node_id`, `node_names`). Lines without a weight get
+    /// `DEFAULT_EDGE_WEIGHT`.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = importCSV))]
+    pub fn import_csv(
+        &mut self,
+        text: &str,
+        default_edge_type: &str,
+    ) -> Result<usize, HarmonyError> {
+        let list = parse_csv(text).context("Invalid CSV")?;
+        self.import_interned(&list, default_edge_type)
+    }
+
+    /// Adds the nodes' IDs and edges of a GraphML document
+    ///
+    /// # Arguments
+    /// * `default_edge_type` - Edge type id for edges without `edge_type`
+    ///
+    /// # Returns
+    /// Number of edges added, as for `importCSV`; declared nodes without
+    /// edges get IDs and are added as isolated nodes too
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = importGraphML))]
+    pub fn import_graphml(
+        &mut self,
+        text: &str,
+        default_edge_type: &str,
+    ) -> Result<usize, HarmonyError> {
+        let list = parse_graphml(text).context("Invalid GraphML")?;
+        self.import_interned(&list, default_edge_type)
+    }
+}
+
+impl WASMEdgeExecutor {
+    fn import_interned(
+        &mut self,
+        list: &EdgeList,
+        default_edge_type: &str,
+    ) -> Result<usize, HarmonyError> {
+        harmony_ids::with_node_ids(|ids| self.import_edge_list(list, ids, default_edge_type))
+    }
+
+    /// Adds the edges of an imported list, all or none
+    ///
+    /// Every node of the list, in order, gets an ID from `ids` (keeping
+    /// IDs it already has), so a map can be shared across imports, and is
+    /// added to the executor, with or without edges. Edges without a
+    /// weight get `DEFAULT_EDGE_WEIGHT`.
+    ///
+    /// # Returns
+    /// Number of edges added; `not_found` naming the edge for an unknown
+    /// edge type, in which case neither the executor nor `ids` change
+    pub fn import_edge_list(
+        &mut self,
+        list: &EdgeList,
+        ids: &mut NodeIdMap,
+        default_edge_type: &str,
+    ) -> Result<usize, HarmonyError> {
+        let codes = list
+            .edges
+            .iter()
+            .enumerate()
+            .map(|(index, edge)| {
+                let name = edge.edge_type.as_deref().unwrap_or(default_edge_type);
+                self.edge_type(name)
+                    .with_context(|| format!("Edge {} ({} -> {})", index, edge.source, edge.target))
+            })
+            .collect::<Result<Vec<u32>, HarmonyError>>()?;
+
+        let mut assigned = ids.clone();
+        let nodes = list
+            .nodes
+            .iter()
+            .map(|node| assigned.intern(&node.id))
+            .collect::<Result<Vec<u32>, HarmonyError>>()?;
+        let edges = list
+            .edges
+            .iter()
+            .zip(codes)
+            .map(|(edge, code)| {
+                let source = assigned.intern(&edge.source)?;
+                let target = assigned.intern(&edge.target)?;
+                let weight = edge.weight.map_or(DEFAULT_EDGE_WEIGHT, |weight| weight as f32);
+                Ok((EdgeBinaryFormat::new(source, target, code), weight))
+            })
+            .collect::<Result<Vec<_>, HarmonyError>>()?;
+        self.reserve(0, edges.len())?;
+        *ids = assigned;
+        for (edge, weight) in edges {
+            self.insert_weighted(edge, weight);
+        }
+        self.add_nodes(nodes);
+        Ok(list.edges.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAPHML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="node_type" attr.type="string"/>
+  <key id="d1" for="edge" attr.name="edge_type" attr.type="string"/>
+  <key id="d2" for="edge" attr.name="weight" attr.type="double"/>
+  <graph id="g" edgedefault="directed">
+    <node id="button"><data key="d0">component</data></node>
+    <node id="icon"><data key="d0">component</data></node>
+    <node id="color-primary"><data key="d0">token</data></node>
+    <node id="orphan"/>
+    <edge id="e1" source="button" target="icon"><data key="d1">composes_of</data></edge>
+    <edge source="button" target="color-primary">
+      <data key="d1">uses_token</data>
+      <data key="d2">0.5</data>
+    </edge>
+  </graph>
+</graphml>"#;
+
+    #[test]
+    fn test_parse_csv() {
+        let list = parse_csv(
+            "source,target,type,weight\n# comment\nbutton, icon ,composes_of\nbutton,color-primary,,0.5\n",
+        )
+        .unwrap();
+        let names: Vec<&str> = list.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(names, ["button", "icon", "color-primary"]);
+        assert_eq!(list.edges.len(), 2);
+        assert_eq!(list.edges[0].edge_type.as_deref(), Some("composes_of"));
+        assert_eq!(list.edges[1].edge_type, None);
+        assert_eq!(list.edges[1].weight, Some(0.5));
+
+        let error = parse_csv("a,b\nc\n").unwrap_err();
+        assert_eq!(error.code(), "malformed");
+        assert_eq!(error.to_string(), "Line 2: Expected 2 to 4 fields, found 1");
+        let error = parse_csv("a,b,uses_token,heavy").unwrap_err();
+        assert_eq!(error.to_string(), "Line 1: Invalid weight 'heavy'");
+        let error = parse_csv("a,b,,1e39").unwrap_err();
+        assert_eq!(error.to_string(), "Line 1: Invalid weight '1e39'");
+    }
+
+    #[test]
+    fn test_parse_graphml() {
+        let list = parse_graphml(GRAPHML).unwrap();
+        assert_eq!(list.nodes.len(), 4);
+        assert_eq!(list.nodes[2].node_type.as_deref(), Some("token"));
+        assert_eq!(list.nodes[3].node_type, None);
+        assert_eq!(list.edges[0].id.as_deref(), Some("e1"));
+        assert_eq!(list.edges[1].edge_type.as_deref(), Some("uses_token"));
+        assert_eq!(list.edges[1].weight, Some(0.5));
+
+        assert_eq!(parse_graphml("<graphml").unwrap_err().code(), "malformed");
+        assert_eq!(
+            parse_graphml("<graphml><graph><edge source=\"a\"/></graph></graphml>")
+                .unwrap_err()
+                .to_string(),
+            "Line 1: <edge> without 'target'"
+        );
+        assert_eq!(
+            EdgeList::parse("", "dot").unwrap_err().code(),
+            "unsupported"
+        );
+    }
+
+    #[test]
+    fn test_import_into_executor() {
+        harmony_ids::clear_node_ids();
+        harmony_ids::intern("already-known").unwrap();
+        let mut executor = WASMEdgeExecutor::new();
+        assert_eq!(executor.import_graphml(GRAPHML, "depends_on").unwrap(), 2);
+        assert_eq!(
+            harmony_ids::node_ids().names(),
+            ["already-known", "button", "icon", "color-primary", "orphan"]
+        );
+        let result = executor.traverse_bfs(1, 1, 0);
+        assert_eq!(result.nodes, vec![1, 2, 3]);
+        assert_eq!(executor.isolated_nodes(), vec![4]);
+        assert_eq!(executor.weights(), [DEFAULT_EDGE_WEIGHT, 0.5]);
+        assert_eq!(
+            result.edges[1].edge_type(),
+            executor.edge_type_code("uses_token").unwrap()
+        );
+
+        // A shared map keeps earlier IDs
+        let mut ids = NodeIdMap::new(0);
+        let list = parse_csv("a,b\nb,c,depends_on").unwrap();
+        let mut executor = WASMEdgeExecutor::new();
+        executor
+            .import_edge_list(&list, &mut ids, "composes_of")
+            .unwrap();
+        let more = parse_csv("c,a\nd,a").unwrap();
+        executor
+            .import_edge_list(&more, &mut ids, "composes_of")
+            .unwrap();
+        assert_eq!(ids.id("c"), Some(2));
+        assert_eq!(ids.name(3), Some("d"));
+        assert_eq!(executor.edge_count(), 4);
+
+        let error = executor
+            .import_edge_list(
+                &parse_csv("e,f,no_such_type").unwrap(),
+                &mut ids,
+                "composes_of",
+            )
+            .unwrap_err();
+        assert_eq!(error.code(), "not_found");
+        assert!(error.to_string().starts_with("Edge 0 (e -> f): "));
+        assert_eq!(ids.len(), 4);
+        assert_eq!(executor.edge_count(), 4);
+    }
+
+    #[test]
+    fn test_edge_limit() {
+        limits::set(limits::Limits {
+            max_edges_per_batch: 1,
+            ..limits::Limits::default()
+        });
+        let error = parse_csv("a,b\nb,c\n").unwrap_err();
+        limits::set(limits::Limits::default());
+        assert_eq!(error.code(), "capacity_exceeded");
+    }
+}
@@ -5,7 +5,13 @@
 //!
 //! See: harmony-design/DESIGN_SYSTEM.md#wasm-edge-executor
 
+mod cursor;
 mod edge_binary_format;
+mod executor;
+mod import;
+mod neighborhood;
+#[cfg(feature = "testing")]
+mod testing;
 
 pub use edge_binary_format::{
     EdgeBinaryFormat,
@@ -13,18 +19,41 @@ pub use edge_binary_format::{
     serialize_edges,
     deserialize_edges,
 };
+pub use cursor::TraversalCursor;
+pub use executor::{
+    edge_type_matches,
+    traversal_path,
+    TraversalDirection,
+    TraversalPath,
+    TraversalResult,
+    WASMEdgeExecutor,
+    DEFAULT_EDGE_WEIGHT,
+    TRAVERSAL_HEADER_SIZE,
+    VISITED_ENTRY_SIZE,
+};
+pub use import::{
+    parse_csv,
+    parse_graphml,
+    EdgeList,
+    ImportedEdge,
+    ImportedNode,
+};
+pub use harmony_ids::NodeIdMap;
+#[cfg(feature = "testing")]
+pub use testing::{generate_edges, DegreeDistribution, GraphSpec};
 
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 /// Initialize the WASM module
-#[wasm_bindgen(start)]
+#[cfg_attr(feature = "wasm", wasm_bindgen(start))]
 pub fn init() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
 }
 
 /// Get the version of the edge executor
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
\ No newline at end of file
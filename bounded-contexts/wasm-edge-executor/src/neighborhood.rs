@@ -0,0 +1,146 @@
+//! Neighborhood of a node, for the "focus + context" canvas view
+//!
+//! `neighborhood` gathers the nodes within `depth` hops of a focal node,
+//! following edges either way, closest first, and stops at `max_nodes` so
+//! a hub cannot flood the view. Its edges are every edge between the
+//! gathered nodes, not only the ones the search crossed, so the canvas can
+//! draw the whole local structure; each edge comes after both its ends, so
+//! edges are ordered by distance too.
+//!
+//! The result uses the traversal result binary layout, with distances as
+//! depths, so `decodeTraversalResult` reads it. It is not a BFS tree:
+//! `traversalPath` does not apply.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-edge-executor
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use harmony_trace::Level;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::executor::{TraversalDirection, TraversalResult, WASMEdgeExecutor, TRACE_TARGET};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl WASMEdgeExecutor {
+    /// Nodes around `node_id` and the edges between them, in the binary
+    /// traversal result layout
+    ///
+    /// # Arguments
+    /// * `node_id` - Focal node, always first
+    /// * `depth` - Maximum hop count, following edges either way
+    /// * `max_nodes` - Most nodes to return, the focal node included; 0 for
+    ///   no limit
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = neighborhood))]
+    pub fn neighborhood_binary(&self, node_id: u32, depth: u32, max_nodes: u32) -> Vec<u8> {
+        self.neighborhood(node_id, depth, max_nodes).to_bytes()
+    }
+}
+
+impl WASMEdgeExecutor {
+    /// Nodes within `depth` hops of `node_id`, closest first, with every
+    /// edge between them; see the module docs
+    pub fn neighborhood(&self, node_id: u32, depth: u32, max_nodes: u32) -> TraversalResult {
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "neighborhood");
+        span.field("node_id", node_id)
+            .field("depth", depth)
+            .field("max_nodes", max_nodes);
+        let limit = match max_nodes {
+            0 => usize::MAX,
+            max_nodes => max_nodes as usize,
+        };
+        let mut result = TraversalResult {
+            nodes: vec![node_id],
+            depths: vec![0],
+            edges: Vec::new(),
+        };
+        let mut positions = HashMap::from([(node_id, 0)]);
+
+        // Breadth-first, one node at a time so the limit cuts the last
+        // level in discovery order
+        let mut expanded = 0;
+        while expanded < result.nodes.len() && result.nodes.len() < limit {
+            let (node, distance) = (result.nodes[expanded], result.depths[expanded]);
+            if distance >= depth {
+                break;
+            }
+            for (_, neighbour) in self.adjacent_edges(node, TraversalDirection::Both) {
+                if result.nodes.len() >= limit {
+                    break;
+                }
+                if let Entry::Vacant(entry) = positions.entry(neighbour) {
+                    entry.insert(result.nodes.len());
+                    result.nodes.push(neighbour);
+                    result.depths.push(distance + 1);
+                }
+            }
+            expanded += 1;
+        }
+
+        // Each edge is taken at whichever end comes later
+        for (position, &node) in result.nodes.iter().enumerate() {
+            for (edge, other) in self.adjacent_edges(node, TraversalDirection::Both) {
+                if positions.get(&other).is_some_and(|&p| p <= position) {
+                    result.edges.push(edge);
+                }
+            }
+        }
+        span.field("nodes", result.nodes.len())
+            .field("edges", result.edges.len());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> WASMEdgeExecutor {
+        let mut executor = WASMEdgeExecutor::new();
+        executor.add_edge(1, 2, 0);
+        executor.add_edge(3, 1, 0);
+        executor.add_edge(2, 4, 0);
+        executor.add_edge(4, 5, 0);
+        executor.add_edge(2, 3, 1);
+        executor.add_edge(3, 3, 0);
+        executor
+    }
+
+    fn pairs(result: &TraversalResult) -> Vec<(u32, u32)> {
+        result
+            .edges
+            .iter()
+            .map(|edge| (edge.source(), edge.target()))
+            .collect()
+    }
+
+    #[test]
+    fn test_neighborhood() {
+        let executor = graph();
+        let near = executor.neighborhood(1, 1, 0);
+        assert_eq!(
+            (near.nodes.clone(), near.depths.clone()),
+            (vec![1, 2, 3], vec![0, 1, 1])
+        );
+        // Includes 2 -> 3 between same-distance nodes, and 3's self loop once
+        assert_eq!(pairs(&near), [(1, 2), (3, 1), (3, 3), (2, 3)]);
+
+        let capped = executor.neighborhood(1, 5, 4);
+        assert_eq!(
+            (capped.nodes.clone(), capped.depths.clone()),
+            (vec![1, 2, 3, 4], vec![0, 1, 1, 2])
+        );
+        assert_eq!(pairs(&capped).last(), Some(&(2, 4)));
+        assert_eq!(executor.neighborhood(1, 5, 0).nodes, vec![1, 2, 3, 4, 5]);
+
+        let alone = executor.neighborhood(1, 0, 0);
+        assert_eq!((alone.nodes, alone.edges.len()), (vec![1], 0));
+        assert_eq!(executor.neighborhood(9, 3, 0).nodes, vec![9]);
+
+        assert_eq!(
+            TraversalResult::from_bytes(&executor.neighborhood_binary(5, 2, 0)).unwrap(),
+            executor.neighborhood(5, 2, 0)
+        );
+    }
+}
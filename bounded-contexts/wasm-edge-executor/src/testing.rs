@@ -0,0 +1,233 @@
+//! Synthetic graphs for tests and benchmarks (`testing` feature)
+//!
+//! `generate_edges` builds a random edge list from a `GraphSpec`; the same
+//! spec and seed give the same edges on every platform. Nodes are IDs
+//! `first_node..first_node + nodes`. Sources are uniform; targets follow
+//! the spec's degree distribution:
+//! - `uniform`: every node equally likely (Erdős–Rényi-like)
+//! - `power_law`: preferential attachment, each node weighted by one plus
+//!   its in-degree so far, giving a few hubs and a long tail like real
+//!   dependency graphs
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#wasm-edge-executor
+
+use harmony_error::{limits, HarmonyError, ResultExt};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::edge_binary_format::EdgeBinaryFormat;
+use crate::executor::WASMEdgeExecutor;
+
+/// How edge targets are chosen
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DegreeDistribution {
+    #[default]
+    Uniform,
+    PowerLaw,
+}
+
+/// Shape of a generated graph
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphSpec {
+    /// Number of nodes
+    pub nodes: u32,
+    /// Mean outgoing edges per node; the edge count is
+    /// `round(nodes * average_degree)`
+    pub average_degree: f64,
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub degree: DegreeDistribution,
+    /// Edge type ids, picked uniformly per edge
+    #[serde(default = "default_edge_types")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub edge_types: Vec<String>,
+    /// ID of the first node
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub first_node: u32,
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub seed: u64,
+}
+
+fn default_edge_types() -> Vec<String> {
+    vec!["depends_on".to_string()]
+}
+
+impl GraphSpec {
+    /// Number of edges the spec asks for
+    pub fn edge_count(&self) -> usize {
+        (f64::from(self.nodes) * self.average_degree).round() as usize
+    }
+}
+
+/// Random edges following `spec`, typed with `executor`'s edge type codes
+///
+/// Self loops are left out; parallel edges are allowed.
+///
+/// # Returns
+/// `invalid_input` for a spec without nodes or edge types, or a negative
+/// degree; `capacity_exceeded` above `max_edges_per_batch`; `not_found`
+/// for an unknown edge type
+pub fn generate_edges(
+    executor: &WASMEdgeExecutor,
+    spec: &GraphSpec,
+) -> Result<Vec<EdgeBinaryFormat>, HarmonyError> {
+    if spec.average_degree.is_nan() || spec.average_degree < 0.0 || spec.edge_types.is_empty() {
+        return Err(HarmonyError::InvalidInput(
+            "Graph spec needs a non-negative average_degree and an edge type".to_string(),
+        ));
+    }
+    spec.first_node
+        .checked_add(spec.nodes.saturating_sub(1))
+        .ok_or_else(|| HarmonyError::InvalidInput("Node IDs overflow u32".to_string()))?;
+    let count = spec.edge_count();
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    if spec.nodes < 2 {
+        return Err(HarmonyError::InvalidInput(
+            "A graph with edges needs at least 2 nodes".to_string(),
+        ));
+    }
+    limits::check(
+        "Generated edge count",
+        count,
+        limits::limits().max_edges_per_batch,
+    )?;
+    let codes = spec
+        .edge_types
+        .iter()
+        .map(|name| executor.edge_type(name))
+        .collect::<Result<Vec<u32>, HarmonyError>>()?;
+
+    let mut rng = fastrand::Rng::with_seed(spec.seed);
+    // One entry per node plus one per edge into it, for preferential
+    // attachment: a uniform pick from this list is weighted by in-degree + 1
+    let mut weighted: Vec<u32> = match spec.degree {
+        DegreeDistribution::Uniform => Vec::new(),
+        DegreeDistribution::PowerLaw => (0..spec.nodes).collect(),
+    };
+    let mut edges = Vec::with_capacity(count);
+    while edges.len() < count {
+        let source = rng.u32(0..spec.nodes);
+        let target = match spec.degree {
+            DegreeDistribution::Uniform => rng.u32(0..spec.nodes),
+            DegreeDistribution::PowerLaw => weighted[rng.usize(0..weighted.len())],
+        };
+        if source == target {
+            continue;
+        }
+        if spec.degree == DegreeDistribution::PowerLaw {
+            weighted.push(target);
+        }
+        let code = codes[rng.usize(0..codes.len())];
+        edges.push(EdgeBinaryFormat::new(
+            spec.first_node + source,
+            spec.first_node + target,
+            code,
+        ));
+    }
+    Ok(edges)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl WASMEdgeExecutor {
+    /// Adds a random graph for tests and benchmarks; every node of the
+    /// spec is added, with or without edges
+    ///
+    /// # Arguments
+    /// * `spec_json` - JSON GraphSpec:
+    ///   `{"nodes", "average_degree", "degree"?, "edge_types"?, "first_node"?, "seed"?}`
+    ///
+    /// # Returns
+    /// Number of edges added
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = generateGraph))]
+    pub fn generate_graph(&mut self, spec_json: &str) -> Result<usize, HarmonyError> {
+        limits::check_json(spec_json).context("Invalid graph spec")?;
+        let spec: GraphSpec = serde_json::from_str(spec_json).context("Invalid graph spec")?;
+        let edges = generate_edges(self, &spec).context("Invalid graph spec")?;
+        self.reserve(spec.nodes as usize, edges.len())?;
+        for edge in &edges {
+            self.insert(*edge);
+        }
+        for node in 0..spec.nodes {
+            self.add_node(spec.first_node + node);
+        }
+        Ok(edges.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(degree: DegreeDistribution, seed: u64) -> GraphSpec {
+        GraphSpec {
+            nodes: 500,
+            average_degree: 4.0,
+            degree,
+            edge_types: vec!["depends_on".to_string(), "composes_of".to_string()],
+            first_node: 100,
+            seed,
+        }
+    }
+
+    fn max_in_degree(edges: &[EdgeBinaryFormat]) -> usize {
+        let mut counts = std::collections::HashMap::new();
+        for edge in edges {
+            *counts.entry(edge.target()).or_insert(0) += 1;
+        }
+        counts.into_values().max().unwrap_or(0)
+    }
+
+    #[test]
+    fn test_generated_graphs() {
+        let executor = WASMEdgeExecutor::new();
+        let uniform = generate_edges(&executor, &spec(DegreeDistribution::Uniform, 7)).unwrap();
+        assert_eq!(uniform.len(), 2000);
+        assert_eq!(
+            uniform,
+            generate_edges(&executor, &spec(DegreeDistribution::Uniform, 7)).unwrap()
+        );
+        assert_ne!(
+            uniform,
+            generate_edges(&executor, &spec(DegreeDistribution::Uniform, 8)).unwrap()
+        );
+        assert!(uniform.iter().all(|edge| edge.source() != edge.target()
+            && (100..600).contains(&edge.source())
+            && (100..600).contains(&edge.target())));
+
+        let power_law = generate_edges(&executor, &spec(DegreeDistribution::PowerLaw, 7)).unwrap();
+        assert!(max_in_degree(&power_law) > 2 * max_in_degree(&uniform));
+    }
+
+    #[test]
+    fn test_generate_graph_json() {
+        let mut executor = WASMEdgeExecutor::new();
+        let added = executor
+            .generate_graph(
+                r#"{"nodes": 10, "average_degree": 1.5, "degree": "power_law", "seed": 1}"#,
+            )
+            .unwrap();
+        assert_eq!((added, executor.edge_count()), (15, 15));
+        assert_eq!(executor.list_nodes(), (0..10).collect::<Vec<u32>>());
+
+        let error = executor
+            .generate_graph(r#"{"nodes": 10, "average_degree": 1, "edge_types": ["nope"]}"#)
+            .unwrap_err();
+        assert_eq!(error.code(), "not_found");
+        assert!(error.to_string().starts_with("Invalid graph spec: "));
+        let error = executor
+            .generate_graph(r#"{"nodes": 1, "average_degree": 1}"#)
+            .unwrap_err();
+        assert_eq!(error.code(), "invalid_input");
+        assert_eq!(executor.edge_count(), 15);
+    }
+}
@@ -0,0 +1,77 @@
+//! Benchmark: block kernels vs. their scalar implementations
+//!
+//! Natively the kernels fall back to scalar code, giving the baseline:
+//!
+//! ```text
+//! cargo run --release --example simd_bench
+//! ```
+//!
+//! To measure the SIMD path, run under a WASI runtime:
+//!
+//! ```text
+//! RUSTFLAGS="-C target-feature=+simd128" cargo build --release \
+//!     --example simd_bench --features simd128 --target wasm32-wasip1
+//! wasmtime target/wasm32-wasip1/release/examples/simd_bench.wasm
+//! ```
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use wasm_node_registry::simd::{self, scalar};
+
+const BLOCK: usize = 512;
+const ITERATIONS: u32 = 20_000;
+
+/// Average nanoseconds per block
+fn time(mut kernel: impl FnMut()) -> f64 {
+    for _ in 0..ITERATIONS / 10 {
+        kernel();
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        kernel();
+    }
+    start.elapsed().as_nanos() as f64 / ITERATIONS as f64
+}
+
+fn report(name: &str, kernel_ns: f64, scalar_ns: f64) {
+    println!(
+        "{:<10} {:>9.1} ns {:>9.1} ns {:>7.2}x",
+        name,
+        kernel_ns,
+        scalar_ns,
+        scalar_ns / kernel_ns
+    );
+}
+
+fn main() {
+    let input: Vec<f32> = (0..BLOCK).map(|n| (n as f32 * 0.05).sin() * 1.5).collect();
+    let mut output = vec![0.0; BLOCK];
+
+    println!("SIMD enabled: {}", simd::SIMD_ENABLED);
+    println!(
+        "{:<10} {:>12} {:>12} {:>8}",
+        "kernel", "kernel", "scalar", "speedup"
+    );
+
+    report(
+        "scale",
+        time(|| simd::scale(black_box(&input), 0.5, black_box(&mut output))),
+        time(|| scalar::scale(black_box(&input), 0.5, black_box(&mut output))),
+    );
+    report(
+        "mix_into",
+        time(|| simd::mix_into(black_box(&mut output), black_box(&input))),
+        time(|| scalar::mix_into(black_box(&mut output), black_box(&input))),
+    );
+    report(
+        "hard_clip",
+        time(|| simd::hard_clip(black_box(&input), 2.0, black_box(&mut output))),
+        time(|| scalar::hard_clip(black_box(&input), 2.0, black_box(&mut output))),
+    );
+    report(
+        "crossfade",
+        time(|| simd::crossfade(black_box(&input), black_box(&mut output), 0.3)),
+        time(|| scalar::crossfade(black_box(&input), black_box(&mut output), 0.3)),
+    );
+}
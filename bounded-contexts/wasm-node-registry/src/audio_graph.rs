@@ -0,0 +1,1156 @@
+//! AudioGraph: block-based execution of connected processors
+//!
+//! Builds one processor per graph node (by registry type_id) and wires them
+//! with the edges of a `WASMEdgeExecutor`. Nodes run in topological order;
+//! each node's input is the sum of its sources' outputs, nodes without
+//! sources read the graph input, and the graph output is the sum of nodes
+//! without targets.
+//!
+//! Channel counts are negotiated when the graph is built: each node is
+//! offered the most channels of any source (or the graph input channels)
+//! and picks its layout with `AudioProcessor::channel_layout`; mono
+//! processors get one instance per channel. Ports with different counts
+//! are up/down-mixed (see `channels`). The graph output has the most
+//! channels of any sink. Audio is processed planar, or interleaved with
+//! `process_interleaved`.
+//!
+//! Node outputs live in a `BufferPool` sized from the topology: a node's
+//! buffer returns to the pool once its last target has read it, so long
+//! chains reuse a handful of buffers. Every buffer is allocated when the
+//! graph is built, so `process` never allocates. Input longer than the
+//! block size is processed in blocks.
+//!
+//! Latency reported by processors is summed along each path; where paths
+//! of different latency meet (at a node, or at the graph output), the
+//! shorter ones are delayed to match, and `latency_samples` reports the
+//! total to the host.
+//!
+//! Bypass and dry/wet are handled here rather than by each processor.
+//! `set_bypass` crossfades a node between its processed output and its
+//! input over the ramp time; a fully bypassed node's processors are not
+//! run. The bypassed signal is delayed by the node's latency, so bypassing
+//! never changes path alignment. `set_mix` blends the whole graph's output
+//! with its latency-aligned input, for using a graph as an effect subgraph.
+//!
+//! `serialize_state` saves every processor's state in one PropsBinaryFormat
+//! buffer, keyed by node ID, to store alongside the graph snapshot.
+//!
+//! Builds are traced as a `debug` harmony-trace span; `process` is not
+//! traced.
+//!
+//! Performance Targets:
+//! - Per-block overhead beyond the processors: < 10µs for 32 nodes
+
+use std::collections::{BTreeSet, HashMap};
+
+use harmony_error::{HarmonyError, ResultExt};
+use harmony_trace::Level;
+use wasm_edge_executor::{edge_type_matches, WASMEdgeExecutor};
+
+use crate::buffer_pool::BufferPool;
+use crate::channels::{
+    deinterleave, interleave, mix_channels, split_channels, split_channels_mut, ChannelLayout,
+    MAX_CHANNELS,
+};
+use crate::latency::DelayLine;
+use crate::processors::smoothing::{ramp_samples, SmoothedParam};
+use crate::processors::{AudioProcessor, ProcessorFactory, DEFAULT_RAMP_SECONDS};
+use crate::props_binary_format::{PropType, PropsBinaryDecoder, PropsBinaryFormat};
+
+/// Web Audio render quantum in samples
+pub const RENDER_QUANTUM: usize = 128;
+
+struct AudioNode {
+    id: u32,
+    type_id: String,
+    /// One instance per channel for mono processors, else a single
+    /// multichannel processor
+    processors: Vec<Box<dyn AudioProcessor>>,
+    multichannel: bool,
+    layout: ChannelLayout,
+    /// Indices (into execution order) of the nodes feeding this one
+    sources: Vec<usize>,
+    /// Delay aligning each source with the node's latest-arriving one
+    compensation: Vec<DelayLine>,
+    /// Delay aligning this sink with the graph's total latency
+    output_delay: DelayLine,
+    /// 1 while processing, 0 when bypassed; ramps between the two
+    engaged: SmoothedParam,
+    /// Delays the node's input by the processor latency while bypassed
+    dry_delay: DelayLine,
+    /// Latency of this node's output relative to the graph input
+    latency: usize,
+    /// Number of nodes reading this node's output
+    consumers: usize,
+    /// Whether this node feeds the graph output
+    is_sink: bool,
+}
+
+/// Executable audio processing graph
+pub struct AudioGraph {
+    /// Nodes in execution order
+    nodes: Vec<AudioNode>,
+    index: HashMap<u32, usize>,
+    /// Node output buffers
+    pool: BufferPool,
+    /// Pool buffer holding each node's output, in execution order
+    slots: Vec<usize>,
+    /// Targets yet to read each node's output in the current block
+    remaining: Vec<usize>,
+    /// Mixed input of the node being processed, planar
+    scratch: Vec<f32>,
+    /// Source output being latency-compensated, or a bypassed node's input,
+    /// planar
+    delayed: Vec<f32>,
+    /// Wet level of the graph output, 0 = input only, 1 = graph only
+    mix: SmoothedParam,
+    /// Graph input delayed by the graph latency, planar
+    dry: Vec<f32>,
+    dry_delay: DelayLine,
+    /// Bypass or mix level at each frame of the current block
+    levels: Vec<f32>,
+    /// Total graph latency in samples
+    latency: usize,
+    /// Planar graph input and output for `process_interleaved`
+    planar_input: Vec<f32>,
+    planar_output: Vec<f32>,
+    input_channels: usize,
+    output_channels: usize,
+    sample_rate: f32,
+    block_size: usize,
+}
+
+impl AudioGraph {
+    /// Builds a graph with mono input from nodes and the edges connecting them
+    ///
+    /// # Arguments
+    /// * `factory` - Creates a processor per node type
+    /// * `nodes` - (node ID, registry type_id) pairs
+    /// * `edges` - Edge store; edges touching nodes not in `nodes` are ignored
+    /// * `edge_type_mask` - Bit per edge type carrying audio; 0 = all
+    ///
+    /// # Returns
+    /// An error for duplicate node IDs, unknown node types or cycles
+    pub fn build(
+        factory: &ProcessorFactory,
+        nodes: &[(u32, &str)],
+        edges: &WASMEdgeExecutor,
+        edge_type_mask: u32,
+        sample_rate: f32,
+        block_size: usize,
+    ) -> Result<Self, HarmonyError> {
+        Self::build_with_channels(
+            factory,
+            nodes,
+            edges,
+            edge_type_mask,
+            sample_rate,
+            block_size,
+            1,
+        )
+    }
+
+    /// Builds a graph whose input has `input_channels` channels
+    ///
+    /// See `build`; `input_channels` is clamped to 1..=MAX_CHANNELS.
+    pub fn build_with_channels(
+        factory: &ProcessorFactory,
+        nodes: &[(u32, &str)],
+        edges: &WASMEdgeExecutor,
+        edge_type_mask: u32,
+        sample_rate: f32,
+        block_size: usize,
+        input_channels: usize,
+    ) -> Result<Self, HarmonyError> {
+        let mut span = harmony_trace::span(Level::Debug, "wasm-node-registry", "build_graph");
+        span.field("nodes", nodes.len());
+        let input_channels = input_channels.clamp(1, MAX_CHANNELS);
+        let mut sources: HashMap<u32, BTreeSet<u32>> = HashMap::new();
+        let mut targets: HashMap<u32, BTreeSet<u32>> = HashMap::new();
+        for &(id, _) in nodes {
+            if sources.insert(id, BTreeSet::new()).is_some() {
+                return Err(HarmonyError::InvalidInput(format!(
+                    "Duplicate audio node {}",
+                    id
+                )));
+            }
+            targets.insert(id, BTreeSet::new());
+        }
+        for edge in edges.edges() {
+            let (source, target) = (edge.source(), edge.target());
+            if edge_type_matches(edge_type_mask, edge.edge_type())
+                && sources.contains_key(&source)
+                && sources.contains_key(&target)
+            {
+                if let Some(s) = sources.get_mut(&target) {
+                    s.insert(source);
+                }
+                if let Some(t) = targets.get_mut(&source) {
+                    t.insert(target);
+                }
+            }
+        }
+
+        // Kahn's algorithm, lowest ready ID first for a stable order
+        let mut pending: HashMap<u32, usize> =
+            sources.iter().map(|(&id, s)| (id, s.len())).collect();
+        let mut ready: BTreeSet<u32> = pending
+            .iter()
+            .filter(|(_, &n)| n == 0)
+            .map(|(&id, _)| id)
+            .collect();
+        let mut order = Vec::with_capacity(nodes.len());
+        while let Some(id) = ready.pop_first() {
+            order.push(id);
+            for target in &targets[&id] {
+                let count = pending.get_mut(target).expect("target is a graph node");
+                *count -= 1;
+                if *count == 0 {
+                    ready.insert(*target);
+                }
+            }
+        }
+        if order.len() < nodes.len() {
+            let stuck = nodes
+                .iter()
+                .map(|&(id, _)| id)
+                .filter(|id| !order.contains(id))
+                .min();
+            return Err(HarmonyError::InvalidInput(format!(
+                "Audio graph has a cycle through node {}",
+                stuck.unwrap_or_default()
+            )));
+        }
+
+        let type_ids: HashMap<u32, &str> = nodes.iter().copied().collect();
+        let index: HashMap<u32, usize> = order.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+        let mut audio_nodes: Vec<AudioNode> = Vec::with_capacity(order.len());
+        for &id in &order {
+            let type_id = type_ids[&id];
+            let node_sources: Vec<usize> =
+                sources[&id].iter().map(|source| index[source]).collect();
+            let offered = node_sources
+                .iter()
+                .map(|&source| audio_nodes[source].layout.outputs)
+                .max()
+                .unwrap_or(input_channels);
+
+            let processor = factory.create(type_id)?;
+            let negotiated = processor.channel_layout(offered);
+            let layout = negotiated.unwrap_or_else(|| ChannelLayout::uniform(offered));
+            let mut processors = vec![processor];
+            if negotiated.is_none() {
+                for _ in 1..layout.outputs {
+                    processors.push(factory.create(type_id)?);
+                }
+            }
+            for processor in &mut processors {
+                processor.prepare(sample_rate, block_size);
+            }
+
+            let compensation = node_sources
+                .iter()
+                .map(|&source| DelayLine::new(audio_nodes[source].layout.outputs, 0))
+                .collect();
+            audio_nodes.push(AudioNode {
+                id,
+                type_id: type_id.to_string(),
+                processors,
+                multichannel: negotiated.is_some(),
+                layout,
+                sources: node_sources,
+                compensation,
+                output_delay: DelayLine::new(layout.outputs, 0),
+                engaged: SmoothedParam::new(1.0),
+                dry_delay: DelayLine::new(layout.outputs, 0),
+                latency: 0,
+                consumers: targets[&id].len(),
+                is_sink: targets[&id].is_empty(),
+            });
+        }
+
+        let max_inputs = audio_nodes.iter().map(|node| node.layout.inputs).max();
+        let max_outputs = audio_nodes.iter().map(|node| node.layout.outputs).max();
+        let output_channels = audio_nodes
+            .iter()
+            .filter(|node| node.is_sink)
+            .map(|node| node.layout.outputs)
+            .max()
+            .unwrap_or(input_channels);
+        let mut graph = Self {
+            pool: BufferPool::new(
+                peak_live_buffers(&audio_nodes),
+                block_size * max_outputs.unwrap_or(1),
+            ),
+            slots: vec![0; audio_nodes.len()],
+            remaining: vec![0; audio_nodes.len()],
+            nodes: audio_nodes,
+            index,
+            scratch: vec![0.0; block_size * max_inputs.unwrap_or(1)],
+            delayed: vec![0.0; block_size * max_outputs.unwrap_or(1)],
+            mix: SmoothedParam::new(1.0),
+            dry: vec![0.0; block_size * output_channels],
+            dry_delay: DelayLine::new(output_channels, 0),
+            levels: vec![0.0; block_size],
+            latency: 0,
+            planar_input: vec![0.0; block_size * input_channels],
+            planar_output: vec![0.0; block_size * output_channels],
+            input_channels,
+            output_channels,
+            sample_rate,
+            block_size,
+        };
+        graph.set_ramp_time(DEFAULT_RAMP_SECONDS);
+        graph.update_latency();
+        Ok(graph)
+    }
+
+    /// Processes mono `input` into mono `output` (same length), block by
+    /// block
+    ///
+    /// The input is up-mixed to the graph's input channels and the output
+    /// down-mixed to mono.
+    pub fn process(&mut self, input: &[f32], output: &mut [f32]) {
+        self.process_planar(&[input], &mut [output]);
+    }
+
+    /// Processes planar channels, all of the same length
+    ///
+    /// Inputs and outputs are mixed to and from the graph's channel counts
+    /// when they differ; channels beyond MAX_CHANNELS are ignored.
+    pub fn process_planar(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        let frames = inputs.first().map_or(0, |channel| channel.len());
+        let input_count = inputs.len().min(MAX_CHANNELS);
+        let output_count = outputs.len().min(MAX_CHANNELS);
+        let mut start = 0;
+        while start < frames {
+            let end = frames.min(start + self.block_size);
+            let mut block_inputs: [&[f32]; MAX_CHANNELS] = Default::default();
+            for (view, channel) in block_inputs.iter_mut().zip(inputs) {
+                *view = &channel[start..end];
+            }
+            let mut block_outputs: [&mut [f32]; MAX_CHANNELS] = Default::default();
+            for (view, channel) in block_outputs.iter_mut().zip(outputs.iter_mut()) {
+                *view = &mut channel[start..end];
+            }
+            self.process_block(
+                &block_inputs[..input_count],
+                &mut block_outputs[..output_count],
+                end - start,
+            );
+            start = end;
+        }
+    }
+
+    /// Processes interleaved frames: `input` has `input_channels()` samples
+    /// per frame and `output` has `output_channels()`
+    pub fn process_interleaved(&mut self, input: &[f32], output: &mut [f32]) {
+        let (input_channels, output_channels) = (self.input_channels, self.output_channels);
+        for (input, output) in input
+            .chunks(self.block_size * input_channels)
+            .zip(output.chunks_mut(self.block_size * output_channels))
+        {
+            let frames = input.len() / input_channels;
+            let mut planar_input = std::mem::take(&mut self.planar_input);
+            let mut planar_output = std::mem::take(&mut self.planar_output);
+            {
+                let mut inputs = split_channels_mut(&mut planar_input, input_channels, frames);
+                deinterleave(input, &mut inputs[..input_channels]);
+            }
+            {
+                let inputs = split_channels(&planar_input, input_channels, frames);
+                let mut outputs = split_channels_mut(&mut planar_output, output_channels, frames);
+                self.process_block(
+                    &inputs[..input_channels],
+                    &mut outputs[..output_channels],
+                    frames,
+                );
+            }
+            interleave(
+                &split_channels(&planar_output, output_channels, frames)[..output_channels],
+                output,
+            );
+            self.planar_input = planar_input;
+            self.planar_output = planar_output;
+        }
+    }
+
+    fn process_block(&mut self, input: &[&[f32]], output: &mut [&mut [f32]], len: usize) {
+        if len == 0 {
+            return;
+        }
+        for channel in output.iter_mut() {
+            channel.fill(0.0);
+        }
+        for (remaining, node) in self.remaining.iter_mut().zip(&self.nodes) {
+            *remaining = node.consumers;
+        }
+        for position in 0..self.nodes.len() {
+            let node = &mut self.nodes[position];
+            let ChannelLayout { inputs, outputs } = node.layout;
+            let scratch = &mut self.scratch[..inputs * len];
+            scratch.fill(0.0);
+            {
+                let mut mixed = split_channels_mut(scratch, inputs, len);
+                if node.sources.is_empty() {
+                    mix_channels(input, &mut mixed[..inputs]);
+                }
+                for (&source, compensation) in node.sources.iter().zip(&mut node.compensation) {
+                    let slot = self.slots[source];
+                    let channels = compensation.channels();
+                    let mut source_buffer = &self.pool.get(slot)[..channels * len];
+                    if compensation.delay() > 0 {
+                        let delayed = &mut self.delayed[..channels * len];
+                        delayed.copy_from_slice(source_buffer);
+                        compensation
+                            .process(&mut split_channels_mut(delayed, channels, len)[..channels]);
+                        source_buffer = delayed;
+                    }
+                    let source_channels = split_channels(source_buffer, channels, len);
+                    mix_channels(&source_channels[..channels], &mut mixed[..inputs]);
+                    self.remaining[source] -= 1;
+                    if self.remaining[source] == 0 {
+                        self.pool.release(slot);
+                    }
+                }
+            }
+
+            let slot = self
+                .pool
+                .acquire()
+                .expect("buffer pool is sized for the graph");
+            self.slots[position] = slot;
+            let node_inputs = split_channels(&self.scratch, inputs, len);
+            let mut node_outputs = split_channels_mut(self.pool.get_mut(slot), outputs, len);
+            let bypassed = node.engaged.current() == 0.0 && !node.engaged.is_smoothing();
+            if bypassed {
+                // Filled from the dry signal below
+            } else if node.multichannel {
+                node.processors[0]
+                    .process_planar(&node_inputs[..inputs], &mut node_outputs[..outputs]);
+            } else {
+                for ((processor, input), output) in node
+                    .processors
+                    .iter_mut()
+                    .zip(&node_inputs)
+                    .zip(node_outputs.iter_mut())
+                {
+                    processor.process(input, output);
+                }
+            }
+
+            // Keep the dry line running for latent nodes, so a bypass
+            // starts from the right samples
+            let engaged = node.engaged.current() == 1.0 && !node.engaged.is_smoothing();
+            if !engaged || node.dry_delay.delay() > 0 {
+                let dry = &mut self.delayed[..outputs * len];
+                dry.fill(0.0);
+                let mut dry_channels = split_channels_mut(dry, outputs, len);
+                mix_channels(&node_inputs[..inputs], &mut dry_channels[..outputs]);
+                node.dry_delay.process(&mut dry_channels[..outputs]);
+                if !engaged {
+                    let levels = &mut self.levels[..len];
+                    ramp_levels(&mut node.engaged, levels);
+                    for (wet, dry) in node_outputs.iter_mut().zip(&dry_channels[..outputs]) {
+                        if bypassed {
+                            wet.copy_from_slice(dry);
+                        } else {
+                            crossfade_levels(dry, wet, levels);
+                        }
+                    }
+                }
+            }
+
+            if node.is_sink {
+                let buffer = self.pool.get_mut(slot);
+                node.output_delay
+                    .process(&mut split_channels_mut(buffer, outputs, len)[..outputs]);
+                let produced = split_channels(buffer, outputs, len);
+                mix_channels(&produced[..outputs], output);
+            }
+            if node.consumers == 0 {
+                self.pool.release(slot);
+            }
+        }
+
+        let wet_only = self.mix.current() == 1.0 && !self.mix.is_smoothing();
+        if !wet_only || self.dry_delay.delay() > 0 {
+            let channels = self.output_channels;
+            let dry = &mut self.dry[..channels * len];
+            dry.fill(0.0);
+            let mut dry_channels = split_channels_mut(dry, channels, len);
+            mix_channels(input, &mut dry_channels[..channels]);
+            self.dry_delay.process(&mut dry_channels[..channels]);
+            if !wet_only {
+                let levels = &mut self.levels[..len];
+                ramp_levels(&mut self.mix, levels);
+                for channel in output.iter_mut() {
+                    for (sample, &level) in channel.iter_mut().zip(levels.iter()) {
+                        *sample *= level;
+                    }
+                }
+                for channel in dry_channels[..channels].iter_mut() {
+                    for (sample, &level) in channel.iter_mut().zip(levels.iter()) {
+                        *sample *= 1.0 - level;
+                    }
+                }
+                mix_channels(&split_channels(dry, channels, len)[..channels], output);
+            }
+        }
+    }
+
+    /// Sets a parameter on one node's processor
+    pub fn set_parameter(
+        &mut self,
+        node_id: u32,
+        name: &str,
+        value: f32,
+    ) -> Result<(), HarmonyError> {
+        let position = self.position(node_id)?;
+        let result = self.nodes[position]
+            .processors
+            .iter_mut()
+            .try_for_each(|processor| processor.set_parameter(name, value));
+        self.update_latency();
+        result
+    }
+
+    /// Sets a table parameter, such as a custom waveshaper curve, on one
+    /// node's processor
+    pub fn set_table(
+        &mut self,
+        node_id: u32,
+        name: &str,
+        table: &[f32],
+    ) -> Result<(), HarmonyError> {
+        let position = self.position(node_id)?;
+        self.nodes[position]
+            .processors
+            .iter_mut()
+            .try_for_each(|processor| processor.set_table(name, table))
+    }
+
+    /// Bypasses a node or brings it back, crossfading over the ramp time
+    ///
+    /// A bypassed node passes its input through, mixed to its output
+    /// channels and delayed by its latency. Its processors are reset when
+    /// it comes back, as they did not run while bypassed.
+    pub fn set_bypass(&mut self, node_id: u32, bypassed: bool) -> Result<(), HarmonyError> {
+        let position = self.position(node_id)?;
+        let node = &mut self.nodes[position];
+        if !bypassed && node.engaged.current() == 0.0 && !node.engaged.is_smoothing() {
+            for processor in &mut node.processors {
+                processor.reset();
+            }
+        }
+        node.engaged.set_target(if bypassed { 0.0 } else { 1.0 });
+        Ok(())
+    }
+
+    /// Whether a node is bypassed (or fading out), None for unknown nodes
+    pub fn is_bypassed(&self, node_id: u32) -> Option<bool> {
+        self.index
+            .get(&node_id)
+            .map(|&i| self.nodes[i].engaged.target() == 0.0)
+    }
+
+    /// Sets the wet level of the graph output (0 = input only, 1 = graph
+    /// only), ramping over the ramp time
+    ///
+    /// The input is delayed by the graph latency before mixing.
+    pub fn set_mix(&mut self, wet: f32) {
+        self.mix.set_target(wet.clamp(0.0, 1.0));
+    }
+
+    /// Wet level of the graph output
+    pub fn mix(&self) -> f32 {
+        self.mix.target()
+    }
+
+    /// Sends a MIDI channel message to one node's processor
+    pub fn send_midi(&mut self, node_id: u32, message: [u8; 3]) -> Result<(), HarmonyError> {
+        let position = self.position(node_id)?;
+        for processor in &mut self.nodes[position].processors {
+            processor.midi_event(message);
+        }
+        Ok(())
+    }
+
+    /// Recomputes path latencies and the delays aligning them
+    fn update_latency(&mut self) {
+        for position in 0..self.nodes.len() {
+            let arrival = self.nodes[position]
+                .sources
+                .iter()
+                .map(|&source| self.nodes[source].latency)
+                .max()
+                .unwrap_or(0);
+            for i in 0..self.nodes[position].sources.len() {
+                let source_latency = self.nodes[self.nodes[position].sources[i]].latency;
+                self.nodes[position].compensation[i].set_delay(arrival - source_latency);
+            }
+            let node = &mut self.nodes[position];
+            let latency = node.processors[0].latency_samples();
+            node.dry_delay.set_delay(latency);
+            node.latency = arrival + latency;
+        }
+
+        self.latency = self
+            .nodes
+            .iter()
+            .filter(|node| node.is_sink)
+            .map(|node| node.latency)
+            .max()
+            .unwrap_or(0);
+        for node in self.nodes.iter_mut().filter(|node| node.is_sink) {
+            node.output_delay.set_delay(self.latency - node.latency);
+        }
+        self.dry_delay.set_delay(self.latency);
+    }
+
+    /// Total latency from graph input to output, in samples
+    pub fn latency_samples(&self) -> usize {
+        self.latency
+    }
+
+    fn position(&self, node_id: u32) -> Result<usize, HarmonyError> {
+        self.index
+            .get(&node_id)
+            .copied()
+            .ok_or_else(|| HarmonyError::NotFound(format!("Unknown audio node {}", node_id)))
+    }
+
+    /// Sets the parameter ramp time of every processor, and of bypass and
+    /// mix changes
+    pub fn set_ramp_time(&mut self, seconds: f32) {
+        for processor in self.processors_mut() {
+            processor.set_ramp_time(seconds);
+        }
+        let samples = ramp_samples(seconds, self.sample_rate);
+        for node in &mut self.nodes {
+            node.engaged.set_ramp_samples(samples);
+        }
+        self.mix.set_ramp_samples(samples);
+    }
+
+    /// Clears every processor's state
+    pub fn reset(&mut self) {
+        for node in &mut self.nodes {
+            for processor in &mut node.processors {
+                processor.reset();
+            }
+            for line in &mut node.compensation {
+                line.reset();
+            }
+            node.output_delay.reset();
+            node.dry_delay.reset();
+            node.engaged.snap();
+        }
+        self.dry_delay.reset();
+        self.mix.snap();
+    }
+
+    fn processors_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn AudioProcessor>> {
+        self.nodes.iter_mut().flat_map(|node| &mut node.processors)
+    }
+
+    /// Saves every processor's state
+    ///
+    /// One Array property per node, named by node ID, holding the
+    /// processor's `serialize_state` buffer.
+    pub fn serialize_state(&self) -> Vec<u8> {
+        let mut encoder = PropsBinaryFormat::new();
+        encoder.init_header(self.nodes.len() as u32);
+        for node in &self.nodes {
+            // Node IDs are decimal numbers, well within the name limit
+            let _ = encoder.write_property(
+                &node.id.to_string(),
+                PropType::Array,
+                &node.processors[0].serialize_state(),
+            );
+        }
+        encoder.finalize()
+    }
+
+    /// Restores processor states saved by `serialize_state`
+    ///
+    /// Nodes missing from `state` keep their current state.
+    ///
+    /// # Returns
+    /// An error for malformed buffers or states of nodes not in the graph
+    pub fn restore_state(&mut self, state: &[u8]) -> Result<(), HarmonyError> {
+        let result = self.restore_processors(state);
+        self.update_latency();
+        result
+    }
+
+    fn restore_processors(&mut self, state: &[u8]) -> Result<(), HarmonyError> {
+        let mut decoder = PropsBinaryDecoder::new(state.to_vec())?;
+        for _ in 0..decoder.property_count() {
+            let (name, _, node_state) = decoder.read_property()?;
+            let node_id = name.parse::<u32>().map_err(|_| {
+                HarmonyError::Malformed(format!("Invalid audio node ID '{}'", name))
+            })?;
+            let position = self.position(node_id)?;
+            for processor in &mut self.nodes[position].processors {
+                processor
+                    .restore_state(&node_state)
+                    .with_context(|| format!("Audio node {}", node_id))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Node IDs in execution order
+    pub fn order(&self) -> Vec<u32> {
+        self.nodes.iter().map(|node| node.id).collect()
+    }
+
+    /// Registry type_id of a node
+    pub fn type_id(&self, node_id: u32) -> Option<&str> {
+        self.index
+            .get(&node_id)
+            .map(|&i| self.nodes[i].type_id.as_str())
+    }
+
+    /// Channel layout a node negotiated
+    pub fn channel_layout(&self, node_id: u32) -> Option<ChannelLayout> {
+        self.index.get(&node_id).map(|&i| self.nodes[i].layout)
+    }
+
+    pub fn input_channels(&self) -> usize {
+        self.input_channels
+    }
+
+    /// Channels of the graph output: the most of any sink
+    pub fn output_channels(&self) -> usize {
+        self.output_channels
+    }
+
+    /// Number of pooled node output buffers
+    pub fn buffer_count(&self) -> usize {
+        self.pool.capacity()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+}
+
+/// Advances `level` over a block, writing its value at each frame
+fn ramp_levels(level: &mut SmoothedParam, levels: &mut [f32]) {
+    if level.is_smoothing() {
+        for value in levels {
+            *value = level.advance();
+        }
+    } else {
+        levels.fill(level.current());
+    }
+}
+
+/// `wet = dry + (wet - dry) * level`, with a level per frame
+fn crossfade_levels(dry: &[f32], wet: &mut [f32], levels: &[f32]) {
+    for ((out, &dry), &level) in wet.iter_mut().zip(dry).zip(levels) {
+        *out = dry + (*out - dry) * level;
+    }
+}
+
+/// Most node outputs alive at once when running `nodes` in order
+///
+/// A node's inputs are mixed before it runs, so it can reuse the buffer of
+/// a source it is the last reader of.
+fn peak_live_buffers(nodes: &[AudioNode]) -> usize {
+    let mut remaining: Vec<usize> = nodes.iter().map(|node| node.consumers).collect();
+    let (mut live, mut peak) = (0usize, 0usize);
+    for node in nodes {
+        for &source in &node.sources {
+            remaining[source] -= 1;
+            if remaining[source] == 0 {
+                live -= 1;
+            }
+        }
+        live += 1;
+        peak = peak.max(live);
+        if node.consumers == 0 {
+            live -= 1;
+        }
+    }
+    peak
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processors::curve;
+
+    fn graph(nodes: &[(u32, &str)], edges: &[(u32, u32)]) -> Result<AudioGraph, HarmonyError> {
+        let mut executor = WASMEdgeExecutor::new();
+        for &(source, target) in edges {
+            executor.add_edge(source, target, 0);
+        }
+        AudioGraph::build(&ProcessorFactory::new(), nodes, &executor, 0, 1000.0, 4)
+    }
+
+    fn set_gain(graph: &mut AudioGraph, node_id: u32, gain: f32) {
+        graph.set_parameter(node_id, "gain", gain).unwrap();
+    }
+
+    #[test]
+    fn test_chain_runs_in_topological_order() {
+        // 3 -> 1 -> 2, built in the "wrong" order
+        let mut graph = graph(
+            &[(1, "audio.delay"), (2, "audio.gain"), (3, "audio.gain")],
+            &[(1, 2), (3, 1)],
+        )
+        .unwrap();
+        assert_eq!(graph.order(), vec![3, 1, 2]);
+        graph.set_parameter(1, "time", 0.002).unwrap();
+        graph.set_parameter(1, "mix", 1.0).unwrap();
+        set_gain(&mut graph, 3, 2.0);
+        set_gain(&mut graph, 2, 0.5);
+        graph.reset();
+
+        // Longer than one block: processed as two
+        let mut output = [0.0; 8];
+        graph.process(&[1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0], &mut output);
+        assert_eq!(output, [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(graph.buffer_count(), 1);
+    }
+
+    #[test]
+    fn test_send() {
+        // Built on one thread, processed on another
+        fn assert_send<T: Send>() {}
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send::<AudioGraph>();
+        assert_send_sync::<ProcessorFactory>();
+    }
+
+    #[test]
+    fn test_parallel_branches_are_summed() {
+        // 1 -> {2, 3}; both sinks
+        let mut graph = graph(
+            &[(1, "audio.gain"), (2, "audio.gain"), (3, "audio.gain")],
+            &[(1, 2), (1, 3), (3, 99)],
+        )
+        .unwrap();
+        set_gain(&mut graph, 2, 0.5);
+        set_gain(&mut graph, 3, 0.25);
+        graph.reset();
+        let mut output = [0.0; 2];
+        graph.process(&[1.0, -1.0], &mut output);
+        assert_eq!(output, [0.75, -0.75]);
+        assert_eq!(graph.type_id(2), Some("audio.gain"));
+        assert_eq!(graph.buffer_count(), 2);
+    }
+
+    #[test]
+    fn test_buffers_are_reused_across_branches() {
+        // Two chains merging: 1 -> 2 -> 5, 3 -> 4 -> 5
+        let mut graph = graph(
+            &[
+                (1, "audio.gain"),
+                (2, "audio.gain"),
+                (3, "audio.gain"),
+                (4, "audio.gain"),
+                (5, "audio.gain"),
+            ],
+            &[(1, 2), (2, 5), (3, 4), (4, 5)],
+        )
+        .unwrap();
+        assert_eq!(graph.order(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(graph.buffer_count(), 2);
+
+        set_gain(&mut graph, 2, 0.5);
+        set_gain(&mut graph, 4, 0.25);
+        graph.reset();
+        let mut output = [0.0; 4];
+        for _ in 0..2 {
+            graph.process(&[1.0, -1.0, 0.5, 0.0], &mut output);
+            assert_eq!(output, [0.75, -0.75, 0.375, 0.0]);
+        }
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let nodes = [
+            (1, "audio.gain"),
+            (2, "audio.biquad"),
+            (3, "audio.waveshaper"),
+        ];
+        let edges = [(1, 2), (2, 3)];
+        let mut original = graph(&nodes, &edges).unwrap();
+        set_gain(&mut original, 1, 0.5);
+        original.set_parameter(2, "type", 1.0).unwrap();
+        original.set_parameter(2, "frequency", 80.0).unwrap();
+        original.set_parameter(3, "oversampling", 2.0).unwrap();
+        let state = original.serialize_state();
+
+        let mut restored = graph(&nodes, &edges).unwrap();
+        restored.restore_state(&state).unwrap();
+        assert_eq!(restored.serialize_state(), state);
+
+        let mut other = graph(&[(1, "audio.gain")], &[]).unwrap();
+        assert!(other.restore_state(&state).is_err());
+        assert!(other.restore_state(&[0; 4]).is_err());
+    }
+
+    /// Hard-left panner: mono in, stereo out
+    struct PanLeft;
+
+    impl AudioProcessor for PanLeft {
+        fn prepare(&mut self, _sample_rate: f32, _max_block_size: usize) {}
+
+        fn process(&mut self, input: &[f32], output: &mut [f32]) {
+            output.copy_from_slice(input);
+        }
+
+        fn channel_layout(&self, _offered: usize) -> Option<ChannelLayout> {
+            Some(ChannelLayout::new(1, 2))
+        }
+
+        fn process_planar(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+            outputs[0].copy_from_slice(inputs[0]);
+            outputs[1].fill(0.0);
+        }
+
+        fn set_parameter(&mut self, name: &str, _value: f32) -> Result<(), HarmonyError> {
+            Err(HarmonyError::NotFound(format!(
+                "Unknown pan parameter '{}'",
+                name
+            )))
+        }
+
+        fn reset(&mut self) {}
+    }
+
+    #[test]
+    fn test_channel_negotiation() {
+        // 1 (pan, mono -> stereo) -> 2 (gain); 3 (gain, mono) -> 2
+        let mut factory = ProcessorFactory::new();
+        factory.register("test.pan-left", || Box::new(PanLeft));
+        let mut edges = WASMEdgeExecutor::new();
+        edges.add_edge(1, 2, 0);
+        edges.add_edge(3, 2, 0);
+        let nodes = [(1, "test.pan-left"), (2, "audio.gain"), (3, "audio.gain")];
+        let mut graph = AudioGraph::build(&factory, &nodes, &edges, 0, 1000.0, 4).unwrap();
+        assert_eq!(graph.channel_layout(1), Some(ChannelLayout::new(1, 2)));
+        assert_eq!(graph.channel_layout(2), Some(ChannelLayout::uniform(2)));
+        assert_eq!(graph.channel_layout(3), Some(ChannelLayout::uniform(1)));
+        assert_eq!((graph.input_channels(), graph.output_channels()), (1, 2));
+
+        set_gain(&mut graph, 2, 0.5);
+        set_gain(&mut graph, 3, 2.0);
+        graph.reset();
+        let (mut left, mut right) = ([0.0; 2], [0.0; 2]);
+        graph.process_planar(&[&[1.0, -0.5]], &mut [&mut left, &mut right]);
+        // Left: (pan + gain 3) * 0.5, right: gain 3 only (mono up-mixed)
+        assert_eq!(left, [1.5, -0.75]);
+        assert_eq!(right, [1.0, -0.5]);
+
+        // Mono output is the down-mix
+        let mut mono = [0.0; 2];
+        graph.process(&[1.0, -0.5], &mut mono);
+        assert_eq!(mono, [1.25, -0.625]);
+    }
+
+    #[test]
+    fn test_interleaved_stereo() {
+        let mut executor = WASMEdgeExecutor::new();
+        executor.add_edge(1, 2, 0);
+        let nodes = [(1, "audio.delay"), (2, "audio.gain")];
+        let mut graph = AudioGraph::build_with_channels(
+            &ProcessorFactory::new(),
+            &nodes,
+            &executor,
+            0,
+            1000.0,
+            2,
+            2,
+        )
+        .unwrap();
+        assert_eq!(graph.output_channels(), 2);
+        graph.set_parameter(1, "time", 0.001).unwrap();
+        graph.set_parameter(1, "mix", 1.0).unwrap();
+        set_gain(&mut graph, 2, 0.5);
+        graph.reset();
+
+        // Each channel has its own delay line; 3 frames span two blocks
+        let mut output = [0.0; 6];
+        graph.process_interleaved(&[1.0, 0.0, 0.0, -1.0, 0.0, 0.0], &mut output);
+        assert_eq!(output, [0.0, 0.0, 0.5, 0.0, 0.0, -0.5]);
+    }
+
+    /// Pure delay of 3 samples that reports it as latency
+    struct Latent {
+        history: [f32; 3],
+    }
+
+    impl AudioProcessor for Latent {
+        fn prepare(&mut self, _sample_rate: f32, _max_block_size: usize) {}
+
+        fn process(&mut self, input: &[f32], output: &mut [f32]) {
+            for (out, &sample) in output.iter_mut().zip(input) {
+                *out = self.history[2];
+                self.history = [sample, self.history[0], self.history[1]];
+            }
+        }
+
+        fn latency_samples(&self) -> usize {
+            3
+        }
+
+        fn set_parameter(&mut self, name: &str, _value: f32) -> Result<(), HarmonyError> {
+            Err(HarmonyError::NotFound(format!(
+                "Unknown parameter '{}'",
+                name
+            )))
+        }
+
+        fn reset(&mut self) {
+            self.history = [0.0; 3];
+        }
+    }
+
+    #[test]
+    fn test_latency_compensation() {
+        // 1 -> 2 (latent) -> 4 and 1 -> 3 -> 4; 5 (latent) and 6 are sinks
+        let mut factory = ProcessorFactory::new();
+        factory.register("test.latent", || Box::new(Latent { history: [0.0; 3] }));
+        let mut edges = WASMEdgeExecutor::new();
+        for (source, target) in [(1, 2), (2, 4), (1, 3), (3, 4), (4, 5), (4, 6)] {
+            edges.add_edge(source, target, 0);
+        }
+        let nodes = [
+            (1, "audio.gain"),
+            (2, "test.latent"),
+            (3, "audio.gain"),
+            (4, "audio.gain"),
+            (5, "test.latent"),
+            (6, "audio.gain"),
+        ];
+        let mut aligned = AudioGraph::build(&factory, &nodes, &edges, 0, 1000.0, 4).unwrap();
+        assert_eq!(aligned.latency_samples(), 6);
+
+        // Both branches arrive at 4 after 3 samples, both sinks at 6
+        let mut impulse = [0.0; 8];
+        impulse[0] = 1.0;
+        let mut output = [0.0; 8];
+        aligned.process(&impulse, &mut output);
+        assert_eq!(output, [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 4.0, 0.0]);
+
+        // Oversampling changes latency at runtime
+        let mut oversampled =
+            graph(&[(1, "audio.gain"), (2, "audio.waveshaper")], &[(1, 2)]).unwrap();
+        assert_eq!(oversampled.latency_samples(), 0);
+        oversampled.set_parameter(2, "oversampling", 2.0).unwrap();
+        assert_eq!(oversampled.latency_samples(), 15);
+    }
+
+    #[test]
+    fn test_bypass_crossfade() {
+        let mut graph = graph(&[(1, "audio.gain")], &[]).unwrap();
+        set_gain(&mut graph, 1, 0.5);
+        graph.set_ramp_time(0.004);
+        graph.reset();
+
+        graph.set_bypass(1, true).unwrap();
+        assert_eq!(graph.is_bypassed(1), Some(true));
+        let mut output = [0.0; 6];
+        graph.process(&[1.0; 6], &mut output);
+        assert_eq!(output, [0.625, 0.75, 0.875, 1.0, 1.0, 1.0]);
+
+        graph.set_bypass(1, false).unwrap();
+        graph.process(&[1.0; 6], &mut output);
+        assert_eq!(output, [0.875, 0.75, 0.625, 0.5, 0.5, 0.5]);
+        assert_eq!(graph.is_bypassed(1), Some(false));
+        assert!(graph.set_bypass(9, true).is_err());
+        assert_eq!(graph.is_bypassed(9), None);
+    }
+
+    #[test]
+    fn test_bypass_keeps_alignment_and_mix() {
+        // 1 (latent) -> 2, with 1 bypassed the impulse still lands at 3
+        let mut factory = ProcessorFactory::new();
+        factory.register("test.latent", || Box::new(Latent { history: [0.0; 3] }));
+        let mut edges = WASMEdgeExecutor::new();
+        edges.add_edge(1, 2, 0);
+        let nodes = [(1, "test.latent"), (2, "audio.gain")];
+        let mut graph = AudioGraph::build(&factory, &nodes, &edges, 0, 1000.0, 4).unwrap();
+        graph.set_ramp_time(0.0);
+        set_gain(&mut graph, 2, 0.5);
+
+        let mut impulse = [0.0; 6];
+        impulse[0] = 1.0;
+        let mut output = [0.0; 6];
+        graph.set_bypass(1, true).unwrap();
+        graph.process(&impulse, &mut output);
+        assert_eq!(output, [0.0, 0.0, 0.0, 0.5, 0.0, 0.0]);
+
+        // Half the latency-aligned input, half the graph
+        graph.set_bypass(1, false).unwrap();
+        graph.set_mix(0.5);
+        assert_eq!(graph.mix(), 0.5);
+        graph.process(&impulse, &mut output);
+        assert_eq!(output, [0.0, 0.0, 0.0, 0.75, 0.0, 0.0]);
+
+        graph.set_mix(0.0);
+        graph.process(&impulse, &mut output);
+        assert_eq!(output, [0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_custom_curve_table() {
+        let mut graph = graph(&[(1, "audio.waveshaper")], &[]).unwrap();
+        let table = curve::from_expression("x ^ 3", curve::DEFAULT_TABLE_SIZE).unwrap();
+        graph.set_table(1, "curve", &table).unwrap();
+
+        let mut output = [0.0; 2];
+        graph.process(&[1.0, -1.0], &mut output);
+        assert_eq!(output, [1.0, -1.0]);
+
+        assert!(graph.set_table(1, "curve", &table[..100]).is_err());
+        assert!(graph.set_table(1, "shape", &table).is_err());
+        assert!(graph.set_table(7, "curve", &table).is_err());
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let cyclic = graph(&[(1, "audio.gain"), (2, "audio.gain")], &[(1, 2), (2, 1)]);
+        assert!(
+            matches!(cyclic, Err(e) if e.to_string() == "Audio graph has a cycle through node 1")
+        );
+        assert!(graph(&[(1, "audio.theremin")], &[]).is_err());
+        assert!(graph(&[(1, "audio.gain"), (1, "audio.delay")], &[]).is_err());
+
+        let mut graph = graph(&[(1, "audio.gain")], &[]).unwrap();
+        assert!(graph.set_parameter(2, "gain", 1.0).is_err());
+    }
+
+    #[test]
+    fn test_edge_type_mask() {
+        let mut executor = WASMEdgeExecutor::new();
+        executor.add_edge(1, 2, 0);
+        executor.add_edge(2, 1, 1);
+        let nodes = [(1, "audio.gain"), (2, "audio.gain")];
+        let graph = AudioGraph::build(
+            &ProcessorFactory::new(),
+            &nodes,
+            &executor,
+            1,
+            48000.0,
+            RENDER_QUANTUM,
+        )
+        .unwrap();
+        assert_eq!(graph.order(), vec![1, 2]);
+        assert_eq!(graph.block_size(), RENDER_QUANTUM);
+    }
+}
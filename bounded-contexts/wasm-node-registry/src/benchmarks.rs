@@ -0,0 +1,273 @@
+//! Benchmarks: in-app checks of the documented performance targets
+//!
+//! Builds synthetic graphs and registries of a chosen size, times the
+//! operations the targets are written for and reports per-operation
+//! timings against each target, so a host can catch regressions on the
+//! machine it runs on:
+//! - `edge_traversal`: BFS over every edge of a graph, < 1ms per 1000 edges
+//! - `registry_lookup`: finding the processor constructor for a type_id,
+//!   < 0.1ms; constructing the processor is not timed
+//!
+//! Synthetic data is generated from a seed, `DEFAULT_SEED` unless one is
+//! given, so a size and seed give the same graph on every run. Every graph
+//! node is reachable from node 0.
+//!
+//! The JS exports return `BenchmarkReport`s as JSON. Times come from
+//! `performance.now()` in browsers and `Instant` natively. Run release
+//! builds; debug builds are far slower than the targets assume.
+
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+use wasm_edge_executor::WASMEdgeExecutor;
+
+use crate::processors::{GainProcessor, ProcessorFactory};
+
+/// Target for one traversal, per 1000 edges
+pub const EDGE_TRAVERSAL_TARGET_MS_PER_1000: f64 = 1.0;
+
+/// Target for one registry lookup
+pub const REGISTRY_LOOKUP_TARGET_MS: f64 = 0.1;
+
+/// Seed for the synthetic data the benchmarks time
+pub const DEFAULT_SEED: u32 = 0x1234_5678;
+
+/// Timings of one benchmark, per operation, in milliseconds
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchmarkReport {
+    pub name: String,
+    /// Edges in the graph, or types in the registry
+    pub size: usize,
+    pub iterations: usize,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub target_ms: f64,
+    /// Whether the median meets the target
+    pub passed: bool,
+}
+
+impl BenchmarkReport {
+    fn from_samples(name: &str, size: usize, mut samples: Vec<f64>, target_ms: f64) -> Self {
+        samples.sort_by(f64::total_cmp);
+        let iterations = samples.len();
+        let median_ms = samples[iterations / 2];
+        Self {
+            name: name.to_string(),
+            size,
+            iterations,
+            mean_ms: samples.iter().sum::<f64>() / iterations as f64,
+            median_ms,
+            min_ms: samples[0],
+            max_ms: samples[iterations - 1],
+            target_ms,
+            passed: median_ms <= target_ms,
+        }
+    }
+}
+
+/// Milliseconds since an arbitrary start, for timing intervals
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    // performance is on the global object in windows and workers alike
+    let performance = js_sys::Reflect::get(&js_sys::global(), &"performance".into())
+        .ok()
+        .filter(|performance| !performance.is_undefined());
+    performance
+        .and_then(|performance| {
+            let now: js_sys::Function = js_sys::Reflect::get(&performance, &"now".into())
+                .ok()?
+                .dyn_into()
+                .ok()?;
+            now.call0(&performance).ok()?.as_f64()
+        })
+        .unwrap_or_else(js_sys::Date::now)
+}
+
+/// Milliseconds since an arbitrary start, for timing intervals
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+fn now_ms() -> f64 {
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    START
+        .get_or_init(std::time::Instant::now)
+        .elapsed()
+        .as_secs_f64()
+        * 1000.0
+}
+
+/// Deterministic pseudo-random numbers (xorshift32)
+struct Rng(u32);
+
+impl Rng {
+    /// xorshift can't leave a zero state, so a zero seed takes the default
+    fn new(seed: u32) -> Self {
+        Self(if seed == 0 { DEFAULT_SEED } else { seed })
+    }
+
+    fn below(&mut self, bound: u32) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 % bound.max(1)
+    }
+}
+
+/// Builds a graph with `edge_count` edges over about `edge_count / 4`
+/// nodes: a random spanning tree rooted at node 0, then random edges.
+/// Edge types cycle through the first four built-in types; the same seed
+/// gives the same graph.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn synthetic_graph(edge_count: usize, seed: u32) -> WASMEdgeExecutor {
+    let node_count = (edge_count / 4 + 1).max(2) as u32;
+    let mut rng = Rng::new(seed);
+    let mut executor = WASMEdgeExecutor::new();
+    for index in 0..edge_count {
+        let edge_type = index as u32 % 4;
+        let index = index as u32;
+        let (source, target) = if index + 1 < node_count {
+            (rng.below(index + 1), index + 1)
+        } else {
+            (rng.below(node_count), rng.below(node_count))
+        };
+        executor.add_edge(source, target, edge_type);
+    }
+    executor
+}
+
+/// Builds a factory with the built-in processors plus `type_count`
+/// synthetic types named `bench.type-<n>`
+pub fn synthetic_factory(type_count: usize) -> (ProcessorFactory, Vec<String>) {
+    let mut factory = ProcessorFactory::new();
+    let type_ids: Vec<String> = (0..type_count)
+        .map(|n| format!("bench.type-{}", n))
+        .collect();
+    for type_id in &type_ids {
+        factory.register(type_id, || Box::new(GainProcessor::new()));
+    }
+    (factory, type_ids)
+}
+
+/// Times `iterations` full traversals of a synthetic graph; the target
+/// scales with the edge count, and is 1ms at least
+pub fn edge_traversal(edge_count: usize, iterations: usize) -> BenchmarkReport {
+    let executor = synthetic_graph(edge_count, DEFAULT_SEED);
+    // Warm up caches and the allocator before timing
+    std::hint::black_box(executor.traverse_bfs(0, u32::MAX, 0));
+    let samples = (0..iterations.max(1))
+        .map(|_| {
+            let start = now_ms();
+            std::hint::black_box(executor.traverse_bfs(0, u32::MAX, 0));
+            now_ms() - start
+        })
+        .collect();
+    let target_ms = EDGE_TRAVERSAL_TARGET_MS_PER_1000 * (edge_count as f64 / 1000.0).max(1.0);
+    BenchmarkReport::from_samples("edge_traversal", edge_count, samples, target_ms)
+}
+
+/// Times lookups in a registry of `type_count` synthetic types; each
+/// iteration looks up every type once, in random order
+///
+/// Only the type_id to constructor lookup is timed; the processor is not
+/// built, so allocation and processor setup do not count.
+pub fn registry_lookup(type_count: usize, iterations: usize) -> BenchmarkReport {
+    let (factory, type_ids) = synthetic_factory(type_count.max(1));
+    let mut rng = Rng::new(DEFAULT_SEED);
+    let mut samples = Vec::with_capacity(iterations.max(1));
+    for _ in 0..iterations.max(1) {
+        let order: Vec<&str> = (0..type_ids.len())
+            .map(|_| type_ids[rng.below(type_ids.len() as u32) as usize].as_str())
+            .collect();
+        let start = now_ms();
+        for type_id in &order {
+            std::hint::black_box(factory.constructor(type_id));
+        }
+        samples.push((now_ms() - start) / order.len() as f64);
+    }
+    BenchmarkReport::from_samples(
+        "registry_lookup",
+        type_ids.len(),
+        samples,
+        REGISTRY_LOOKUP_TARGET_MS,
+    )
+}
+
+/// Runs the edge traversal benchmark
+///
+/// # Returns
+/// JSON `BenchmarkReport`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn bench_edge_traversal(edge_count: usize, iterations: usize) -> String {
+    to_json(&edge_traversal(edge_count, iterations))
+}
+
+/// Runs the registry lookup benchmark
+///
+/// # Returns
+/// JSON `BenchmarkReport`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn bench_registry_lookup(type_count: usize, iterations: usize) -> String {
+    to_json(&registry_lookup(type_count, iterations))
+}
+
+/// Runs every benchmark
+///
+/// # Returns
+/// JSON array of `BenchmarkReport`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn run_benchmarks(edge_count: usize, type_count: usize, iterations: usize) -> String {
+    to_json(&[
+        edge_traversal(edge_count, iterations),
+        registry_lookup(type_count, iterations),
+    ])
+}
+
+fn to_json(value: &impl Serialize) -> String {
+    // Reports hold only strings, numbers and bools
+    serde_json::to_string(value).expect("benchmark reports serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_graph_is_connected() {
+        let executor = synthetic_graph(1000, DEFAULT_SEED);
+        assert_eq!(executor.edge_count(), 1000);
+        let result = executor.traverse_bfs(0, u32::MAX, 0);
+        assert_eq!(result.nodes.len(), 251);
+        assert_eq!(synthetic_graph(1000, 0).edges(), executor.edges());
+        assert_ne!(synthetic_graph(1000, 7).edges(), executor.edges());
+    }
+
+    #[test]
+    fn test_reports() {
+        let report = edge_traversal(1000, 5);
+        assert_eq!(report.name, "edge_traversal");
+        assert_eq!((report.size, report.iterations), (1000, 5));
+        assert!(report.min_ms <= report.median_ms && report.median_ms <= report.max_ms);
+        assert_eq!(report.target_ms, 1.0);
+        assert_eq!(report.passed, report.median_ms <= 1.0);
+
+        let reports: serde_json::Value = serde_json::from_str(&run_benchmarks(100, 20, 0)).unwrap();
+        assert_eq!(reports[1]["name"], "registry_lookup");
+        assert_eq!(reports[1]["size"], 20);
+        assert_eq!(reports[1]["iterations"], 1);
+        assert_eq!(reports[1]["target_ms"], 0.1);
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_typescript_declarations() {
+        assert_eq!(
+            BenchmarkReport::DECL,
+            "export interface BenchmarkReport {\n    name: string;\n    size: number;\n    \
+             iterations: number;\n    mean_ms: number;\n    median_ms: number;\n    \
+             min_ms: number;\n    max_ms: number;\n    target_ms: number;\n    passed: boolean;\n}"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! BufferPool: preallocated f32 buffers for the audio thread
+//!
+//! Hands out fixed-length buffers by index and takes them back for reuse.
+//! All buffers and the free list are allocated up front, so `acquire` and
+//! `release` never allocate. `AudioGraph` sizes its pool from the graph
+//! topology: the most node outputs alive at once, not one per node.
+
+/// Fixed set of reusable audio buffers
+pub struct BufferPool {
+    buffers: Vec<Vec<f32>>,
+    /// Free buffer indices; the lowest index is handed out first
+    free: Vec<usize>,
+    /// Whether each buffer is handed out
+    in_use: Vec<bool>,
+}
+
+impl BufferPool {
+    /// Create `count` zeroed buffers of `buffer_len` samples
+    pub fn new(count: usize, buffer_len: usize) -> Self {
+        Self {
+            buffers: vec![vec![0.0; buffer_len]; count],
+            free: (0..count).rev().collect(),
+            in_use: vec![false; count],
+        }
+    }
+
+    /// Take a free buffer
+    ///
+    /// # Returns
+    /// The buffer's index, or None if every buffer is in use
+    pub fn acquire(&mut self) -> Option<usize> {
+        let index = self.free.pop()?;
+        self.in_use[index] = true;
+        Some(index)
+    }
+
+    /// Return a buffer to the pool; releasing a free buffer does nothing
+    pub fn release(&mut self, index: usize) {
+        if let Some(in_use) = self.in_use.get_mut(index) {
+            if *in_use {
+                *in_use = false;
+                self.free.push(index);
+            }
+        }
+    }
+
+    /// Return every buffer to the pool
+    pub fn release_all(&mut self) {
+        self.free.clear();
+        self.free.extend((0..self.buffers.len()).rev());
+        self.in_use.fill(false);
+    }
+
+    pub fn get(&self, index: usize) -> &[f32] {
+        &self.buffers[index]
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> &mut [f32] {
+        &mut self.buffers[index]
+    }
+
+    /// Total number of buffers
+    pub fn capacity(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Number of buffers not handed out
+    pub fn available(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Samples per buffer
+    pub fn buffer_len(&self) -> usize {
+        self.buffers.first().map_or(0, Vec::len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acquire_and_release() {
+        let mut pool = BufferPool::new(2, 4);
+        assert_eq!(pool.buffer_len(), 4);
+
+        let first = pool.acquire().unwrap();
+        let second = pool.acquire().unwrap();
+        assert_eq!((first, second), (0, 1));
+        assert_eq!(pool.acquire(), None);
+
+        pool.get_mut(second).fill(0.5);
+        pool.release(second);
+        pool.release(second);
+        assert_eq!(pool.available(), 1);
+        assert_eq!(pool.acquire(), Some(second));
+        assert_eq!(pool.get(second), &[0.5; 4]);
+
+        pool.release_all();
+        assert_eq!(pool.available(), pool.capacity());
+        assert_eq!(pool.acquire(), Some(0));
+    }
+}
@@ -0,0 +1,176 @@
+//! Multichannel audio: layouts, up/down-mixing and interleaving
+//!
+//! Processors work on planar audio: one slice per channel. Connected ports
+//! with different channel counts are mixed with the Web Audio "speakers"
+//! rules for mono and stereo, and discretely otherwise:
+//! - mono -> stereo or more: copied to the first two channels
+//! - stereo -> mono: (L + R) / 2
+//! - anything else: channel i to channel i, extra channels dropped
+//!
+//! Planar views are fixed arrays of slices on the stack, so splitting a
+//! buffer into channels never allocates.
+
+use crate::simd;
+
+/// Most channels a port carries
+pub const MAX_CHANNELS: usize = 8;
+
+/// Channel counts a processor negotiated for its input and output ports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelLayout {
+    pub inputs: usize,
+    pub outputs: usize,
+}
+
+impl ChannelLayout {
+    /// Create a layout, clamping both counts to 1..=MAX_CHANNELS
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        Self {
+            inputs: inputs.clamp(1, MAX_CHANNELS),
+            outputs: outputs.clamp(1, MAX_CHANNELS),
+        }
+    }
+
+    /// Same channel count in and out
+    pub fn uniform(channels: usize) -> Self {
+        Self::new(channels, channels)
+    }
+}
+
+/// Mixes `sources` into `targets` (adding), converting the channel count
+///
+/// All slices have the same length.
+pub fn mix_channels(sources: &[&[f32]], targets: &mut [&mut [f32]]) {
+    match (sources.len(), targets.len()) {
+        (_, 0) | (0, _) => {}
+        (1, n) if n >= 2 => {
+            simd::mix_into(targets[0], sources[0]);
+            simd::mix_into(targets[1], sources[0]);
+        }
+        (2, 1) => {
+            for ((out, &left), &right) in targets[0].iter_mut().zip(sources[0]).zip(sources[1]) {
+                *out += 0.5 * (left + right);
+            }
+        }
+        _ => {
+            for (target, source) in targets.iter_mut().zip(sources) {
+                simd::mix_into(target, source);
+            }
+        }
+    }
+}
+
+/// Splits interleaved frames into planar channels (one per `planar` slice)
+pub fn deinterleave(interleaved: &[f32], planar: &mut [&mut [f32]]) {
+    let channels = planar.len();
+    if channels == 0 {
+        return;
+    }
+    for (frame, samples) in interleaved.chunks_exact(channels).enumerate() {
+        for (channel, &sample) in planar.iter_mut().zip(samples) {
+            channel[frame] = sample;
+        }
+    }
+}
+
+/// Writes planar channels as interleaved frames
+pub fn interleave(planar: &[&[f32]], interleaved: &mut [f32]) {
+    let channels = planar.len();
+    if channels == 0 {
+        return;
+    }
+    for (frame, samples) in interleaved.chunks_exact_mut(channels).enumerate() {
+        for (sample, channel) in samples.iter_mut().zip(planar) {
+            *sample = channel[frame];
+        }
+    }
+}
+
+/// Views the first `channels` runs of `frames` samples in a planar buffer
+pub(crate) fn split_channels(
+    buffer: &[f32],
+    channels: usize,
+    frames: usize,
+) -> [&[f32]; MAX_CHANNELS] {
+    let mut views: [&[f32]; MAX_CHANNELS] = Default::default();
+    if frames > 0 {
+        for (view, channel) in views
+            .iter_mut()
+            .zip(buffer[..channels * frames].chunks_exact(frames))
+        {
+            *view = channel;
+        }
+    }
+    views
+}
+
+/// Mutable version of `split_channels`
+pub(crate) fn split_channels_mut(
+    buffer: &mut [f32],
+    channels: usize,
+    frames: usize,
+) -> [&mut [f32]; MAX_CHANNELS] {
+    let mut views: [&mut [f32]; MAX_CHANNELS] = Default::default();
+    if frames > 0 {
+        for (view, channel) in views
+            .iter_mut()
+            .zip(buffer[..channels * frames].chunks_exact_mut(frames))
+        {
+            *view = channel;
+        }
+    }
+    views
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mix(sources: &[&[f32]], channels: usize) -> Vec<Vec<f32>> {
+        let mut buffer = vec![0.0; channels * 2];
+        let mut targets = split_channels_mut(&mut buffer, channels, 2);
+        mix_channels(sources, &mut targets[..channels]);
+        buffer.chunks(2).map(<[f32]>::to_vec).collect()
+    }
+
+    #[test]
+    fn test_mixing_rules() {
+        let mono: &[f32] = &[1.0, -1.0];
+        let left: &[f32] = &[0.5, 0.0];
+        let right: &[f32] = &[0.25, 1.0];
+
+        assert_eq!(mix(&[mono], 2), vec![vec![1.0, -1.0], vec![1.0, -1.0]]);
+        assert_eq!(
+            mix(&[mono], 3),
+            vec![vec![1.0, -1.0], vec![1.0, -1.0], vec![0.0, 0.0]]
+        );
+        assert_eq!(mix(&[left, right], 1), vec![vec![0.375, 0.5]]);
+        assert_eq!(
+            mix(&[left, right], 2),
+            vec![vec![0.5, 0.0], vec![0.25, 1.0]]
+        );
+        assert_eq!(
+            mix(&[left, right, mono], 2),
+            vec![vec![0.5, 0.0], vec![0.25, 1.0]]
+        );
+    }
+
+    #[test]
+    fn test_interleave_round_trip() {
+        let interleaved = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let mut buffer = [0.0; 6];
+        {
+            let mut planar = split_channels_mut(&mut buffer, 2, 3);
+            deinterleave(&interleaved, &mut planar[..2]);
+        }
+        assert_eq!(buffer, [1.0, 3.0, 5.0, 2.0, 4.0, 6.0]);
+
+        let mut output = [0.0; 6];
+        interleave(&split_channels(&buffer, 2, 3)[..2], &mut output);
+        assert_eq!(output, interleaved);
+        assert_eq!(
+            ChannelLayout::new(0, 12),
+            ChannelLayout::new(1, MAX_CHANNELS)
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Latency compensation
+//!
+//! Processors report the delay they add with
+//! `AudioProcessor::latency_samples`. `AudioGraph` sums it along every path
+//! and delays the shorter paths into a node with `DelayLine`s, so parallel
+//! branches stay sample-aligned when they are mixed; the host reads the
+//! total from `AudioGraph::latency_samples`.
+
+/// Fixed multichannel delay for aligning paths
+pub struct DelayLine {
+    channels: usize,
+    delay: usize,
+    /// `channels` rings of `delay` samples, planar
+    buffer: Vec<f32>,
+    position: usize,
+}
+
+impl DelayLine {
+    /// Create a delay of `delay` samples on `channels` channels
+    pub fn new(channels: usize, delay: usize) -> Self {
+        Self {
+            channels,
+            delay,
+            buffer: vec![0.0; channels * delay],
+            position: 0,
+        }
+    }
+
+    /// Change the delay, clearing the line; the same delay is kept as is
+    ///
+    /// Grows the buffer (allocating) if the delay is longer than before.
+    pub fn set_delay(&mut self, delay: usize) {
+        if delay == self.delay {
+            return;
+        }
+        self.delay = delay;
+        self.buffer.resize(self.channels * delay, 0.0);
+        self.reset();
+    }
+
+    pub fn delay(&self) -> usize {
+        self.delay
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Delay planar channels in place
+    pub fn process(&mut self, channels: &mut [&mut [f32]]) {
+        if self.delay == 0 {
+            return;
+        }
+        let mut position = self.position;
+        for (channel, ring) in channels
+            .iter_mut()
+            .zip(self.buffer.chunks_exact_mut(self.delay))
+        {
+            position = self.position;
+            for sample in channel.iter_mut() {
+                std::mem::swap(sample, &mut ring[position]);
+                position += 1;
+                if position == self.delay {
+                    position = 0;
+                }
+            }
+        }
+        self.position = position;
+    }
+
+    /// Clear the delayed samples
+    pub fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.position = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_line() {
+        let mut line = DelayLine::new(2, 2);
+        let (mut left, mut right) = ([1.0, 2.0, 3.0], [-1.0, -2.0, -3.0]);
+        line.process(&mut [&mut left, &mut right]);
+        assert_eq!(left, [0.0, 0.0, 1.0]);
+        assert_eq!(right, [0.0, 0.0, -1.0]);
+
+        let mut left = [4.0];
+        line.process(&mut [&mut left]);
+        assert_eq!(left, [2.0]);
+
+        line.set_delay(0);
+        let mut left = [5.0];
+        line.process(&mut [&mut left]);
+        assert_eq!(left, [5.0]);
+        assert_eq!((line.channels(), line.delay()), (2, 0));
+    }
+}
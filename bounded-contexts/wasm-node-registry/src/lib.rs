@@ -3,22 +3,91 @@
 //! Registry of node types compiled to WebAssembly for high-performance
 //! graph execution.
 
+pub mod audio_graph;
+pub mod benchmarks;
+pub mod buffer_pool;
+pub mod channels;
+pub mod latency;
 pub mod node_binary_format;
+pub mod processors;
 pub mod props_binary_format;
+pub mod scheduler;
+pub mod simd;
+pub mod string_table;
 
+use harmony_error::HarmonyError;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 use props_binary_format::{PropsBinaryFormat, PropsBinaryDecoder, PropType};
+use string_table::StringTable;
+
+/// Export StringTable to JavaScript
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct SharedStringTable {
+    inner: StringTable,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl SharedStringTable {
+    /// Create an empty string table
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            inner: StringTable::new(),
+        }
+    }
+
+    /// Decode a string table from its binary layout
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = fromBytes))]
+    pub fn from_bytes(bytes: &[u8]) -> Result<SharedStringTable, HarmonyError> {
+        StringTable::from_bytes(bytes)
+            .map(|inner| SharedStringTable { inner })
+    }
+
+    /// Intern a string and return its id
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn intern(&mut self, value: &str) -> u32 {
+        self.inner.intern(value)
+    }
+
+    /// Look up a string by id
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn get(&self, id: u32) -> Option<String> {
+        self.inner.get(id).map(str::to_string)
+    }
+
+    /// Get the number of distinct strings
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getLength))]
+    pub fn get_length(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Encode the table as bytes
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = toBytes))]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+}
+
+impl Default for SharedStringTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 /// Export PropsBinaryFormat encoder to JavaScript
-#[wasm_bindgen]
+///
+/// Inline property names must be shorter than 65535 bytes; the write
+/// methods throw `invalid_input` otherwise.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct PropsBinaryEncoder {
     inner: PropsBinaryFormat,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl PropsBinaryEncoder {
     /// Create a new encoder
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new() -> Self {
         Self {
             inner: PropsBinaryFormat::new(),
@@ -26,117 +95,176 @@ impl PropsBinaryEncoder {
     }
 
     /// Initialize header with property count
-    #[wasm_bindgen(js_name = initHeader)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = initHeader))]
     pub fn init_header(&mut self, property_count: u32) {
         self.inner.init_header(property_count);
     }
 
     /// Write a Float32 property
-    #[wasm_bindgen(js_name = writeFloat32)]
-    pub fn write_float32(&mut self, name: &str, value: f32) {
-        self.inner.write_float32(name, value);
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeFloat32))]
+    pub fn write_float32(&mut self, name: &str, value: f32) -> Result<(), HarmonyError> {
+        self.inner.write_float32(name, value)
     }
 
     /// Write a Float64 property
-    #[wasm_bindgen(js_name = writeFloat64)]
-    pub fn write_float64(&mut self, name: &str, value: f64) {
-        self.inner.write_float64(name, value);
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeFloat64))]
+    pub fn write_float64(&mut self, name: &str, value: f64) -> Result<(), HarmonyError> {
+        self.inner.write_float64(name, value)
     }
 
     /// Write an Int32 property
-    #[wasm_bindgen(js_name = writeInt32)]
-    pub fn write_int32(&mut self, name: &str, value: i32) {
-        self.inner.write_int32(name, value);
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeInt32))]
+    pub fn write_int32(&mut self, name: &str, value: i32) -> Result<(), HarmonyError> {
+        self.inner.write_int32(name, value)
     }
 
     /// Write a Uint32 property
-    #[wasm_bindgen(js_name = writeUint32)]
-    pub fn write_uint32(&mut self, name: &str, value: u32) {
-        self.inner.write_uint32(name, value);
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeUint32))]
+    pub fn write_uint32(&mut self, name: &str, value: u32) -> Result<(), HarmonyError> {
+        self.inner.write_uint32(name, value)
     }
 
     /// Write a Bool property
-    #[wasm_bindgen(js_name = writeBool)]
-    pub fn write_bool(&mut self, name: &str, value: bool) {
-        self.inner.write_bool(name, value);
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeBool))]
+    pub fn write_bool(&mut self, name: &str, value: bool) -> Result<(), HarmonyError> {
+        self.inner.write_bool(name, value)
     }
 
     /// Write a String property
-    #[wasm_bindgen(js_name = writeString)]
-    pub fn write_string(&mut self, name: &str, value: &str) {
-        self.inner.write_string(name, value);
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeString))]
+    pub fn write_string(&mut self, name: &str, value: &str) -> Result<(), HarmonyError> {
+        self.inner.write_string(name, value)
+    }
+
+    /// Write a Float32 property with an interned name
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeFloat32Interned))]
+    pub fn write_float32_interned(&mut self, table: &mut SharedStringTable, name: &str, value: f32) {
+        self.inner
+            .write_property_interned(&mut table.inner, name, PropType::Float32, &value.to_le_bytes());
+    }
+
+    /// Write a Float64 property with an interned name
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeFloat64Interned))]
+    pub fn write_float64_interned(&mut self, table: &mut SharedStringTable, name: &str, value: f64) {
+        self.inner
+            .write_property_interned(&mut table.inner, name, PropType::Float64, &value.to_le_bytes());
+    }
+
+    /// Write an Int32 property with an interned name
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeInt32Interned))]
+    pub fn write_int32_interned(&mut self, table: &mut SharedStringTable, name: &str, value: i32) {
+        self.inner
+            .write_property_interned(&mut table.inner, name, PropType::Int32, &value.to_le_bytes());
+    }
+
+    /// Write a Uint32 property with an interned name
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeUint32Interned))]
+    pub fn write_uint32_interned(&mut self, table: &mut SharedStringTable, name: &str, value: u32) {
+        self.inner
+            .write_property_interned(&mut table.inner, name, PropType::Uint32, &value.to_le_bytes());
+    }
+
+    /// Write a Bool property with an interned name
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeBoolInterned))]
+    pub fn write_bool_interned(&mut self, table: &mut SharedStringTable, name: &str, value: bool) {
+        self.inner
+            .write_property_interned(&mut table.inner, name, PropType::Bool, &[value as u8]);
+    }
+
+    /// Write a String property with interned name and value
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = writeStringInterned))]
+    pub fn write_string_interned(&mut self, table: &mut SharedStringTable, name: &str, value: &str) {
+        self.inner.write_string_interned(&mut table.inner, name, value);
     }
 
     /// Get buffer pointer
-    #[wasm_bindgen(js_name = getPtr)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getPtr))]
     pub fn get_ptr(&self) -> *const u8 {
         self.inner.as_ptr()
     }
 
     /// Get buffer length
-    #[wasm_bindgen(js_name = getLength)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = getLength))]
     pub fn get_length(&self) -> usize {
         self.inner.len()
     }
 
     /// Finalize and get buffer as Uint8Array
-    #[wasm_bindgen(js_name = finalize)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = finalize))]
     pub fn finalize(self) -> Vec<u8> {
         self.inner.finalize()
     }
 }
 
+impl Default for PropsBinaryEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Export PropsBinaryDecoder to JavaScript
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct PropsDecoder {
     inner: PropsBinaryDecoder,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl PropsDecoder {
     /// Create a new decoder from buffer
-    #[wasm_bindgen(constructor)]
-    pub fn new(buffer: Vec<u8>) -> Result<PropsDecoder, JsValue> {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(buffer: Vec<u8>) -> Result<PropsDecoder, HarmonyError> {
         PropsBinaryDecoder::new(buffer)
             .map(|inner| PropsDecoder { inner })
-            .map_err(|e| JsValue::from_str(e))
     }
 
     /// Get property count
-    #[wasm_bindgen(js_name = propertyCount)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = propertyCount))]
     pub fn property_count(&self) -> u32 {
         self.inner.property_count()
     }
 
     /// Read next property (returns JSON string)
+    #[cfg(feature = "wasm")]
     #[wasm_bindgen(js_name = readProperty)]
-    pub fn read_property(&mut self) -> Result<JsValue, JsValue> {
+    pub fn read_property(&mut self) -> Result<JsValue, HarmonyError> {
         self.inner
             .read_property()
-            .map(|(name, prop_type, value)| {
-                let type_str = match prop_type {
-                    PropType::Float32 => "float32",
-                    PropType::Float64 => "float64",
-                    PropType::Int32 => "int32",
-                    PropType::Uint32 => "uint32",
-                    PropType::Int16 => "int16",
-                    PropType::Uint16 => "uint16",
-                    PropType::Int8 => "int8",
-                    PropType::Uint8 => "uint8",
-                    PropType::Bool => "bool",
-                    PropType::String => "string",
-                    PropType::Array => "array",
-                };
-                
-                let js_obj = js_sys::Object::new();
-                js_sys::Reflect::set(&js_obj, &"name".into(), &name.into()).unwrap();
-                js_sys::Reflect::set(&js_obj, &"type".into(), &type_str.into()).unwrap();
-                
-                let value_array = js_sys::Uint8Array::from(&value[..]);
-                js_sys::Reflect::set(&js_obj, &"value".into(), &value_array.into()).unwrap();
-                
-                js_obj.into()
-            })
-            .map_err(|e| JsValue::from_str(e))
-    }
-}
\ No newline at end of file
+            .map(property_to_js)
+    }
+
+    /// Read next property, resolving interned names through the string table
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(js_name = readPropertyWithTable)]
+    pub fn read_property_with_table(&mut self, table: &SharedStringTable) -> Result<JsValue, HarmonyError> {
+        self.inner
+            .read_property_with_table(&table.inner)
+            .map(property_to_js)
+    }
+}
+
+#[cfg(feature = "wasm")]
+fn property_to_js((name, prop_type, value): (String, PropType, Vec<u8>)) -> JsValue {
+    let type_str = match prop_type {
+        PropType::Float32 => "float32",
+        PropType::Float64 => "float64",
+        PropType::Int32 => "int32",
+        PropType::Uint32 => "uint32",
+        PropType::Int16 => "int16",
+        PropType::Uint16 => "uint16",
+        PropType::Int8 => "int8",
+        PropType::Uint8 => "uint8",
+        PropType::Bool => "bool",
+        PropType::String => "string",
+        PropType::Array => "array",
+        PropType::StringRef => "string_ref",
+    };
+
+    let js_obj = js_sys::Object::new();
+    js_sys::Reflect::set(&js_obj, &"name".into(), &name.into()).unwrap();
+    js_sys::Reflect::set(&js_obj, &"type".into(), &type_str.into()).unwrap();
+
+    let value_array = js_sys::Uint8Array::from(&value[..]);
+    js_sys::Reflect::set(&js_obj, &"value".into(), &value_array.into()).unwrap();
+
+    js_obj.into()
+}
@@ -13,7 +13,7 @@
 //! - Deserialization: < 100ns per node
 //! - Memory overhead: 12 bytes per node (fixed)
 
-use std::mem;
+use harmony_error::HarmonyError;
 
 /// Size of a single node in binary format (12 bytes)
 pub const NODE_BINARY_SIZE: usize = 12;
@@ -40,6 +40,7 @@ impl NodeBinaryFormat {
     ///
     /// # Example
     /// ```
+    /// # use wasm_node_registry::node_binary_format::NodeBinaryFormat;
     /// let node = NodeBinaryFormat::new(1, 5, 0);
     /// assert_eq!(node.id, 1);
     /// ```
@@ -82,9 +83,11 @@ impl NodeBinaryFormat {
     /// # Errors
     /// Returns error if byte slice is too short
     #[inline]
-    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HarmonyError> {
         if bytes.len() < NODE_BINARY_SIZE {
-            return Err("Insufficient bytes for NodeBinaryFormat");
+            return Err(HarmonyError::Malformed(
+                "Insufficient bytes for NodeBinaryFormat".into(),
+            ));
         }
 
         let id = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
@@ -109,9 +112,11 @@ impl NodeBinaryFormat {
     /// # Errors
     /// Returns error if buffer is too small
     #[inline]
-    pub fn write_to(&self, buffer: &mut [u8]) -> Result<(), &'static str> {
+    pub fn write_to(&self, buffer: &mut [u8]) -> Result<(), HarmonyError> {
         if buffer.len() < NODE_BINARY_SIZE {
-            return Err("Buffer too small for NodeBinaryFormat");
+            return Err(HarmonyError::CapacityExceeded(
+                "Buffer too small for NodeBinaryFormat".into(),
+            ));
         }
 
         buffer[0..4].copy_from_slice(&self.id.to_le_bytes());
@@ -132,9 +137,9 @@ impl NodeBinaryFormat {
     /// # Safety
     /// This function assumes the buffer is properly aligned and contains valid data
     #[inline]
-    pub fn read_from(buffer: &[u8]) -> Result<&Self, &'static str> {
+    pub fn read_from(buffer: &[u8]) -> Result<&Self, HarmonyError> {
         if buffer.len() < NODE_BINARY_SIZE {
-            return Err("Buffer too small for NodeBinaryFormat");
+            return Err(HarmonyError::Malformed("Buffer too small for NodeBinaryFormat".into()));
         }
 
         // Safety: We've verified the size, and NodeBinaryFormat is repr(C)
@@ -221,7 +226,7 @@ impl NodeBuffer {
     }
 
     /// Creates an iterator over the nodes
-    pub fn iter(&self) -> NodeBufferIter {
+    pub fn iter(&self) -> NodeBufferIter<'_> {
         NodeBufferIter {
             buffer: &self.buffer,
             index: 0,
@@ -264,6 +269,7 @@ impl<'a> ExactSizeIterator for NodeBufferIter<'a> {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::mem;
 
     #[test]
     fn test_node_binary_size() {
@@ -0,0 +1,257 @@
+//! Biquad filter processor
+//!
+//! Second-order IIR filter with coefficients from the RBJ Audio EQ
+//! Cookbook, in transposed direct form II. Parameters: `type` (0 =
+//! low-pass, 1 = high-pass, 2 = band-pass, 3 = notch), `frequency` (Hz,
+//! 10 up to just below Nyquist) and `q` (0.1-20). Coefficients are
+//! recalculated whenever a parameter or the sample rate changes, and per
+//! sample while `frequency` (exponentially) or `q` ramps.
+
+use std::f32::consts::PI;
+
+use harmony_error::HarmonyError;
+
+use super::smoothing::{ramp_samples, RampShape, SmoothedParam};
+use super::{check_finite, AudioProcessor, DEFAULT_RAMP_SECONDS, DEFAULT_SAMPLE_RATE};
+
+/// Filter response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterType {
+    LowPass,
+    HighPass,
+    /// Constant 0 dB peak gain
+    BandPass,
+    Notch,
+}
+
+/// Normalized biquad coefficients (a0 = 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Coefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+/// Biquad filter
+pub struct BiquadProcessor {
+    filter_type: FilterType,
+    frequency: SmoothedParam,
+    q: SmoothedParam,
+    sample_rate: f32,
+    ramp_time: f32,
+    coefficients: Coefficients,
+    z1: f32,
+    z2: f32,
+}
+
+impl BiquadProcessor {
+    /// Registry type_id of the biquad filter node
+    pub const TYPE_ID: &'static str = "audio.biquad";
+
+    /// Create a 1kHz Butterworth low-pass filter
+    pub fn new() -> Self {
+        let mut processor = Self {
+            filter_type: FilterType::LowPass,
+            frequency: SmoothedParam::new(1000.0).with_shape(RampShape::Exponential),
+            q: SmoothedParam::new(std::f32::consts::FRAC_1_SQRT_2),
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            ramp_time: DEFAULT_RAMP_SECONDS,
+            coefficients: Coefficients {
+                b0: 1.0,
+                b1: 0.0,
+                b2: 0.0,
+                a1: 0.0,
+                a2: 0.0,
+            },
+            z1: 0.0,
+            z2: 0.0,
+        };
+        processor.update_ramps();
+        processor.update_coefficients();
+        processor
+    }
+
+    fn update_ramps(&mut self) {
+        let samples = ramp_samples(self.ramp_time, self.sample_rate);
+        self.frequency.set_ramp_samples(samples);
+        self.q.set_ramp_samples(samples);
+    }
+
+    /// Set the filter response
+    pub fn set_filter_type(&mut self, filter_type: FilterType) {
+        self.filter_type = filter_type;
+        self.update_coefficients();
+    }
+
+    fn update_coefficients(&mut self) {
+        let frequency = self
+            .frequency
+            .current()
+            .clamp(10.0, self.sample_rate * 0.49);
+        let w0 = 2.0 * PI * frequency / self.sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * self.q.current());
+
+        let (b0, b1, b2) = match self.filter_type {
+            FilterType::LowPass => ((1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0),
+            FilterType::HighPass => ((1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0),
+            FilterType::BandPass => (alpha, 0.0, -alpha),
+            FilterType::Notch => (1.0, -2.0 * cos, 1.0),
+        };
+        let a0 = 1.0 + alpha;
+        self.coefficients = Coefficients {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+        };
+    }
+}
+
+impl Default for BiquadProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioProcessor for BiquadProcessor {
+    fn prepare(&mut self, sample_rate: f32, _max_block_size: usize) {
+        self.sample_rate = sample_rate;
+        self.update_ramps();
+        self.reset();
+    }
+
+    /// # Performance
+    /// Target: < 1ms for 512 sample buffer @ 48kHz
+    fn process(&mut self, input: &[f32], output: &mut [f32]) {
+        for (out, &sample) in output.iter_mut().zip(input) {
+            if self.frequency.is_smoothing() || self.q.is_smoothing() {
+                self.frequency.advance();
+                self.q.advance();
+                self.update_coefficients();
+            }
+            let Coefficients { b0, b1, b2, a1, a2 } = self.coefficients;
+            let filtered = b0 * sample + self.z1;
+            self.z1 = b1 * sample - a1 * filtered + self.z2;
+            self.z2 = b2 * sample - a2 * filtered;
+            *out = filtered;
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> Result<(), HarmonyError> {
+        check_finite(name, value)?;
+        match name {
+            "type" => {
+                self.filter_type = match value.round().clamp(0.0, 3.0) as u8 {
+                    0 => FilterType::LowPass,
+                    1 => FilterType::HighPass,
+                    2 => FilterType::BandPass,
+                    _ => FilterType::Notch,
+                }
+            }
+            "frequency" => self.frequency.set_target(value.max(10.0)),
+            "q" => self.q.set_target(value.clamp(0.1, 20.0)),
+            _ => {
+                return Err(HarmonyError::NotFound(format!(
+                    "Unknown biquad parameter '{}'",
+                    name
+                )))
+            }
+        }
+        self.update_coefficients();
+        Ok(())
+    }
+
+    fn set_ramp_time(&mut self, seconds: f32) {
+        self.ramp_time = seconds;
+        self.update_ramps();
+    }
+
+    fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+        self.frequency.snap();
+        self.q.snap();
+        self.update_coefficients();
+    }
+
+    fn parameter_values(&self) -> Vec<(&'static str, f32)> {
+        vec![
+            ("type", self.filter_type as u8 as f32),
+            ("frequency", self.frequency.target()),
+            ("q", self.q.target()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    fn filter(filter_type: FilterType, frequency: f32) -> BiquadProcessor {
+        let mut processor = BiquadProcessor::new();
+        processor.prepare(SAMPLE_RATE, 4800);
+        processor.set_filter_type(filter_type);
+        processor.set_parameter("frequency", frequency).unwrap();
+        processor.reset();
+        processor
+    }
+
+    /// Peak output amplitude over the last half of 100ms of a sine
+    fn response(processor: &mut BiquadProcessor, frequency: f32) -> f32 {
+        let input: Vec<f32> = (0..4800)
+            .map(|n| (2.0 * PI * frequency * n as f32 / SAMPLE_RATE).sin())
+            .collect();
+        let mut output = vec![0.0; input.len()];
+        processor.process(&input, &mut output);
+        output[2400..].iter().fold(0.0, |peak, x| x.abs().max(peak))
+    }
+
+    #[test]
+    fn test_low_and_high_pass() {
+        let mut low_pass = filter(FilterType::LowPass, 1000.0);
+        assert!(response(&mut low_pass, 100.0) > 0.99);
+        low_pass.reset();
+        assert!(response(&mut low_pass, 10000.0) < 0.05);
+
+        let mut high_pass = filter(FilterType::HighPass, 1000.0);
+        assert!(response(&mut high_pass, 100.0) < 0.05);
+        high_pass.reset();
+        assert!(response(&mut high_pass, 10000.0) > 0.99);
+    }
+
+    #[test]
+    fn test_band_pass_and_notch() {
+        let mut band_pass = filter(FilterType::BandPass, 1000.0);
+        assert!((response(&mut band_pass, 1000.0) - 1.0).abs() < 0.01);
+        band_pass.reset();
+        assert!(response(&mut band_pass, 50.0) < 0.1);
+
+        let mut notch = filter(FilterType::Notch, 1000.0);
+        assert!(response(&mut notch, 1000.0) < 0.01);
+        notch.reset();
+        assert!(response(&mut notch, 10000.0) > 0.95);
+    }
+
+    #[test]
+    fn test_parameters_recalculate_coefficients() {
+        let mut processor = filter(FilterType::LowPass, 1000.0);
+        let before = processor.coefficients;
+        processor.set_parameter("q", 4.0).unwrap();
+        processor.process(&[0.0], &mut [0.0]);
+        assert_ne!(processor.coefficients, before);
+        processor.set_parameter("frequency", 2000.0).unwrap();
+        processor.process(&[0.0; 959], &mut [0.0; 959]);
+        assert!(processor.frequency.is_smoothing());
+        processor.process(&[0.0], &mut [0.0]);
+        assert_eq!(processor.frequency.current(), 2000.0);
+        processor.set_parameter("type", 3.0).unwrap();
+        assert_eq!(processor.filter_type, FilterType::Notch);
+        assert!(processor.set_parameter("gain", 1.0).is_err());
+    }
+}
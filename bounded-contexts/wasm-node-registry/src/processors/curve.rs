@@ -0,0 +1,511 @@
+//! Custom waveshaping curves
+//!
+//! Builds the lookup tables behind `WaveshapeCurve::Custom` from
+//! breakpoints or a formula in `x`, on the control thread. Every table is
+//! resampled to a power-of-two length, and inputs are validated up front so
+//! a bad curve never reaches the audio thread:
+//! - no NaN or infinite values
+//! - breakpoint inputs strictly increasing within -1..1
+//! - outputs within -MAX_CURVE_OUTPUT..MAX_CURVE_OUTPUT
+//! - at most MAX_BREAKPOINTS points, MAX_EXPRESSION_LEN characters and
+//!   MIN_TABLE_SIZE..=MAX_TABLE_SIZE table entries
+//! - expressions nested at most `max_depth` deep (see harmony-error
+//!   `limits`)
+//!
+//! The finished table is set as the waveshaper's "curve" table parameter
+//! (`AudioGraph::set_table`).
+//!
+//! Expressions use numbers, `x`, `pi`, `+ - * / ^`, parentheses and the
+//! functions abs, sign, sqrt, exp, ln, sin, cos, tan, tanh, atan, min, max
+//! and clamp, e.g. `tanh(3 * x) / tanh(3)`.
+
+use harmony_error::{limits, HarmonyError, ResultExt};
+
+/// Table length used when the caller has no preference
+pub const DEFAULT_TABLE_SIZE: usize = 1024;
+
+/// Shortest table accepted
+pub const MIN_TABLE_SIZE: usize = 16;
+
+/// Longest table accepted
+pub const MAX_TABLE_SIZE: usize = 65536;
+
+/// Most breakpoints in a curve
+pub const MAX_BREAKPOINTS: usize = 256;
+
+/// Longest expression accepted, in bytes
+pub const MAX_EXPRESSION_LEN: usize = 256;
+
+/// Largest output magnitude of a curve
+pub const MAX_CURVE_OUTPUT: f32 = 4.0;
+
+/// Builds a table by linear interpolation between `(input, output)`
+/// breakpoints
+///
+/// Inputs left of the first breakpoint or right of the last hold its
+/// output.
+///
+/// # Returns
+/// A table of `size` entries spanning inputs -1..1, or an error describing
+/// the first invalid breakpoint
+pub fn from_breakpoints(points: &[(f32, f32)], size: usize) -> Result<Vec<f32>, HarmonyError> {
+    check_size(size)?;
+    if points.len() < 2 || points.len() > MAX_BREAKPOINTS {
+        return Err(HarmonyError::InvalidInput(format!(
+            "Curve needs 2 to {} breakpoints, got {}",
+            MAX_BREAKPOINTS,
+            points.len()
+        )));
+    }
+    for (i, &(x, y)) in points.iter().enumerate() {
+        if !x.is_finite() || !(-1.0..=1.0).contains(&x) {
+            return Err(HarmonyError::InvalidInput(format!(
+                "Breakpoint {} input {} is outside -1..1",
+                i, x
+            )));
+        }
+        check_output(y).with_context(|| format!("Breakpoint {}", i))?;
+        if i > 0 && x <= points[i - 1].0 {
+            return Err(HarmonyError::InvalidInput(format!(
+                "Breakpoint {} input {} is not increasing",
+                i, x
+            )));
+        }
+    }
+
+    let mut segment = 0;
+    let table = (0..size)
+        .map(|i| {
+            let x = table_input(i, size);
+            while segment + 2 < points.len() && x > points[segment + 1].0 {
+                segment += 1;
+            }
+            let (x0, y0) = points[segment];
+            let (x1, y1) = points[segment + 1];
+            let t = ((x - x0) / (x1 - x0)).clamp(0.0, 1.0);
+            y0 + (y1 - y0) * t
+        })
+        .collect();
+    Ok(table)
+}
+
+/// Builds a table by evaluating a formula in `x` (the input, -1..1)
+///
+/// # Returns
+/// A table of `size` entries, or an error for syntax errors and formulas
+/// whose output is NaN, infinite or out of range anywhere in the table
+pub fn from_expression(expression: &str, size: usize) -> Result<Vec<f32>, HarmonyError> {
+    check_size(size)?;
+    if expression.len() > MAX_EXPRESSION_LEN {
+        return Err(HarmonyError::InvalidInput(format!(
+            "Curve expression is longer than {} bytes",
+            MAX_EXPRESSION_LEN
+        )));
+    }
+    let expr = Parser::new(expression).parse()?;
+    (0..size)
+        .map(|i| {
+            let x = table_input(i, size);
+            let y = expr.eval(x as f64) as f32;
+            check_output(y).with_context(|| format!("At x = {}", x))?;
+            Ok(y)
+        })
+        .collect()
+}
+
+/// Resamples a table spanning -1..1 to `size` entries
+///
+/// Also validates the values, so tables of any length from presets can be
+/// passed through it before use.
+pub fn resample(table: &[f32], size: usize) -> Result<Vec<f32>, HarmonyError> {
+    check_size(size)?;
+    if table.len() < 2 || table.len() > MAX_TABLE_SIZE {
+        return Err(HarmonyError::InvalidInput(format!(
+            "Curve table needs 2 to {} entries, got {}",
+            MAX_TABLE_SIZE,
+            table.len()
+        )));
+    }
+    table.iter().try_for_each(|&y| check_output(y))?;
+
+    let last = (table.len() - 1) as f32;
+    let resampled = (0..size)
+        .map(|i| {
+            let position = (table_input(i, size) + 1.0) * 0.5 * last;
+            let index = (position as usize).min(table.len() - 2);
+            let frac = position - index as f32;
+            table[index] + (table[index + 1] - table[index]) * frac
+        })
+        .collect();
+    Ok(resampled)
+}
+
+/// Checks a finished table, as set through the "curve" table parameter
+pub fn validate_table(table: &[f32]) -> Result<(), HarmonyError> {
+    check_size(table.len())?;
+    table.iter().try_for_each(|&y| check_output(y))
+}
+
+fn check_size(size: usize) -> Result<(), HarmonyError> {
+    if !size.is_power_of_two() || !(MIN_TABLE_SIZE..=MAX_TABLE_SIZE).contains(&size) {
+        return Err(HarmonyError::InvalidInput(format!(
+            "Curve table size {} is not a power of two in {}..={}",
+            size, MIN_TABLE_SIZE, MAX_TABLE_SIZE
+        )));
+    }
+    Ok(())
+}
+
+fn check_output(y: f32) -> Result<(), HarmonyError> {
+    if y.is_nan() {
+        return Err(HarmonyError::InvalidInput(
+            "Curve output is NaN".to_string(),
+        ));
+    }
+    if !(-MAX_CURVE_OUTPUT..=MAX_CURVE_OUTPUT).contains(&y) {
+        return Err(HarmonyError::InvalidInput(format!(
+            "Curve output {} is outside -{}..{}",
+            y, MAX_CURVE_OUTPUT, MAX_CURVE_OUTPUT
+        )));
+    }
+    Ok(())
+}
+
+/// Input value at table entry `i`, matching the waveshaper's lookup
+fn table_input(i: usize, size: usize) -> f32 {
+    i as f32 / (size - 1) as f32 * 2.0 - 1.0
+}
+
+/// Parsed curve expression
+#[derive(Debug)]
+enum Expr {
+    Number(f64),
+    Input,
+    Negate(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Function {
+    Abs,
+    Sign,
+    Sqrt,
+    Exp,
+    Ln,
+    Sin,
+    Cos,
+    Tan,
+    Tanh,
+    Atan,
+    Min,
+    Max,
+    Clamp,
+}
+
+impl Function {
+    fn lookup(name: &str) -> Option<(Self, usize)> {
+        let function = match name {
+            "abs" => (Self::Abs, 1),
+            "sign" => (Self::Sign, 1),
+            "sqrt" => (Self::Sqrt, 1),
+            "exp" => (Self::Exp, 1),
+            "ln" => (Self::Ln, 1),
+            "sin" => (Self::Sin, 1),
+            "cos" => (Self::Cos, 1),
+            "tan" => (Self::Tan, 1),
+            "tanh" => (Self::Tanh, 1),
+            "atan" => (Self::Atan, 1),
+            "min" => (Self::Min, 2),
+            "max" => (Self::Max, 2),
+            "clamp" => (Self::Clamp, 3),
+            _ => return None,
+        };
+        Some(function)
+    }
+}
+
+impl Expr {
+    fn eval(&self, x: f64) -> f64 {
+        match self {
+            Self::Number(value) => *value,
+            Self::Input => x,
+            Self::Negate(operand) => -operand.eval(x),
+            Self::Binary(op, left, right) => {
+                let (left, right) = (left.eval(x), right.eval(x));
+                match op {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    '/' => left / right,
+                    _ => left.powf(right),
+                }
+            }
+            Self::Call(function, args) => {
+                let arg = |i: usize| args[i].eval(x);
+                match function {
+                    Function::Abs => arg(0).abs(),
+                    Function::Sign => {
+                        let value = arg(0);
+                        if value == 0.0 {
+                            0.0
+                        } else {
+                            value.signum()
+                        }
+                    }
+                    Function::Sqrt => arg(0).sqrt(),
+                    Function::Exp => arg(0).exp(),
+                    Function::Ln => arg(0).ln(),
+                    Function::Sin => arg(0).sin(),
+                    Function::Cos => arg(0).cos(),
+                    Function::Tan => arg(0).tan(),
+                    Function::Tanh => arg(0).tanh(),
+                    Function::Atan => arg(0).atan(),
+                    Function::Min => arg(0).min(arg(1)),
+                    Function::Max => arg(0).max(arg(1)),
+                    // NaN bounds propagate instead of panicking
+                    Function::Clamp => arg(0).max(arg(1)).min(arg(2)),
+                }
+            }
+        }
+    }
+}
+
+/// Recursive descent parser
+///
+/// ```text
+/// sum     := product (('+' | '-') product)*
+/// product := unary (('*' | '/') unary)*
+/// unary   := '-' unary | power
+/// power   := atom ('^' unary)?
+/// atom    := number | name | name '(' sum (',' sum)* ')' | '(' sum ')'
+/// ```
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+    /// Nested `unary` calls, which every recursion passes through
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            position: 0,
+            depth: 0,
+            max_depth: limits::limits().max_depth,
+        }
+    }
+
+    fn parse(mut self) -> Result<Expr, HarmonyError> {
+        let expr = self.sum()?;
+        match self.peek() {
+            None => Ok(expr),
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+        }
+    }
+
+    fn error(&self, message: &str) -> HarmonyError {
+        HarmonyError::InvalidInput(format!(
+            "Curve expression error at column {}: {}",
+            self.position + 1,
+            message
+        ))
+    }
+
+    /// Next non-space character, without consuming it
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        self.source[self.position..].chars().next()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), HarmonyError> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", expected)))
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, HarmonyError> {
+        let mut expr = self.product()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.position += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, HarmonyError> {
+        let mut expr = self.unary()?;
+        while let Some(op @ ('*' | '/')) = self.peek() {
+            self.position += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, HarmonyError> {
+        self.depth += 1;
+        limits::check("Curve expression nesting depth", self.depth, self.max_depth)?;
+        let expr = if self.eat('-') {
+            Expr::Negate(Box::new(self.unary()?))
+        } else {
+            self.power()?
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn power(&mut self) -> Result<Expr, HarmonyError> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            return Ok(Expr::Binary('^', Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, HarmonyError> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let expr = self.sum()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_ascii_alphabetic() => self.name(),
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Expr, HarmonyError> {
+        let start = self.position;
+        let rest = &self.source[start..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        self.position += len;
+        rest[..len].parse().map(Expr::Number).map_err(|_| {
+            HarmonyError::InvalidInput(format!(
+                "Curve expression error at column {}: bad number",
+                start + 1
+            ))
+        })
+    }
+
+    fn name(&mut self) -> Result<Expr, HarmonyError> {
+        let start = self.position;
+        let rest = &self.source[start..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let name = &rest[..len];
+        self.position += len;
+        match name {
+            "x" => return Ok(Expr::Input),
+            "pi" => return Ok(Expr::Number(std::f64::consts::PI)),
+            _ => {}
+        }
+
+        let Some((function, arity)) = Function::lookup(name) else {
+            self.position = start;
+            return Err(self.error(&format!("unknown name '{}'", name)));
+        };
+        self.expect('(')?;
+        let mut args = vec![self.sum()?];
+        while self.eat(',') {
+            args.push(self.sum()?);
+        }
+        self.expect(')')?;
+        if args.len() != arity {
+            self.position = start;
+            return Err(self.error(&format!(
+                "{} takes {} argument(s), got {}",
+                name,
+                arity,
+                args.len()
+            )));
+        }
+        Ok(Expr::Call(function, args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breakpoints() {
+        let table = from_breakpoints(&[(-0.5, -1.0), (0.0, 0.0), (0.5, 1.0)], 16).unwrap();
+        assert_eq!(table.len(), 16);
+        assert_eq!((table[0], table[15]), (-1.0, 1.0));
+        // Entry 9 sits at x = 0.2
+        assert!((table[9] - 0.4).abs() < 1e-5);
+
+        assert!(from_breakpoints(&[(0.0, 0.0)], 16).is_err());
+        assert!(from_breakpoints(&[(-1.0, 0.0), (f32::NAN, 1.0)], 16).is_err());
+        assert!(from_breakpoints(&[(-1.0, 0.0), (1.5, 1.0)], 16).is_err());
+        assert!(from_breakpoints(&[(0.5, 0.0), (0.5, 1.0)], 16).is_err());
+        assert!(from_breakpoints(&[(-1.0, 0.0), (1.0, 9.0)], 16).is_err());
+        assert!(from_breakpoints(&[(-1.0, 0.0), (1.0, 1.0)], 100).is_err());
+        let too_many: Vec<(f32, f32)> = (0..=MAX_BREAKPOINTS)
+            .map(|i| (i as f32 / MAX_BREAKPOINTS as f32 * 2.0 - 1.0, 0.0))
+            .collect();
+        assert!(from_breakpoints(&too_many, 16).is_err());
+    }
+
+    #[test]
+    fn test_expressions() {
+        let table = from_expression("tanh(3 * x) / tanh(3)", 64).unwrap();
+        assert!((table[0] + 1.0).abs() < 1e-6);
+        assert!((table[63] - 1.0).abs() < 1e-6);
+
+        let table = from_expression("clamp(-2 * x ^ 2 + max(x, 0), -1, 1)", 16).unwrap();
+        let x = table_input(3, 16);
+        assert!((table[3] - (-2.0 * x * x)).abs() < 1e-6);
+
+        assert_eq!(
+            from_expression("2 * y", 16).unwrap_err().to_string(),
+            "Curve expression error at column 5: unknown name 'y'"
+        );
+        assert!(from_expression("sin(x", 16).is_err());
+        assert!(from_expression("min(x)", 16).is_err());
+        assert!(from_expression("x )", 16).is_err());
+        assert!(from_expression("1 / x", 16).is_err());
+        assert!(from_expression("sqrt(x)", 16).is_err());
+        assert!(from_expression(&"x+".repeat(MAX_EXPRESSION_LEN), 16).is_err());
+
+        let nested = format!("{}x{}", "(".repeat(70), ")".repeat(70));
+        let error = from_expression(&nested, 16).unwrap_err();
+        assert_eq!(error.code(), "capacity_exceeded");
+        assert!(from_expression(&format!("{}x", "-".repeat(63)), 16).is_ok());
+    }
+
+    #[test]
+    fn test_resample() {
+        let table = resample(&[-1.0, 0.0, 1.0], 16).unwrap();
+        assert!(validate_table(&table).is_ok());
+        assert!((table[5] - table_input(5, 16)).abs() < 1e-6);
+
+        let long: Vec<f32> = (0..1000).map(|i| i as f32 / 999.0).collect();
+        let table = resample(&long, 32).unwrap();
+        assert_eq!((table[0], table[31]), (0.0, 1.0));
+
+        assert!(resample(&[0.5], 16).is_err());
+        assert!(validate_table(&[0.0; 24]).is_err());
+        assert!(validate_table(&[f32::NAN; 16]).is_err());
+    }
+}
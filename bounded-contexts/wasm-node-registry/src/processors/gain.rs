@@ -0,0 +1,125 @@
+//! Gain processor
+//!
+//! Scales the signal by a linear `gain` (0-4, i.e. up to +12 dB).
+
+use harmony_error::HarmonyError;
+
+use super::smoothing::{ramp_samples, SmoothedParam};
+use super::{check_finite, AudioProcessor, DEFAULT_RAMP_SECONDS, DEFAULT_SAMPLE_RATE};
+use crate::simd;
+
+/// Linear gain processor
+pub struct GainProcessor {
+    gain: SmoothedParam,
+    sample_rate: f32,
+    ramp_time: f32,
+}
+
+impl GainProcessor {
+    /// Registry type_id of the gain node
+    pub const TYPE_ID: &'static str = "audio.gain";
+
+    /// Create a unity-gain processor
+    pub fn new() -> Self {
+        let mut processor = Self {
+            gain: SmoothedParam::new(1.0),
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            ramp_time: DEFAULT_RAMP_SECONDS,
+        };
+        processor.update_ramps();
+        processor
+    }
+
+    fn update_ramps(&mut self) {
+        let samples = ramp_samples(self.ramp_time, self.sample_rate);
+        self.gain.set_ramp_samples(samples);
+    }
+}
+
+impl Default for GainProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioProcessor for GainProcessor {
+    fn prepare(&mut self, sample_rate: f32, _max_block_size: usize) {
+        self.sample_rate = sample_rate;
+        self.update_ramps();
+    }
+
+    fn process(&mut self, input: &[f32], output: &mut [f32]) {
+        if !self.gain.is_smoothing() {
+            simd::scale(input, self.gain.current(), output);
+            return;
+        }
+        for (out, &sample) in output.iter_mut().zip(input) {
+            *out = sample * self.gain.advance();
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> Result<(), HarmonyError> {
+        check_finite(name, value)?;
+        match name {
+            "gain" => self.gain.set_target(value.clamp(0.0, 4.0)),
+            _ => {
+                return Err(HarmonyError::NotFound(format!(
+                    "Unknown gain parameter '{}'",
+                    name
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn set_ramp_time(&mut self, seconds: f32) {
+        self.ramp_time = seconds;
+        self.update_ramps();
+    }
+
+    fn reset(&mut self) {
+        self.gain.snap();
+    }
+
+    fn parameter_values(&self) -> Vec<(&'static str, f32)> {
+        vec![("gain", self.gain.target())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gain() {
+        let mut processor = GainProcessor::new();
+        processor.set_ramp_time(0.0);
+        let mut output = [0.0; 3];
+        processor.process(&[0.5, -0.25, 0.0], &mut output);
+        assert_eq!(output, [0.5, -0.25, 0.0]);
+
+        processor.set_parameter("gain", 2.0).unwrap();
+        processor.process(&[0.5, -0.25, 0.0], &mut output);
+        assert_eq!(output, [1.0, -0.5, 0.0]);
+
+        processor.set_parameter("gain", 100.0).unwrap();
+        assert_eq!(processor.gain.target(), 4.0);
+        assert!(processor.set_parameter("level", 1.0).is_err());
+    }
+
+    #[test]
+    fn test_gain_ramps() {
+        let mut processor = GainProcessor::new();
+        processor.prepare(1000.0, 8);
+        processor.set_ramp_time(0.004);
+        processor.set_parameter("gain", 0.0).unwrap();
+        let mut output = [0.0; 6];
+        processor.process(&[1.0; 6], &mut output);
+        assert_eq!(output, [0.75, 0.5, 0.25, 0.0, 0.0, 0.0]);
+
+        processor.set_parameter("gain", 1.0).unwrap();
+        processor.reset();
+        processor.process(&[1.0; 2], &mut output[..2]);
+        assert_eq!(&output[..2], &[1.0, 1.0]);
+    }
+}
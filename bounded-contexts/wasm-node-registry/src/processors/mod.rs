@@ -0,0 +1,198 @@
+//! Audio processors
+//!
+//! DSP implementations for registered node types. Every processor
+//! implements `AudioProcessor`; `ProcessorFactory` creates them by the
+//! node type's registry `type_id` (e.g. "audio.waveshaper").
+//!
+//! Lifecycle: `prepare` once before the first block (and whenever the
+//! sample rate or block size changes), then `process` per block. `reset`
+//! clears internal state such as delay lines and finishes parameter ramps
+//! without touching parameter values.
+//!
+//! Continuous parameters ramp to new values over the processor's ramp time
+//! (see `smoothing`) to avoid zipper noise; discrete ones such as filter
+//! type switch immediately.
+//!
+//! Processors are mono unless they negotiate a `ChannelLayout` in
+//! `channel_layout`; the graph runs one instance of a mono processor per
+//! channel, and hands multichannel processors planar blocks through
+//! `process_planar`.
+//!
+//! `serialize_state` saves a processor's parameters as a PropsBinaryFormat
+//! buffer (one Float32 property per parameter) for presets and patch
+//! snapshots; `restore_state` loads one back.
+
+pub mod biquad;
+pub mod compressor;
+pub mod curve;
+pub mod delay;
+pub mod factory;
+pub mod gain;
+pub mod oversampling;
+pub mod smoothing;
+pub mod waveshaper;
+
+pub use biquad::{BiquadProcessor, FilterType};
+pub use compressor::CompressorProcessor;
+pub use delay::DelayProcessor;
+pub use factory::ProcessorFactory;
+pub use gain::GainProcessor;
+pub use oversampling::OversampledProcessor;
+pub use smoothing::{RampShape, SmoothedParam};
+pub use waveshaper::{WaveshapeCurve, WaveshaperProcessor};
+
+use harmony_error::HarmonyError;
+
+use crate::channels::ChannelLayout;
+use crate::props_binary_format::{PropType, PropsBinaryDecoder, PropsBinaryFormat};
+
+/// Sample rate assumed until `AudioProcessor::prepare` is called
+pub const DEFAULT_SAMPLE_RATE: f32 = 48000.0;
+
+/// Parameter ramp time until `AudioProcessor::set_ramp_time` is called
+pub const DEFAULT_RAMP_SECONDS: f32 = 0.02;
+
+/// Fails with `invalid_input` if a parameter value is NaN or infinite
+///
+/// Clamping passes NaN through, and one NaN in a filter or delay line
+/// silences the processor until it is reset.
+pub(crate) fn check_finite(name: &str, value: f32) -> Result<(), HarmonyError> {
+    if !value.is_finite() {
+        return Err(HarmonyError::InvalidInput(format!(
+            "Value {} for parameter '{}' is not finite",
+            value, name
+        )));
+    }
+    Ok(())
+}
+
+/// A block-based audio processor, mono unless it negotiates channels
+///
+/// Processors are `Send` so a graph can be built on one thread and run on
+/// another (e.g. a worker or a wasm-threads pool).
+pub trait AudioProcessor: Send {
+    /// Prepare for processing at `sample_rate` with blocks of at most
+    /// `max_block_size` samples
+    fn prepare(&mut self, sample_rate: f32, max_block_size: usize);
+
+    /// Process one block
+    ///
+    /// `input` and `output` have the same length, at most the prepared
+    /// `max_block_size`.
+    fn process(&mut self, input: &[f32], output: &mut [f32]);
+
+    /// Negotiate channel counts with the connected ports
+    ///
+    /// `offered` is the channel count of the node's sources (the most of
+    /// any source), or of the graph input for nodes without sources.
+    /// Sources are up/down-mixed to the returned `inputs`.
+    ///
+    /// # Returns
+    /// None (the default) for mono processors, which the graph runs once
+    /// per offered channel
+    fn channel_layout(&self, _offered: usize) -> Option<ChannelLayout> {
+        None
+    }
+
+    /// Process one block of planar channels, with the channel counts
+    /// negotiated by `channel_layout`
+    ///
+    /// The default runs `process` on each channel in turn, which only suits
+    /// stateless processors; multichannel processors override it.
+    fn process_planar(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        for (input, output) in inputs.iter().zip(outputs.iter_mut()) {
+            self.process(input, output);
+        }
+    }
+
+    /// Set a parameter by name
+    ///
+    /// Values outside the parameter's range are clamped. Continuous
+    /// parameters ramp to the new value over the ramp time.
+    ///
+    /// # Returns
+    /// An error if the value is NaN or infinite, or the processor has no
+    /// such parameter
+    fn set_parameter(&mut self, name: &str, value: f32) -> Result<(), HarmonyError>;
+
+    /// Set a table parameter (e.g. a waveshaping curve) by name
+    ///
+    /// Tables apply immediately, without ramping.
+    ///
+    /// # Returns
+    /// An error if the processor has no such table or the table is invalid
+    fn set_table(&mut self, name: &str, _table: &[f32]) -> Result<(), HarmonyError> {
+        Err(HarmonyError::NotFound(format!(
+            "Unknown table parameter '{}'",
+            name
+        )))
+    }
+
+    /// Delay the processor adds to its output, in samples at the host
+    /// sample rate
+    ///
+    /// The graph delays parallel paths to match. May change with
+    /// parameters; the graph re-reads it after each parameter change.
+    fn latency_samples(&self) -> usize {
+        0
+    }
+
+    /// Set how long continuous parameters take to reach a new value
+    ///
+    /// Processors without smoothed parameters ignore it.
+    fn set_ramp_time(&mut self, _seconds: f32) {}
+
+    /// Handle a MIDI channel message (status, data1, data2)
+    ///
+    /// Called between blocks, at the frame the message is scheduled for.
+    /// Processors that don't respond to MIDI ignore it.
+    fn midi_event(&mut self, _message: [u8; 3]) {}
+
+    /// Clear internal state and finish parameter ramps, keeping parameters
+    fn reset(&mut self);
+
+    /// Current parameter values (ramp targets), in the order they should
+    /// be restored
+    ///
+    /// Processors without parameters return nothing.
+    fn parameter_values(&self) -> Vec<(&'static str, f32)> {
+        Vec::new()
+    }
+
+    /// Save the parameter values as a PropsBinaryFormat buffer
+    fn serialize_state(&self) -> Vec<u8> {
+        let values = self.parameter_values();
+        let mut encoder = PropsBinaryFormat::new();
+        encoder.init_header(values.len() as u32);
+        for (name, value) in values {
+            // Parameter names are short constants
+            let _ = encoder.write_float32(name, value);
+        }
+        encoder.finalize()
+    }
+
+    /// Load parameter values saved by `serialize_state`
+    ///
+    /// Values apply immediately and internal state is cleared, as when a
+    /// patch is loaded.
+    ///
+    /// # Returns
+    /// An error for malformed buffers, non-Float32 properties or unknown
+    /// parameters; values before the failing one are kept
+    fn restore_state(&mut self, state: &[u8]) -> Result<(), HarmonyError> {
+        let mut decoder = PropsBinaryDecoder::new(state.to_vec())?;
+        for _ in 0..decoder.property_count() {
+            let (name, prop_type, value) = decoder.read_property()?;
+            if prop_type != PropType::Float32 {
+                return Err(HarmonyError::Malformed(format!(
+                    "Parameter '{}' is not a Float32 property",
+                    name
+                )));
+            }
+            let value = PropsBinaryDecoder::read_float32(&value)?;
+            self.set_parameter(&name, value)?;
+        }
+        self.reset();
+        Ok(())
+    }
+}
@@ -0,0 +1,397 @@
+//! Oversampling wrapper
+//!
+//! Runs a nonlinear processor at 2x or 4x the sample rate so harmonics it
+//! generates above Nyquist are filtered out instead of aliasing back into
+//! the audible band. Each 2x stage is a polyphase half-band FIR: the
+//! upsampler computes both output phases from the input history without
+//! inserting zeros, and the downsampler filters only the samples it keeps.
+//! 4x cascades two stages.
+//!
+//! The filters delay the signal by `(TAPS - 1) * (1 - 1/factor)` samples,
+//! rounded down (22.5 at 4x), which `latency_samples` reports.
+//!
+//! The factor is the `oversampling` parameter (1 = off, 2 or 4); every
+//! other parameter is forwarded to the wrapped processor. Buffers for 4x
+//! are allocated in `prepare`, so switching factors never allocates.
+//!
+//! Performance Targets (512 samples @ 48kHz, release):
+//! - 4x overhead beyond the wrapped processor: < 200µs
+
+use std::f64::consts::PI;
+
+use harmony_error::HarmonyError;
+
+use super::{check_finite, AudioProcessor, DEFAULT_SAMPLE_RATE};
+
+/// Highest supported oversampling factor
+pub const MAX_OVERSAMPLING: usize = 4;
+
+/// Half-band filter length (odd, so the filter delay is a whole sample)
+const TAPS: usize = 31;
+
+/// Taps per polyphase branch
+const PHASE_TAPS: usize = TAPS.div_ceil(2);
+
+/// Windowed-sinc half-band lowpass split into its even and odd phases
+///
+/// Normalized to unity DC gain, so each phase sums to 0.5.
+fn half_band_phases() -> [[f32; PHASE_TAPS]; 2] {
+    let center = (TAPS - 1) as f64 / 2.0;
+    let mut taps = [0.0f64; TAPS];
+    for (n, tap) in taps.iter_mut().enumerate() {
+        let x = n as f64 - center;
+        let sinc = if x == 0.0 {
+            1.0
+        } else {
+            (PI * x / 2.0).sin() / (PI * x / 2.0)
+        };
+        // Blackman window
+        let w = 2.0 * PI * n as f64 / (TAPS - 1) as f64;
+        *tap = sinc * (0.42 - 0.5 * w.cos() + 0.08 * (2.0 * w).cos());
+    }
+    let sum: f64 = taps.iter().sum();
+
+    let mut phases = [[0.0; PHASE_TAPS]; 2];
+    for (n, tap) in taps.iter().enumerate() {
+        phases[n % 2][n / 2] = (tap / sum) as f32;
+    }
+    phases
+}
+
+/// Newest-first sample history for one polyphase branch
+#[derive(Clone)]
+struct History([f32; PHASE_TAPS]);
+
+impl History {
+    fn new() -> Self {
+        Self([0.0; PHASE_TAPS])
+    }
+
+    #[inline]
+    fn push(&mut self, sample: f32) {
+        self.0.copy_within(..PHASE_TAPS - 1, 1);
+        self.0[0] = sample;
+    }
+
+    #[inline]
+    fn dot(&self, coefficients: &[f32; PHASE_TAPS]) -> f32 {
+        self.0.iter().zip(coefficients).map(|(x, h)| x * h).sum()
+    }
+
+    fn clear(&mut self) {
+        self.0 = [0.0; PHASE_TAPS];
+    }
+}
+
+/// One 2x up/down sampling stage
+#[derive(Clone)]
+struct HalfBandStage {
+    phases: [[f32; PHASE_TAPS]; 2],
+    up: History,
+    down_even: History,
+    down_odd: History,
+    /// Odd sample of the previous downsampled pair
+    pending_odd: f32,
+}
+
+impl HalfBandStage {
+    fn new() -> Self {
+        Self {
+            phases: half_band_phases(),
+            up: History::new(),
+            down_even: History::new(),
+            down_odd: History::new(),
+            pending_odd: 0.0,
+        }
+    }
+
+    /// `output` has twice the length of `input`
+    fn upsample(&mut self, input: &[f32], output: &mut [f32]) {
+        for (&sample, pair) in input.iter().zip(output.chunks_exact_mut(2)) {
+            self.up.push(sample);
+            // x2 restores the energy lost to the implied zero samples
+            pair[0] = 2.0 * self.up.dot(&self.phases[0]);
+            pair[1] = 2.0 * self.up.dot(&self.phases[1]);
+        }
+    }
+
+    /// `input` has twice the length of `output`
+    fn downsample(&mut self, input: &[f32], output: &mut [f32]) {
+        for (pair, out) in input.chunks_exact(2).zip(output.iter_mut()) {
+            self.down_even.push(pair[0]);
+            self.down_odd.push(self.pending_odd);
+            self.pending_odd = pair[1];
+            *out = self.down_even.dot(&self.phases[0]) + self.down_odd.dot(&self.phases[1]);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.up.clear();
+        self.down_even.clear();
+        self.down_odd.clear();
+        self.pending_odd = 0.0;
+    }
+}
+
+/// Runs a processor at 1x, 2x or 4x the host sample rate
+pub struct OversampledProcessor<P: AudioProcessor> {
+    inner: P,
+    factor: usize,
+    stages: [HalfBandStage; 2],
+    /// 2x rate buffer
+    half: Vec<f32>,
+    /// 4x rate (or 2x, at factor 2) buffers around the wrapped processor
+    upsampled: Vec<f32>,
+    processed: Vec<f32>,
+    sample_rate: f32,
+    max_block_size: usize,
+}
+
+impl<P: AudioProcessor> OversampledProcessor<P> {
+    /// Wrap a processor, initially without oversampling
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            factor: 1,
+            stages: [HalfBandStage::new(), HalfBandStage::new()],
+            half: Vec::new(),
+            upsampled: Vec::new(),
+            processed: Vec::new(),
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            max_block_size: 0,
+        }
+    }
+
+    /// Current oversampling factor (1, 2 or 4)
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    /// Set the oversampling factor, rounding to 1, 2 or 4
+    pub fn set_factor(&mut self, factor: usize) {
+        let factor = match factor {
+            0 | 1 => 1,
+            2 | 3 => 2,
+            _ => MAX_OVERSAMPLING,
+        };
+        if factor != self.factor {
+            self.factor = factor;
+            self.prepare_inner();
+            for stage in &mut self.stages {
+                stage.reset();
+            }
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut P {
+        &mut self.inner
+    }
+
+    fn resize_buffers(&mut self, block_size: usize) {
+        self.half.resize(block_size * 2, 0.0);
+        self.upsampled.resize(block_size * MAX_OVERSAMPLING, 0.0);
+        self.processed.resize(block_size * MAX_OVERSAMPLING, 0.0);
+    }
+
+    fn prepare_inner(&mut self) {
+        self.inner.prepare(
+            self.sample_rate * self.factor as f32,
+            self.max_block_size * self.factor,
+        );
+    }
+}
+
+impl<P: AudioProcessor> AudioProcessor for OversampledProcessor<P> {
+    fn prepare(&mut self, sample_rate: f32, max_block_size: usize) {
+        self.sample_rate = sample_rate;
+        self.max_block_size = max_block_size;
+        self.resize_buffers(max_block_size);
+        self.prepare_inner();
+    }
+
+    fn process(&mut self, input: &[f32], output: &mut [f32]) {
+        let len = input.len();
+        if self.factor == 1 {
+            self.inner.process(input, output);
+            return;
+        }
+        if self.upsampled.len() < len * self.factor {
+            // Blocks longer than prepared for
+            self.resize_buffers(len);
+        }
+
+        let [first, second] = &mut self.stages;
+        let upsampled = &mut self.upsampled[..len * self.factor];
+        let processed = &mut self.processed[..len * self.factor];
+        if self.factor == 2 {
+            first.upsample(input, upsampled);
+            self.inner.process(upsampled, processed);
+            first.downsample(processed, output);
+        } else {
+            let half = &mut self.half[..len * 2];
+            first.upsample(input, half);
+            second.upsample(half, upsampled);
+            self.inner.process(upsampled, processed);
+            second.downsample(processed, half);
+            first.downsample(half, output);
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> Result<(), HarmonyError> {
+        check_finite(name, value)?;
+        match name {
+            "oversampling" => {
+                self.set_factor(value.round().clamp(1.0, MAX_OVERSAMPLING as f32) as usize);
+                Ok(())
+            }
+            _ => self.inner.set_parameter(name, value),
+        }
+    }
+
+    fn set_ramp_time(&mut self, seconds: f32) {
+        self.inner.set_ramp_time(seconds);
+    }
+
+    fn set_table(&mut self, name: &str, table: &[f32]) -> Result<(), HarmonyError> {
+        self.inner.set_table(name, table)
+    }
+
+    fn midi_event(&mut self, message: [u8; 3]) {
+        self.inner.midi_event(message);
+    }
+
+    fn latency_samples(&self) -> usize {
+        // Each 2x stage delays (TAPS - 1) / 2 samples up and down at its rate
+        let filters = (TAPS - 1) * (self.factor - 1) / self.factor;
+        filters + self.inner.latency_samples() / self.factor
+    }
+
+    fn parameter_values(&self) -> Vec<(&'static str, f32)> {
+        let mut values = self.inner.parameter_values();
+        values.push(("oversampling", self.factor as f32));
+        values
+    }
+
+    fn reset(&mut self) {
+        for stage in &mut self.stages {
+            stage.reset();
+        }
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processors::{GainProcessor, WaveshapeCurve, WaveshaperProcessor};
+
+    /// Magnitude of the `frequency` component of `signal`
+    fn magnitude(signal: &[f32], frequency: f32, sample_rate: f32) -> f32 {
+        let (mut re, mut im) = (0.0f32, 0.0f32);
+        for (n, &sample) in signal.iter().enumerate() {
+            let phase = 2.0 * std::f32::consts::PI * frequency * n as f32 / sample_rate;
+            re += sample * phase.cos();
+            im += sample * phase.sin();
+        }
+        2.0 * (re * re + im * im).sqrt() / signal.len() as f32
+    }
+
+    #[test]
+    fn test_linear_processor_passes_through() {
+        for factor in [1.0, 2.0, 4.0] {
+            let mut processor = OversampledProcessor::new(GainProcessor::new());
+            processor.prepare(48000.0, 64);
+            processor.set_parameter("oversampling", factor).unwrap();
+
+            // DC settles to unity once the filters fill
+            let mut output = [0.0; 64];
+            processor.process(&[1.0; 64], &mut output);
+            assert!(
+                (output[63] - 1.0).abs() < 1e-4,
+                "{}x: {}",
+                factor,
+                output[63]
+            );
+
+            // A 1kHz tone keeps its level
+            let input: Vec<f32> = (0..480)
+                .map(|n| (2.0 * std::f32::consts::PI * 1000.0 * n as f32 / 48000.0).sin())
+                .collect();
+            let mut output = vec![0.0; 480];
+            for (input, output) in input.chunks(48).zip(output.chunks_mut(48)) {
+                processor.process(input, output);
+            }
+            let level = magnitude(&output[96..], 1000.0, 48000.0);
+            assert!((level - 1.0).abs() < 0.02, "{}x: {}", factor, level);
+        }
+    }
+
+    #[test]
+    fn test_latency_matches_impulse_peak() {
+        for factor in [1, 2, 4] {
+            let mut processor = OversampledProcessor::new(GainProcessor::new());
+            processor.prepare(48000.0, 64);
+            processor.set_factor(factor);
+            let mut impulse = [0.0; 64];
+            impulse[0] = 1.0;
+            let mut output = [0.0; 64];
+            processor.process(&impulse, &mut output);
+
+            let peak = (0..output.len())
+                .max_by(|&a, &b| output[a].total_cmp(&output[b]))
+                .unwrap();
+            let latency = processor.latency_samples();
+            assert!(
+                peak.abs_diff(latency) <= 1,
+                "{}x: {} vs {}",
+                factor,
+                peak,
+                latency
+            );
+        }
+    }
+
+    #[test]
+    fn test_oversampling_reduces_aliasing() {
+        // 3rd harmonic of 15kHz (45kHz) aliases to 3kHz at 48kHz
+        let input: Vec<f32> = (0..4800)
+            .map(|n| (2.0 * std::f32::consts::PI * 15000.0 * n as f32 / 48000.0).sin())
+            .collect();
+        let alias = |factor: f32| {
+            let mut processor = OversampledProcessor::new(WaveshaperProcessor::new());
+            processor.inner_mut().set_curve(WaveshapeCurve::Hard);
+            processor.prepare(48000.0, 480);
+            processor.set_parameter("drive", 4.0).unwrap();
+            processor.set_parameter("oversampling", factor).unwrap();
+            processor.reset();
+            let mut output = vec![0.0; input.len()];
+            for (input, output) in input.chunks(480).zip(output.chunks_mut(480)) {
+                processor.process(input, output);
+            }
+            magnitude(&output[480..], 3000.0, 48000.0)
+        };
+
+        let plain = alias(1.0);
+        assert!(plain > 0.1, "expected aliasing without oversampling");
+        assert!(alias(2.0) < plain * 0.1);
+        assert!(alias(4.0) < plain * 0.1);
+    }
+
+    #[test]
+    fn test_parameters() {
+        let mut processor = OversampledProcessor::new(WaveshaperProcessor::new());
+        processor.set_parameter("oversampling", 3.4).unwrap();
+        assert_eq!(processor.factor(), 2);
+        processor.set_parameter("oversampling", 16.0).unwrap();
+        assert_eq!(processor.factor(), 4);
+        processor.set_parameter("oversampling", 0.0).unwrap();
+        assert_eq!(processor.factor(), 1);
+
+        processor.set_parameter("curve", 1.0).unwrap();
+        assert!(processor.set_parameter("tone", 0.5).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! Parameter smoothing
+//!
+//! `SmoothedParam` ramps a parameter to each new target over a fixed
+//! number of samples instead of jumping, so UI-driven changes don't cause
+//! zipper noise. Processors call `advance` once per sample.
+//!
+//! Linear ramps suit amplitudes and mixes; exponential ramps move by a
+//! constant ratio per sample, which sounds even for frequencies. An
+//! exponential ramp between values that are not both positive falls back
+//! to linear.
+
+/// Ramp shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RampShape {
+    Linear,
+    Exponential,
+}
+
+/// Number of samples in a ramp of `seconds` at `sample_rate`
+pub fn ramp_samples(seconds: f32, sample_rate: f32) -> usize {
+    (seconds.max(0.0) * sample_rate).round() as usize
+}
+
+/// A parameter that ramps to new values
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmoothedParam {
+    current: f32,
+    target: f32,
+    shape: RampShape,
+    ramp_samples: usize,
+
+    /// Per-sample increment (linear) or factor (exponential)
+    step: f32,
+    exponential: bool,
+    remaining: usize,
+}
+
+impl SmoothedParam {
+    /// Create a linear parameter at `value` that changes instantly until a
+    /// ramp length is set
+    pub fn new(value: f32) -> Self {
+        Self {
+            current: value,
+            target: value,
+            shape: RampShape::Linear,
+            ramp_samples: 0,
+            step: 0.0,
+            exponential: false,
+            remaining: 0,
+        }
+    }
+
+    /// Use a different ramp shape
+    pub fn with_shape(mut self, shape: RampShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Set the length of future ramps; a ramp in progress keeps its pace
+    pub fn set_ramp_samples(&mut self, samples: usize) {
+        self.ramp_samples = samples;
+    }
+
+    pub fn ramp_samples(&self) -> usize {
+        self.ramp_samples
+    }
+
+    /// Start ramping from the current value to `target`
+    pub fn set_target(&mut self, target: f32) {
+        self.target = target;
+        if self.ramp_samples == 0 || target == self.current {
+            self.snap();
+            return;
+        }
+        let steps = self.ramp_samples as f32;
+        self.exponential =
+            self.shape == RampShape::Exponential && self.current > 0.0 && target > 0.0;
+        self.step = if self.exponential {
+            (target / self.current).powf(1.0 / steps)
+        } else {
+            (target - self.current) / steps
+        };
+        self.remaining = self.ramp_samples;
+    }
+
+    /// Jump to `value` without ramping
+    pub fn set_immediate(&mut self, value: f32) {
+        self.target = value;
+        self.snap();
+    }
+
+    /// Finish any ramp in progress
+    pub fn snap(&mut self) {
+        self.current = self.target;
+        self.remaining = 0;
+    }
+
+    /// Advance one sample and return the new value
+    #[inline]
+    pub fn advance(&mut self) -> f32 {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                self.current = self.target;
+            } else if self.exponential {
+                self.current *= self.step;
+            } else {
+                self.current += self.step;
+            }
+        }
+        self.current
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Check whether a ramp is in progress
+    pub fn is_smoothing(&self) -> bool {
+        self.remaining > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(param: &mut SmoothedParam, samples: usize) -> Vec<f32> {
+        (0..samples).map(|_| param.advance()).collect()
+    }
+
+    #[test]
+    fn test_linear_ramp() {
+        let mut param = SmoothedParam::new(0.0);
+        param.set_target(1.0);
+        assert!(!param.is_smoothing());
+        assert_eq!(param.current(), 1.0);
+
+        param.set_ramp_samples(4);
+        param.set_target(0.0);
+        assert_eq!(ramp(&mut param, 5), vec![0.75, 0.5, 0.25, 0.0, 0.0]);
+        assert!(!param.is_smoothing());
+
+        // Retargeting mid-ramp starts from the current value
+        param.set_target(1.0);
+        param.advance();
+        param.set_target(0.75);
+        assert_eq!(ramp(&mut param, 4), vec![0.375, 0.5, 0.625, 0.75]);
+    }
+
+    #[test]
+    fn test_exponential_ramp() {
+        let mut param = SmoothedParam::new(100.0).with_shape(RampShape::Exponential);
+        param.set_ramp_samples(2);
+        param.set_target(10000.0);
+        let values = ramp(&mut param, 2);
+        assert!((values[0] - 1000.0).abs() < 0.01);
+        assert_eq!(values[1], 10000.0);
+
+        // Falls back to linear through zero
+        param.set_target(-10000.0);
+        assert_eq!(ramp(&mut param, 2), vec![0.0, -10000.0]);
+    }
+
+    #[test]
+    fn test_set_immediate_and_ramp_samples() {
+        let mut param = SmoothedParam::new(0.0);
+        param.set_ramp_samples(ramp_samples(0.01, 48000.0));
+        assert_eq!(param.ramp_samples(), 480);
+        param.set_target(1.0);
+        param.set_immediate(0.5);
+        assert!(!param.is_smoothing());
+        assert_eq!(param.advance(), 0.5);
+    }
+}
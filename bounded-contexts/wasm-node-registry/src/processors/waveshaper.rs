@@ -0,0 +1,287 @@
+//! Waveshaper distortion processor
+//!
+//! Drives the input into a saturation curve and mixes the result with the
+//! dry signal. Parameters: `drive` (0-10), `mix` (0 = dry, 1 = wet) and
+//! `curve` (0 = soft, 1 = hard, 2 = asymmetric). Custom lookup-table
+//! curves are set with `set_curve` or as the "curve" table parameter; see
+//! `curve` for building them.
+
+use harmony_error::HarmonyError;
+
+use super::curve;
+use super::smoothing::{ramp_samples, SmoothedParam};
+use super::{check_finite, AudioProcessor, DEFAULT_RAMP_SECONDS, DEFAULT_SAMPLE_RATE};
+use crate::simd;
+
+/// Waveshaper distortion processor
+pub struct WaveshaperProcessor {
+    drive: SmoothedParam,
+    mix: SmoothedParam,
+    curve_type: WaveshapeCurve,
+    sample_rate: f32,
+    ramp_time: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WaveshapeCurve {
+    Soft,
+    Hard,
+    Asymmetric,
+    /// Lookup table spanning inputs -1..1
+    Custom(Vec<f32>),
+}
+
+impl WaveshaperProcessor {
+    /// Registry type_id of the waveshaper node
+    pub const TYPE_ID: &'static str = "audio.waveshaper";
+
+    /// Create new waveshaper processor
+    pub fn new() -> Self {
+        let mut processor = Self {
+            drive: SmoothedParam::new(1.0),
+            mix: SmoothedParam::new(1.0),
+            curve_type: WaveshapeCurve::Soft,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            ramp_time: DEFAULT_RAMP_SECONDS,
+        };
+        processor.update_ramps();
+        processor
+    }
+
+    fn update_ramps(&mut self) {
+        let samples = ramp_samples(self.ramp_time, self.sample_rate);
+        self.drive.set_ramp_samples(samples);
+        self.mix.set_ramp_samples(samples);
+    }
+
+    /// Apply waveshaping curve
+    #[inline]
+    fn apply_curve(&self, input: f32) -> f32 {
+        match &self.curve_type {
+            WaveshapeCurve::Soft => {
+                // Soft clipping using tanh
+                input.tanh()
+            }
+            WaveshapeCurve::Hard => {
+                // Hard clipping
+                input.clamp(-1.0, 1.0)
+            }
+            WaveshapeCurve::Asymmetric => {
+                // Asymmetric distortion
+                if input > 0.0 {
+                    1.0 - (-input).exp()
+                } else {
+                    -1.0 + input.exp()
+                }
+            }
+            WaveshapeCurve::Custom(curve) if curve.is_empty() => input,
+            WaveshapeCurve::Custom(curve) => {
+                // Lookup table interpolation
+                let last = (curve.len() - 1) as f32;
+                let normalized = (input + 1.0) * 0.5; // Map -1..1 to 0..1
+                let index = (normalized * last).clamp(0.0, last);
+                let idx = index.floor() as usize;
+                let frac = index - idx as f32;
+
+                if idx + 1 < curve.len() {
+                    curve[idx] * (1.0 - frac) + curve[idx + 1] * frac
+                } else {
+                    curve[idx]
+                }
+            }
+        }
+    }
+
+    /// Set curve type
+    pub fn set_curve(&mut self, curve: WaveshapeCurve) {
+        self.curve_type = curve;
+    }
+}
+
+impl Default for WaveshaperProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioProcessor for WaveshaperProcessor {
+    fn prepare(&mut self, sample_rate: f32, _max_block_size: usize) {
+        self.sample_rate = sample_rate;
+        self.update_ramps();
+    }
+
+    /// Process audio buffer
+    ///
+    /// # Performance
+    /// Target: < 1ms for 512 sample buffer @ 48kHz
+    fn process(&mut self, input: &[f32], output: &mut [f32]) {
+        if !self.drive.is_smoothing() && !self.mix.is_smoothing() {
+            let drive = self.drive.current();
+            if self.curve_type == WaveshapeCurve::Hard {
+                simd::hard_clip(input, drive, output);
+            } else {
+                for (out, &sample) in output.iter_mut().zip(input) {
+                    *out = self.apply_curve(sample * drive);
+                }
+            }
+            simd::crossfade(input, output, self.mix.current());
+            return;
+        }
+        for (out, &sample) in output.iter_mut().zip(input) {
+            let driven = sample * self.drive.advance();
+            let shaped = self.apply_curve(driven);
+            let mix = self.mix.advance();
+            *out = sample * (1.0 - mix) + shaped * mix;
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> Result<(), HarmonyError> {
+        check_finite(name, value)?;
+        match name {
+            "drive" => self.drive.set_target(value.clamp(0.0, 10.0)),
+            "mix" => self.mix.set_target(value.clamp(0.0, 1.0)),
+            "curve" => {
+                self.curve_type = match value.round().clamp(0.0, 2.0) as u8 {
+                    0 => WaveshapeCurve::Soft,
+                    1 => WaveshapeCurve::Hard,
+                    _ => WaveshapeCurve::Asymmetric,
+                }
+            }
+            _ => {
+                return Err(HarmonyError::NotFound(format!(
+                    "Unknown waveshaper parameter '{}'",
+                    name
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn set_table(&mut self, name: &str, table: &[f32]) -> Result<(), HarmonyError> {
+        match name {
+            "curve" => {
+                curve::validate_table(table)?;
+                self.curve_type = WaveshapeCurve::Custom(table.to_vec());
+                Ok(())
+            }
+            _ => Err(HarmonyError::NotFound(format!(
+                "Unknown waveshaper table parameter '{}'",
+                name
+            ))),
+        }
+    }
+
+    fn set_ramp_time(&mut self, seconds: f32) {
+        self.ramp_time = seconds;
+        self.update_ramps();
+    }
+
+    fn reset(&mut self) {
+        self.drive.snap();
+        self.mix.snap();
+    }
+
+    /// Custom curves are not parameters and are left out
+    fn parameter_values(&self) -> Vec<(&'static str, f32)> {
+        let mut values = vec![("drive", self.drive.target()), ("mix", self.mix.target())];
+        let curve = match self.curve_type {
+            WaveshapeCurve::Soft => Some(0.0),
+            WaveshapeCurve::Hard => Some(1.0),
+            WaveshapeCurve::Asymmetric => Some(2.0),
+            WaveshapeCurve::Custom(_) => None,
+        };
+        if let Some(curve) = curve {
+            values.push(("curve", curve));
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_soft_clipping() {
+        let mut processor = WaveshaperProcessor::new();
+        processor.set_curve(WaveshapeCurve::Soft);
+
+        let input = vec![0.0, 0.5, 1.0, 2.0];
+        let mut output = vec![0.0; 4];
+
+        processor.process(&input, &mut output);
+
+        // Verify output is bounded
+        assert!(output.iter().all(|&x| x.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_mix_parameter() {
+        let mut processor = WaveshaperProcessor::new();
+        processor.set_parameter("mix", 0.0).unwrap();
+        processor.reset();
+
+        let input = vec![0.5; 4];
+        let mut output = vec![0.0; 4];
+
+        processor.process(&input, &mut output);
+
+        // With mix=0, output should equal input
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn test_curve_parameter() {
+        let mut processor = WaveshaperProcessor::new();
+        processor.set_parameter("curve", 1.0).unwrap();
+        processor.set_parameter("drive", 4.0).unwrap();
+        processor.reset();
+        assert_eq!(processor.curve_type, WaveshapeCurve::Hard);
+
+        let mut output = [0.0; 2];
+        processor.process(&[0.5, -0.1], &mut output);
+        assert_eq!(output, [1.0, -0.4]);
+
+        assert!(processor.set_parameter("tone", 0.5).is_err());
+    }
+
+    #[test]
+    fn test_curve_table() {
+        let mut processor = WaveshaperProcessor::new();
+        let table = curve::from_breakpoints(&[(-1.0, 0.5), (1.0, -0.5)], 16).unwrap();
+        processor.set_table("curve", &table).unwrap();
+        assert!(processor
+            .parameter_values()
+            .iter()
+            .all(|&(name, _)| name != "curve"));
+
+        let mut output = [0.0; 3];
+        processor.process(&[-1.0, 0.5, 1.0], &mut output);
+        assert_eq!(output[0], 0.5);
+        assert!((output[1] + 0.25).abs() < 1e-6);
+        assert_eq!(output[2], -0.5);
+
+        assert!(processor.set_table("curve", &[0.0; 10]).is_err());
+        assert!(processor.set_table("curve", &[f32::NAN; 16]).is_err());
+        assert!(processor.set_table("drive", &table).is_err());
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let mut processor = WaveshaperProcessor::new();
+        processor.set_parameter("drive", 3.0).unwrap();
+        processor.set_parameter("mix", 0.5).unwrap();
+        processor.set_parameter("curve", 2.0).unwrap();
+        let state = processor.serialize_state();
+
+        let mut restored = WaveshaperProcessor::new();
+        restored.restore_state(&state).unwrap();
+        assert_eq!(restored.parameter_values(), processor.parameter_values());
+        assert!(!restored.drive.is_smoothing());
+        assert_eq!(restored.curve_type, WaveshapeCurve::Asymmetric);
+
+        assert!(restored.restore_state(&[1, 2, 3]).is_err());
+        let gain_state = crate::processors::GainProcessor::new().serialize_state();
+        assert!(restored.restore_state(&gain_state).is_err());
+    }
+}
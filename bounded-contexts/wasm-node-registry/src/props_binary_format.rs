@@ -2,8 +2,16 @@
 //!
 //! Provides efficient serialization/deserialization of node properties
 //! with support for various data types and minimal memory overhead.
+//!
+//! Property names and string values may be interned in a shared
+//! [`StringTable`] and referenced by id instead of being stored inline.
+
+use harmony_error::HarmonyError;
 
-use std::mem;
+use crate::string_table::StringTable;
+
+/// Name length marker indicating a u32 string table id follows instead of name bytes
+pub const NAME_REF_MARKER: u16 = u16::MAX;
 
 /// Property type identifiers for binary encoding
 #[repr(u8)]
@@ -20,6 +28,8 @@ pub enum PropType {
     Bool = 8,
     String = 9,
     Array = 10,
+    /// u32 id into a shared string table
+    StringRef = 11,
 }
 
 impl PropType {
@@ -36,6 +46,7 @@ impl PropType {
             8 => Some(PropType::Bool),
             9 => Some(PropType::String),
             10 => Some(PropType::Array),
+            11 => Some(PropType::StringRef),
             _ => None,
         }
     }
@@ -53,6 +64,7 @@ impl PropType {
             PropType::Bool => 1,
             PropType::String => 0, // Variable length
             PropType::Array => 0,  // Variable length
+            PropType::StringRef => 4,
         }
     }
 }
@@ -64,8 +76,8 @@ impl PropType {
 ///   - property_count (u32)
 ///   - total_size (u32)
 /// - Property entries (variable):
-///   - name_length (u16)
-///   - name_bytes (variable)
+///   - name_length (u16), or NAME_REF_MARKER for interned names
+///   - name_bytes (variable), or name_id (u32) for interned names
 ///   - prop_type (u8)
 ///   - value_size (u32)
 ///   - value_bytes (variable)
@@ -101,15 +113,54 @@ impl PropsBinaryFormat {
     }
 
     /// Write a property to the buffer
-    pub fn write_property(&mut self, name: &str, prop_type: PropType, value: &[u8]) {
+    ///
+    /// # Returns
+    /// `invalid_input` if the name is `u16::MAX` bytes or longer; such
+    /// names do not fit the length field (the largest value marks an
+    /// interned name), so use `write_property_interned`
+    pub fn write_property(
+        &mut self,
+        name: &str,
+        prop_type: PropType,
+        value: &[u8],
+    ) -> Result<(), HarmonyError> {
         // Write name length
         let name_bytes = name.as_bytes();
-        let name_len = name_bytes.len() as u16;
+        let name_len = u16::try_from(name_bytes.len())
+            .ok()
+            .filter(|&len| len != NAME_REF_MARKER)
+            .ok_or_else(|| {
+                HarmonyError::InvalidInput(format!(
+                    "Property name of {} bytes is too long to store inline",
+                    name_bytes.len()
+                ))
+            })?;
         self.buffer.extend_from_slice(&name_len.to_le_bytes());
 
         // Write name bytes
         self.buffer.extend_from_slice(name_bytes);
 
+        self.write_type_and_value(prop_type, value);
+        Ok(())
+    }
+
+    /// Write a property whose name is interned in the string table
+    pub fn write_property_interned(
+        &mut self,
+        table: &mut StringTable,
+        name: &str,
+        prop_type: PropType,
+        value: &[u8],
+    ) {
+        // Write name reference
+        let name_id = table.intern(name);
+        self.buffer.extend_from_slice(&NAME_REF_MARKER.to_le_bytes());
+        self.buffer.extend_from_slice(&name_id.to_le_bytes());
+
+        self.write_type_and_value(prop_type, value);
+    }
+
+    fn write_type_and_value(&mut self, prop_type: PropType, value: &[u8]) {
         // Write property type
         self.buffer.push(prop_type as u8);
 
@@ -124,33 +175,39 @@ impl PropsBinaryFormat {
     }
 
     /// Write a Float32 property
-    pub fn write_float32(&mut self, name: &str, value: f32) {
-        self.write_property(name, PropType::Float32, &value.to_le_bytes());
+    pub fn write_float32(&mut self, name: &str, value: f32) -> Result<(), HarmonyError> {
+        self.write_property(name, PropType::Float32, &value.to_le_bytes())
     }
 
     /// Write a Float64 property
-    pub fn write_float64(&mut self, name: &str, value: f64) {
-        self.write_property(name, PropType::Float64, &value.to_le_bytes());
+    pub fn write_float64(&mut self, name: &str, value: f64) -> Result<(), HarmonyError> {
+        self.write_property(name, PropType::Float64, &value.to_le_bytes())
     }
 
     /// Write an Int32 property
-    pub fn write_int32(&mut self, name: &str, value: i32) {
-        self.write_property(name, PropType::Int32, &value.to_le_bytes());
+    pub fn write_int32(&mut self, name: &str, value: i32) -> Result<(), HarmonyError> {
+        self.write_property(name, PropType::Int32, &value.to_le_bytes())
     }
 
     /// Write a Uint32 property
-    pub fn write_uint32(&mut self, name: &str, value: u32) {
-        self.write_property(name, PropType::Uint32, &value.to_le_bytes());
+    pub fn write_uint32(&mut self, name: &str, value: u32) -> Result<(), HarmonyError> {
+        self.write_property(name, PropType::Uint32, &value.to_le_bytes())
     }
 
     /// Write a Bool property
-    pub fn write_bool(&mut self, name: &str, value: bool) {
-        self.write_property(name, PropType::Bool, &[value as u8]);
+    pub fn write_bool(&mut self, name: &str, value: bool) -> Result<(), HarmonyError> {
+        self.write_property(name, PropType::Bool, &[value as u8])
     }
 
     /// Write a String property
-    pub fn write_string(&mut self, name: &str, value: &str) {
-        self.write_property(name, PropType::String, value.as_bytes());
+    pub fn write_string(&mut self, name: &str, value: &str) -> Result<(), HarmonyError> {
+        self.write_property(name, PropType::String, value.as_bytes())
+    }
+
+    /// Write a String property with both name and value interned
+    pub fn write_string_interned(&mut self, table: &mut StringTable, name: &str, value: &str) {
+        let value_id = table.intern(value);
+        self.write_property_interned(table, name, PropType::StringRef, &value_id.to_le_bytes());
     }
 
     /// Finalize and return the buffer
@@ -175,6 +232,17 @@ impl PropsBinaryFormat {
     pub fn len(&self) -> usize {
         self.buffer.len()
     }
+
+    /// Check whether nothing has been written yet
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+}
+
+impl Default for PropsBinaryFormat {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Binary format decoder for node properties
@@ -187,9 +255,9 @@ pub struct PropsBinaryDecoder {
 
 impl PropsBinaryDecoder {
     /// Create a new decoder from buffer
-    pub fn new(buffer: Vec<u8>) -> Result<Self, &'static str> {
+    pub fn new(buffer: Vec<u8>) -> Result<Self, HarmonyError> {
         if buffer.len() < 8 {
-            return Err("Buffer too small for header");
+            return Err(HarmonyError::Malformed("Buffer too small for header".into()));
         }
 
         let property_count = u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
@@ -208,41 +276,87 @@ impl PropsBinaryDecoder {
         self.property_count
     }
 
+    /// Get total encoded size recorded in the header
+    pub fn total_size(&self) -> u32 {
+        self.total_size
+    }
+
     /// Read next property
-    pub fn read_property(&mut self) -> Result<(String, PropType, Vec<u8>), &'static str> {
+    ///
+    /// Fails on interned names; use [`Self::read_property_with_table`] for those.
+    pub fn read_property(&mut self) -> Result<(String, PropType, Vec<u8>), HarmonyError> {
+        self.read_entry(None)
+    }
+
+    /// Read next property, resolving interned names through the string table
+    pub fn read_property_with_table(
+        &mut self,
+        table: &StringTable,
+    ) -> Result<(String, PropType, Vec<u8>), HarmonyError> {
+        self.read_entry(Some(table))
+    }
+
+    fn read_entry(
+        &mut self,
+        table: Option<&StringTable>,
+    ) -> Result<(String, PropType, Vec<u8>), HarmonyError> {
         if self.cursor >= self.buffer.len() {
-            return Err("End of buffer");
+            return Err(HarmonyError::Malformed("End of buffer".into()));
         }
 
         // Read name length
         if self.cursor + 2 > self.buffer.len() {
-            return Err("Invalid name length");
+            return Err(HarmonyError::Malformed("Invalid name length".into()));
         }
         let name_len = u16::from_le_bytes([
             self.buffer[self.cursor],
             self.buffer[self.cursor + 1],
-        ]) as usize;
+        ]);
         self.cursor += 2;
 
-        // Read name bytes
-        if self.cursor + name_len > self.buffer.len() {
-            return Err("Invalid name bytes");
-        }
-        let name = String::from_utf8(self.buffer[self.cursor..self.cursor + name_len].to_vec())
-            .map_err(|_| "Invalid UTF-8 in name")?;
-        self.cursor += name_len;
+        let name = if name_len == NAME_REF_MARKER {
+            // Read name reference
+            if self.cursor + 4 > self.buffer.len() {
+                return Err(HarmonyError::Malformed("Invalid name reference".into()));
+            }
+            let name_id = u32::from_le_bytes([
+                self.buffer[self.cursor],
+                self.buffer[self.cursor + 1],
+                self.buffer[self.cursor + 2],
+                self.buffer[self.cursor + 3],
+            ]);
+            self.cursor += 4;
+
+            let table = table.ok_or_else(|| {
+                HarmonyError::InvalidInput("String table required for interned name".into())
+            })?;
+            table
+                .get(name_id)
+                .ok_or_else(|| HarmonyError::NotFound("Unknown string id".into()))?
+                .to_string()
+        } else {
+            // Read name bytes
+            let name_len = name_len as usize;
+            if self.cursor + name_len > self.buffer.len() {
+                return Err(HarmonyError::Malformed("Invalid name bytes".into()));
+            }
+            let name = String::from_utf8(self.buffer[self.cursor..self.cursor + name_len].to_vec())
+                .map_err(|_| HarmonyError::Malformed("Invalid UTF-8 in name".into()))?;
+            self.cursor += name_len;
+            name
+        };
 
         // Read property type
         if self.cursor >= self.buffer.len() {
-            return Err("Invalid property type");
+            return Err(HarmonyError::Malformed("Invalid property type".into()));
         }
         let prop_type = PropType::from_u8(self.buffer[self.cursor])
-            .ok_or("Unknown property type")?;
+            .ok_or_else(|| HarmonyError::Malformed("Unknown property type".into()))?;
         self.cursor += 1;
 
         // Read value size
         if self.cursor + 4 > self.buffer.len() {
-            return Err("Invalid value size");
+            return Err(HarmonyError::Malformed("Invalid value size".into()));
         }
         let value_size = u32::from_le_bytes([
             self.buffer[self.cursor],
@@ -254,7 +368,7 @@ impl PropsBinaryDecoder {
 
         // Read value bytes
         if self.cursor + value_size > self.buffer.len() {
-            return Err("Invalid value bytes");
+            return Err(HarmonyError::Malformed("Invalid value bytes".into()));
         }
         let value = self.buffer[self.cursor..self.cursor + value_size].to_vec();
         self.cursor += value_size;
@@ -263,17 +377,17 @@ impl PropsBinaryDecoder {
     }
 
     /// Read Float32 value from bytes
-    pub fn read_float32(bytes: &[u8]) -> Result<f32, &'static str> {
+    pub fn read_float32(bytes: &[u8]) -> Result<f32, HarmonyError> {
         if bytes.len() != 4 {
-            return Err("Invalid Float32 size");
+            return Err(HarmonyError::Malformed("Invalid Float32 size".into()));
         }
         Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
     }
 
     /// Read Float64 value from bytes
-    pub fn read_float64(bytes: &[u8]) -> Result<f64, &'static str> {
+    pub fn read_float64(bytes: &[u8]) -> Result<f64, HarmonyError> {
         if bytes.len() != 8 {
-            return Err("Invalid Float64 size");
+            return Err(HarmonyError::Malformed("Invalid Float64 size".into()));
         }
         Ok(f64::from_le_bytes([
             bytes[0], bytes[1], bytes[2], bytes[3],
@@ -282,32 +396,43 @@ impl PropsBinaryDecoder {
     }
 
     /// Read Int32 value from bytes
-    pub fn read_int32(bytes: &[u8]) -> Result<i32, &'static str> {
+    pub fn read_int32(bytes: &[u8]) -> Result<i32, HarmonyError> {
         if bytes.len() != 4 {
-            return Err("Invalid Int32 size");
+            return Err(HarmonyError::Malformed("Invalid Int32 size".into()));
         }
         Ok(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
     }
 
     /// Read Uint32 value from bytes
-    pub fn read_uint32(bytes: &[u8]) -> Result<u32, &'static str> {
+    pub fn read_uint32(bytes: &[u8]) -> Result<u32, HarmonyError> {
         if bytes.len() != 4 {
-            return Err("Invalid Uint32 size");
+            return Err(HarmonyError::Malformed("Invalid Uint32 size".into()));
         }
         Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
     }
 
     /// Read Bool value from bytes
-    pub fn read_bool(bytes: &[u8]) -> Result<bool, &'static str> {
+    pub fn read_bool(bytes: &[u8]) -> Result<bool, HarmonyError> {
         if bytes.len() != 1 {
-            return Err("Invalid Bool size");
+            return Err(HarmonyError::Malformed("Invalid Bool size".into()));
         }
         Ok(bytes[0] != 0)
     }
 
     /// Read String value from bytes
-    pub fn read_string(bytes: &[u8]) -> Result<String, &'static str> {
-        String::from_utf8(bytes.to_vec()).map_err(|_| "Invalid UTF-8 in string")
+    pub fn read_string(bytes: &[u8]) -> Result<String, HarmonyError> {
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| HarmonyError::Malformed("Invalid UTF-8 in string".into()))
+    }
+
+    /// Read StringRef value from bytes, resolving it through the string table
+    pub fn read_string_ref(bytes: &[u8], table: &StringTable) -> Result<String, HarmonyError> {
+        let id = Self::read_uint32(bytes)
+            .map_err(|_| HarmonyError::Malformed("Invalid StringRef size".into()))?;
+        table
+            .get(id)
+            .map(str::to_string)
+            .ok_or_else(|| HarmonyError::NotFound("Unknown string id".into()))
     }
 }
 
@@ -319,10 +444,10 @@ mod tests {
     fn test_encode_decode_properties() {
         let mut encoder = PropsBinaryFormat::new();
         encoder.init_header(4);
-        encoder.write_float32("frequency", 440.0);
-        encoder.write_float64("gain", 0.75);
-        encoder.write_int32("octave", -2);
-        encoder.write_bool("enabled", true);
+        encoder.write_float32("frequency", 440.0).unwrap();
+        encoder.write_float64("gain", 0.75).unwrap();
+        encoder.write_int32("octave", -2).unwrap();
+        encoder.write_bool("enabled", true).unwrap();
 
         let buffer = encoder.finalize();
         let mut decoder = PropsBinaryDecoder::new(buffer).unwrap();
@@ -347,14 +472,14 @@ mod tests {
         let (name, prop_type, value) = decoder.read_property().unwrap();
         assert_eq!(name, "enabled");
         assert_eq!(prop_type, PropType::Bool);
-        assert_eq!(PropsBinaryDecoder::read_bool(&value).unwrap(), true);
+        assert!(PropsBinaryDecoder::read_bool(&value).unwrap());
     }
 
     #[test]
     fn test_string_property() {
         let mut encoder = PropsBinaryFormat::new();
         encoder.init_header(1);
-        encoder.write_string("label", "Oscillator");
+        encoder.write_string("label", "Oscillator").unwrap();
 
         let buffer = encoder.finalize();
         let mut decoder = PropsBinaryDecoder::new(buffer).unwrap();
@@ -364,4 +489,62 @@ mod tests {
         assert_eq!(prop_type, PropType::String);
         assert_eq!(PropsBinaryDecoder::read_string(&value).unwrap(), "Oscillator");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_interned_properties() {
+        let mut table = StringTable::new();
+        let mut encoder = PropsBinaryFormat::new();
+        encoder.init_header(3);
+        encoder.write_property_interned(&mut table, "frequency", PropType::Float32, &440.0f32.to_le_bytes());
+        encoder.write_string_interned(&mut table, "mode", "lowpass");
+        encoder.write_property_interned(&mut table, "frequency", PropType::Float32, &880.0f32.to_le_bytes());
+
+        // "frequency", "lowpass" and "mode" are stored once each
+        assert_eq!(table.len(), 3);
+
+        let buffer = encoder.finalize();
+        let mut decoder = PropsBinaryDecoder::new(buffer).unwrap();
+
+        let (name, prop_type, value) = decoder.read_property_with_table(&table).unwrap();
+        assert_eq!(name, "frequency");
+        assert_eq!(prop_type, PropType::Float32);
+        assert_eq!(PropsBinaryDecoder::read_float32(&value).unwrap(), 440.0);
+
+        let (name, prop_type, value) = decoder.read_property_with_table(&table).unwrap();
+        assert_eq!(name, "mode");
+        assert_eq!(prop_type, PropType::StringRef);
+        assert_eq!(PropsBinaryDecoder::read_string_ref(&value, &table).unwrap(), "lowpass");
+
+        let (name, _, value) = decoder.read_property_with_table(&table).unwrap();
+        assert_eq!(name, "frequency");
+        assert_eq!(PropsBinaryDecoder::read_float32(&value).unwrap(), 880.0);
+    }
+
+    #[test]
+    fn test_interned_name_requires_table() {
+        let mut table = StringTable::new();
+        let mut encoder = PropsBinaryFormat::new();
+        encoder.init_header(1);
+        encoder.write_property_interned(&mut table, "gain", PropType::Bool, &[1]);
+
+        let mut decoder = PropsBinaryDecoder::new(encoder.finalize()).unwrap();
+        assert!(decoder.read_property().is_err());
+    }
+
+    #[test]
+    fn test_long_names_rejected_inline() {
+        let mut encoder = PropsBinaryFormat::new();
+        encoder.init_header(1);
+        let longest = "n".repeat(u16::MAX as usize - 1);
+        encoder.write_bool(&longest, true).unwrap();
+
+        // u16::MAX would read back as NAME_REF_MARKER
+        for len in [u16::MAX as usize, u16::MAX as usize + 1] {
+            let error = encoder.write_bool(&"n".repeat(len), true).unwrap_err();
+            assert_eq!(error.code(), "invalid_input");
+        }
+
+        let mut decoder = PropsBinaryDecoder::new(encoder.finalize()).unwrap();
+        assert_eq!(decoder.read_property().unwrap().0, longest);
+    }
+}
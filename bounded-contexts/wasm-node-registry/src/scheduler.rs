@@ -0,0 +1,434 @@
+//! Scheduler: sample-accurate parameter and MIDI events
+//!
+//! Queues events stamped with the audio frame they land on and runs an
+//! `AudioGraph` in sub-blocks split at those frames, so a change scheduled
+//! for frame N takes effect at output sample N whatever the block size.
+//! Events usually arrive from the main thread on a wasm-bridge channel as
+//! `ScheduledParameter`, `MidiEvent`, `NodeBypass` and `GraphMix`
+//! messages; `drain_channel` moves them into the queue.
+//!
+//! Parameter names travel as ids in the scheduler's `StringTable`; share
+//! its bytes with the producer so both sides agree on them.
+//!
+//! Events for frames already processed apply at the start of the next
+//! block. The queue is allocated up front and rejects events once full.
+
+use std::collections::VecDeque;
+
+use wasm_bridge::{
+    BridgeContext, BridgeMessage, GraphMix, MidiEvent, NodeBypass, ScheduledParameter,
+};
+
+use crate::audio_graph::AudioGraph;
+use crate::string_table::StringTable;
+
+/// Most events the queue holds
+pub const MAX_PENDING_EVENTS: usize = 1024;
+
+/// What a scheduled event does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventKind {
+    /// Set a parameter; `param_id` is an id in the scheduler's name table
+    Parameter {
+        node_id: u32,
+        param_id: u32,
+        value: f32,
+    },
+    /// MIDI channel message (status, data1, data2)
+    Midi { node_id: u32, message: [u8; 3] },
+    /// Bypass a node or bring it back
+    Bypass { node_id: u32, bypassed: bool },
+    /// Set the graph's dry/wet level
+    Mix { wet: f32 },
+}
+
+/// Event stamped with the audio frame it lands on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScheduledEvent {
+    pub frame: u64,
+    pub kind: EventKind,
+}
+
+impl From<ScheduledParameter> for ScheduledEvent {
+    fn from(message: ScheduledParameter) -> Self {
+        Self {
+            frame: message.frame as u64,
+            kind: EventKind::Parameter {
+                node_id: message.node_id,
+                param_id: message.param_id,
+                value: message.value,
+            },
+        }
+    }
+}
+
+impl From<MidiEvent> for ScheduledEvent {
+    fn from(message: MidiEvent) -> Self {
+        Self {
+            frame: message.frame as u64,
+            kind: EventKind::Midi {
+                node_id: message.node_id,
+                message: [
+                    message.status as u8,
+                    message.data1 as u8,
+                    message.data2 as u8,
+                ],
+            },
+        }
+    }
+}
+
+impl From<NodeBypass> for ScheduledEvent {
+    fn from(message: NodeBypass) -> Self {
+        Self {
+            frame: message.frame as u64,
+            kind: EventKind::Bypass {
+                node_id: message.node_id,
+                bypassed: message.bypassed != 0,
+            },
+        }
+    }
+}
+
+impl From<GraphMix> for ScheduledEvent {
+    fn from(message: GraphMix) -> Self {
+        Self {
+            frame: message.frame as u64,
+            kind: EventKind::Mix { wet: message.wet },
+        }
+    }
+}
+
+/// Frame-ordered event queue driving an `AudioGraph`
+pub struct Scheduler {
+    /// Pending events, ordered by frame then arrival
+    queue: VecDeque<ScheduledEvent>,
+    parameter_names: StringTable,
+    /// Frames processed so far
+    frame: u64,
+    /// Events dropped because the queue was full or their target was invalid
+    rejected: u64,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::with_capacity(MAX_PENDING_EVENTS),
+            parameter_names: StringTable::new(),
+            frame: 0,
+            rejected: 0,
+        }
+    }
+
+    /// Id for a parameter name in scheduled events
+    pub fn parameter_id(&mut self, name: &str) -> u32 {
+        self.parameter_names.intern(name)
+    }
+
+    /// Parameter name table, for sharing with producers
+    pub fn parameter_names(&self) -> &StringTable {
+        &self.parameter_names
+    }
+
+    /// Queues an event
+    ///
+    /// # Returns
+    /// false if the queue is full
+    pub fn schedule(&mut self, event: ScheduledEvent) -> bool {
+        if self.queue.len() >= MAX_PENDING_EVENTS {
+            self.rejected += 1;
+            return false;
+        }
+        let position = self
+            .queue
+            .partition_point(|queued| queued.frame <= event.frame);
+        self.queue.insert(position, event);
+        true
+    }
+
+    /// Queues a parameter change by name
+    pub fn schedule_parameter(&mut self, frame: u64, node_id: u32, name: &str, value: f32) -> bool {
+        let param_id = self.parameter_id(name);
+        self.schedule(ScheduledEvent {
+            frame,
+            kind: EventKind::Parameter {
+                node_id,
+                param_id,
+                value,
+            },
+        })
+    }
+
+    /// Queues every scheduled event waiting on a bridge channel
+    ///
+    /// Other message types on the channel are discarded, so give the
+    /// scheduler a channel of its own.
+    ///
+    /// # Returns
+    /// Number of events queued
+    pub fn drain_channel(&mut self, ctx: &mut BridgeContext, channel: &str) -> usize {
+        let mut queued = 0;
+        while let Some((header, payload)) = ctx.receive(channel) {
+            let event = match header.msg_type {
+                ScheduledParameter::MSG_TYPE => {
+                    ScheduledParameter::decode(&payload).map(ScheduledEvent::from)
+                }
+                MidiEvent::MSG_TYPE => MidiEvent::decode(&payload).map(ScheduledEvent::from),
+                NodeBypass::MSG_TYPE => NodeBypass::decode(&payload).map(ScheduledEvent::from),
+                GraphMix::MSG_TYPE => GraphMix::decode(&payload).map(ScheduledEvent::from),
+                _ => None,
+            };
+            if let Some(event) = event {
+                if self.schedule(event) {
+                    queued += 1;
+                }
+            }
+        }
+        queued
+    }
+
+    /// Processes `input` into `output` (same length), applying each event
+    /// right before the sample it is scheduled for
+    pub fn process(&mut self, graph: &mut AudioGraph, input: &[f32], output: &mut [f32]) {
+        let len = input.len();
+        let mut start = 0;
+        while start < len {
+            while let Some(event) = self.queue.front() {
+                if event.frame > self.frame {
+                    break;
+                }
+                if !apply(&self.parameter_names, graph, event) {
+                    self.rejected += 1;
+                }
+                self.queue.pop_front();
+            }
+
+            let until_event = self
+                .queue
+                .front()
+                .map_or(usize::MAX, |event| (event.frame - self.frame) as usize);
+            let end = len.min(start.saturating_add(until_event));
+            graph.process(&input[start..end], &mut output[start..end]);
+            self.frame += (end - start) as u64;
+            start = end;
+        }
+    }
+
+    /// Frames processed so far
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Moves the frame counter, e.g. when the transport jumps
+    ///
+    /// Queued events keep their frames.
+    pub fn seek(&mut self, frame: u64) {
+        self.frame = frame;
+    }
+
+    /// Drops every queued event
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+
+    /// Number of queued events
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Events dropped because the queue was full or their node or
+    /// parameter did not exist
+    pub fn rejected_events(&self) -> u64 {
+        self.rejected
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Applies an event to the graph, returning false if its target is invalid
+fn apply(parameter_names: &StringTable, graph: &mut AudioGraph, event: &ScheduledEvent) -> bool {
+    match event.kind {
+        EventKind::Parameter {
+            node_id,
+            param_id,
+            value,
+        } => parameter_names
+            .get(param_id)
+            .is_some_and(|name| graph.set_parameter(node_id, name, value).is_ok()),
+        EventKind::Midi { node_id, message } => graph.send_midi(node_id, message).is_ok(),
+        EventKind::Bypass { node_id, bypassed } => graph.set_bypass(node_id, bypassed).is_ok(),
+        EventKind::Mix { wet } => {
+            graph.set_mix(wet);
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processors::{AudioProcessor, ProcessorFactory};
+    use harmony_error::HarmonyError;
+    use wasm_edge_executor::WASMEdgeExecutor;
+
+    /// Passes input while a note is held
+    struct NoteGate {
+        open: bool,
+    }
+
+    impl AudioProcessor for NoteGate {
+        fn prepare(&mut self, _sample_rate: f32, _max_block_size: usize) {}
+
+        fn process(&mut self, input: &[f32], output: &mut [f32]) {
+            let gain = if self.open { 1.0 } else { 0.0 };
+            for (out, &sample) in output.iter_mut().zip(input) {
+                *out = sample * gain;
+            }
+        }
+
+        fn set_parameter(&mut self, name: &str, _value: f32) -> Result<(), HarmonyError> {
+            Err(HarmonyError::NotFound(format!(
+                "Unknown gate parameter '{}'",
+                name
+            )))
+        }
+
+        fn midi_event(&mut self, message: [u8; 3]) {
+            match message[0] & 0xF0 {
+                0x90 if message[2] > 0 => self.open = true,
+                0x80 | 0x90 => self.open = false,
+                _ => {}
+            }
+        }
+
+        fn reset(&mut self) {}
+    }
+
+    /// Gain (node 1) feeding a note gate (node 2), 4-sample blocks
+    fn graph() -> AudioGraph {
+        let mut factory = ProcessorFactory::new();
+        factory.register("test.gate", || Box::new(NoteGate { open: false }));
+        let mut edges = WASMEdgeExecutor::new();
+        edges.add_edge(1, 2, 0);
+        let mut graph = AudioGraph::build(
+            &factory,
+            &[(1, "audio.gain"), (2, "test.gate")],
+            &edges,
+            0,
+            1000.0,
+            4,
+        )
+        .unwrap();
+        graph.set_ramp_time(0.0);
+        graph
+    }
+
+    #[test]
+    fn test_events_land_on_exact_frames() {
+        let mut graph = graph();
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(ScheduledEvent {
+            frame: 2,
+            kind: EventKind::Midi {
+                node_id: 2,
+                message: [0x90, 60, 100],
+            },
+        });
+        scheduler.schedule_parameter(5, 1, "gain", 0.5);
+        scheduler.schedule(ScheduledEvent {
+            frame: 7,
+            kind: EventKind::Midi {
+                node_id: 2,
+                message: [0x80, 60, 0],
+            },
+        });
+
+        let mut output = [0.0; 8];
+        scheduler.process(&mut graph, &[1.0; 8], &mut output);
+        assert_eq!(output, [0.0, 0.0, 1.0, 1.0, 1.0, 0.5, 0.5, 0.0]);
+        assert_eq!(scheduler.frame(), 8);
+        assert_eq!(scheduler.pending(), 0);
+
+        // Late events apply at the start of the next block
+        scheduler.schedule_parameter(3, 1, "gain", 1.0);
+        scheduler.schedule_parameter(9, 1, "level", 1.0);
+        scheduler.schedule_parameter(9, 7, "gain", 1.0);
+        scheduler.schedule(ScheduledEvent {
+            frame: 8,
+            kind: EventKind::Midi {
+                node_id: 2,
+                message: [0x90, 64, 90],
+            },
+        });
+        scheduler.process(&mut graph, &[1.0; 2], &mut output[..2]);
+        assert_eq!(&output[..2], &[1.0, 1.0]);
+        assert_eq!(scheduler.rejected_events(), 2);
+    }
+
+    #[test]
+    fn test_drain_bridge_channel() {
+        let mut scheduler = Scheduler::new();
+        let gain = scheduler.parameter_id("gain");
+
+        let mut ctx = BridgeContext::new(1024);
+        ctx.open_channel("automation", 256);
+        assert!(ctx.register_schema_types());
+        let change = ScheduledParameter {
+            frame: 3.0,
+            node_id: 1,
+            param_id: gain,
+            value: 0.25,
+        };
+        let note = MidiEvent {
+            frame: 1.0,
+            node_id: 2,
+            status: 0x90,
+            data1: 60,
+            data2: 100,
+        };
+        ctx.send_message("automation", &change);
+        ctx.send_message("automation", &note);
+        assert_eq!(scheduler.drain_channel(&mut ctx, "automation"), 2);
+
+        let mut graph = graph();
+        let mut output = [0.0; 4];
+        scheduler.process(&mut graph, &[1.0; 4], &mut output);
+        assert_eq!(output, [0.0, 1.0, 1.0, 0.25]);
+
+        // Bypass the gain at frame 5, then go half dry at frame 6
+        let bypass = NodeBypass {
+            frame: 5.0,
+            node_id: 1,
+            bypassed: 1,
+        };
+        ctx.send_message("automation", &bypass);
+        ctx.send_message(
+            "automation",
+            &GraphMix {
+                frame: 6.0,
+                wet: 0.5,
+            },
+        );
+        assert_eq!(scheduler.drain_channel(&mut ctx, "automation"), 2);
+        scheduler.process(&mut graph, &[1.0, 1.0, 1.0, 0.5], &mut output);
+        assert_eq!(output, [0.25, 1.0, 1.0, 0.5]);
+        assert_eq!(graph.is_bypassed(1), Some(true));
+    }
+
+    #[test]
+    fn test_queue_limit() {
+        let mut scheduler = Scheduler::new();
+        for frame in 0..MAX_PENDING_EVENTS as u64 {
+            assert!(scheduler.schedule_parameter(frame, 1, "gain", 1.0));
+        }
+        assert!(!scheduler.schedule_parameter(0, 1, "gain", 1.0));
+        assert_eq!(scheduler.rejected_events(), 1);
+
+        scheduler.clear();
+        assert_eq!(scheduler.pending(), 0);
+    }
+}
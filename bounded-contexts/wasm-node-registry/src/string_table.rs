@@ -0,0 +1,188 @@
+//! StringTable: Shared id → string table referenced by binary entries
+//!
+//! Snapshots repeat the same property names ("frequency", "gain") and enum
+//! values thousands of times. The string table stores each distinct string
+//! once; props and edge metadata entries then reference it by a u32 id.
+//!
+//! Binary Layout:
+//! - Header (8 bytes):
+//!   - string_count (u32)
+//!   - total_size (u32)
+//! - String entries (variable), id is the entry index:
+//!   - byte_length (u32)
+//!   - utf8_bytes (variable)
+
+use std::collections::HashMap;
+
+use harmony_error::HarmonyError;
+
+/// Size of the string table header in bytes
+pub const STRING_TABLE_HEADER_SIZE: usize = 8;
+
+/// Interning table mapping u32 ids to strings
+#[derive(Debug, Clone, Default)]
+pub struct StringTable {
+    /// Strings in id order
+    strings: Vec<String>,
+    /// Reverse lookup used while interning
+    lookup: HashMap<String, u32>,
+}
+
+impl StringTable {
+    /// Creates an empty string table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Interns a string and returns its id
+    ///
+    /// Interning the same string twice returns the same id.
+    pub fn intern(&mut self, value: &str) -> u32 {
+        if let Some(&id) = self.lookup.get(value) {
+            return id;
+        }
+
+        let id = self.strings.len() as u32;
+        self.strings.push(value.to_string());
+        self.lookup.insert(value.to_string(), id);
+        id
+    }
+
+    /// Returns the string for an id, if present
+    #[inline]
+    pub fn get(&self, id: u32) -> Option<&str> {
+        self.strings.get(id as usize).map(String::as_str)
+    }
+
+    /// Returns the id of an already interned string
+    #[inline]
+    pub fn id_of(&self, value: &str) -> Option<u32> {
+        self.lookup.get(value).copied()
+    }
+
+    /// Returns the number of distinct strings
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns true if no strings have been interned
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Serializes the table to its binary layout
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let body_size: usize = self.strings.iter().map(|s| 4 + s.len()).sum();
+        let total_size = STRING_TABLE_HEADER_SIZE + body_size;
+
+        let mut buffer = Vec::with_capacity(total_size);
+        buffer.extend_from_slice(&(self.strings.len() as u32).to_le_bytes());
+        buffer.extend_from_slice(&(total_size as u32).to_le_bytes());
+
+        for value in &self.strings {
+            buffer.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            buffer.extend_from_slice(value.as_bytes());
+        }
+
+        buffer
+    }
+
+    /// Deserializes a table from its binary layout
+    ///
+    /// # Errors
+    /// Returns error if the buffer is truncated or contains invalid UTF-8
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HarmonyError> {
+        if bytes.len() < STRING_TABLE_HEADER_SIZE {
+            return Err(HarmonyError::Malformed(
+                "Buffer too small for string table header".into(),
+            ));
+        }
+
+        let count = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        let total_size = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+        if total_size > bytes.len() {
+            return Err(HarmonyError::Malformed(
+                "String table size exceeds buffer".into(),
+            ));
+        }
+
+        let mut table = Self::new();
+        let mut cursor = STRING_TABLE_HEADER_SIZE;
+
+        for _ in 0..count {
+            if cursor + 4 > total_size {
+                return Err(HarmonyError::Malformed("Invalid string length".into()));
+            }
+            let len = u32::from_le_bytes([
+                bytes[cursor],
+                bytes[cursor + 1],
+                bytes[cursor + 2],
+                bytes[cursor + 3],
+            ]) as usize;
+            cursor += 4;
+
+            if cursor + len > total_size {
+                return Err(HarmonyError::Malformed("Invalid string bytes".into()));
+            }
+            let value = std::str::from_utf8(&bytes[cursor..cursor + len])
+                .map_err(|_| HarmonyError::Malformed("Invalid UTF-8 in string table".into()))?;
+            cursor += len;
+
+            // Push directly so duplicate entries keep their original ids
+            let id = table.strings.len() as u32;
+            table.lookup.entry(value.to_string()).or_insert(id);
+            table.strings.push(value.to_string());
+        }
+
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_deduplicates() {
+        let mut table = StringTable::new();
+        let a = table.intern("frequency");
+        let b = table.intern("gain");
+        let c = table.intern("frequency");
+
+        assert_eq!(a, c);
+        assert_ne!(a, b);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.get(b), Some("gain"));
+        assert_eq!(table.id_of("frequency"), Some(a));
+        assert_eq!(table.get(99), None);
+    }
+
+    #[test]
+    fn test_serialization_roundtrip() {
+        let mut table = StringTable::new();
+        table.intern("frequency");
+        table.intern("lowpass");
+        table.intern("");
+
+        let bytes = table.to_bytes();
+        let restored = StringTable::from_bytes(&bytes).unwrap();
+
+        assert_eq!(restored.len(), 3);
+        assert_eq!(restored.get(0), Some("frequency"));
+        assert_eq!(restored.get(1), Some("lowpass"));
+        assert_eq!(restored.get(2), Some(""));
+        assert_eq!(restored.id_of("lowpass"), Some(1));
+    }
+
+    #[test]
+    fn test_truncated_buffer() {
+        let mut table = StringTable::new();
+        table.intern("frequency");
+
+        let bytes = table.to_bytes();
+        assert!(StringTable::from_bytes(&bytes[..4]).is_err());
+        assert!(StringTable::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -12,6 +12,8 @@ pub struct WaveshaperNodeConfig {
     pub mix: f32,
     /// Waveshaping curve type
     pub curve_type: WaveshapeCurve,
+    /// Oversampling factor (1 = off, 2 or 4) to reduce aliasing
+    pub oversampling: u8,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,7 +21,12 @@ pub enum WaveshapeCurve {
     Soft,
     Hard,
     Asymmetric,
+    /// Lookup table spanning inputs -1..1, resampled to a power-of-two length
     Custom(Vec<f32>),
+    /// (input, output) points with inputs increasing within -1..1
+    Breakpoints(Vec<(f32, f32)>),
+    /// Formula in `x`, e.g. "tanh(3 * x) / tanh(3)"
+    Expression(String),
 }
 
 impl Default for WaveshaperNodeConfig {
@@ -28,6 +35,7 @@ impl Default for WaveshaperNodeConfig {
             drive: 1.0,
             mix: 1.0,
             curve_type: WaveshapeCurve::Soft,
+            oversampling: 1,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,269 @@
+//! Incremental graph updates with a change log
+//!
+//! `apply_changeset` applies a list of operations as one batch and appends
+//! each applied operation to the graph's change log under an increasing
+//! sequence number. Downstream indexes (spatial, full-text, edge executor)
+//! keep the last sequence they processed and pull `changes_since` it to
+//! sync incrementally; `replay` rebuilds another graph from the records.
+//!
+//! Removing a node logs a `RemoveEdge` for each incident edge before the
+//! `RemoveNode`, so consumers see every edge that disappears. Mutations
+//! made through the direct APIs (`add_node`, `connect`, ...) are not
+//! logged.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-change-log
+
+use crate::graph::queries::component_domain_queries::ComponentDomainLink;
+use crate::graph::{GraphError, GraphNode, HarmonyGraph};
+use harmony_schemas::Edge;
+use serde::{Deserialize, Serialize};
+
+/// One graph mutation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum GraphOp {
+    AddNode {
+        node: GraphNode,
+    },
+
+    /// Replace an existing node, keeping its edges
+    UpdateNode {
+        node: GraphNode,
+    },
+
+    RemoveNode {
+        id: String,
+    },
+    AddEdge {
+        edge: Edge,
+    },
+
+    /// Replace an existing edge with the same ID
+    UpdateEdge {
+        edge: Edge,
+    },
+
+    RemoveEdge {
+        id: String,
+    },
+
+    /// Add or replace a component's link to a domain type
+    AddDomainLink {
+        link: ComponentDomainLink,
+    },
+
+    RemoveDomainLink {
+        component_id: String,
+        domain_type: String,
+    },
+}
+
+/// A logged operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    /// Starts at 1 and increases by 1 per record
+    pub sequence: u64,
+    pub op: GraphOp,
+}
+
+impl HarmonyGraph {
+    /// Apply operations in order, all-or-nothing, and log them
+    ///
+    /// # Returns
+    /// The sequence number of the last record written
+    pub fn apply_changeset(&mut self, ops: Vec<GraphOp>) -> Result<u64, GraphError> {
+        self.batch(|graph| {
+            for op in ops {
+                graph.apply_op(op)?;
+            }
+            Ok(graph.last_sequence())
+        })
+    }
+
+    /// Sequence number of the newest record, or 0 if none were written
+    pub fn last_sequence(&self) -> u64 {
+        self.last_sequence
+    }
+
+    /// Records after a sequence number, oldest first
+    pub fn changes_since(&self, sequence: u64) -> &[ChangeRecord] {
+        let start = self
+            .change_log
+            .partition_point(|record| record.sequence <= sequence);
+        &self.change_log[start..]
+    }
+
+    /// Drop records up to and including a sequence number
+    ///
+    /// Sequence numbers are not reused.
+    pub fn truncate_change_log(&mut self, sequence: u64) {
+        let end = self
+            .change_log
+            .partition_point(|record| record.sequence <= sequence);
+        self.change_log.drain(..end);
+    }
+
+    /// Apply records from another graph's change log, all-or-nothing
+    ///
+    /// The records are applied as a changeset, so they are logged again
+    /// under this graph's own sequence numbers.
+    pub fn replay(&mut self, records: &[ChangeRecord]) -> Result<u64, GraphError> {
+        self.apply_changeset(records.iter().map(|record| record.op.clone()).collect())
+    }
+
+    fn apply_op(&mut self, op: GraphOp) -> Result<(), GraphError> {
+        if let GraphOp::RemoveNode { id } = &op {
+            let incident: Vec<String> = self
+                .outgoing(id)
+                .chain(self.incoming(id))
+                .map(|edge| edge.id.clone())
+                .collect();
+            for edge_id in incident {
+                // Self-loops appear in both directions
+                if self.contains_edge(&edge_id) {
+                    self.apply_op(GraphOp::RemoveEdge { id: edge_id })?;
+                }
+            }
+        }
+        self.execute_op(&op)?;
+        self.last_sequence += 1;
+        self.change_log.push(ChangeRecord {
+            sequence: self.last_sequence,
+            op,
+        });
+        Ok(())
+    }
+
+    /// Apply one operation without logging it
+    pub(crate) fn execute_op(&mut self, op: &GraphOp) -> Result<(), GraphError> {
+        match op {
+            GraphOp::AddNode { node } => self.add_node(node.clone())?,
+            GraphOp::UpdateNode { node } => {
+                if !self.contains_node(&node.id) {
+                    return Err(GraphError::UnknownNode(node.id.clone()));
+                }
+                self.upsert_node(node.clone());
+            }
+            GraphOp::RemoveNode { id } => {
+                self.remove_node(id)?;
+            }
+            GraphOp::AddEdge { edge } => self.add_edge(edge.clone())?,
+            GraphOp::UpdateEdge { edge } => {
+                self.remove_edge(&edge.id)?;
+                self.add_edge(edge.clone())?;
+            }
+            GraphOp::RemoveEdge { id } => {
+                self.remove_edge(id)?;
+            }
+            GraphOp::AddDomainLink { link } => self.add_domain_link(link.clone())?,
+            GraphOp::RemoveDomainLink {
+                component_id,
+                domain_type,
+            } => {
+                self.remove_domain_link(component_id, domain_type);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harmony_schemas::EdgeType;
+
+    fn edge(from: &str, to: &str, edge_type: EdgeType) -> Edge {
+        Edge::new(
+            HarmonyGraph::edge_id(from, to, edge_type),
+            from.to_string(),
+            to.to_string(),
+            edge_type,
+        )
+    }
+
+    #[test]
+    fn test_apply_changeset_and_sync() {
+        let mut graph = HarmonyGraph::new();
+        let last = graph
+            .apply_changeset(vec![
+                GraphOp::AddNode {
+                    node: GraphNode::new("button", "component"),
+                },
+                GraphOp::AddNode {
+                    node: GraphNode::new("color-primary", "token"),
+                },
+                GraphOp::AddEdge {
+                    edge: edge("button", "color-primary", EdgeType::UsesToken),
+                },
+            ])
+            .unwrap();
+        assert_eq!(last, 3);
+
+        // A downstream index syncs from its cursor
+        let mut mirror = HarmonyGraph::new();
+        let mut cursor = 0;
+        mirror.replay(graph.changes_since(cursor)).unwrap();
+        cursor = graph.last_sequence();
+
+        graph
+            .apply_changeset(vec![
+                GraphOp::UpdateNode {
+                    node: GraphNode::new("button", "component")
+                        .with_property("lifecycle_state", "published"),
+                },
+                GraphOp::RemoveNode {
+                    id: "color-primary".to_string(),
+                },
+            ])
+            .unwrap();
+        let changes = graph.changes_since(cursor);
+        assert_eq!(
+            changes.iter().map(|r| r.sequence).collect::<Vec<_>>(),
+            vec![4, 5, 6]
+        );
+        assert_eq!(
+            changes[1].op,
+            GraphOp::RemoveEdge {
+                id: "uses_token:button->color-primary".to_string()
+            }
+        );
+        mirror.replay(changes).unwrap();
+        assert_eq!(mirror.node("button"), graph.node("button"));
+        assert_eq!(mirror.node_count(), 1);
+        assert_eq!(mirror.edge_count(), 0);
+
+        graph.truncate_change_log(5);
+        assert_eq!(graph.changes_since(0).len(), 1);
+        assert_eq!(graph.last_sequence(), 6);
+    }
+
+    #[test]
+    fn test_failed_changeset_is_not_logged() {
+        let mut graph = HarmonyGraph::new();
+        let result = graph.apply_changeset(vec![
+            GraphOp::AddNode {
+                node: GraphNode::new("button", "component"),
+            },
+            GraphOp::UpdateEdge {
+                edge: edge("button", "button", EdgeType::ComposesOf),
+            },
+        ]);
+        assert_eq!(
+            result,
+            Err(GraphError::UnknownEdge(
+                "composes_of:button->button".to_string()
+            ))
+        );
+        assert_eq!(graph.node_count(), 0);
+        assert_eq!(graph.last_sequence(), 0);
+
+        let json = serde_json::to_value(GraphOp::RemoveNode {
+            id: "button".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"op": "remove_node", "id": "button"})
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Component → Message Links Graph Operations
+//!
+//! Tracks which catalog messages each component displays, and answers which
+//! components still show untranslated copy for a locale.
+//! See: harmony-design/DESIGN_SYSTEM.md#localization
+
+use harmony_schemas::{ComponentMessageLink, MessageCatalog};
+use std::collections::BTreeMap;
+
+/// A component with copy missing in some locale
+#[derive(Debug, Clone, PartialEq)]
+pub struct UntranslatedComponent {
+    pub component_id: String,
+
+    /// Keys the component uses that lack complete text for the locale
+    pub message_keys: Vec<String>,
+}
+
+/// Manages Component → Message link relationships in the graph
+pub struct ComponentMessageLinkManager {
+    /// Maps component_id → messages it displays
+    links: BTreeMap<String, Vec<ComponentMessageLink>>,
+}
+
+impl ComponentMessageLinkManager {
+    /// Create a new ComponentMessageLinkManager
+    pub fn new() -> Self {
+        Self {
+            links: BTreeMap::new(),
+        }
+    }
+
+    /// Add a Component → Message link
+    pub fn add_link(&mut self, link: ComponentMessageLink) {
+        self.links
+            .entry(link.component_id.clone())
+            .or_default()
+            .push(link);
+    }
+
+    /// Get all messages a component displays
+    pub fn get_messages(&self, component_id: &str) -> Vec<&ComponentMessageLink> {
+        self.links
+            .get(component_id)
+            .map(|links| links.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get IDs of all components displaying a message
+    pub fn get_components_using(&self, message_key: &str) -> Vec<&str> {
+        self.links
+            .iter()
+            .filter(|(_, links)| links.iter().any(|link| link.message_key == message_key))
+            .map(|(component_id, _)| component_id.as_str())
+            .collect()
+    }
+
+    /// Remove all links for a component
+    pub fn remove_component_links(&mut self, component_id: &str) {
+        self.links.remove(component_id);
+    }
+
+    /// Find components using messages not fully translated for a locale
+    ///
+    /// Keys missing from the catalog count as untranslated. Results are
+    /// ordered by component ID.
+    pub fn find_untranslated(
+        &self,
+        catalog: &MessageCatalog,
+        locale: &str,
+    ) -> Vec<UntranslatedComponent> {
+        self.links
+            .iter()
+            .filter_map(|(component_id, links)| {
+                let mut message_keys: Vec<String> = links
+                    .iter()
+                    .filter(|link| !catalog.is_translated(&link.message_key, locale))
+                    .map(|link| link.message_key.clone())
+                    .collect();
+                message_keys.sort();
+                message_keys.dedup();
+                (!message_keys.is_empty()).then(|| UntranslatedComponent {
+                    component_id: component_id.clone(),
+                    message_keys,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for ComponentMessageLinkManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harmony_schemas::{LocaleDefinition, Message, MessageText};
+
+    #[test]
+    fn test_find_untranslated_components() {
+        let mut catalog = MessageCatalog::new("en");
+        catalog.locales.push(LocaleDefinition::new("de"));
+        catalog.messages.push(
+            Message::new("button.submit", "Submit")
+                .with_translation("de", MessageText::Text("Senden".to_string())),
+        );
+        catalog.messages.push(Message::new("button.cancel", "Cancel"));
+
+        let mut manager = ComponentMessageLinkManager::new();
+        manager.add_link(ComponentMessageLink::new("form-footer", "button.submit"));
+        manager.add_link(ComponentMessageLink::new("form-footer", "button.cancel"));
+        manager.add_link(ComponentMessageLink::new("submit-button", "button.submit").with_target("label"));
+
+        assert_eq!(
+            manager.find_untranslated(&catalog, "de"),
+            vec![UntranslatedComponent {
+                component_id: "form-footer".to_string(),
+                message_keys: vec!["button.cancel".to_string()],
+            }]
+        );
+        assert!(manager.find_untranslated(&catalog, "en").is_empty());
+        assert_eq!(
+            manager.get_components_using("button.submit"),
+            vec!["form-footer", "submit-button"]
+        );
+    }
+}
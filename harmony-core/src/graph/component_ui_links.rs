@@ -3,13 +3,16 @@
 //! Manages relationships between components and UI locations where they're used.
 //! See: harmony-design/DESIGN_SYSTEM.md#component-ui-links
 
-use harmony_schemas::{ComponentUILink, UIUsageContext};
-use std::collections::HashMap;
+use harmony_schemas::ComponentUILink;
+use std::collections::{BTreeSet, HashMap};
 
 /// Manages Component → UI link relationships in the graph
 pub struct ComponentUILinkManager {
     /// Maps component_id → list of UI locations where it's used
     links: HashMap<String, Vec<ComponentUILink>>,
+
+    /// Maps ui_location → IDs of components linked to it
+    by_location: HashMap<String, BTreeSet<String>>,
 }
 
 impl ComponentUILinkManager {
@@ -17,16 +20,63 @@ impl ComponentUILinkManager {
     pub fn new() -> Self {
         Self {
             links: HashMap::new(),
+            by_location: HashMap::new(),
         }
     }
     
     /// Add a Component → UI link
     pub fn add_link(&mut self, link: ComponentUILink) {
+        self.by_location
+            .entry(link.ui_location.clone())
+            .or_default()
+            .insert(link.component_id.clone());
         self.links
             .entry(link.component_id.clone())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(link);
     }
+
+    /// Replace a component's links to the link's UI location with the link
+    ///
+    /// # Returns
+    /// The replaced links (empty if the link is new)
+    pub fn update_link(&mut self, link: ComponentUILink) -> Vec<ComponentUILink> {
+        let replaced = self.remove_link(&link.component_id, &link.ui_location);
+        self.add_link(link);
+        replaced
+    }
+
+    /// Remove a component's links to one UI location
+    ///
+    /// # Returns
+    /// The removed links
+    pub fn remove_link(&mut self, component_id: &str, ui_location: &str) -> Vec<ComponentUILink> {
+        let Some(links) = self.links.get_mut(component_id) else {
+            return Vec::new();
+        };
+        let (removed, kept) = links
+            .drain(..)
+            .partition(|link| link.ui_location == ui_location);
+        *links = kept;
+        if links.is_empty() {
+            self.links.remove(component_id);
+        }
+        self.unindex(component_id, ui_location);
+        removed
+    }
+
+    /// Remove every link to a UI location
+    ///
+    /// # Returns
+    /// The removed links
+    pub fn remove_links_for_ui(&mut self, ui_location: &str) -> Vec<ComponentUILink> {
+        self.by_location
+            .remove(ui_location)
+            .into_iter()
+            .flatten()
+            .flat_map(|component_id| self.remove_link(&component_id, ui_location))
+            .collect()
+    }
     
     /// Get all UI locations where a component is used
     pub fn get_ui_locations(&self, component_id: &str) -> Vec<&ComponentUILink> {
@@ -38,8 +88,11 @@ impl ComponentUILinkManager {
     
     /// Get all components used in a specific UI location
     pub fn get_components_in_ui(&self, ui_location: &str) -> Vec<&ComponentUILink> {
-        self.links
-            .values()
+        self.by_location
+            .get(ui_location)
+            .into_iter()
+            .flatten()
+            .filter_map(|component_id| self.links.get(component_id))
             .flatten()
             .filter(|link| link.ui_location == ui_location)
             .collect()
@@ -47,7 +100,9 @@ impl ComponentUILinkManager {
     
     /// Remove all links for a component
     pub fn remove_component_links(&mut self, component_id: &str) {
-        self.links.remove(component_id);
+        for link in self.links.remove(component_id).unwrap_or_default() {
+            self.unindex(component_id, &link.ui_location);
+        }
     }
     
     /// Get usage count for a component
@@ -57,6 +112,15 @@ impl ComponentUILinkManager {
             .map(|links| links.len())
             .unwrap_or(0)
     }
+
+    fn unindex(&mut self, component_id: &str, ui_location: &str) {
+        if let Some(components) = self.by_location.get_mut(ui_location) {
+            components.remove(component_id);
+            if components.is_empty() {
+                self.by_location.remove(ui_location);
+            }
+        }
+    }
 }
 
 impl Default for ComponentUILinkManager {
@@ -68,6 +132,7 @@ impl Default for ComponentUILinkManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use harmony_schemas::UIUsageContext;
 
     #[test]
     fn test_add_and_get_links() {
@@ -130,4 +195,42 @@ mod tests {
         assert_eq!(manager.get_usage_count("button-primary"), 2);
         assert_eq!(manager.get_usage_count("nonexistent"), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_remove_and_update_links() {
+        let mut manager = ComponentUILinkManager::new();
+        for (component_id, ui_location) in [
+            ("button-primary", "app-shell"),
+            ("button-primary", "playback-view"),
+            ("icon-play", "playback-view"),
+        ] {
+            manager.add_link(ComponentUILink::new(
+                component_id.to_string(),
+                ui_location.to_string(),
+                format!("src/ui/{}.html", ui_location),
+                UIUsageContext::Template,
+            ));
+        }
+
+        let mut moved = ComponentUILink::new(
+            "icon-play".to_string(),
+            "playback-view".to_string(),
+            "src/ui/playback-controls.html".to_string(),
+            UIUsageContext::Template,
+        );
+        moved.line_number = Some(12);
+        let replaced = manager.update_link(moved);
+        assert_eq!(replaced[0].file_path, "src/ui/playback-view.html");
+        assert_eq!(manager.get_ui_locations("icon-play")[0].line_number, Some(12));
+
+        let removed = manager.remove_links_for_ui("playback-view");
+        assert_eq!(removed.len(), 2);
+        assert!(manager.get_components_in_ui("playback-view").is_empty());
+        assert_eq!(manager.get_usage_count("icon-play"), 0);
+        assert_eq!(manager.get_usage_count("button-primary"), 1);
+
+        assert_eq!(manager.remove_link("button-primary", "app-shell").len(), 1);
+        assert!(manager.remove_link("button-primary", "app-shell").is_empty());
+        assert!(manager.get_components_in_ui("app-shell").is_empty());
+    }
+}
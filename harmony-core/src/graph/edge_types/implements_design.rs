@@ -3,8 +3,8 @@
 //! Links .tsx/.ts/.js implementation files to their corresponding DesignSpecNodes.
 //! See: harmony-design/DESIGN_SYSTEM.md#implementation-tracking
 
+use harmony_schemas::DesignSpecNode;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ImplementsDesignEdge {
@@ -16,6 +16,8 @@ pub struct ImplementsDesignEdge {
     pub last_verified: Option<i64>,  // Unix timestamp
     pub deviations: Vec<String>,
     pub notes: Option<String>,
+    #[serde(default)]
+    pub spec_version: Option<u32>,  // Spec version implemented; None if unpinned
 }
 
 impl ImplementsDesignEdge {
@@ -29,6 +31,7 @@ impl ImplementsDesignEdge {
             last_verified: None,
             deviations: Vec::new(),
             notes: None,
+            spec_version: None,
         }
     }
 
@@ -47,6 +50,25 @@ impl ImplementsDesignEdge {
         self
     }
 
+    pub fn with_spec_version(mut self, version: u32) -> Self {
+        self.spec_version = Some(version);
+        self
+    }
+
+    /// Sets completeness from the spec's states and variants, recording
+    /// each missing one as a deviation, and pins the spec's version
+    pub fn measured_against(mut self, spec: &DesignSpecNode) -> Self {
+        self.spec_version = Some(spec.version);
+        self.completeness = spec.completeness(&self.implemented_states, &self.implemented_variants);
+        for state in spec.missing_states(&self.implemented_states) {
+            self.deviations.push(format!("Missing state: {}", state));
+        }
+        for variant in spec.missing_variants(&self.implemented_variants) {
+            self.deviations.push(format!("Missing variant: {}", variant));
+        }
+        self
+    }
+
     pub fn with_deviation(mut self, deviation: String) -> Self {
         self.deviations.push(deviation);
         self
@@ -62,6 +84,11 @@ impl ImplementsDesignEdge {
         self
     }
 
+    /// Whether the edge is pinned to a version older than the spec's
+    pub fn is_outdated(&self, spec: &DesignSpecNode) -> bool {
+        self.spec_version.is_some_and(|version| version < spec.version)
+    }
+
     pub fn is_complete(&self) -> bool {
         self.completeness >= 0.99 && self.deviations.is_empty()
     }
@@ -162,4 +189,23 @@ mod tests {
         let incomplete = complete.clone().with_deviation("Missing hover state".to_string());
         assert!(!incomplete.is_complete());
     }
+
+    #[test]
+    fn test_edge_measured_against_spec() {
+        let spec = DesignSpecNode::new("spec:button".to_string(), "Button".to_string())
+            .with_states(vec!["default".to_string(), "hover".to_string()])
+            .with_variants(vec!["primary".to_string()]);
+        let edge = ImplementsDesignEdge::new(
+            "impl:Button.tsx".to_string(),
+            "spec:button".to_string(),
+        )
+        .with_states(vec!["default".to_string()])
+        .with_variants(vec!["primary".to_string()])
+        .measured_against(&spec);
+
+        assert_eq!(edge.completeness, 2.0 / 3.0);
+        assert_eq!(edge.spec_version, Some(1));
+        assert_eq!(edge.deviations, vec!["Missing state: hover".to_string()]);
+        assert!(!edge.is_complete());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,5 @@
+//! Edge types with dedicated payloads
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-engine
+
+pub mod implements_design;
@@ -0,0 +1,226 @@
+//! Graph export to DOT and GraphML
+//!
+//! For inspecting the design graph in Graphviz or Gephi during audits.
+//! Edges carry styling hints by edge type category (composition,
+//! dependency, implementation, variant, succession, documentation): a
+//! colour, plus a dashed line for the reverse half of a pair (e.g.
+//! used_by). Nodes get a shape by node type.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-export
+
+use crate::graph::HarmonyGraph;
+use harmony_schemas::EdgeType;
+use std::fmt::Write;
+
+/// Styling hints for an edge type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeStyle {
+    /// Hex colour, e.g. "#1f77b4"
+    pub color: &'static str,
+
+    /// "solid" or "dashed"
+    pub line: &'static str,
+}
+
+/// Styling hints for an edge type
+pub fn edge_style(edge_type: EdgeType) -> EdgeStyle {
+    let color = if edge_type.is_composition() {
+        "#1f77b4"
+    } else if edge_type == EdgeType::UsesToken {
+        "#2ca02c"
+    } else if edge_type.is_dependency() {
+        "#17becf"
+    } else if edge_type == EdgeType::ImplementsDesign {
+        "#ff7f0e"
+    } else if edge_type.is_variant() {
+        "#9467bd"
+    } else if edge_type.is_succession() {
+        "#d62728"
+    } else {
+        "#7f7f7f"
+    };
+    EdgeStyle {
+        color,
+        line: if edge_type.is_reverse() {
+            "dashed"
+        } else {
+            "solid"
+        },
+    }
+}
+
+/// Graphviz shape for a node type
+fn node_shape(node_type: &str) -> &'static str {
+    match node_type {
+        "component" => "box",
+        "token" => "ellipse",
+        "design_spec" => "note",
+        _ => "oval",
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", dot_escape(text))
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl HarmonyGraph {
+    /// Export as a Graphviz DOT digraph
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph harmony {\n");
+        for node in self.nodes() {
+            let _ = writeln!(
+                dot,
+                "  {} [label=\"{}\\n{}\", shape={}];",
+                dot_quote(&node.id),
+                dot_escape(&node.id),
+                dot_escape(&node.node_type),
+                node_shape(&node.node_type)
+            );
+        }
+        for edge in self.edges() {
+            let style = edge_style(edge.edge_type);
+            let _ = writeln!(
+                dot,
+                "  {} -> {} [label={}, color={}, style={}];",
+                dot_quote(&edge.from),
+                dot_quote(&edge.to),
+                dot_quote(edge.edge_type.as_str()),
+                dot_quote(style.color),
+                style.line
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Export as GraphML
+    ///
+    /// Nodes carry their type and properties (as JSON); edges their type
+    /// and styling hints.
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"node_type\" for=\"node\" attr.name=\"node_type\" attr.type=\"string\"/>\n",
+            "  <key id=\"properties\" for=\"node\" attr.name=\"properties\" attr.type=\"string\"/>\n",
+            "  <key id=\"edge_type\" for=\"edge\" attr.name=\"edge_type\" attr.type=\"string\"/>\n",
+            "  <key id=\"color\" for=\"edge\" attr.name=\"color\" attr.type=\"string\"/>\n",
+            "  <key id=\"line\" for=\"edge\" attr.name=\"line\" attr.type=\"string\"/>\n",
+            "  <graph id=\"harmony\" edgedefault=\"directed\">\n",
+        ));
+        for node in self.nodes() {
+            let _ = writeln!(xml, "    <node id=\"{}\">", xml_escape(&node.id));
+            let _ = writeln!(
+                xml,
+                "      <data key=\"node_type\">{}</data>",
+                xml_escape(&node.node_type)
+            );
+            if !node.properties.is_empty() {
+                let properties = serde_json::to_string(&node.properties).unwrap_or_default();
+                let _ = writeln!(
+                    xml,
+                    "      <data key=\"properties\">{}</data>",
+                    xml_escape(&properties)
+                );
+            }
+            xml.push_str("    </node>\n");
+        }
+        for edge in self.edges() {
+            let style = edge_style(edge.edge_type);
+            let _ = writeln!(
+                xml,
+                "    <edge id=\"{}\" source=\"{}\" target=\"{}\">",
+                xml_escape(&edge.id),
+                xml_escape(&edge.from),
+                xml_escape(&edge.to)
+            );
+            let _ = writeln!(
+                xml,
+                "      <data key=\"edge_type\">{}</data>",
+                edge.edge_type.as_str()
+            );
+            let _ = writeln!(xml, "      <data key=\"color\">{}</data>", style.color);
+            let _ = writeln!(xml, "      <data key=\"line\">{}</data>", style.line);
+            xml.push_str("    </edge>\n");
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphNode;
+
+    fn graph() -> HarmonyGraph {
+        let mut graph = HarmonyGraph::new();
+        graph
+            .add_node(
+                GraphNode::new("button", "component").with_property("label", "Say \"hi\" & <go>"),
+            )
+            .unwrap();
+        graph.add_node(GraphNode::new("icon", "component")).unwrap();
+        graph
+            .add_node(GraphNode::new("color-primary", "token"))
+            .unwrap();
+        graph
+            .connect("button", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        graph.connect("icon", "button", EdgeType::UsedBy).unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_edge_styles() {
+        assert_eq!(
+            edge_style(EdgeType::ComposesOf).color,
+            edge_style(EdgeType::UsedBy).color
+        );
+        assert_eq!(edge_style(EdgeType::ComposesOf).line, "solid");
+        assert_eq!(edge_style(EdgeType::UsedBy).line, "dashed");
+        assert_ne!(
+            edge_style(EdgeType::UsesToken).color,
+            edge_style(EdgeType::DependsOn).color
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = graph().to_dot();
+        assert!(dot.starts_with("digraph harmony {\n"));
+        assert!(dot.contains("  \"button\" [label=\"button\\ncomponent\", shape=box];\n"));
+        assert!(
+            dot.contains("  \"color-primary\" [label=\"color-primary\\ntoken\", shape=ellipse];\n")
+        );
+        assert!(dot.contains(
+            "  \"icon\" -> \"button\" [label=\"used_by\", color=\"#1f77b4\", style=dashed];\n"
+        ));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_to_graphml() {
+        let xml = graph().to_graphml();
+        assert!(xml.contains(
+            "    <edge id=\"uses_token:button-&gt;color-primary\" source=\"button\" target=\"color-primary\">\n"
+        ));
+        assert!(xml.contains("      <data key=\"edge_type\">uses_token</data>\n"));
+        assert!(xml.contains(
+            "      <data key=\"properties\">{&quot;label&quot;:&quot;Say \\&quot;hi\\&quot; &amp; &lt;go&gt;&quot;}</data>\n"
+        ));
+        assert_eq!(xml.matches("<node ").count(), 3);
+        assert_eq!(xml.matches("<edge ").count(), 2);
+    }
+}
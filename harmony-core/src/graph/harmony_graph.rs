@@ -0,0 +1,580 @@
+//! HarmonyGraph: the design graph container
+//!
+//! Nodes are stored by string ID and carry a type ("component", "token",
+//! "design_spec", ...) plus free-form JSON properties. Edges are
+//! `harmony_schemas::Edge`s stored by edge ID and indexed by source, target
+//! and edge type.
+//!
+//! Invariants kept by the mutation APIs:
+//! - node and edge IDs are unique
+//! - every edge connects two existing nodes and passes `Edge::validate`
+//! - removing a node removes its incident edges
+//!
+//! Component → Domain links (which domain types a component renders) are
+//! kept in a side store, since domain types are not graph nodes. A
+//! component has at most one primary domain.
+//!
+//! Iteration is ordered by ID, so query results are deterministic.
+//!
+//! `batch` applies several mutations all-or-nothing. In strict mode a
+//! batch is also rejected if it leaves a composition cycle (see
+//! `validation`). Transactions (`begin_transaction`) extend the same
+//! guarantee across separate calls.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-engine
+
+use crate::graph::queries::component_domain_queries::{BindingMode, ComponentDomainLink};
+use crate::graph::changeset::{ChangeRecord, GraphOp};
+use crate::graph::history::GraphEvent;
+use crate::graph::merge::MergeConflict;
+use crate::graph::validation::CompositionCycle;
+use harmony_schemas::{Edge, EdgeType, SchemaError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+/// A node of the design graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// Unique identifier, e.g. "button", "color-primary", "spec:button"
+    pub id: String,
+
+    /// Node type, e.g. "component", "token", "design_spec"
+    pub node_type: String,
+
+    /// Free-form properties, e.g. "lifecycle_state"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, Value>,
+}
+
+impl GraphNode {
+    /// Create a node without properties
+    pub fn new(id: &str, node_type: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            node_type: node_type.to_string(),
+            properties: BTreeMap::new(),
+        }
+    }
+
+    /// Set a property
+    pub fn with_property(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.properties.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Look up a property
+    pub fn property(&self, name: &str) -> Option<&Value> {
+        self.properties.get(name)
+    }
+}
+
+/// Error from a graph mutation
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphError {
+    DuplicateNode(String),
+    UnknownNode(String),
+    DuplicateEdge(String),
+    UnknownEdge(String),
+
+    /// The edge failed schema validation
+    InvalidEdge(SchemaError),
+
+    /// The component already has a different primary domain
+    PrimaryDomainConflict(String),
+
+    /// A strict-mode batch left cycles in composition edges
+    CompositionCycles(Vec<CompositionCycle>),
+
+    /// A merge with `MergeStrategy::Error` found conflicting items
+    MergeConflicts(Vec<MergeConflict>),
+
+    /// `begin_transaction` while a transaction is open
+    TransactionActive,
+
+    /// `commit` or `rollback` without an open transaction
+    NoTransaction,
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::DuplicateNode(id) => write!(f, "Node already exists: {}", id),
+            GraphError::UnknownNode(id) => write!(f, "Unknown node: {}", id),
+            GraphError::DuplicateEdge(id) => write!(f, "Edge already exists: {}", id),
+            GraphError::UnknownEdge(id) => write!(f, "Unknown edge: {}", id),
+            GraphError::InvalidEdge(error) => write!(f, "Invalid edge: {}", error),
+            GraphError::PrimaryDomainConflict(id) => {
+                write!(f, "Component already has a primary domain: {}", id)
+            }
+            GraphError::CompositionCycles(cycles) => {
+                let paths: Vec<String> = cycles.iter().map(|c| c.to_string()).collect();
+                write!(f, "Composition cycles: {}", paths.join("; "))
+            }
+            GraphError::MergeConflicts(conflicts) => {
+                let items: Vec<String> = conflicts.iter().map(|c| c.to_string()).collect();
+                write!(f, "Merge conflicts: {}", items.join(", "))
+            }
+            GraphError::TransactionActive => write!(f, "A transaction is already open"),
+            GraphError::NoTransaction => write!(f, "No transaction is open"),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+impl From<SchemaError> for GraphError {
+    fn from(error: SchemaError) -> Self {
+        GraphError::InvalidEdge(error)
+    }
+}
+
+/// The design system graph
+#[derive(Debug, Clone, Default)]
+pub struct HarmonyGraph {
+    nodes: BTreeMap<String, GraphNode>,
+    edges: BTreeMap<String, Edge>,
+
+    /// node ID → IDs of edges leaving it
+    outgoing: HashMap<String, BTreeSet<String>>,
+
+    /// node ID → IDs of edges entering it
+    incoming: HashMap<String, BTreeSet<String>>,
+
+    /// edge type → IDs of edges of that type
+    by_type: HashMap<EdgeType, BTreeSet<String>>,
+
+    /// component ID → domain types it renders
+    domain_links: BTreeMap<String, Vec<ComponentDomainLink>>,
+
+    /// Validate composition cycles at the end of each batch
+    strict: bool,
+
+    /// Operations applied by `apply_changeset`, oldest first
+    pub(crate) change_log: Vec<ChangeRecord>,
+    pub(crate) last_sequence: u64,
+
+    /// State at `begin_transaction`, restored by `rollback`
+    pub(crate) transaction: Option<Box<HarmonyGraph>>,
+
+    /// Every mutation, oldest first (see `history`)
+    pub(crate) events: Vec<GraphEvent>,
+
+    /// Actor recorded on new events
+    pub(crate) actor: String,
+
+    /// Incremented by every change, including restores; never reused
+    pub(crate) revision: u64,
+}
+
+impl HarmonyGraph {
+    /// Create an empty graph
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Edge ID used by `connect`
+    pub fn edge_id(from: &str, to: &str, edge_type: EdgeType) -> String {
+        format!("{}:{}->{}", edge_type.as_str(), from, to)
+    }
+
+    /// Enable or disable strict mode
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Apply a batch of mutations all-or-nothing
+    ///
+    /// If `apply` fails, or in strict mode the result has composition
+    /// cycles, the graph is restored to its state before the batch.
+    pub fn batch<R>(
+        &mut self,
+        apply: impl FnOnce(&mut Self) -> Result<R, GraphError>,
+    ) -> Result<R, GraphError> {
+        let snapshot = self.snapshot();
+        let result = apply(self).and_then(|value| self.check_strict().map(|_| value));
+        if result.is_err() {
+            self.restore(snapshot);
+        }
+        result
+    }
+
+    /// Copy of the graph without the open transaction's state
+    pub(crate) fn snapshot(&self) -> HarmonyGraph {
+        HarmonyGraph {
+            nodes: self.nodes.clone(),
+            edges: self.edges.clone(),
+            outgoing: self.outgoing.clone(),
+            incoming: self.incoming.clone(),
+            by_type: self.by_type.clone(),
+            domain_links: self.domain_links.clone(),
+            strict: self.strict,
+            change_log: self.change_log.clone(),
+            last_sequence: self.last_sequence,
+            transaction: None,
+            events: self.events.clone(),
+            actor: self.actor.clone(),
+            revision: self.revision,
+        }
+    }
+
+    /// Restore a snapshot, keeping the open transaction
+    pub(crate) fn restore(&mut self, snapshot: HarmonyGraph) {
+        let transaction = self.transaction.take();
+        let revision = self.revision;
+        *self = snapshot;
+        self.transaction = transaction;
+        self.revision = revision + 1;
+    }
+
+    /// Counter that changes whenever the graph does
+    ///
+    /// Restoring earlier state (a failed batch, a rollback) also moves it
+    /// forward, so a revision always identifies one state of this graph.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// In strict mode, fail if there are composition cycles
+    pub(crate) fn check_strict(&self) -> Result<(), GraphError> {
+        if self.strict {
+            let cycles = self.find_composition_cycles();
+            if !cycles.is_empty() {
+                return Err(GraphError::CompositionCycles(cycles));
+            }
+        }
+        Ok(())
+    }
+
+    // --- Nodes ---
+
+    /// Add a node; fails if the ID is taken
+    pub fn add_node(&mut self, node: GraphNode) -> Result<(), GraphError> {
+        if self.nodes.contains_key(&node.id) {
+            return Err(GraphError::DuplicateNode(node.id));
+        }
+        self.record(GraphOp::AddNode { node: node.clone() }, vec![node.id.clone()]);
+        self.nodes.insert(node.id.clone(), node);
+        Ok(())
+    }
+
+    /// Insert or replace a node, keeping its edges
+    ///
+    /// # Returns
+    /// The node previously stored under the ID
+    pub fn upsert_node(&mut self, node: GraphNode) -> Option<GraphNode> {
+        let op = if self.nodes.contains_key(&node.id) {
+            GraphOp::UpdateNode { node: node.clone() }
+        } else {
+            GraphOp::AddNode { node: node.clone() }
+        };
+        self.record(op, vec![node.id.clone()]);
+        self.nodes.insert(node.id.clone(), node)
+    }
+
+    /// Set a property on an existing node
+    pub fn set_node_property(&mut self, id: &str, name: &str, value: Value) -> Result<(), GraphError> {
+        let node = self
+            .nodes
+            .get_mut(id)
+            .ok_or_else(|| GraphError::UnknownNode(id.to_string()))?;
+        node.properties.insert(name.to_string(), value);
+        let node = node.clone();
+        self.record(GraphOp::UpdateNode { node }, vec![id.to_string()]);
+        Ok(())
+    }
+
+    /// Remove a node and its incident edges
+    ///
+    /// # Returns
+    /// The node and the removed edges
+    pub fn remove_node(&mut self, id: &str) -> Result<(GraphNode, Vec<Edge>), GraphError> {
+        let node = self
+            .nodes
+            .remove(id)
+            .ok_or_else(|| GraphError::UnknownNode(id.to_string()))?;
+        let incident: BTreeSet<String> = self
+            .outgoing
+            .remove(id)
+            .into_iter()
+            .chain(self.incoming.remove(id))
+            .flatten()
+            .collect();
+        let edges = incident
+            .iter()
+            .filter_map(|edge_id| self.remove_edge(edge_id).ok())
+            .collect();
+        self.domain_links.remove(id);
+        self.record(GraphOp::RemoveNode { id: id.to_string() }, vec![id.to_string()]);
+        Ok((node, edges))
+    }
+
+    /// Look up a node
+    pub fn node(&self, id: &str) -> Option<&GraphNode> {
+        self.nodes.get(id)
+    }
+
+    /// Whether a node exists
+    pub fn contains_node(&self, id: &str) -> bool {
+        self.nodes.contains_key(id)
+    }
+
+    /// All nodes, ordered by ID
+    pub fn nodes(&self) -> impl Iterator<Item = &GraphNode> {
+        self.nodes.values()
+    }
+
+    /// Nodes of one type, ordered by ID
+    pub fn nodes_of_type<'a>(&'a self, node_type: &'a str) -> impl Iterator<Item = &'a GraphNode> {
+        self.nodes.values().filter(move |n| n.node_type == node_type)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // --- Edges ---
+
+    /// Add an edge between existing nodes
+    ///
+    /// Fails if the edge ID is taken, an endpoint is missing, or the edge's
+    /// properties do not match its type.
+    pub fn add_edge(&mut self, edge: Edge) -> Result<(), GraphError> {
+        if self.edges.contains_key(&edge.id) {
+            return Err(GraphError::DuplicateEdge(edge.id));
+        }
+        for endpoint in [&edge.from, &edge.to] {
+            if !self.nodes.contains_key(endpoint) {
+                return Err(GraphError::UnknownNode(endpoint.clone()));
+            }
+        }
+        edge.validate()?;
+
+        self.outgoing
+            .entry(edge.from.clone())
+            .or_default()
+            .insert(edge.id.clone());
+        self.incoming
+            .entry(edge.to.clone())
+            .or_default()
+            .insert(edge.id.clone());
+        self.by_type
+            .entry(edge.edge_type)
+            .or_default()
+            .insert(edge.id.clone());
+        self.record(GraphOp::AddEdge { edge: edge.clone() }, endpoints(&edge));
+        self.edges.insert(edge.id.clone(), edge);
+        Ok(())
+    }
+
+    /// Add an edge without metadata, ID generated by `edge_id`
+    ///
+    /// # Returns
+    /// The new edge's ID
+    pub fn connect(&mut self, from: &str, to: &str, edge_type: EdgeType) -> Result<String, GraphError> {
+        let id = Self::edge_id(from, to, edge_type);
+        self.add_edge(Edge::new(id.clone(), from.to_string(), to.to_string(), edge_type))?;
+        Ok(id)
+    }
+
+    /// Remove an edge
+    pub fn remove_edge(&mut self, id: &str) -> Result<Edge, GraphError> {
+        let edge = self
+            .edges
+            .remove(id)
+            .ok_or_else(|| GraphError::UnknownEdge(id.to_string()))?;
+        for (index, node_id) in [(&mut self.outgoing, &edge.from), (&mut self.incoming, &edge.to)] {
+            if let Some(ids) = index.get_mut(node_id) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    index.remove(node_id);
+                }
+            }
+        }
+        if let Some(ids) = self.by_type.get_mut(&edge.edge_type) {
+            ids.remove(id);
+        }
+        self.record(GraphOp::RemoveEdge { id: id.to_string() }, endpoints(&edge));
+        Ok(edge)
+    }
+
+    /// Look up an edge
+    pub fn edge(&self, id: &str) -> Option<&Edge> {
+        self.edges.get(id)
+    }
+
+    /// Whether an edge exists
+    pub fn contains_edge(&self, id: &str) -> bool {
+        self.edges.contains_key(id)
+    }
+
+    /// All edges, ordered by ID
+    pub fn edges(&self) -> impl Iterator<Item = &Edge> {
+        self.edges.values()
+    }
+
+    /// Edges of one type, ordered by ID
+    pub fn edges_of_type(&self, edge_type: EdgeType) -> impl Iterator<Item = &Edge> {
+        self.lookup(self.by_type.get(&edge_type))
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Edges leaving a node
+    pub fn outgoing(&self, node_id: &str) -> impl Iterator<Item = &Edge> {
+        self.lookup(self.outgoing.get(node_id))
+    }
+
+    /// Edges entering a node
+    pub fn incoming(&self, node_id: &str) -> impl Iterator<Item = &Edge> {
+        self.lookup(self.incoming.get(node_id))
+    }
+
+    /// Targets of a node's outgoing edges of one type
+    pub fn successors(&self, node_id: &str, edge_type: EdgeType) -> Vec<&str> {
+        self.outgoing(node_id)
+            .filter(|e| e.edge_type == edge_type)
+            .map(|e| e.to.as_str())
+            .collect()
+    }
+
+    /// Sources of a node's incoming edges of one type
+    pub fn predecessors(&self, node_id: &str, edge_type: EdgeType) -> Vec<&str> {
+        self.incoming(node_id)
+            .filter(|e| e.edge_type == edge_type)
+            .map(|e| e.from.as_str())
+            .collect()
+    }
+
+    // --- Component → Domain links ---
+
+    /// Link a component to a domain type it renders
+    ///
+    /// Replaces an existing link between the same component and domain
+    /// type. Fails if the component is unknown, or if the link is primary
+    /// and the component already has a primary link to another domain.
+    pub fn add_domain_link(&mut self, link: ComponentDomainLink) -> Result<(), GraphError> {
+        if !self.nodes.contains_key(&link.component_id) {
+            return Err(GraphError::UnknownNode(link.component_id));
+        }
+        let links = self.domain_links.entry(link.component_id.clone()).or_default();
+        if link.binding_mode == BindingMode::Primary
+            && links
+                .iter()
+                .any(|l| l.binding_mode == BindingMode::Primary && l.domain_type != link.domain_type)
+        {
+            return Err(GraphError::PrimaryDomainConflict(link.component_id));
+        }
+        links.retain(|l| l.domain_type != link.domain_type);
+        links.push(link.clone());
+        let component_id = link.component_id.clone();
+        self.record(GraphOp::AddDomainLink { link }, vec![component_id]);
+        Ok(())
+    }
+
+    /// Remove the link between a component and a domain type
+    pub fn remove_domain_link(&mut self, component_id: &str, domain_type: &str) -> Option<ComponentDomainLink> {
+        let links = self.domain_links.get_mut(component_id)?;
+        let index = links.iter().position(|l| l.domain_type == domain_type)?;
+        let link = links.remove(index);
+        if links.is_empty() {
+            self.domain_links.remove(component_id);
+        }
+        self.record(
+            GraphOp::RemoveDomainLink {
+                component_id: component_id.to_string(),
+                domain_type: domain_type.to_string(),
+            },
+            vec![component_id.to_string()],
+        );
+        Some(link)
+    }
+
+    /// Domain links of a component, in insertion order
+    pub fn domain_links(&self, component_id: &str) -> &[ComponentDomainLink] {
+        self.domain_links
+            .get(component_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// All domain links, ordered by component ID
+    pub fn all_domain_links(&self) -> impl Iterator<Item = &ComponentDomainLink> {
+        self.domain_links.values().flatten()
+    }
+
+    fn lookup<'a>(&'a self, ids: Option<&'a BTreeSet<String>>) -> impl Iterator<Item = &'a Edge> {
+        ids.into_iter()
+            .flatten()
+            .filter_map(|id| self.edges.get(id))
+    }
+}
+
+/// Node IDs an edge touches
+fn endpoints(edge: &Edge) -> Vec<String> {
+    if edge.from == edge.to {
+        vec![edge.from.clone()]
+    } else {
+        vec![edge.from.clone(), edge.to.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harmony_schemas::EdgeMetadata;
+
+    fn graph() -> HarmonyGraph {
+        let mut graph = HarmonyGraph::new();
+        graph.add_node(GraphNode::new("form", "component")).unwrap();
+        graph.add_node(GraphNode::new("button", "component")).unwrap();
+        graph.add_node(GraphNode::new("color-primary", "token")).unwrap();
+        graph.connect("form", "button", EdgeType::ComposesOf).unwrap();
+        graph.connect("button", "color-primary", EdgeType::UsesToken).unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_indexes() {
+        let graph = graph();
+        assert_eq!(graph.successors("form", EdgeType::ComposesOf), vec!["button"]);
+        assert_eq!(graph.predecessors("color-primary", EdgeType::UsesToken), vec!["button"]);
+        assert_eq!(graph.edges_of_type(EdgeType::UsesToken).count(), 1);
+        assert_eq!(graph.nodes_of_type("component").count(), 2);
+    }
+
+    #[test]
+    fn test_mutation_invariants() {
+        let mut graph = graph();
+        assert_eq!(
+            graph.add_node(GraphNode::new("form", "component")),
+            Err(GraphError::DuplicateNode("form".to_string()))
+        );
+        assert_eq!(
+            graph.connect("form", "missing", EdgeType::ComposesOf),
+            Err(GraphError::UnknownNode("missing".to_string()))
+        );
+        let invalid = Edge::with_metadata(
+            "bad".to_string(),
+            "form".to_string(),
+            "button".to_string(),
+            EdgeType::ComposesOf,
+            EdgeMetadata::default().with_property("css_property", "color"),
+        );
+        assert!(matches!(graph.add_edge(invalid), Err(GraphError::InvalidEdge(_))));
+
+        let (node, edges) = graph.remove_node("button").unwrap();
+        assert_eq!(node.id, "button");
+        assert_eq!(edges.len(), 2);
+        assert_eq!(graph.edge_count(), 0);
+        assert!(graph.outgoing("form").next().is_none());
+        assert_eq!(graph.edges_of_type(EdgeType::UsesToken).count(), 0);
+    }
+}
@@ -0,0 +1,250 @@
+//! Graph health report
+//!
+//! Collects graph statistics and problems into one serializable document
+//! so the app can gate on graph health: node and edge counts by type,
+//! degree distributions, composition cycles, orphans and schema
+//! violations.
+//!
+//! Schema checks cover what the graph does not enforce on insert: edge
+//! endpoints of the wrong node type (`uses_token` must point at a token,
+//! `implements_design` at a design spec) and unparseable lifecycle states.
+//! Edge properties are re-validated too, although `add_edge` already
+//! rejects invalid ones.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-health
+
+use crate::graph::queries::token_impact::LIFECYCLE_STATE_PROPERTY;
+use crate::graph::HarmonyGraph;
+use harmony_schemas::{EdgeType, LifecycleState};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Distribution of per-node edge counts
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DegreeDistribution {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+
+    /// degree → number of nodes with that degree
+    pub histogram: BTreeMap<usize, usize>,
+}
+
+impl DegreeDistribution {
+    fn from_degrees(degrees: impl Iterator<Item = usize>) -> Self {
+        let mut histogram = BTreeMap::new();
+        let mut total = 0;
+        let mut count = 0;
+        for degree in degrees {
+            *histogram.entry(degree).or_default() += 1;
+            total += degree;
+            count += 1;
+        }
+        Self {
+            min: histogram.keys().next().copied().unwrap_or(0),
+            max: histogram.keys().next_back().copied().unwrap_or(0),
+            mean: if count == 0 {
+                0.0
+            } else {
+                total as f64 / count as f64
+            },
+            histogram,
+        }
+    }
+}
+
+/// A node or edge that violates the schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaViolation {
+    /// Node or edge ID
+    pub id: String,
+    pub message: String,
+}
+
+/// Produced by `HarmonyGraph::health_report`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub nodes_by_type: BTreeMap<String, usize>,
+    pub edges_by_type: BTreeMap<String, usize>,
+    pub in_degree: DegreeDistribution,
+    pub out_degree: DegreeDistribution,
+
+    /// Composition cycles, see `find_composition_cycles`
+    pub cycle_count: usize,
+
+    /// Nodes with no edges at all
+    pub orphan_count: usize,
+
+    /// Sorted by ID
+    pub schema_violations: Vec<SchemaViolation>,
+}
+
+impl HealthReport {
+    /// True if there are no cycles or schema violations
+    ///
+    /// Orphans are reported but do not count against health.
+    pub fn is_healthy(&self) -> bool {
+        self.cycle_count == 0 && self.schema_violations.is_empty()
+    }
+}
+
+/// Node type an edge type must point at, if constrained
+fn required_target_type(edge_type: EdgeType) -> Option<&'static str> {
+    match edge_type {
+        EdgeType::UsesToken => Some("token"),
+        EdgeType::ImplementsDesign => Some("design_spec"),
+        _ => None,
+    }
+}
+
+impl HarmonyGraph {
+    /// Statistics and problems of the whole graph
+    pub fn health_report(&self) -> HealthReport {
+        let mut nodes_by_type = BTreeMap::new();
+        for node in self.nodes() {
+            *nodes_by_type.entry(node.node_type.clone()).or_default() += 1;
+        }
+        let mut edges_by_type = BTreeMap::new();
+        for edge in self.edges() {
+            *edges_by_type
+                .entry(edge.edge_type.as_str().to_string())
+                .or_default() += 1;
+        }
+
+        HealthReport {
+            node_count: self.node_count(),
+            edge_count: self.edge_count(),
+            nodes_by_type,
+            edges_by_type,
+            in_degree: DegreeDistribution::from_degrees(
+                self.nodes().map(|node| self.incoming(&node.id).count()),
+            ),
+            out_degree: DegreeDistribution::from_degrees(
+                self.nodes().map(|node| self.outgoing(&node.id).count()),
+            ),
+            cycle_count: self.find_composition_cycles().len(),
+            orphan_count: self
+                .nodes()
+                .filter(|node| {
+                    self.incoming(&node.id).next().is_none()
+                        && self.outgoing(&node.id).next().is_none()
+                })
+                .count(),
+            schema_violations: self.schema_violations(),
+        }
+    }
+
+    fn schema_violations(&self) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+        for node in self.nodes() {
+            if let Some(state) = node.property(LIFECYCLE_STATE_PROPERTY) {
+                if serde_json::from_value::<LifecycleState>(state.clone()).is_err() {
+                    violations.push(SchemaViolation {
+                        id: node.id.clone(),
+                        message: format!("Invalid lifecycle state {}", state),
+                    });
+                }
+            }
+        }
+        for edge in self.edges() {
+            if let Err(error) = edge.validate() {
+                violations.push(SchemaViolation {
+                    id: edge.id.clone(),
+                    message: error.to_string(),
+                });
+            }
+            let Some(required) = required_target_type(edge.edge_type) else {
+                continue;
+            };
+            if let Some(target) = self.node(&edge.to) {
+                if target.node_type != required {
+                    violations.push(SchemaViolation {
+                        id: edge.id.clone(),
+                        message: format!(
+                            "{} must point at a {}, not a {}",
+                            edge.edge_type.as_str(),
+                            required,
+                            target.node_type
+                        ),
+                    });
+                }
+            }
+        }
+        violations.sort_by(|a, b| a.id.cmp(&b.id));
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphNode;
+
+    fn graph() -> HarmonyGraph {
+        let mut graph = HarmonyGraph::new();
+        for (id, node_type) in [
+            ("form", "component"),
+            ("button", "component"),
+            ("color-primary", "token"),
+            ("spacing-unused", "token"),
+        ] {
+            graph.add_node(GraphNode::new(id, node_type)).unwrap();
+        }
+        graph
+            .connect("form", "button", EdgeType::ComposesOf)
+            .unwrap();
+        graph
+            .connect("button", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_healthy_graph() {
+        let report = graph().health_report();
+        assert!(report.is_healthy());
+        assert_eq!(report.node_count, 4);
+        assert_eq!(report.nodes_by_type["component"], 2);
+        assert_eq!(report.edges_by_type["uses_token"], 1);
+        assert_eq!(report.orphan_count, 1);
+        assert_eq!(report.out_degree.max, 1);
+        assert_eq!(
+            report.out_degree.histogram,
+            BTreeMap::from([(0, 2), (1, 2)])
+        );
+        assert_eq!(report.in_degree.mean, 0.5);
+    }
+
+    #[test]
+    fn test_unhealthy_graph() {
+        let mut graph = graph();
+        graph
+            .connect("button", "form", EdgeType::ComposesOf)
+            .unwrap();
+        graph
+            .connect("form", "button", EdgeType::UsesToken)
+            .unwrap();
+        graph
+            .set_node_property("button", LIFECYCLE_STATE_PROPERTY, "shiny".into())
+            .unwrap();
+
+        let report = graph.health_report();
+        assert!(!report.is_healthy());
+        assert_eq!(report.cycle_count, 1);
+        let violations: Vec<&str> = report
+            .schema_violations
+            .iter()
+            .map(|v| v.id.as_str())
+            .collect();
+        assert_eq!(violations, vec!["button", "uses_token:form->button"]);
+        assert_eq!(
+            report.schema_violations[1].message,
+            "uses_token must point at a token, not a component"
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["cycle_count"], 1);
+    }
+}
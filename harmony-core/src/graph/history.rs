@@ -0,0 +1,178 @@
+//! Event-sourced graph history
+//!
+//! Every mutation of a HarmonyGraph, through any API, is recorded as an
+//! event with the current actor and a timestamp. `history` lists the
+//! events that touched a node (e.g. when a dependency edge appeared) and
+//! `as_of` rebuilds the graph as it was at a point in time.
+//!
+//! Events are part of the graph's state: mutations undone by a failed
+//! batch or a rollback leave no events. Removing a node records the
+//! removal of each incident edge first. Timestamps are Unix milliseconds
+//! and never decrease, even if the system clock does.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-history
+
+use crate::graph::{GraphOp, HarmonyGraph};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A recorded mutation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphEvent {
+    /// Starts at 1 and increases by 1 per event
+    pub sequence: u64,
+
+    /// Unix time in milliseconds
+    pub timestamp: i64,
+
+    /// Who made the change; empty if unattributed
+    pub actor: String,
+
+    /// Nodes the mutation touched: the node itself, or an edge's endpoints
+    pub node_ids: Vec<String>,
+
+    pub op: GraphOp,
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as i64)
+}
+
+impl HarmonyGraph {
+    /// Set the actor recorded on subsequent events
+    pub fn set_actor(&mut self, actor: &str) {
+        self.actor = actor.to_string();
+    }
+
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    /// All events, oldest first
+    pub fn events(&self) -> &[GraphEvent] {
+        &self.events
+    }
+
+    /// Events that touched a node, oldest first
+    pub fn history(&self, node_id: &str) -> Vec<&GraphEvent> {
+        self.events
+            .iter()
+            .filter(|event| event.node_ids.iter().any(|id| id == node_id))
+            .collect()
+    }
+
+    /// The graph as it was at a point in time
+    ///
+    /// Replays the events up to and including `timestamp` onto an empty
+    /// graph. The result keeps those events, this graph's actor and its
+    /// strict flag, but not the change log.
+    pub fn as_of(&self, timestamp: i64) -> HarmonyGraph {
+        let mut graph = HarmonyGraph::new();
+        let events: Vec<GraphEvent> = self
+            .events
+            .iter()
+            .take_while(|event| event.timestamp <= timestamp)
+            .cloned()
+            .collect();
+        for event in &events {
+            // Events were valid when recorded, so replay cannot fail
+            let _ = graph.execute_op(&event.op);
+        }
+        graph.events = events;
+        graph.actor = self.actor.clone();
+        graph.set_strict(self.is_strict());
+        graph
+    }
+
+    pub(crate) fn record(&mut self, op: GraphOp, node_ids: Vec<String>) {
+        let timestamp = self
+            .events
+            .last()
+            .map_or(i64::MIN, |event| event.timestamp)
+            .max(now_millis());
+        self.revision += 1;
+        self.events.push(GraphEvent {
+            sequence: self.events.len() as u64 + 1,
+            timestamp,
+            actor: self.actor.clone(),
+            node_ids,
+            op,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphNode;
+    use harmony_schemas::EdgeType;
+
+    /// Spread events one second apart so `as_of` can tell them apart
+    fn space_out(graph: &mut HarmonyGraph) {
+        for (index, event) in graph.events.iter_mut().enumerate() {
+            event.timestamp = 1_000 * (index as i64 + 1);
+        }
+    }
+
+    #[test]
+    fn test_history_and_as_of() {
+        let mut graph = HarmonyGraph::new();
+        graph.set_actor("alice");
+        graph
+            .add_node(GraphNode::new("button", "component"))
+            .unwrap();
+        graph
+            .add_node(GraphNode::new("color-primary", "token"))
+            .unwrap();
+        graph.set_actor("bob");
+        graph
+            .connect("button", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        graph
+            .set_node_property("button", "lifecycle_state", "published".into())
+            .unwrap();
+        graph.remove_node("color-primary").unwrap();
+        space_out(&mut graph);
+
+        let history = graph.history("color-primary");
+        let summary: Vec<(u64, &str)> = history
+            .iter()
+            .map(|e| (e.sequence, e.actor.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(2, "alice"), (3, "bob"), (5, "bob"), (6, "bob")]
+        );
+        // When did the dependency appear?
+        assert!(matches!(history[1].op, GraphOp::AddEdge { .. }));
+        assert_eq!(history[1].timestamp, 3_000);
+        assert!(matches!(history[2].op, GraphOp::RemoveEdge { .. }));
+
+        let before_publish = graph.as_of(3_000);
+        assert_eq!(before_publish.edge_count(), 1);
+        assert!(before_publish.node("button").unwrap().properties.is_empty());
+        assert_eq!(before_publish.events().len(), 3);
+
+        let now = graph.as_of(i64::MAX);
+        assert_eq!(now.node("button"), graph.node("button"));
+        assert_eq!(now.node_count(), 1);
+        assert_eq!(graph.as_of(0).node_count(), 0);
+    }
+
+    #[test]
+    fn test_rolled_back_mutations_leave_no_events() {
+        let mut graph = HarmonyGraph::new();
+        graph
+            .add_node(GraphNode::new("button", "component"))
+            .unwrap();
+        let result = graph.batch(|g| {
+            g.add_node(GraphNode::new("icon", "component"))?;
+            g.connect("icon", "missing", EdgeType::ComposesOf)
+        });
+        assert!(result.is_err());
+        assert_eq!(graph.events().len(), 1);
+        assert!(graph.history("icon").is_empty());
+    }
+}
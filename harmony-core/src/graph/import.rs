@@ -0,0 +1,166 @@
+//! Graph import from GraphML and CSV edge lists
+//!
+//! Parsing is shared with the edge executor (`wasm_edge_executor::parse_graphml`
+//! and `parse_csv`), so one file loads into both graphs with the same nodes
+//! and edges: the executor keys nodes by the u32 IDs of a `NodeIdMap`, the
+//! HarmonyGraph by the names themselves. `to_graphml` output imports back
+//! unchanged, apart from node properties.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-engine
+
+use crate::graph::{GraphError, GraphNode, HarmonyGraph};
+use harmony_schemas::{Edge, EdgeMetadata, EdgeType, SchemaError};
+use wasm_edge_executor::EdgeList;
+
+/// What `HarmonyGraph::import_edge_list` added
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportReport {
+    /// IDs of the nodes created, in file order; nodes the graph already
+    /// had are reused as they are
+    pub added_nodes: Vec<String>,
+
+    /// Graph edge ID of each imported edge, in file order
+    pub edge_ids: Vec<String>,
+}
+
+/// Edge type with the given snake_case name
+fn edge_type_named(name: &str) -> Result<EdgeType, GraphError> {
+    EdgeType::ALL
+        .iter()
+        .find(|t| t.as_str() == name)
+        .copied()
+        .ok_or_else(|| {
+            GraphError::InvalidEdge(SchemaError::UnknownEdgeType {
+                edge_type: name.to_string(),
+            })
+        })
+}
+
+impl HarmonyGraph {
+    /// Add the nodes and edges of a parsed GraphML or CSV file, all or none
+    ///
+    /// New nodes get the file's node type, or `default_node_type`. Edges
+    /// get the file's edge type, or `default_edge_type`, and keep their
+    /// GraphML ID if they have one; others get an ID from `edge_id`.
+    /// Weights go to the edge metadata.
+    pub fn import_edge_list(
+        &mut self,
+        list: &EdgeList,
+        default_node_type: &str,
+        default_edge_type: EdgeType,
+    ) -> Result<ImportReport, GraphError> {
+        self.batch(|graph| {
+            let mut report = ImportReport::default();
+            for node in &list.nodes {
+                if !graph.contains_node(&node.id) {
+                    let node_type = node.node_type.as_deref().unwrap_or(default_node_type);
+                    graph.add_node(GraphNode::new(&node.id, node_type))?;
+                    report.added_nodes.push(node.id.clone());
+                }
+            }
+            for imported in &list.edges {
+                let edge_type = match &imported.edge_type {
+                    Some(name) => edge_type_named(name)?,
+                    None => default_edge_type,
+                };
+                let id = imported.id.clone().unwrap_or_else(|| {
+                    Self::edge_id(&imported.source, &imported.target, edge_type)
+                });
+                let mut edge = Edge::new(
+                    id.clone(),
+                    imported.source.clone(),
+                    imported.target.clone(),
+                    edge_type,
+                );
+                if let Some(weight) = imported.weight {
+                    edge.metadata = Some(EdgeMetadata {
+                        weight: Some(weight as f32),
+                        ..EdgeMetadata::default()
+                    });
+                }
+                graph.add_edge(edge)?;
+                report.edge_ids.push(id);
+            }
+            Ok(report)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_edge_executor::{parse_csv, parse_graphml, NodeIdMap, WASMEdgeExecutor};
+
+    #[test]
+    fn test_import_csv() {
+        let list = parse_csv("source,target,type,weight\nbutton,icon,composes_of\nbutton,color-primary,uses_token,0.5\n").unwrap();
+        let mut graph = HarmonyGraph::new();
+        graph
+            .add_node(GraphNode::new("button", "component"))
+            .unwrap();
+        let report = graph
+            .import_edge_list(&list, "token", EdgeType::DependsOn)
+            .unwrap();
+        assert_eq!(report.added_nodes, vec!["icon", "color-primary"]);
+        assert_eq!(
+            report.edge_ids,
+            vec![
+                "composes_of:button->icon",
+                "uses_token:button->color-primary"
+            ]
+        );
+        assert_eq!(graph.node("icon").unwrap().node_type, "token");
+        let weighted = graph.edge("uses_token:button->color-primary").unwrap();
+        assert_eq!(weighted.metadata.as_ref().unwrap().weight, Some(0.5));
+    }
+
+    #[test]
+    fn test_import_is_all_or_nothing() {
+        let list = parse_csv("a,b,composes_of\nb,c,no_such_type\n").unwrap();
+        let mut graph = HarmonyGraph::new();
+        let error = graph
+            .import_edge_list(&list, "component", EdgeType::ComposesOf)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            GraphError::InvalidEdge(SchemaError::UnknownEdgeType { .. })
+        ));
+        assert_eq!(graph.node_count(), 0);
+        assert_eq!(graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn test_graphml_round_trip_into_both_graphs() {
+        let mut source = HarmonyGraph::new();
+        source
+            .add_node(GraphNode::new("button", "component"))
+            .unwrap();
+        source
+            .add_node(GraphNode::new("color-primary", "token"))
+            .unwrap();
+        source
+            .connect("button", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        let list = parse_graphml(&source.to_graphml()).unwrap();
+
+        let mut graph = HarmonyGraph::new();
+        let report = graph
+            .import_edge_list(&list, "component", EdgeType::ComposesOf)
+            .unwrap();
+        assert_eq!(report.edge_ids, vec!["uses_token:button->color-primary"]);
+        assert_eq!(graph.node("color-primary").unwrap().node_type, "token");
+
+        let mut executor = WASMEdgeExecutor::new();
+        let mut ids = NodeIdMap::new(0);
+        executor
+            .import_edge_list(&list, &mut ids, "composes_of")
+            .unwrap();
+        let reached = executor.traverse_bfs(ids.id("button").unwrap(), 1, 0);
+        let names: Vec<&str> = reached
+            .nodes
+            .iter()
+            .map(|&id| ids.name(id).unwrap())
+            .collect();
+        assert_eq!(names, vec!["button", "color-primary"]);
+    }
+}
@@ -0,0 +1,269 @@
+//! Merging graphs
+//!
+//! Combines per-team sub-graphs into one canonical design graph. Nodes,
+//! edges and domain links are matched by ID (domain links by component and
+//! domain type); an item present in both graphs with different content is
+//! a conflict, resolved by the `MergeStrategy`. Identical items are
+//! skipped.
+//!
+//! A merge runs as a batch: it applies fully or not at all, and strict
+//! mode rejects merges that create composition cycles.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-merge
+
+use crate::graph::queries::component_domain_queries::BindingMode;
+use crate::graph::{GraphError, HarmonyGraph};
+use std::fmt;
+
+/// How to resolve items that differ between the two graphs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Replace the target's item with the source's
+    SourceWins,
+
+    /// Keep the target's item
+    TargetWins,
+
+    /// Fail the merge, leaving the target untouched
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeConflict {
+    Node(String),
+    Edge(String),
+
+    /// The links differ, or both are primary for different domain types
+    DomainLink {
+        component_id: String,
+        domain_type: String,
+    },
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeConflict::Node(id) => write!(f, "node {}", id),
+            MergeConflict::Edge(id) => write!(f, "edge {}", id),
+            MergeConflict::DomainLink {
+                component_id,
+                domain_type,
+            } => write!(f, "domain link {} -> {}", component_id, domain_type),
+        }
+    }
+}
+
+/// What a merge changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeReport {
+    pub nodes_added: usize,
+    pub edges_added: usize,
+    pub domain_links_added: usize,
+
+    /// Conflicts found, resolved by the strategy
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl HarmonyGraph {
+    /// Merge another graph into this one
+    ///
+    /// Fails with `GraphError::MergeConflicts` if the strategy is `Error`
+    /// and any item conflicts.
+    pub fn merge(
+        &mut self,
+        source: &HarmonyGraph,
+        strategy: MergeStrategy,
+    ) -> Result<MergeReport, GraphError> {
+        self.batch(|target| {
+            let mut report = MergeReport::default();
+            let source_wins = strategy == MergeStrategy::SourceWins;
+
+            for node in source.nodes() {
+                match target.node(&node.id) {
+                    None => {
+                        target.add_node(node.clone())?;
+                        report.nodes_added += 1;
+                    }
+                    Some(existing) if existing == node => {}
+                    Some(_) => {
+                        report.conflicts.push(MergeConflict::Node(node.id.clone()));
+                        if source_wins {
+                            target.upsert_node(node.clone());
+                        }
+                    }
+                }
+            }
+
+            for edge in source.edges() {
+                match target.edge(&edge.id) {
+                    None => {
+                        target.add_edge(edge.clone())?;
+                        report.edges_added += 1;
+                    }
+                    Some(existing) if existing == edge => {}
+                    Some(_) => {
+                        report.conflicts.push(MergeConflict::Edge(edge.id.clone()));
+                        if source_wins {
+                            target.remove_edge(&edge.id)?;
+                            target.add_edge(edge.clone())?;
+                        }
+                    }
+                }
+            }
+
+            for link in source.all_domain_links() {
+                let existing = target.domain_links(&link.component_id);
+                if existing.contains(link) {
+                    continue;
+                }
+                let conflicting: Vec<String> = existing
+                    .iter()
+                    .filter(|l| {
+                        l.domain_type == link.domain_type
+                            || (l.binding_mode == BindingMode::Primary
+                                && link.binding_mode == BindingMode::Primary)
+                    })
+                    .map(|l| l.domain_type.clone())
+                    .collect();
+                if conflicting.is_empty() {
+                    target.add_domain_link(link.clone())?;
+                    report.domain_links_added += 1;
+                    continue;
+                }
+                report.conflicts.push(MergeConflict::DomainLink {
+                    component_id: link.component_id.clone(),
+                    domain_type: link.domain_type.clone(),
+                });
+                if source_wins {
+                    for domain_type in conflicting {
+                        target.remove_domain_link(&link.component_id, &domain_type);
+                    }
+                    target.add_domain_link(link.clone())?;
+                }
+            }
+
+            if strategy == MergeStrategy::Error && !report.conflicts.is_empty() {
+                return Err(GraphError::MergeConflicts(report.conflicts));
+            }
+            Ok(report)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::queries::component_domain_queries::ComponentDomainLink;
+    use crate::graph::GraphNode;
+    use harmony_schemas::{Edge, EdgeMetadata, EdgeType};
+
+    fn team_graphs() -> (HarmonyGraph, HarmonyGraph) {
+        let mut target = HarmonyGraph::new();
+        target
+            .add_node(GraphNode::new("button", "component").with_property("owner", "core"))
+            .unwrap();
+        target
+            .add_node(GraphNode::new("icon", "component"))
+            .unwrap();
+        target
+            .connect("button", "icon", EdgeType::ComposesOf)
+            .unwrap();
+        target
+            .add_domain_link(ComponentDomainLink::new(
+                "button",
+                "Action",
+                BindingMode::Primary,
+            ))
+            .unwrap();
+
+        let mut source = HarmonyGraph::new();
+        source
+            .add_node(GraphNode::new("button", "component").with_property("owner", "player"))
+            .unwrap();
+        source
+            .add_node(GraphNode::new("icon", "component"))
+            .unwrap();
+        source
+            .add_node(GraphNode::new("color-primary", "token"))
+            .unwrap();
+        let mut edge = Edge::new(
+            HarmonyGraph::edge_id("button", "icon", EdgeType::ComposesOf),
+            "button".to_string(),
+            "icon".to_string(),
+            EdgeType::ComposesOf,
+        );
+        edge.metadata = Some(EdgeMetadata {
+            label: Some("leading".to_string()),
+            ..Default::default()
+        });
+        source.add_edge(edge).unwrap();
+        source
+            .connect("button", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        source
+            .add_domain_link(ComponentDomainLink::new(
+                "button",
+                "Track",
+                BindingMode::Primary,
+            ))
+            .unwrap();
+        (target, source)
+    }
+
+    fn owner(graph: &HarmonyGraph) -> &str {
+        graph
+            .node("button")
+            .unwrap()
+            .property("owner")
+            .unwrap()
+            .as_str()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_merge_strategies() {
+        let (mut target, source) = team_graphs();
+        let report = target.merge(&source, MergeStrategy::TargetWins).unwrap();
+        assert_eq!(report.nodes_added, 1);
+        assert_eq!(report.edges_added, 1);
+        assert_eq!(report.domain_links_added, 0);
+        assert_eq!(
+            report.conflicts,
+            vec![
+                MergeConflict::Node("button".to_string()),
+                MergeConflict::Edge("composes_of:button->icon".to_string()),
+                MergeConflict::DomainLink {
+                    component_id: "button".to_string(),
+                    domain_type: "Track".to_string(),
+                },
+            ]
+        );
+        assert_eq!(owner(&target), "core");
+        assert!(target.contains_node("color-primary"));
+
+        let (mut target, source) = team_graphs();
+        target.merge(&source, MergeStrategy::SourceWins).unwrap();
+        assert_eq!(owner(&target), "player");
+        assert_eq!(
+            target.edge("composes_of:button->icon"),
+            source.edge("composes_of:button->icon")
+        );
+        assert_eq!(target.domain_links("button")[0].domain_type, "Track");
+        assert_eq!(target.domain_links("button").len(), 1);
+
+        let (mut target, source) = team_graphs();
+        match target.merge(&source, MergeStrategy::Error) {
+            Err(GraphError::MergeConflicts(conflicts)) => assert_eq!(conflicts.len(), 3),
+            other => panic!("expected conflicts, got {:?}", other),
+        }
+        assert!(!target.contains_node("color-primary"));
+        assert_eq!(owner(&target), "core");
+
+        // Merging a graph into itself changes nothing
+        let copy = target.clone();
+        assert_eq!(
+            target.merge(&copy, MergeStrategy::Error),
+            Ok(MergeReport::default())
+        );
+    }
+}
@@ -2,6 +2,32 @@
 //! 
 //! See: harmony-design/DESIGN_SYSTEM.md#graph-engine
 
+pub mod changeset;
+pub mod component_message_links;
 pub mod component_ui_links;
+pub mod edge_types;
+pub mod export;
+pub mod harmony_graph;
+pub mod health;
+pub mod history;
+pub mod import;
+pub mod merge;
+pub mod persistence;
+pub mod queries;
+pub mod query_cache;
+pub mod transaction;
+pub mod ui_usage_scanner;
+pub mod validation;
 
-pub use component_ui_links::ComponentUILinkManager;
\ No newline at end of file
+pub use changeset::{ChangeRecord, GraphOp};
+pub use component_message_links::{ComponentMessageLinkManager, UntranslatedComponent};
+pub use component_ui_links::ComponentUILinkManager;
+pub use harmony_graph::{GraphError, GraphNode, HarmonyGraph};
+pub use health::HealthReport;
+pub use history::GraphEvent;
+pub use import::ImportReport;
+pub use merge::{MergeConflict, MergeReport, MergeStrategy};
+pub use persistence::GraphDocument;
+pub use query_cache::QueryCache;
+pub use ui_usage_scanner::UIUsageScanner;
+pub use validation::CompositionCycle;
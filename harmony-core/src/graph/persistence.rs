@@ -0,0 +1,349 @@
+//! HarmonyGraph snapshots
+//!
+//! Two formats:
+//! - JSON: a versioned `GraphDocument` (`schema_version` field, migrated
+//!   through harmony-schemas' `MigrationRegistry`), pretty-printed so it
+//!   diffs well when checked into a repo.
+//! - Binary: compact snapshot for fast loading in the browser, reusing the
+//!   registry and executor binary formats.
+//!
+//! Binary Layout (all integers u32, little-endian):
+//! - Header (20 bytes): magic "HGRF", format version, node_count,
+//!   edge_count, domain_links offset
+//! - String table (`StringTable` layout): node IDs, node types, edge IDs
+//! - Nodes: node_count × `NodeBinaryFormat` (id and node_type are string
+//!   ids, props_offset points into the JSON section)
+//! - Edges: edge_count × `EdgeBinaryFormat` (source and target are string
+//!   ids of node IDs, edge_type is the `EdgeTypeRegistry` code)
+//! - Edge extras: edge_count × (edge ID string id, metadata offset)
+//! - JSON section: byte_length, then entries of (byte_length, utf8 JSON)
+//!
+//! Offsets are relative to the start of the JSON section body; `NO_ENTRY`
+//! marks a node without properties or an edge without metadata.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-binary-formats
+
+use crate::graph::queries::component_domain_queries::ComponentDomainLink;
+use crate::graph::{GraphNode, HarmonyGraph};
+use harmony_schemas::versioning::{to_value, Schema};
+use harmony_schemas::{Edge, EdgeMetadata, EdgeType, MigrationRegistry};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use wasm_edge_executor::{EdgeBinaryFormat, EDGE_SIZE};
+use wasm_node_registry::node_binary_format::{NodeBinaryFormat, NODE_BINARY_SIZE};
+use wasm_node_registry::string_table::StringTable;
+
+/// Magic bytes opening a binary snapshot
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"HGRF";
+
+/// Binary snapshot format version
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Size of the binary snapshot header in bytes
+pub const SNAPSHOT_HEADER_SIZE: usize = 20;
+
+/// Offset value for an absent JSON entry
+pub const NO_ENTRY: u32 = u32::MAX;
+
+/// Serializable form of a whole graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphDocument {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<Edge>,
+    #[serde(default)]
+    pub domain_links: Vec<ComponentDomainLink>,
+}
+
+impl Schema for GraphDocument {
+    const NAME: &'static str = "harmony_graph";
+    const VERSION: u32 = 1;
+}
+
+impl HarmonyGraph {
+    /// Snapshot the graph as a document
+    pub fn to_document(&self) -> GraphDocument {
+        GraphDocument {
+            nodes: self.nodes().cloned().collect(),
+            edges: self.edges().cloned().collect(),
+            domain_links: self.all_domain_links().cloned().collect(),
+        }
+    }
+
+    /// Rebuild a graph from a document, checking every invariant
+    pub fn from_document(document: GraphDocument) -> Result<Self, String> {
+        let mut graph = Self::new();
+        for node in document.nodes {
+            graph.add_node(node).map_err(|e| e.to_string())?;
+        }
+        for edge in document.edges {
+            graph.add_edge(edge).map_err(|e| e.to_string())?;
+        }
+        for link in document.domain_links {
+            graph.add_domain_link(link).map_err(|e| e.to_string())?;
+        }
+        Ok(graph)
+    }
+
+    /// Save as a versioned, pretty-printed JSON document
+    pub fn to_json(&self) -> Result<String, String> {
+        let value = to_value(&self.to_document())?;
+        serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+    }
+
+    /// Load a JSON document, migrating older versions
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        Self::from_json_with(&MigrationRegistry::new(), json)
+    }
+
+    /// Load a JSON document using the given migrations
+    pub fn from_json_with(migrations: &MigrationRegistry, json: &str) -> Result<Self, String> {
+        Self::from_document(migrations.from_json(json)?)
+    }
+
+    /// Save as a binary snapshot
+    pub fn to_binary(&self) -> Result<Vec<u8>, String> {
+        let mut strings = StringTable::new();
+        let mut json = JsonSection::default();
+
+        let mut nodes = Vec::with_capacity(self.node_count() * NODE_BINARY_SIZE);
+        for node in self.nodes() {
+            let props_offset = if node.properties.is_empty() {
+                NO_ENTRY
+            } else {
+                json.push(&node.properties)?
+            };
+            let entry = NodeBinaryFormat::new(
+                strings.intern(&node.id),
+                strings.intern(&node.node_type),
+                props_offset,
+            );
+            nodes.extend_from_slice(&entry.to_bytes());
+        }
+
+        let mut edges = vec![0u8; self.edge_count() * EDGE_SIZE];
+        let mut extras = Vec::with_capacity(self.edge_count() * 8);
+        for (i, edge) in self.edges().enumerate() {
+            let entry = EdgeBinaryFormat::new(
+                strings.intern(&edge.from),
+                strings.intern(&edge.to),
+                edge_type_code(edge.edge_type),
+            );
+            entry
+                .write_to(&mut edges, i * EDGE_SIZE)
+                .map_err(|e| e.to_string())?;
+            let metadata_offset = match &edge.metadata {
+                Some(metadata) => json.push(metadata)?,
+                None => NO_ENTRY,
+            };
+            extras.extend_from_slice(&strings.intern(&edge.id).to_le_bytes());
+            extras.extend_from_slice(&metadata_offset.to_le_bytes());
+        }
+
+        let domain_links: Vec<&ComponentDomainLink> = self.all_domain_links().collect();
+        let domain_links_offset = json.push(&domain_links)?;
+
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(SNAPSHOT_MAGIC);
+        for value in [
+            SNAPSHOT_VERSION,
+            self.node_count() as u32,
+            self.edge_count() as u32,
+            domain_links_offset,
+        ] {
+            buffer.extend_from_slice(&value.to_le_bytes());
+        }
+        buffer.extend_from_slice(&strings.to_bytes());
+        buffer.extend_from_slice(&nodes);
+        buffer.extend_from_slice(&edges);
+        buffer.extend_from_slice(&extras);
+        buffer.extend_from_slice(&(json.bytes.len() as u32).to_le_bytes());
+        buffer.extend_from_slice(&json.bytes);
+        Ok(buffer)
+    }
+
+    /// Load a binary snapshot
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, cursor: 0 };
+        if reader.take(4)? != SNAPSHOT_MAGIC {
+            return Err("Not a HarmonyGraph snapshot".to_string());
+        }
+        let version = reader.u32()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(format!("Unsupported snapshot version: {}", version));
+        }
+        let node_count = reader.u32()? as usize;
+        let edge_count = reader.u32()? as usize;
+        let domain_links_offset = reader.u32()?;
+
+        let strings = StringTable::from_bytes(&bytes[reader.cursor..]).map_err(|e| e.to_string())?;
+        let table_size = u32::from_le_bytes(
+            bytes[reader.cursor + 4..reader.cursor + 8]
+                .try_into()
+                .map_err(|_| "Truncated string table")?,
+        );
+        reader.take(table_size as usize)?;
+        let string = |id: u32| {
+            strings
+                .get(id)
+                .map(str::to_string)
+                .ok_or_else(|| format!("Unknown string id: {}", id))
+        };
+
+        let node_bytes = reader.take(node_count * NODE_BINARY_SIZE)?;
+        let edge_bytes = reader.take(edge_count * EDGE_SIZE)?;
+        let extra_bytes = reader.take(edge_count * 8)?;
+        let json_size = reader.u32()? as usize;
+        let json = JsonSection {
+            bytes: reader.take(json_size)?.to_vec(),
+        };
+
+        let mut document = GraphDocument {
+            nodes: Vec::with_capacity(node_count),
+            edges: Vec::with_capacity(edge_count),
+            domain_links: json.get(domain_links_offset)?.unwrap_or_default(),
+        };
+        for chunk in node_bytes.chunks_exact(NODE_BINARY_SIZE) {
+            let entry = NodeBinaryFormat::from_bytes(chunk).map_err(|e| e.to_string())?;
+            document.nodes.push(GraphNode {
+                id: string(entry.id)?,
+                node_type: string(entry.node_type)?,
+                properties: json.get(entry.props_offset)?.unwrap_or_default(),
+            });
+        }
+        for (i, extra) in extra_bytes.chunks_exact(8).enumerate() {
+            let entry = EdgeBinaryFormat::read_from(edge_bytes, i * EDGE_SIZE)
+                .map_err(|e| e.to_string())?;
+            let edge_type = EdgeType::ALL
+                .get(entry.edge_type() as usize)
+                .copied()
+                .ok_or_else(|| format!("Unknown edge type code: {}", entry.edge_type()))?;
+            let id = u32::from_le_bytes([extra[0], extra[1], extra[2], extra[3]]);
+            let metadata_offset = u32::from_le_bytes([extra[4], extra[5], extra[6], extra[7]]);
+            let mut edge = Edge::new(string(id)?, string(entry.source())?, string(entry.target())?, edge_type);
+            edge.metadata = json.get::<EdgeMetadata>(metadata_offset)?;
+            document.edges.push(edge);
+        }
+        Self::from_document(document)
+    }
+}
+
+/// Code of a built-in edge type, matching `EdgeTypeRegistry`
+fn edge_type_code(edge_type: EdgeType) -> u32 {
+    EdgeType::ALL
+        .iter()
+        .position(|t| *t == edge_type)
+        .expect("EdgeType::ALL lists every edge type") as u32
+}
+
+/// Length-prefixed JSON entries addressed by offset
+#[derive(Default)]
+struct JsonSection {
+    bytes: Vec<u8>,
+}
+
+impl JsonSection {
+    fn push<T: Serialize>(&mut self, value: &T) -> Result<u32, String> {
+        let offset = self.bytes.len() as u32;
+        let json = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+        self.bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        self.bytes.extend_from_slice(&json);
+        Ok(offset)
+    }
+
+    fn get<T: DeserializeOwned>(&self, offset: u32) -> Result<Option<T>, String> {
+        if offset == NO_ENTRY {
+            return Ok(None);
+        }
+        let mut reader = Reader {
+            bytes: &self.bytes,
+            cursor: offset as usize,
+        };
+        let len = reader.u32()? as usize;
+        serde_json::from_slice(reader.take(len)?)
+            .map(Some)
+            .map_err(|e| format!("Invalid JSON entry at {}: {}", offset, e))
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.cursor + len;
+        let slice = self
+            .bytes
+            .get(self.cursor..end)
+            .ok_or_else(|| format!("Snapshot truncated at byte {}", self.cursor))?;
+        self.cursor = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::queries::component_domain_queries::BindingMode;
+    use harmony_schemas::EdgeMetadata;
+
+    fn graph() -> HarmonyGraph {
+        let mut graph = HarmonyGraph::new();
+        graph
+            .add_node(GraphNode::new("button", "component").with_property("lifecycle_state", "published"))
+            .unwrap();
+        graph.add_node(GraphNode::new("color-primary", "token")).unwrap();
+        graph.add_node(GraphNode::new("form", "component")).unwrap();
+        graph.connect("form", "button", EdgeType::ComposesOf).unwrap();
+        graph
+            .add_edge(Edge::with_metadata(
+                "button-primary".to_string(),
+                "button".to_string(),
+                "color-primary".to_string(),
+                EdgeType::UsesToken,
+                EdgeMetadata::default().with_property("css_property", "background-color"),
+            ))
+            .unwrap();
+        graph
+            .add_domain_link(ComponentDomainLink::new("form", "Playlist", BindingMode::Primary))
+            .unwrap();
+        graph
+    }
+
+    fn assert_same(a: &HarmonyGraph, b: &HarmonyGraph) {
+        assert_eq!(
+            serde_json::to_value(a.to_document()).unwrap(),
+            serde_json::to_value(b.to_document()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let graph = graph();
+        let json = graph.to_json().unwrap();
+        assert!(json.contains("\"schema_version\": 1"));
+        assert_same(&graph, &HarmonyGraph::from_json(&json).unwrap());
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let graph = graph();
+        let bytes = graph.to_binary().unwrap();
+        assert_eq!(&bytes[0..4], SNAPSHOT_MAGIC);
+        let loaded = HarmonyGraph::from_binary(&bytes).unwrap();
+        assert_same(&graph, &loaded);
+        assert_eq!(
+            loaded.node("button").unwrap().property("lifecycle_state"),
+            Some(&serde_json::json!("published"))
+        );
+
+        assert!(HarmonyGraph::from_binary(&bytes[..bytes.len() - 1]).is_err());
+        assert!(HarmonyGraph::from_binary(b"nope").is_err());
+    }
+}
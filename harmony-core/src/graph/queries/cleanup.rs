@@ -0,0 +1,117 @@
+//! Orphan and dead-node detection
+//!
+//! Finds graph nodes that nothing depends on, for design-system hygiene:
+//! - components no other component uses
+//! - tokens no node uses
+//! - design specs with no implementation (the graph counterpart of
+//!   `ImplementationQuery::find_unimplemented_specs`)
+//!
+//! Edges are not mirrored automatically, so a component counts as used if
+//! it has an outgoing `used_by` edge or an incoming `composes_of` edge.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-hygiene
+
+use crate::graph::HarmonyGraph;
+use harmony_schemas::EdgeType;
+use serde::Serialize;
+
+/// Dead nodes found by `HarmonyGraph::cleanup_report`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CleanupReport {
+    pub unused_components: Vec<String>,
+    pub unused_tokens: Vec<String>,
+    pub unimplemented_specs: Vec<String>,
+}
+
+impl CleanupReport {
+    /// True if nothing needs cleaning up
+    pub fn is_clean(&self) -> bool {
+        self.total() == 0
+    }
+
+    pub fn total(&self) -> usize {
+        self.unused_components.len() + self.unused_tokens.len() + self.unimplemented_specs.len()
+    }
+}
+
+impl HarmonyGraph {
+    /// Components with no `used_by` edge (nor an incoming `composes_of`)
+    pub fn find_unused_components(&self) -> Vec<String> {
+        self.nodes_of_type("component")
+            .filter(|node| {
+                self.successors(&node.id, EdgeType::UsedBy).is_empty()
+                    && self.predecessors(&node.id, EdgeType::ComposesOf).is_empty()
+            })
+            .map(|node| node.id.clone())
+            .collect()
+    }
+
+    /// Tokens with no incoming `uses_token` edge
+    pub fn find_unused_tokens(&self) -> Vec<String> {
+        self.nodes_of_type("token")
+            .filter(|node| self.predecessors(&node.id, EdgeType::UsesToken).is_empty())
+            .map(|node| node.id.clone())
+            .collect()
+    }
+
+    /// Design specs with no incoming `implements_design` edge
+    pub fn find_unimplemented_specs(&self) -> Vec<String> {
+        self.nodes_of_type("design_spec")
+            .filter(|node| {
+                self.predecessors(&node.id, EdgeType::ImplementsDesign)
+                    .is_empty()
+            })
+            .map(|node| node.id.clone())
+            .collect()
+    }
+
+    /// Run all dead-node queries
+    pub fn cleanup_report(&self) -> CleanupReport {
+        CleanupReport {
+            unused_components: self.find_unused_components(),
+            unused_tokens: self.find_unused_tokens(),
+            unimplemented_specs: self.find_unimplemented_specs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphNode;
+
+    #[test]
+    fn test_cleanup_report() {
+        let mut graph = HarmonyGraph::new();
+        for (id, node_type) in [
+            ("app", "component"),
+            ("button", "component"),
+            ("icon", "component"),
+            ("legacy", "component"),
+            ("color-primary", "token"),
+            ("color-legacy", "token"),
+            ("spec:button", "design_spec"),
+            ("spec:tooltip", "design_spec"),
+        ] {
+            graph.add_node(GraphNode::new(id, node_type)).unwrap();
+        }
+        graph
+            .connect("app", "button", EdgeType::ComposesOf)
+            .unwrap();
+        graph.connect("icon", "button", EdgeType::UsedBy).unwrap();
+        graph
+            .connect("button", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        graph
+            .connect("button", "spec:button", EdgeType::ImplementsDesign)
+            .unwrap();
+
+        let report = graph.cleanup_report();
+        assert_eq!(report.unused_components, vec!["app", "legacy"]);
+        assert_eq!(report.unused_tokens, vec!["color-legacy"]);
+        assert_eq!(report.unimplemented_specs, vec!["spec:tooltip"]);
+        assert_eq!(report.total(), 4);
+        assert!(!report.is_clean());
+        assert!(HarmonyGraph::new().cleanup_report().is_clean());
+    }
+}
@@ -1,8 +1,12 @@
 //! Query functions for Component → Domain links
-//! 
+//!
+//! Links are stored on HarmonyGraph (`add_domain_link`); these functions
+//! answer which domain types a component renders and vice versa.
+//!
 //! See: harmony-design/DESIGN_SYSTEM.md#component-domain-links
 
 use crate::graph::HarmonyGraph;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Get all domain types rendered by a component
@@ -10,9 +14,21 @@ pub fn get_component_domains(
     graph: &HarmonyGraph,
     component_id: &str,
 ) -> Vec<ComponentDomainLink> {
-    // Implementation will be generated from schema
-    // Returns list of domain types this component can render
-    vec![]
+    graph.domain_links(component_id).to_vec()
+}
+
+/// Get a component's links with one binding mode
+pub fn get_component_domains_by_mode(
+    graph: &HarmonyGraph,
+    component_id: &str,
+    binding_mode: BindingMode,
+) -> Vec<ComponentDomainLink> {
+    graph
+        .domain_links(component_id)
+        .iter()
+        .filter(|link| link.binding_mode == binding_mode)
+        .cloned()
+        .collect()
 }
 
 /// Get all components that render a specific domain type
@@ -20,9 +36,11 @@ pub fn get_domain_components(
     graph: &HarmonyGraph,
     domain_type: &str,
 ) -> Vec<String> {
-    // Implementation will be generated from schema
-    // Returns list of component IDs that render this domain type
-    vec![]
+    graph
+        .all_domain_links()
+        .filter(|link| link.domain_type == domain_type)
+        .map(|link| link.component_id.clone())
+        .collect()
 }
 
 /// Get the primary domain type for a component (if any)
@@ -30,22 +48,121 @@ pub fn get_component_primary_domain(
     graph: &HarmonyGraph,
     component_id: &str,
 ) -> Option<String> {
-    // Implementation will be generated from schema
-    // Returns the primary domain type this component renders
-    None
+    graph
+        .domain_links(component_id)
+        .iter()
+        .find(|link| link.binding_mode == BindingMode::Primary)
+        .map(|link| link.domain_type.clone())
 }
 
-#[derive(Debug, Clone)]
+/// Get how a component maps a domain type's fields onto its props
+///
+/// # Returns
+/// prop name → domain field path, or None if the component does not
+/// render the domain type
+pub fn get_props_mapping<'a>(
+    graph: &'a HarmonyGraph,
+    component_id: &str,
+    domain_type: &str,
+) -> Option<&'a HashMap<String, String>> {
+    graph
+        .domain_links(component_id)
+        .iter()
+        .find(|link| link.domain_type == domain_type)
+        .map(|link| &link.props_mapping)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ComponentDomainLink {
     pub component_id: String,
     pub domain_type: String,
     pub binding_mode: BindingMode,
+    #[serde(default)]
     pub props_mapping: HashMap<String, String>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl ComponentDomainLink {
+    pub fn new(component_id: &str, domain_type: &str, binding_mode: BindingMode) -> Self {
+        Self {
+            component_id: component_id.to_string(),
+            domain_type: domain_type.to_string(),
+            binding_mode,
+            props_mapping: HashMap::new(),
+        }
+    }
+
+    /// Map a prop to a domain field path
+    pub fn with_prop(mut self, prop: &str, field: &str) -> Self {
+        self.props_mapping.insert(prop.to_string(), field.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BindingMode {
+    /// The domain type the component exists to render
     Primary,
+
+    /// Renders a reference to an instance (e.g. a link or chip)
     Reference,
+
+    /// Renders a list of instances
     Collection,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{GraphError, GraphNode};
+
+    fn graph() -> HarmonyGraph {
+        let mut graph = HarmonyGraph::new();
+        graph.add_node(GraphNode::new("track-card", "component")).unwrap();
+        graph.add_node(GraphNode::new("track-list", "component")).unwrap();
+        graph
+            .add_domain_link(
+                ComponentDomainLink::new("track-card", "Track", BindingMode::Primary)
+                    .with_prop("title", "name")
+                    .with_prop("duration", "length_ms"),
+            )
+            .unwrap();
+        graph
+            .add_domain_link(ComponentDomainLink::new("track-card", "Artist", BindingMode::Reference))
+            .unwrap();
+        graph
+            .add_domain_link(ComponentDomainLink::new("track-list", "Track", BindingMode::Collection))
+            .unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_domain_queries() {
+        let graph = graph();
+        assert_eq!(get_component_domains(&graph, "track-card").len(), 2);
+        assert_eq!(
+            get_domain_components(&graph, "Track"),
+            vec!["track-card".to_string(), "track-list".to_string()]
+        );
+        assert_eq!(get_component_primary_domain(&graph, "track-card"), Some("Track".to_string()));
+        assert_eq!(get_component_primary_domain(&graph, "track-list"), None);
+        assert_eq!(
+            get_component_domains_by_mode(&graph, "track-card", BindingMode::Reference)[0].domain_type,
+            "Artist"
+        );
+        let mapping = get_props_mapping(&graph, "track-card", "Track").unwrap();
+        assert_eq!(mapping["duration"], "length_ms");
+        assert!(get_props_mapping(&graph, "track-list", "Artist").is_none());
+    }
+
+    #[test]
+    fn test_single_primary_domain() {
+        let mut graph = graph();
+        assert_eq!(
+            graph.add_domain_link(ComponentDomainLink::new("track-card", "Album", BindingMode::Primary)),
+            Err(GraphError::PrimaryDomainConflict("track-card".to_string()))
+        );
+        graph.remove_node("track-card").unwrap();
+        assert_eq!(get_domain_components(&graph, "Track"), vec!["track-list".to_string()]);
+    }
+}
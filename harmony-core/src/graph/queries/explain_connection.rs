@@ -0,0 +1,203 @@
+//! Connection explanation query
+//!
+//! For design-review tooling: how are two nodes related? Finds the
+//! shortest paths between them, following edges in either direction, and
+//! spells each one out in the edges' own direction, e.g.
+//! "form composes_of button, button uses_token color-primary".
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-queries
+
+use crate::graph::{GraphError, HarmonyGraph};
+use harmony_schemas::EdgeType;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// Most paths returned by `explain_connection`
+pub const MAX_EXPLAINED_PATHS: usize = 16;
+
+/// One edge of a connection, in the edge's direction
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionStep {
+    pub from: String,
+    pub to: String,
+    pub edge_type: EdgeType,
+
+    /// `EdgeType::description` of the edge type
+    pub description: &'static str,
+}
+
+/// A path between two nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionPath {
+    /// Steps from the first node to the second; an edge may point against
+    /// the walking direction
+    pub steps: Vec<ConnectionStep>,
+}
+
+impl fmt::Display for ConnectionPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|step| format!("{} {} {}", step.from, step.edge_type.as_str(), step.to))
+            .collect();
+        write!(f, "{}", steps.join(", "))
+    }
+}
+
+impl HarmonyGraph {
+    /// Shortest paths between two nodes
+    ///
+    /// # Returns
+    /// Up to MAX_EXPLAINED_PATHS paths of equal, minimal length; empty if
+    /// the nodes are not connected, a single empty path if `a == b`
+    pub fn explain_connection(&self, a: &str, b: &str) -> Result<Vec<ConnectionPath>, GraphError> {
+        for id in [a, b] {
+            if !self.contains_node(id) {
+                return Err(GraphError::UnknownNode(id.to_string()));
+            }
+        }
+
+        // node → (distance from a, edge IDs reaching it on shortest paths)
+        let mut reached: HashMap<&str, (usize, Vec<&str>)> = HashMap::from([(a, (0, Vec::new()))]);
+        let mut queue = VecDeque::from([a]);
+        while let Some(id) = queue.pop_front() {
+            let distance = reached[id].0;
+            if reached.get(b).is_some_and(|(d, _)| *d <= distance) {
+                break;
+            }
+            let neighbours = self
+                .outgoing(id)
+                .map(|e| (e.id.as_str(), e.to.as_str()))
+                .chain(self.incoming(id).map(|e| (e.id.as_str(), e.from.as_str())));
+            for (edge_id, next) in neighbours {
+                match reached.get_mut(next) {
+                    None => {
+                        reached.insert(next, (distance + 1, vec![edge_id]));
+                        queue.push_back(next);
+                    }
+                    Some((d, edges)) if *d == distance + 1 && !edges.contains(&edge_id) => {
+                        edges.push(edge_id)
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        if !reached.contains_key(b) {
+            return Ok(Vec::new());
+        }
+
+        // Walk back from b, newest step first
+        let mut paths = Vec::new();
+        let mut pending: Vec<(&str, Vec<ConnectionStep>)> = vec![(b, Vec::new())];
+        while let Some((id, steps)) = pending.pop() {
+            if id == a {
+                paths.push(ConnectionPath {
+                    steps: steps.into_iter().rev().collect(),
+                });
+                if paths.len() == MAX_EXPLAINED_PATHS {
+                    break;
+                }
+                continue;
+            }
+            for edge_id in reached[id].1.iter().rev() {
+                let Some(edge) = self.edge(edge_id) else {
+                    continue;
+                };
+                let previous = if edge.to == id {
+                    edge.from.as_str()
+                } else {
+                    edge.to.as_str()
+                };
+                let mut steps = steps.clone();
+                steps.push(ConnectionStep {
+                    from: edge.from.clone(),
+                    to: edge.to.clone(),
+                    edge_type: edge.edge_type,
+                    description: edge.edge_type.description(),
+                });
+                pending.push((previous, steps));
+            }
+        }
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphNode;
+
+    fn graph() -> HarmonyGraph {
+        let mut graph = HarmonyGraph::new();
+        for (id, node_type) in [
+            ("form", "component"),
+            ("button", "component"),
+            ("link", "component"),
+            ("color-primary", "token"),
+            ("island", "component"),
+        ] {
+            graph.add_node(GraphNode::new(id, node_type)).unwrap();
+        }
+        graph
+            .connect("form", "button", EdgeType::ComposesOf)
+            .unwrap();
+        graph.connect("form", "link", EdgeType::ComposesOf).unwrap();
+        graph
+            .connect("button", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        graph
+            .connect("link", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_explain_connection() {
+        let graph = graph();
+        let paths = graph.explain_connection("form", "color-primary").unwrap();
+        let explained: Vec<String> = paths.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            explained,
+            vec![
+                "form composes_of button, button uses_token color-primary",
+                "form composes_of link, link uses_token color-primary",
+            ]
+        );
+        assert_eq!(
+            paths[0].steps[1].description,
+            "Component uses a design token"
+        );
+
+        // Edges are followed backwards but reported as they point
+        let paths = graph.explain_connection("button", "link").unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(
+            paths[0].to_string(),
+            "button uses_token color-primary, link uses_token color-primary"
+        );
+        assert_eq!(
+            paths[1].to_string(),
+            "form composes_of button, form composes_of link"
+        );
+    }
+
+    #[test]
+    fn test_unconnected_and_unknown() {
+        let graph = graph();
+        assert!(graph
+            .explain_connection("form", "island")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            graph.explain_connection("form", "form").unwrap()[0]
+                .steps
+                .len(),
+            0
+        );
+        assert_eq!(
+            graph.explain_connection("form", "missing"),
+            Err(GraphError::UnknownNode("missing".to_string()))
+        );
+    }
+}
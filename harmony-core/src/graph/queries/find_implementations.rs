@@ -3,11 +3,16 @@
 //! See: harmony-design/DESIGN_SYSTEM.md#querying-implementations
 
 use crate::graph::edge_types::implements_design::{ImplementsDesignEdge, ImplementationNode};
+use harmony_schemas::DesignSpecNode;
 use std::collections::HashMap;
 
 pub struct ImplementationQuery {
+    /// spec_id → edges of its implementations
     edges: HashMap<String, Vec<ImplementsDesignEdge>>,
     nodes: HashMap<String, ImplementationNode>,
+
+    /// impl_id → spec_id; an implementation implements one spec
+    spec_by_impl: HashMap<String, String>,
 }
 
 impl ImplementationQuery {
@@ -15,9 +20,50 @@ impl ImplementationQuery {
         Self {
             edges: HashMap::new(),
             nodes: HashMap::new(),
+            spec_by_impl: HashMap::new(),
         }
     }
 
+    /// Add or replace an implementation node
+    pub fn add_node(&mut self, node: ImplementationNode) -> Option<ImplementationNode> {
+        self.nodes.insert(node.id.clone(), node)
+    }
+
+    /// Link an implementation to a spec
+    ///
+    /// Replaces the implementation's existing edge, if any.
+    ///
+    /// # Returns
+    /// The replaced edge
+    pub fn add_edge(
+        &mut self,
+        edge: ImplementsDesignEdge,
+    ) -> Result<Option<ImplementsDesignEdge>, String> {
+        edge.validate()?;
+        let previous = self.remove_edge(&edge.source);
+        self.spec_by_impl.insert(edge.source.clone(), edge.target.clone());
+        self.edges.entry(edge.target.clone()).or_default().push(edge);
+        Ok(previous)
+    }
+
+    /// Remove an implementation and its edge
+    pub fn remove(&mut self, impl_id: &str) -> Option<ImplementationNode> {
+        self.remove_edge(impl_id);
+        self.nodes.remove(impl_id)
+    }
+
+    /// Remove an implementation's edge, keeping the node
+    pub fn remove_edge(&mut self, impl_id: &str) -> Option<ImplementsDesignEdge> {
+        let spec_id = self.spec_by_impl.remove(impl_id)?;
+        let edges = self.edges.get_mut(&spec_id)?;
+        let index = edges.iter().position(|e| e.source == impl_id)?;
+        let edge = edges.remove(index);
+        if edges.is_empty() {
+            self.edges.remove(&spec_id);
+        }
+        Some(edge)
+    }
+
     /// Find all implementations for a given design spec
     pub fn find_for_spec(&self, spec_id: &str) -> Vec<&ImplementationNode> {
         self.edges
@@ -33,12 +79,7 @@ impl ImplementationQuery {
 
     /// Find the design spec for a given implementation
     pub fn find_spec_for_impl(&self, impl_id: &str) -> Option<String> {
-        for (spec_id, edges) in &self.edges {
-            if edges.iter().any(|e| e.source == impl_id) {
-                return Some(spec_id.clone());
-            }
-        }
-        None
+        self.spec_by_impl.get(impl_id).cloned()
     }
 
     /// Find incomplete implementations (completeness < 1.0)
@@ -75,6 +116,36 @@ impl ImplementationQuery {
         result
     }
 
+    /// Find implementations pinned to an older version of their spec
+    ///
+    /// Implementations of specs not in `specs` are ignored.
+    pub fn find_outdated(
+        &self,
+        specs: &[DesignSpecNode],
+    ) -> Vec<(&ImplementationNode, &ImplementsDesignEdge)> {
+        specs
+            .iter()
+            .flat_map(|spec| {
+                self.edges
+                    .get(&spec.id)
+                    .into_iter()
+                    .flatten()
+                    .filter(|edge| edge.is_outdated(spec))
+            })
+            .filter_map(|edge| self.nodes.get(&edge.source).map(|node| (node, edge)))
+            .collect()
+    }
+
+    /// Find implementations not pinned to a spec version
+    pub fn find_unpinned(&self) -> Vec<(&ImplementationNode, &ImplementsDesignEdge)> {
+        self.edges
+            .values()
+            .flatten()
+            .filter(|edge| edge.spec_version.is_none())
+            .filter_map(|edge| self.nodes.get(&edge.source).map(|node| (node, edge)))
+            .collect()
+    }
+
     /// Find specs without implementations
     pub fn find_unimplemented_specs(&self, all_spec_ids: &[String]) -> Vec<String> {
         all_spec_ids
@@ -85,6 +156,12 @@ impl ImplementationQuery {
     }
 }
 
+impl Default for ImplementationQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,4 +186,68 @@ mod tests {
         let incomplete = query.find_incomplete();
         assert_eq!(incomplete.len(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_mutations_keep_reverse_index() {
+        let mut query = ImplementationQuery::new();
+        let node = ImplementationNode::new("Button.tsx".to_string(), "Button".to_string());
+        let impl_id = node.id.clone();
+        query.add_node(node);
+        query
+            .add_edge(ImplementsDesignEdge::new(impl_id.clone(), "spec:button".to_string()))
+            .unwrap();
+        assert_eq!(query.find_spec_for_impl(&impl_id), Some("spec:button".to_string()));
+        assert_eq!(query.find_for_spec("spec:button").len(), 1);
+
+        // Re-pointing an implementation moves its edge
+        let previous = query
+            .add_edge(ImplementsDesignEdge::new(impl_id.clone(), "spec:icon-button".to_string()))
+            .unwrap();
+        assert_eq!(previous.unwrap().target, "spec:button");
+        assert!(query.find_for_spec("spec:button").is_empty());
+        assert_eq!(query.find_spec_for_impl(&impl_id), Some("spec:icon-button".to_string()));
+
+        let mut invalid = ImplementsDesignEdge::new(impl_id.clone(), "spec:button".to_string());
+        invalid.completeness = 2.0;
+        assert!(query.add_edge(invalid).is_err());
+
+        assert!(query.remove(&impl_id).is_some());
+        assert_eq!(query.find_spec_for_impl(&impl_id), None);
+        assert_eq!(
+            query.find_unimplemented_specs(&["spec:icon-button".to_string()]),
+            vec!["spec:icon-button".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_find_outdated_after_spec_bump() {
+        let mut query = ImplementationQuery::new();
+        let mut spec = DesignSpecNode::new("spec:button".to_string(), "Button".to_string());
+        for (file, pinned) in [
+            ("Button.tsx", true),
+            ("IconButton.tsx", true),
+            ("LegacyButton.tsx", false),
+        ] {
+            let node = ImplementationNode::new(file.to_string(), file.to_string());
+            let mut edge = ImplementsDesignEdge::new(node.id.clone(), spec.id.clone());
+            if pinned {
+                edge = edge.measured_against(&spec);
+            }
+            query.add_node(node);
+            query.add_edge(edge).unwrap();
+        }
+        assert!(query.find_outdated(std::slice::from_ref(&spec)).is_empty());
+
+        assert_eq!(spec.bump_version(), 2);
+        let node = ImplementationNode::new("Button.tsx".to_string(), "Button".to_string());
+        query
+            .add_edge(ImplementsDesignEdge::new(node.id, spec.id.clone()).measured_against(&spec))
+            .unwrap();
+
+        let outdated = query.find_outdated(std::slice::from_ref(&spec));
+        assert_eq!(outdated.len(), 1);
+        assert_eq!(outdated[0].0.file_path, "IconButton.tsx");
+        assert_eq!(outdated[0].1.spec_version, Some(1));
+        assert_eq!(query.find_unpinned()[0].0.file_path, "LegacyButton.tsx");
+    }
+}
@@ -0,0 +1,10 @@
+//! Graph queries
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-engine
+
+pub mod cleanup;
+pub mod component_domain_queries;
+pub mod explain_connection;
+pub mod find_implementations;
+pub mod query_language;
+pub mod token_impact;
@@ -0,0 +1,516 @@
+//! Graph query language
+//!
+//! A small pattern-matching language over HarmonyGraph:
+//!
+//! ```text
+//! MATCH (c:Component)-[:uses_token]->(t:Token) WHERE t.id = "color-primary" RETURN c
+//! ```
+//!
+//! - `(var:Label)` matches a node; the label is compared to the node type
+//!   ignoring case and underscores (`DesignSpec` matches "design_spec").
+//!   Both the variable and the label are optional.
+//! - `-[:type]->`, `<-[:type]-` and `-[:type]-` follow an edge forwards,
+//!   backwards or either way. `[:a|b]` accepts several edge types, `[]`
+//!   any type.
+//! - `WHERE` takes `var.field = value` or `!=` conditions joined by `AND`.
+//!   `id` and `type` are the node's ID and type, other fields its
+//!   properties. Values are double-quoted strings, numbers or booleans.
+//! - `RETURN` lists variables; each row holds the matched node IDs.
+//!
+//! Keywords are case-insensitive. A variable used twice in a pattern must
+//! bind the same node. Rows are distinct and sorted.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-queries
+
+use crate::graph::{GraphNode, HarmonyGraph};
+use harmony_schemas::EdgeType;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+/// A parsed query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    /// Node patterns in path order
+    pub nodes: Vec<NodePattern>,
+
+    /// `edges[i]` connects `nodes[i]` and `nodes[i + 1]`
+    pub edges: Vec<EdgePattern>,
+
+    pub conditions: Vec<Condition>,
+    pub returns: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodePattern {
+    /// Variable name; anonymous nodes get a generated `_n<i>` name
+    pub var: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgePattern {
+    /// Accepted edge types; empty accepts any
+    pub edge_types: Vec<EdgeType>,
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Outgoing,
+    Incoming,
+    Either,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub var: String,
+    pub field: String,
+    pub negated: bool,
+    pub value: Value,
+}
+
+/// Rows of matched node IDs
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Symbol(&'static str),
+    Word(String),
+    Str(String),
+}
+
+const SYMBOLS: &[&str] = &[
+    "!=", "(", ")", "[", "]", ":", ",", ".", "-", ">", "<", "|", "=",
+];
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        if let Some(symbol) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+            tokens.push(Token::Symbol(symbol));
+            rest = &rest[symbol.len()..];
+        } else if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted
+                .find('"')
+                .ok_or_else(|| "Unterminated string literal".to_string())?;
+            tokens.push(Token::Str(quoted[..end].to_string()));
+            rest = &quoted[end + 1..];
+        } else {
+            let first = rest.chars().next().unwrap_or_default();
+            if !first.is_alphanumeric() && first != '_' {
+                return Err(format!("Unexpected character '{}'", first));
+            }
+            // Numbers may contain a decimal point
+            let end = rest
+                .find(|c: char| {
+                    !(c.is_alphanumeric() || c == '_' || (c == '.' && first.is_ascii_digit()))
+                })
+                .unwrap_or(rest.len());
+            tokens.push(Token::Word(rest[..end].to_string()));
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol_ref(symbol))) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", symbol)))
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn word(&mut self, what: &str) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Word(word)) => {
+                let word = word.clone();
+                self.position += 1;
+                Ok(word)
+            }
+            _ => Err(self.unexpected(what)),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> String {
+        match self.peek() {
+            Some(Token::Symbol(symbol)) => format!("Expected {}, found '{}'", expected, symbol),
+            Some(Token::Word(word)) => format!("Expected {}, found '{}'", expected, word),
+            Some(Token::Str(text)) => format!("Expected {}, found \"{}\"", expected, text),
+            None => format!("Expected {}, found end of query", expected),
+        }
+    }
+
+    fn node(&mut self, index: usize) -> Result<NodePattern, String> {
+        self.expect("(")?;
+        let var = match self.peek() {
+            Some(Token::Word(_)) => self.word("variable")?,
+            _ => format!("_n{}", index),
+        };
+        let label = if self.eat(":") {
+            Some(self.word("node label")?)
+        } else {
+            None
+        };
+        self.expect(")")?;
+        Ok(NodePattern { var, label })
+    }
+
+    /// Parses an edge pattern, or returns None if no edge follows
+    fn edge(&mut self) -> Result<Option<EdgePattern>, String> {
+        let incoming = if self.eat("<") {
+            self.expect("-")?;
+            true
+        } else if self.eat("-") {
+            false
+        } else {
+            return Ok(None);
+        };
+        self.expect("[")?;
+        let mut edge_types = Vec::new();
+        if self.eat(":") {
+            loop {
+                let name = self.word("edge type")?;
+                let edge_type = EdgeType::ALL
+                    .iter()
+                    .find(|t| t.as_str() == name)
+                    .copied()
+                    .ok_or_else(|| format!("Unknown edge type: {}", name))?;
+                edge_types.push(edge_type);
+                if !self.eat("|") {
+                    break;
+                }
+            }
+        }
+        self.expect("]")?;
+        self.expect("-")?;
+        let outgoing = self.eat(">");
+        let direction = match (incoming, outgoing) {
+            (false, true) => Direction::Outgoing,
+            (true, false) => Direction::Incoming,
+            (false, false) => Direction::Either,
+            (true, true) => return Err("Edge cannot point both ways".to_string()),
+        };
+        Ok(Some(EdgePattern {
+            edge_types,
+            direction,
+        }))
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Token::Str(text)) => Ok(Value::String(text)),
+            Some(Token::Word(word)) => {
+                serde_json::from_str(&word).map_err(|_| format!("Invalid value: {}", word))
+            }
+            _ => {
+                self.position -= 1;
+                Err(self.unexpected("value"))
+            }
+        }
+    }
+}
+
+/// Interned form of a symbol, for comparison with `Token::Symbol`
+fn symbol_ref(symbol: &str) -> &'static str {
+    SYMBOLS
+        .iter()
+        .find(|s| **s == symbol)
+        .copied()
+        .unwrap_or("")
+}
+
+/// Parse a query
+pub fn parse(input: &str) -> Result<Query, String> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        position: 0,
+    };
+    if !parser.eat_keyword("MATCH") {
+        return Err(parser.unexpected("MATCH"));
+    }
+    let mut query = Query {
+        nodes: vec![parser.node(0)?],
+        edges: Vec::new(),
+        conditions: Vec::new(),
+        returns: Vec::new(),
+    };
+    while let Some(edge) = parser.edge()? {
+        query.edges.push(edge);
+        query.nodes.push(parser.node(query.nodes.len())?);
+    }
+
+    let is_bound = |query: &Query, var: &str| query.nodes.iter().any(|n| n.var == var);
+    if parser.eat_keyword("WHERE") {
+        loop {
+            let var = parser.word("variable")?;
+            if !is_bound(&query, &var) {
+                return Err(format!("Unknown variable: {}", var));
+            }
+            parser.expect(".")?;
+            let field = parser.word("field")?;
+            let negated = if parser.eat("!=") {
+                true
+            } else {
+                parser.expect("=")?;
+                false
+            };
+            let value = parser.value()?;
+            query.conditions.push(Condition {
+                var,
+                field,
+                negated,
+                value,
+            });
+            if !parser.eat_keyword("AND") {
+                break;
+            }
+        }
+    }
+
+    if !parser.eat_keyword("RETURN") {
+        return Err(parser.unexpected("RETURN"));
+    }
+    loop {
+        let var = parser.word("variable")?;
+        if !is_bound(&query, &var) {
+            return Err(format!("Unknown variable: {}", var));
+        }
+        query.returns.push(var);
+        if !parser.eat(",") {
+            break;
+        }
+    }
+    if parser.peek().is_some() {
+        return Err(parser.unexpected("end of query"));
+    }
+    Ok(query)
+}
+
+fn normalize_label(label: &str) -> String {
+    label.replace('_', "").to_lowercase()
+}
+
+fn field_value(node: &GraphNode, field: &str) -> Value {
+    match field {
+        "id" => Value::String(node.id.clone()),
+        "type" => Value::String(node.node_type.clone()),
+        _ => node.property(field).cloned().unwrap_or(Value::Null),
+    }
+}
+
+impl HarmonyGraph {
+    /// Parse and run a query
+    pub fn query(&self, input: &str) -> Result<QueryResult, String> {
+        Ok(self.execute(&parse(input)?))
+    }
+
+    /// Run a parsed query
+    pub fn execute(&self, query: &Query) -> QueryResult {
+        let mut rows = BTreeSet::new();
+        let mut bindings = HashMap::new();
+        self.match_from(query, 0, None, &mut bindings, &mut rows);
+        QueryResult {
+            columns: query.returns.clone(),
+            rows: rows.into_iter().collect(),
+        }
+    }
+
+    fn match_from<'g>(
+        &'g self,
+        query: &Query,
+        position: usize,
+        previous: Option<&'g str>,
+        bindings: &mut HashMap<String, &'g str>,
+        rows: &mut BTreeSet<Vec<String>>,
+    ) {
+        if position == query.nodes.len() {
+            rows.insert(
+                query
+                    .returns
+                    .iter()
+                    .map(|var| bindings[var].to_string())
+                    .collect(),
+            );
+            return;
+        }
+
+        let candidates: Vec<&str> = match previous {
+            None => self.nodes().map(|n| n.id.as_str()).collect(),
+            Some(from) => {
+                let pattern = &query.edges[position - 1];
+                let accepts = |edge_type: EdgeType| {
+                    pattern.edge_types.is_empty() || pattern.edge_types.contains(&edge_type)
+                };
+                let mut next = Vec::new();
+                if pattern.direction != Direction::Incoming {
+                    next.extend(
+                        self.outgoing(from)
+                            .filter(|e| accepts(e.edge_type))
+                            .map(|e| e.to.as_str()),
+                    );
+                }
+                if pattern.direction != Direction::Outgoing {
+                    next.extend(
+                        self.incoming(from)
+                            .filter(|e| accepts(e.edge_type))
+                            .map(|e| e.from.as_str()),
+                    );
+                }
+                next
+            }
+        };
+
+        let pattern = &query.nodes[position];
+        for id in candidates {
+            if let Some(bound) = bindings.get(&pattern.var) {
+                if *bound == id {
+                    self.match_from(query, position + 1, Some(id), bindings, rows);
+                }
+                continue;
+            }
+            let Some(node) = self.node(id) else { continue };
+            let label_matches = pattern
+                .label
+                .as_ref()
+                .is_none_or(|label| normalize_label(label) == normalize_label(&node.node_type));
+            let conditions_hold = query
+                .conditions
+                .iter()
+                .filter(|c| c.var == pattern.var)
+                .all(|c| (field_value(node, &c.field) == c.value) != c.negated);
+            if label_matches && conditions_hold {
+                bindings.insert(pattern.var.clone(), id);
+                self.match_from(query, position + 1, Some(id), bindings, rows);
+                bindings.remove(&pattern.var);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> HarmonyGraph {
+        let mut graph = HarmonyGraph::new();
+        for (id, node_type) in [
+            ("form", "component"),
+            ("button", "component"),
+            ("icon", "component"),
+            ("color-primary", "token"),
+            ("spacing-md", "token"),
+        ] {
+            graph
+                .add_node(GraphNode::new(id, node_type).with_property("published", id != "icon"))
+                .unwrap();
+        }
+        graph
+            .connect("form", "button", EdgeType::ComposesOf)
+            .unwrap();
+        graph
+            .connect("button", "icon", EdgeType::ComposesOf)
+            .unwrap();
+        graph
+            .connect("button", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        graph
+            .connect("icon", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        graph
+            .connect("form", "spacing-md", EdgeType::UsesToken)
+            .unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_match_where_return() {
+        let result = graph()
+            .query(r#"MATCH (c:Component)-[:uses_token]->(t:Token) WHERE t.id = "color-primary" RETURN c"#)
+            .unwrap();
+        assert_eq!(result.columns, vec!["c"]);
+        assert_eq!(result.rows, vec![vec!["button"], vec!["icon"]]);
+
+        let result = graph()
+            .query("match (c)-[:uses_token]->(t) where c.published = true and t.id != \"spacing-md\" return c, t")
+            .unwrap();
+        assert_eq!(result.rows, vec![vec!["button", "color-primary"]]);
+    }
+
+    #[test]
+    fn test_paths_and_directions() {
+        let graph = graph();
+        let result = graph
+            .query("MATCH (parent)-[:composes_of]->()-[:composes_of]->(leaf) RETURN parent, leaf")
+            .unwrap();
+        assert_eq!(result.rows, vec![vec!["form", "icon"]]);
+
+        let result = graph
+            .query("MATCH (t:Token)<-[:uses_token]-(c)<-[:composes_of]-(p) RETURN p")
+            .unwrap();
+        assert_eq!(result.rows, vec![vec!["button"], vec!["form"]]);
+
+        let result = graph
+            .query("MATCH (a:Component)-[:composes_of|uses_token]-(b) WHERE b.id = \"button\" RETURN a")
+            .unwrap();
+        assert_eq!(result.rows, vec![vec!["form"], vec!["icon"]]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("MATCH (c RETURN c"),
+            Err("Expected ')', found 'RETURN'".to_string())
+        );
+        assert_eq!(
+            parse("MATCH (c)-[:likes]->(d) RETURN c"),
+            Err("Unknown edge type: likes".to_string())
+        );
+        assert_eq!(
+            parse("MATCH (c) RETURN x"),
+            Err("Unknown variable: x".to_string())
+        );
+        assert!(parse("MATCH (c) WHERE c.id = \"x RETURN c").is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Token impact analysis
+//!
+//! Answers "what breaks if I change this token": walks reverse
+//! `uses_token` and `composes_of` edges from a token and reports every
+//! component reached, by distance from the token and lifecycle state.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#token-impact
+
+use crate::graph::{GraphError, HarmonyGraph};
+use harmony_schemas::{EdgeType, LifecycleState};
+use std::collections::{HashMap, HashSet};
+
+/// Node property holding a component's lifecycle state
+pub const LIFECYCLE_STATE_PROPERTY: &str = "lifecycle_state";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AffectedComponent {
+    pub id: String,
+
+    /// None if the node has no (valid) lifecycle state
+    pub lifecycle_state: Option<LifecycleState>,
+}
+
+/// Components at one distance from the token
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpactLevel {
+    /// 1 for direct token users, 2 for their composers, ...
+    pub depth: usize,
+
+    /// Sorted by ID
+    pub components: Vec<AffectedComponent>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenImpact {
+    pub token_id: String,
+
+    /// Non-empty levels in increasing depth
+    pub levels: Vec<ImpactLevel>,
+}
+
+impl TokenImpact {
+    /// All affected components, nearest first
+    pub fn components(&self) -> impl Iterator<Item = &AffectedComponent> {
+        self.levels.iter().flat_map(|level| &level.components)
+    }
+
+    pub fn total(&self) -> usize {
+        self.levels.iter().map(|level| level.components.len()).sum()
+    }
+
+    /// Affected component IDs grouped by lifecycle state
+    pub fn by_lifecycle_state(&self) -> HashMap<Option<LifecycleState>, Vec<&str>> {
+        let mut groups: HashMap<_, Vec<&str>> = HashMap::new();
+        for component in self.components() {
+            groups
+                .entry(component.lifecycle_state)
+                .or_default()
+                .push(&component.id);
+        }
+        groups
+    }
+}
+
+impl HarmonyGraph {
+    /// Find every component affected by a change to a token
+    ///
+    /// Only "component" nodes are reported, but the walk passes through
+    /// other node types. Each component appears once, at its shortest
+    /// distance from the token.
+    pub fn impact_of_token(&self, token_id: &str) -> Result<TokenImpact, GraphError> {
+        if !self.contains_node(token_id) {
+            return Err(GraphError::UnknownNode(token_id.to_string()));
+        }
+
+        let mut visited = HashSet::from([token_id]);
+        let mut frontier = vec![token_id];
+        let mut levels = Vec::new();
+        let mut depth = 0;
+        while !frontier.is_empty() {
+            depth += 1;
+            let mut next: Vec<&str> = frontier
+                .iter()
+                .flat_map(|id| {
+                    let mut dependents = self.predecessors(id, EdgeType::UsesToken);
+                    dependents.extend(self.predecessors(id, EdgeType::ComposesOf));
+                    dependents
+                })
+                .filter(|id| visited.insert(*id))
+                .collect();
+            next.sort_unstable();
+
+            let components: Vec<AffectedComponent> = next
+                .iter()
+                .filter_map(|id| self.node(id))
+                .filter(|node| node.node_type == "component")
+                .map(|node| AffectedComponent {
+                    id: node.id.clone(),
+                    lifecycle_state: node
+                        .property(LIFECYCLE_STATE_PROPERTY)
+                        .and_then(|value| serde_json::from_value(value.clone()).ok()),
+                })
+                .collect();
+            if !components.is_empty() {
+                levels.push(ImpactLevel { depth, components });
+            }
+            frontier = next;
+        }
+
+        Ok(TokenImpact {
+            token_id: token_id.to_string(),
+            levels,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphNode;
+
+    #[test]
+    fn test_impact_of_token() {
+        let mut graph = HarmonyGraph::new();
+        graph
+            .add_node(GraphNode::new("color-primary", "token"))
+            .unwrap();
+        for (id, state) in [
+            ("button", "published"),
+            ("icon", "published"),
+            ("toolbar", "draft"),
+            ("dialog", "bogus"),
+            ("unrelated", "published"),
+        ] {
+            graph
+                .add_node(
+                    GraphNode::new(id, "component").with_property(LIFECYCLE_STATE_PROPERTY, state),
+                )
+                .unwrap();
+        }
+        graph
+            .connect("button", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        graph
+            .connect("icon", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        graph
+            .connect("button", "icon", EdgeType::ComposesOf)
+            .unwrap();
+        graph
+            .connect("toolbar", "button", EdgeType::ComposesOf)
+            .unwrap();
+        graph
+            .connect("dialog", "toolbar", EdgeType::ComposesOf)
+            .unwrap();
+
+        let impact = graph.impact_of_token("color-primary").unwrap();
+        let ids: Vec<Vec<&str>> = impact
+            .levels
+            .iter()
+            .map(|level| level.components.iter().map(|c| c.id.as_str()).collect())
+            .collect();
+        // button reaches icon directly, so stays at depth 1
+        assert_eq!(
+            ids,
+            vec![vec!["button", "icon"], vec!["toolbar"], vec!["dialog"]]
+        );
+        assert_eq!(impact.total(), 4);
+
+        let groups = impact.by_lifecycle_state();
+        assert_eq!(
+            groups[&Some(LifecycleState::Published)],
+            vec!["button", "icon"]
+        );
+        assert_eq!(groups[&Some(LifecycleState::Draft)], vec!["toolbar"]);
+        assert_eq!(groups[&None], vec!["dialog"]);
+
+        assert_eq!(
+            graph.impact_of_token("missing"),
+            Err(GraphError::UnknownNode("missing".to_string()))
+        );
+    }
+}
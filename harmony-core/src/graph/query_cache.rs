@@ -0,0 +1,162 @@
+//! Query result caching
+//!
+//! Dashboards re-run the same expensive queries (token impact, cleanup,
+//! health) every few seconds against a graph that rarely changes. A
+//! QueryCache stores each result with the graph revision it was computed
+//! at and recomputes only once the graph has changed since.
+//!
+//! A cache belongs to one graph: revisions of different graphs (or of a
+//! graph and its clone) are not comparable.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-queries
+
+use crate::graph::queries::cleanup::CleanupReport;
+use crate::graph::queries::token_impact::TokenImpact;
+use crate::graph::{GraphError, HarmonyGraph, HealthReport};
+use std::any::Any;
+use std::collections::HashMap;
+
+/// Cached query results keyed by query
+#[derive(Default)]
+pub struct QueryCache {
+    /// key → (graph revision, result)
+    entries: HashMap<String, (u64, Box<dyn Any + Send + Sync>)>,
+    hits: u64,
+    misses: u64,
+}
+
+impl QueryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the cached result for `key`, computing it if missing or stale
+    ///
+    /// `key` must identify the query and its arguments; results of
+    /// different types must not share a key.
+    pub fn get_or_compute<T: Clone + Send + Sync + 'static>(
+        &mut self,
+        graph: &HarmonyGraph,
+        key: &str,
+        compute: impl FnOnce(&HarmonyGraph) -> T,
+    ) -> T {
+        let revision = graph.revision();
+        if let Some((cached_revision, value)) = self.entries.get(key) {
+            if *cached_revision == revision {
+                if let Some(value) = value.downcast_ref::<T>() {
+                    self.hits += 1;
+                    return value.clone();
+                }
+            }
+        }
+        self.misses += 1;
+        let value = compute(graph);
+        self.entries
+            .insert(key.to_string(), (revision, Box::new(value.clone())));
+        value
+    }
+
+    /// Cached `HarmonyGraph::impact_of_token`
+    pub fn impact_of_token(
+        &mut self,
+        graph: &HarmonyGraph,
+        token_id: &str,
+    ) -> Result<TokenImpact, GraphError> {
+        self.get_or_compute(graph, &format!("impact_of_token:{}", token_id), |g| {
+            g.impact_of_token(token_id)
+        })
+    }
+
+    /// Cached `HarmonyGraph::cleanup_report`
+    pub fn cleanup_report(&mut self, graph: &HarmonyGraph) -> CleanupReport {
+        self.get_or_compute(graph, "cleanup_report", HarmonyGraph::cleanup_report)
+    }
+
+    /// Cached `HarmonyGraph::health_report`
+    pub fn health_report(&mut self, graph: &HarmonyGraph) -> HealthReport {
+        self.get_or_compute(graph, "health_report", HarmonyGraph::health_report)
+    }
+
+    /// Drop results computed before the graph's current revision
+    pub fn evict_stale(&mut self, graph: &HarmonyGraph) {
+        let revision = graph.revision();
+        self.entries.retain(|_, (cached, _)| *cached == revision);
+    }
+
+    /// Drop every result
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// (hits, misses) since creation
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphNode;
+    use harmony_schemas::EdgeType;
+
+    fn graph() -> HarmonyGraph {
+        let mut graph = HarmonyGraph::new();
+        graph
+            .add_node(GraphNode::new("button", "component"))
+            .unwrap();
+        graph
+            .add_node(GraphNode::new("color-primary", "token"))
+            .unwrap();
+        graph
+            .connect("button", "color-primary", EdgeType::UsesToken)
+            .unwrap();
+        graph
+    }
+
+    fn impacted(cache: &mut QueryCache, graph: &HarmonyGraph) -> usize {
+        cache.impact_of_token(graph, "color-primary").unwrap().total()
+    }
+
+    #[test]
+    fn test_cache_hits_until_graph_changes() {
+        let mut graph = graph();
+        let mut cache = QueryCache::new();
+        assert_eq!(impacted(&mut cache, &graph), 1);
+        assert_eq!(impacted(&mut cache, &graph), 1);
+        assert!(!cache.cleanup_report(&graph).is_clean());
+        assert_eq!(cache.stats(), (1, 2));
+
+        graph.add_node(GraphNode::new("card", "component")).unwrap();
+        graph
+            .connect("card", "button", EdgeType::ComposesOf)
+            .unwrap();
+        assert_eq!(impacted(&mut cache, &graph), 2);
+        assert_eq!(cache.stats(), (1, 3));
+
+        cache.evict_stale(&graph);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_rollback_invalidates() {
+        let mut graph = graph();
+        let mut cache = QueryCache::new();
+        graph.begin_transaction().unwrap();
+        graph
+            .remove_edge("uses_token:button->color-primary")
+            .unwrap();
+        assert_eq!(impacted(&mut cache, &graph), 0);
+        graph.rollback().unwrap();
+        assert_eq!(impacted(&mut cache, &graph), 1);
+        assert_eq!(cache.stats(), (0, 2));
+    }
+}
@@ -0,0 +1,142 @@
+//! Graph transactions
+//!
+//! `begin_transaction` records the graph's state; mutations made through
+//! any API until `commit` can be undone together with `rollback`, so
+//! multi-step edits (e.g. deprecating a component and rewiring its users)
+//! either fully apply or leave the graph untouched. In strict mode
+//! `commit` rolls back instead if the result has composition cycles.
+//!
+//! Transactions do not nest; batches inside a transaction still roll back
+//! on their own. The change log is part of the recorded state, so
+//! consumers syncing from it should wait until `in_transaction` is false.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-transactions
+
+use crate::graph::{GraphError, HarmonyGraph};
+
+impl HarmonyGraph {
+    /// Start a transaction
+    pub fn begin_transaction(&mut self) -> Result<(), GraphError> {
+        if self.transaction.is_some() {
+            return Err(GraphError::TransactionActive);
+        }
+        self.transaction = Some(Box::new(self.snapshot()));
+        Ok(())
+    }
+
+    /// Keep the transaction's changes
+    ///
+    /// In strict mode, fails with `GraphError::CompositionCycles` and
+    /// rolls back if the graph has composition cycles.
+    pub fn commit(&mut self) -> Result<(), GraphError> {
+        let snapshot = self.transaction.take().ok_or(GraphError::NoTransaction)?;
+        if let Err(error) = self.check_strict() {
+            self.restore(*snapshot);
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Undo the transaction's changes
+    pub fn rollback(&mut self) -> Result<(), GraphError> {
+        let snapshot = self.transaction.take().ok_or(GraphError::NoTransaction)?;
+        self.restore(*snapshot);
+        Ok(())
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{GraphError, GraphNode, HarmonyGraph};
+    use harmony_schemas::EdgeType;
+
+    fn graph() -> HarmonyGraph {
+        let mut graph = HarmonyGraph::new();
+        for id in ["form", "old-button", "button"] {
+            graph.add_node(GraphNode::new(id, "component")).unwrap();
+        }
+        graph
+            .connect("form", "old-button", EdgeType::ComposesOf)
+            .unwrap();
+        graph
+    }
+
+    /// Deprecate old-button and move its users to button
+    fn rewire(graph: &mut HarmonyGraph) -> Result<(), GraphError> {
+        graph.set_node_property("old-button", "lifecycle_state", "deprecated".into())?;
+        graph.connect("button", "old-button", EdgeType::Replaces)?;
+        for user in graph
+            .predecessors("old-button", EdgeType::ComposesOf)
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>()
+        {
+            graph.remove_edge(&HarmonyGraph::edge_id(
+                &user,
+                "old-button",
+                EdgeType::ComposesOf,
+            ))?;
+            graph.connect(&user, "button", EdgeType::ComposesOf)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_and_rollback() {
+        let mut graph = graph();
+        graph.begin_transaction().unwrap();
+        assert_eq!(
+            graph.begin_transaction(),
+            Err(GraphError::TransactionActive)
+        );
+        rewire(&mut graph).unwrap();
+        graph.rollback().unwrap();
+        assert!(!graph.in_transaction());
+        assert_eq!(
+            graph.successors("form", EdgeType::ComposesOf),
+            vec!["old-button"]
+        );
+        assert!(graph.node("old-button").unwrap().properties.is_empty());
+
+        graph.begin_transaction().unwrap();
+        rewire(&mut graph).unwrap();
+        graph.commit().unwrap();
+        assert_eq!(
+            graph.successors("form", EdgeType::ComposesOf),
+            vec!["button"]
+        );
+        assert_eq!(graph.rollback(), Err(GraphError::NoTransaction));
+        assert_eq!(graph.commit(), Err(GraphError::NoTransaction));
+    }
+
+    #[test]
+    fn test_batch_inside_transaction_and_strict_commit() {
+        let mut graph = graph();
+        graph.set_strict(true);
+        graph.begin_transaction().unwrap();
+        graph
+            .connect("old-button", "button", EdgeType::ComposesOf)
+            .unwrap();
+
+        // A failed batch undoes only itself
+        assert!(graph
+            .batch(|g| g.connect("form", "missing", EdgeType::ComposesOf))
+            .is_err());
+        assert!(graph.in_transaction());
+        assert_eq!(graph.edge_count(), 2);
+
+        graph
+            .connect("button", "form", EdgeType::ComposesOf)
+            .unwrap();
+        assert!(matches!(
+            graph.commit(),
+            Err(GraphError::CompositionCycles(_))
+        ));
+        assert_eq!(graph.edge_count(), 1);
+        assert!(!graph.in_transaction());
+    }
+}
@@ -0,0 +1,250 @@
+//! UI usage scanner
+//!
+//! Finds where components are used in HTML/TSX source text and emits
+//! ComponentUILinks, so ComponentUILinkManager can be populated from the
+//! sources instead of by hand. Two usages are recognised:
+//! - custom element tags (`<harmony-button ...>`): names starting with a
+//!   lowercase letter and containing a hyphen; HTML comments are skipped
+//! - dynamic imports (`import("./components/harmony-button.js")`): the
+//!   module's file name without extensions names the component
+//!
+//! Tag and module names are the component IDs unless aliased. The UI
+//! location is the scanned file's name without extensions, e.g.
+//! "src/ui/app-shell.html" → "app-shell".
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#component-ui-links
+
+use crate::graph::ComponentUILinkManager;
+use harmony_schemas::{ComponentUILink, UIUsageContext};
+use std::collections::HashMap;
+
+/// Scans source text for component usages
+#[derive(Debug, Clone, Default)]
+pub struct UIUsageScanner {
+    /// tag or module name → component ID
+    aliases: HashMap<String, String>,
+}
+
+impl UIUsageScanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map a tag or module name to a component ID
+    pub fn with_alias(mut self, name: &str, component_id: &str) -> Self {
+        self.aliases
+            .insert(name.to_string(), component_id.to_string());
+        self
+    }
+
+    /// Find the component usages in a file, in source order
+    ///
+    /// Tags in `.html` files are reported as `Template` usages, tags in
+    /// other files as `WebComponentTag`.
+    pub fn scan_source(&self, file_path: &str, source: &str) -> Vec<ComponentUILink> {
+        let ui_location = file_stem(file_path);
+        let tag_context = if file_path.ends_with(".html") {
+            UIUsageContext::Template
+        } else {
+            UIUsageContext::WebComponentTag
+        };
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        let mut usages: Vec<(usize, &str, UIUsageContext)> = find_tags(source)
+            .into_iter()
+            .map(|(offset, name)| (offset, name, tag_context.clone()))
+            .chain(
+                find_dynamic_imports(source)
+                    .into_iter()
+                    .map(|(offset, name)| (offset, name, UIUsageContext::DynamicImport)),
+            )
+            .collect();
+        usages.sort_by_key(|(offset, _, _)| *offset);
+
+        usages
+            .into_iter()
+            .map(|(offset, name, context)| {
+                let line = line_starts.partition_point(|start| *start <= offset);
+                ComponentUILink::new(
+                    self.resolve(name),
+                    ui_location.to_string(),
+                    file_path.to_string(),
+                    context,
+                )
+                .with_line_number(line as u32)
+            })
+            .collect()
+    }
+
+    /// Scan a file and add its usages to a manager
+    ///
+    /// Links previously recorded for the file's UI location are replaced,
+    /// so rescanning a changed file does not leave stale links.
+    ///
+    /// # Returns
+    /// The number of links added
+    pub fn scan_into(
+        &self,
+        manager: &mut ComponentUILinkManager,
+        file_path: &str,
+        source: &str,
+    ) -> usize {
+        let links = self.scan_source(file_path, source);
+        manager.remove_links_for_ui(file_stem(file_path));
+        let count = links.len();
+        for link in links {
+            manager.add_link(link);
+        }
+        count
+    }
+
+    fn resolve(&self, name: &str) -> String {
+        self.aliases
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    }
+}
+
+/// File name without directories or extensions
+fn file_stem(path: &str) -> &str {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    name.split('.').next().unwrap_or(name)
+}
+
+/// Byte ranges of HTML comments
+fn comment_ranges(source: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut position = 0;
+    while let Some(start) = source[position..].find("<!--").map(|i| i + position) {
+        let end = source[start..]
+            .find("-->")
+            .map_or(source.len(), |i| start + i + 3);
+        ranges.push((start, end));
+        position = end;
+    }
+    ranges
+}
+
+/// Opening custom element tags as (offset, name)
+fn find_tags(source: &str) -> Vec<(usize, &str)> {
+    let comments = comment_ranges(source);
+    source
+        .match_indices('<')
+        .filter(|(offset, _)| {
+            !comments
+                .iter()
+                .any(|(start, end)| start <= offset && offset < end)
+        })
+        .filter_map(|(offset, _)| {
+            let rest = &source[offset + 1..];
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                .unwrap_or(rest.len());
+            let name = &rest[..end];
+            let is_custom_element = name.starts_with(|c: char| c.is_ascii_lowercase())
+                && name.contains('-')
+                && !name.ends_with('-');
+            is_custom_element.then_some((offset, name))
+        })
+        .collect()
+}
+
+/// `import(...)` calls with a string literal as (offset, module name)
+fn find_dynamic_imports(source: &str) -> Vec<(usize, &str)> {
+    source
+        .match_indices("import")
+        .filter(|(offset, _)| {
+            !source[..*offset]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$' || c == '.')
+        })
+        .filter_map(|(offset, keyword)| {
+            let rest = source[offset + keyword.len()..].trim_start();
+            let rest = rest.strip_prefix('(')?.trim_start();
+            let quote = rest
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '"' | '\'' | '`'))?;
+            let specifier = &rest[1..];
+            let specifier = &specifier[..specifier.find(quote)?];
+            let name = file_stem(specifier);
+            (!name.is_empty()).then_some((offset, name))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_html() {
+        let source = r#"<main>
+  <harmony-button variant="primary"></harmony-button>
+  <!-- <legacy-button></legacy-button> -->
+  <div><icon-play></icon-play></div>
+</main>"#;
+        let links = UIUsageScanner::new()
+            .with_alias("harmony-button", "button-primary")
+            .scan_source("src/ui/app-shell.html", source);
+        let found: Vec<(&str, Option<u32>)> = links
+            .iter()
+            .map(|l| (l.component_id.as_str(), l.line_number))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("button-primary", Some(2)), ("icon-play", Some(4))]
+        );
+        assert!(links.iter().all(|l| l.ui_location == "app-shell"));
+        assert!(links
+            .iter()
+            .all(|l| l.usage_context == UIUsageContext::Template));
+    }
+
+    #[test]
+    fn test_scan_tsx() {
+        let source = r#"const Player = () => {
+  const loaded = import("./components/playback-controls.js");
+  const later = import( '../lib/track-list' );
+  myimport("./not-a-call.js");
+  return <section>{a < b}<track-card id={id} /></section>;
+};"#;
+        let links = UIUsageScanner::new().scan_source("src/views/Player.tsx", source);
+        let found: Vec<(&str, &UIUsageContext, Option<u32>)> = links
+            .iter()
+            .map(|l| (l.component_id.as_str(), &l.usage_context, l.line_number))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("playback-controls", &UIUsageContext::DynamicImport, Some(2)),
+                ("track-list", &UIUsageContext::DynamicImport, Some(3)),
+                ("track-card", &UIUsageContext::WebComponentTag, Some(5)),
+            ]
+        );
+        assert_eq!(links[0].ui_location, "Player");
+    }
+
+    #[test]
+    fn test_scan_into_replaces_stale_links() {
+        let scanner = UIUsageScanner::new();
+        let mut manager = ComponentUILinkManager::new();
+        let path = "src/ui/playback-view.html";
+        assert_eq!(
+            scanner.scan_into(&mut manager, path, "<icon-play></icon-play>"),
+            1
+        );
+        assert_eq!(
+            scanner.scan_into(&mut manager, path, "<icon-pause></icon-pause>"),
+            1
+        );
+
+        let components = manager.get_components_in_ui("playback-view");
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].component_id, "icon-pause");
+    }
+}
@@ -0,0 +1,155 @@
+//! Graph validation passes
+//!
+//! Composition (`composes_of`) and pattern inheritance (`inherits_pattern`)
+//! must form a DAG: a component cannot contain or inherit from itself.
+//! Individual mutations do not check this, since a batch may pass through
+//! a cyclic state; in strict mode `HarmonyGraph::batch` runs the check
+//! when the batch completes.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#graph-validation
+
+use crate::graph::HarmonyGraph;
+use harmony_schemas::EdgeType;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Edge types that must not form cycles
+pub const COMPOSITION_EDGE_TYPES: [EdgeType; 2] = [EdgeType::ComposesOf, EdgeType::InheritsPattern];
+
+/// A cycle of composition edges
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositionCycle {
+    /// Node IDs along the cycle; the first and last are the same node
+    pub path: Vec<String>,
+}
+
+impl fmt::Display for CompositionCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.join(" -> "))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+impl HarmonyGraph {
+    /// Find cycles in `composes_of` / `inherits_pattern` edges
+    ///
+    /// Reports one cycle per back edge found by a depth-first search in ID
+    /// order, so every cyclic region is reported at least once (though not
+    /// every elementary cycle through it). Empty if the graph is valid.
+    pub fn find_composition_cycles(&self) -> Vec<CompositionCycle> {
+        let mut visits = HashMap::new();
+        let mut stack = Vec::new();
+        let mut cycles = Vec::new();
+        for node in self.nodes() {
+            if !visits.contains_key(node.id.as_str()) {
+                self.visit_composition(&node.id, &mut visits, &mut stack, &mut cycles);
+            }
+        }
+        cycles
+    }
+
+    fn visit_composition<'g>(
+        &'g self,
+        id: &'g str,
+        visits: &mut HashMap<&'g str, Visit>,
+        stack: &mut Vec<&'g str>,
+        cycles: &mut Vec<CompositionCycle>,
+    ) {
+        visits.insert(id, Visit::InProgress);
+        stack.push(id);
+        for edge in self
+            .outgoing(id)
+            .filter(|e| COMPOSITION_EDGE_TYPES.contains(&e.edge_type))
+        {
+            match visits.get(edge.to.as_str()) {
+                None => self.visit_composition(&edge.to, visits, stack, cycles),
+                Some(Visit::InProgress) => {
+                    let start = stack.iter().position(|n| *n == edge.to).unwrap_or(0);
+                    let mut path: Vec<String> =
+                        stack[start..].iter().map(|n| n.to_string()).collect();
+                    path.push(edge.to.clone());
+                    cycles.push(CompositionCycle { path });
+                }
+                Some(Visit::Done) => {}
+            }
+        }
+        stack.pop();
+        visits.insert(id, Visit::Done);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{GraphError, GraphNode};
+
+    fn graph() -> HarmonyGraph {
+        let mut graph = HarmonyGraph::new();
+        for id in ["card", "header", "icon", "base"] {
+            graph.add_node(GraphNode::new(id, "component")).unwrap();
+        }
+        graph
+            .connect("card", "header", EdgeType::ComposesOf)
+            .unwrap();
+        graph
+            .connect("header", "icon", EdgeType::ComposesOf)
+            .unwrap();
+        graph
+            .connect("card", "base", EdgeType::InheritsPattern)
+            .unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_find_composition_cycles() {
+        let mut graph = graph();
+        assert!(graph.find_composition_cycles().is_empty());
+
+        graph.connect("icon", "card", EdgeType::ComposesOf).unwrap();
+        graph
+            .connect("base", "base", EdgeType::InheritsPattern)
+            .unwrap();
+        let cycles = graph.find_composition_cycles();
+        assert_eq!(cycles.len(), 2);
+        assert_eq!(cycles[0].to_string(), "base -> base");
+        assert_eq!(cycles[1].to_string(), "card -> header -> icon -> card");
+    }
+
+    #[test]
+    fn test_strict_batch_rolls_back_cycles() {
+        let mut graph = graph();
+        graph.set_strict(true);
+
+        let result = graph.batch(|g| {
+            g.add_node(GraphNode::new("footer", "component"))?;
+            g.connect("icon", "card", EdgeType::ComposesOf)
+        });
+        match result {
+            Err(GraphError::CompositionCycles(cycles)) => {
+                assert_eq!(cycles[0].path, vec!["card", "header", "icon", "card"]);
+            }
+            other => panic!("expected cycle error, got {:?}", other),
+        }
+        assert!(!graph.contains_node("footer"));
+        assert_eq!(graph.edge_count(), 3);
+
+        // A transient cycle is fine if the batch resolves it
+        graph
+            .batch(|g| {
+                let id = g.connect("icon", "card", EdgeType::ComposesOf)?;
+                g.remove_edge(&id).map(|_| ())
+            })
+            .unwrap();
+
+        graph.set_strict(false);
+        graph
+            .batch(|g| g.connect("icon", "card", EdgeType::ComposesOf))
+            .unwrap();
+        assert_eq!(graph.find_composition_cycles().len(), 1);
+    }
+}
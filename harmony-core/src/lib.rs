@@ -4,4 +4,23 @@
 
 pub mod graph;
 
-pub use graph::ComponentUILinkManager;
\ No newline at end of file
+pub use graph::{
+    ChangeRecord,
+    ComponentMessageLinkManager,
+    ComponentUILinkManager,
+    CompositionCycle,
+    GraphDocument,
+    GraphError,
+    GraphEvent,
+    GraphNode,
+    GraphOp,
+    HarmonyGraph,
+    HealthReport,
+    ImportReport,
+    MergeConflict,
+    MergeReport,
+    MergeStrategy,
+    QueryCache,
+    UIUsageScanner,
+    UntranslatedComponent,
+};
\ No newline at end of file
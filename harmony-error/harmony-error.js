@@ -0,0 +1,37 @@
+/**
+ * HarmonyError: errors raised by the WASM bounded contexts
+ *
+ * Rust APIs throw a JS `Error` with `name === 'HarmonyError'`, a `code`
+ * from `ErrorCode` and a `context` array (outermost first); JSON APIs
+ * return `{ code, message, context }` or carry `code` next to `error`.
+ *
+ * @module HarmonyError
+ */
+
+/**
+ * Error codes (matches Rust HarmonyError::code)
+ * @enum {string}
+ */
+export const ErrorCode = {
+  INVALID_INPUT: 'invalid_input',
+  NOT_FOUND: 'not_found',
+  MALFORMED: 'malformed',
+  CAPACITY_EXCEEDED: 'capacity_exceeded',
+  UNSUPPORTED: 'unsupported',
+  INTERNAL: 'internal',
+};
+
+/**
+ * Whether a caught value is a HarmonyError, optionally of one code
+ *
+ * @param {unknown} error - Value caught from a WASM call
+ * @param {string} [code] - ErrorCode to match
+ * @returns {boolean}
+ */
+export function isHarmonyError(error, code) {
+  return (
+    error instanceof Error &&
+    error.name === 'HarmonyError' &&
+    (code === undefined || error.code === code)
+  );
+}
@@ -0,0 +1,253 @@
+//! Harmony Error
+//!
+//! Error type shared by the bounded contexts, so failures look the same
+//! from every crate and JS can branch on the kind of problem instead of
+//! parsing messages. Each error has:
+//! - a stable machine-readable `code()` (snake_case, matching `ErrorCode`
+//!   in harmony-error.js)
+//! - the message of the underlying failure
+//! - optional context, added by callers with `with_context` to say what
+//!   was being done, outermost first
+//!
+//! `Display` gives "context: ...: message". With the `wasm` feature
+//! (default), errors cross into JS as an `Error` with
+//! `name = "HarmonyError"` and `code` and `context` properties. APIs that
+//! return JSON use `to_json` instead. serde-wasm-bindgen conversion errors
+//! are `malformed`, like JSON errors.
+//!
+//! With the `schemas` feature, harmony-schemas `SchemaError`s convert into
+//! `HarmonyError`, keeping their own code as context.
+//!
+//! `limits` holds the input caps the entry points check, which fail with
+//! `capacity_exceeded`. `wire` encodes and decodes the CBOR and MessagePack
+//! payloads of the `...Encoded` APIs.
+
+pub mod limits;
+pub mod wire;
+
+use std::fmt;
+
+use serde_json::json;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::JsValue;
+
+/// Error returned across the bounded contexts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HarmonyError {
+    /// An argument or value outside what the operation accepts
+    InvalidInput(String),
+
+    /// A referenced node, index, type or parameter that does not exist
+    NotFound(String),
+
+    /// Binary or JSON data that could not be decoded
+    Malformed(String),
+
+    /// A buffer, queue or table too small for the operation
+    CapacityExceeded(String),
+
+    /// An operation the target does not support
+    Unsupported(String),
+
+    /// A broken invariant inside a context
+    Internal(String),
+
+    /// Another error, with what was being done when it happened
+    Context {
+        context: String,
+        source: Box<HarmonyError>,
+    },
+}
+
+impl HarmonyError {
+    /// Stable machine-readable code of the underlying error
+    pub fn code(&self) -> &'static str {
+        match self.root() {
+            HarmonyError::InvalidInput(_) => "invalid_input",
+            HarmonyError::NotFound(_) => "not_found",
+            HarmonyError::Malformed(_) => "malformed",
+            HarmonyError::CapacityExceeded(_) => "capacity_exceeded",
+            HarmonyError::Unsupported(_) => "unsupported",
+            HarmonyError::Internal(_) => "internal",
+            HarmonyError::Context { .. } => unreachable!("root is never a context"),
+        }
+    }
+
+    /// Message of the underlying error, without context
+    pub fn message(&self) -> &str {
+        match self.root() {
+            HarmonyError::InvalidInput(message)
+            | HarmonyError::NotFound(message)
+            | HarmonyError::Malformed(message)
+            | HarmonyError::CapacityExceeded(message)
+            | HarmonyError::Unsupported(message)
+            | HarmonyError::Internal(message) => message,
+            HarmonyError::Context { .. } => unreachable!("root is never a context"),
+        }
+    }
+
+    /// Context added to the error, outermost first
+    pub fn context(&self) -> Vec<&str> {
+        let mut context = Vec::new();
+        let mut error = self;
+        while let HarmonyError::Context {
+            context: outer,
+            source,
+        } = error
+        {
+            context.push(outer.as_str());
+            error = source;
+        }
+        context
+    }
+
+    /// The error without any context
+    pub fn root(&self) -> &HarmonyError {
+        match self {
+            HarmonyError::Context { source, .. } => source.root(),
+            error => error,
+        }
+    }
+
+    /// Wraps the error with what was being done
+    pub fn with_context(self, context: impl Into<String>) -> Self {
+        HarmonyError::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// JSON form: `{ "code", "message", "context" }`
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "code": self.code(),
+            "message": self.message(),
+            "context": self.context(),
+        })
+    }
+}
+
+impl fmt::Display for HarmonyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for context in self.context() {
+            write!(f, "{}: ", context)?;
+        }
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for HarmonyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HarmonyError::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for HarmonyError {
+    fn from(error: serde_json::Error) -> Self {
+        HarmonyError::Malformed(error.to_string())
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl From<serde_wasm_bindgen::Error> for HarmonyError {
+    fn from(error: serde_wasm_bindgen::Error) -> Self {
+        HarmonyError::Malformed(error.to_string())
+    }
+}
+
+/// A JS `Error` with `name`, `code` and `context` set
+#[cfg(feature = "wasm")]
+impl From<HarmonyError> for JsValue {
+    fn from(error: HarmonyError) -> Self {
+        let js_error = js_sys::Error::new(&error.to_string());
+        js_error.set_name("HarmonyError");
+        let context: js_sys::Array = error.context().into_iter().map(JsValue::from).collect();
+        // Setting properties on a fresh Error object cannot fail
+        let _ = js_sys::Reflect::set(&js_error, &"code".into(), &error.code().into());
+        let _ = js_sys::Reflect::set(&js_error, &"context".into(), &context);
+        js_error.into()
+    }
+}
+
+#[cfg(feature = "schemas")]
+impl From<harmony_schemas::SchemaError> for HarmonyError {
+    fn from(error: harmony_schemas::SchemaError) -> Self {
+        use harmony_schemas::SchemaError;
+
+        let code = error.code();
+        let message = error.to_string();
+        let error = match error {
+            SchemaError::UnknownTemplate { .. }
+            | SchemaError::UnknownEdgeType { .. }
+            | SchemaError::UnknownLocale { .. } => HarmonyError::NotFound(message),
+            _ => HarmonyError::InvalidInput(message),
+        };
+        error.with_context(code)
+    }
+}
+
+/// Adds context to the error of a `Result`
+pub trait ResultExt<T> {
+    fn context(self, context: impl Into<String>) -> Result<T, HarmonyError>;
+
+    /// Like `context`, building the context only on error
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C)
+        -> Result<T, HarmonyError>;
+}
+
+impl<T, E: Into<HarmonyError>> ResultExt<T> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T, HarmonyError> {
+        self.map_err(|error| error.into().with_context(context))
+    }
+
+    fn with_context<C: Into<String>>(
+        self,
+        context: impl FnOnce() -> C,
+    ) -> Result<T, HarmonyError> {
+        self.map_err(|error| error.into().with_context(context()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_message_and_context() {
+        let error = HarmonyError::NotFound("Unknown audio node 7".to_string());
+        assert_eq!(error.code(), "not_found");
+        assert!(error.context().is_empty());
+        assert_eq!(error.to_string(), "Unknown audio node 7");
+
+        let result: Result<(), _> = Err(error.clone());
+        let wrapped = result
+            .context("restore_state")
+            .with_context(|| format!("graph {}", 2))
+            .unwrap_err();
+        assert_eq!(wrapped.code(), "not_found");
+        assert_eq!(wrapped.message(), "Unknown audio node 7");
+        assert_eq!(wrapped.context(), vec!["graph 2", "restore_state"]);
+        assert_eq!(wrapped.root(), &error);
+        assert_eq!(
+            wrapped.to_string(),
+            "graph 2: restore_state: Unknown audio node 7"
+        );
+        assert_eq!(
+            wrapped.to_json(),
+            json!({
+                "code": "not_found",
+                "message": "Unknown audio node 7",
+                "context": ["graph 2", "restore_state"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_errors_are_malformed() {
+        let error: HarmonyError = serde_json::from_str::<u32>("{").unwrap_err().into();
+        assert_eq!(error.code(), "malformed");
+    }
+}
@@ -0,0 +1,144 @@
+//! Input limits for the WASM entry points
+//!
+//! Caps on what a single call may hand a context, so a malformed or hostile
+//! payload fails with `capacity_exceeded` instead of exhausting the WASM
+//! instance's memory or stack:
+//! - `max_json_bytes`: length of a JSON string argument, or of a CBOR or
+//!   MessagePack payload
+//! - `max_depth`: nesting of JSON arrays/objects, and of parenthesised or
+//!   unary sub-expressions in curve formulas
+//! - `max_edges_per_batch`: edges decoded from one binary buffer, or read
+//!   from one imported GraphML or CSV document
+//! - `max_tokens_per_document`: tokens indexed for one full-text document
+//!
+//! Limits are per thread, like the rest of a WASM module's state. With the
+//! `wasm` feature, `set_limits` and `get_limits` are exported to JS.
+
+use std::cell::Cell;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::HarmonyError;
+
+/// Caps checked at the entry points; see the module docs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    pub max_json_bytes: usize,
+    pub max_depth: usize,
+    pub max_edges_per_batch: usize,
+    pub max_tokens_per_document: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_json_bytes: 1024 * 1024,
+            max_depth: 64,
+            max_edges_per_batch: 1_000_000,
+            max_tokens_per_document: 100_000,
+        }
+    }
+}
+
+thread_local! {
+    static LIMITS: Cell<Limits> = Cell::new(Limits::default());
+}
+
+/// The limits in force
+pub fn limits() -> Limits {
+    LIMITS.with(Cell::get)
+}
+
+/// Replace the limits in force
+pub fn set(limits: Limits) {
+    LIMITS.with(|current| current.set(limits));
+}
+
+/// Fails with `capacity_exceeded` if `count` is over `max`
+///
+/// `what` names the counted thing, e.g. "Edge batch"
+pub fn check(what: &str, count: usize, max: usize) -> Result<(), HarmonyError> {
+    if count > max {
+        return Err(HarmonyError::CapacityExceeded(format!(
+            "{} is {}, over the limit of {}",
+            what, count, max
+        )));
+    }
+    Ok(())
+}
+
+/// Checks a JSON argument's length and nesting before it is parsed
+///
+/// Only strings and brackets are scanned; anything else malformed is left
+/// for the parser to report.
+pub fn check_json(json: &str) -> Result<(), HarmonyError> {
+    let limits = limits();
+    check("JSON payload size", json.len(), limits.max_json_bytes)?;
+
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for byte in json.bytes() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                check("JSON nesting depth", depth, limits.max_depth)?;
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Set limits from JSON; omitted fields take their defaults
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn set_limits(limits_json: &str) -> Result<(), HarmonyError> {
+    set(serde_json::from_str(limits_json)?);
+    Ok(())
+}
+
+/// The limits in force, as JSON
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_limits() -> String {
+    serde_json::to_string(&limits()).unwrap_or_else(|_| "{}".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_size_and_depth() {
+        set_limits(r#"{"max_json_bytes": 32, "max_depth": 2}"#).unwrap();
+        assert_eq!(limits().max_edges_per_batch, 1_000_000);
+
+        assert!(check_json(r#"{"a": [1, "[[[{"]}"#).is_ok());
+        assert!(check_json(r#"{"a": "\"[[["}"#).is_ok());
+        let error = check_json(r#"{"a": [[1]]}"#).unwrap_err();
+        assert_eq!(error.code(), "capacity_exceeded");
+        assert_eq!(
+            error.to_string(),
+            "JSON nesting depth is 3, over the limit of 2"
+        );
+        let error = check_json(&format!(r#""{}""#, "x".repeat(40))).unwrap_err();
+        assert_eq!(error.code(), "capacity_exceeded");
+
+        assert_eq!(set_limits("\"none\"").unwrap_err().code(), "malformed");
+        set(Limits::default());
+        assert!(check_json(r#"{"a": [[1]]}"#).is_ok());
+    }
+}
@@ -0,0 +1,177 @@
+//! Wire formats for the import/export and result APIs
+//!
+//! Every JSON-string API of the contexts has an `...Encoded` twin taking a
+//! `format` argument and passing bytes (`Uint8Array` in JS) instead:
+//! - "json": UTF-8 JSON, identical to the string APIs
+//! - "cbor": CBOR (RFC 8949)
+//! - "msgpack": MessagePack, with structs as maps keyed by field name
+//!
+//! All three carry the same data model, so a CBOR or MessagePack decoder in
+//! JS gives the same objects as `JSON.parse` on the string API; binary
+//! payloads are smaller and faster to produce than JSON text.
+//!
+//! Decoding checks the same limits as JSON arguments: the payload length
+//! against `max_json_bytes` and the nesting against `max_depth`.
+
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{limits, HarmonyError};
+
+/// Encoding of an `...Encoded` payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireFormat {
+    Json,
+    Cbor,
+    MessagePack,
+}
+
+impl FromStr for WireFormat {
+    type Err = HarmonyError;
+
+    /// Parses "json", "cbor" or "msgpack"
+    fn from_str(format: &str) -> Result<Self, HarmonyError> {
+        match format {
+            "json" => Ok(Self::Json),
+            "cbor" => Ok(Self::Cbor),
+            "msgpack" => Ok(Self::MessagePack),
+            _ => Err(HarmonyError::InvalidInput(format!(
+                "Unknown wire format '{}'; expected 'json', 'cbor' or 'msgpack'",
+                format
+            ))),
+        }
+    }
+}
+
+/// Encodes `value` in `format`
+pub fn encode<T: Serialize + ?Sized>(
+    value: &T,
+    format: WireFormat,
+) -> Result<Vec<u8>, HarmonyError> {
+    match format {
+        WireFormat::Json => Ok(serde_json::to_vec(value)?),
+        WireFormat::Cbor => {
+            let mut bytes = Vec::new();
+            ciborium::ser::into_writer(value, &mut bytes).map_err(|error| {
+                HarmonyError::Internal(format!("CBOR encoding failed: {}", error))
+            })?;
+            Ok(bytes)
+        }
+        WireFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|error| {
+            HarmonyError::Internal(format!("MessagePack encoding failed: {}", error))
+        }),
+    }
+}
+
+/// Decodes a `format` payload, failing with `capacity_exceeded` over the
+/// limits and `malformed` for invalid data
+pub fn decode<T: DeserializeOwned>(bytes: &[u8], format: WireFormat) -> Result<T, HarmonyError> {
+    let max = limits::limits();
+    match format {
+        WireFormat::Json => {
+            let json = std::str::from_utf8(bytes).map_err(|error| {
+                HarmonyError::Malformed(format!("JSON is not UTF-8: {}", error))
+            })?;
+            limits::check_json(json)?;
+            Ok(serde_json::from_str(json)?)
+        }
+        WireFormat::Cbor => {
+            limits::check("CBOR payload size", bytes.len(), max.max_json_bytes)?;
+            ciborium::de::from_reader_with_recursion_limit(bytes, max.max_depth)
+                .map_err(|error| HarmonyError::Malformed(format!("Invalid CBOR: {}", error)))
+        }
+        WireFormat::MessagePack => {
+            limits::check("MessagePack payload size", bytes.len(), max.max_json_bytes)?;
+            let mut deserializer = rmp_serde::Deserializer::from_read_ref(bytes);
+            deserializer.set_max_depth(max.max_depth);
+            T::deserialize(&mut deserializer)
+                .map_err(|error| HarmonyError::Malformed(format!("Invalid MessagePack: {}", error)))
+        }
+    }
+}
+
+/// Parses `format` and encodes `value` in it
+pub fn encode_as<T: Serialize + ?Sized>(value: &T, format: &str) -> Result<Vec<u8>, HarmonyError> {
+    encode(value, format.parse()?)
+}
+
+/// Parses `format` and decodes a payload in it
+pub fn decode_as<T: DeserializeOwned>(bytes: &[u8], format: &str) -> Result<T, HarmonyError> {
+    decode(bytes, format.parse()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Edge {
+        source: u32,
+        target: u32,
+        kind: String,
+    }
+
+    #[test]
+    fn test_round_trip_in_every_format() {
+        let edges = vec![
+            Edge {
+                source: 1,
+                target: 2,
+                kind: "audio".to_string(),
+            },
+            Edge {
+                source: 2,
+                target: 3,
+                kind: "control".to_string(),
+            },
+        ];
+        for format in [WireFormat::Json, WireFormat::Cbor, WireFormat::MessagePack] {
+            let bytes = encode(&edges, format).unwrap();
+            assert_eq!(decode::<Vec<Edge>>(&bytes, format).unwrap(), edges);
+            // Structs keep their field names in every format
+            let value: Value = decode(&bytes, format).unwrap();
+            assert_eq!(value[1]["kind"], "control");
+        }
+        assert_eq!(
+            encode_as(&edges, "json").unwrap(),
+            serde_json::to_vec(&edges).unwrap()
+        );
+        assert!(
+            encode_as(&edges, "cbor").unwrap().len() < serde_json::to_vec(&edges).unwrap().len()
+        );
+        assert_eq!(
+            encode_as(&edges, "xml").unwrap_err().code(),
+            "invalid_input"
+        );
+    }
+
+    #[test]
+    fn test_decode_errors_and_limits() {
+        for format in ["json", "cbor", "msgpack"] {
+            let error = decode_as::<Vec<Edge>>(&[0xc1, 0xff, 0x00], format).unwrap_err();
+            assert_eq!(error.code(), "malformed", "{}", format);
+        }
+
+        let mut nested = json!(0);
+        for _ in 0..100 {
+            nested = json!([nested]);
+        }
+        for format in [WireFormat::Json, WireFormat::Cbor, WireFormat::MessagePack] {
+            let bytes = encode(&nested, format).unwrap();
+            assert!(decode::<Value>(&bytes, format).is_err(), "{:?}", format);
+        }
+
+        let bytes = encode(&vec![0u32; 64], WireFormat::MessagePack).unwrap();
+        limits::set(limits::Limits {
+            max_json_bytes: 16,
+            ..Default::default()
+        });
+        let error = decode::<Vec<u32>>(&bytes, WireFormat::MessagePack).unwrap_err();
+        limits::set(limits::Limits::default());
+        assert_eq!(error.code(), "capacity_exceeded");
+    }
+}
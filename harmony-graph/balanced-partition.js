@@ -9,6 +9,8 @@
  * See: harmony-design/DESIGN_SYSTEM.md#graph-algorithms-balanced-partition
  */
 
+import { resolveRandom } from './seeded-random.js';
+
 /**
  * @typedef {Object} Partition
  * @property {string} id - Partition identifier
@@ -30,6 +32,8 @@
  * @property {number} maxImbalance - Maximum allowed imbalance ratio (default: 1.1)
  * @property {string} strategy - Partitioning strategy: 'greedy', 'bfs', 'random' (default: 'greedy')
  * @property {string} [seedNode] - Starting node for BFS-based strategies
+ * @property {number} [seed] - Random seed for the 'random' strategy (default:
+ *   the deterministic mode seed, or unseeded)
  */
 
 /**
@@ -68,7 +72,8 @@ export class BalancedPartition {
       numPartitions = 2,
       maxImbalance = 1.1,
       strategy = 'greedy',
-      seedNode = null
+      seedNode = null,
+      seed
     } = options;
 
     if (numPartitions < 1) {
@@ -89,7 +94,7 @@ export class BalancedPartition {
         partitions = this._bfsPartition(numPartitions, seedNode);
         break;
       case 'random':
-        partitions = this._randomPartition(numPartitions);
+        partitions = this._randomPartition(numPartitions, resolveRandom(seed));
         break;
       default:
         throw new Error(`Unknown partitioning strategy: ${strategy}`);
@@ -286,9 +291,10 @@ export class BalancedPartition {
    * 
    * @private
    * @param {number} numPartitions - Number of partitions
+   * @param {import('./seeded-random.js').RandomSource} random - Random source
    * @returns {Partition[]} Array of partitions
    */
-  _randomPartition(numPartitions) {
+  _randomPartition(numPartitions, random) {
     const partitions = Array.from({ length: numPartitions }, (_, i) => ({
       id: `partition-${i}`,
       nodes: new Set(),
@@ -296,7 +302,7 @@ export class BalancedPartition {
     }));
 
     const nodeIds = Array.from(this.nodes.keys());
-    const shuffled = this._shuffle(nodeIds);
+    const shuffled = this._shuffle(nodeIds, random);
 
     shuffled.forEach((nodeId, index) => {
       const partitionIndex = index % numPartitions;
@@ -441,12 +447,13 @@ export class BalancedPartition {
    * 
    * @private
    * @param {Array} array - Array to shuffle
+   * @param {import('./seeded-random.js').RandomSource} random - Random source
    * @returns {Array} Shuffled array
    */
-  _shuffle(array) {
+  _shuffle(array, random) {
     const result = [...array];
     for (let i = result.length - 1; i > 0; i--) {
-      const j = Math.floor(Math.random() * (i + 1));
+      const j = Math.floor(random() * (i + 1));
       [result[i], result[j]] = [result[j], result[i]];
     }
     return result;
@@ -15,6 +15,7 @@
 
 import { EventBus } from '../core/event-bus.js';
 import { GraphIntrospector } from './graph-introspector.js';
+import { resolveRandom } from './seeded-random.js';
 
 /**
  * Layout algorithms for graph visualization
@@ -51,6 +52,8 @@ export class GraphVisualizer {
    * @param {Object} [options.edgeStyle] - Default edge styling
    * @param {boolean} [options.interactive=true] - Enable pan/zoom interactions
    * @param {number} [options.animationDuration=300] - Animation duration in ms
+   * @param {number} [options.seed] - Seed for initial force-directed positions
+   *   (default: the deterministic mode seed, or unseeded)
    */
   constructor(options = {}) {
     this.width = options.width || 800;
@@ -58,6 +61,7 @@ export class GraphVisualizer {
     this.layout = options.layout || LayoutType.FORCE_DIRECTED;
     this.interactive = options.interactive !== false;
     this.animationDuration = options.animationDuration || 300;
+    this.seed = options.seed;
     
     this.nodeStyle = {
       radius: 20,
@@ -178,10 +182,11 @@ export class GraphVisualizer {
     const nodes = Array.from(graph.nodes.keys());
     
     // Initialize random positions
+    const random = resolveRandom(this.seed);
     nodes.forEach(nodeId => {
       positions.set(nodeId, {
-        x: Math.random() * this.width,
-        y: Math.random() * this.height
+        x: random() * this.width,
+        y: random() * this.height
       });
       velocities.set(nodeId, { x: 0, y: 0 });
     });
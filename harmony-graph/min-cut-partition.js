@@ -16,6 +16,8 @@
  * @module harmony-graph/min-cut-partition
  */
 
+import { resolveRandom } from './seeded-random.js';
+
 /**
  * Result of a minimum cut partition operation
  * @typedef {Object} MinCutResult
@@ -36,6 +38,9 @@
  * @property {number} [balanceTolerance=0.2] - Tolerance for balance constraint (0.0-1.0)
  * @property {number} [timeBudgetMs=15] - Maximum time budget per operation
  * @property {Function} [edgeWeight] - Custom edge weight function (edge) => number
+ * @property {number} [seed] - Random seed for Karger's algorithm (default: the
+ *   deterministic mode seed, or unseeded). Results only reproduce if the time
+ *   budget doesn't cut the iterations short.
  */
 
 /**
@@ -381,9 +386,10 @@ export class MinCutPartitioner {
   _karger(adjacency) {
     let bestCut = Infinity;
     let bestPartition = null;
+    const random = resolveRandom(this.config.seed);
     
     for (let iter = 0; iter < this.config.kargerIterations; iter++) {
-      const result = this._kargerIteration(adjacency);
+      const result = this._kargerIteration(adjacency, random);
       
       if (result.cutWeight < bestCut) {
         bestCut = result.cutWeight;
@@ -404,9 +410,10 @@ export class MinCutPartitioner {
    * Single iteration of Karger's algorithm
    * @private
    * @param {Map<string, Map<string, number>>} adjacency
+   * @param {import('./seeded-random.js').RandomSource} random - Random source
    * @returns {MinCutResult}
    */
-  _kargerIteration(adjacency) {
+  _kargerIteration(adjacency, random) {
     // Create mutable copy
     const graph = new Map();
     adjacency.forEach((neighbors, node) => {
@@ -419,7 +426,7 @@ export class MinCutPartitioner {
     // Contract until 2 nodes remain
     while (graph.size > 2) {
       // Pick random edge
-      const edge = this._pickRandomEdge(graph);
+      const edge = this._pickRandomEdge(graph, random);
       if (!edge) break;
       
       this._contractNodes(graph, contractions, edge.from, edge.to);
@@ -449,9 +456,10 @@ export class MinCutPartitioner {
    * Pick random edge from graph
    * @private
    * @param {Map<string, Map<string, number>>} graph
+   * @param {import('./seeded-random.js').RandomSource} random - Random source
    * @returns {{from: string, to: string, weight: number}|null}
    */
-  _pickRandomEdge(graph) {
+  _pickRandomEdge(graph, random) {
     const edges = [];
     
     graph.forEach((neighbors, from) => {
@@ -465,7 +473,7 @@ export class MinCutPartitioner {
     
     if (edges.length === 0) return null;
     
-    const randomIdx = Math.floor(Math.random() * edges.length);
+    const randomIdx = Math.floor(random() * edges.length);
     return edges[randomIdx];
   }
 
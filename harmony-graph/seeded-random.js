@@ -0,0 +1,84 @@
+/**
+ * @fileoverview SeededRandom: Reproducible random numbers for graph algorithms
+ * @module harmony-graph/seeded-random
+ *
+ * Randomized algorithms (random partitioning, Karger contraction, layout
+ * seeding) take their random numbers from `resolveRandom(seed)`:
+ * - An explicit seed gives a generator seeded with it
+ * - Otherwise, in deterministic mode, a generator seeded with the global seed
+ * - Otherwise `Math.random`
+ *
+ * Every call to `resolveRandom` starts a fresh sequence, so the same seed
+ * gives the same result on every run and in every browser. Turn deterministic
+ * mode on in tests and snapshot runs.
+ *
+ * See: harmony-design/DESIGN_SYSTEM.md#graph-algorithms-seeded-random
+ */
+
+/**
+ * Seed used by deterministic mode unless another is given
+ * @type {number}
+ */
+export const DEFAULT_SEED = 0x2545f491;
+
+/**
+ * Returns a number in [0, 1), like Math.random
+ * @typedef {function(): number} RandomSource
+ */
+
+let deterministic = false;
+let globalSeed = DEFAULT_SEED;
+
+/**
+ * Create a generator seeded with `seed` (mulberry32)
+ *
+ * @param {number} seed - Any number; only its low 32 bits are used
+ * @returns {RandomSource} Generator giving the same sequence for the same seed
+ *
+ * @example
+ * const random = createSeededRandom(42);
+ * const index = Math.floor(random() * items.length);
+ */
+export function createSeededRandom(seed) {
+  let state = seed >>> 0;
+  return () => {
+    state = (state + 0x6d2b79f5) >>> 0;
+    let t = state;
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+}
+
+/**
+ * Turn deterministic mode on or off
+ *
+ * @param {boolean} enabled - Whether unseeded algorithms use the global seed
+ * @param {number} [seed=DEFAULT_SEED] - Global seed while enabled
+ */
+export function setDeterministicMode(enabled, seed = DEFAULT_SEED) {
+  deterministic = Boolean(enabled);
+  globalSeed = seed >>> 0;
+}
+
+/**
+ * Check whether deterministic mode is on
+ *
+ * @returns {boolean}
+ */
+export function isDeterministicMode() {
+  return deterministic;
+}
+
+/**
+ * Get the random source for one run of a randomized algorithm
+ *
+ * @param {number} [seed] - Explicit seed, taking precedence over the mode
+ * @returns {RandomSource}
+ */
+export function resolveRandom(seed) {
+  if (seed !== undefined && seed !== null) {
+    return createSeededRandom(seed);
+  }
+  return deterministic ? createSeededRandom(globalSeed) : Math.random;
+}
@@ -0,0 +1,105 @@
+/**
+ * @fileoverview Tests for SeededRandom and the seeded graph algorithms
+ * @see DESIGN_SYSTEM.md § Graph Algorithms → Seeded Random
+ */
+
+import {
+  createSeededRandom,
+  isDeterministicMode,
+  resolveRandom,
+  setDeterministicMode
+} from './seeded-random.js';
+import { BalancedPartition } from './balanced-partition.js';
+import { MinCutPartitioner } from './min-cut-partition.js';
+
+/**
+ * Test suite for SeededRandom.
+ */
+export function runSeededRandomTests() {
+  console.group('SeededRandom Tests');
+
+  testSeededSequences();
+  testDeterministicMode();
+  testSeededPartitions();
+
+  console.groupEnd();
+}
+
+function testSeededSequences() {
+  console.group('Seeded Sequences');
+
+  const first = createSeededRandom(42);
+  const second = createSeededRandom(42);
+  const values = Array.from({ length: 100 }, () => first());
+  console.assert(
+    values.every(value => value === second()),
+    'Same seed should give the same sequence'
+  );
+  console.assert(
+    values.every(value => value >= 0 && value < 1),
+    'Values should be in [0, 1)'
+  );
+  console.assert(createSeededRandom(43)() !== values[0], 'Seeds should differ');
+
+  console.log('✓ Seeded sequences reproduce');
+  console.groupEnd();
+}
+
+function testDeterministicMode() {
+  console.group('Deterministic Mode');
+
+  console.assert(!isDeterministicMode(), 'Should be off by default');
+  console.assert(resolveRandom() === Math.random, 'Should use Math.random when off');
+
+  setDeterministicMode(true, 7);
+  console.assert(isDeterministicMode(), 'Should turn on');
+  console.assert(
+    resolveRandom()() === createSeededRandom(7)(),
+    'Should seed each run with the global seed'
+  );
+  console.assert(
+    resolveRandom(8)() === createSeededRandom(8)(),
+    'Explicit seed should take precedence'
+  );
+  setDeterministicMode(false);
+
+  console.log('✓ Deterministic mode works correctly');
+  console.groupEnd();
+}
+
+function testSeededPartitions() {
+  console.group('Seeded Partitions');
+
+  const ids = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+  const nodes = new Map(ids.map(id => [id, {}]));
+  const adjacency = new Map(ids.map((id, i) => [id, new Set([ids[(i + 1) % ids.length]])]));
+  const balanced = new BalancedPartition({ nodes, adjacency });
+  const randomPartition = () => balanced
+    .partition({ strategy: 'random', seed: 3 })
+    .partitions.map(partition => [...partition.nodes].join(''))
+    .join('|');
+  console.assert(randomPartition() === randomPartition(), 'Random strategy should reproduce');
+
+  const graph = {
+    nodes: ids.map(id => ({ id })),
+    edges: ids.map((id, i) => ({ source: id, target: ids[(i + 1) % ids.length] }))
+  };
+  const minCut = () => {
+    const result = new MinCutPartitioner(graph, {
+      algorithm: 'karger',
+      kargerIterations: 5,
+      timeBudgetMs: Infinity,
+      seed: 3
+    }).partition();
+    return [...result.partition1].sort().join('');
+  };
+  console.assert(minCut() === minCut(), 'Karger should reproduce');
+
+  console.log('✓ Seeded partitions reproduce');
+  console.groupEnd();
+}
+
+// Auto-run tests if this file is executed directly
+if (import.meta.url === `file://${process.argv[1]}`) {
+  runSeededRandomTests();
+}
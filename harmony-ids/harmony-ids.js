@@ -0,0 +1,35 @@
+/**
+ * HarmonyIds: node ID interning shared by the WASM bounded contexts
+ *
+ * Each WASM module linking harmony-ids exports `intern_node_id`,
+ * `intern_node_ids`, `node_id`, `node_name`, `node_names`,
+ * `export_node_ids`, `import_node_ids` and `clear_node_ids`. The map is per
+ * module: the edge executor's imports, spatial inserts and full-text
+ * documents of one module all intern into it, so a name has the same u32
+ * in every context of that module (e.g. a worker built from
+ * harmony-worker-protocol).
+ *
+ * @module HarmonyIds
+ */
+
+/**
+ * @typedef {Object} NodeIdTable
+ * @property {number} first - ID of `names[0]`
+ * @property {string[]} names - Names in ID order: node `first + i` is `names[i]`
+ */
+
+/**
+ * Copy one module's node ID map into others
+ *
+ * Modules built separately each have their own map. Intern in one of them
+ * and copy its map to the rest before translating IDs there.
+ *
+ * @param {{export_node_ids: function(): string}} source - WASM exports to copy from
+ * @param {Array<{import_node_ids: function(string): void}>} modules - WASM exports to copy to
+ */
+export function syncNodeIds(source, modules) {
+  const json = source.export_node_ids();
+  for (const module of modules) {
+    module.import_node_ids(json);
+  }
+}
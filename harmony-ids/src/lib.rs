@@ -0,0 +1,293 @@
+//! Harmony IDs
+//!
+//! Node ID interning shared by the bounded contexts. harmony-schemas, the
+//! spatial index and the full-text index name nodes with strings; the edge
+//! executor packs u32 node IDs into its edges. A `NodeIdMap` gives each
+//! name a u32, handed out consecutively in the order names are first seen,
+//! and translates both ways.
+//!
+//! Each module has one map, per thread like the rest of its state: the
+//! executor's imports, spatial inserts and full-text documents all intern
+//! into it, so a name found by one context is the same u32 everywhere in
+//! the module. IDs are never reused; the map only grows until it is
+//! cleared or replaced.
+//!
+//! With the `wasm` feature (default), every module linking this crate
+//! exports `intern_node_id`, `intern_node_ids`, `node_id`, `node_name`,
+//! `node_names`, `export_node_ids`, `import_node_ids` and `clear_node_ids`
+//! (see harmony-ids.js).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+use harmony_error::{limits, HarmonyError};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Two-way mapping between node names and u32 node IDs
+///
+/// IDs are handed out consecutively from `first`. Serializes as
+/// `{"first", "names"}`: the node with ID `first + i` is `names[i]`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "NodeIdTable")]
+pub struct NodeIdMap {
+    first: u32,
+    names: Vec<String>,
+    #[serde(skip)]
+    ids: HashMap<String, u32>,
+}
+
+/// Serialized form of a `NodeIdMap`, checked on the way in
+#[derive(Deserialize)]
+struct NodeIdTable {
+    #[serde(default)]
+    first: u32,
+    names: Vec<String>,
+}
+
+/// Why a serialized `NodeIdMap` was rejected
+#[derive(Debug)]
+pub struct InvalidNodeIds(String);
+
+impl fmt::Display for InvalidNodeIds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<NodeIdTable> for NodeIdMap {
+    type Error = InvalidNodeIds;
+
+    fn try_from(table: NodeIdTable) -> Result<Self, Self::Error> {
+        let mut map = NodeIdMap::new(table.first);
+        for name in table.names {
+            if map.id(&name).is_some() {
+                return Err(InvalidNodeIds(format!("Duplicate node name '{}'", name)));
+            }
+            map.intern(&name)
+                .map_err(|error| InvalidNodeIds(error.to_string()))?;
+        }
+        Ok(map)
+    }
+}
+
+impl NodeIdMap {
+    /// Empty map handing out IDs from `first`, e.g. past the IDs already
+    /// used in an executor
+    pub fn new(first: u32) -> Self {
+        Self {
+            first,
+            ..Self::default()
+        }
+    }
+
+    /// ID of `name`, if interned
+    pub fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    /// Name the ID was handed out for
+    pub fn name(&self, id: u32) -> Option<&str> {
+        let index = id.checked_sub(self.first)?;
+        self.names.get(index as usize).map(String::as_str)
+    }
+
+    /// ID of `name`, handing out the next one if it has none
+    ///
+    /// # Returns
+    /// `capacity_exceeded` once the u32 ID space is used up
+    pub fn intern(&mut self, name: &str) -> Result<u32, HarmonyError> {
+        if let Some(id) = self.id(name) {
+            return Ok(id);
+        }
+        let id = u32::try_from(self.names.len())
+            .ok()
+            .and_then(|offset| self.first.checked_add(offset))
+            .ok_or_else(|| {
+                HarmonyError::CapacityExceeded(format!("No node ID left for '{}'", name))
+            })?;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        Ok(id)
+    }
+
+    /// First ID handed out
+    pub fn first(&self) -> u32 {
+        self.first
+    }
+
+    /// Interned names, in ID order
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+thread_local! {
+    static NODE_IDS: RefCell<NodeIdMap> = RefCell::new(NodeIdMap::default());
+}
+
+/// Run `f` on the module's map
+///
+/// `f` must not call back into this module's functions.
+pub fn with_node_ids<R>(f: impl FnOnce(&mut NodeIdMap) -> R) -> R {
+    NODE_IDS.with(|ids| f(&mut ids.borrow_mut()))
+}
+
+/// ID of `name` in the module's map, handing out the next one if it has none
+pub fn intern(name: &str) -> Result<u32, HarmonyError> {
+    with_node_ids(|ids| ids.intern(name))
+}
+
+/// ID of `name` in the module's map, if interned
+pub fn id(name: &str) -> Option<u32> {
+    with_node_ids(|ids| ids.id(name))
+}
+
+/// Name of `id` in the module's map
+pub fn name(id: u32) -> Option<String> {
+    with_node_ids(|ids| ids.name(id).map(str::to_string))
+}
+
+/// Copy of the module's map, e.g. for a snapshot
+pub fn node_ids() -> NodeIdMap {
+    with_node_ids(|ids| ids.clone())
+}
+
+/// Replace the module's map
+pub fn set_node_ids(map: NodeIdMap) {
+    with_node_ids(|ids| *ids = map);
+}
+
+/// Node ID of a name, handing out the next one if it has none
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn intern_node_id(name: &str) -> Result<u32, HarmonyError> {
+    intern(name)
+}
+
+/// Node IDs of several names, handing out new ones as needed; all or none
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn intern_node_ids(names: Vec<String>) -> Result<Vec<u32>, HarmonyError> {
+    with_node_ids(|ids| {
+        let mut interned = ids.clone();
+        let result = names
+            .iter()
+            .map(|name| interned.intern(name))
+            .collect::<Result<Vec<u32>, HarmonyError>>()?;
+        *ids = interned;
+        Ok(result)
+    })
+}
+
+/// Node ID of a name, or undefined if it was never interned
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn node_id(name: &str) -> Option<u32> {
+    id(name)
+}
+
+/// Name of a node ID, or undefined if it was never handed out
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn node_name(id: u32) -> Option<String> {
+    name(id)
+}
+
+/// Names of several node IDs, e.g. a traversal result's nodes
+///
+/// # Returns
+/// `not_found` for an ID that was never handed out
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn node_names(ids: Vec<u32>) -> Result<Vec<String>, HarmonyError> {
+    with_node_ids(|map| {
+        ids.iter()
+            .map(|&id| {
+                map.name(id)
+                    .map(str::to_string)
+                    .ok_or_else(|| HarmonyError::NotFound(format!("Unknown node ID {}", id)))
+            })
+            .collect()
+    })
+}
+
+/// The module's map as JSON `{"first", "names"}`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn export_node_ids() -> String {
+    serde_json::to_string(&node_ids()).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Replace the module's map with JSON from `export_node_ids`
+///
+/// # Returns
+/// `malformed` for invalid JSON or a name listed twice; the map is
+/// unchanged
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn import_node_ids(json: &str) -> Result<(), HarmonyError> {
+    limits::check_json(json)?;
+    set_node_ids(serde_json::from_str(json)?);
+    Ok(())
+}
+
+/// Forget every name; IDs start again from 0
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn clear_node_ids() {
+    set_node_ids(NodeIdMap::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_id_map() {
+        let mut map = NodeIdMap::new(10);
+        assert_eq!(map.intern("button").unwrap(), 10);
+        assert_eq!(map.intern("icon").unwrap(), 11);
+        assert_eq!(map.intern("button").unwrap(), 10);
+        assert_eq!(map.id("icon"), Some(11));
+        assert_eq!(map.name(11), Some("icon"));
+        assert_eq!(map.name(9), None);
+        assert_eq!(map.len(), 2);
+
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"first":10,"names":["button","icon"]}"#);
+        assert_eq!(serde_json::from_str::<NodeIdMap>(&json).unwrap(), map);
+        let error = serde_json::from_str::<NodeIdMap>(r#"{"names":["a","a"]}"#).unwrap_err();
+        assert!(error.to_string().starts_with("Duplicate node name 'a'"));
+
+        let mut full = NodeIdMap::new(u32::MAX);
+        full.intern("last").unwrap();
+        assert_eq!(full.intern("over").unwrap_err().code(), "capacity_exceeded");
+    }
+
+    #[test]
+    fn test_module_map() {
+        clear_node_ids();
+        assert_eq!(
+            intern_node_ids(vec!["a".into(), "b".into(), "a".into()]).unwrap(),
+            [0, 1, 0]
+        );
+        assert_eq!(intern_node_id("c").unwrap(), 2);
+        assert_eq!(node_id("b"), Some(1));
+        assert_eq!(node_id("d"), None);
+        assert_eq!(node_names(vec![2, 0]).unwrap(), ["c", "a"]);
+        assert_eq!(node_names(vec![7]).unwrap_err().code(), "not_found");
+
+        let saved = export_node_ids();
+        clear_node_ids();
+        assert_eq!(node_name(0), None);
+        import_node_ids(&saved).unwrap();
+        assert_eq!(node_name(2).as_deref(), Some("c"));
+
+        let error = import_node_ids(r#"{"names":["x","x"]}"#).unwrap_err();
+        assert_eq!(error.code(), "malformed");
+        assert_eq!(node_ids().len(), 3);
+    }
+}
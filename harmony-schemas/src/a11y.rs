@@ -0,0 +1,298 @@
+//! Accessibility metadata and template checks
+//!
+//! `A11yMetadata` declares what a component or template must provide to
+//! assistive technology: the ARIA roles it may take, the keyboard
+//! interactions it supports and the ARIA attributes it requires. It can be
+//! attached to a `TemplateNode` or a `DesignSpecNode`.
+//!
+//! `check_template` cross-checks a template's root element against the
+//! metadata:
+//! - the role (explicit `role` attribute, else the element's implicit role)
+//!   must be one of `roles`
+//! - every required ARIA attribute (or one of its alternatives) must be
+//!   present, with an allowed value when values are restricted
+//! - templates with keyboard interactions must be focusable, natively or
+//!   through `tabindex`
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#accessibility
+
+use serde::{Deserialize, Serialize};
+
+use crate::template_node::TemplateNode;
+
+/// A key binding the component responds to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyboardInteraction {
+    /// Key or chord, e.g. "Enter", "Space", "Shift+Tab"
+    pub key: String,
+
+    /// What the key does, e.g. "Activates the button"
+    pub action: String,
+}
+
+/// An ARIA attribute the component must carry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AriaRequirement {
+    /// Attribute name, e.g. "aria-label"
+    pub attribute: String,
+
+    /// Attributes that satisfy the requirement instead, e.g. "aria-labelledby"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<String>,
+
+    /// Allowed values; empty allows any non-empty value
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_values: Vec<String>,
+}
+
+impl AriaRequirement {
+    /// Requirement for an attribute with any non-empty value
+    pub fn new(attribute: &str) -> Self {
+        Self {
+            attribute: attribute.to_string(),
+            alternatives: Vec::new(),
+            allowed_values: Vec::new(),
+        }
+    }
+}
+
+/// Accessibility requirements of a component or template
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct A11yMetadata {
+    /// Acceptable ARIA roles; empty accepts any role
+    #[serde(default)]
+    pub roles: Vec<String>,
+
+    /// Supported keyboard interactions
+    #[serde(default)]
+    pub keyboard_interactions: Vec<KeyboardInteraction>,
+
+    /// Required ARIA attributes
+    #[serde(default)]
+    pub required_aria: Vec<AriaRequirement>,
+}
+
+/// Kind of accessibility problem found in a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum A11yIssueKind {
+    RoleMismatch,
+    MissingAria,
+    InvalidAriaValue,
+    NotFocusable,
+}
+
+/// An accessibility problem found in a template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct A11yIssue {
+    pub kind: A11yIssueKind,
+    pub template_id: String,
+    pub message: String,
+}
+
+/// Implicit ARIA role of an HTML element, if it has one
+pub fn implicit_role(template: &TemplateNode) -> Option<&'static str> {
+    let attribute = |name: &str| {
+        template
+            .attributes
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| attr.value.as_str())
+    };
+    let role = match template.element_type.to_ascii_lowercase().as_str() {
+        "a" | "area" if attribute("href").is_some() => "link",
+        "article" => "article",
+        "aside" => "complementary",
+        "button" => "button",
+        "dialog" => "dialog",
+        "footer" => "contentinfo",
+        "form" => "form",
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => "heading",
+        "header" => "banner",
+        "hr" => "separator",
+        "img" => "img",
+        "input" => match attribute("type").unwrap_or("text") {
+            "button" | "submit" | "reset" | "image" => "button",
+            "checkbox" => "checkbox",
+            "radio" => "radio",
+            "range" => "slider",
+            "number" => "spinbutton",
+            "search" => "searchbox",
+            "hidden" => return None,
+            _ => "textbox",
+        },
+        "li" => "listitem",
+        "main" => "main",
+        "nav" => "navigation",
+        "ol" | "ul" => "list",
+        "progress" => "progressbar",
+        "section" => "region",
+        "select" => "combobox",
+        "table" => "table",
+        "textarea" => "textbox",
+        _ => return None,
+    };
+    Some(role)
+}
+
+fn is_focusable(template: &TemplateNode) -> bool {
+    let has = |name: &str| template.attributes.iter().any(|attr| attr.name == name);
+    if has("tabindex") {
+        return true;
+    }
+    match template.element_type.to_ascii_lowercase().as_str() {
+        "button" | "input" | "select" | "textarea" => !has("disabled"),
+        "a" | "area" => has("href"),
+        _ => template
+            .shadow_config
+            .as_ref()
+            .is_some_and(|shadow| shadow.delegates_focus),
+    }
+}
+
+/// Check a template's root element against accessibility metadata
+///
+/// # Returns
+/// Every issue found; empty when the template satisfies the metadata
+pub fn check_template(template: &TemplateNode, metadata: &A11yMetadata) -> Vec<A11yIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |kind, message: String| {
+        issues.push(A11yIssue {
+            kind,
+            template_id: template.template_id.clone(),
+            message,
+        })
+    };
+    let attribute = |name: &str| {
+        template
+            .attributes
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| attr.value.trim())
+    };
+
+    if !metadata.roles.is_empty() {
+        let role = attribute("role").or_else(|| implicit_role(template));
+        if !role.is_some_and(|role| metadata.roles.iter().any(|r| r == role)) {
+            issue(
+                A11yIssueKind::RoleMismatch,
+                format!(
+                    "Role {} is not one of [{}]",
+                    role.map_or("(none)".to_string(), |r| format!("'{}'", r)),
+                    metadata.roles.join(", ")
+                ),
+            );
+        }
+    }
+
+    for requirement in &metadata.required_aria {
+        let present = std::iter::once(&requirement.attribute)
+            .chain(&requirement.alternatives)
+            .find_map(|name| attribute(name).filter(|v| !v.is_empty()).map(|v| (name, v)));
+        match present {
+            None => {
+                let mut names = vec![requirement.attribute.as_str()];
+                names.extend(requirement.alternatives.iter().map(String::as_str));
+                issue(
+                    A11yIssueKind::MissingAria,
+                    format!("Missing required attribute {}", names.join(" or ")),
+                );
+            }
+            Some((name, value))
+                if !requirement.allowed_values.is_empty()
+                    && !requirement.allowed_values.iter().any(|v| v == value) =>
+            {
+                issue(
+                    A11yIssueKind::InvalidAriaValue,
+                    format!(
+                        "{}=\"{}\" is not one of [{}]",
+                        name,
+                        value,
+                        requirement.allowed_values.join(", ")
+                    ),
+                );
+            }
+            Some(_) => {}
+        }
+    }
+
+    if !metadata.keyboard_interactions.is_empty() && !is_focusable(template) {
+        issue(
+            A11yIssueKind::NotFocusable,
+            format!(
+                "<{}> handles keyboard input but is not focusable",
+                template.element_type
+            ),
+        );
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toggle_metadata() -> A11yMetadata {
+        A11yMetadata {
+            roles: vec!["button".to_string(), "switch".to_string()],
+            keyboard_interactions: vec![KeyboardInteraction {
+                key: "Space".to_string(),
+                action: "Toggles the state".to_string(),
+            }],
+            required_aria: vec![
+                AriaRequirement {
+                    alternatives: vec!["aria-labelledby".to_string()],
+                    ..AriaRequirement::new("aria-label")
+                },
+                AriaRequirement {
+                    allowed_values: vec!["true".to_string(), "false".to_string()],
+                    ..AriaRequirement::new("aria-pressed")
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_conforming_template() {
+        let template = TemplateNode::new("toggle".to_string(), "button".to_string())
+            .with_attribute("aria-labelledby".to_string(), "toggle-label".to_string())
+            .with_attribute("aria-pressed".to_string(), "false".to_string());
+        assert!(check_template(&template, &toggle_metadata()).is_empty());
+    }
+
+    #[test]
+    fn test_reports_each_problem() {
+        let template = TemplateNode::new("toggle".to_string(), "div".to_string())
+            .with_attribute("aria-pressed".to_string(), "yes".to_string());
+        let issues = check_template(&template, &toggle_metadata());
+        let kinds: Vec<A11yIssueKind> = issues.iter().map(|i| i.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                A11yIssueKind::RoleMismatch,
+                A11yIssueKind::MissingAria,
+                A11yIssueKind::InvalidAriaValue,
+                A11yIssueKind::NotFocusable,
+            ]
+        );
+        assert_eq!(issues[1].message, "Missing required attribute aria-label or aria-labelledby");
+
+        let fixed = template
+            .with_attribute("role".to_string(), "switch".to_string())
+            .with_attribute("tabindex".to_string(), "0".to_string());
+        let issues = check_template(&fixed, &toggle_metadata());
+        assert_eq!(issues.len(), 2);
+    }
+
+    #[test]
+    fn test_implicit_roles() {
+        let link = TemplateNode::new("l".to_string(), "a".to_string())
+            .with_attribute("href".to_string(), "/docs".to_string());
+        assert_eq!(implicit_role(&link), Some("link"));
+        let checkbox = TemplateNode::new("c".to_string(), "input".to_string())
+            .with_attribute("type".to_string(), "checkbox".to_string());
+        assert_eq!(implicit_role(&checkbox), Some("checkbox"));
+        assert_eq!(implicit_role(&TemplateNode::new("d".to_string(), "div".to_string())), None);
+    }
+}
@@ -4,10 +4,17 @@
 //! See harmony-design/DESIGN_SYSTEM.md § Component Lifecycle for usage patterns.
 
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
 use std::fmt;
+use std::str::FromStr;
+
+use crate::graph::EdgeType;
+use crate::lifecycle_states::{LifecycleState, StateMetadata};
 
 /// Component lifecycle states
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ComponentState {
     /// Initial state - component is being drafted
@@ -20,6 +27,8 @@ pub enum ComponentState {
     Implemented,
     /// Component is published and available for use
     Published,
+    /// Component is deprecated and should not be used in new work (terminal)
+    Deprecated,
 }
 
 impl fmt::Display for ComponentState {
@@ -30,26 +39,147 @@ impl fmt::Display for ComponentState {
             ComponentState::InDevelopment => write!(f, "in_development"),
             ComponentState::Implemented => write!(f, "implemented"),
             ComponentState::Published => write!(f, "published"),
+            ComponentState::Deprecated => write!(f, "deprecated"),
+        }
+    }
+}
+
+impl FromStr for ComponentState {
+    type Err = String;
+
+    /// Parses the snake_case name used in JSON
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(ComponentState::Draft),
+            "design_complete" => Ok(ComponentState::DesignComplete),
+            "in_development" => Ok(ComponentState::InDevelopment),
+            "implemented" => Ok(ComponentState::Implemented),
+            "published" => Ok(ComponentState::Published),
+            "deprecated" => Ok(ComponentState::Deprecated),
+            _ => Err(format!("Unknown component state: {}", s)),
         }
     }
 }
 
 /// Represents a state transition request
+#[cfg_attr(feature = "wasm", derive(Tsify))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StateTransition {
     pub component_id: String,
     pub from_state: ComponentState,
     pub to_state: ComponentState,
     pub reason: Option<String>,
+    /// Who made the change and why, recorded in the component history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<StateMetadata>,
+    /// Caller requesting the transition, checked against required roles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<Actor>,
+}
+
+/// Person or system performing a transition
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    pub id: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+impl Actor {
+    /// Returns true if the actor holds any of `roles`
+    pub fn has_any_role(&self, roles: &[String]) -> bool {
+        roles.iter().any(|role| self.roles.contains(role))
+    }
+}
+
+/// Why a transition was refused for lack of permission
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizationError {
+    /// Requesting actor, if one was supplied
+    pub actor_id: Option<String>,
+    /// Roles of which the actor needed at least one
+    pub required_roles: Vec<String>,
 }
 
 /// Result of a state transition attempt
+#[cfg_attr(feature = "wasm", derive(Tsify))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransitionResult {
     pub success: bool,
     pub component_id: String,
     pub new_state: Option<ComponentState>,
     pub error: Option<String>,
+    /// Guard flags the component still lacks, when a guard blocked the transition
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub unmet_guards: Vec<String>,
+    /// Dependencies that prevented publishing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub blocking_dependencies: Vec<BlockingDependency>,
+    /// Set when the actor lacked a required role
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization: Option<AuthorizationError>,
+}
+
+/// A dependency whose state prevents a component from being published
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockingDependency {
+    /// The dependency (edge target)
+    pub component_id: String,
+    /// Relationship to the dependent component
+    pub edge_type: EdgeType,
+    pub state: ComponentState,
+}
+
+/// Several transitions submitted together, applied in order
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitionBatch {
+    pub transitions: Vec<StateTransition>,
+    /// Apply nothing unless every transition succeeds
+    #[serde(default)]
+    pub all_or_nothing: bool,
+}
+
+/// Result of a batch, with one entry per submitted transition
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchTransitionResult {
+    /// True if every transition was applied
+    pub success: bool,
+    /// Number of transitions applied
+    pub applied: usize,
+    pub results: Vec<TransitionResult>,
+}
+
+impl From<ComponentState> for LifecycleState {
+    fn from(state: ComponentState) -> Self {
+        match state {
+            ComponentState::Draft => LifecycleState::Draft,
+            ComponentState::DesignComplete => LifecycleState::DesignComplete,
+            ComponentState::InDevelopment => LifecycleState::InDevelopment,
+            ComponentState::Implemented => LifecycleState::Implemented,
+            ComponentState::Published => LifecycleState::Published,
+            ComponentState::Deprecated => LifecycleState::Deprecated,
+        }
+    }
+}
+
+impl From<LifecycleState> for ComponentState {
+    fn from(state: LifecycleState) -> Self {
+        match state {
+            LifecycleState::Draft => ComponentState::Draft,
+            LifecycleState::DesignComplete => ComponentState::DesignComplete,
+            LifecycleState::InDevelopment => ComponentState::InDevelopment,
+            LifecycleState::Implemented => ComponentState::Implemented,
+            LifecycleState::Published => ComponentState::Published,
+            LifecycleState::Deprecated => ComponentState::Deprecated,
+        }
+    }
 }
 
 impl ComponentState {
@@ -69,7 +199,11 @@ impl ComponentState {
             (ComponentState::Published, ComponentState::Implemented) => true,
             
             // Same state (no-op)
-            (a, b) if a == b => true,
+            (a, b) if *a == b => true,
+            
+            // Deprecation is allowed from any live state and is final
+            (ComponentState::Deprecated, _) => false,
+            (_, ComponentState::Deprecated) => true,
             
             // All other transitions are invalid
             _ => false,
@@ -79,20 +213,30 @@ impl ComponentState {
     /// Get the next valid states from current state
     pub fn next_states(&self) -> Vec<ComponentState> {
         match self {
-            ComponentState::Draft => vec![ComponentState::DesignComplete],
+            ComponentState::Draft => vec![
+                ComponentState::DesignComplete,
+                ComponentState::Deprecated,
+            ],
             ComponentState::DesignComplete => vec![
                 ComponentState::InDevelopment,
                 ComponentState::Draft,
+                ComponentState::Deprecated,
             ],
             ComponentState::InDevelopment => vec![
                 ComponentState::Implemented,
                 ComponentState::DesignComplete,
+                ComponentState::Deprecated,
             ],
             ComponentState::Implemented => vec![
                 ComponentState::Published,
                 ComponentState::InDevelopment,
+                ComponentState::Deprecated,
+            ],
+            ComponentState::Published => vec![
+                ComponentState::Implemented,
+                ComponentState::Deprecated,
             ],
-            ComponentState::Published => vec![ComponentState::Implemented],
+            ComponentState::Deprecated => vec![],
         }
     }
 
@@ -104,8 +248,19 @@ impl ComponentState {
             ComponentState::InDevelopment,
             ComponentState::Implemented,
             ComponentState::Published,
+            ComponentState::Deprecated,
         ]
     }
+
+    /// Returns true if a published component may depend on one in this state
+    pub fn satisfies_publish_dependency(&self) -> bool {
+        !matches!(self, ComponentState::Draft | ComponentState::Deprecated)
+    }
+
+    /// Returns true once the component can no longer change state
+    pub fn is_terminal(&self) -> bool {
+        self.next_states().is_empty()
+    }
 }
 
 #[cfg(test)]
@@ -142,15 +297,59 @@ mod tests {
         assert!(ComponentState::Published.can_transition_to(ComponentState::Published));
     }
 
+    #[test]
+    fn test_parse_state() {
+        for state in ComponentState::all_states() {
+            assert_eq!(state.to_string().parse::<ComponentState>(), Ok(state));
+        }
+        assert!("archived".parse::<ComponentState>().is_err());
+    }
+
     #[test]
     fn test_next_states() {
         let draft_next = ComponentState::Draft.next_states();
-        assert_eq!(draft_next.len(), 1);
+        assert_eq!(draft_next.len(), 2);
         assert!(draft_next.contains(&ComponentState::DesignComplete));
 
         let design_next = ComponentState::DesignComplete.next_states();
-        assert_eq!(design_next.len(), 2);
+        assert_eq!(design_next.len(), 3);
         assert!(design_next.contains(&ComponentState::InDevelopment));
         assert!(design_next.contains(&ComponentState::Draft));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_deprecation() {
+        for state in ComponentState::all_states() {
+            assert!(state.can_transition_to(ComponentState::Deprecated));
+            assert_eq!(
+                state.next_states().contains(&ComponentState::Deprecated),
+                state != ComponentState::Deprecated
+            );
+        }
+        assert!(ComponentState::Deprecated.is_terminal());
+        assert!(!ComponentState::Deprecated.can_transition_to(ComponentState::Draft));
+        assert!(!ComponentState::Published.is_terminal());
+    }
+
+    #[test]
+    fn test_lifecycle_state_mapping() {
+        for state in ComponentState::all_states() {
+            assert_eq!(ComponentState::from(LifecycleState::from(state)), state);
+        }
+        assert_eq!(LifecycleState::all().len(), ComponentState::all_states().len());
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_typescript_declarations() {
+        assert_eq!(
+            ComponentState::DECL,
+            "export type ComponentState = \"draft\" | \"design_complete\" | \"in_development\" \
+             | \"implemented\" | \"published\" | \"deprecated\";"
+        );
+        // Fields skipped when empty or None are optional
+        assert!(TransitionResult::DECL.contains("    new_state: ComponentState | null;\n"));
+        assert!(TransitionResult::DECL.contains("    unmet_guards?: string[];\n"));
+        assert!(TransitionResult::DECL.contains("    authorization?: AuthorizationError;\n"));
+    }
+}
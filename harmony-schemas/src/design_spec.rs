@@ -0,0 +1,292 @@
+//! DesignSpecNode: the design specification a component implements
+//!
+//! Target of `implements_design` edges. A spec lists the states and variants
+//! a component must support, the props contract it exposes, its
+//! accessibility requirements and, optionally, the Figma frame it was drawn
+//! in. Implementation completeness is measured against the spec's states
+//! and variants.
+//!
+//! Specs carry a version, bumped when the contract changes; implementations
+//! record the version they were built against.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#implementation-tracking
+
+use serde::{Deserialize, Serialize};
+
+use crate::a11y::A11yMetadata;
+use crate::error::SchemaError;
+
+fn default_version() -> u32 {
+    1
+}
+
+/// A prop the implementation must accept
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PropContract {
+    /// Prop (attribute) name
+    pub name: String,
+
+    /// Type name, e.g. "string", "boolean", "'sm' | 'md' | 'lg'"
+    pub prop_type: String,
+
+    /// Whether the prop must be provided
+    #[serde(default)]
+    pub required: bool,
+
+    /// Default value, when the prop is optional
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+
+    /// What the prop controls
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// An accessibility requirement from the spec
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct A11yRequirement {
+    /// Requirement text, e.g. "Focus ring visible in every state"
+    pub description: String,
+
+    /// WCAG success criterion, e.g. "2.4.7"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wcag_criterion: Option<String>,
+}
+
+/// Location of the spec in Figma
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FigmaReference {
+    /// Figma file key
+    pub file_key: String,
+
+    /// Node ID of the frame or component, e.g. "12:345"
+    pub node_id: String,
+
+    /// Direct link to the node
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Design specification node
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DesignSpecNode {
+    /// Unique identifier, e.g. "spec:button"
+    pub id: String,
+
+    /// Component name the spec describes
+    pub name: String,
+
+    /// Contract version, starting at 1
+    #[serde(default = "default_version")]
+    pub version: u32,
+
+    /// Interaction states, e.g. "default", "hover", "disabled"
+    #[serde(default)]
+    pub states: Vec<String>,
+
+    /// Variants, e.g. "primary", "secondary", "compact"
+    #[serde(default)]
+    pub variants: Vec<String>,
+
+    /// Props contract
+    #[serde(default)]
+    pub props: Vec<PropContract>,
+
+    /// Accessibility requirements
+    #[serde(default)]
+    pub a11y_requirements: Vec<A11yRequirement>,
+
+    /// Machine-checkable accessibility metadata for implementations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub a11y: Option<A11yMetadata>,
+
+    /// Figma source, if the spec was drawn there
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub figma: Option<FigmaReference>,
+}
+
+impl DesignSpecNode {
+    /// Create an empty spec for a component
+    pub fn new(id: String, name: String) -> Self {
+        Self {
+            id,
+            name,
+            version: default_version(),
+            states: Vec::new(),
+            variants: Vec::new(),
+            props: Vec::new(),
+            a11y_requirements: Vec::new(),
+            a11y: None,
+            figma: None,
+        }
+    }
+
+    /// Set the contract version
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Increment the version after a contract change
+    pub fn bump_version(&mut self) -> u32 {
+        self.version += 1;
+        self.version
+    }
+
+    /// Set the interaction states
+    pub fn with_states(mut self, states: Vec<String>) -> Self {
+        self.states = states;
+        self
+    }
+
+    /// Set the variants
+    pub fn with_variants(mut self, variants: Vec<String>) -> Self {
+        self.variants = variants;
+        self
+    }
+
+    /// Add a prop to the contract
+    pub fn with_prop(mut self, prop: PropContract) -> Self {
+        self.props.push(prop);
+        self
+    }
+
+    /// Add an accessibility requirement
+    pub fn with_a11y_requirement(mut self, requirement: A11yRequirement) -> Self {
+        self.a11y_requirements.push(requirement);
+        self
+    }
+
+    /// Attach machine-checkable accessibility metadata
+    pub fn with_a11y(mut self, metadata: A11yMetadata) -> Self {
+        self.a11y = Some(metadata);
+        self
+    }
+
+    /// Set the Figma source
+    pub fn with_figma(mut self, figma: FigmaReference) -> Self {
+        self.figma = Some(figma);
+        self
+    }
+
+    /// Spec states missing from `implemented`
+    pub fn missing_states<'a>(&'a self, implemented: &[String]) -> Vec<&'a str> {
+        missing(&self.states, implemented)
+    }
+
+    /// Spec variants missing from `implemented`
+    pub fn missing_variants<'a>(&'a self, implemented: &[String]) -> Vec<&'a str> {
+        missing(&self.variants, implemented)
+    }
+
+    /// Fraction of the spec's states and variants that are implemented
+    ///
+    /// Entries not in the spec are ignored. A spec without states or
+    /// variants is trivially complete (1.0).
+    pub fn completeness(&self, implemented_states: &[String], implemented_variants: &[String]) -> f32 {
+        let total = self.states.len() + self.variants.len();
+        if total == 0 {
+            return 1.0;
+        }
+        let missing = self.missing_states(implemented_states).len()
+            + self.missing_variants(implemented_variants).len();
+        (total - missing) as f32 / total as f32
+    }
+
+    /// Check the spec for empty or duplicate entries
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        if self.id.is_empty() {
+            return Err(SchemaError::EmptyValue {
+                path: "id".to_string(),
+            });
+        }
+        for (field, names) in [
+            ("states", self.states.iter().collect::<Vec<_>>()),
+            ("variants", self.variants.iter().collect()),
+            ("props", self.props.iter().map(|p| &p.name).collect()),
+        ] {
+            for (i, name) in names.iter().enumerate() {
+                if name.is_empty() {
+                    return Err(SchemaError::EmptyValue {
+                        path: format!("{}[{}]", field, i),
+                    });
+                }
+                if names[..i].contains(name) {
+                    return Err(SchemaError::DuplicateEntry {
+                        path: field.to_string(),
+                        name: name.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn missing<'a>(required: &'a [String], implemented: &[String]) -> Vec<&'a str> {
+    required
+        .iter()
+        .filter(|name| !implemented.contains(name))
+        .map(String::as_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn button_spec() -> DesignSpecNode {
+        DesignSpecNode::new("spec:button".to_string(), "Button".to_string())
+            .with_states(strings(&["default", "hover", "pressed", "disabled"]))
+            .with_variants(strings(&["primary", "secondary"]))
+            .with_prop(PropContract {
+                name: "label".to_string(),
+                prop_type: "string".to_string(),
+                required: true,
+                default: None,
+                description: None,
+            })
+    }
+
+    #[test]
+    fn test_completeness_against_spec() {
+        let spec = button_spec();
+        let states = strings(&["default", "hover", "focus"]);
+        let variants = strings(&["primary", "secondary"]);
+
+        assert_eq!(spec.missing_states(&states), vec!["pressed", "disabled"]);
+        assert!(spec.missing_variants(&variants).is_empty());
+        assert_eq!(spec.completeness(&states, &variants), 4.0 / 6.0);
+
+        let empty = DesignSpecNode::new("spec:divider".to_string(), "Divider".to_string());
+        assert_eq!(empty.completeness(&[], &[]), 1.0);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(button_spec().validate().is_ok());
+        let duplicate = button_spec().with_variants(strings(&["primary", "primary"]));
+        assert_eq!(
+            duplicate.validate(),
+            Err(SchemaError::DuplicateEntry {
+                path: "variants".to_string(),
+                name: "primary".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_serialization_defaults() {
+        let spec: DesignSpecNode = serde_json::from_str(
+            r#"{"id":"spec:card","name":"Card","figma":{"file_key":"abc","node_id":"1:2"}}"#,
+        )
+        .unwrap();
+        assert!(spec.states.is_empty());
+        assert_eq!(spec.version, 1);
+        assert_eq!(spec.figma.unwrap().node_id, "1:2");
+    }
+}
@@ -0,0 +1,291 @@
+//! Structured validation errors
+//!
+//! Lifecycle, template, edge and message catalog validation report a
+//! `SchemaError` rather than a bare message, so tooling can branch on the
+//! kind of problem. Each error has:
+//! - a stable machine-readable `code()` (snake_case, also the `code` tag in
+//!   its JSON form)
+//! - a `path()` to the offending field, dotted from the validated value
+//! - a `severity()`; warnings flag problems that do not make the value
+//!   unusable, such as accessibility issues
+//!
+//! `Display` gives the human-readable message.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::a11y::{A11yIssue, A11yIssueKind};
+use crate::graph::PropertyType;
+
+/// How serious a validation problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A validation problem found in schema data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum SchemaError {
+    /// Lifecycle state change not allowed by the transition table
+    InvalidTransition { from: String, to: String },
+
+    /// A template ID that is not among the known templates
+    UnknownTemplate { template_id: String },
+
+    /// Templates that contain themselves, listed from the first repeat
+    TemplateCycle { cycle: Vec<String> },
+
+    /// An element or attribute name that is not a valid HTML name
+    InvalidName { path: String, name: String },
+
+    /// A void element (`input`, `img`, ...) with children or slots
+    VoidElementContent {
+        template_id: String,
+        element: String,
+    },
+
+    /// A required slot with neither content nor fallback
+    MissingRequiredSlot {
+        template_id: String,
+        slot_name: String,
+    },
+
+    /// An identifier or name that must not be empty
+    EmptyValue { path: String },
+
+    /// A name that must be unique appears more than once
+    DuplicateEntry { path: String, name: String },
+
+    /// An accessibility requirement the template does not meet
+    Accessibility {
+        template_id: String,
+        kind: A11yIssueKind,
+        message: String,
+    },
+
+    /// A property the edge type does not declare
+    UndeclaredProperty { edge_type: String, property: String },
+
+    /// A property value of the wrong type
+    PropertyTypeMismatch {
+        edge_type: String,
+        property: String,
+        expected: PropertyType,
+        actual: PropertyType,
+    },
+
+    /// A required property that is absent
+    MissingRequiredProperty { edge_type: String, property: String },
+
+    /// An edge type that is not registered
+    UnknownEdgeType { edge_type: String },
+
+    /// An edge type id that is already registered
+    DuplicateEdgeType { edge_type: String },
+
+    /// A reverse declaration the registry cannot accept
+    InvalidReverse {
+        edge_type: String,
+        reverse: String,
+        reason: String,
+    },
+
+    /// A locale code the message catalog does not declare
+    UnknownLocale { locale: String },
+}
+
+impl SchemaError {
+    /// Stable machine-readable code
+    pub fn code(&self) -> &'static str {
+        match self {
+            SchemaError::InvalidTransition { .. } => "invalid_transition",
+            SchemaError::UnknownTemplate { .. } => "unknown_template",
+            SchemaError::TemplateCycle { .. } => "template_cycle",
+            SchemaError::InvalidName { .. } => "invalid_name",
+            SchemaError::VoidElementContent { .. } => "void_element_content",
+            SchemaError::MissingRequiredSlot { .. } => "missing_required_slot",
+            SchemaError::EmptyValue { .. } => "empty_value",
+            SchemaError::DuplicateEntry { .. } => "duplicate_entry",
+            SchemaError::Accessibility { .. } => "accessibility",
+            SchemaError::UndeclaredProperty { .. } => "undeclared_property",
+            SchemaError::PropertyTypeMismatch { .. } => "property_type_mismatch",
+            SchemaError::MissingRequiredProperty { .. } => "missing_required_property",
+            SchemaError::UnknownEdgeType { .. } => "unknown_edge_type",
+            SchemaError::DuplicateEdgeType { .. } => "duplicate_edge_type",
+            SchemaError::InvalidReverse { .. } => "invalid_reverse",
+            SchemaError::UnknownLocale { .. } => "unknown_locale",
+        }
+    }
+
+    /// Dotted path to the offending field
+    pub fn path(&self) -> String {
+        match self {
+            SchemaError::InvalidTransition { .. } => "state".to_string(),
+            SchemaError::UnknownTemplate { template_id } => template_id.clone(),
+            SchemaError::TemplateCycle { cycle } => {
+                format!("{}.children", cycle.first().map_or("", String::as_str))
+            }
+            SchemaError::InvalidName { path, .. }
+            | SchemaError::EmptyValue { path }
+            | SchemaError::DuplicateEntry { path, .. } => path.clone(),
+            SchemaError::VoidElementContent { template_id, .. } => {
+                format!("{}.children", template_id)
+            }
+            SchemaError::MissingRequiredSlot {
+                template_id,
+                slot_name,
+            } => {
+                format!("{}.slots.{}", template_id, slot_name)
+            }
+            SchemaError::Accessibility { template_id, .. } => {
+                format!("{}.attributes", template_id)
+            }
+            SchemaError::UndeclaredProperty { property, .. }
+            | SchemaError::PropertyTypeMismatch { property, .. }
+            | SchemaError::MissingRequiredProperty { property, .. } => {
+                format!("metadata.properties.{}", property)
+            }
+            SchemaError::UnknownEdgeType { .. } => "edge_type".to_string(),
+            SchemaError::DuplicateEdgeType { .. } => "id".to_string(),
+            SchemaError::InvalidReverse { .. } => "reverse".to_string(),
+            SchemaError::UnknownLocale { .. } => "locales".to_string(),
+        }
+    }
+
+    /// How serious the problem is
+    pub fn severity(&self) -> Severity {
+        match self {
+            SchemaError::Accessibility { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::InvalidTransition { from, to } => {
+                write!(f, "Invalid transition from {} to {}", from, to)
+            }
+            SchemaError::UnknownTemplate { template_id } => {
+                write!(f, "Unknown template: {}", template_id)
+            }
+            SchemaError::TemplateCycle { cycle } => {
+                write!(f, "Template cycle: {}", cycle.join(" -> "))
+            }
+            SchemaError::InvalidName { path, name } => {
+                write!(f, "Invalid name '{}' at {}", name, path)
+            }
+            SchemaError::VoidElementContent {
+                template_id,
+                element,
+            } => write!(
+                f,
+                "Void element <{}> in {} cannot have content",
+                element, template_id
+            ),
+            SchemaError::MissingRequiredSlot {
+                template_id,
+                slot_name,
+            } => write!(
+                f,
+                "Required slot '{}' of {} has no content",
+                slot_name, template_id
+            ),
+            SchemaError::EmptyValue { path } => write!(f, "{} must not be empty", path),
+            SchemaError::DuplicateEntry { path, name } => {
+                write!(f, "Duplicate '{}' in {}", name, path)
+            }
+            SchemaError::Accessibility { message, .. } => write!(f, "{}", message),
+            SchemaError::UndeclaredProperty {
+                edge_type,
+                property,
+            } => write!(
+                f,
+                "Property '{}' is not declared for {} edges",
+                property, edge_type
+            ),
+            SchemaError::PropertyTypeMismatch {
+                edge_type,
+                property,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Property '{}' of {} edges must be {:?}, got {:?}",
+                property, edge_type, expected, actual
+            ),
+            SchemaError::MissingRequiredProperty {
+                edge_type,
+                property,
+            } => write!(
+                f,
+                "Missing required property '{}' for {} edges",
+                property, edge_type
+            ),
+            SchemaError::UnknownEdgeType { edge_type } => {
+                write!(f, "Unknown edge type: {}", edge_type)
+            }
+            SchemaError::DuplicateEdgeType { edge_type } => {
+                write!(f, "Edge type already registered: {}", edge_type)
+            }
+            SchemaError::InvalidReverse {
+                edge_type,
+                reverse,
+                reason,
+            } => write!(
+                f,
+                "Invalid reverse '{}' for {}: {}",
+                reverse, edge_type, reason
+            ),
+            SchemaError::UnknownLocale { locale } => write!(f, "Unknown locale: {}", locale),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<A11yIssue> for SchemaError {
+    fn from(issue: A11yIssue) -> Self {
+        SchemaError::Accessibility {
+            template_id: issue.template_id,
+            kind: issue.kind,
+            message: issue.message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_paths_and_severity() {
+        let error = SchemaError::MissingRequiredSlot {
+            template_id: "card".to_string(),
+            slot_name: "footer".to_string(),
+        };
+        assert_eq!(error.code(), "missing_required_slot");
+        assert_eq!(error.path(), "card.slots.footer");
+        assert_eq!(error.severity(), Severity::Error);
+        assert_eq!(
+            error.to_string(),
+            "Required slot 'footer' of card has no content"
+        );
+
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], error.code());
+
+        let warning = SchemaError::from(A11yIssue {
+            kind: A11yIssueKind::MissingAria,
+            template_id: "icon-button".to_string(),
+            message: "Missing required attribute aria-label".to_string(),
+        });
+        assert_eq!(warning.severity(), Severity::Warning);
+        assert_eq!(warning.path(), "icon-button.attributes");
+    }
+}
@@ -0,0 +1,526 @@
+//! Figma import mapping
+//!
+//! Converts Figma export JSON into design graph structures:
+//! - `COMPONENT_SET` and standalone `COMPONENT` nodes become
+//!   `DesignSpecNode`s. Variant properties named "State" give the spec's
+//!   states, other variant properties its variants; component properties
+//!   become the props contract (Figma's `#id` suffixes are dropped).
+//! - every `COMPONENT` subtree becomes `TemplateNode`s with template IDs
+//!   `figma:<node id>`. Text nodes render their characters as default slot
+//!   fallback, instances reference their main component's template, and
+//!   vector shapes are skipped.
+//! - fill and text styles become tokens in one `TokenSet`, at paths derived
+//!   from the style name ("Color/Primary 500" -> `color.primary-500`).
+//!
+//! Input follows the Figma REST API node format (camelCase). Styles carry
+//! their resolved values, as written by the Harmony Figma exporter; the REST
+//! `styles` map alone only holds style names.
+//!
+//! Nodes and styles that cannot be mapped are reported in
+//! `FigmaImport::skipped` rather than failing the import.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#figma-import
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::design_spec::{DesignSpecNode, FigmaReference, PropContract};
+use crate::template_node::{SlotDefinition, TemplateNode};
+use crate::theme::{Token, TokenSet};
+
+/// Variant property whose values are interaction states
+const STATE_PROPERTY: &str = "state";
+
+/// Node types drawn as vector graphics, which have no template equivalent
+const VECTOR_TYPES: &[&str] = &[
+    "VECTOR",
+    "BOOLEAN_OPERATION",
+    "STAR",
+    "LINE",
+    "ELLIPSE",
+    "REGULAR_POLYGON",
+];
+
+/// Exported Figma file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FigmaFile {
+    pub name: String,
+    pub document: FigmaNode,
+    #[serde(default)]
+    pub styles: Vec<FigmaStyle>,
+}
+
+/// A node of the Figma document tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaNode {
+    pub id: String,
+    pub name: String,
+
+    /// Figma node type, e.g. "FRAME", "COMPONENT", "TEXT"
+    #[serde(rename = "type")]
+    pub node_type: String,
+
+    #[serde(default)]
+    pub children: Vec<FigmaNode>,
+
+    /// Text content of `TEXT` nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub characters: Option<String>,
+
+    /// Main component of `INSTANCE` nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component_id: Option<String>,
+
+    /// Properties of `COMPONENT_SET` and standalone `COMPONENT` nodes
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub component_property_definitions: BTreeMap<String, FigmaComponentProperty>,
+}
+
+/// Kind of a Figma component property
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FigmaPropertyType {
+    Boolean,
+    Text,
+    InstanceSwap,
+    Variant,
+}
+
+/// A component property definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaComponentProperty {
+    #[serde(rename = "type")]
+    pub property_type: FigmaPropertyType,
+
+    #[serde(default)]
+    pub default_value: Value,
+
+    /// Options of `VARIANT` properties
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variant_options: Vec<String>,
+}
+
+/// Kind of a Figma style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FigmaStyleType {
+    Fill,
+    Text,
+    Effect,
+    Grid,
+}
+
+/// RGBA color with channels in 0..=1
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FigmaColor {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    #[serde(default = "opaque")]
+    pub a: f64,
+}
+
+fn opaque() -> f64 {
+    1.0
+}
+
+impl FigmaColor {
+    /// CSS hex notation, with an alpha pair only when not opaque
+    pub fn to_hex(&self) -> String {
+        let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let mut hex = format!(
+            "#{:02x}{:02x}{:02x}",
+            channel(self.r),
+            channel(self.g),
+            channel(self.b)
+        );
+        if channel(self.a) < 255 {
+            hex.push_str(&format!("{:02x}", channel(self.a)));
+        }
+        hex
+    }
+}
+
+/// A paint of a fill style
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FigmaPaint {
+    /// Paint type, e.g. "SOLID", "GRADIENT_LINEAR", "IMAGE"
+    #[serde(rename = "type")]
+    pub paint_type: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<FigmaColor>,
+
+    #[serde(default = "opaque")]
+    pub opacity: f64,
+}
+
+/// Type settings of a text style
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaTypeStyle {
+    pub font_family: String,
+    pub font_weight: f64,
+    pub font_size: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_height_px: Option<f64>,
+}
+
+/// A named style with its resolved values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaStyle {
+    pub key: String,
+    pub name: String,
+    pub style_type: FigmaStyleType,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+
+    /// Paints of `FILL` styles
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fills: Vec<FigmaPaint>,
+
+    /// Settings of `TEXT` styles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<FigmaTypeStyle>,
+}
+
+/// A node or style left out of an import
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedItem {
+    /// Node ID or style key
+    pub id: String,
+    pub reason: String,
+}
+
+/// Design graph structures hydrated from a Figma file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FigmaImport {
+    pub specs: Vec<DesignSpecNode>,
+    pub templates: Vec<TemplateNode>,
+    pub tokens: TokenSet,
+    #[serde(default)]
+    pub skipped: Vec<SkippedItem>,
+}
+
+/// Template ID for a Figma node
+pub fn template_id(node_id: &str) -> String {
+    format!("figma:{}", node_id)
+}
+
+/// Lowercase, hyphen-separated form of a Figma name
+fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Token path for a style name: "/" separates groups
+pub fn token_path(style_name: &str) -> String {
+    style_name
+        .split('/')
+        .map(slug)
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Prop name for a component property, without Figma's "#id" suffix
+fn prop_name(property_name: &str) -> String {
+    slug(property_name.split('#').next().unwrap_or_default())
+}
+
+/// Import a Figma file
+///
+/// # Arguments
+/// * `file_key` - Key of the Figma file, recorded on every spec
+pub fn import_file(file: &FigmaFile, file_key: &str) -> FigmaImport {
+    let mut import = FigmaImport {
+        specs: Vec::new(),
+        templates: Vec::new(),
+        tokens: TokenSet::new(format!("figma:{}", slug(&file.name))),
+        skipped: Vec::new(),
+    };
+    collect_components(&file.document, file_key, &mut import);
+    for style in &file.styles {
+        add_style_tokens(style, &mut import);
+    }
+    import
+}
+
+fn collect_components(node: &FigmaNode, file_key: &str, import: &mut FigmaImport) {
+    match node.node_type.as_str() {
+        "COMPONENT_SET" => {
+            import.specs.push(spec_from_node(node, file_key));
+            for variant in &node.children {
+                add_templates(variant, import);
+            }
+        }
+        "COMPONENT" => {
+            import.specs.push(spec_from_node(node, file_key));
+            add_templates(node, import);
+        }
+        _ => {
+            for child in &node.children {
+                collect_components(child, file_key, import);
+            }
+        }
+    }
+}
+
+/// Convert a `COMPONENT_SET` or `COMPONENT` node into a design spec
+pub fn spec_from_node(node: &FigmaNode, file_key: &str) -> DesignSpecNode {
+    let mut spec = DesignSpecNode::new(format!("spec:{}", slug(&node.name)), node.name.clone())
+        .with_figma(FigmaReference {
+            file_key: file_key.to_string(),
+            node_id: node.id.clone(),
+            url: None,
+        });
+    for (name, property) in &node.component_property_definitions {
+        let name = prop_name(name);
+        if property.property_type == FigmaPropertyType::Variant && name == STATE_PROPERTY {
+            spec.states
+                .extend(property.variant_options.iter().map(|o| slug(o)));
+            continue;
+        }
+        let prop_type = match property.property_type {
+            FigmaPropertyType::Boolean => "boolean".to_string(),
+            FigmaPropertyType::Text | FigmaPropertyType::InstanceSwap => "string".to_string(),
+            FigmaPropertyType::Variant => {
+                spec.variants
+                    .extend(property.variant_options.iter().map(|o| slug(o)));
+                property
+                    .variant_options
+                    .iter()
+                    .map(|o| format!("'{}'", slug(o)))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            }
+        };
+        let default = match (&property.default_value, property.property_type) {
+            (Value::Null, _) => None,
+            (Value::String(option), FigmaPropertyType::Variant) => {
+                Some(Value::String(slug(option)))
+            }
+            (value, _) => Some(value.clone()),
+        };
+        spec = spec.with_prop(PropContract {
+            name,
+            prop_type,
+            required: false,
+            default,
+            description: None,
+        });
+    }
+    spec
+}
+
+/// Convert a node subtree into templates, appending them to the import
+///
+/// Returns the node's template ID, or None if the node was skipped.
+fn add_templates(node: &FigmaNode, import: &mut FigmaImport) -> Option<String> {
+    if node.node_type == "INSTANCE" {
+        return match &node.component_id {
+            Some(component_id) => Some(template_id(component_id)),
+            None => {
+                import.skipped.push(SkippedItem {
+                    id: node.id.clone(),
+                    reason: "Instance without componentId".to_string(),
+                });
+                None
+            }
+        };
+    }
+    if VECTOR_TYPES.contains(&node.node_type.as_str()) {
+        import.skipped.push(SkippedItem {
+            id: node.id.clone(),
+            reason: format!("{} nodes are not imported", node.node_type),
+        });
+        return None;
+    }
+
+    let id = template_id(&node.id);
+    let element_type = if node.node_type == "TEXT" {
+        "span"
+    } else {
+        "div"
+    };
+    let mut template = TemplateNode::new(id.clone(), element_type.to_string())
+        .with_attribute("class".to_string(), slug(&node.name))
+        .with_attribute("data-figma-node".to_string(), node.id.clone());
+    if let Some(characters) = &node.characters {
+        template = template.with_slot(SlotDefinition {
+            slot_name: String::new(),
+            fallback_content: Some(characters.clone()),
+            allowed_types: Vec::new(),
+            required: false,
+        });
+    }
+    // Reserve the parent's position so templates stay in document order
+    let index = import.templates.len();
+    import.templates.push(template);
+    let children: Vec<String> = node
+        .children
+        .iter()
+        .filter_map(|child| add_templates(child, import))
+        .collect();
+    import.templates[index].children = children;
+    Some(id)
+}
+
+fn add_style_tokens(style: &FigmaStyle, import: &mut FigmaImport) {
+    let path = token_path(&style.name);
+    let description = (!style.description.is_empty()).then(|| style.description.clone());
+    let mut add = |path: String, value: Value, token_type: &str| {
+        import.tokens.tokens.insert(
+            path,
+            Token {
+                value,
+                token_type: Some(token_type.to_string()),
+                description: description.clone(),
+            },
+        );
+    };
+    let skip_reason = match (style.style_type, style.fills.as_slice(), &style.style) {
+        (FigmaStyleType::Fill, [paint], _) if paint.paint_type == "SOLID" => match paint.color {
+            Some(color) => {
+                let color = FigmaColor {
+                    a: color.a * paint.opacity,
+                    ..color
+                };
+                add(path, Value::String(color.to_hex()), "color");
+                None
+            }
+            None => Some("Solid paint without color"),
+        },
+        (FigmaStyleType::Fill, _, _) => Some("Only single solid fills are imported"),
+        (FigmaStyleType::Text, _, Some(text)) => {
+            add(
+                format!("{}.font-family", path),
+                Value::String(text.font_family.clone()),
+                "fontFamily",
+            );
+            add(
+                format!("{}.font-weight", path),
+                Value::from(text.font_weight),
+                "fontWeight",
+            );
+            add(
+                format!("{}.font-size", path),
+                Value::String(format!("{}px", text.font_size)),
+                "dimension",
+            );
+            if let Some(line_height) = text.line_height_px {
+                add(
+                    format!("{}.line-height", path),
+                    Value::String(format!("{}px", line_height)),
+                    "dimension",
+                );
+            }
+            None
+        }
+        (FigmaStyleType::Text, _, None) => Some("Text style without type settings"),
+        (FigmaStyleType::Effect | FigmaStyleType::Grid, _, _) => {
+            Some("Effect and grid styles are not imported")
+        }
+    };
+    if let Some(reason) = skip_reason {
+        import.skipped.push(SkippedItem {
+            id: style.key.clone(),
+            reason: reason.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template_render::TemplateRenderer;
+    use serde_json::json;
+
+    fn file() -> FigmaFile {
+        serde_json::from_value(json!({
+            "name": "Harmony Components",
+            "document": {"id": "0:0", "name": "Document", "type": "DOCUMENT", "children": [
+                {"id": "1:0", "name": "Page", "type": "CANVAS", "children": [
+                    {"id": "2:0", "name": "Icon", "type": "COMPONENT", "children": [
+                        {"id": "2:1", "name": "Glyph", "type": "VECTOR"}
+                    ]},
+                    {
+                        "id": "3:0", "name": "Button", "type": "COMPONENT_SET",
+                        "componentPropertyDefinitions": {
+                            "State": {"type": "VARIANT", "defaultValue": "Default", "variantOptions": ["Default", "Hover"]},
+                            "Variant": {"type": "VARIANT", "defaultValue": "Primary", "variantOptions": ["Primary", "Secondary"]},
+                            "Label#12:0": {"type": "TEXT", "defaultValue": "Button"},
+                            "Show icon#12:1": {"type": "BOOLEAN", "defaultValue": true}
+                        },
+                        "children": [
+                            {"id": "3:1", "name": "State=Default, Variant=Primary", "type": "COMPONENT", "children": [
+                                {"id": "3:2", "name": "Icon", "type": "INSTANCE", "componentId": "2:0"},
+                                {"id": "3:3", "name": "Label", "type": "TEXT", "characters": "Button"}
+                            ]}
+                        ]
+                    }
+                ]}
+            ]},
+            "styles": [
+                {"key": "s1", "name": "Color/Primary 500", "styleType": "FILL", "description": "Brand",
+                 "fills": [{"type": "SOLID", "color": {"r": 0.0, "g": 0.4, "b": 1.0}, "opacity": 0.5}]},
+                {"key": "s2", "name": "Type/Body", "styleType": "TEXT",
+                 "style": {"fontFamily": "Inter", "fontWeight": 400, "fontSize": 16, "lineHeightPx": 24}},
+                {"key": "s3", "name": "Shadow/Card", "styleType": "EFFECT"}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_component_set_to_spec() {
+        let import = import_file(&file(), "abc123");
+        let button = import.specs.iter().find(|s| s.id == "spec:button").unwrap();
+        assert_eq!(button.states, vec!["default", "hover"]);
+        assert_eq!(button.variants, vec!["primary", "secondary"]);
+        assert_eq!(button.figma.as_ref().unwrap().node_id, "3:0");
+        let names: Vec<&str> = button.props.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["label", "show-icon", "variant"]);
+        assert_eq!(button.props[2].prop_type, "'primary' | 'secondary'");
+        assert_eq!(button.props[2].default, Some(json!("primary")));
+        assert!(button.validate().is_ok());
+    }
+
+    #[test]
+    fn test_templates_render() {
+        let import = import_file(&file(), "abc123");
+        let html = TemplateRenderer::new(&import.templates)
+            .render("figma:3:1")
+            .unwrap();
+        assert_eq!(
+            html,
+            "<div class=\"state-default-variant-primary\" data-figma-node=\"3:1\">\
+             <div class=\"icon\" data-figma-node=\"2:0\"></div>\
+             <span class=\"label\" data-figma-node=\"3:3\">Button</span></div>"
+        );
+        assert_eq!(import.skipped[0].id, "2:1");
+    }
+
+    #[test]
+    fn test_styles_to_tokens() {
+        let import = import_file(&file(), "abc123");
+        let tokens = &import.tokens.tokens;
+        assert_eq!(import.tokens.id, "figma:harmony-components");
+        assert_eq!(tokens["color.primary-500"].value, json!("#0066ff80"));
+        assert_eq!(
+            tokens["color.primary-500"].description.as_deref(),
+            Some("Brand")
+        );
+        assert_eq!(tokens["type.body.font-size"].value, json!("16px"));
+        assert_eq!(tokens["type.body.line-height"].value, json!("24px"));
+        assert!(import.skipped.iter().any(|s| s.id == "s3"));
+    }
+}
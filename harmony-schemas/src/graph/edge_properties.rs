@@ -0,0 +1,216 @@
+//! Typed edge properties
+//!
+//! Each edge type declares the properties its edges may carry. Values are
+//! typed (`PropertyValue`) and checked against the declaration when an edge
+//! is built with `Edge::try_with_metadata` or checked with `Edge::validate`:
+//! undeclared properties, wrong value types and missing required
+//! properties are rejected.
+//!
+//! Built-in declarations:
+//! - uses_token: css_property (string)
+//! - composes_of / used_by: slot (string), order (number)
+//! - implements_design: completeness (number), implemented_states (string list)
+//! - variant_of / has_variant: axis (string), e.g. "theme", "density"
+//! - replaces / replaced_by: since_version (string)
+//! - documents / documented_by: section (string)
+//!
+//! Custom edge types declare theirs on their `EdgeTypeDefinition`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+use super::edge_types::EdgeType;
+use crate::error::SchemaError;
+
+/// Type of an edge property
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PropertyType {
+    String,
+    Number,
+    Boolean,
+    StringList,
+}
+
+/// A property value
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PropertyValue {
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    StringList(Vec<String>),
+}
+
+impl PropertyValue {
+    /// The type of this value
+    pub fn property_type(&self) -> PropertyType {
+        match self {
+            PropertyValue::Boolean(_) => PropertyType::Boolean,
+            PropertyValue::Number(_) => PropertyType::Number,
+            PropertyValue::String(_) => PropertyType::String,
+            PropertyValue::StringList(_) => PropertyType::StringList,
+        }
+    }
+}
+
+impl From<&str> for PropertyValue {
+    fn from(value: &str) -> Self {
+        PropertyValue::String(value.to_string())
+    }
+}
+
+impl From<f64> for PropertyValue {
+    fn from(value: f64) -> Self {
+        PropertyValue::Number(value)
+    }
+}
+
+impl From<bool> for PropertyValue {
+    fn from(value: bool) -> Self {
+        PropertyValue::Boolean(value)
+    }
+}
+
+impl From<Vec<String>> for PropertyValue {
+    fn from(value: Vec<String>) -> Self {
+        PropertyValue::StringList(value)
+    }
+}
+
+/// Property bag carried by an edge
+#[cfg_attr(feature = "wasm", tsify::declare)]
+pub type EdgeProperties = BTreeMap<String, PropertyValue>;
+
+/// Declaration of one property an edge type accepts
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropertySpec {
+    pub name: String,
+    pub property_type: PropertyType,
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl PropertySpec {
+    /// Declare an optional property
+    pub fn optional(name: &str, property_type: PropertyType) -> Self {
+        Self {
+            name: name.to_string(),
+            property_type,
+            required: false,
+        }
+    }
+
+    /// Declare a required property
+    pub fn required(name: &str, property_type: PropertyType) -> Self {
+        Self {
+            required: true,
+            ..Self::optional(name, property_type)
+        }
+    }
+}
+
+impl EdgeType {
+    /// Properties edges of this type may carry
+    pub fn property_specs(&self) -> Vec<PropertySpec> {
+        use PropertyType::*;
+        let specs: &[(&str, PropertyType)] = match self {
+            EdgeType::UsesToken => &[("css_property", String)],
+            EdgeType::ComposesOf | EdgeType::UsedBy => &[("slot", String), ("order", Number)],
+            EdgeType::ImplementsDesign => {
+                &[("completeness", Number), ("implemented_states", StringList)]
+            }
+            EdgeType::VariantOf | EdgeType::HasVariant => &[("axis", String)],
+            EdgeType::Replaces | EdgeType::ReplacedBy => &[("since_version", String)],
+            EdgeType::Documents | EdgeType::DocumentedBy => &[("section", String)],
+            _ => &[],
+        };
+        specs
+            .iter()
+            .map(|(name, property_type)| PropertySpec::optional(name, *property_type))
+            .collect()
+    }
+}
+
+/// Check a property bag against declarations
+///
+/// # Arguments
+/// * `edge_type` - Edge type id, used in error messages
+pub fn validate_properties(
+    edge_type: &str,
+    specs: &[PropertySpec],
+    properties: &EdgeProperties,
+) -> Result<(), SchemaError> {
+    for (name, value) in properties {
+        let spec = specs.iter().find(|spec| spec.name == *name).ok_or_else(|| {
+            SchemaError::UndeclaredProperty {
+                edge_type: edge_type.to_string(),
+                property: name.clone(),
+            }
+        })?;
+        if value.property_type() != spec.property_type {
+            return Err(SchemaError::PropertyTypeMismatch {
+                edge_type: edge_type.to_string(),
+                property: name.clone(),
+                expected: spec.property_type,
+                actual: value.property_type(),
+            });
+        }
+    }
+    if let Some(spec) = specs
+        .iter()
+        .find(|spec| spec.required && !properties.contains_key(&spec.name))
+    {
+        return Err(SchemaError::MissingRequiredProperty {
+            edge_type: edge_type.to_string(),
+            property: spec.name.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_properties() {
+        let specs = EdgeType::UsesToken.property_specs();
+        let mut properties = EdgeProperties::new();
+        properties.insert("css_property".to_string(), "background-color".into());
+        assert!(validate_properties("uses_token", &specs, &properties).is_ok());
+
+        properties.insert("css_property".to_string(), 1.0.into());
+        let error = validate_properties("uses_token", &specs, &properties).unwrap_err();
+        assert_eq!(error.code(), "property_type_mismatch");
+        assert_eq!(error.path(), "metadata.properties.css_property");
+        assert_eq!(
+            error.to_string(),
+            "Property 'css_property' of uses_token edges must be String, got Number"
+        );
+
+        properties.clear();
+        properties.insert("slot".to_string(), "icon".into());
+        assert!(validate_properties("uses_token", &specs, &properties).is_err());
+
+        let required = [PropertySpec::required("owner", PropertyType::String)];
+        assert!(validate_properties("owned_by", &required, &EdgeProperties::new()).is_err());
+    }
+
+    #[test]
+    fn test_property_value_json() {
+        let properties: EdgeProperties = serde_json::from_str(
+            r#"{"completeness":0.5,"implemented_states":["default"],"draft":true}"#,
+        )
+        .unwrap();
+        assert_eq!(properties["completeness"], PropertyValue::Number(0.5));
+        assert_eq!(properties["implemented_states"].property_type(), PropertyType::StringList);
+        assert_eq!(properties["draft"], PropertyValue::Boolean(true));
+    }
+}
@@ -0,0 +1,328 @@
+//! Runtime edge type registry
+//!
+//! Holds the built-in `EdgeType`s alongside custom edge types registered at
+//! runtime, so teams can model domain-specific relationships (e.g.
+//! `tested_by`, `localizes`) without forking the schema. Every type gets a
+//! stable numeric code for binary edge formats: built-ins take codes
+//! `0..EdgeType::ALL.len()` in declaration order, custom types follow in
+//! registration order.
+//!
+//! A custom type is either directed, optionally naming its reverse type, or
+//! undirected, in which case it is its own reverse. Reverses are resolved
+//! when queried, so a pair can be registered in either order.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+use super::edge_properties::{validate_properties, EdgeProperties, PropertySpec};
+use super::edge_types::EdgeType;
+use crate::error::SchemaError;
+
+fn default_directed() -> bool {
+    true
+}
+
+/// Definition of an edge type, built-in or custom
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdgeTypeDefinition {
+    /// Unique snake_case identifier
+    pub id: String,
+
+    /// Whether `A -> B` differs from `B -> A`
+    #[serde(default = "default_directed")]
+    pub directed: bool,
+
+    /// Identifier of the reverse edge type (directed types only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverse: Option<String>,
+
+    /// Edge represents a dependency
+    #[serde(default)]
+    pub dependency: bool,
+
+    /// Edge represents composition
+    #[serde(default)]
+    pub composition: bool,
+
+    /// Human-readable description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Properties edges of this type may carry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub properties: Vec<PropertySpec>,
+}
+
+impl EdgeTypeDefinition {
+    /// Creates a directed definition with no reverse or classification
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            directed: true,
+            reverse: None,
+            dependency: false,
+            composition: false,
+            description: None,
+            properties: Vec::new(),
+        }
+    }
+}
+
+impl From<EdgeType> for EdgeTypeDefinition {
+    fn from(edge_type: EdgeType) -> Self {
+        Self {
+            id: edge_type.as_str().to_string(),
+            directed: true,
+            reverse: edge_type.reverse().map(|r| r.as_str().to_string()),
+            dependency: edge_type.is_dependency(),
+            composition: edge_type.is_composition(),
+            description: Some(edge_type.description().to_string()),
+            properties: edge_type.property_specs(),
+        }
+    }
+}
+
+/// Registry of built-in and custom edge types
+#[derive(Debug, Clone)]
+pub struct EdgeTypeRegistry {
+    /// Definitions indexed by numeric code
+    definitions: Vec<EdgeTypeDefinition>,
+    codes: HashMap<String, u32>,
+}
+
+impl Default for EdgeTypeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EdgeTypeRegistry {
+    /// Creates a registry containing the built-in edge types
+    pub fn new() -> Self {
+        let mut registry = Self {
+            definitions: Vec::new(),
+            codes: HashMap::new(),
+        };
+        for edge_type in EdgeType::ALL {
+            registry.insert(edge_type.into());
+        }
+        registry
+    }
+
+    fn insert(&mut self, definition: EdgeTypeDefinition) -> u32 {
+        let code = self.definitions.len() as u32;
+        self.codes.insert(definition.id.clone(), code);
+        self.definitions.push(definition);
+        code
+    }
+
+    /// Registers a custom edge type
+    ///
+    /// Fails if the id is empty or taken, if an undirected type names a
+    /// reverse, or if the reverse is a built-in or a registered type that
+    /// names a different reverse.
+    ///
+    /// # Returns
+    /// The numeric code assigned to the new type
+    pub fn register(&mut self, definition: EdgeTypeDefinition) -> Result<u32, SchemaError> {
+        let id = definition.id.as_str();
+        if id.is_empty() {
+            return Err(SchemaError::EmptyValue {
+                path: "id".to_string(),
+            });
+        }
+        if self.codes.contains_key(id) {
+            return Err(SchemaError::DuplicateEdgeType {
+                edge_type: id.to_string(),
+            });
+        }
+        if let Some(reverse) = &definition.reverse {
+            let invalid = |reason: String| SchemaError::InvalidReverse {
+                edge_type: id.to_string(),
+                reverse: reverse.clone(),
+                reason,
+            };
+            if !definition.directed {
+                return Err(invalid("undirected edge types are their own reverse".to_string()));
+            }
+            if self.is_builtin(reverse) {
+                return Err(invalid("built-in edge types cannot be reversed".to_string()));
+            }
+            if let Some(existing) = self.get(reverse) {
+                if let Some(other) = existing.reverse.as_deref().filter(|r| *r != id) {
+                    return Err(invalid(format!("already the reverse of {}", other)));
+                }
+            }
+        }
+        Ok(self.insert(definition))
+    }
+
+    /// Removes the most recently registered custom type
+    ///
+    /// Only the last type can go without changing other types' codes.
+    ///
+    /// # Returns
+    /// The removed definition, or None if only built-ins are registered
+    pub fn pop_custom(&mut self) -> Option<EdgeTypeDefinition> {
+        if self.definitions.len() <= EdgeType::ALL.len() {
+            return None;
+        }
+        let definition = self.definitions.pop()?;
+        self.codes.remove(&definition.id);
+        Some(definition)
+    }
+
+    /// Returns true if `id` names a built-in edge type
+    pub fn is_builtin(&self, id: &str) -> bool {
+        self.code(id)
+            .is_some_and(|code| (code as usize) < EdgeType::ALL.len())
+    }
+
+    /// Looks up a definition by id
+    pub fn get(&self, id: &str) -> Option<&EdgeTypeDefinition> {
+        self.code(id).map(|code| &self.definitions[code as usize])
+    }
+
+    /// Numeric code of an edge type
+    pub fn code(&self, id: &str) -> Option<u32> {
+        self.codes.get(id).copied()
+    }
+
+    /// Looks up a definition by numeric code
+    pub fn by_code(&self, code: u32) -> Option<&EdgeTypeDefinition> {
+        self.definitions.get(code as usize)
+    }
+
+    /// Id of the reverse edge type, if it is registered
+    ///
+    /// Undirected types are their own reverse. A type that is named as the
+    /// reverse of another, without naming one itself, reverses to that type.
+    pub fn reverse(&self, id: &str) -> Option<&str> {
+        let definition = self.get(id)?;
+        if !definition.directed {
+            return Some(&definition.id);
+        }
+        match &definition.reverse {
+            Some(reverse) => self.get(reverse).map(|d| d.id.as_str()),
+            None => self
+                .definitions
+                .iter()
+                .find(|d| d.reverse.as_deref() == Some(id))
+                .map(|d| d.id.as_str()),
+        }
+    }
+
+    /// Returns true if the edge type represents a dependency
+    pub fn is_dependency(&self, id: &str) -> bool {
+        self.get(id).is_some_and(|d| d.dependency)
+    }
+
+    /// Returns true if the edge type represents composition
+    pub fn is_composition(&self, id: &str) -> bool {
+        self.get(id).is_some_and(|d| d.composition)
+    }
+
+    /// Checks a property bag against the edge type's declarations
+    pub fn validate_properties(&self, id: &str, properties: &EdgeProperties) -> Result<(), SchemaError> {
+        let definition = self.get(id).ok_or_else(|| SchemaError::UnknownEdgeType {
+            edge_type: id.to_string(),
+        })?;
+        validate_properties(id, &definition.properties, properties)
+    }
+
+    /// Every definition, ordered by numeric code
+    pub fn definitions(&self) -> &[EdgeTypeDefinition] {
+        &self.definitions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtins_are_preregistered() {
+        let registry = EdgeTypeRegistry::new();
+        for (code, edge_type) in EdgeType::ALL.iter().enumerate() {
+            assert_eq!(registry.code(edge_type.as_str()), Some(code as u32));
+        }
+        assert!(registry.is_builtin("composes_of"));
+        assert_eq!(registry.reverse("used_by"), Some("composes_of"));
+        assert!(registry.is_dependency("uses_token"));
+        assert!(registry.is_composition("composes_of"));
+    }
+
+    #[test]
+    fn test_register_custom_pair() {
+        let mut registry = EdgeTypeRegistry::new();
+        let tests: EdgeTypeDefinition = serde_json::from_str(
+            r#"{"id":"tested_by","reverse":"tests","dependency":false}"#,
+        )
+        .unwrap();
+        let code = registry.register(tests).unwrap();
+        assert_eq!(code as usize, EdgeType::ALL.len());
+        assert_eq!(registry.by_code(code).unwrap().id, "tested_by");
+        // Reverse named but not yet registered
+        assert_eq!(registry.reverse("tested_by"), None);
+
+        registry.register(EdgeTypeDefinition::new("tests")).unwrap();
+        assert_eq!(registry.reverse("tested_by"), Some("tests"));
+        assert_eq!(registry.reverse("tests"), Some("tested_by"));
+        assert!(!registry.is_builtin("tests"));
+
+        assert_eq!(registry.pop_custom().unwrap().id, "tests");
+        assert_eq!(registry.code("tests"), None);
+        assert_eq!(registry.pop_custom().unwrap().id, "tested_by");
+        assert_eq!(registry.pop_custom(), None);
+        assert_eq!(registry.definitions().len(), EdgeType::ALL.len());
+    }
+
+    #[test]
+    fn test_custom_type_properties() {
+        let mut registry = EdgeTypeRegistry::new();
+        let definition: EdgeTypeDefinition = serde_json::from_str(
+            r#"{"id":"tested_by","properties":[{"name":"suite","property_type":"string","required":true}]}"#,
+        )
+        .unwrap();
+        registry.register(definition).unwrap();
+
+        let mut properties = EdgeProperties::new();
+        assert!(registry.validate_properties("tested_by", &properties).is_err());
+        properties.insert("suite".to_string(), "visual".into());
+        assert!(registry.validate_properties("tested_by", &properties).is_ok());
+        assert!(registry.validate_properties("uses_token", &properties).is_err());
+    }
+
+    #[test]
+    fn test_undirected_and_invalid_registrations() {
+        let mut registry = EdgeTypeRegistry::new();
+        let pairs = EdgeTypeDefinition {
+            directed: false,
+            ..EdgeTypeDefinition::new("pairs_with")
+        };
+        registry.register(pairs.clone()).unwrap();
+        assert_eq!(registry.reverse("pairs_with"), Some("pairs_with"));
+
+        assert!(registry.register(pairs).is_err());
+        assert!(registry.register(EdgeTypeDefinition::new("")).is_err());
+        assert!(registry.register(EdgeTypeDefinition::new("used_by")).is_err());
+        assert!(registry
+            .register(EdgeTypeDefinition {
+                reverse: Some("composes_of".to_string()),
+                ..EdgeTypeDefinition::new("contains")
+            })
+            .is_err());
+        assert!(registry
+            .register(EdgeTypeDefinition {
+                directed: false,
+                reverse: Some("x".to_string()),
+                ..EdgeTypeDefinition::new("near")
+            })
+            .is_err());
+    }
+}
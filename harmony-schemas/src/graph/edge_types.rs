@@ -6,10 +6,21 @@
 //! - implements_design: Design implementation relationships
 //! - uses_token: Token usage relationships
 //! - used_by: Reverse dependency tracking
+//! - depends_on / depended_on_by: Generic dependency relationships
+//! - variant_of / has_variant: Theme and density variants
+//! - replaces / replaced_by: Deprecation successors
+//! - documents / documented_by: Documentation pages
 
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+use crate::error::SchemaError;
+
+use super::edge_properties::{validate_properties, EdgeProperties, PropertyValue};
 
 /// Edge types representing relationships in the design system graph
+#[cfg_attr(feature = "wasm", derive(Tsify))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EdgeType {
@@ -37,26 +48,130 @@ pub enum EdgeType {
     /// Direction: A -> B (A is contained in B)
     /// Example: Button -> Form (Button is used by Form)
     UsedBy,
+
+    /// Node A depends on Node B without composing or styling with it
+    /// Direction: A -> B (A requires B)
+    /// Example: DatePicker -> DateFormatUtility
+    DependsOn,
+
+    /// Node A is depended on by Node B (reverse of depends_on)
+    /// Direction: A -> B (B requires A)
+    /// Example: DateFormatUtility -> DatePicker
+    DependedOnBy,
+
+    /// Component A is a variant of Component B
+    /// Direction: A -> B (A adapts B)
+    /// Example: DarkButton -> Button, CompactTable -> Table
+    VariantOf,
+
+    /// Component A has Component B as a variant (reverse of variant_of)
+    /// Direction: A -> B (B adapts A)
+    /// Example: Button -> DarkButton
+    HasVariant,
+
+    /// Component A replaces deprecated Component B
+    /// Direction: A -> B (A succeeds B)
+    /// Example: ButtonV2 -> Button
+    Replaces,
+
+    /// Component A is replaced by Component B (reverse of replaces)
+    /// Direction: A -> B (B succeeds A)
+    /// Example: Button -> ButtonV2
+    ReplacedBy,
+
+    /// Page A documents Component B
+    /// Direction: A -> B (A describes B)
+    /// Example: ButtonDocsPage -> Button
+    Documents,
+
+    /// Component A is documented by Page B (reverse of documents)
+    /// Direction: A -> B (B describes A)
+    /// Example: Button -> ButtonDocsPage
+    DocumentedBy,
 }
 
 impl EdgeType {
+    /// Every built-in edge type, in declaration order
+    pub const ALL: [EdgeType; 13] = [
+        EdgeType::ComposesOf,
+        EdgeType::InheritsPattern,
+        EdgeType::ImplementsDesign,
+        EdgeType::UsesToken,
+        EdgeType::UsedBy,
+        EdgeType::DependsOn,
+        EdgeType::DependedOnBy,
+        EdgeType::VariantOf,
+        EdgeType::HasVariant,
+        EdgeType::Replaces,
+        EdgeType::ReplacedBy,
+        EdgeType::Documents,
+        EdgeType::DocumentedBy,
+    ];
+
+    /// Returns the snake_case name used in serialized graphs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeType::ComposesOf => "composes_of",
+            EdgeType::InheritsPattern => "inherits_pattern",
+            EdgeType::ImplementsDesign => "implements_design",
+            EdgeType::UsesToken => "uses_token",
+            EdgeType::UsedBy => "used_by",
+            EdgeType::DependsOn => "depends_on",
+            EdgeType::DependedOnBy => "depended_on_by",
+            EdgeType::VariantOf => "variant_of",
+            EdgeType::HasVariant => "has_variant",
+            EdgeType::Replaces => "replaces",
+            EdgeType::ReplacedBy => "replaced_by",
+            EdgeType::Documents => "documents",
+            EdgeType::DocumentedBy => "documented_by",
+        }
+    }
+
     /// Returns the reverse edge type if applicable
     /// 
     /// Some edge types have natural inverses:
     /// - composes_of <-> used_by
+    /// - depends_on <-> depended_on_by
+    /// - variant_of <-> has_variant
+    /// - replaces <-> replaced_by
+    /// - documents <-> documented_by
     /// 
     /// Returns None for edge types without defined inverses
     pub fn reverse(&self) -> Option<EdgeType> {
         match self {
             EdgeType::ComposesOf => Some(EdgeType::UsedBy),
             EdgeType::UsedBy => Some(EdgeType::ComposesOf),
+            EdgeType::DependsOn => Some(EdgeType::DependedOnBy),
+            EdgeType::DependedOnBy => Some(EdgeType::DependsOn),
+            EdgeType::VariantOf => Some(EdgeType::HasVariant),
+            EdgeType::HasVariant => Some(EdgeType::VariantOf),
+            EdgeType::Replaces => Some(EdgeType::ReplacedBy),
+            EdgeType::ReplacedBy => Some(EdgeType::Replaces),
+            EdgeType::Documents => Some(EdgeType::DocumentedBy),
+            EdgeType::DocumentedBy => Some(EdgeType::Documents),
             _ => None,
         }
     }
     
+    /// Returns true if this edge type is the reverse half of a pair
+    /// (e.g. used_by rather than composes_of)
+    pub fn is_reverse(&self) -> bool {
+        matches!(
+            self,
+            EdgeType::UsedBy
+                | EdgeType::DependedOnBy
+                | EdgeType::HasVariant
+                | EdgeType::ReplacedBy
+                | EdgeType::DocumentedBy
+        )
+    }
+    
     /// Returns true if this edge type represents a dependency
     pub fn is_dependency(&self) -> bool {
-        matches!(self, EdgeType::UsesToken | EdgeType::InheritsPattern)
+        matches!(
+            self,
+            EdgeType::UsesToken | EdgeType::InheritsPattern | EdgeType::DependsOn
+        )
     }
     
     /// Returns true if this edge type represents composition
@@ -64,6 +179,21 @@ impl EdgeType {
         matches!(self, EdgeType::ComposesOf | EdgeType::UsedBy)
     }
     
+    /// Returns true if this edge type links a component to one of its variants
+    pub fn is_variant(&self) -> bool {
+        matches!(self, EdgeType::VariantOf | EdgeType::HasVariant)
+    }
+    
+    /// Returns true if this edge type links a deprecated component to its successor
+    pub fn is_succession(&self) -> bool {
+        matches!(self, EdgeType::Replaces | EdgeType::ReplacedBy)
+    }
+    
+    /// Returns true if this edge type links documentation to a component
+    pub fn is_documentation(&self) -> bool {
+        matches!(self, EdgeType::Documents | EdgeType::DocumentedBy)
+    }
+    
     /// Returns a human-readable description of the edge type
     pub fn description(&self) -> &'static str {
         match self {
@@ -72,12 +202,21 @@ impl EdgeType {
             EdgeType::ImplementsDesign => "Component implements a design specification",
             EdgeType::UsesToken => "Component uses a design token",
             EdgeType::UsedBy => "Component is used by another component",
+            EdgeType::DependsOn => "Node depends on another node",
+            EdgeType::DependedOnBy => "Node is depended on by another node",
+            EdgeType::VariantOf => "Component is a variant of another component",
+            EdgeType::HasVariant => "Component has a variant",
+            EdgeType::Replaces => "Component replaces a deprecated component",
+            EdgeType::ReplacedBy => "Component is replaced by a successor",
+            EdgeType::Documents => "Page documents a component",
+            EdgeType::DocumentedBy => "Component is documented by a page",
         }
     }
 }
 
 /// Edge data structure representing a relationship between two nodes
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Edge {
     /// Unique identifier for this edge
     pub id: String,
@@ -97,7 +236,8 @@ pub struct Edge {
 }
 
 /// Metadata that can be attached to edges
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct EdgeMetadata {
     /// Weight or strength of the relationship (0.0 to 1.0)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -107,9 +247,19 @@ pub struct EdgeMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
     
-    /// Additional custom properties
+    /// Typed properties declared by the edge type
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub properties: Option<serde_json::Value>,
+    pub properties: Option<EdgeProperties>,
+}
+
+impl EdgeMetadata {
+    /// Add a property
+    pub fn with_property(mut self, name: &str, value: impl Into<PropertyValue>) -> Self {
+        self.properties
+            .get_or_insert_with(EdgeProperties::new)
+            .insert(name.to_string(), value.into());
+        self
+    }
 }
 
 impl Edge {
@@ -141,6 +291,35 @@ impl Edge {
         }
     }
     
+    /// Creates a new edge with metadata, validating its properties
+    /// against the edge type's declarations
+    pub fn try_with_metadata(
+        id: String,
+        from: String,
+        to: String,
+        edge_type: EdgeType,
+        metadata: EdgeMetadata,
+    ) -> Result<Self, SchemaError> {
+        let edge = Self::with_metadata(id, from, to, edge_type, metadata);
+        edge.validate()?;
+        Ok(edge)
+    }
+    
+    /// Checks the edge's properties against its edge type's declarations
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        let empty = EdgeProperties::new();
+        let properties = self
+            .metadata
+            .as_ref()
+            .and_then(|m| m.properties.as_ref())
+            .unwrap_or(&empty);
+        validate_properties(
+            self.edge_type.as_str(),
+            &self.edge_type.property_specs(),
+            properties,
+        )
+    }
+    
     /// Returns the reverse edge if the edge type supports reversal
     pub fn reverse(&self) -> Option<Edge> {
         self.edge_type.reverse().map(|reversed_type| {
@@ -172,6 +351,26 @@ mod tests {
         assert_eq!(EdgeType::UsesToken.reverse(), None);
     }
 
+    #[test]
+    fn test_new_edge_type_reverses() {
+        let pairs = [
+            (EdgeType::DependsOn, EdgeType::DependedOnBy),
+            (EdgeType::VariantOf, EdgeType::HasVariant),
+            (EdgeType::Replaces, EdgeType::ReplacedBy),
+            (EdgeType::Documents, EdgeType::DocumentedBy),
+        ];
+        for (forward, backward) in pairs {
+            assert_eq!(forward.reverse(), Some(backward));
+            assert_eq!(backward.reverse(), Some(forward));
+            assert!(!forward.is_reverse());
+            assert!(backward.is_reverse());
+        }
+        assert_eq!(
+            serde_json::to_string(&EdgeType::DependedOnBy).unwrap(),
+            "\"depended_on_by\""
+        );
+    }
+
     #[test]
     fn test_edge_type_classification() {
         assert!(EdgeType::UsesToken.is_dependency());
@@ -181,6 +380,20 @@ mod tests {
         assert!(EdgeType::ComposesOf.is_composition());
         assert!(EdgeType::UsedBy.is_composition());
         assert!(!EdgeType::UsesToken.is_composition());
+
+        assert!(EdgeType::DependsOn.is_dependency());
+        assert!(EdgeType::VariantOf.is_variant());
+        assert!(EdgeType::ReplacedBy.is_succession());
+        assert!(EdgeType::Documents.is_documentation());
+        assert!(!EdgeType::Documents.is_dependency());
+    }
+
+    #[test]
+    fn test_edge_type_names_match_serde() {
+        for edge_type in EdgeType::ALL {
+            let json = serde_json::to_string(&edge_type).unwrap();
+            assert_eq!(json, format!("\"{}\"", edge_type.as_str()));
+        }
     }
 
     #[test]
@@ -214,6 +427,35 @@ mod tests {
         assert_eq!(reversed.edge_type, EdgeType::UsedBy);
     }
 
+    #[test]
+    fn test_edge_properties_validated() {
+        let metadata = EdgeMetadata::default().with_property("css_property", "color");
+        let edge = Edge::try_with_metadata(
+            "edge1".to_string(),
+            "button".to_string(),
+            "color-token".to_string(),
+            EdgeType::UsesToken,
+            metadata.clone(),
+        )
+        .unwrap();
+        assert!(edge.validate().is_ok());
+
+        let invalid = Edge::try_with_metadata(
+            "edge2".to_string(),
+            "button".to_string(),
+            "icon".to_string(),
+            EdgeType::ComposesOf,
+            metadata,
+        );
+        assert_eq!(
+            invalid.unwrap_err(),
+            SchemaError::UndeclaredProperty {
+                edge_type: "composes_of".to_string(),
+                property: "css_property".to_string(),
+            }
+        );
+    }
+
     #[test]
     fn test_edge_serialization() {
         let edge = Edge::new(
@@ -3,6 +3,10 @@
 //! Provides graph structures and edge type definitions for modeling
 //! relationships between design system components, patterns, and tokens.
 
+pub mod edge_properties;
+pub mod edge_registry;
 pub mod edge_types;
 
+pub use edge_properties::{EdgeProperties, PropertySpec, PropertyType, PropertyValue};
+pub use edge_registry::{EdgeTypeDefinition, EdgeTypeRegistry};
 pub use edge_types::{Edge, EdgeMetadata, EdgeType};
\ No newline at end of file
@@ -0,0 +1,311 @@
+//! Localized component copy
+//!
+//! A `MessageCatalog` holds the user-facing strings of the design system.
+//! Each `Message` has a dotted key (`button.submit.label`), its text in the
+//! catalog's source locale and translations keyed by locale code.
+//!
+//! Message text is either plain or a set of plural forms keyed by CLDR
+//! plural category. Each `LocaleDefinition` lists the categories its
+//! language distinguishes, so a plural message only counts as translated
+//! for a locale once every one of those forms is present. Plain text is
+//! accepted for any locale.
+//!
+//! Components reference messages through `ComponentMessageLink`s, which the
+//! graph uses to find components with untranslated copy.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#localization
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::SchemaError;
+
+/// CLDR plural category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+fn default_plural_categories() -> Vec<PluralCategory> {
+    vec![PluralCategory::One, PluralCategory::Other]
+}
+
+/// A locale the catalog is translated into
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocaleDefinition {
+    /// BCP 47 code, e.g. "en-US", "pl"
+    pub code: String,
+
+    /// Plural forms the language needs, "one" and "other" by default
+    #[serde(default = "default_plural_categories")]
+    pub plural_categories: Vec<PluralCategory>,
+}
+
+impl LocaleDefinition {
+    /// Create a locale with the default plural forms
+    pub fn new(code: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            plural_categories: default_plural_categories(),
+        }
+    }
+
+    /// Set the plural forms the language needs
+    pub fn with_plural_categories(mut self, categories: Vec<PluralCategory>) -> Self {
+        self.plural_categories = categories;
+        self
+    }
+}
+
+/// Text of a message in one locale
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageText {
+    Text(String),
+    Plural(BTreeMap<PluralCategory, String>),
+}
+
+impl MessageText {
+    /// Whether the text is usable in a locale with the given plural forms
+    pub fn is_complete(&self, plural_categories: &[PluralCategory]) -> bool {
+        match self {
+            MessageText::Text(text) => !text.is_empty(),
+            MessageText::Plural(forms) => plural_categories
+                .iter()
+                .all(|category| forms.get(category).is_some_and(|form| !form.is_empty())),
+        }
+    }
+}
+
+/// A user-facing string and its translations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+    /// Dotted key, e.g. "button.submit.label"
+    pub key: String,
+
+    /// Context for translators
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Text in the catalog's source locale
+    pub source: MessageText,
+
+    /// Translations keyed by locale code
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, MessageText>,
+}
+
+impl Message {
+    /// Create a plain-text message with no translations
+    pub fn new(key: &str, source: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            description: None,
+            source: MessageText::Text(source.to_string()),
+            translations: BTreeMap::new(),
+        }
+    }
+
+    /// Add a translation
+    pub fn with_translation(mut self, locale: &str, text: MessageText) -> Self {
+        self.translations.insert(locale.to_string(), text);
+        self
+    }
+}
+
+/// Messages of the design system with their supported locales
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageCatalog {
+    /// Locale the `source` text of every message is written in
+    pub source_locale: String,
+
+    #[serde(default)]
+    pub locales: Vec<LocaleDefinition>,
+
+    #[serde(default)]
+    pub messages: Vec<Message>,
+}
+
+impl MessageCatalog {
+    /// Create an empty catalog
+    pub fn new(source_locale: &str) -> Self {
+        Self {
+            source_locale: source_locale.to_string(),
+            locales: vec![LocaleDefinition::new(source_locale)],
+            messages: Vec::new(),
+        }
+    }
+
+    /// Look up a message by key
+    pub fn get(&self, key: &str) -> Option<&Message> {
+        self.messages.iter().find(|m| m.key == key)
+    }
+
+    /// Look up a locale by code
+    pub fn locale(&self, code: &str) -> Option<&LocaleDefinition> {
+        self.locales.iter().find(|l| l.code == code)
+    }
+
+    /// Whether a message has complete text for a locale
+    ///
+    /// Unknown keys and undeclared locales are never translated.
+    pub fn is_translated(&self, key: &str, locale: &str) -> bool {
+        let (Some(message), Some(definition)) = (self.get(key), self.locale(locale)) else {
+            return false;
+        };
+        let text = if locale == self.source_locale {
+            Some(&message.source)
+        } else {
+            message.translations.get(locale)
+        };
+        text.is_some_and(|text| text.is_complete(&definition.plural_categories))
+    }
+
+    /// Keys of messages lacking complete text for a locale
+    pub fn untranslated(&self, locale: &str) -> Vec<&str> {
+        self.messages
+            .iter()
+            .filter(|m| !self.is_translated(&m.key, locale))
+            .map(|m| m.key.as_str())
+            .collect()
+    }
+
+    /// Check that keys and locale codes are non-empty and unique and that
+    /// the source locale is declared
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        if self.locale(&self.source_locale).is_none() {
+            return Err(SchemaError::UnknownLocale {
+                locale: self.source_locale.clone(),
+            });
+        }
+        for (field, names) in [
+            (
+                "locales",
+                self.locales.iter().map(|l| &l.code).collect::<Vec<_>>(),
+            ),
+            ("messages", self.messages.iter().map(|m| &m.key).collect()),
+        ] {
+            for (i, name) in names.iter().enumerate() {
+                if name.is_empty() {
+                    return Err(SchemaError::EmptyValue {
+                        path: format!("{}[{}]", field, i),
+                    });
+                }
+                if names[..i].contains(name) {
+                    return Err(SchemaError::DuplicateEntry {
+                        path: field.to_string(),
+                        name: name.to_string(),
+                    });
+                }
+            }
+        }
+        for message in &self.messages {
+            if let Some(locale) = message
+                .translations
+                .keys()
+                .find(|l| self.locale(l).is_none())
+            {
+                return Err(SchemaError::UnknownLocale {
+                    locale: locale.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Link from a component to a message it displays
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentMessageLink {
+    pub component_id: String,
+    pub message_key: String,
+
+    /// Prop or slot the message fills, e.g. "label"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+impl ComponentMessageLink {
+    /// Create a Component → Message link
+    pub fn new(component_id: &str, message_key: &str) -> Self {
+        Self {
+            component_id: component_id.to_string(),
+            message_key: message_key.to_string(),
+            target: None,
+        }
+    }
+
+    /// Set the prop or slot the message fills
+    pub fn with_target(mut self, target: &str) -> Self {
+        self.target = Some(target.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> MessageCatalog {
+        serde_json::from_str(
+            r#"{
+                "source_locale": "en",
+                "locales": [
+                    {"code": "en"},
+                    {"code": "pl", "plural_categories": ["one", "few", "many", "other"]}
+                ],
+                "messages": [
+                    {"key": "button.submit", "source": "Submit", "translations": {"pl": "Wyślij"}},
+                    {
+                        "key": "list.items",
+                        "source": {"one": "{count} item", "other": "{count} items"},
+                        "translations": {"pl": {"one": "{count} element", "other": "{count} elementu"}}
+                    }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_plural_forms_follow_locale_rules() {
+        let catalog = catalog();
+        assert!(catalog.validate().is_ok());
+        assert!(catalog.is_translated("list.items", "en"));
+        assert!(catalog.is_translated("button.submit", "pl"));
+        assert_eq!(catalog.untranslated("pl"), vec!["list.items"]);
+        assert_eq!(
+            catalog.untranslated("de"),
+            vec!["button.submit", "list.items"]
+        );
+    }
+
+    #[test]
+    fn test_validate_catalog() {
+        let mut catalog = catalog();
+        catalog.messages.push(Message::new("button.submit", "Send"));
+        assert_eq!(
+            catalog.validate(),
+            Err(SchemaError::DuplicateEntry {
+                path: "messages".to_string(),
+                name: "button.submit".to_string(),
+            })
+        );
+
+        catalog.messages.pop();
+        catalog.messages[0]
+            .translations
+            .insert("de".to_string(), MessageText::Text("Senden".to_string()));
+        assert_eq!(
+            catalog.validate().unwrap_err().to_string(),
+            "Unknown locale: de"
+        );
+    }
+}
@@ -3,11 +3,64 @@
 //! This crate contains all schema definitions for the Harmony Design System.
 //! Schemas define the structure and validation rules for design system data.
 
+pub mod a11y;
+pub mod component_lifecycle;
+pub mod component_ui_link;
+pub mod design_spec;
+pub mod error;
+pub mod figma;
+pub mod graph;
+pub mod i18n;
 pub mod lifecycle_states;
+pub mod responsive;
+pub mod template_diff;
+pub mod template_node;
+pub mod template_render;
+pub mod theme;
+pub mod versioning;
 
+pub use a11y::{A11yIssue, A11yIssueKind, A11yMetadata, AriaRequirement, KeyboardInteraction};
+pub use component_lifecycle::{
+    Actor,
+    AuthorizationError,
+    BatchTransitionResult,
+    BlockingDependency,
+    ComponentState,
+    StateTransition,
+    TransitionBatch,
+    TransitionResult,
+};
+pub use component_ui_link::{ComponentUILink, UIUsageContext};
+pub use design_spec::{A11yRequirement, DesignSpecNode, FigmaReference, PropContract};
+pub use error::{SchemaError, Severity};
+pub use figma::{import_file, FigmaFile, FigmaImport, FigmaNode, FigmaStyle, SkippedItem};
+pub use graph::{
+    Edge,
+    EdgeMetadata,
+    EdgeProperties,
+    EdgeType,
+    EdgeTypeDefinition,
+    EdgeTypeRegistry,
+    PropertySpec,
+    PropertyType,
+    PropertyValue,
+};
+pub use i18n::{
+    ComponentMessageLink,
+    LocaleDefinition,
+    Message,
+    MessageCatalog,
+    MessageText,
+    PluralCategory,
+};
 pub use lifecycle_states::{
     LifecycleState,
     LifecycleEntry,
     LifecycleHistory,
     StateMetadata,
-};
\ No newline at end of file
+};
+pub use responsive::{Breakpoint, Breakpoints, EffectiveVariant, ResponsiveComponent, ResponsiveOverride};
+pub use template_diff::{diff, TemplateDiff};
+pub use template_render::TemplateRenderer;
+pub use theme::{MissingAlias, ResolvedTheme, Theme, ThemeResolver, Token, TokenSet};
+pub use versioning::{from_json, to_json, MigrationRegistry, Schema, SCHEMA_VERSION_FIELD};
@@ -6,9 +6,14 @@
 //! See: harmony-design/DESIGN_SYSTEM.md#lifecycle-states
 
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
 use std::fmt;
 
+use crate::error::SchemaError;
+
 /// Lifecycle state of a component in the design system
+#[cfg_attr(feature = "wasm", derive(Tsify))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LifecycleState {
@@ -101,7 +106,8 @@ impl fmt::Display for LifecycleState {
 }
 
 /// Metadata associated with a lifecycle state change
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct StateMetadata {
     /// Optional reason for the state change
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -115,7 +121,8 @@ pub struct StateMetadata {
 }
 
 /// A lifecycle state entry with timestamp and metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LifecycleEntry {
     /// The lifecycle state
     pub state: LifecycleState,
@@ -147,7 +154,8 @@ impl LifecycleEntry {
 }
 
 /// Complete history of lifecycle state changes
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LifecycleHistory {
     /// Ordered list of state changes (oldest first)
     pub entries: Vec<LifecycleEntry>,
@@ -168,13 +176,13 @@ impl LifecycleHistory {
 
     /// Adds a new state to the history
     /// Returns an error if the transition is not valid
-    pub fn transition_to(&mut self, new_state: LifecycleState) -> Result<(), String> {
+    pub fn transition_to(&mut self, new_state: LifecycleState) -> Result<(), SchemaError> {
         if let Some(current) = self.current_state() {
             if !current.state.can_transition_to(&new_state) {
-                return Err(format!(
-                    "Invalid transition from {} to {}",
-                    current.state, new_state
-                ));
+                return Err(SchemaError::InvalidTransition {
+                    from: current.state.to_string(),
+                    to: new_state.to_string(),
+                });
             }
         }
         
@@ -187,13 +195,13 @@ impl LifecycleHistory {
         &mut self,
         new_state: LifecycleState,
         metadata: StateMetadata,
-    ) -> Result<(), String> {
+    ) -> Result<(), SchemaError> {
         if let Some(current) = self.current_state() {
             if !current.state.can_transition_to(&new_state) {
-                return Err(format!(
-                    "Invalid transition from {} to {}",
-                    current.state, new_state
-                ));
+                return Err(SchemaError::InvalidTransition {
+                    from: current.state.to_string(),
+                    to: new_state.to_string(),
+                });
             }
         }
         
@@ -0,0 +1,261 @@
+//! Responsive variants
+//!
+//! Breakpoints are mobile-first: a breakpoint applies from its `min_width`
+//! upward, and the defaults mirror `tokens/breakpoints.js` (mobile 0,
+//! tablet 768, desktop 1024, wide 1440).
+//!
+//! A `ResponsiveComponent` has a base template, variant and props plus
+//! overrides keyed by breakpoint. For a viewport width, the overrides of
+//! every breakpoint at or below that width are applied in ascending order,
+//! so a desktop viewport also picks up tablet overrides it does not
+//! replace. Props merge key by key; template and variant are replaced.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#responsive-breakpoints
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A named breakpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    pub name: String,
+
+    /// Smallest viewport width (px) the breakpoint applies to
+    pub min_width: u32,
+}
+
+/// Ordered set of breakpoints, serialized as a list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<Breakpoint>", into = "Vec<Breakpoint>")]
+pub struct Breakpoints {
+    /// Sorted by `min_width`
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl Default for Breakpoints {
+    fn default() -> Self {
+        let defaults = [("mobile", 0), ("tablet", 768), ("desktop", 1024), ("wide", 1440)];
+        Self {
+            breakpoints: defaults
+                .iter()
+                .map(|(name, min_width)| Breakpoint {
+                    name: name.to_string(),
+                    min_width: *min_width,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<Vec<Breakpoint>> for Breakpoints {
+    type Error = String;
+
+    fn try_from(breakpoints: Vec<Breakpoint>) -> Result<Self, String> {
+        Self::new(breakpoints)
+    }
+}
+
+impl From<Breakpoints> for Vec<Breakpoint> {
+    fn from(breakpoints: Breakpoints) -> Self {
+        breakpoints.breakpoints
+    }
+}
+
+impl Breakpoints {
+    /// Build a breakpoint set, sorted by width
+    ///
+    /// Names and widths must be unique and one breakpoint must start at 0.
+    pub fn new(mut breakpoints: Vec<Breakpoint>) -> Result<Self, String> {
+        breakpoints.sort_by_key(|b| b.min_width);
+        if breakpoints.first().map(|b| b.min_width) != Some(0) {
+            return Err("A breakpoint must start at min_width 0".to_string());
+        }
+        for (i, breakpoint) in breakpoints.iter().enumerate().skip(1) {
+            if breakpoint.min_width == breakpoints[i - 1].min_width {
+                return Err(format!("Duplicate breakpoint width: {}", breakpoint.min_width));
+            }
+            if breakpoints[..i].iter().any(|b| b.name == breakpoint.name) {
+                return Err(format!("Duplicate breakpoint name: {}", breakpoint.name));
+            }
+        }
+        Ok(Self { breakpoints })
+    }
+
+    /// Breakpoints in ascending width order
+    pub fn iter(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.breakpoints.iter()
+    }
+
+    /// Look up a breakpoint by name
+    pub fn get(&self, name: &str) -> Option<&Breakpoint> {
+        self.breakpoints.iter().find(|b| b.name == name)
+    }
+
+    /// The breakpoint in effect at a viewport width
+    pub fn active(&self, width: u32) -> &Breakpoint {
+        self.breakpoints
+            .iter()
+            .rev()
+            .find(|b| b.min_width <= width)
+            .unwrap_or(&self.breakpoints[0])
+    }
+}
+
+/// Overrides applied from one breakpoint upward
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponsiveOverride {
+    /// Breakpoint name
+    pub breakpoint: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub props: Map<String, Value>,
+}
+
+/// Component with per-breakpoint template, variant and prop overrides
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponsiveComponent {
+    pub component_id: String,
+
+    /// Template used below every override
+    pub template_id: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub props: Map<String, Value>,
+
+    #[serde(default)]
+    pub overrides: Vec<ResponsiveOverride>,
+}
+
+/// Template, variant and props in effect at a viewport width
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveVariant {
+    pub component_id: String,
+
+    /// Active breakpoint name
+    pub breakpoint: String,
+
+    pub template_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+
+    pub props: Map<String, Value>,
+}
+
+impl ResponsiveComponent {
+    /// Check that every override names a known breakpoint, at most once
+    pub fn validate(&self, breakpoints: &Breakpoints) -> Result<(), String> {
+        for (i, item) in self.overrides.iter().enumerate() {
+            if breakpoints.get(&item.breakpoint).is_none() {
+                return Err(format!(
+                    "Unknown breakpoint '{}' in {}",
+                    item.breakpoint, self.component_id
+                ));
+            }
+            if self.overrides[..i].iter().any(|o| o.breakpoint == item.breakpoint) {
+                return Err(format!(
+                    "Duplicate overrides for '{}' in {}",
+                    item.breakpoint, self.component_id
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Effective template, variant and props at a viewport width
+    pub fn resolve(&self, breakpoints: &Breakpoints, width: u32) -> Result<EffectiveVariant, String> {
+        self.validate(breakpoints)?;
+        let mut effective = EffectiveVariant {
+            component_id: self.component_id.clone(),
+            breakpoint: breakpoints.active(width).name.clone(),
+            template_id: self.template_id.clone(),
+            variant: self.variant.clone(),
+            props: self.props.clone(),
+        };
+        for breakpoint in breakpoints.iter().take_while(|b| b.min_width <= width) {
+            let Some(item) = self.overrides.iter().find(|o| o.breakpoint == breakpoint.name) else {
+                continue;
+            };
+            if let Some(template_id) = &item.template_id {
+                effective.template_id = template_id.clone();
+            }
+            if item.variant.is_some() {
+                effective.variant = item.variant.clone();
+            }
+            effective
+                .props
+                .extend(item.props.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        Ok(effective)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn nav() -> ResponsiveComponent {
+        serde_json::from_value(json!({
+            "component_id": "nav",
+            "template_id": "nav-drawer",
+            "variant": "compact",
+            "props": {"collapsed": true, "items": 5},
+            "overrides": [
+                {"breakpoint": "desktop", "template_id": "nav-bar", "props": {"collapsed": false}},
+                {"breakpoint": "tablet", "variant": "comfortable", "props": {"items": 8}}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolves_cascading_overrides() {
+        let breakpoints = Breakpoints::default();
+
+        let mobile = nav().resolve(&breakpoints, 375).unwrap();
+        assert_eq!(mobile.breakpoint, "mobile");
+        assert_eq!(mobile.template_id, "nav-drawer");
+        assert_eq!(mobile.props["items"], json!(5));
+
+        let tablet = nav().resolve(&breakpoints, 768).unwrap();
+        assert_eq!(tablet.variant.as_deref(), Some("comfortable"));
+        assert_eq!(tablet.template_id, "nav-drawer");
+
+        let wide = nav().resolve(&breakpoints, 1920).unwrap();
+        assert_eq!(wide.breakpoint, "wide");
+        assert_eq!(wide.template_id, "nav-bar");
+        assert_eq!(wide.variant.as_deref(), Some("comfortable"));
+        assert_eq!(wide.props["collapsed"], json!(false));
+        assert_eq!(wide.props["items"], json!(8));
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        let mut component = nav();
+        component.overrides[0].breakpoint = "tv".to_string();
+        assert_eq!(
+            component.resolve(&Breakpoints::default(), 1024),
+            Err("Unknown breakpoint 'tv' in nav".to_string())
+        );
+
+        let breakpoint = |name: &str, min_width| Breakpoint {
+            name: name.to_string(),
+            min_width,
+        };
+        assert!(Breakpoints::new(vec![breakpoint("sm", 320)]).is_err());
+        assert!(Breakpoints::new(vec![breakpoint("a", 0), breakpoint("a", 600)]).is_err());
+        let custom = Breakpoints::new(vec![breakpoint("lg", 900), breakpoint("base", 0)]).unwrap();
+        assert_eq!(custom.active(1000).name, "lg");
+        assert!(serde_json::from_str::<Breakpoints>(r#"[{"name":"sm","min_width":320}]"#).is_err());
+    }
+}
@@ -0,0 +1,305 @@
+//! TemplateNode diffing
+//!
+//! `diff(a, b)` compares two versions of a template node and produces a
+//! serializable change set for the design-review UI:
+//! - attributes are matched by name (first occurrence wins)
+//! - slots are matched by slot name
+//! - children are compared as ordered ID lists using a longest common
+//!   subsequence, so moves show up as a removal plus an insertion; removal
+//!   indices refer to `a`, insertion indices to `b`
+//!
+//! Only the node itself is compared; child templates are diffed separately.
+//!
+//! Related: docs/architecture/template-storage-strategy.md
+
+use serde::{Deserialize, Serialize};
+
+use crate::a11y::A11yMetadata;
+use crate::template_node::{Attribute, GpuMetadata, ShadowConfig, SlotDefinition, TemplateNode};
+
+/// Old and new value of a changed field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueChange<T> {
+    pub from: T,
+    pub to: T,
+}
+
+/// Change to one attribute
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AttributeChange {
+    Added { name: String, value: String },
+    Removed { name: String, value: String },
+    Changed { name: String, from: String, to: String },
+}
+
+/// Change to one slot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SlotChange {
+    Added { slot: SlotDefinition },
+    Removed { slot: SlotDefinition },
+    Changed { from: SlotDefinition, to: SlotDefinition },
+}
+
+/// Insertion or removal of a child reference
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChildChange {
+    /// `child_id` inserted at `index` of the new children
+    Inserted { index: usize, child_id: String },
+    /// `child_id` removed from `index` of the old children
+    Removed { index: usize, child_id: String },
+}
+
+/// Structured changes between two versions of a template node
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TemplateDiff {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<ValueChange<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element_type: Option<ValueChange<String>>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<AttributeChange>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slots: Vec<SlotChange>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ChildChange>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadow_config: Option<ValueChange<Option<ShadowConfig>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpu_metadata: Option<ValueChange<Option<GpuMetadata>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub a11y: Option<ValueChange<Option<A11yMetadata>>>,
+}
+
+impl TemplateDiff {
+    /// True when the two versions are identical
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn change<T: Clone + PartialEq>(a: &T, b: &T) -> Option<ValueChange<T>> {
+    (a != b).then(|| ValueChange {
+        from: a.clone(),
+        to: b.clone(),
+    })
+}
+
+/// Compare two versions of a template node
+pub fn diff(a: &TemplateNode, b: &TemplateNode) -> TemplateDiff {
+    TemplateDiff {
+        template_id: change(&a.template_id, &b.template_id),
+        element_type: change(&a.element_type, &b.element_type),
+        attributes: diff_attributes(a, b),
+        slots: diff_slots(a, b),
+        children: diff_children(&a.children, &b.children),
+        shadow_config: change(&a.shadow_config, &b.shadow_config),
+        gpu_metadata: change(&a.gpu_metadata, &b.gpu_metadata),
+        a11y: change(&a.a11y, &b.a11y),
+    }
+}
+
+/// Attributes of a node, skipping later duplicates of a name
+fn unique_attributes(node: &TemplateNode) -> impl Iterator<Item = &Attribute> {
+    node.attributes
+        .iter()
+        .enumerate()
+        .filter(|(i, attr)| !node.attributes[..*i].iter().any(|a| a.name == attr.name))
+        .map(|(_, attr)| attr)
+}
+
+fn attribute<'a>(node: &'a TemplateNode, name: &str) -> Option<&'a str> {
+    node.attributes
+        .iter()
+        .find(|attr| attr.name == name)
+        .map(|attr| attr.value.as_str())
+}
+
+fn diff_attributes(a: &TemplateNode, b: &TemplateNode) -> Vec<AttributeChange> {
+    let mut changes = Vec::new();
+    for attr in unique_attributes(a) {
+        match attribute(b, &attr.name) {
+            None => changes.push(AttributeChange::Removed {
+                name: attr.name.clone(),
+                value: attr.value.clone(),
+            }),
+            Some(value) if value != attr.value => changes.push(AttributeChange::Changed {
+                name: attr.name.clone(),
+                from: attr.value.clone(),
+                to: value.to_string(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for attr in unique_attributes(b) {
+        if attribute(a, &attr.name).is_none() {
+            changes.push(AttributeChange::Added {
+                name: attr.name.clone(),
+                value: attr.value.clone(),
+            });
+        }
+    }
+    changes
+}
+
+fn diff_slots(a: &TemplateNode, b: &TemplateNode) -> Vec<SlotChange> {
+    let find = |node: &TemplateNode, name: &str| {
+        node.slots
+            .iter()
+            .find(|slot| slot.slot_name == name)
+            .cloned()
+    };
+    let mut changes = Vec::new();
+    for slot in &a.slots {
+        match find(b, &slot.slot_name) {
+            None => changes.push(SlotChange::Removed { slot: slot.clone() }),
+            Some(new) if new != *slot => changes.push(SlotChange::Changed {
+                from: slot.clone(),
+                to: new,
+            }),
+            Some(_) => {}
+        }
+    }
+    for slot in &b.slots {
+        if find(a, &slot.slot_name).is_none() {
+            changes.push(SlotChange::Added { slot: slot.clone() });
+        }
+    }
+    changes
+}
+
+fn diff_children(a: &[String], b: &[String]) -> Vec<ChildChange> {
+    // lcs[i][j] = length of the LCS of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            changes.push(ChildChange::Inserted {
+                index: j,
+                child_id: b[j].clone(),
+            });
+            j += 1;
+        } else {
+            changes.push(ChildChange::Removed {
+                index: i,
+                child_id: a[i].clone(),
+            });
+            i += 1;
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template_node::{ShadowConfig, ShadowMode};
+
+    fn slot(name: &str, required: bool) -> SlotDefinition {
+        SlotDefinition {
+            slot_name: name.to_string(),
+            fallback_content: None,
+            allowed_types: Vec::new(),
+            required,
+        }
+    }
+
+    fn node(children: &[&str]) -> TemplateNode {
+        let mut node = TemplateNode::new("button".to_string(), "button".to_string())
+            .with_attribute("class".to_string(), "hds-button".to_string())
+            .with_attribute("type".to_string(), "button".to_string())
+            .with_slot(slot("icon", false))
+            .with_slot(slot("", false));
+        node.children = children.iter().map(|c| c.to_string()).collect();
+        node
+    }
+
+    #[test]
+    fn test_identical_templates() {
+        assert!(diff(&node(&["label"]), &node(&["label"])).is_empty());
+    }
+
+    #[test]
+    fn test_attribute_and_slot_changes() {
+        let a = node(&[]);
+        let mut b = node(&[]).with_attribute("aria-busy".to_string(), "false".to_string());
+        b.attributes[0].value = "hds-button hds-button--primary".to_string();
+        b.attributes.remove(1);
+        b.slots[1].required = true;
+        b.slots.remove(0);
+        b.slots.push(slot("suffix", false));
+        b.shadow_config = Some(ShadowConfig {
+            mode: ShadowMode::Open,
+            delegates_focus: false,
+        });
+
+        let changes = diff(&a, &b);
+        assert_eq!(
+            changes.attributes,
+            vec![
+                AttributeChange::Changed {
+                    name: "class".to_string(),
+                    from: "hds-button".to_string(),
+                    to: "hds-button hds-button--primary".to_string(),
+                },
+                AttributeChange::Removed {
+                    name: "type".to_string(),
+                    value: "button".to_string(),
+                },
+                AttributeChange::Added {
+                    name: "aria-busy".to_string(),
+                    value: "false".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            changes.slots,
+            vec![
+                SlotChange::Removed { slot: slot("icon", false) },
+                SlotChange::Changed { from: slot("", false), to: slot("", true) },
+                SlotChange::Added { slot: slot("suffix", false) },
+            ]
+        );
+        assert_eq!(changes.shadow_config.unwrap().from, None);
+        assert!(changes.element_type.is_none());
+    }
+
+    #[test]
+    fn test_child_insertions_and_removals() {
+        let changes = diff(&node(&["icon", "label", "badge"]), &node(&["label", "spinner", "badge"]));
+        assert_eq!(
+            changes.children,
+            vec![
+                ChildChange::Removed { index: 0, child_id: "icon".to_string() },
+                ChildChange::Inserted { index: 1, child_id: "spinner".to_string() },
+            ]
+        );
+
+        let json = serde_json::to_value(&changes).unwrap();
+        assert_eq!(json["children"][1]["kind"], "inserted");
+        assert!(json.get("attributes").is_none());
+    }
+}
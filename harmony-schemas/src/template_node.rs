@@ -7,8 +7,10 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::a11y::{check_template, A11yIssue, A11yMetadata};
+
 /// Template node stored in the graph
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TemplateNode {
     /// Unique identifier for this template
     pub template_id: String,
@@ -30,17 +32,21 @@ pub struct TemplateNode {
     
     /// GPU acceleration metadata
     pub gpu_metadata: Option<GpuMetadata>,
+    
+    /// Accessibility requirements checked against this template
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub a11y: Option<A11yMetadata>,
 }
 
 /// HTML attribute key-value pair
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Attribute {
     pub name: String,
     pub value: String,
 }
 
 /// Slot definition for component composition
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SlotDefinition {
     /// Slot name (empty string for default slot)
     pub slot_name: String,
@@ -56,7 +62,7 @@ pub struct SlotDefinition {
 }
 
 /// Shadow DOM configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ShadowConfig {
     /// Shadow DOM mode (open or closed)
     pub mode: ShadowMode,
@@ -66,7 +72,7 @@ pub struct ShadowConfig {
 }
 
 /// Shadow DOM mode enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ShadowMode {
     Open,
@@ -74,7 +80,7 @@ pub enum ShadowMode {
 }
 
 /// GPU acceleration metadata for template
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GpuMetadata {
     /// Whether this template uses GPU acceleration
     pub gpu_accelerated: bool,
@@ -97,6 +103,7 @@ impl TemplateNode {
             children: Vec::new(),
             shadow_config: None,
             gpu_metadata: None,
+            a11y: None,
         }
     }
     
@@ -123,6 +130,22 @@ impl TemplateNode {
         self.gpu_metadata = Some(metadata);
         self
     }
+    
+    /// Attach accessibility requirements
+    pub fn with_a11y(mut self, metadata: A11yMetadata) -> Self {
+        self.a11y = Some(metadata);
+        self
+    }
+    
+    /// Check the template against its accessibility requirements
+    ///
+    /// Returns no issues when no requirements are attached.
+    pub fn check_a11y(&self) -> Vec<A11yIssue> {
+        self.a11y
+            .as_ref()
+            .map(|metadata| check_template(self, metadata))
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,269 @@
+//! TemplateNode HTML rendering
+//!
+//! Materializes a TemplateNode tree into an HTML string for SSR previews and
+//! template snapshot tests. Child IDs are looked up among the templates
+//! given to the renderer.
+//!
+//! Rendering rules:
+//! - content is the node's children followed by its slots, in order
+//! - a slot renders the content supplied for it, else its fallback; slot
+//!   content only applies to the root template, nested templates always use
+//!   their fallbacks
+//! - a fallback naming a known template renders that template, otherwise
+//!   it is escaped text
+//! - a required slot with neither content nor fallback is an error
+//! - shadow roots are emitted as declarative shadow DOM
+//!   (`<template shadowrootmode="open">`) around the node's content
+//! - void elements (`input`, `img`, ...) have no content or closing tag
+//! - attribute values and fallback text are escaped; supplied slot content
+//!   is trusted HTML and inserted as-is
+//!
+//! Related: docs/architecture/template-storage-strategy.md
+
+use std::collections::HashMap;
+
+use crate::error::SchemaError;
+use crate::template_node::{ShadowMode, SlotDefinition, TemplateNode};
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source",
+    "track", "wbr",
+];
+
+/// Escape text for use in element content
+pub fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Escape text for use in a double-quoted attribute value
+pub fn escape_attribute(value: &str) -> String {
+    escape_text(value).replace('"', "&quot;").replace('\'', "&#39;")
+}
+
+fn is_valid_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+}
+
+/// Renders TemplateNode trees to HTML
+#[derive(Debug, Default)]
+pub struct TemplateRenderer<'a> {
+    templates: HashMap<&'a str, &'a TemplateNode>,
+    slot_content: HashMap<String, String>,
+}
+
+impl<'a> TemplateRenderer<'a> {
+    /// Create a renderer over a set of templates
+    pub fn new(templates: impl IntoIterator<Item = &'a TemplateNode>) -> Self {
+        Self {
+            templates: templates
+                .into_iter()
+                .map(|t| (t.template_id.as_str(), t))
+                .collect(),
+            slot_content: HashMap::new(),
+        }
+    }
+
+    /// Supply HTML for a root template slot ("" is the default slot)
+    pub fn with_slot_content(mut self, slot_name: &str, html: &str) -> Self {
+        self.slot_content.insert(slot_name.to_string(), html.to_string());
+        self
+    }
+
+    /// Render the template with the given ID
+    pub fn render(&self, template_id: &str) -> Result<String, SchemaError> {
+        let mut html = String::new();
+        let mut path = Vec::new();
+        self.render_node(template_id, &mut path, &mut html)?;
+        Ok(html)
+    }
+
+    fn render_node(
+        &self,
+        template_id: &'a str,
+        path: &mut Vec<&'a str>,
+        html: &mut String,
+    ) -> Result<(), SchemaError> {
+        let node = *self
+            .templates
+            .get(template_id)
+            .ok_or_else(|| SchemaError::UnknownTemplate {
+                template_id: template_id.to_string(),
+            })?;
+        if let Some(start) = path.iter().position(|id| *id == template_id) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|id| id.to_string()).collect();
+            cycle.push(template_id.to_string());
+            return Err(SchemaError::TemplateCycle { cycle });
+        }
+        if !is_valid_name(&node.element_type) {
+            return Err(SchemaError::InvalidName {
+                path: format!("{}.element_type", template_id),
+                name: node.element_type.clone(),
+            });
+        }
+
+        html.push('<');
+        html.push_str(&node.element_type);
+        for attribute in &node.attributes {
+            if !is_valid_name(&attribute.name) {
+                return Err(SchemaError::InvalidName {
+                    path: format!("{}.attributes", template_id),
+                    name: attribute.name.clone(),
+                });
+            }
+            html.push_str(&format!(" {}=\"{}\"", attribute.name, escape_attribute(&attribute.value)));
+        }
+        html.push('>');
+
+        let element = node.element_type.to_ascii_lowercase();
+        if VOID_ELEMENTS.contains(&element.as_str()) {
+            if !node.children.is_empty() || !node.slots.is_empty() {
+                return Err(SchemaError::VoidElementContent {
+                    template_id: template_id.to_string(),
+                    element,
+                });
+            }
+            return Ok(());
+        }
+
+        if let Some(shadow) = &node.shadow_config {
+            let mode = match shadow.mode {
+                ShadowMode::Open => "open",
+                ShadowMode::Closed => "closed",
+            };
+            html.push_str(&format!("<template shadowrootmode=\"{}\"", mode));
+            if shadow.delegates_focus {
+                html.push_str(" shadowrootdelegatesfocus");
+            }
+            html.push('>');
+        }
+
+        path.push(node.template_id.as_str());
+        for child in &node.children {
+            self.render_node(child, path, html)?;
+        }
+        for slot in &node.slots {
+            self.render_slot(node, slot, path, html)?;
+        }
+        path.pop();
+
+        if node.shadow_config.is_some() {
+            html.push_str("</template>");
+        }
+        html.push_str(&format!("</{}>", node.element_type));
+        Ok(())
+    }
+
+    fn render_slot(
+        &self,
+        node: &'a TemplateNode,
+        slot: &'a SlotDefinition,
+        path: &mut Vec<&'a str>,
+        html: &mut String,
+    ) -> Result<(), SchemaError> {
+        // Root template is the only entry on the path
+        if path.len() == 1 {
+            if let Some(content) = self.slot_content.get(&slot.slot_name) {
+                html.push_str(content);
+                return Ok(());
+            }
+        }
+        match slot.fallback_content.as_deref() {
+            Some(fallback) if self.templates.contains_key(fallback) => {
+                self.render_node(fallback, path, html)
+            }
+            Some(fallback) => {
+                html.push_str(&escape_text(fallback));
+                Ok(())
+            }
+            None if slot.required => Err(SchemaError::MissingRequiredSlot {
+                template_id: node.template_id.clone(),
+                slot_name: slot.slot_name.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template_node::ShadowConfig;
+
+    fn slot(name: &str, fallback: Option<&str>, required: bool) -> SlotDefinition {
+        SlotDefinition {
+            slot_name: name.to_string(),
+            fallback_content: fallback.map(str::to_string),
+            allowed_types: Vec::new(),
+            required,
+        }
+    }
+
+    fn templates() -> Vec<TemplateNode> {
+        let mut button = TemplateNode::new("button".to_string(), "button".to_string())
+            .with_attribute("class".to_string(), "hds-button".to_string())
+            .with_attribute("title".to_string(), "Say \"hi\" & <wave>".to_string())
+            .with_slot(slot("icon", Some("default-icon"), false))
+            .with_slot(slot("", Some("Click <me>"), false));
+        button.children.push("spinner".to_string());
+        vec![
+            button,
+            TemplateNode::new("spinner".to_string(), "span".to_string())
+                .with_attribute("class".to_string(), "spinner".to_string()),
+            TemplateNode::new("default-icon".to_string(), "svg".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_renders_fallbacks_and_escapes() {
+        let templates = templates();
+        let html = TemplateRenderer::new(&templates).render("button").unwrap();
+        assert_eq!(
+            html,
+            "<button class=\"hds-button\" title=\"Say &quot;hi&quot; &amp; &lt;wave&gt;\">\
+             <span class=\"spinner\"></span><svg></svg>Click &lt;me&gt;</button>"
+        );
+    }
+
+    #[test]
+    fn test_slot_content_and_shadow_root() {
+        let mut templates = templates();
+        templates[0].shadow_config = Some(ShadowConfig {
+            mode: ShadowMode::Open,
+            delegates_focus: true,
+        });
+        let html = TemplateRenderer::new(&templates)
+            .with_slot_content("", "<b>Save</b>")
+            .render("button")
+            .unwrap();
+        assert!(html.contains("<template shadowrootmode=\"open\" shadowrootdelegatesfocus>"));
+        assert!(html.ends_with("<svg></svg><b>Save</b></template></button>"));
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let mut templates = templates();
+        templates[0].slots.push(slot("footer", None, true));
+        let renderer = TemplateRenderer::new(&templates);
+        assert_eq!(
+            renderer.render("button"),
+            Err(SchemaError::MissingRequiredSlot {
+                template_id: "button".to_string(),
+                slot_name: "footer".to_string(),
+            })
+        );
+        assert!(renderer.render("missing").is_err());
+
+        templates[1].children.push("button".to_string());
+        let renderer = TemplateRenderer::new(&templates);
+        assert_eq!(
+            renderer.render("spinner").unwrap_err().to_string(),
+            "Template cycle: spinner -> button -> spinner"
+        );
+
+        let input = TemplateNode::new("field".to_string(), "input".to_string());
+        assert_eq!(TemplateRenderer::new([&input]).render("field").unwrap(), "<input>");
+    }
+}
@@ -0,0 +1,409 @@
+//! Themes and token sets with alias resolution
+//!
+//! A `TokenSet` is a flat map of dotted token paths (`color.primary.500`) to
+//! tokens. A `Theme` layers token sets and its own overrides on top of the
+//! theme it `extends`. Resolution walks the chain from the base theme to the
+//! requested one; later layers replace earlier values for the same path.
+//!
+//! A token whose value is exactly `"{some.path}"` is an alias and resolves to
+//! the effective value of that path, following chains of aliases. Aliases to
+//! undefined paths and alias cycles are reported; the affected tokens (and
+//! any alias depending on them) are left out of the resolved values.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#design-tokens
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A single design token
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Token {
+    /// Literal value, or an alias string such as "{color.primary.500}"
+    pub value: Value,
+
+    /// Token type, e.g. "color", "dimension"
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+
+    /// What the token is for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl Token {
+    /// Create an untyped token
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            token_type: None,
+            description: None,
+        }
+    }
+
+    /// Path this token aliases, if its value is `"{path}"`
+    pub fn alias(&self) -> Option<&str> {
+        self.value
+            .as_str()?
+            .strip_prefix('{')?
+            .strip_suffix('}')
+            .filter(|path| !path.is_empty() && !path.contains(['{', '}']))
+    }
+}
+
+/// Named collection of tokens keyed by dotted path
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TokenSet {
+    pub id: String,
+    #[serde(default)]
+    pub tokens: BTreeMap<String, Token>,
+}
+
+impl TokenSet {
+    /// Create an empty token set
+    pub fn new(id: String) -> Self {
+        Self {
+            id,
+            tokens: BTreeMap::new(),
+        }
+    }
+
+    /// Build a token set from nested token JSON as found in `tokens/*.json`
+    ///
+    /// Every object with a `value` key is a token; its path is the chain of
+    /// keys leading to it.
+    pub fn from_nested(id: String, json: &Value) -> Result<Self, String> {
+        let mut set = Self::new(id);
+        flatten(json, String::new(), &mut set.tokens)?;
+        Ok(set)
+    }
+}
+
+fn flatten(json: &Value, path: String, tokens: &mut BTreeMap<String, Token>) -> Result<(), String> {
+    let Some(object) = json.as_object() else {
+        return Err(format!("Expected an object at '{}'", path));
+    };
+    if object.contains_key("value") {
+        let token = serde_json::from_value(json.clone())
+            .map_err(|e| format!("Invalid token at '{}': {}", path, e))?;
+        tokens.insert(path, token);
+        return Ok(());
+    }
+    for (key, child) in object {
+        let child_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        flatten(child, child_path, tokens)?;
+    }
+    Ok(())
+}
+
+/// Theme built from token sets, overrides and an optional parent theme
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Theme {
+    pub id: String,
+    pub name: String,
+
+    /// Parent theme whose values this theme starts from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+
+    /// Token set ids applied in order
+    #[serde(default)]
+    pub token_sets: Vec<String>,
+
+    /// Tokens applied after the token sets
+    #[serde(default)]
+    pub overrides: BTreeMap<String, Token>,
+}
+
+impl Theme {
+    /// Create a theme with no sets or overrides
+    pub fn new(id: String, name: String) -> Self {
+        Self {
+            id,
+            name,
+            extends: None,
+            token_sets: Vec::new(),
+            overrides: BTreeMap::new(),
+        }
+    }
+}
+
+/// An alias that points at an undefined token
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MissingAlias {
+    /// Token holding the alias
+    pub token: String,
+    /// Undefined path it refers to
+    pub alias: String,
+}
+
+/// Effective token values for a theme
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ResolvedTheme {
+    /// Resolved literal values keyed by path
+    pub tokens: BTreeMap<String, Value>,
+
+    /// Aliases to undefined tokens, ordered by token path
+    pub missing_aliases: Vec<MissingAlias>,
+
+    /// Alias cycles, each listed from its first token in path order
+    pub circular_aliases: Vec<Vec<String>>,
+}
+
+impl ResolvedTheme {
+    /// True when every token resolved
+    pub fn is_complete(&self) -> bool {
+        self.missing_aliases.is_empty() && self.circular_aliases.is_empty()
+    }
+}
+
+/// Holds themes and token sets and resolves theme chains
+#[derive(Debug, Clone, Default)]
+pub struct ThemeResolver {
+    token_sets: HashMap<String, TokenSet>,
+    themes: HashMap<String, Theme>,
+}
+
+impl ThemeResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a token set
+    pub fn add_token_set(&mut self, set: TokenSet) {
+        self.token_sets.insert(set.id.clone(), set);
+    }
+
+    /// Add or replace a theme
+    pub fn add_theme(&mut self, theme: Theme) {
+        self.themes.insert(theme.id.clone(), theme);
+    }
+
+    /// Themes from the root base theme down to `theme_id`
+    pub fn theme_chain(&self, theme_id: &str) -> Result<Vec<&Theme>, String> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut next = Some(theme_id);
+        while let Some(id) = next {
+            if !seen.insert(id) {
+                return Err(format!("Circular theme inheritance at '{}'", id));
+            }
+            let theme = self
+                .themes
+                .get(id)
+                .ok_or_else(|| format!("Unknown theme: {}", id))?;
+            chain.push(theme);
+            next = theme.extends.as_deref();
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+
+    /// Effective token values of a theme, following its `extends` chain
+    pub fn resolve(&self, theme_id: &str) -> Result<ResolvedTheme, String> {
+        self.resolve_chain(&self.theme_chain(theme_id)?)
+    }
+
+    /// Effective token values of an explicit chain, base theme first
+    ///
+    /// `extends` is ignored; the chain alone decides the layering.
+    pub fn resolve_chain(&self, chain: &[&Theme]) -> Result<ResolvedTheme, String> {
+        let mut raw: BTreeMap<&str, &Token> = BTreeMap::new();
+        for theme in chain {
+            for set_id in &theme.token_sets {
+                let set = self
+                    .token_sets
+                    .get(set_id)
+                    .ok_or_else(|| format!("Unknown token set '{}' in theme {}", set_id, theme.id))?;
+                raw.extend(set.tokens.iter().map(|(path, token)| (path.as_str(), token)));
+            }
+            raw.extend(theme.overrides.iter().map(|(path, token)| (path.as_str(), token)));
+        }
+
+        let mut resolution = Resolution {
+            raw: &raw,
+            marks: HashMap::new(),
+            stack: Vec::new(),
+            result: ResolvedTheme::default(),
+        };
+        for path in raw.keys() {
+            resolution.resolve(path);
+        }
+        Ok(resolution.result)
+    }
+}
+
+enum Mark {
+    InProgress,
+    Done(Option<Value>),
+}
+
+struct Resolution<'a> {
+    raw: &'a BTreeMap<&'a str, &'a Token>,
+    marks: HashMap<&'a str, Mark>,
+    stack: Vec<&'a str>,
+    result: ResolvedTheme,
+}
+
+impl<'a> Resolution<'a> {
+    /// Resolves one path depth-first, memoizing the outcome
+    fn resolve(&mut self, path: &'a str) -> Option<Value> {
+        match self.marks.get(path) {
+            Some(Mark::Done(value)) => return value.clone(),
+            Some(Mark::InProgress) => {
+                let start = self.stack.iter().position(|p| *p == path).unwrap_or(0);
+                let cycle = self.stack[start..].iter().map(|p| p.to_string()).collect();
+                self.result.circular_aliases.push(cycle);
+                return None;
+            }
+            None => {}
+        }
+
+        let token = self.raw[path];
+        let value = match token.alias() {
+            None => Some(token.value.clone()),
+            Some(alias) => match self.raw.get_key_value(alias) {
+                None => {
+                    self.result.missing_aliases.push(MissingAlias {
+                        token: path.to_string(),
+                        alias: alias.to_string(),
+                    });
+                    None
+                }
+                Some((target, _)) => {
+                    self.marks.insert(path, Mark::InProgress);
+                    self.stack.push(path);
+                    let value = self.resolve(target);
+                    self.stack.pop();
+                    value
+                }
+            },
+        };
+
+        if let Some(value) = &value {
+            self.result.tokens.insert(path.to_string(), value.clone());
+        }
+        self.marks.insert(path, Mark::Done(value.clone()));
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn token_set(id: &str, tokens: &[(&str, Value)]) -> TokenSet {
+        TokenSet {
+            id: id.to_string(),
+            tokens: tokens
+                .iter()
+                .map(|(path, value)| (path.to_string(), Token::new(value.clone())))
+                .collect(),
+        }
+    }
+
+    fn resolver() -> ThemeResolver {
+        let mut resolver = ThemeResolver::new();
+        resolver.add_token_set(token_set(
+            "palette",
+            &[
+                ("color.blue.500", json!("#2563eb")),
+                ("color.gray.900", json!("#111827")),
+                ("color.gray.50", json!("#f9fafb")),
+            ],
+        ));
+        resolver.add_token_set(token_set(
+            "semantic",
+            &[
+                ("color.primary", json!("{color.blue.500}")),
+                ("color.text", json!("{color.gray.900}")),
+                ("color.link", json!("{color.primary}")),
+            ],
+        ));
+        let mut base = Theme::new("base".to_string(), "Base".to_string());
+        base.token_sets = vec!["palette".to_string(), "semantic".to_string()];
+        let mut dark = Theme::new("dark".to_string(), "Dark".to_string());
+        dark.extends = Some("base".to_string());
+        dark.overrides
+            .insert("color.text".to_string(), Token::new(json!("{color.gray.50}")));
+        resolver.add_theme(base);
+        resolver.add_theme(dark);
+        resolver
+    }
+
+    #[test]
+    fn test_resolves_aliases_through_chain() {
+        let resolved = resolver().resolve("dark").unwrap();
+        assert!(resolved.is_complete());
+        assert_eq!(resolved.tokens["color.text"], json!("#f9fafb"));
+        assert_eq!(resolved.tokens["color.link"], json!("#2563eb"));
+
+        let base = resolver().resolve("base").unwrap();
+        assert_eq!(base.tokens["color.text"], json!("#111827"));
+    }
+
+    #[test]
+    fn test_reports_missing_and_circular_aliases() {
+        let mut resolver = resolver();
+        resolver.add_token_set(token_set(
+            "broken",
+            &[
+                ("a", json!("{b}")),
+                ("b", json!("{c}")),
+                ("c", json!("{a}")),
+                ("d", json!("{a}")),
+                ("color.accent", json!("{color.orange.500}")),
+            ],
+        ));
+        let mut theme = Theme::new("broken".to_string(), "Broken".to_string());
+        theme.extends = Some("base".to_string());
+        theme.token_sets = vec!["broken".to_string()];
+        resolver.add_theme(theme);
+
+        let resolved = resolver.resolve("broken").unwrap();
+        assert_eq!(
+            resolved.missing_aliases,
+            vec![MissingAlias {
+                token: "color.accent".to_string(),
+                alias: "color.orange.500".to_string(),
+            }]
+        );
+        assert_eq!(resolved.circular_aliases, vec![vec!["a", "b", "c"]]);
+        for path in ["a", "b", "c", "d", "color.accent"] {
+            assert!(!resolved.tokens.contains_key(path));
+        }
+        assert_eq!(resolved.tokens["color.link"], json!("#2563eb"));
+    }
+
+    #[test]
+    fn test_theme_chain_errors() {
+        let mut resolver = resolver();
+        assert_eq!(resolver.resolve("missing"), Err("Unknown theme: missing".to_string()));
+
+        let mut loop_theme = Theme::new("base".to_string(), "Base".to_string());
+        loop_theme.extends = Some("dark".to_string());
+        resolver.add_theme(loop_theme);
+        assert!(resolver.theme_chain("dark").unwrap_err().starts_with("Circular"));
+    }
+
+    #[test]
+    fn test_from_nested_json() {
+        let json = json!({
+            "color": {
+                "primary": {
+                    "default": {"value": "{color.blue.500}", "type": "color"}
+                }
+            }
+        });
+        let set = TokenSet::from_nested("semantic".to_string(), &json).unwrap();
+        let token = &set.tokens["color.primary.default"];
+        assert_eq!(token.alias(), Some("color.blue.500"));
+        assert_eq!(token.token_type.as_deref(), Some("color"));
+    }
+}
@@ -0,0 +1,273 @@
+//! Schema versions and payload migrations
+//!
+//! Every serializable structure in this crate implements `Schema`, which
+//! names it and gives its current version. `to_json` writes a payload with a
+//! top-level `schema_version` field; `from_json` reads it back, upgrading
+//! older payloads one version at a time through the migrations registered
+//! for that schema. The version covers the whole payload, nested structures
+//! included. Payloads without `schema_version` predate versioning and are
+//! treated as version 1.
+//!
+//! When a structure changes incompatibly, bump its version in the
+//! `impl_schema!` list below and register a migration from the previous
+//! version in `MigrationRegistry::new`.
+
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::a11y::{A11yIssue, A11yMetadata, AriaRequirement, KeyboardInteraction};
+use crate::component_lifecycle::{
+    Actor, AuthorizationError, BatchTransitionResult, BlockingDependency, StateTransition,
+    TransitionBatch, TransitionResult,
+};
+use crate::component_ui_link::ComponentUILink;
+use crate::design_spec::{A11yRequirement, DesignSpecNode, FigmaReference, PropContract};
+use crate::figma::{FigmaImport, SkippedItem};
+use crate::graph::{Edge, EdgeMetadata, EdgeTypeDefinition, PropertySpec};
+use crate::i18n::{ComponentMessageLink, LocaleDefinition, Message, MessageCatalog};
+use crate::lifecycle_states::{LifecycleEntry, LifecycleHistory, StateMetadata};
+use crate::responsive::{Breakpoint, EffectiveVariant, ResponsiveComponent, ResponsiveOverride};
+use crate::template_node::{Attribute, GpuMetadata, ShadowConfig, SlotDefinition, TemplateNode};
+use crate::theme::{MissingAlias, ResolvedTheme, Theme, Token, TokenSet};
+
+/// Name of the version field in serialized payloads
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// Version assumed for payloads written before versioning
+pub const UNVERSIONED: u32 = 1;
+
+/// A versioned, serializable schema structure
+pub trait Schema: Serialize + DeserializeOwned {
+    /// Stable name used to look up migrations
+    const NAME: &'static str;
+
+    /// Current version written by `to_json`
+    const VERSION: u32;
+}
+
+macro_rules! impl_schema {
+    ($($ty:ty => $name:literal, $version:literal;)*) => {
+        $(
+            impl Schema for $ty {
+                const NAME: &'static str = $name;
+                const VERSION: u32 = $version;
+            }
+        )*
+    };
+}
+
+impl_schema! {
+    A11yMetadata => "a11y_metadata", 1;
+    A11yIssue => "a11y_issue", 1;
+    AriaRequirement => "aria_requirement", 1;
+    KeyboardInteraction => "keyboard_interaction", 1;
+    StateTransition => "state_transition", 1;
+    Actor => "actor", 1;
+    AuthorizationError => "authorization_error", 1;
+    TransitionResult => "transition_result", 1;
+    BlockingDependency => "blocking_dependency", 1;
+    TransitionBatch => "transition_batch", 1;
+    BatchTransitionResult => "batch_transition_result", 1;
+    ComponentUILink => "component_ui_link", 1;
+    PropContract => "prop_contract", 1;
+    A11yRequirement => "a11y_requirement", 1;
+    FigmaReference => "figma_reference", 1;
+    DesignSpecNode => "design_spec_node", 1;
+    Edge => "edge", 1;
+    EdgeMetadata => "edge_metadata", 1;
+    EdgeTypeDefinition => "edge_type_definition", 1;
+    PropertySpec => "property_spec", 1;
+    StateMetadata => "state_metadata", 1;
+    LifecycleEntry => "lifecycle_entry", 1;
+    LifecycleHistory => "lifecycle_history", 1;
+    TemplateNode => "template_node", 1;
+    Attribute => "attribute", 1;
+    SlotDefinition => "slot_definition", 1;
+    ShadowConfig => "shadow_config", 1;
+    GpuMetadata => "gpu_metadata", 1;
+    Breakpoint => "breakpoint", 1;
+    ResponsiveOverride => "responsive_override", 1;
+    ResponsiveComponent => "responsive_component", 1;
+    EffectiveVariant => "effective_variant", 1;
+    Token => "token", 1;
+    TokenSet => "token_set", 1;
+    Theme => "theme", 1;
+    MissingAlias => "missing_alias", 1;
+    ResolvedTheme => "resolved_theme", 1;
+    LocaleDefinition => "locale_definition", 1;
+    Message => "message", 1;
+    MessageCatalog => "message_catalog", 1;
+    ComponentMessageLink => "component_message_link", 1;
+    SkippedItem => "skipped_item", 1;
+    FigmaImport => "figma_import", 1;
+}
+
+/// Upgrades a payload from one version to the next
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+/// Migrations keyed by schema name and the version they upgrade from
+#[derive(Debug, Clone, Default)]
+pub struct MigrationRegistry {
+    migrations: HashMap<(&'static str, u32), Migration>,
+}
+
+impl MigrationRegistry {
+    /// Creates a registry holding the crate's built-in migrations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the migration from `from_version` to `from_version + 1`
+    pub fn register(&mut self, schema: &'static str, from_version: u32, migration: Migration) {
+        self.migrations.insert((schema, from_version), migration);
+    }
+
+    /// Upgrades a payload of `schema` to `target_version`
+    ///
+    /// The returned payload has `schema_version` set to the target.
+    pub fn migrate(&self, schema: &str, mut payload: Value, target_version: u32) -> Result<Value, String> {
+        let mut version = payload_version(&payload)?;
+        if version > target_version {
+            return Err(format!(
+                "{} payload has version {}, newer than supported version {}",
+                schema, version, target_version
+            ));
+        }
+        while version < target_version {
+            let migration = self
+                .migrations
+                .get(&(schema, version))
+                .ok_or_else(|| format!("No migration for {} from version {}", schema, version))?;
+            payload = migration(payload)?;
+            version += 1;
+        }
+        set_version(&mut payload, version)?;
+        Ok(payload)
+    }
+
+    /// Deserializes a payload, migrating it to the current version first
+    pub fn from_value<T: Schema>(&self, payload: Value) -> Result<T, String> {
+        let mut payload = self.migrate(T::NAME, payload, T::VERSION)?;
+        if let Some(object) = payload.as_object_mut() {
+            object.remove(SCHEMA_VERSION_FIELD);
+        }
+        serde_json::from_value(payload).map_err(|e| format!("Invalid {} payload: {}", T::NAME, e))
+    }
+
+    /// Parses and deserializes a JSON payload, migrating it first
+    pub fn from_json<T: Schema>(&self, json: &str) -> Result<T, String> {
+        let payload = serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+        self.from_value(payload)
+    }
+}
+
+fn payload_version(payload: &Value) -> Result<u32, String> {
+    let object = payload
+        .as_object()
+        .ok_or_else(|| "Versioned payload must be a JSON object".to_string())?;
+    match object.get(SCHEMA_VERSION_FIELD) {
+        None => Ok(UNVERSIONED),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| format!("Invalid {}: {}", SCHEMA_VERSION_FIELD, version)),
+    }
+}
+
+fn set_version(payload: &mut Value, version: u32) -> Result<(), String> {
+    payload
+        .as_object_mut()
+        .ok_or_else(|| "Versioned payload must be a JSON object".to_string())?
+        .insert(SCHEMA_VERSION_FIELD.to_string(), Value::from(version));
+    Ok(())
+}
+
+/// Serializes a structure with its current `schema_version`
+pub fn to_value<T: Schema>(value: &T) -> Result<Value, String> {
+    let mut payload = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    set_version(&mut payload, T::VERSION)?;
+    Ok(payload)
+}
+
+/// Serializes a structure to JSON with its current `schema_version`
+pub fn to_json<T: Schema>(value: &T) -> Result<String, String> {
+    serde_json::to_string(&to_value(value)?).map_err(|e| e.to_string())
+}
+
+/// Deserializes JSON using the built-in migrations
+pub fn from_json<T: Schema>(json: &str) -> Result<T, String> {
+    MigrationRegistry::new().from_json(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EdgeType;
+    use serde::Deserialize;
+
+    /// Version 3 of a test schema: v1 had `label`, v2 renamed it to
+    /// `title`, v3 added `tags`
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Widget {
+        title: String,
+        tags: Vec<String>,
+    }
+
+    impl Schema for Widget {
+        const NAME: &'static str = "widget";
+        const VERSION: u32 = 3;
+    }
+
+    fn registry() -> MigrationRegistry {
+        let mut registry = MigrationRegistry::new();
+        registry.register("widget", 1, |mut payload| {
+            let object = payload.as_object_mut().ok_or("expected object")?;
+            let label = object.remove("label").ok_or("missing label")?;
+            object.insert("title".to_string(), label);
+            Ok(payload)
+        });
+        registry.register("widget", 2, |mut payload| {
+            payload["tags"] = Value::Array(Vec::new());
+            Ok(payload)
+        });
+        registry
+    }
+
+    #[test]
+    fn test_round_trip_writes_version() {
+        let edge = Edge::new("e1".to_string(), "a".to_string(), "b".to_string(), EdgeType::UsesToken);
+        let json = to_json(&edge).unwrap();
+        assert!(json.contains(r#""schema_version":1"#));
+
+        let parsed: Edge = from_json(&json).unwrap();
+        assert_eq!(parsed.id, "e1");
+        // Payloads from before versioning still load
+        let legacy: Edge = from_json(r#"{"id":"e2","from":"a","to":"b","edge_type":"composes_of"}"#).unwrap();
+        assert_eq!(legacy.edge_type, EdgeType::ComposesOf);
+    }
+
+    #[test]
+    fn test_migrates_through_each_version() {
+        let registry = registry();
+        let widget: Widget = registry.from_json(r#"{"label":"Save"}"#).unwrap();
+        assert_eq!(widget, Widget { title: "Save".to_string(), tags: vec![] });
+
+        let widget: Widget = registry
+            .from_json(r#"{"schema_version":2,"title":"Open"}"#)
+            .unwrap();
+        assert_eq!(widget.title, "Open");
+    }
+
+    #[test]
+    fn test_rejects_unsupported_versions() {
+        let registry = registry();
+        let newer = registry.from_json::<Widget>(r#"{"schema_version":4,"title":"x","tags":[]}"#);
+        assert!(newer.unwrap_err().contains("newer than supported version 3"));
+
+        let unmigrated = from_json::<Widget>(r#"{"label":"x"}"#);
+        assert_eq!(unmigrated.unwrap_err(), "No migration for widget from version 1");
+    }
+}
@@ -0,0 +1,46 @@
+/**
+ * HarmonyTrace: tracing in the WASM bounded contexts
+ *
+ * Each WASM module linking harmony-trace exports `set_log_level`,
+ * `get_log_level`, `use_console_sink`, `use_ring_buffer_sink` and
+ * `drain_trace_records`. Level and sink are per module. Records at or
+ * below the level go to the console, or to a ring buffer read back with
+ * `drain_trace_records`.
+ *
+ * @module HarmonyTrace
+ */
+
+/**
+ * Log levels, most severe first (matches Rust Level)
+ * @enum {string}
+ */
+export const LogLevel = {
+  OFF: 'off',
+  ERROR: 'error',
+  WARN: 'warn',
+  INFO: 'info',
+  DEBUG: 'debug',
+  TRACE: 'trace',
+};
+
+/**
+ * @typedef {Object} TraceRecord
+ * @property {string} level - LogLevel of the record
+ * @property {string} target - Context that recorded it
+ * @property {string} message - Event message or span name
+ * @property {number} [duration_ms] - How long the span was open
+ * @property {Object<string, string>} [fields] - Span fields
+ * @property {number} timestamp_ms - Milliseconds since the Unix epoch
+ */
+
+/**
+ * Set the same log level on several WASM modules
+ *
+ * @param {Array<{set_log_level: function(string): void}>} modules - WASM exports
+ * @param {string} level - LogLevel
+ */
+export function setLogLevel(modules, level) {
+  for (const module of modules) {
+    module.set_log_level(level);
+  }
+}
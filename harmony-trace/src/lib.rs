@@ -0,0 +1,405 @@
+//! Harmony Trace
+//!
+//! Tracing facade shared by the bounded contexts. Contexts record events
+//! and timed spans (traversals, searches, registrations) under a target
+//! naming the context. Records at or below the current level go to the
+//! installed sink:
+//! - `ConsoleSink` (default) writes to the browser console, or to stderr in
+//!   native builds
+//! - `RingBufferSink` keeps the most recent records for later export with
+//!   `drain`
+//!
+//! The level defaults to `warn`, so a disabled span costs one level check
+//! and formats nothing. Level and sink are per thread: each WASM module has
+//! its own.
+//!
+//! With the `wasm` feature (default), every module linking this crate
+//! exports `set_log_level`, `get_log_level`, `use_console_sink`,
+//! `use_ring_buffer_sink` and `drain_trace_records` (see harmony-trace.js).
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+use harmony_error::HarmonyError;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Severity of a record, most severe first
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    /// Lowercase name, as used by `set_log_level`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Level {
+    type Err = HarmonyError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(HarmonyError::InvalidInput(format!(
+                "Unknown log level '{}'",
+                name
+            ))),
+        }
+    }
+}
+
+/// One event, or one finished span
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraceRecord {
+    pub level: Level,
+    /// Context that recorded it, e.g. "wasm-edge-executor"
+    pub target: String,
+    /// Event message or span name
+    pub message: String,
+    /// How long the span was open; absent for events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<f64>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub fields: BTreeMap<String, String>,
+    /// Milliseconds since the Unix epoch when it was recorded
+    pub timestamp_ms: f64,
+}
+
+impl fmt::Display for TraceRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.target, self.message)?;
+        if let Some(duration_ms) = self.duration_ms {
+            write!(f, " ({:.3}ms)", duration_ms)?;
+        }
+        for (key, value) in &self.fields {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Destination for records that pass the level check
+pub trait Sink {
+    fn record(&mut self, record: TraceRecord);
+
+    /// Takes the records kept for export; sinks that keep none return none
+    fn drain(&mut self) -> Vec<TraceRecord> {
+        Vec::new()
+    }
+}
+
+/// Writes each record to the console (stderr in native builds)
+#[derive(Debug, Default)]
+pub struct ConsoleSink;
+
+impl Sink for ConsoleSink {
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    fn record(&mut self, record: TraceRecord) {
+        let line = JsValue::from_str(&record.to_string());
+        match record.level {
+            Level::Error => web_sys::console::error_1(&line),
+            Level::Warn => web_sys::console::warn_1(&line),
+            Level::Info => web_sys::console::info_1(&line),
+            Level::Debug | Level::Trace => web_sys::console::debug_1(&line),
+        }
+    }
+
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+    fn record(&mut self, record: TraceRecord) {
+        eprintln!("{:>5} {}", record.level.as_str().to_uppercase(), record);
+    }
+}
+
+/// Keeps the last `capacity` records, dropping the oldest
+#[derive(Debug)]
+pub struct RingBufferSink {
+    capacity: usize,
+    records: VecDeque<TraceRecord>,
+}
+
+impl RingBufferSink {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Records kept, oldest first
+    pub fn records(&self) -> impl Iterator<Item = &TraceRecord> {
+        self.records.iter()
+    }
+}
+
+impl Sink for RingBufferSink {
+    fn record(&mut self, record: TraceRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    fn drain(&mut self) -> Vec<TraceRecord> {
+        self.records.drain(..).collect()
+    }
+}
+
+thread_local! {
+    // None when tracing is off
+    static LEVEL: Cell<Option<Level>> = const { Cell::new(Some(Level::Warn)) };
+    static SINK: RefCell<Box<dyn Sink>> = RefCell::new(Box::new(ConsoleSink));
+}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+/// Set the most verbose level recorded; `None` turns tracing off
+pub fn set_level(level: Option<Level>) {
+    LEVEL.with(|current| current.set(level));
+}
+
+/// The most verbose level recorded, or `None` when tracing is off
+pub fn level() -> Option<Level> {
+    LEVEL.with(Cell::get)
+}
+
+/// Check whether records at `level` reach the sink
+pub fn enabled(level: Level) -> bool {
+    self::level().is_some_and(|max| level <= max)
+}
+
+/// Replace the sink, dropping any records the old one kept
+pub fn set_sink(sink: impl Sink + 'static) {
+    SINK.with(|current| *current.borrow_mut() = Box::new(sink));
+}
+
+/// Take the records kept by the sink, oldest first
+pub fn drain() -> Vec<TraceRecord> {
+    SINK.with(|sink| sink.borrow_mut().drain())
+}
+
+fn emit(record: TraceRecord) {
+    SINK.with(|sink| {
+        // A sink that traces while recording would re-enter; drop its records
+        if let Ok(mut sink) = sink.try_borrow_mut() {
+            sink.record(record);
+        }
+    });
+}
+
+/// Record an event; `message` is only formatted when `level` is enabled
+pub fn event(level: Level, target: &str, message: impl fmt::Display) {
+    if !enabled(level) {
+        return;
+    }
+    emit(TraceRecord {
+        level,
+        target: target.to_string(),
+        message: message.to_string(),
+        duration_ms: None,
+        fields: BTreeMap::new(),
+        timestamp_ms: now_ms(),
+    });
+}
+
+/// Open a span, recorded with its duration when dropped
+pub fn span(level: Level, target: &str, name: &str) -> Span {
+    let inner = enabled(level).then(|| SpanInner {
+        level,
+        target: target.to_string(),
+        name: name.to_string(),
+        fields: BTreeMap::new(),
+        start_ms: now_ms(),
+    });
+    Span { inner }
+}
+
+/// A timed operation; does nothing when its level was disabled on opening
+#[must_use = "a span is recorded when it is dropped"]
+#[derive(Debug)]
+pub struct Span {
+    inner: Option<SpanInner>,
+}
+
+#[derive(Debug)]
+struct SpanInner {
+    level: Level,
+    target: String,
+    name: String,
+    fields: BTreeMap<String, String>,
+    start_ms: f64,
+}
+
+impl Span {
+    /// Check whether the span will be recorded
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Attach a field, replacing any with the same key
+    pub fn field(&mut self, key: &str, value: impl fmt::Display) -> &mut Self {
+        if let Some(inner) = &mut self.inner {
+            inner.fields.insert(key.to_string(), value.to_string());
+        }
+        self
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.take() {
+            let end_ms = now_ms();
+            emit(TraceRecord {
+                level: inner.level,
+                target: inner.target,
+                message: inner.name,
+                duration_ms: Some((end_ms - inner.start_ms).max(0.0)),
+                fields: inner.fields,
+                timestamp_ms: end_ms,
+            });
+        }
+    }
+}
+
+/// Set the log level by name: "error", "warn", "info", "debug", "trace"
+/// or "off"
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn set_log_level(level: &str) -> Result<(), HarmonyError> {
+    if level.eq_ignore_ascii_case("off") {
+        set_level(None);
+    } else {
+        set_level(Some(level.parse()?));
+    }
+    Ok(())
+}
+
+/// The current log level name, or "off"
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_log_level() -> String {
+    level().map_or("off", Level::as_str).to_string()
+}
+
+/// Send records to the console
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn use_console_sink() {
+    set_sink(ConsoleSink);
+}
+
+/// Keep the last `capacity` records for `drain_trace_records`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn use_ring_buffer_sink(capacity: usize) {
+    set_sink(RingBufferSink::new(capacity));
+}
+
+/// Take the records kept by the ring buffer sink, oldest first
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(unchecked_return_type = "TraceRecord[]")]
+pub fn drain_trace_records() -> Result<JsValue, HarmonyError> {
+    Ok(drain().serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_filter_records() {
+        use_ring_buffer_sink(8);
+        assert_eq!(get_log_level(), "warn");
+
+        event(Level::Warn, "spatial-index", "Node out of bounds");
+        event(Level::Debug, "spatial-index", format_args!("{} nodes", 3));
+        set_log_level("DEBUG").unwrap();
+        event(Level::Debug, "spatial-index", format_args!("{} nodes", 3));
+
+        let records = drain();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].message, "Node out of bounds");
+        assert_eq!(records[1].level, Level::Debug);
+        assert_eq!(records[1].message, "3 nodes");
+        assert!(drain().is_empty());
+
+        set_log_level("off").unwrap();
+        assert!(!enabled(Level::Error));
+        assert_eq!(get_log_level(), "off");
+
+        let error = set_log_level("loud").unwrap_err();
+        assert_eq!(error.code(), "invalid_input");
+    }
+
+    #[test]
+    fn test_spans_and_ring_buffer() {
+        set_sink(RingBufferSink::new(2));
+        set_level(Some(Level::Trace));
+
+        for query in ["low", "high", "band"] {
+            let mut span = span(Level::Debug, "full-text-index", "search");
+            span.field("query", query).field("results", 1);
+        }
+        // The level is checked when a span opens, not when it closes
+        let open = span(Level::Trace, "full-text-index", "search");
+        assert!(open.is_enabled());
+        set_level(Some(Level::Info));
+        assert!(!span(Level::Debug, "full-text-index", "search").is_enabled());
+        drop(open);
+
+        let records = drain();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].fields["query"], "band");
+        assert_eq!(records[0].fields["results"], "1");
+        assert!(records[0].duration_ms.is_some());
+        assert!(records[1].fields.is_empty());
+        assert_eq!(
+            records[0].to_string(),
+            format!(
+                "[full-text-index] search ({:.3}ms) query=band results=1",
+                records[0].duration_ms.unwrap()
+            )
+        );
+    }
+}
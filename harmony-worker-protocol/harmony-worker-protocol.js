@@ -0,0 +1,186 @@
+/**
+ * HarmonyWorkerProtocol: bounded contexts in dedicated workers
+ *
+ * The main thread talks to a worker through a `WorkerClient`; the worker
+ * answers with `serveWorker` around the `WorkerDispatcher` of its WASM
+ * module. Each message is `{ request | response, buffers }`:
+ * - `request`: `{ version, seq, target, method, args, payloads }`
+ * - `response`: `{ seq, status: 'ok', result, payloads }` or
+ *   `{ seq, status: 'error', error: { code, message, context } }`
+ * - `payloads`: `{ name, byteLength }` for each entry of `buffers`, which
+ *   are transferred, not copied
+ *
+ * Method and argument names follow each context's JS API; see the handlers
+ * in src/handlers.rs.
+ *
+ * @module HarmonyWorkerProtocol
+ * @see {@link file://./DESIGN_SYSTEM.md#worker-protocol Worker Protocol}
+ */
+
+import { ErrorCode } from '../harmony-error/harmony-error.js';
+
+/** Protocol version sent with each request (matches Rust PROTOCOL_VERSION) */
+export const PROTOCOL_VERSION = 1;
+
+/**
+ * Contexts a request can address (matches Rust Target)
+ * @enum {string}
+ */
+export const Target = {
+  EDGE_EXECUTOR: 'edge_executor',
+  SPATIAL_INDEX: 'spatial_index',
+  FULL_TEXT_INDEX: 'full_text_index',
+  AUDIO_GRAPH: 'audio_graph',
+  NODE_IDS: 'node_ids',
+};
+
+/**
+ * @typedef {Object} CallResult
+ * @property {unknown} result - JSON result of the method
+ * @property {Object<string, Uint8Array>} payloads - Binary results by name
+ */
+
+/**
+ * Error shaped like the WASM contexts' HarmonyError
+ *
+ * @param {{code: string, message: string, context?: string[]}} error
+ * @returns {Error}
+ */
+function toHarmonyError({ code, message, context = [] }) {
+  const error = new Error(context.length > 0 ? `${context.join(': ')}: ${message}` : message);
+  error.name = 'HarmonyError';
+  error.code = code;
+  error.context = context;
+  return error;
+}
+
+/**
+ * Buffer to transfer for a payload
+ *
+ * A typed array spanning its whole buffer transfers that buffer (detaching
+ * it for the sender); a view of part of a buffer is copied first.
+ *
+ * @param {ArrayBuffer|ArrayBufferView} payload
+ * @returns {ArrayBuffer}
+ */
+function transferable(payload) {
+  if (payload instanceof ArrayBuffer) {
+    return payload;
+  }
+  const { buffer, byteOffset, byteLength } = payload;
+  if (byteOffset === 0 && byteLength === buffer.byteLength) {
+    return buffer;
+  }
+  return buffer.slice(byteOffset, byteOffset + byteLength);
+}
+
+/**
+ * Main-thread end of the protocol for one worker
+ */
+export class WorkerClient {
+  /**
+   * @param {Worker} worker - Worker running `serveWorker`
+   */
+  constructor(worker) {
+    this.worker = worker;
+    this.nextSeq = 1;
+    /** @type {Map<number, {resolve: Function, reject: Function}>} */
+    this.pending = new Map();
+    this.worker.addEventListener('message', (event) => this.#receive(event.data));
+    this.worker.addEventListener('error', (event) => {
+      this.#rejectAll(toHarmonyError({ code: ErrorCode.INTERNAL, message: event.message ?? 'Worker error' }));
+    });
+  }
+
+  /**
+   * Call a method of a context in the worker
+   *
+   * @param {string} target - Target
+   * @param {string} method - Method name, e.g. 'traverseBFS'
+   * @param {Object} [args={}] - Named arguments
+   * @param {Object<string, ArrayBuffer|ArrayBufferView>} [payloads={}] -
+   *   Binary arguments by name; their buffers are transferred
+   * @returns {Promise<CallResult>}
+   * @throws {Error} HarmonyError from the handler, with 'target.method' as
+   *   outermost context
+   */
+  call(target, method, args = {}, payloads = {}) {
+    const seq = this.nextSeq;
+    this.nextSeq = (this.nextSeq % 0xffffffff) + 1;
+    const buffers = Object.values(payloads).map(transferable);
+    const request = {
+      version: PROTOCOL_VERSION,
+      seq,
+      target,
+      method,
+      args,
+      payloads: Object.keys(payloads).map((name, i) => ({ name, byteLength: buffers[i].byteLength })),
+    };
+    return new Promise((resolve, reject) => {
+      this.pending.set(seq, { resolve, reject });
+      this.worker.postMessage({ request, buffers }, buffers);
+    });
+  }
+
+  /**
+   * Stop the worker, rejecting every pending call
+   */
+  terminate() {
+    this.worker.terminate();
+    this.#rejectAll(toHarmonyError({ code: ErrorCode.INTERNAL, message: 'Worker terminated' }));
+  }
+
+  /**
+   * @private
+   * @param {{response: Object, buffers: Uint8Array[]}} message
+   */
+  #receive({ response, buffers }) {
+    const call = this.pending.get(response.seq);
+    if (!call) {
+      console.warn(`[WorkerClient] Response to unknown request ${response.seq}`);
+      return;
+    }
+    this.pending.delete(response.seq);
+    if (response.status === 'ok') {
+      const payloads = {};
+      response.payloads.forEach(({ name }, i) => {
+        payloads[name] = buffers[i];
+      });
+      call.resolve({ result: response.result, payloads });
+    } else {
+      call.reject(toHarmonyError(response.error));
+    }
+  }
+
+  /**
+   * @private
+   * @param {Error} error
+   */
+  #rejectAll(error) {
+    for (const { reject } of this.pending.values()) {
+      reject(error);
+    }
+    this.pending.clear();
+  }
+}
+
+/**
+ * Answer requests in a worker
+ *
+ * @param {{dispatch: function(Object, Array): {response: Object, buffers: Uint8Array[]}}} dispatcher -
+ *   WorkerDispatcher of the worker's WASM module
+ * @param {DedicatedWorkerGlobalScope} [scope=self] - Worker global scope
+ *
+ * @example
+ * // worker.js
+ * import init, { WorkerDispatcher } from './pkg/harmony_worker_protocol.js';
+ * import { serveWorker } from './harmony-worker-protocol.js';
+ * await init();
+ * serveWorker(new WorkerDispatcher());
+ */
+export function serveWorker(dispatcher, scope = self) {
+  scope.addEventListener('message', ({ data: { request, buffers } }) => {
+    const reply = dispatcher.dispatch(request, buffers);
+    scope.postMessage(reply, reply.buffers.map((buffer) => buffer.buffer));
+  });
+}
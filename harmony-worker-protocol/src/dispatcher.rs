@@ -0,0 +1,380 @@
+//! Routes requests to the handler of their target
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use harmony_error::{HarmonyError, ResultExt};
+use harmony_trace::Level;
+#[cfg(feature = "wasm")]
+use js_sys::{Array, Object, Reflect, Uint8Array};
+#[cfg(feature = "wasm")]
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use serde_wasm_bindgen::Serializer;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::message::{Call, Outcome, Payloads, Reply, Request, Response, Target, PROTOCOL_VERSION};
+
+/// harmony-trace target of the dispatcher's spans
+const TRACE_TARGET: &str = "worker-protocol";
+
+/// Runs the calls addressed to one target
+///
+/// Handlers are `Send` so a dispatcher can be moved to the thread that
+/// serves it.
+pub trait Handler: Send {
+    /// Handles one call
+    ///
+    /// # Returns
+    /// `unsupported` for an unknown method (see `Call::unsupported`)
+    fn handle(&mut self, call: Call) -> Result<Outcome, HarmonyError>;
+}
+
+/// A handler shared with other handlers, e.g. the edge executor that the
+/// spatial index's graph-aware queries read
+impl<H: Handler> Handler for Arc<Mutex<H>> {
+    fn handle(&mut self, call: Call) -> Result<Outcome, HarmonyError> {
+        self.lock()
+            .map_err(|_| {
+                HarmonyError::Internal("Handler poisoned by an earlier panic".to_string())
+            })?
+            .handle(call)
+    }
+}
+
+/// Handlers by target, answering requests in arrival order
+#[derive(Default)]
+pub struct Dispatcher {
+    handlers: HashMap<Target, Box<dyn Handler>>,
+}
+
+impl Dispatcher {
+    /// Dispatcher without handlers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Dispatcher with a fresh handler for every context enabled by features
+    ///
+    /// With both features, the spatial index handler shares the edge
+    /// executor (see `queryNearestReachable`).
+    pub fn with_default_handlers() -> Self {
+        let mut dispatcher = Self::new();
+        #[cfg(feature = "edge-executor")]
+        let executor = Arc::new(Mutex::new(wasm_edge_executor::WASMEdgeExecutor::new()));
+        #[cfg(feature = "edge-executor")]
+        dispatcher.register(Target::EdgeExecutor, Arc::clone(&executor));
+        #[cfg(all(feature = "spatial-index", feature = "edge-executor"))]
+        dispatcher.register(
+            Target::SpatialIndex,
+            crate::handlers::SpatialIndexHandler::with_executor(executor),
+        );
+        #[cfg(all(feature = "spatial-index", not(feature = "edge-executor")))]
+        dispatcher.register(
+            Target::SpatialIndex,
+            crate::handlers::SpatialIndexHandler::new(),
+        );
+        #[cfg(feature = "full-text-index")]
+        dispatcher.register(Target::FullTextIndex, crate::handlers::FullTextIndexHandler);
+        #[cfg(feature = "audio-graph")]
+        dispatcher.register(
+            Target::AudioGraph,
+            crate::handlers::AudioGraphHandler::default(),
+        );
+        dispatcher.register(Target::NodeIds, crate::handlers::NodeIdsHandler);
+        dispatcher
+    }
+
+    /// Sets the handler for `target`, replacing any previous one
+    pub fn register(&mut self, target: Target, handler: impl Handler + 'static) -> &mut Self {
+        self.handlers.insert(target, Box::new(handler));
+        self
+    }
+
+    /// Handles a request and the buffers of its transfer list
+    ///
+    /// Never fails: errors are returned as an error reply, with
+    /// "target.method" as context.
+    ///
+    /// # Returns
+    /// The response and the buffers of its transfer list
+    pub fn dispatch(
+        &mut self,
+        request: Request,
+        buffers: Vec<Vec<u8>>,
+    ) -> (Response, Vec<Vec<u8>>) {
+        let mut span = harmony_trace::span(Level::Debug, TRACE_TARGET, "dispatch");
+        span.field("target", request.target.name())
+            .field("method", &request.method);
+        let seq = request.seq;
+        let what = format!("{}.{}", request.target.name(), request.method);
+        match self.run(request, buffers).context(what) {
+            Ok(outcome) => {
+                let (payloads, buffers) = outcome.payloads.into_parts();
+                let reply = Reply::Ok {
+                    result: outcome.result,
+                    payloads,
+                };
+                (Response { seq, reply }, buffers)
+            }
+            Err(error) => {
+                span.field("error", error.code());
+                (error_response(seq, &error), Vec::new())
+            }
+        }
+    }
+
+    fn run(&mut self, request: Request, buffers: Vec<Vec<u8>>) -> Result<Outcome, HarmonyError> {
+        if request.version > PROTOCOL_VERSION {
+            return Err(HarmonyError::Unsupported(format!(
+                "Protocol version {} is newer than {}",
+                request.version, PROTOCOL_VERSION
+            )));
+        }
+        let handler = self.handlers.get_mut(&request.target).ok_or_else(|| {
+            HarmonyError::NotFound("No handler for this target in the worker".to_string())
+        })?;
+        let payloads = Payloads::from_parts(&request.payloads, buffers)?;
+        handler.handle(Call {
+            method: request.method,
+            args: request.args,
+            payloads,
+        })
+    }
+}
+
+fn error_response(seq: u32, error: &HarmonyError) -> Response {
+    Response {
+        seq,
+        reply: Reply::Error {
+            error: error.to_json(),
+        },
+    }
+}
+
+/// Dispatcher for a worker's WASM module; see harmony-worker-protocol.js
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub struct WorkerDispatcher {
+    dispatcher: Dispatcher,
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl WorkerDispatcher {
+    /// Dispatcher with every context enabled in this build
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            dispatcher: Dispatcher::with_default_handlers(),
+        }
+    }
+
+    /// Handles one request
+    ///
+    /// # Arguments
+    /// * `request` - Request object
+    /// * `buffers` - ArrayBuffers or typed arrays, in the order of the
+    ///   request's payload descriptors
+    ///
+    /// # Returns
+    /// `{response, buffers}`, buffers being the Uint8Arrays to post (and
+    /// transfer) with the response
+    #[wasm_bindgen(unchecked_return_type = "{ response: object; buffers: Uint8Array[] }")]
+    pub fn dispatch(&mut self, request: JsValue, buffers: Array) -> Result<JsValue, HarmonyError> {
+        let (response, buffers) = match serde_wasm_bindgen::from_value::<Request>(request.clone()) {
+            Ok(request) => {
+                let buffers = buffers
+                    .iter()
+                    .map(|buffer| Uint8Array::new(&buffer).to_vec())
+                    .collect();
+                self.dispatcher.dispatch(request, buffers)
+            }
+            Err(error) => {
+                let seq = Reflect::get(&request, &"seq".into())
+                    .ok()
+                    .and_then(|seq| seq.as_f64())
+                    .unwrap_or(0.0) as u32;
+                let error = HarmonyError::from(error).with_context("Invalid request");
+                (error_response(seq, &error), Vec::new())
+            }
+        };
+        let response = response.serialize(&Serializer::json_compatible())?;
+        let buffers: Array = buffers
+            .iter()
+            .map(|buffer| Uint8Array::from(buffer.as_slice()))
+            .collect();
+        // Setting properties on a fresh plain object cannot fail
+        let reply = Object::new();
+        let _ = Reflect::set(&reply, &"response".into(), &response);
+        let _ = Reflect::set(&reply, &"buffers".into(), &buffers);
+        Ok(reply.into())
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl Default for WorkerDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, feature = "edge-executor"))]
+mod tests {
+    use serde_json::{json, Value};
+    use wasm_edge_executor::WASMEdgeExecutor;
+
+    use super::*;
+    use crate::message::PayloadDescriptor;
+
+    fn request(seq: u32, method: &str, args: Value) -> Request {
+        serde_json::from_value(json!({
+            "seq": seq,
+            "target": "edge_executor",
+            "method": method,
+            "args": args,
+        }))
+        .unwrap()
+    }
+
+    fn error(response: &Response) -> &Value {
+        match &response.reply {
+            Reply::Error { error } => error,
+            Reply::Ok { .. } => panic!("expected an error reply: {:?}", response),
+        }
+    }
+
+    #[test]
+    fn test_dispatch_with_payloads() {
+        let mut dispatcher = Dispatcher::with_default_handlers();
+        let mut source = WASMEdgeExecutor::new();
+        source.add_edge(1, 2, 0);
+        source.add_edge(2, 3, 0);
+        let edges = source.export_edges();
+
+        let mut load = request(7, "loadEdges", Value::Null);
+        load.payloads = vec![PayloadDescriptor {
+            name: "edges".to_string(),
+            byte_length: edges.len(),
+        }];
+        let (response, buffers) = dispatcher.dispatch(load, vec![edges.clone()]);
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            json!({ "seq": 7, "status": "ok", "result": 2, "payloads": [] })
+        );
+        assert!(buffers.is_empty());
+
+        let traverse = request(8, "traverseBFS", json!({ "start": 1, "maxDepth": 5 }));
+        let (response, _) = dispatcher.dispatch(traverse, Vec::new());
+        match response.reply {
+            Reply::Ok { result, .. } => assert_eq!(result["nodes"], json!([1, 2, 3])),
+            Reply::Error { error } => panic!("unexpected error {}", error),
+        }
+        let args = json!({ "start": 3, "maxDepth": 5, "direction": "incoming" });
+        let (response, _) = dispatcher.dispatch(request(9, "traverseBFS", args), Vec::new());
+        match response.reply {
+            Reply::Ok { result, .. } => assert_eq!(result["nodes"], json!([3, 2, 1])),
+            Reply::Error { error } => panic!("unexpected error {}", error),
+        }
+        let args = json!({ "nodeId": 2, "depth": 1 });
+        let (_, buffers) = dispatcher.dispatch(request(9, "neighborhood", args), Vec::new());
+        let neighborhood = wasm_edge_executor::TraversalResult::from_bytes(&buffers[0]).unwrap();
+        assert_eq!(neighborhood.nodes, vec![2, 3, 1]);
+
+        let (response, buffers) =
+            dispatcher.dispatch(request(10, "exportEdges", Value::Null), Vec::new());
+        assert_eq!(buffers, vec![edges.clone()]);
+        assert_eq!(
+            response.reply,
+            Reply::Ok {
+                result: Value::Null,
+                payloads: vec![PayloadDescriptor {
+                    name: "edges".to_string(),
+                    byte_length: edges.len(),
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_weight_payloads() {
+        let mut dispatcher = Dispatcher::with_default_handlers();
+        for (seq, (source, target)) in [(1, 2), (2, 3)].into_iter().enumerate() {
+            let args = json!({ "source": source, "target": target, "edgeType": 0 });
+            dispatcher.dispatch(request(seq as u32, "addEdge", args), Vec::new());
+        }
+        let indices: Vec<u8> = 1u32.to_le_bytes().to_vec();
+        let weights: Vec<u8> = 0.25f32.to_le_bytes().to_vec();
+        let mut update = request(3, "updateWeights", Value::Null);
+        update.payloads = vec![
+            PayloadDescriptor {
+                name: "indices".to_string(),
+                byte_length: 4,
+            },
+            PayloadDescriptor {
+                name: "weights".to_string(),
+                byte_length: 4,
+            },
+        ];
+        let (response, _) = dispatcher.dispatch(update.clone(), vec![indices, weights]);
+        assert!(matches!(response.reply, Reply::Ok { .. }), "{:?}", response);
+
+        let (_, buffers) =
+            dispatcher.dispatch(request(4, "exportWeights", Value::Null), Vec::new());
+        let expected: Vec<u8> = [1.0f32, 0.25]
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .collect();
+        assert_eq!(buffers, vec![expected]);
+
+        // Past the last edge: nothing changes
+        let (response, _) = dispatcher.dispatch(
+            update,
+            vec![2u32.to_le_bytes().to_vec(), 5.0f32.to_le_bytes().to_vec()],
+        );
+        assert_eq!(error(&response)["code"], "invalid_input");
+        let (response, _) =
+            dispatcher.dispatch(request(5, "getWeight", json!({ "index": 1 })), Vec::new());
+        match response.reply {
+            Reply::Ok { result, .. } => assert_eq!(result, json!(0.25)),
+            Reply::Error { error } => panic!("unexpected error {}", error),
+        }
+    }
+
+    #[test]
+    fn test_dispatcher_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Dispatcher>();
+    }
+
+    #[test]
+    fn test_error_replies() {
+        let mut dispatcher = Dispatcher::with_default_handlers();
+        let (response, _) = dispatcher.dispatch(request(1, "frobnicate", Value::Null), Vec::new());
+        assert_eq!(response.seq, 1);
+        assert_eq!(error(&response)["code"], "unsupported");
+        assert_eq!(
+            error(&response)["context"],
+            json!(["edge_executor.frobnicate"])
+        );
+
+        let (response, _) =
+            dispatcher.dispatch(request(2, "loadEdges", Value::Null), vec![vec![0; 12]]);
+        assert_eq!(error(&response)["code"], "malformed");
+
+        let (response, _) =
+            dispatcher.dispatch(request(3, "traverseBFS", json!({ "start": 1 })), Vec::new());
+        assert_eq!(error(&response)["code"], "invalid_input");
+        assert_eq!(error(&response)["message"], "Missing argument 'maxDepth'");
+
+        let mut newer = request(4, "edgeCount", Value::Null);
+        newer.version = PROTOCOL_VERSION + 1;
+        let (response, _) = dispatcher.dispatch(newer, Vec::new());
+        assert_eq!(error(&response)["code"], "unsupported");
+
+        let (response, _) =
+            Dispatcher::new().dispatch(request(5, "edgeCount", Value::Null), Vec::new());
+        assert_eq!(error(&response)["code"], "not_found");
+    }
+}
@@ -0,0 +1,806 @@
+//! Handlers running each context inside a worker
+//!
+//! Method names and argument names follow the contexts' JS APIs, with
+//! buffers as named payloads instead of `Uint8Array` arguments. Audio
+//! samples are f32 little-endian, interleaved.
+
+#[cfg(feature = "audio-graph")]
+pub use self::audio_graph::AudioGraphHandler;
+#[cfg(feature = "full-text-index")]
+pub use self::full_text::FullTextIndexHandler;
+pub use self::node_ids::NodeIdsHandler;
+#[cfg(feature = "spatial-index")]
+pub use self::spatial::SpatialIndexHandler;
+
+mod node_ids {
+    use harmony_error::HarmonyError;
+    use harmony_ids::NodeIdMap;
+
+    use crate::dispatcher::Handler;
+    use crate::message::{Call, Outcome};
+
+    /// Runs the worker module's node ID map, shared by all its contexts
+    #[derive(Default)]
+    pub struct NodeIdsHandler;
+
+    impl Handler for NodeIdsHandler {
+        /// Methods: intern (name), internAll (names), id (name), name (id),
+        /// names (ids), export, import (map), clear; as the harmony-ids
+        /// exports, with `import` taking the exported object
+        fn handle(&mut self, call: Call) -> Result<Outcome, HarmonyError> {
+            match call.method.as_str() {
+                "intern" => {
+                    let name: String = call.arg("name")?;
+                    Outcome::value(harmony_ids::intern(&name)?)
+                }
+                "internAll" => Outcome::value(harmony_ids::intern_node_ids(call.arg("names")?)?),
+                "id" => {
+                    let name: String = call.arg("name")?;
+                    Outcome::value(harmony_ids::id(&name))
+                }
+                "name" => Outcome::value(harmony_ids::name(call.arg("id")?)),
+                "names" => Outcome::value(harmony_ids::node_names(call.arg("ids")?)?),
+                "export" => Outcome::value(harmony_ids::node_ids()),
+                "import" => {
+                    let map: NodeIdMap = call.arg("map")?;
+                    harmony_ids::set_node_ids(map);
+                    Outcome::value(())
+                }
+                "clear" => {
+                    harmony_ids::clear_node_ids();
+                    Outcome::value(())
+                }
+                _ => Err(call.unsupported()),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use serde_json::json;
+
+        use super::*;
+
+        #[test]
+        fn test_node_ids_handler() {
+            let mut handler = NodeIdsHandler;
+            handler.handle(Call::new("clear", json!({}))).unwrap();
+            let ids = handler
+                .handle(Call::new(
+                    "internAll",
+                    json!({ "names": ["osc", "filter"] }),
+                ))
+                .unwrap();
+            assert_eq!(ids.result, json!([0, 1]));
+            let name = handler
+                .handle(Call::new("name", json!({ "id": 1 })))
+                .unwrap();
+            assert_eq!(name.result, "filter");
+            let missing = handler
+                .handle(Call::new("id", json!({ "name": "lfo" })))
+                .unwrap();
+            assert_eq!(missing.result, json!(null));
+
+            let map = handler
+                .handle(Call::new("export", json!({})))
+                .unwrap()
+                .result;
+            handler.handle(Call::new("clear", json!({}))).unwrap();
+            handler
+                .handle(Call::new("import", json!({ "map": map })))
+                .unwrap();
+            let names = handler
+                .handle(Call::new("names", json!({ "ids": [1, 0] })))
+                .unwrap();
+            assert_eq!(names.result, json!(["filter", "osc"]));
+            let error = handler
+                .handle(Call::new(
+                    "import",
+                    json!({ "map": { "names": ["a", "a"] } }),
+                ))
+                .unwrap_err();
+            assert_eq!(error.code(), "malformed");
+        }
+    }
+}
+
+#[cfg(feature = "edge-executor")]
+mod edge_executor {
+    use harmony_error::HarmonyError;
+    use wasm_edge_executor::WASMEdgeExecutor;
+
+    use crate::dispatcher::Handler;
+    use crate::message::{Call, Outcome};
+
+    /// Little-endian 4-byte words of a payload
+    fn words(what: &str, bytes: &[u8]) -> Result<Vec<[u8; 4]>, HarmonyError> {
+        if !bytes.len().is_multiple_of(4) {
+            return Err(HarmonyError::Malformed(format!(
+                "{} buffer of {} bytes is not a whole number of 4-byte values",
+                what,
+                bytes.len()
+            )));
+        }
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|word| [word[0], word[1], word[2], word[3]])
+            .collect())
+    }
+
+    fn weights(bytes: &[u8]) -> Result<Vec<f32>, HarmonyError> {
+        Ok(words("Weight", bytes)?
+            .into_iter()
+            .map(f32::from_le_bytes)
+            .collect())
+    }
+
+    impl Handler for WASMEdgeExecutor {
+        /// Methods: addEdge, addNamedEdge, addNode, addNodes, hasNode,
+        /// listNodes, isolatedNodes, nodeCount, registerEdgeType,
+        /// edgeTypeCode, getEdgeTypes, reserve, edgeCount, loadEdges and
+        /// restore (payload "edges"), exportEdges (returns "edges"),
+        /// updateWeights (payloads "indices", u32, and "weights", f32),
+        /// setWeights (payload "weights"), exportWeights (returns
+        /// "weights"), getWeight, traverseBFS, traverseBFSTypes,
+        /// traverseBFSBinary and neighborhood (returns "result"); the mask
+        /// traversals take an optional direction ("outgoing", "incoming" or
+        /// "both"); generateGraph (spec) with the `testing` feature
+        fn handle(&mut self, mut call: Call) -> Result<Outcome, HarmonyError> {
+            match call.method.as_str() {
+                "addEdge" => Outcome::value(self.add_edge(
+                    call.arg("source")?,
+                    call.arg("target")?,
+                    call.arg("edgeType")?,
+                )),
+                "addNamedEdge" => {
+                    let edge_type: String = call.arg("edgeType")?;
+                    Outcome::value(self.add_named_edge(
+                        call.arg("source")?,
+                        call.arg("target")?,
+                        &edge_type,
+                    )?)
+                }
+                "addNode" => Outcome::value(self.add_node(call.arg("id")?)),
+                "addNodes" => Outcome::value(self.add_nodes(call.arg("ids")?)),
+                "hasNode" => Outcome::value(self.has_node(call.arg("id")?)),
+                "listNodes" => Outcome::value(self.list_nodes()),
+                "isolatedNodes" => Outcome::value(self.isolated_nodes()),
+                "nodeCount" => Outcome::value(self.node_count()),
+                "registerEdgeType" => {
+                    let definition: serde_json::Value = call.arg("definition")?;
+                    Outcome::value(self.register_edge_type(&definition.to_string())?)
+                }
+                "edgeTypeCode" => {
+                    let edge_type: String = call.arg("edgeType")?;
+                    Outcome::value(self.edge_type_code(&edge_type))
+                }
+                "getEdgeTypes" => Outcome::value(self.edge_types().definitions()),
+                "reserve" => {
+                    self.reserve(call.arg("nodes")?, call.arg("edges")?)?;
+                    Outcome::value(())
+                }
+                "edgeCount" => Outcome::value(self.edge_count()),
+                #[cfg(feature = "testing")]
+                "generateGraph" => {
+                    let spec: serde_json::Value = call.arg("spec")?;
+                    Outcome::value(self.generate_graph(&spec.to_string())?)
+                }
+                "loadEdges" => {
+                    let edges = call.payloads.take("edges")?;
+                    Outcome::value(self.load_edges(&edges)?)
+                }
+                "exportEdges" => Ok(Outcome::payload("edges", self.export_edges())),
+                "updateWeights" => {
+                    let indices: Vec<u32> = words("Index", &call.payloads.take("indices")?)?
+                        .into_iter()
+                        .map(u32::from_le_bytes)
+                        .collect();
+                    let weights = weights(&call.payloads.take("weights")?)?;
+                    self.update_weights(&indices, &weights)?;
+                    Outcome::value(())
+                }
+                "setWeights" => {
+                    self.set_weights(&weights(&call.payloads.take("weights")?)?)?;
+                    Outcome::value(())
+                }
+                "exportWeights" => Ok(Outcome::payload(
+                    "weights",
+                    self.weights()
+                        .iter()
+                        .flat_map(|weight| weight.to_le_bytes())
+                        .collect(),
+                )),
+                "getWeight" => Outcome::value(self.weight(call.arg("index")?)),
+                "restore" => {
+                    let edge_types: serde_json::Value = call.arg("edgeTypes")?;
+                    let edges = call.payloads.take("edges")?;
+                    Outcome::value(self.restore(&edge_types.to_string(), &edges)?)
+                }
+                "traverseBFS" => Outcome::value(self.traverse_bfs_directed(
+                    call.arg("start")?,
+                    call.arg("maxDepth")?,
+                    call.optional_arg("edgeTypeMask")?.unwrap_or(0),
+                    call.optional_arg("direction")?.unwrap_or_default(),
+                )),
+                "traverseBFSTypes" => {
+                    let edge_types: Vec<String> = call.arg("edgeTypes")?;
+                    Outcome::value(self.traverse_bfs_types(
+                        call.arg("start")?,
+                        call.arg("maxDepth")?,
+                        &edge_types,
+                    )?)
+                }
+                "traverseBFSBinary" => Ok(Outcome::payload(
+                    "result",
+                    self.traverse_bfs_directed_binary(
+                        call.arg("start")?,
+                        call.arg("maxDepth")?,
+                        call.optional_arg("edgeTypeMask")?.unwrap_or(0),
+                        call.optional_arg("direction")?.unwrap_or_default(),
+                    ),
+                )),
+                "neighborhood" => Ok(Outcome::payload(
+                    "result",
+                    self.neighborhood_binary(
+                        call.arg("nodeId")?,
+                        call.arg("depth")?,
+                        call.optional_arg("maxNodes")?.unwrap_or(0),
+                    ),
+                )),
+                _ => Err(call.unsupported()),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "spatial-index")]
+mod spatial {
+    use std::collections::HashMap;
+    #[cfg(feature = "edge-executor")]
+    use std::collections::HashSet;
+    #[cfg(feature = "edge-executor")]
+    use std::sync::{Arc, Mutex};
+
+    use harmony_error::HarmonyError;
+    use spatial_index::SpatialIndex;
+    #[cfg(feature = "edge-executor")]
+    use wasm_edge_executor::WASMEdgeExecutor;
+
+    use crate::dispatcher::Handler;
+    use crate::message::{Call, Outcome};
+
+    /// Runs a `SpatialIndex`, created by the "create" method
+    #[derive(Default)]
+    pub struct SpatialIndexHandler {
+        index: Option<SpatialIndex>,
+        #[cfg(feature = "edge-executor")]
+        executor: Option<Arc<Mutex<WASMEdgeExecutor>>>,
+    }
+
+    impl SpatialIndexHandler {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Handler whose graph-aware queries read `executor`, the worker's
+        /// edge executor
+        #[cfg(feature = "edge-executor")]
+        pub fn with_executor(executor: Arc<Mutex<WASMEdgeExecutor>>) -> Self {
+            Self {
+                index: None,
+                executor: Some(executor),
+            }
+        }
+
+        /// The k nodes nearest (x, y) that are within maxHops of node
+        /// `from` in the shared executor (`from` itself included), closest
+        /// first: spatial candidates are streamed nearest first and checked
+        /// against the set the traversal reached, through the worker's node
+        /// ID map
+        #[cfg(feature = "edge-executor")]
+        fn nearest_reachable(&mut self, call: &Call) -> Result<Outcome, HarmonyError> {
+            let executor = self.executor.as_ref().ok_or_else(|| {
+                HarmonyError::NotFound("No edge executor shared with the spatial index".to_string())
+            })?;
+            let reachable: HashSet<u32> = executor
+                .lock()
+                .map_err(|_| HarmonyError::Internal("Edge executor poisoned".to_string()))?
+                .traverse_bfs_directed(
+                    call.arg("from")?,
+                    call.arg("maxHops")?,
+                    call.optional_arg("edgeTypeMask")?.unwrap_or(0),
+                    call.optional_arg("direction")?.unwrap_or_default(),
+                )
+                .nodes
+                .into_iter()
+                .collect();
+            let (x, y, k) = (call.arg("x")?, call.arg("y")?, call.arg("k")?);
+            Outcome::value(self.index()?.nearest_where(x, y, k, |node| {
+                harmony_ids::id(&node.id).is_some_and(|id| reachable.contains(&id))
+            }))
+        }
+
+        fn index(&mut self) -> Result<&mut SpatialIndex, HarmonyError> {
+            self.index.as_mut().ok_or_else(|| {
+                HarmonyError::NotFound("No spatial index; call 'create' first".to_string())
+            })
+        }
+    }
+
+    impl Handler for SpatialIndexHandler {
+        /// Methods: create, insert, queryRange, queryRadius, queryNearest,
+        /// queryViewport, setLodThreshold, getPosition, size, clear,
+        /// reserve, capacity, tune;
+        /// queryNearestReachable (x, y, k, from, maxHops, edgeTypeMask?,
+        /// direction?) with the `edge-executor` feature; generateNodes
+        /// (spec) with the `testing` feature
+        fn handle(&mut self, call: Call) -> Result<Outcome, HarmonyError> {
+            #[cfg(feature = "edge-executor")]
+            if call.method == "queryNearestReachable" {
+                return self.nearest_reachable(&call);
+            }
+            if call.method == "create" {
+                self.index = Some(SpatialIndex::with_capacity(
+                    call.arg("minX")?,
+                    call.arg("minY")?,
+                    call.arg("maxX")?,
+                    call.arg("maxY")?,
+                    call.optional_arg("capacity")?.unwrap_or(4),
+                    call.optional_arg("expectedNodes")?.unwrap_or(0),
+                )?);
+                return Outcome::value(());
+            }
+            let index = self.index()?;
+            match call.method.as_str() {
+                "insert" => {
+                    let metadata: HashMap<String, String> =
+                        call.optional_arg("metadata")?.unwrap_or_default();
+                    Outcome::value(index.insert_node(
+                        call.arg("id")?,
+                        call.arg("x")?,
+                        call.arg("y")?,
+                        metadata,
+                    )?)
+                }
+                "queryRange" => Outcome::value(index.range(
+                    call.arg("minX")?,
+                    call.arg("minY")?,
+                    call.arg("maxX")?,
+                    call.arg("maxY")?,
+                )),
+                "queryRadius" => Outcome::value(index.radius(
+                    call.arg("centerX")?,
+                    call.arg("centerY")?,
+                    call.arg("radius")?,
+                )),
+                "queryNearest" => {
+                    Outcome::value(index.nearest(call.arg("x")?, call.arg("y")?, call.arg("k")?))
+                }
+                "queryViewport" => Outcome::value(index.viewport(
+                    call.arg("minX")?,
+                    call.arg("minY")?,
+                    call.arg("maxX")?,
+                    call.arg("maxY")?,
+                    call.arg("zoom")?,
+                )?),
+                "setLodThreshold" => {
+                    index.set_lod_threshold(call.arg("threshold")?);
+                    Outcome::value(())
+                }
+                "getPosition" => {
+                    let id: String = call.arg("id")?;
+                    Outcome::value(index.position(&id))
+                }
+                "size" => Outcome::value(index.size()),
+                "capacity" => Outcome::value(index.capacity()),
+                "tune" => Outcome::value(index.tune_capacity()),
+                "clear" => {
+                    index.clear();
+                    Outcome::value(())
+                }
+                "reserve" => {
+                    index.reserve(call.arg("additional")?)?;
+                    Outcome::value(())
+                }
+                #[cfg(feature = "testing")]
+                "generateNodes" => {
+                    let spec: serde_json::Value = call.arg("spec")?;
+                    Outcome::value(index.generate_nodes(&spec.to_string())?)
+                }
+                _ => Err(call.unsupported()),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use serde_json::{json, Value};
+
+        use super::*;
+
+        #[test]
+        fn test_spatial_index_handler() {
+            let mut handler = SpatialIndexHandler::new();
+            let error = handler.handle(Call::new("size", Value::Null)).unwrap_err();
+            assert_eq!(error.code(), "not_found");
+
+            let bounds = json!({ "minX": 0, "minY": 0, "maxX": 100, "maxY": 100 });
+            handler.handle(Call::new("create", bounds)).unwrap();
+            let node =
+                json!({ "id": "osc", "x": 10, "y": 20, "metadata": { "kind": "oscillator" } });
+            assert_eq!(
+                handler.handle(Call::new("insert", node)).unwrap().result,
+                json!(true)
+            );
+            let found = handler
+                .handle(Call::new(
+                    "queryRadius",
+                    json!({ "centerX": 10, "centerY": 20, "radius": 1 }),
+                ))
+                .unwrap();
+            assert_eq!(found.result[0]["metadata"]["kind"], "oscillator");
+            let position = handler
+                .handle(Call::new("getPosition", json!({ "id": "osc" })))
+                .unwrap();
+            assert_eq!(position.result, json!({ "x": 10.0, "y": 20.0 }));
+            let view = json!({ "minX": 0, "minY": 0, "maxX": 50, "maxY": 50, "zoom": 2 });
+            let visible = handler
+                .handle(Call::new("queryViewport", view.clone()))
+                .unwrap();
+            assert_eq!(visible.result["kind"], "nodes");
+            handler
+                .handle(Call::new("setLodThreshold", json!({ "threshold": 0 })))
+                .unwrap();
+            let clustered = handler.handle(Call::new("queryViewport", view)).unwrap();
+            assert_eq!(clustered.result["kind"], "clusters");
+            assert_eq!(clustered.result["clusters"][0]["id"], "osc");
+            let report = handler.handle(Call::new("tune", Value::Null)).unwrap();
+            assert_eq!(report.result["previous_capacity"], 4);
+        }
+
+        #[cfg(feature = "edge-executor")]
+        #[test]
+        fn test_query_nearest_reachable() {
+            let executor = Arc::new(Mutex::new(WASMEdgeExecutor::new()));
+            let mut handler = SpatialIndexHandler::with_executor(Arc::clone(&executor));
+            let bounds = json!({ "minX": 0, "minY": 0, "maxX": 100, "maxY": 100 });
+            handler.handle(Call::new("create", bounds)).unwrap();
+            // "d" is closest to the query point but not connected
+            for (id, x) in [("a", 50), ("b", 40), ("c", 30), ("d", 1)] {
+                let node = json!({ "id": id, "x": x, "y": 0 });
+                handler.handle(Call::new("insert", node)).unwrap();
+            }
+            let id = |name| harmony_ids::id(name).unwrap();
+            executor.lock().unwrap().add_edge(id("a"), id("b"), 0);
+            executor.lock().unwrap().add_edge(id("b"), id("c"), 0);
+
+            let mut query = |max_hops, k| {
+                let args = json!({ "x": 0, "y": 0, "k": k, "from": id("a"), "maxHops": max_hops });
+                let found = handler
+                    .handle(Call::new("queryNearestReachable", args))
+                    .unwrap();
+                found
+                    .result
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|node| node["id"].as_str().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(query(1, 5), ["b", "a"]);
+            assert_eq!(query(2, 2), ["c", "b"]);
+
+            let error = SpatialIndexHandler::new()
+                .handle(Call::new("queryNearestReachable", Value::Null))
+                .unwrap_err();
+            assert_eq!(error.code(), "not_found");
+        }
+
+        #[cfg(feature = "testing")]
+        #[test]
+        fn test_generate_nodes() {
+            let mut handler = SpatialIndexHandler::new();
+            let bounds = json!({ "minX": 0, "minY": 0, "maxX": 100, "maxY": 100 });
+            handler.handle(Call::new("create", bounds)).unwrap();
+            let spec = json!({ "spec": { "count": 20, "distribution": "clustered", "seed": 5 } });
+            let generated = handler.handle(Call::new("generateNodes", spec)).unwrap();
+            assert_eq!(generated.result, json!(20));
+            let size = handler.handle(Call::new("size", Value::Null)).unwrap();
+            assert_eq!(size.result, json!(20));
+        }
+    }
+}
+
+#[cfg(feature = "full-text-index")]
+mod full_text {
+    use full_text_index::IndexConfig;
+    use harmony_error::HarmonyError;
+
+    use crate::dispatcher::Handler;
+    use crate::message::{Call, Outcome};
+
+    /// Runs the full-text indices of the worker's module
+    #[derive(Default)]
+    pub struct FullTextIndexHandler;
+
+    impl Handler for FullTextIndexHandler {
+        /// Methods: createIndex, addDocument, reserve, exportIndices,
+        /// deleteIndex, removeDocument, search, explain, indexStats, clearIndex;
+        /// results are the fields of the JSON API's responses, failures are
+        /// errors. With the `testing` feature also generateDocuments (spec)
+        /// and addGeneratedDocuments (indexId, spec)
+        fn handle(&mut self, call: Call) -> Result<Outcome, HarmonyError> {
+            let result = match call.method.as_str() {
+                "createIndex" => {
+                    let config: IndexConfig = call.arg("config")?;
+                    full_text_index::try_create_index(config)
+                }
+                "addDocument" => {
+                    let index_id: String = call.arg("indexId")?;
+                    full_text_index::try_add_document(
+                        &index_id,
+                        call.arg("nodeId")?,
+                        call.arg("content")?,
+                    )
+                }
+                "reserve" => {
+                    let index_id: String = call.arg("indexId")?;
+                    full_text_index::try_reserve(&index_id, call.arg("documentCount")?)
+                }
+                "exportIndices" => full_text_index::try_export_indices(),
+                "deleteIndex" => {
+                    let index_id: String = call.arg("indexId")?;
+                    full_text_index::try_delete_index(&index_id)
+                }
+                "removeDocument" => {
+                    let index_id: String = call.arg("indexId")?;
+                    full_text_index::try_remove_document(&index_id, call.arg("nodeId")?)
+                }
+                "search" => {
+                    let index_id: String = call.arg("indexId")?;
+                    let query: String = call.arg("query")?;
+                    full_text_index::try_search(&index_id, &query)
+                }
+                "explain" => {
+                    let index_id: String = call.arg("indexId")?;
+                    let query: String = call.arg("query")?;
+                    full_text_index::try_explain(&index_id, &query)
+                }
+                "indexStats" => {
+                    let index_id: String = call.arg("indexId")?;
+                    full_text_index::try_index_stats(&index_id)
+                }
+                "clearIndex" => {
+                    let index_id: String = call.arg("indexId")?;
+                    full_text_index::try_clear_index(&index_id)
+                }
+                #[cfg(feature = "testing")]
+                "generateDocuments" => full_text_index::try_generate_documents(&call.arg("spec")?),
+                #[cfg(feature = "testing")]
+                "addGeneratedDocuments" => {
+                    let index_id: String = call.arg("indexId")?;
+                    full_text_index::try_add_generated_documents(&index_id, &call.arg("spec")?)
+                }
+                _ => Err(call.unsupported()),
+            };
+            Outcome::value(result?)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use serde_json::json;
+
+        use super::*;
+
+        #[test]
+        fn test_full_text_index_handler() {
+            let mut handler = FullTextIndexHandler;
+            let config = json!({ "config": { "index_id": "names", "property_name": "name" } });
+            handler.handle(Call::new("createIndex", config)).unwrap();
+            let document =
+                json!({ "indexId": "names", "nodeId": "1", "content": "Low pass filter" });
+            handler.handle(Call::new("addDocument", document)).unwrap();
+            let found = handler
+                .handle(Call::new(
+                    "search",
+                    json!({ "indexId": "names", "query": "filter" }),
+                ))
+                .unwrap();
+            assert_eq!(found.result["results"][0]["node_id"], "1");
+            let explained = handler
+                .handle(Call::new(
+                    "explain",
+                    json!({ "indexId": "names", "query": "filter" }),
+                ))
+                .unwrap();
+            assert_eq!(explained.result["terms"][0]["document_frequency"], 1);
+            let stats = handler
+                .handle(Call::new("indexStats", json!({ "indexId": "names" })))
+                .unwrap();
+            assert_eq!(stats.result["postings"], 3);
+            let error = handler
+                .handle(Call::new("clearIndex", json!({ "indexId": "none" })))
+                .unwrap_err();
+            assert_eq!(error.code(), "not_found");
+        }
+    }
+}
+
+#[cfg(feature = "audio-graph")]
+mod audio_graph {
+    use harmony_error::HarmonyError;
+    use serde_json::json;
+    use wasm_edge_executor::WASMEdgeExecutor;
+    use wasm_node_registry::audio_graph::AudioGraph;
+    use wasm_node_registry::processors::ProcessorFactory;
+
+    use crate::dispatcher::Handler;
+    use crate::message::{Call, Outcome};
+
+    /// Runs an `AudioGraph`, built by the "build" method
+    pub struct AudioGraphHandler {
+        factory: ProcessorFactory,
+        graph: Option<AudioGraph>,
+    }
+
+    impl Default for AudioGraphHandler {
+        fn default() -> Self {
+            Self::new(ProcessorFactory::new())
+        }
+    }
+
+    impl AudioGraphHandler {
+        /// Handler building graphs with the processors of `factory`
+        pub fn new(factory: ProcessorFactory) -> Self {
+            Self {
+                factory,
+                graph: None,
+            }
+        }
+
+        fn graph(&mut self) -> Result<&mut AudioGraph, HarmonyError> {
+            self.graph.as_mut().ok_or_else(|| {
+                HarmonyError::NotFound("No audio graph; call 'build' first".to_string())
+            })
+        }
+    }
+
+    /// Decodes f32 little-endian samples
+    fn samples(bytes: &[u8]) -> Result<Vec<f32>, HarmonyError> {
+        if !bytes.len().is_multiple_of(4) {
+            return Err(HarmonyError::Malformed(format!(
+                "Sample buffer of {} bytes is not a whole number of f32 samples",
+                bytes.len()
+            )));
+        }
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|sample| f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]))
+            .collect())
+    }
+
+    fn sample_bytes(samples: &[f32]) -> Vec<u8> {
+        samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect()
+    }
+
+    impl Handler for AudioGraphHandler {
+        /// Methods: build (payload "edges"), process (payload "input",
+        /// returns "output"), setParameter, setBypass, setMix, sendMidi,
+        /// setRampTime, reset, latency, serializeState (returns "state"),
+        /// restoreState (payload "state")
+        fn handle(&mut self, mut call: Call) -> Result<Outcome, HarmonyError> {
+            if call.method == "build" {
+                let nodes: Vec<(u32, String)> = call.arg("nodes")?;
+                let nodes: Vec<(u32, &str)> = nodes
+                    .iter()
+                    .map(|(id, type_id)| (*id, type_id.as_str()))
+                    .collect();
+                let mut edges = WASMEdgeExecutor::new();
+                edges.load_edges(&call.payloads.take("edges")?)?;
+                let graph = AudioGraph::build_with_channels(
+                    &self.factory,
+                    &nodes,
+                    &edges,
+                    call.optional_arg("edgeTypeMask")?.unwrap_or(0),
+                    call.arg("sampleRate")?,
+                    call.arg("blockSize")?,
+                    call.optional_arg("inputChannels")?.unwrap_or(1),
+                )?;
+                let summary = json!({
+                    "order": graph.order(),
+                    "inputChannels": graph.input_channels(),
+                    "outputChannels": graph.output_channels(),
+                    "latency": graph.latency_samples(),
+                });
+                self.graph = Some(graph);
+                return Outcome::value(summary);
+            }
+            let graph = self.graph()?;
+            match call.method.as_str() {
+                "process" => {
+                    let input = samples(&call.payloads.take("input")?)?;
+                    if !input.len().is_multiple_of(graph.input_channels()) {
+                        return Err(HarmonyError::InvalidInput(format!(
+                            "{} samples do not make whole frames of {} channels",
+                            input.len(),
+                            graph.input_channels()
+                        )));
+                    }
+                    let frames = input.len() / graph.input_channels();
+                    let mut output = vec![0.0; frames * graph.output_channels()];
+                    graph.process_interleaved(&input, &mut output);
+                    Ok(Outcome::payload("output", sample_bytes(&output)))
+                }
+                "setParameter" => {
+                    let name: String = call.arg("name")?;
+                    graph.set_parameter(call.arg("nodeId")?, &name, call.arg("value")?)?;
+                    Outcome::value(())
+                }
+                "setBypass" => {
+                    graph.set_bypass(call.arg("nodeId")?, call.arg("bypassed")?)?;
+                    Outcome::value(())
+                }
+                "setMix" => {
+                    graph.set_mix(call.arg("wet")?);
+                    Outcome::value(())
+                }
+                "sendMidi" => {
+                    graph.send_midi(call.arg("nodeId")?, call.arg("message")?)?;
+                    Outcome::value(())
+                }
+                "setRampTime" => {
+                    graph.set_ramp_time(call.arg("seconds")?);
+                    Outcome::value(())
+                }
+                "reset" => {
+                    graph.reset();
+                    Outcome::value(())
+                }
+                "latency" => Outcome::value(graph.latency_samples()),
+                "serializeState" => Ok(Outcome::payload("state", graph.serialize_state())),
+                "restoreState" => {
+                    graph.restore_state(&call.payloads.take("state")?)?;
+                    Outcome::value(())
+                }
+                _ => Err(call.unsupported()),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use serde_json::{json, Value};
+
+        use super::*;
+
+        #[test]
+        fn test_audio_graph_handler() {
+            let mut handler = AudioGraphHandler::default();
+            let mut build = Call::new(
+                "build",
+                json!({ "nodes": [[1, "audio.gain"]], "sampleRate": 1000.0, "blockSize": 4 }),
+            );
+            build.payloads.push("edges", Vec::new());
+            let summary = handler.handle(build).unwrap();
+            assert_eq!(summary.result["order"], json!([1]));
+            assert_eq!(summary.result["outputChannels"], 1);
+
+            let input: Vec<u8> = [0.5f32; 6]
+                .iter()
+                .flat_map(|sample| sample.to_le_bytes())
+                .collect();
+            let mut process = Call::new("process", Value::Null);
+            process.payloads.push("input", input.clone());
+            let mut outcome = handler.handle(process).unwrap();
+            assert_eq!(outcome.payloads.take("output").unwrap(), input);
+
+            let mut process = Call::new("process", Value::Null);
+            process.payloads.push("input", vec![0; 5]);
+            assert_eq!(handler.handle(process).unwrap_err().code(), "malformed");
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Harmony Worker Protocol
+//!
+//! Messages and dispatch for running the edge executor, the spatial and
+//! full-text indices and the audio graph inside dedicated Web Workers.
+//! All contexts of a worker share its harmony-ids node ID map, reached
+//! through the `node_ids` target, and the spatial index handler reads the
+//! worker's edge executor for `queryNearestReachable`.
+//! Each `postMessage` carries a JSON-compatible `Request` or `Response`
+//! plus a transfer list of buffers:
+//! - `seq`: chosen by the sender of a request and echoed in its response,
+//!   so replies can arrive in any order
+//! - `payloads`: one `PayloadDescriptor` (name, byte length) per buffer in
+//!   the transfer list, in order; edge buffers, traversal results, audio
+//!   blocks and states move between threads without copying or JSON
+//! - a reply is `{"status": "ok", result, payloads}` or
+//!   `{"status": "error", error}`, the error being a HarmonyError's
+//!   `to_json`
+//!
+//! In the worker, a `Dispatcher` hands each request to the `Handler` of its
+//! target (see `handlers`) and never fails itself. Handlers are behind one
+//! feature per context, so a worker module links only what it runs. With
+//! the `wasm` feature, `WorkerDispatcher` exposes the dispatcher to JS;
+//! harmony-worker-protocol.js holds the main-thread client and the worker
+//! loop.
+//!
+//! See: harmony-design/DESIGN_SYSTEM.md#worker-protocol
+
+pub mod dispatcher;
+pub mod handlers;
+pub mod message;
+
+#[cfg(feature = "wasm")]
+pub use dispatcher::WorkerDispatcher;
+pub use dispatcher::{Dispatcher, Handler};
+pub use message::{
+    Call, Outcome, PayloadDescriptor, Payloads, Reply, Request, Response, Target, PROTOCOL_VERSION,
+};
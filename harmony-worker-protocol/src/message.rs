@@ -0,0 +1,244 @@
+//! Request and response messages, and the binary payloads sent with them
+
+use harmony_error::HarmonyError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Protocol version; workers reject requests from a newer version
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Context a request is addressed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    EdgeExecutor,
+    SpatialIndex,
+    FullTextIndex,
+    AudioGraph,
+    NodeIds,
+}
+
+impl Target {
+    /// Name used in messages and error context
+    pub fn name(self) -> &'static str {
+        match self {
+            Target::EdgeExecutor => "edge_executor",
+            Target::SpatialIndex => "spatial_index",
+            Target::FullTextIndex => "full_text_index",
+            Target::AudioGraph => "audio_graph",
+            Target::NodeIds => "node_ids",
+        }
+    }
+}
+
+/// Describes one buffer in a message's transfer list
+///
+/// Buffers travel next to the JSON part of a message, in the order of the
+/// descriptors, and are transferred rather than copied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadDescriptor {
+    /// Name the handler looks the buffer up by, e.g. "edges"
+    pub name: String,
+    pub byte_length: usize,
+}
+
+/// Call from the main thread to a worker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    /// Protocol version of the sender
+    #[serde(default = "protocol_version")]
+    pub version: u32,
+    /// Chosen by the sender; echoed in the response
+    pub seq: u32,
+    pub target: Target,
+    /// Method name, as in the context's JS API (e.g. "traverseBFS")
+    pub method: String,
+    /// Named arguments
+    #[serde(default)]
+    pub args: Value,
+    #[serde(default)]
+    pub payloads: Vec<PayloadDescriptor>,
+}
+
+fn protocol_version() -> u32 {
+    PROTOCOL_VERSION
+}
+
+/// Outcome of a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Reply {
+    Ok {
+        result: Value,
+        #[serde(default)]
+        payloads: Vec<PayloadDescriptor>,
+    },
+    /// HarmonyError as `to_json`: `{code, message, context}`
+    Error { error: Value },
+}
+
+/// Answer to the request with the same `seq`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub seq: u32,
+    #[serde(flatten)]
+    pub reply: Reply,
+}
+
+/// Binary payloads of one message, by name
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Payloads {
+    buffers: Vec<(String, Vec<u8>)>,
+}
+
+impl Payloads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pairs received buffers with their descriptors
+    ///
+    /// # Returns
+    /// `malformed` if the counts or any buffer length differ
+    pub fn from_parts(
+        descriptors: &[PayloadDescriptor],
+        buffers: Vec<Vec<u8>>,
+    ) -> Result<Self, HarmonyError> {
+        if descriptors.len() != buffers.len() {
+            return Err(HarmonyError::Malformed(format!(
+                "{} payloads described but {} buffers sent",
+                descriptors.len(),
+                buffers.len()
+            )));
+        }
+        let buffers = descriptors
+            .iter()
+            .zip(buffers)
+            .map(|(descriptor, buffer)| {
+                if buffer.len() != descriptor.byte_length {
+                    return Err(HarmonyError::Malformed(format!(
+                        "Payload '{}' is {} bytes, described as {}",
+                        descriptor.name,
+                        buffer.len(),
+                        descriptor.byte_length
+                    )));
+                }
+                Ok((descriptor.name.clone(), buffer))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { buffers })
+    }
+
+    /// Adds a buffer; names need not be unique, `take` finds the first
+    pub fn push(&mut self, name: impl Into<String>, buffer: Vec<u8>) {
+        self.buffers.push((name.into(), buffer));
+    }
+
+    /// Removes and returns the buffer named `name`
+    ///
+    /// # Returns
+    /// `invalid_input` if the message has no such payload
+    pub fn take(&mut self, name: &str) -> Result<Vec<u8>, HarmonyError> {
+        let position = self
+            .buffers
+            .iter()
+            .position(|(buffer_name, _)| buffer_name == name)
+            .ok_or_else(|| HarmonyError::InvalidInput(format!("Missing payload '{}'", name)))?;
+        Ok(self.buffers.remove(position).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// Descriptors and buffers, in transfer-list order
+    pub fn into_parts(self) -> (Vec<PayloadDescriptor>, Vec<Vec<u8>>) {
+        self.buffers
+            .into_iter()
+            .map(|(name, buffer)| {
+                let descriptor = PayloadDescriptor {
+                    name,
+                    byte_length: buffer.len(),
+                };
+                (descriptor, buffer)
+            })
+            .unzip()
+    }
+}
+
+/// A request as a handler sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub method: String,
+    pub args: Value,
+    pub payloads: Payloads,
+}
+
+impl Call {
+    /// Call without payloads
+    pub fn new(method: impl Into<String>, args: Value) -> Self {
+        Self {
+            method: method.into(),
+            args,
+            payloads: Payloads::new(),
+        }
+    }
+
+    /// Required argument `name`
+    ///
+    /// # Returns
+    /// `invalid_input` if missing, `malformed` if of the wrong type
+    pub fn arg<T: DeserializeOwned>(&self, name: &str) -> Result<T, HarmonyError> {
+        self.optional_arg(name)?
+            .ok_or_else(|| HarmonyError::InvalidInput(format!("Missing argument '{}'", name)))
+    }
+
+    /// `unsupported` error for a method the handler does not know
+    pub fn unsupported(&self) -> HarmonyError {
+        HarmonyError::Unsupported(format!("Unknown method '{}'", self.method))
+    }
+
+    /// Argument `name`, or None if missing or null
+    pub fn optional_arg<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, HarmonyError> {
+        match self.args.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => T::deserialize(value).map(Some).map_err(|error| {
+                HarmonyError::Malformed(format!("Argument '{}': {}", name, error))
+            }),
+        }
+    }
+}
+
+/// What a handler returns: a JSON result and any binary payloads
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub result: Value,
+    pub payloads: Payloads,
+}
+
+impl Outcome {
+    /// Outcome with only a JSON result
+    pub fn value(result: impl Serialize) -> Result<Self, HarmonyError> {
+        Ok(Self {
+            result: serde_json::to_value(result)?,
+            payloads: Payloads::new(),
+        })
+    }
+
+    /// Outcome with a null result and one payload
+    pub fn payload(name: impl Into<String>, buffer: Vec<u8>) -> Self {
+        let mut payloads = Payloads::new();
+        payloads.push(name, buffer);
+        Self {
+            result: Value::Null,
+            payloads,
+        }
+    }
+}
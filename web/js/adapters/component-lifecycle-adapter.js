@@ -41,6 +41,12 @@ export class ComponentLifecycleAdapter {
       this.handleTransition.bind(this)
     );
 
+    // Command: Deprecate component
+    this.eventBus.subscribe(
+      ComponentLifecycleEvents.DEPRECATE,
+      this.handleDeprecate.bind(this)
+    );
+
     // Query: Get component state
     this.eventBus.subscribe(
       ComponentLifecycleEvents.GET_STATE,
@@ -93,7 +99,7 @@ export class ComponentLifecycleAdapter {
    */
   handleTransition(event) {
     try {
-      const { componentId, fromState, toState, reason } = event.payload;
+      const { componentId, fromState, toState, reason, actor } = event.payload;
 
       if (!componentId || !fromState || !toState) {
         console.error('[ComponentLifecycleAdapter] Missing required fields in transition command', event);
@@ -105,6 +111,7 @@ export class ComponentLifecycleAdapter {
         from_state: fromState,
         to_state: toState,
         reason: reason || null,
+        actor: actor || null,
       };
 
       const resultJson = this.bc.transitionComponent(JSON.stringify(transition));
@@ -136,6 +143,54 @@ export class ComponentLifecycleAdapter {
     }
   }
 
+  /**
+   * Handle deprecate component command
+   * @param {Object} event - Event object
+   * @private
+   */
+  handleDeprecate(event) {
+    try {
+      const { componentId, reason, actor } = event.payload;
+
+      if (!componentId) {
+        console.error('[ComponentLifecycleAdapter] Missing componentId in deprecate command', event);
+        return;
+      }
+
+      const fromState = JSON.parse(this.bc.getComponentState(componentId));
+      const resultJson = this.bc.deprecateComponent(
+        componentId,
+        reason || '',
+        actor ? JSON.stringify(actor) : undefined
+      );
+      const result = JSON.parse(resultJson);
+
+      if (result.success) {
+        this.eventBus.publish({
+          type: ComponentLifecycleEvents.STATE_CHANGED,
+          payload: {
+            componentId: result.component_id,
+            fromState,
+            toState: result.new_state,
+            reason,
+          },
+        });
+      } else {
+        this.eventBus.publish({
+          type: ComponentLifecycleEvents.TRANSITION_FAILED,
+          payload: {
+            componentId,
+            fromState,
+            toState: 'deprecated',
+            error: result.error,
+          },
+        });
+      }
+    } catch (error) {
+      console.error('[ComponentLifecycleAdapter] Error deprecating component:', error, event);
+    }
+  }
+
   /**
    * Handle get state query
    * @param {Object} event - Event object
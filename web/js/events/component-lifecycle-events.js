@@ -24,6 +24,16 @@
  * @property {string} payload.fromState - Expected current state
  * @property {string} payload.toState - Target state
  * @property {string} [payload.reason] - Optional reason for transition
+ * @property {{id: string, roles: string[]}} [payload.actor] - Caller, checked against transition roles
+ */
+
+/**
+ * Command: Deprecate a component from its current state (terminal)
+ * @typedef {Object} DeprecateComponentCommand
+ * @property {string} type - 'component_lifecycle.deprecate'
+ * @property {Object} payload
+ * @property {string} payload.componentId - Component identifier
+ * @property {string} [payload.reason] - Optional reason, e.g. the replacement
  */
 
 /**
@@ -68,6 +78,7 @@ export const ComponentLifecycleEvents = {
   // Commands
   INITIALIZE: 'component_lifecycle.initialize',
   TRANSITION: 'component_lifecycle.transition',
+  DEPRECATE: 'component_lifecycle.deprecate',
   
   // Queries
   GET_STATE: 'component_lifecycle.get_state',
@@ -87,4 +98,5 @@ export const ComponentStates = {
   IN_DEVELOPMENT: 'in_development',
   IMPLEMENTED: 'implemented',
   PUBLISHED: 'published',
+  DEPRECATED: 'deprecated',
 };
\ No newline at end of file